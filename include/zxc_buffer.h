@@ -92,6 +92,44 @@ ZXC_EXPORT int zxc_default_level(void);
  */
 ZXC_EXPORT const char* zxc_version_string(void);
 
+/**
+ * @brief Code path picked by the runtime SIMD dispatcher.
+ */
+typedef enum {
+    ZXC_SIMD_AUTO = -1,   /**< Let the dispatcher pick (argument of zxc_simd_force()). */
+    ZXC_SIMD_DEFAULT = 0, /**< Baseline variant (scalar; SSE2 on x86-64, NEON on AArch64). */
+    ZXC_SIMD_NEON = 1,    /**< 32-bit ARM NEON variant. */
+    ZXC_SIMD_AVX2 = 2,    /**< x86-64 AVX2 variant. */
+    ZXC_SIMD_AVX512 = 3   /**< x86-64 AVX-512 (F+BW+VBMI2) variant. */
+} zxc_simd_variant_t;
+
+/**
+ * @brief Returns the variant the dispatcher runs on this CPU.
+ *
+ * Reflects both the CPU and the variants compiled into the library (see
+ * @c ZXC_ONLY_DEFAULT and @c ZXC_DISABLE_AVX512), or the variant pinned
+ * with zxc_simd_force(). The decision is the same for every call and every
+ * context.
+ *
+ * @return A @ref zxc_simd_variant_t value.
+ */
+ZXC_EXPORT int zxc_simd_variant(void);
+
+/**
+ * @brief Pins the dispatcher to a variant, for benchmarks and debugging.
+ *
+ * The baseline is always accepted; a SIMD variant only when this CPU and
+ * build would run it or a superset of it (AVX-512 CPUs accept AVX2).
+ * @ref ZXC_SIMD_AUTO restores detection. The setting is process-wide:
+ * calls started before it finish on the previous variant, so pin it before
+ * starting work rather than while other threads compress.
+ *
+ * @param[in] variant A @ref zxc_simd_variant_t value.
+ * @return The variant in effect afterwards: @p variant when it was
+ *         accepted, the previous one otherwise.
+ */
+ZXC_EXPORT int zxc_simd_force(int variant);
+
 /** @} */ /* end of library_info */
 
 /**
@@ -94,6 +94,20 @@
 #define ZXC_MAX_THREADS 512
 /** @} */ /* end of threading */
 
+/**
+ * @defgroup input_size Input Size Limit
+ * @brief Largest input @ref zxc_compress_bound accepts.
+ *
+ * A guard of the bound's arithmetic rather than a limit of the format: the
+ * top 1/256th of @c SIZE_MAX is kept for the per-block overhead.
+ * @{
+ */
+/** @brief log2 of the share of @c SIZE_MAX kept free of input. */
+#define ZXC_INPUT_SIZE_HEADROOM_LOG2 8
+/** @brief Largest input size accepted; @ref zxc_compress_bound returns 0 above it. */
+#define ZXC_INPUT_SIZE_MAX (SIZE_MAX - (SIZE_MAX >> ZXC_INPUT_SIZE_HEADROOM_LOG2))
+/** @} */ /* end of input_size */
+
 /**
  * @defgroup file_format File Format Constants
  * @brief On-disk byte sizes of the archive header and footer.
@@ -12,7 +12,8 @@
  * Defines @ref zxc_compress_opts_t, @ref zxc_decompress_opts_t and
  * @ref zxc_progress_callback_t. These types are consumed by every public
  * ZXC API (one-shot buffer, multi-threaded @c FILE* streaming, push
- * streaming, seekable).
+ * streaming, seekable). @ref zxc_stream_ext_t and the types it refers to
+ * extend the multi-threaded @c FILE* streaming API only.
  *
  * This header is never used in isolation: include the API header you
  * actually use (@c zxc_buffer.h, @c zxc_stream.h, @c zxc_pstream.h, ...)
@@ -46,6 +47,46 @@ extern "C" {
 typedef void (*zxc_progress_callback_t)(uint64_t bytes_processed, uint64_t bytes_total,
                                         const void* user_data);
 
+/**
+ * @brief Statistics of one block, passed to @ref zxc_block_callback_t.
+ */
+typedef struct {
+    uint64_t index;    /**< Block number in file order, from 0. */
+    uint64_t in_size;  /**< Bytes the block consumed: plain data when compressing, the
+                            encoded block (header and checksum included) when decompressing. */
+    uint64_t out_size; /**< Bytes the block produced. */
+    uint64_t time_ns;  /**< Time the worker spent on the block, in nanoseconds. */
+    int worker_id;     /**< Worker thread that processed it, from 0. */
+} zxc_block_stats_t;
+
+/**
+ * @brief Per-block statistics callback function type.
+ * Invoked by the multi-threaded @c FILE* streaming API once per block, from the
+ * writer thread and in file order, after the block is written.
+ * @param[in] stats     Statistics of the block; only valid during the call.
+ * @param[in] user_data User-provided context pointer (the options' @c user_data).
+ * @note Like @ref zxc_progress_callback_t, it should be fast and non-blocking.
+ */
+typedef void (*zxc_block_callback_t)(const zxc_block_stats_t* stats, const void* user_data);
+
+/**
+ * @brief Stage timings of one multi-threaded @c FILE* streaming call, filled in
+ * through the @c stats pointer of @ref zxc_stream_ext_t.
+ *
+ * A run is I/O bound when @c read_ns or @c write_ns is close to @c wall_ns, and
+ * CPU bound when @c compute_ns is close to @c wall_ns times @c n_workers.
+ */
+typedef struct {
+    uint64_t wall_ns;    /**< Duration of the whole call, in nanoseconds. */
+    uint64_t read_ns;    /**< Time the calling thread spent reading blocks from the input. */
+    uint64_t compute_ns; /**< Time the workers spent on blocks, summed over the workers. */
+    uint64_t write_ns;   /**< Time the writer thread spent writing blocks to the output. */
+    int n_workers;       /**< Worker threads that ran. */
+    uint64_t* worker_ns; /**< Optional caller array receiving each worker's busy time,
+                              indexed like @c zxc_block_stats_t::worker_id (NULL = skip). */
+    int worker_ns_len;   /**< Length of @c worker_ns; further workers are not reported. */
+} zxc_stream_stats_t;
+
 /**
  * @brief Options for streaming compression.
  *
@@ -72,7 +113,7 @@ typedef struct {
                                zxc_dict_huf() (NULL = none; ignored without dict).
                                Becomes part of the archive's dict_id binding. */
     zxc_progress_callback_t progress_cb; /**< Optional progress callback (NULL to disable). */
-    void* user_data;                     /**< User context pointer passed to progress_cb. */
+    void* user_data;                     /**< User context pointer passed to the callbacks. */
 } zxc_compress_opts_t;
 
 /**
@@ -94,9 +135,28 @@ typedef struct {
                                code-lengths header matching the one used at
                                compression time (NULL = none; ignored without dict). */
     zxc_progress_callback_t progress_cb; /**< Optional progress callback (NULL to disable). */
-    void* user_data;                     /**< User context pointer passed to progress_cb. */
+    void* user_data;                     /**< User context pointer passed to the callbacks. */
 } zxc_decompress_opts_t;
 
+/**
+ * @brief Extensions of the multi-threaded @c FILE* streaming API, passed to
+ * @ref zxc_stream_compress_ex and @ref zxc_stream_decompress_ex.
+ *
+ * Kept apart from the options structs so that their layout does not change.
+ * Zero-initialise to disable everything; the callbacks receive the options'
+ * @c user_data.
+ *
+ * @code
+ * zxc_stream_ext_t ext = { .block_cb = on_block };
+ * zxc_stream_compress_ex(f_in, f_out, &opts, &ext);
+ * @endcode
+ */
+typedef struct {
+    zxc_block_callback_t block_cb; /**< Optional per-block statistics callback (NULL to disable). */
+    zxc_stream_stats_t* stats;     /**< Optional stage timings, filled in on success (NULL to
+                                        disable). */
+} zxc_stream_ext_t;
+
 /**
  * @brief Returns `sizeof(zxc_compress_opts_t)` as compiled into the library.
  *
@@ -23,8 +23,8 @@
  *      - Reader thread: reads chunks from @c f_in.
  *      - Worker threads: compress/decompress chunks in parallel.
  *      - Writer thread: orders the results and writes them to @c f_out.
- *    Functions: @ref zxc_stream_compress, @ref zxc_stream_decompress,
- *    @ref zxc_stream_get_decompressed_size.
+ *    Functions: @ref zxc_stream_compress, @ref zxc_stream_decompress, their
+ *    @c _ex variants, @ref zxc_stream_get_decompressed_size.
  *
  * 2. **Seekable @c FILE* open helper**: thin wrapper that adapts a
  *    @c FILE* into a thread-safe @c pread / @c ReadFile-backed
@@ -88,6 +88,33 @@ ZXC_EXPORT int64_t zxc_stream_compress(FILE* f_in, FILE* f_out, const zxc_compre
 ZXC_EXPORT int64_t zxc_stream_decompress(FILE* f_in, FILE* f_out,
                                          const zxc_decompress_opts_t* opts);
 
+/**
+ * @brief Like @ref zxc_stream_compress, with the extensions in @p ext.
+ *
+ * @param[in] f_in   Input file stream (must be opened in "rb" mode).
+ * @param[out] f_out  Output file stream (must be opened in "wb" mode).
+ * @param[in] opts   Compression options (NULL uses all defaults).
+ * @param[in] ext    Extensions such as the block callback (NULL disables them).
+ *
+ * @return Total compressed bytes written, or a negative zxc_error_t code.
+ */
+ZXC_EXPORT int64_t zxc_stream_compress_ex(FILE* f_in, FILE* f_out, const zxc_compress_opts_t* opts,
+                                          const zxc_stream_ext_t* ext);
+
+/**
+ * @brief Like @ref zxc_stream_decompress, with the extensions in @p ext.
+ *
+ * @param[in] f_in   Input file stream (must be opened in "rb" mode).
+ * @param[out] f_out  Output file stream (must be opened in "wb" mode).
+ * @param[in] opts   Decompression options (NULL uses all defaults).
+ * @param[in] ext    Extensions such as the block callback (NULL disables them).
+ *
+ * @return Total decompressed bytes written, or a negative zxc_error_t code.
+ */
+ZXC_EXPORT int64_t zxc_stream_decompress_ex(FILE* f_in, FILE* f_out,
+                                            const zxc_decompress_opts_t* opts,
+                                            const zxc_stream_ext_t* ext);
+
 /**
  * @brief Returns the decompressed size stored in a ZXC compressed file.
  *
@@ -852,7 +852,7 @@ int zxc_read_ghi_header_and_desc(const uint8_t* RESTRICT src, const size_t len,
 uint64_t zxc_compress_bound(const size_t input_size) {
     // Guard against uint64 overflow when summing per-block overhead
     // across very large inputs (input_size approaching SIZE_MAX).
-    if (UNLIKELY(input_size > (SIZE_MAX - (SIZE_MAX >> 8)))) return 0;
+    if (UNLIKELY(input_size > ZXC_INPUT_SIZE_MAX)) return 0;
     uint64_t n = ((uint64_t)input_size + ZXC_BLOCK_SIZE_MIN - 1) / ZXC_BLOCK_SIZE_MIN;
     if (n == 0) n = 1;
     return ZXC_FILE_HEADER_SIZE +
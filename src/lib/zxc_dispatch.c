@@ -30,6 +30,11 @@
 #define ZXC_ONLY_DEFAULT
 #endif
 
+/*
+ * ZXC_DISABLE_AVX512 => the _avx512 variant is not linked in; AVX-512 capable
+ * CPUs are served by the _avx2 variant instead.
+ */
+
 #if defined(_MSC_VER)
 #include <intrin.h>
 #if defined(_M_X64)
@@ -259,6 +264,10 @@ static zxc_cpu_feature_t zxc_detect_cpu_features(void) {
 #endif
 #endif
 
+#if defined(ZXC_DISABLE_AVX512)
+    if (features == ZXC_CPU_AVX512) features = ZXC_CPU_AVX2;
+#endif
+
     return features;
 #endif
 }
@@ -286,12 +295,41 @@ static ZXC_ATOMIC zxc_decompress_func_t zxc_decompress_dict_ptr = (zxc_decompres
 static ZXC_ATOMIC zxc_decompress_func_t zxc_decompress_safe_ptr = (zxc_decompress_func_t)0;
 /** @brief Lazily-resolved pointer to the best compression variant. */
 static ZXC_ATOMIC zxc_compress_func_t zxc_compress_ptr = (zxc_compress_func_t)0;
+/** @brief Variant pinned by @ref zxc_simd_force, or @ref ZXC_SIMD_AUTO. */
+static ZXC_ATOMIC int zxc_forced_variant = ZXC_SIMD_AUTO;
+
+/**
+ * @brief Feature level the dispatchers select from.
+ *
+ * The detected level, unless @ref zxc_simd_force pinned a variant.
+ *
+ * @return The @ref zxc_cpu_feature_t level to dispatch on.
+ */
+static zxc_cpu_feature_t zxc_dispatch_cpu(void) {
+#if ZXC_USE_C11_ATOMICS
+    const int forced = atomic_load_explicit(&zxc_forced_variant, memory_order_acquire);
+#else
+    const int forced = zxc_forced_variant;
+#endif
+    switch (forced) {
+        case ZXC_SIMD_DEFAULT:
+            return ZXC_CPU_GENERIC;
+        case ZXC_SIMD_NEON:
+            return ZXC_CPU_NEON;
+        case ZXC_SIMD_AVX2:
+            return ZXC_CPU_AVX2;
+        case ZXC_SIMD_AVX512:
+            return ZXC_CPU_AVX512;
+        default:
+            return zxc_detect_cpu_features();
+    }
+}
 
 /**
  * @brief First-call initialiser for the decompression dispatcher.
  *
- * Detects CPU features, selects the best implementation, stores the
- * pointer atomically, then tail-calls into it.
+ * Detects CPU features (or takes the pinned variant), selects the best
+ * implementation, stores the pointer atomically, then tail-calls into it.
  *
  * @param[in]  ctx      Decompression context (its @c dict_size picks the dict variant).
  * @param[in]  src      Compressed input chunk.
@@ -305,16 +343,19 @@ static ZXC_ATOMIC zxc_compress_func_t zxc_compress_ptr = (zxc_compress_func_t)0;
 static int zxc_decompress_dispatch_init(const zxc_cctx_t* RESTRICT ctx, const uint8_t* RESTRICT src,
                                         const size_t src_sz, uint8_t* RESTRICT dst,
                                         const size_t dst_cap) {
-    const zxc_cpu_feature_t cpu = zxc_detect_cpu_features();
+    const zxc_cpu_feature_t cpu = zxc_dispatch_cpu();
     zxc_decompress_func_t zxc_decompress_ptr_local = NULL;
     zxc_decompress_func_t zxc_decompress_dict_ptr_local = NULL;
 
 #ifndef ZXC_ONLY_DEFAULT
 #if defined(__x86_64__) || defined(_M_X64)
+#ifndef ZXC_DISABLE_AVX512
     if (cpu == ZXC_CPU_AVX512) {
         zxc_decompress_ptr_local = zxc_decompress_chunk_wrapper_avx512;
         zxc_decompress_dict_ptr_local = zxc_decompress_chunk_wrapper_dict_avx512;
-    } else if (cpu == ZXC_CPU_AVX2) {
+    } else
+#endif
+    if (cpu == ZXC_CPU_AVX2) {
         zxc_decompress_ptr_local = zxc_decompress_chunk_wrapper_avx2;
         zxc_decompress_dict_ptr_local = zxc_decompress_chunk_wrapper_dict_avx2;
     } else {
@@ -373,14 +414,17 @@ static int zxc_decompress_dispatch_init(const zxc_cctx_t* RESTRICT ctx, const ui
 static int zxc_decompress_safe_dispatch_init(const zxc_cctx_t* RESTRICT ctx,
                                              const uint8_t* RESTRICT src, const size_t src_sz,
                                              uint8_t* RESTRICT dst, const size_t dst_cap) {
-    const zxc_cpu_feature_t cpu = zxc_detect_cpu_features();
+    const zxc_cpu_feature_t cpu = zxc_dispatch_cpu();
     zxc_decompress_func_t zxc_decompress_safe_ptr_local = NULL;
 
 #ifndef ZXC_ONLY_DEFAULT
 #if defined(__x86_64__) || defined(_M_X64)
+#ifndef ZXC_DISABLE_AVX512
     if (cpu == ZXC_CPU_AVX512)
         zxc_decompress_safe_ptr_local = zxc_decompress_chunk_wrapper_safe_avx512;
-    else if (cpu == ZXC_CPU_AVX2)
+    else
+#endif
+    if (cpu == ZXC_CPU_AVX2)
         zxc_decompress_safe_ptr_local = zxc_decompress_chunk_wrapper_safe_avx2;
     else
         zxc_decompress_safe_ptr_local = zxc_decompress_chunk_wrapper_safe_default;
@@ -412,8 +456,8 @@ static int zxc_decompress_safe_dispatch_init(const zxc_cctx_t* RESTRICT ctx,
 /**
  * @brief First-call initialiser for the compression dispatcher.
  *
- * Detects CPU features, selects the best implementation, stores the
- * pointer atomically, then tail-calls into it.
+ * Detects CPU features (or takes the pinned variant), selects the best
+ * implementation, stores the pointer atomically, then tail-calls into it.
  *
  * @param[in,out] ctx      Compression context.
  * @param[in]     src      Uncompressed input chunk.
@@ -427,14 +471,17 @@ static int zxc_decompress_safe_dispatch_init(const zxc_cctx_t* RESTRICT ctx,
 static int zxc_compress_dispatch_init(zxc_cctx_t* RESTRICT ctx, const uint8_t* RESTRICT src,
                                       const size_t src_sz, uint8_t* RESTRICT dst,
                                       const size_t dst_cap) {
-    const zxc_cpu_feature_t cpu = zxc_detect_cpu_features();
+    const zxc_cpu_feature_t cpu = zxc_dispatch_cpu();
     zxc_compress_func_t zxc_compress_ptr_local = NULL;
 
 #ifndef ZXC_ONLY_DEFAULT
 #if defined(__x86_64__) || defined(_M_X64)
+#ifndef ZXC_DISABLE_AVX512
     if (cpu == ZXC_CPU_AVX512)
         zxc_compress_ptr_local = zxc_compress_chunk_wrapper_avx512;
-    else if (cpu == ZXC_CPU_AVX2)
+    else
+#endif
+    if (cpu == ZXC_CPU_AVX2)
         zxc_compress_ptr_local = zxc_compress_chunk_wrapper_avx2;
     else
         zxc_compress_ptr_local = zxc_compress_chunk_wrapper_default;
@@ -535,6 +582,77 @@ int zxc_compress_chunk_wrapper(zxc_cctx_t* RESTRICT ctx, const uint8_t* RESTRICT
     return func(ctx, src, src_sz, dst, dst_cap);
 }
 
+/**
+ * @brief Maps a CPU feature level to the variant the dispatchers above run.
+ *
+ * Mirrors their selection logic without resolving any pointer.
+ *
+ * @param[in] cpu Feature level, detected or pinned.
+ * @return A @ref zxc_simd_variant_t value.
+ */
+// LCOV_EXCL_START
+static int zxc_variant_for(const zxc_cpu_feature_t cpu) {
+#ifndef ZXC_ONLY_DEFAULT
+#if defined(__x86_64__) || defined(_M_X64)
+#ifndef ZXC_DISABLE_AVX512
+    if (cpu == ZXC_CPU_AVX512) return ZXC_SIMD_AVX512;
+#endif
+    if (cpu == ZXC_CPU_AVX2) return ZXC_SIMD_AVX2;
+#elif defined(__arm__) || defined(_M_ARM)
+    // cppcheck-suppress knownConditionTrueFalse
+    if (cpu == ZXC_CPU_NEON) return ZXC_SIMD_NEON;
+#endif
+#endif
+    (void)cpu;
+    return ZXC_SIMD_DEFAULT;
+}
+// LCOV_EXCL_STOP
+
+/**
+ * @brief Reports the variant the dispatchers above select on this CPU.
+ *
+ * Honours a variant pinned with @ref zxc_simd_force.
+ *
+ * @return A @ref zxc_simd_variant_t value.
+ */
+int zxc_simd_variant(void) { return zxc_variant_for(zxc_dispatch_cpu()); }
+
+/**
+ * @brief Pins the dispatchers to @p variant, or restores detection.
+ *
+ * A variant is accepted when the detected one is it or a superset of it
+ * (AVX-512 CPUs also run AVX2), and the baseline always is. The lazily
+ * resolved pointers are cleared so the next call of each kind re-resolves;
+ * calls already running finish on the variant they started with.
+ *
+ * @param[in] variant A @ref zxc_simd_variant_t value, or @ref ZXC_SIMD_AUTO.
+ * @return The variant in effect after the call: @p variant when accepted,
+ *         the previous one otherwise.
+ */
+int zxc_simd_force(const int variant) {
+    const int best = zxc_variant_for(zxc_detect_cpu_features());
+    const int ok = variant == ZXC_SIMD_AUTO || variant == ZXC_SIMD_DEFAULT || variant == best ||
+                   (variant == ZXC_SIMD_AVX2 && best == ZXC_SIMD_AVX512);
+    if (ok) {
+#if ZXC_USE_C11_ATOMICS
+        atomic_store_explicit(&zxc_forced_variant, variant, memory_order_release);
+        atomic_store_explicit(&zxc_decompress_ptr, (zxc_decompress_func_t)0, memory_order_release);
+        atomic_store_explicit(&zxc_decompress_dict_ptr, (zxc_decompress_func_t)0,
+                              memory_order_release);
+        atomic_store_explicit(&zxc_decompress_safe_ptr, (zxc_decompress_func_t)0,
+                              memory_order_release);
+        atomic_store_explicit(&zxc_compress_ptr, (zxc_compress_func_t)0, memory_order_release);
+#else
+        zxc_forced_variant = variant;
+        zxc_decompress_ptr = (zxc_decompress_func_t)0;
+        zxc_decompress_dict_ptr = (zxc_decompress_func_t)0;
+        zxc_decompress_safe_ptr = (zxc_decompress_func_t)0;
+        zxc_compress_ptr = (zxc_compress_func_t)0;
+#endif
+    }
+    return zxc_simd_variant();
+}
+
 /*
  * ============================================================================
  * HUFFMAN TRAMPOLINES
@@ -1617,8 +1735,10 @@ int64_t zxc_compress_block(zxc_cctx* cctx, const void* RESTRICT src, const size_
     if (UNLIKELY(cctx->owns_workspace && level >= ZXC_LEVEL_DENSITY && !cctx->inner.opt_scratch))
         return ZXC_ERROR_BAD_LEVEL;
 
+    /* Store the dict-free size: storing the effective one would grow the
+     * block size by the dictionary on every call reusing this context. */
     cctx->stored_level = level;
-    cctx->stored_block_size = effective_block_size;
+    cctx->stored_block_size = base_block_size;
     cctx->stored_checksum = checksum_enabled;
 
     /* Re-init when block_size changed, or when a per-call level raise into
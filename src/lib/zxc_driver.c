@@ -155,9 +155,36 @@ static int pthread_join(pthread_t thread, void** retval) {
 #define sysconf(x) zxc_get_num_procs()
 #define _SC_NPROCESSORS_ONLN 0
 
+/**
+ * @brief Monotonic clock timing blocks and stages for @ref zxc_block_callback_t
+ * and @ref zxc_stream_stats_t.
+ * @return Nanoseconds since an unspecified origin.
+ */
+static uint64_t zxc_now_ns(void) {
+    LARGE_INTEGER frequency;
+    LARGE_INTEGER count;
+    QueryPerformanceFrequency(&frequency);
+    QueryPerformanceCounter(&count);
+    const uint64_t f = (uint64_t)frequency.QuadPart;
+    const uint64_t c = (uint64_t)count.QuadPart;
+    return (c / f) * 1000000000ULL + (c % f) * 1000000000ULL / f;
+}
+
 #else
 #include <pthread.h>
+#include <time.h>
 #include <unistd.h>
+
+/**
+ * @brief Monotonic clock timing blocks and stages for @ref zxc_block_callback_t
+ * and @ref zxc_stream_stats_t.
+ * @return Nanoseconds since an unspecified origin.
+ */
+static uint64_t zxc_now_ns(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+    return (uint64_t)ts.tv_sec * 1000000000ULL + (uint64_t)ts.tv_nsec;
+}
 #endif
 
 /*
@@ -207,6 +234,11 @@ typedef enum { JOB_STATUS_FREE, JOB_STATUS_FILLED, JOB_STATUS_PROCESSED } job_st
  *      The actual size of the valid data produced in the output buffer.
  * @var zxc_stream_job_t::job_id
  *      A unique identifier for the job, often used for ordering or debugging.
+ * @var zxc_stream_job_t::time_ns
+ *      Time the worker spent processing the job (only measured when a
+ * @ref zxc_block_callback_t or @ref zxc_stream_stats_t is set).
+ * @var zxc_stream_job_t::worker_id
+ *      Index of the worker that processed the job.
  * @var zxc_stream_job_t::status
  *      The current state of this job (Free, Filled, or Processed).
  * @var zxc_stream_job_t::pad
@@ -222,6 +254,8 @@ typedef struct {
     size_t out_cap;
     size_t result_sz;
     int job_id;
+    uint64_t time_ns;
+    int worker_id;
     ZXC_ATOMIC job_status_t status;  // Atomic for lock-free status updates
     char pad[ZXC_CACHE_LINE_SIZE];   // Prevent False Sharing
 } zxc_stream_job_t;
@@ -304,7 +338,15 @@ typedef int (*zxc_chunk_processor_t)(zxc_cctx_t* RESTRICT ctx, const uint8_t* RE
  * @var zxc_stream_ctx_t::progress_cb
  *     Optional callback function for reporting progress during processing.
  * @var zxc_stream_ctx_t::progress_user_data
- *    User data pointer to be passed to the progress callback function.
+ *    User data pointer to be passed to the progress and block callbacks.
+ * @var zxc_stream_ctx_t::block_cb
+ *     Optional per-block statistics callback, NULL when disabled.
+ * @var zxc_stream_ctx_t::next_worker_id
+ *     Index handed to the next worker thread that starts.
+ * @var zxc_stream_ctx_t::timed
+ *     Non-zero when blocks and stages are timed (block callback or stats set).
+ * @var zxc_stream_ctx_t::worker_ns
+ *     Busy time of each worker, indexed by worker id; NULL without stats.
  * @var zxc_stream_ctx_t::total_input_bytes
  *     Total size of the input data in bytes, used for progress tracking.
  * @var zxc_stream_ctx_t::dict
@@ -338,6 +380,10 @@ typedef struct {
     int file_has_checksum;
     zxc_progress_callback_t progress_cb;
     void* progress_user_data;
+    zxc_block_callback_t block_cb;
+    int next_worker_id;
+    int timed;
+    uint64_t* worker_ns;
     uint64_t total_input_bytes;
     const uint8_t* dict;
     size_t dict_size;
@@ -369,6 +415,12 @@ typedef struct {
  * @var writer_args_t::bytes_processed
  * The number of bytes processed so far, used for progress reporting.
  *
+ * @var writer_args_t::block_index
+ * Index of the next block written, reported to the block callback.
+ *
+ * @var writer_args_t::write_ns
+ * Time spent writing blocks, reported in the stream stats.
+ *
  * @var writer_args_t::seek_comp
  * Array of compressed block sizes for seek table construction.
  *
@@ -384,6 +436,8 @@ typedef struct {
     int64_t total_bytes;
     uint32_t global_hash;
     uint64_t bytes_processed;  // For progress callback
+    uint64_t block_index;      // For block callback
+    uint64_t write_ns;         // For stream stats
     uint32_t* seek_comp;
     uint32_t seek_count;
     uint32_t seek_cap;
@@ -444,6 +498,12 @@ static void* zxc_stream_worker(void* arg) {
 
     cctx.compression_level = ctx->compression_level;
 
+    pthread_mutex_lock(&ctx->lock);
+    const int worker_id = ctx->next_worker_id++;
+    pthread_mutex_unlock(&ctx->lock);
+
+    uint64_t busy_ns = 0;
+
     /* Per-worker dict buffer for assembling [dict | block_data] */
     const size_t dsz = ctx->dict_size;
     uint8_t* const dict_work = cctx.dict_buffer;
@@ -465,6 +525,7 @@ static void* zxc_stream_worker(void* arg) {
         job = &ctx->jobs[jid];
         pthread_mutex_unlock(&ctx->lock);
 
+        const uint64_t start_ns = ctx->timed ? zxc_now_ns() : 0;
         int res;
         if (dict_work && ctx->compression_mode == 1) {
             ZXC_MEMCPY(dict_work + dsz, job->in_buf, job->in_sz);
@@ -477,8 +538,13 @@ static void* zxc_stream_worker(void* arg) {
             res = ctx->processor(&cctx, job->in_buf, job->in_sz, job->out_buf, job->out_cap);
         }
 
+        const uint64_t time_ns = ctx->timed ? zxc_now_ns() - start_ns : 0;
+        busy_ns += time_ns;
+
         pthread_mutex_lock(&ctx->lock);
         job->result_sz = UNLIKELY(res < 0) ? 0 : (size_t)res;
+        job->time_ns = time_ns;
+        job->worker_id = worker_id;
         job->status = JOB_STATUS_PROCESSED;
         if (UNLIKELY(res < 0)) {
             ctx->io_error = 1;
@@ -489,6 +555,8 @@ static void* zxc_stream_worker(void* arg) {
         }
         pthread_mutex_unlock(&ctx->lock);
     }
+    // Each worker owns its slot; the engine reads them after joining.
+    if (ctx->worker_ns) ctx->worker_ns[worker_id] = busy_ns;
     zxc_cctx_free(&cctx);
     return NULL;
 }
@@ -534,7 +602,10 @@ static void* zxc_async_writer(void* arg) {
         if (result_sz == (size_t)-1) break;
 
         if (args->f && result_sz > 0) {
-            if (fwrite(job->out_buf, 1, result_sz, args->f) != result_sz) {
+            const uint64_t start_ns = ctx->timed ? zxc_now_ns() : 0;
+            const size_t written = fwrite(job->out_buf, 1, result_sz, args->f);
+            if (ctx->timed) args->write_ns += zxc_now_ns() - start_ns;
+            if (written != result_sz) {
                 pthread_mutex_lock(&ctx->lock);
                 ctx->io_error = 1;
                 pthread_cond_signal(&ctx->cond_reader);
@@ -587,6 +658,16 @@ static void* zxc_async_writer(void* arg) {
             // LCOV_EXCL_STOP
         }
 
+        if (ctx->block_cb) {
+            const zxc_block_stats_t stats = {.index = args->block_index,
+                                             .in_size = in_sz,
+                                             .out_size = result_sz,
+                                             .time_ns = job->time_ns,
+                                             .worker_id = job->worker_id};
+            ctx->block_cb(&stats, ctx->progress_user_data);
+        }
+        args->block_index++;
+
         pthread_mutex_lock(&ctx->lock);
         job->status = JOB_STATUS_FREE;
         ctx->write_idx = (ctx->write_idx + 1) % ctx->ring_size;
@@ -629,7 +710,9 @@ static void* zxc_async_writer(void* arg) {
  * @param[in]  seekable         Non-zero to emit a seek table (compression mode).
  * @param[in]  func             Chunk processor (compression or decompression).
  * @param[in]  progress_cb      Optional progress callback, or NULL.
- * @param[in]  user_data        Opaque pointer passed to @p progress_cb.
+ * @param[in]  block_cb         Optional per-block statistics callback, or NULL.
+ * @param[out] stats            Optional stage timings, filled in on success, or NULL.
+ * @param[in]  user_data        Opaque pointer passed to both callbacks.
  * @param[in]  dict             Optional dictionary content, or NULL.
  * @param[in]  dict_size        Dictionary length in bytes (0 if none).
  * @param[in]  dict_huf         Optional shared literal Huffman table, or NULL.
@@ -640,9 +723,12 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
                                      const int level, const size_t block_size,
                                      const int checksum_enabled, const int seekable,
                                      zxc_chunk_processor_t func,
-                                     zxc_progress_callback_t progress_cb, void* user_data,
+                                     zxc_progress_callback_t progress_cb,
+                                     zxc_block_callback_t block_cb,
+                                     zxc_stream_stats_t* stats, void* user_data,
                                      const uint8_t* dict, const size_t dict_size,
                                      const uint8_t* dict_huf) {
+    const uint64_t start_ns = stats ? zxc_now_ns() : 0;
     zxc_stream_ctx_t ctx;
     ZXC_MEMSET(&ctx, 0, sizeof(ctx));
 
@@ -694,6 +780,8 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
     ctx.file_has_checksum = mode == 1 ? checksum_enabled : file_has_chk;
     ctx.progress_cb = progress_cb;
     ctx.progress_user_data = user_data;
+    ctx.block_cb = block_cb;
+    ctx.timed = block_cb || stats;
     ctx.total_input_bytes = total_file_size;
     ctx.dict = dict;
     ctx.dict_size = dict_size;
@@ -747,8 +835,11 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
     pthread_cond_init(&ctx.cond_writer, NULL);
 
     pthread_t* const workers = ZXC_MALLOC((size_t)num_workers * sizeof(pthread_t));
-    if (UNLIKELY(!workers)) {
+    if (stats) ctx.worker_ns = (uint64_t*)ZXC_CALLOC((size_t)num_workers, sizeof(uint64_t));
+    if (UNLIKELY(!workers || (stats && !ctx.worker_ns))) {
         // LCOV_EXCL_START
+        ZXC_FREE(ctx.worker_ns);
+        ZXC_FREE(workers);
         ZXC_ALIGNED_FREE(mem_block);
         return ZXC_ERROR_MEMORY;
         // LCOV_EXCL_STOP
@@ -764,13 +855,14 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
         pthread_cond_destroy(&ctx.cond_worker);
         pthread_cond_destroy(&ctx.cond_reader);
         pthread_mutex_destroy(&ctx.lock);
+        ZXC_FREE(ctx.worker_ns);
         ZXC_FREE(workers);
         ZXC_ALIGNED_FREE(mem_block);
         return ZXC_ERROR_MEMORY;
         // LCOV_EXCL_STOP
     }
 
-    writer_args_t w_args = {&ctx, f_out, 0, 0, 0, NULL, 0, 0};
+    writer_args_t w_args = {&ctx, f_out, 0, 0, 0, 0, 0, NULL, 0, 0};
 
     /* Seekable: allocate initial block-size tracking array */
     if (mode == 1 && seekable) {
@@ -787,6 +879,7 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
             pthread_cond_destroy(&ctx.cond_worker);
             pthread_cond_destroy(&ctx.cond_reader);
             pthread_mutex_destroy(&ctx.lock);
+            ZXC_FREE(ctx.worker_ns);
             ZXC_FREE(workers);
             ZXC_ALIGNED_FREE(mem_block);
             return ZXC_ERROR_MEMORY;
@@ -815,6 +908,7 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
         pthread_cond_destroy(&ctx.cond_worker);
         pthread_cond_destroy(&ctx.cond_reader);
         pthread_mutex_destroy(&ctx.lock);
+        ZXC_FREE(ctx.worker_ns);
         ZXC_FREE(workers);
         ZXC_ALIGNED_FREE(mem_block);
         return ZXC_ERROR_MEMORY;
@@ -824,6 +918,7 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
     int read_idx = 0;
     int read_eof = 0;
     uint64_t total_src_bytes = 0;
+    uint64_t read_ns = 0;
 
     // Reader Loop: Reads from file, prepares jobs, pushes to worker queue.
     while (!read_eof && !ctx.io_error) {
@@ -835,6 +930,7 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
 
         if (UNLIKELY(ctx.io_error)) break;
 
+        const uint64_t read_start_ns = ctx.timed ? zxc_now_ns() : 0;
         size_t read_sz = 0;
         if (mode == 1) {
             read_sz = fread(job->in_buf, 1, runtime_chunk_sz, f_in);
@@ -891,6 +987,7 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
             }
         }
     _job_prepared:
+        if (ctx.timed) read_ns += zxc_now_ns() - read_start_ns;
         if (UNLIKELY(read_eof && read_sz == 0)) break;
 
         job->in_sz = read_sz;
@@ -1012,6 +1109,20 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
         }
     }
 
+    if (stats && !ctx.io_error) {
+        stats->read_ns = read_ns;
+        stats->write_ns = w_args.write_ns;
+        stats->compute_ns = 0;
+        for (int i = 0; i < started_workers; i++) {
+            stats->compute_ns += ctx.worker_ns[i];
+            if (stats->worker_ns && i < stats->worker_ns_len)
+                stats->worker_ns[i] = ctx.worker_ns[i];
+        }
+        stats->n_workers = started_workers;
+        stats->wall_ns = zxc_now_ns() - start_ns;
+    }
+
+    ZXC_FREE(ctx.worker_ns);
     ZXC_FREE(w_args.seek_comp);
     ZXC_FREE(workers);
     ZXC_ALIGNED_FREE(mem_block);
@@ -1024,10 +1135,8 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
 /**
  * @brief Compresses a @c FILE* stream to another @c FILE* stream.
  *
- * Public API; full contract in @c zxc_stream.h. Resolves the options (threads,
- * level, block size, checksums, seekable, dictionary) with their defaults, then
- * drives @ref zxc_stream_engine_run in compression mode with the
- * compress chunk processor.
+ * Public API; full contract in @c zxc_stream.h. @ref zxc_stream_compress_ex
+ * without extensions.
  *
  * @param[in]  f_in   Input stream (must be non-NULL).
  * @param[out] f_out  Output stream (NULL performs a dry run / size estimate).
@@ -1035,6 +1144,25 @@ static int64_t zxc_stream_engine_run(FILE* f_in, FILE* f_out, const int n_thread
  * @return Total bytes written on success, or a negative @ref zxc_error_t.
  */
 int64_t zxc_stream_compress(FILE* f_in, FILE* f_out, const zxc_compress_opts_t* opts) {
+    return zxc_stream_compress_ex(f_in, f_out, opts, NULL);
+}
+
+/**
+ * @brief Compresses a @c FILE* stream, with the streaming extensions.
+ *
+ * Public API; see @c zxc_stream.h. Resolves the options (threads, level, block
+ * size, checksums, seekable, dictionary) with their defaults and the
+ * extensions, then drives @ref zxc_stream_engine_run in compression mode with
+ * the compress chunk processor.
+ *
+ * @param[in]  f_in   Input stream (must be non-NULL).
+ * @param[out] f_out  Output stream (NULL performs a dry run / size estimate).
+ * @param[in]  opts   Compression options, or NULL for all defaults.
+ * @param[in]  ext    Extensions, or NULL to disable them.
+ * @return Total bytes written on success, or a negative @ref zxc_error_t.
+ */
+int64_t zxc_stream_compress_ex(FILE* f_in, FILE* f_out, const zxc_compress_opts_t* opts,
+                               const zxc_stream_ext_t* ext) {
     if (UNLIKELY(!f_in)) return ZXC_ERROR_NULL_INPUT;
 
     const int n_threads = opts ? opts->n_threads : 0;
@@ -1046,6 +1174,8 @@ int64_t zxc_stream_compress(FILE* f_in, FILE* f_out, const zxc_compress_opts_t*
     const uint8_t* dict = opts ? (const uint8_t*)opts->dict : NULL;
     const size_t dict_size = (opts && opts->dict) ? opts->dict_size : 0;
     zxc_progress_callback_t cb = opts ? opts->progress_cb : NULL;
+    zxc_block_callback_t block_cb = ext ? ext->block_cb : NULL;
+    zxc_stream_stats_t* stats = ext ? ext->stats : NULL;
     void* ud = opts ? opts->user_data : NULL;
 
     if (UNLIKELY(!zxc_validate_block_size(block_size))) return ZXC_ERROR_BAD_BLOCK_SIZE;
@@ -1053,24 +1183,41 @@ int64_t zxc_stream_compress(FILE* f_in, FILE* f_out, const zxc_compress_opts_t*
 
     const uint8_t* dict_huf = (opts && opts->dict) ? (const uint8_t*)opts->dict_huf : NULL;
     return zxc_stream_engine_run(f_in, f_out, n_threads, 1, level, block_size, checksum_enabled,
-                                 seekable, zxc_compress_chunk_wrapper, cb, ud, dict, dict_size,
-                                 dict_huf);
+                                 seekable, zxc_compress_chunk_wrapper, cb, block_cb, stats, ud,
+                                 dict, dict_size, dict_huf);
 }
 
 /**
  * @brief Decompresses a @c FILE* stream to another @c FILE* stream.
  *
- * Public API; full contract in @c zxc_stream.h. Resolves the options (threads,
- * checksums, dictionary), then drives @ref zxc_stream_engine_run in
+ * Public API; full contract in @c zxc_stream.h. @ref zxc_stream_decompress_ex
+ * without extensions.
+ *
+ * @param[in]  f_in   Input (compressed) stream (must be non-NULL).
+ * @param[out] f_out  Output (decompressed) stream.
+ * @param[in]  opts   Decompression options, or NULL for all defaults.
+ * @return Total bytes written on success, or a negative @ref zxc_error_t.
+ */
+int64_t zxc_stream_decompress(FILE* f_in, FILE* f_out, const zxc_decompress_opts_t* opts) {
+    return zxc_stream_decompress_ex(f_in, f_out, opts, NULL);
+}
+
+/**
+ * @brief Decompresses a @c FILE* stream, with the streaming extensions.
+ *
+ * Public API; see @c zxc_stream.h. Resolves the options (threads, checksums,
+ * dictionary) and the extensions, then drives @ref zxc_stream_engine_run in
  * decompression mode with the decompress chunk processor. The block size and
  * level are recovered from the archive header, not from @p opts.
  *
  * @param[in]  f_in   Input (compressed) stream (must be non-NULL).
  * @param[out] f_out  Output (decompressed) stream.
  * @param[in]  opts   Decompression options, or NULL for all defaults.
+ * @param[in]  ext    Extensions, or NULL to disable them.
  * @return Total bytes written on success, or a negative @ref zxc_error_t.
  */
-int64_t zxc_stream_decompress(FILE* f_in, FILE* f_out, const zxc_decompress_opts_t* opts) {
+int64_t zxc_stream_decompress_ex(FILE* f_in, FILE* f_out, const zxc_decompress_opts_t* opts,
+                                 const zxc_stream_ext_t* ext) {
     if (UNLIKELY(!f_in)) return ZXC_ERROR_NULL_INPUT;
 
     const int n_threads = opts ? opts->n_threads : 0;
@@ -1078,12 +1225,14 @@ int64_t zxc_stream_decompress(FILE* f_in, FILE* f_out, const zxc_decompress_opts
     const uint8_t* dict = opts ? (const uint8_t*)opts->dict : NULL;
     const size_t dict_size = (opts && opts->dict) ? opts->dict_size : 0;
     zxc_progress_callback_t cb = opts ? opts->progress_cb : NULL;
+    zxc_block_callback_t block_cb = ext ? ext->block_cb : NULL;
+    zxc_stream_stats_t* stats = ext ? ext->stats : NULL;
     void* ud = opts ? opts->user_data : NULL;
 
     const uint8_t* dict_huf = (opts && opts->dict) ? (const uint8_t*)opts->dict_huf : NULL;
     return zxc_stream_engine_run(f_in, f_out, n_threads, 0, 0, 0, checksum_enabled, 0,
-                                 (zxc_chunk_processor_t)zxc_decompress_chunk_wrapper, cb, ud, dict,
-                                 dict_size, dict_huf);
+                                 (zxc_chunk_processor_t)zxc_decompress_chunk_wrapper, cb, block_cb,
+                                 stats, ud, dict, dict_size, dict_huf);
 }
 
 /**
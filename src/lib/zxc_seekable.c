@@ -37,8 +37,29 @@
 /*  Platform Threading Layer                                                 */
 /* ========================================================================= */
 
+#if defined(ZXC_NO_THREADS)
+/*
+ * Single-threaded builds (e.g. wasm32 without threads support): there is no
+ * thread API to call, so zxc_seekable_decompress_range_mt() always takes its
+ * sequential fallback and these stubs are never reached.
+ */
+typedef int zxc_thread_t;
+
+// LCOV_EXCL_START
+static int zxc_seek_thread_create(zxc_thread_t* t, void* (*fn)(void*), void* arg) {
+    (void)t;
+    (void)fn;
+    (void)arg;
+    return ZXC_ERROR_MEMORY;
+}
+
+static void zxc_seek_thread_join(zxc_thread_t t) { (void)t; }
+// LCOV_EXCL_STOP
+
+static int zxc_seek_get_num_procs(void) { return 1; }
+
 // LCOV_EXCL_START - Windows platform layer, not reachable on POSIX CI
-#if defined(_WIN32)
+#elif defined(_WIN32)
 #include <process.h> /* _beginthreadex */
 #include <windows.h>
 
@@ -1019,6 +1040,10 @@ int64_t zxc_seekable_decompress_range_mt(zxc_seekable* s, void* dst, const size_
     /* Auto-detect thread count (0 = use all available cores) */
     if (n_threads == 0) n_threads = zxc_seek_get_num_procs();
 
+#ifdef ZXC_NO_THREADS
+    n_threads = 1;
+#endif
+
     /* Fallback to single-threaded path for trivial cases */
     if (n_threads <= 1 || num_jobs <= 1) {
         return zxc_seekable_decompress_range(s, dst, dst_capacity, offset, len);
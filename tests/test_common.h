@@ -98,6 +98,8 @@ int test_truncated_input(void);
 int test_io_failures(void);
 int test_thread_params(void);
 int test_multithread_roundtrip(void);
+int test_stream_block_callback(void);
+int test_stream_stats(void);
 int test_stream_get_decompressed_size_errors(void);
 int test_stream_engine_errors(void);
 
@@ -198,6 +200,7 @@ int test_dict_get_id_apis(void);
 int test_dict_buffer_roundtrip(void);
 int test_dict_block_roundtrip(void);
 int test_dict_block_safe_roundtrip(void);
+int test_dict_block_cctx_reuse(void);
 int test_dict_mismatch_error(void);
 int test_dict_required_error(void);
 int test_dict_no_dict_compat(void);
@@ -405,6 +405,54 @@ cleanup:
     return result;
 }
 
+int test_dict_block_cctx_reuse(void) {
+    printf("=== TEST: Dict - Block API context reused across many dict blocks ===\n");
+
+    /* Each call used to fold the dictionary into the stored block size, so a
+     * context reused for small dict blocks doubled its size until it failed. */
+    uint8_t dict[1024];
+    for (size_t i = 0; i < sizeof(dict); i++) dict[i] = (uint8_t)("telemetry "[i % 10] + i / 97);
+    uint8_t src[200];
+    uint8_t compressed[512];
+    uint8_t decompressed[sizeof(src)];
+    zxc_cctx* cctx = zxc_create_cctx(NULL);
+    zxc_dctx* dctx = zxc_create_dctx();
+
+    int result = 0;
+    if (!cctx || !dctx) {
+        printf("  [FAIL] allocation failed\n");
+        goto cleanup;
+    }
+
+    for (int i = 0; i < 64; i++) {
+        memcpy(src, dict + (size_t)(i * 13) % (sizeof(dict) - sizeof(src)), sizeof(src));
+        src[0] = (uint8_t)i;
+        zxc_compress_opts_t copts = {.level = 3, .dict = dict, .dict_size = sizeof(dict)};
+        int64_t comp_size =
+            zxc_compress_block(cctx, src, sizeof(src), compressed, sizeof(compressed), &copts);
+        if (comp_size <= 0) {
+            printf("  [FAIL] block %d: compress_block returned %lld (%s)\n", i,
+                   (long long)comp_size, zxc_error_name((int)comp_size));
+            goto cleanup;
+        }
+        zxc_decompress_opts_t dopts = {.dict = dict, .dict_size = sizeof(dict)};
+        int64_t dec_size = zxc_decompress_block_safe(dctx, compressed, (size_t)comp_size,
+                                                     decompressed, sizeof(src), &dopts);
+        if (dec_size != (int64_t)sizeof(src) || memcmp(src, decompressed, sizeof(src)) != 0) {
+            printf("  [FAIL] block %d: dec_size=%lld\n", i, (long long)dec_size);
+            goto cleanup;
+        }
+    }
+    printf("  [PASS] 64 blocks through one context\n");
+    result = 1;
+
+cleanup:
+    zxc_free_cctx(cctx);
+    zxc_free_dctx(dctx);
+    if (result) printf("PASS\n\n");
+    return result;
+}
+
 int test_dict_mismatch_error(void) {
     printf("=== TEST: Dict - dict_id mismatch error ===\n");
 
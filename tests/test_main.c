@@ -83,6 +83,8 @@ static const test_entry_t g_tests[] = {
     TEST_CASE(test_io_failures),
     TEST_CASE(test_thread_params),
     TEST_CASE(test_multithread_roundtrip),
+    TEST_CASE(test_stream_block_callback),
+    TEST_CASE(test_stream_stats),
     TEST_CASE(test_stream_get_decompressed_size_errors),
     TEST_CASE(test_stream_engine_errors),
 
@@ -123,6 +125,7 @@ static const test_entry_t g_tests[] = {
     TEST_CASE(test_dict_buffer_roundtrip),
     TEST_CASE(test_dict_block_roundtrip),
     TEST_CASE(test_dict_block_safe_roundtrip),
+    TEST_CASE(test_dict_block_cctx_reuse),
     TEST_CASE(test_dict_mismatch_error),
     TEST_CASE(test_dict_required_error),
     TEST_CASE(test_dict_no_dict_compat),
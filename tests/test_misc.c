@@ -63,7 +63,7 @@ int test_error_name() {
 }
 
 int test_library_info_api() {
-    printf("=== TEST: Unit - Library Info API (zxc_min/max/default_level, zxc_version_string) ===\n");
+    printf("=== TEST: Unit - Library Info API (zxc_min/max/default_level, zxc_version_string, zxc_simd_variant, zxc_simd_force) ===\n");
 
     // 1. Min level must match compile-time constant
     int min = zxc_min_level();
@@ -106,6 +106,44 @@ int test_library_info_api() {
     }
     printf("  [PASS] zxc_version_string() == \"%s\"\n", ver);
 
+    // 5. The dispatched variant is a known one, and stable across calls
+    const int variant = zxc_simd_variant();
+    if (variant < ZXC_SIMD_DEFAULT || variant > ZXC_SIMD_AVX512 || variant != zxc_simd_variant()) {
+        printf("Failed: zxc_simd_variant() returned %d\n", variant);
+        return 0;
+    }
+    printf("  [PASS] zxc_simd_variant() == %d\n", variant);
+
+    // 6. The baseline can always be pinned and still round-trips; a variant
+    //    above the detected one is refused; AUTO restores detection.
+    if (zxc_simd_force(ZXC_SIMD_DEFAULT) != ZXC_SIMD_DEFAULT ||
+        zxc_simd_variant() != ZXC_SIMD_DEFAULT) {
+        printf("Failed: zxc_simd_force(ZXC_SIMD_DEFAULT) was not applied\n");
+        return 0;
+    }
+    uint8_t src[4096];
+    gen_lz_data(src, sizeof(src));
+    uint8_t comp[8192];
+    uint8_t back[4096];
+    const int64_t csize = zxc_compress(src, sizeof(src), comp, sizeof(comp), NULL);
+    const int64_t dsize = csize > 0 ? zxc_decompress(comp, (size_t)csize, back, sizeof(back), NULL)
+                                    : csize;
+    if (dsize != (int64_t)sizeof(src) || memcmp(src, back, sizeof(src)) != 0) {
+        printf("Failed: round trip on the pinned baseline (%lld)\n", (long long)dsize);
+        zxc_simd_force(ZXC_SIMD_AUTO);
+        return 0;
+    }
+    if (variant == ZXC_SIMD_DEFAULT && zxc_simd_force(ZXC_SIMD_AVX512) != ZXC_SIMD_DEFAULT) {
+        printf("Failed: zxc_simd_force(ZXC_SIMD_AVX512) accepted on a baseline CPU\n");
+        zxc_simd_force(ZXC_SIMD_AUTO);
+        return 0;
+    }
+    if (zxc_simd_force(ZXC_SIMD_AUTO) != variant) {
+        printf("Failed: zxc_simd_force(ZXC_SIMD_AUTO) did not restore %d\n", variant);
+        return 0;
+    }
+    printf("  [PASS] zxc_simd_force() pins and restores the variant\n");
+
     printf("PASS\n\n");
     return 1;
 }
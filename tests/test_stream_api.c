@@ -357,6 +357,132 @@ cleanup:
     return result;
 }
 
+// Accumulates what the per-block callback reports
+typedef struct {
+    uint64_t blocks;
+    uint64_t in_total;
+    uint64_t out_total;
+    int out_of_order;
+    int bad_worker;
+    int max_workers;
+} block_tally_t;
+
+static void tally_block(const zxc_block_stats_t* stats, const void* user_data) {
+    block_tally_t* const t = (block_tally_t*)(uintptr_t)user_data;
+    if (stats->index != t->blocks) t->out_of_order = 1;
+    if (stats->worker_id < 0 || stats->worker_id >= t->max_workers) t->bad_worker = 1;
+    t->blocks++;
+    t->in_total += stats->in_size;
+    t->out_total += stats->out_size;
+}
+
+// Per-block statistics callback: one call per block, in order, sizes summing up
+int test_stream_block_callback() {
+    printf("=== TEST: Unit - Stream Block Callback ===\n");
+
+    const size_t SIZE = 1024 * 1024 + 123;  // 3 blocks of 512 KB, the last partial
+    int result = 0;
+    uint8_t* input = malloc(SIZE);
+    FILE* f_in = tmpfile();
+    FILE* f_comp = tmpfile();
+    FILE* f_decomp = tmpfile();
+    if (!input || !f_in || !f_comp || !f_decomp) goto cleanup;
+    gen_lz_data(input, SIZE);
+    fwrite(input, 1, SIZE, f_in);
+    fseek(f_in, 0, SEEK_SET);
+
+    block_tally_t ct = {.max_workers = 3};
+    const zxc_stream_ext_t ext = {.block_cb = tally_block};
+    zxc_compress_opts_t co = {.n_threads = 4, .checksum_enabled = 1, .user_data = &ct};
+    const int64_t comp_size = zxc_stream_compress_ex(f_in, f_comp, &co, &ext);
+    if (comp_size < 0 || ct.blocks != 3 || ct.in_total != SIZE || ct.out_of_order ||
+        ct.bad_worker) {
+        printf("Failed: compression reported %llu blocks, %llu bytes in\n",
+               (unsigned long long)ct.blocks, (unsigned long long)ct.in_total);
+        goto cleanup;
+    }
+    // Blocks plus file header, EOF block and footer make up the archive.
+    if (ct.out_total >= (uint64_t)comp_size) {
+        printf("Failed: %llu block bytes for a %lld-byte archive\n",
+               (unsigned long long)ct.out_total, (long long)comp_size);
+        goto cleanup;
+    }
+
+    fseek(f_comp, 0, SEEK_SET);
+    block_tally_t dt = {.max_workers = 1};
+    zxc_decompress_opts_t dopt = {.n_threads = 1, .user_data = &dt};
+    if (zxc_stream_decompress_ex(f_comp, f_decomp, &dopt, &ext) != (int64_t)SIZE || dt.blocks != 3 ||
+        dt.out_total != SIZE || dt.in_total != ct.out_total || dt.out_of_order ||
+        dt.bad_worker) {
+        printf("Failed: decompression reported %llu blocks, %llu bytes out\n",
+               (unsigned long long)dt.blocks, (unsigned long long)dt.out_total);
+        goto cleanup;
+    }
+
+    printf("PASS\n\n");
+    result = 1;
+
+cleanup:
+    if (f_in) fclose(f_in);
+    if (f_comp) fclose(f_comp);
+    if (f_decomp) fclose(f_decomp);
+    free(input);
+    return result;
+}
+
+// Stage timings: every stage measured, workers reported up to the array length
+int test_stream_stats() {
+    printf("=== TEST: Unit - Stream Stage Timings ===\n");
+
+    const size_t SIZE = 2 * 1024 * 1024;
+    int result = 0;
+    uint8_t* input = malloc(SIZE);
+    FILE* f_in = tmpfile();
+    FILE* f_comp = tmpfile();
+    FILE* f_decomp = tmpfile();
+    if (!input || !f_in || !f_comp || !f_decomp) goto cleanup;
+    gen_lz_data(input, SIZE);
+    fwrite(input, 1, SIZE, f_in);
+    fseek(f_in, 0, SEEK_SET);
+
+    uint64_t busy[2] = {0, 0};
+    zxc_stream_stats_t cs = {.worker_ns = busy, .worker_ns_len = 2};
+    const zxc_stream_ext_t cext = {.stats = &cs};
+    zxc_compress_opts_t co = {.n_threads = 4};
+    if (zxc_stream_compress_ex(f_in, f_comp, &co, &cext) < 0) goto cleanup;
+    if (cs.n_workers != 3 || cs.wall_ns == 0 || cs.compute_ns == 0 || cs.read_ns == 0 ||
+        cs.write_ns == 0 || busy[0] + busy[1] > cs.compute_ns ||
+        cs.read_ns > cs.wall_ns || cs.write_ns > cs.wall_ns) {
+        printf("Failed: compression stats wall=%llu read=%llu compute=%llu write=%llu "
+               "workers=%d\n",
+               (unsigned long long)cs.wall_ns, (unsigned long long)cs.read_ns,
+               (unsigned long long)cs.compute_ns, (unsigned long long)cs.write_ns, cs.n_workers);
+        goto cleanup;
+    }
+
+    fseek(f_comp, 0, SEEK_SET);
+    zxc_stream_stats_t ds = {0};
+    const zxc_stream_ext_t dext = {.stats = &ds};
+    zxc_decompress_opts_t dopt = {.n_threads = 1};
+    if (zxc_stream_decompress_ex(f_comp, f_decomp, &dopt, &dext) != (int64_t)SIZE ||
+        ds.n_workers != 1 ||
+        ds.compute_ns == 0 || ds.compute_ns > ds.wall_ns) {
+        printf("Failed: decompression stats compute=%llu wall=%llu workers=%d\n",
+               (unsigned long long)ds.compute_ns, (unsigned long long)ds.wall_ns, ds.n_workers);
+        goto cleanup;
+    }
+
+    printf("PASS\n\n");
+    result = 1;
+
+cleanup:
+    if (f_in) fclose(f_in);
+    if (f_comp) fclose(f_comp);
+    if (f_decomp) fclose(f_decomp);
+    free(input);
+    return result;
+}
+
 int test_stream_get_decompressed_size_errors() {
     printf("=== TEST: Unit - zxc_stream_get_decompressed_size Error Codes ===\n");
 
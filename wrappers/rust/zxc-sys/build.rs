@@ -15,12 +15,109 @@
 //! On 32-bit ARM: `_default` and `_neon32` (NEON is optional there; the
 //! dispatcher probes it at runtime).
 //! Everywhere else (incl. AArch64, where NEON is baseline, and i686):
-//! `_default` only.
+//! `_default` only. This covers the mobile targets (`aarch64-linux-android`,
+//! `aarch64-apple-ios`); use the NDK / Xcode clang through the usual
+//! `CC_<target>` variables when cross-compiling. `*-linux-musl` targets
+//! build with `musl-gcc` (or a `CC_<target>` musl cross compiler) and link
+//! fully static by default. FreeBSD, NetBSD and illumos build like Linux
+//! (same defines, `-pthread`, FILE* driver included).
+//!
+//! A universal (fat) macOS binary is one build per architecture merged with
+//! `lipo`: Cargo compiles a single target at a time, and the x86_64 and
+//! arm64 slices carry different variant sets. `-arch` flags for other
+//! architectures than the target's in `CFLAGS` (as set for universal C
+//! builds) are dropped with a warning rather than passed on, which would
+//! make every object fat and hand the AVX flags to the arm64 slice.
+//!
+//! Cargo features can trim the variant set for targets whose CPUs are known
+//! in advance:
+//!
+//! - `no-avx512`: skip `_avx512`; AVX-512 CPUs dispatch to `_avx2`.
+//! - `default-only`: compile `_default` alone (baseline SSE2/AArch64 NEON
+//!   code paths are kept).
+//! - `scalar-only`: `default-only` plus `ZXC_DISABLE_SIMD`, i.e. no explicit
+//!   intrinsics at all.
+//!
+//! On wasm targets only the buffer, block, push-stream and seekable APIs are
+//! built: the FILE* driver (`zxc_driver.c`) is left out, SIMD and threads
+//! are disabled, and on `wasm32-unknown-unknown` the C code gets its libc
+//! from `wasm-shim/` plus an allocator exported by this crate. The FILE*
+//! driver is also left out on bare-metal targets, without the (default)
+//! `std` feature, and with `no-stream`; lib.rs sees the result as
+//! `cfg(zxc_stream)`.
+//!
+//! The `native` feature (or `ZXC_NATIVE_ARCH=1`) additionally compiles every
+//! unit with `-march=native`, like the CMake option of the same name. The
+//! resulting library only runs on CPUs compatible with the build machine.
+//!
+//! Release tuning knobs, mirroring the CMake options:
+//!
+//! - `lto` feature (or `ZXC_ENABLE_LTO=1`): emit ThinLTO bitcode so the C
+//!   objects take part in cross-language LTO. Requires Clang, and the Rust
+//!   side must be built with `-Clinker-plugin-lto` and a matching LLVM.
+//! - `ZXC_PGO_MODE=generate|use` with `ZXC_PGO_DIR=<dir>`: instrument the C
+//!   objects or optimise them with collected profiles. Clang reads
+//!   `<dir>/default.profdata` (merge the `.profraw` files with
+//!   `llvm-profdata`); GCC reads the `.gcda` files in `<dir>`.
+//!
+//! The `sanitize` feature (or `ZXC_SANITIZE=1`) compiles the C sources with
+//! `-fsanitize=<rust sanitizers>,undefined`, picking up the sanitizers rustc
+//! was given through `-Zsanitizer` (ASan when there are none). UBSan runs in
+//! trap mode since only the Rust-linked runtime is available.
+//!
+//! The `bindings-only` feature stops after the version and level constants:
+//! nothing is compiled or linked, and the crate is left with declarations
+//! only. The `zxc` crate's `mock-backend` uses it to run without C.
 
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Drops `-arch` flags naming another architecture than `target`'s from the
+/// `CFLAGS` variables cc reads, so that a universal setup such as
+/// `CFLAGS="-arch arm64 -arch x86_64"` builds each target's slice alone.
+fn drop_foreign_arch_flags(target: &str) {
+    let arch = match target.split('-').next().unwrap_or_default() {
+        "aarch64" => "arm64",
+        a => a,
+    };
+    let vars = [
+        format!("CFLAGS_{target}"),
+        format!("CFLAGS_{}", target.replace('-', "_")),
+        "TARGET_CFLAGS".to_string(),
+        "HOST_CFLAGS".to_string(),
+        "CFLAGS".to_string(),
+    ];
+    for var in vars {
+        let Ok(flags) = env::var(&var) else {
+            continue;
+        };
+        let (mut kept, mut dropped) = (Vec::new(), Vec::new());
+        let mut words = flags.split_ascii_whitespace();
+        while let Some(word) = words.next() {
+            match (word, words.clone().next()) {
+                ("-arch", Some(a)) => {
+                    words.next();
+                    match a == arch {
+                        true => kept.extend([word, a]),
+                        false => dropped.push(a),
+                    }
+                }
+                _ => kept.push(word),
+            }
+        }
+        if !dropped.is_empty() {
+            println!(
+                "cargo:warning=zxc-sys: ignoring -arch {} in {var}: Cargo builds one architecture per target ({arch} here); merge the targets' builds with lipo",
+                dropped.join(" -arch ")
+            );
+            // SAFETY: the build script runs on one thread, and cc reads the
+            // variable afterwards.
+            unsafe { env::set_var(&var, kept.join(" ")) };
+        }
+    }
+}
+
 /// Extract version constants from zxc_constants.h
 fn extract_version(include_dir: &Path) -> (u32, u32, u32) {
     let header_path = include_dir.join("zxc_constants.h");
@@ -104,21 +201,131 @@ fn extract_compression_levels(include_dir: &Path) -> (i32, i32, i32, i32, i32, i
     )
 }
 
+/// Extract the block size and frame layout limits from zxc_constants.h
+fn extract_format_limits(include_dir: &Path) -> (u32, u32, u32, u32, u32) {
+    let header_path = include_dir.join("zxc_constants.h");
+    let content = fs::read_to_string(&header_path).expect("Failed to read zxc_constants.h");
+
+    let mut min_log2 = None;
+    let mut max_log2 = None;
+    let mut header_size = None;
+    let mut footer_size = None;
+    let mut headroom_log2 = None;
+
+    for line in content.lines() {
+        // Parse lines like: #define ZXC_FILE_HEADER_SIZE 16
+        if let Some(rest) = line.trim().strip_prefix("#define ") {
+            let mut parts = rest.split_whitespace();
+            let name = parts.next().unwrap_or("");
+            let value: Option<u32> = parts.next().and_then(|v| v.parse().ok());
+
+            match name {
+                "ZXC_BLOCK_SIZE_MIN_LOG2" => min_log2 = value,
+                "ZXC_BLOCK_SIZE_MAX_LOG2" => max_log2 = value,
+                "ZXC_FILE_HEADER_SIZE" => header_size = value,
+                "ZXC_FILE_FOOTER_SIZE" => footer_size = value,
+                "ZXC_INPUT_SIZE_HEADROOM_LOG2" => headroom_log2 = value,
+                _ => {}
+            }
+        }
+    }
+
+    (
+        min_log2.expect("ZXC_BLOCK_SIZE_MIN_LOG2 not found"),
+        max_log2.expect("ZXC_BLOCK_SIZE_MAX_LOG2 not found"),
+        header_size.expect("ZXC_FILE_HEADER_SIZE not found"),
+        footer_size.expect("ZXC_FILE_FOOTER_SIZE not found"),
+        headroom_log2.expect("ZXC_INPUT_SIZE_HEADROOM_LOG2 not found"),
+    )
+}
+
+/// Extract the frame format version from zxc_internal.h
+fn extract_format_version(src_lib: &Path) -> u32 {
+    let header_path = src_lib.join("zxc_internal.h");
+    let content = fs::read_to_string(&header_path).expect("Failed to read zxc_internal.h");
+
+    content
+        .lines()
+        .find_map(|line| {
+            // Parse the line: #define ZXC_FILE_FORMAT_VERSION 7
+            let rest = line.trim().strip_prefix("#define ")?;
+            let mut parts = rest.split_whitespace();
+            match parts.next() {
+                Some("ZXC_FILE_FORMAT_VERSION") => parts.next()?.parse().ok(),
+                _ => None,
+            }
+        })
+        .expect("ZXC_FILE_FORMAT_VERSION not found")
+}
+
+/// Preprocessor defines, extra include directories and compiler flags
+/// applied to every translation unit.
+#[derive(Default)]
+struct CConfig {
+    defines: Vec<&'static str>,
+    includes: Vec<PathBuf>,
+    /// Headers included ahead of every translation unit.
+    force_includes: Vec<PathBuf>,
+    flags: Vec<String>,
+}
+
+/// Returns a `cc::Build` preconfigured with the include paths, optimisation
+/// level and the shared [`CConfig`].
+fn base_build(include_dir: &Path, src_lib: &Path, cfg: &CConfig) -> cc::Build {
+    let mut build = cc::Build::new();
+    for dir in &cfg.includes {
+        build.include(dir);
+    }
+    build
+        .include(include_dir)
+        .include(src_lib)
+        .include(src_lib.join("vendors"))
+        .define("ZXC_STATIC_DEFINE", None)
+        .opt_level(3)
+        .warnings(false);
+    for def in &cfg.defines {
+        build.define(def, None);
+    }
+    for flag in &cfg.flags {
+        build.flag_if_supported(flag);
+    }
+    for header in &cfg.force_includes {
+        if build.get_compiler().is_like_msvc() {
+            build.flag(format!("/FI{}", header.display()));
+        } else {
+            build.flag("-include").flag(header);
+        }
+    }
+    build
+}
+
+/// Returns true if an opt-in environment knob is set to a truthy value
+/// (`1`, `on`, `true`, `yes`; case-insensitive).
+fn env_enabled(name: &str) -> bool {
+    println!("cargo:rerun-if-env-changed={name}");
+    env::var(name)
+        .is_ok_and(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "on" | "true" | "yes"))
+}
+
 /// Compiles one FMV variant of the three per-ISA translation units
 /// (zxc_compress.c, zxc_decompress.c, zxc_huffman.c) with the given function
 /// suffix and ISA flags.
-fn compile_variant(include_dir: &Path, src_lib: &Path, suffix: &str, flags: &[&str]) {
+fn compile_variant(
+    include_dir: &Path,
+    src_lib: &Path,
+    cfg: &CConfig,
+    suffix: &str,
+    flags: &[&str],
+) {
+    // Each variant gets its own object directory: the units share file names,
+    // and per-object artefacts (e.g. GCC .gcda profiles) must not collide.
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join(format!("variant{suffix}"));
     for unit in ["zxc_compress", "zxc_decompress", "zxc_huffman"] {
-        let mut build = cc::Build::new();
+        let mut build = base_build(include_dir, src_lib, cfg);
         build
-            .include(include_dir)
-            .include(src_lib)
-            .include(src_lib.join("vendors"))
-            .define("ZXC_STATIC_DEFINE", None)
+            .out_dir(&out_dir)
             .file(src_lib.join(format!("{unit}.c")))
-            .define("ZXC_FUNCTION_SUFFIX", suffix)
-            .opt_level(3)
-            .warnings(false);
+            .define("ZXC_FUNCTION_SUFFIX", suffix);
         for flag in flags {
             build.flag_if_supported(flag);
         }
@@ -126,28 +333,53 @@ fn compile_variant(include_dir: &Path, src_lib: &Path, suffix: &str, flags: &[&s
     }
 }
 
-fn main() {
-    // Path to ZXC source files
-    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
-
-    // We use mirrored symlinks under zxc/ to preserve relative include paths
-    // expected by the C source code (../../include/...).
-    // During `cargo publish`, these symlinks are followed and real files are packaged.
-    let src_lib = manifest_dir.join("zxc/src/lib");
-    let include_dir = manifest_dir.join("zxc/include");
+/// Locates the C sources as `(src/lib, include)`.
+///
+/// The C translation units include their public headers through relative
+/// paths (`../../include/...`), so whatever root is chosen must preserve the
+/// upstream `src/lib` + `include` layout. Candidates, in order:
+///
+/// 1. `ZXC_SOURCE_DIR`: explicit override for out-of-tree and packaging builds.
+/// 2. `<manifest>/zxc`: the copy vendored by `vendor.sh` and shipped in the
+///    published crate.
+/// 3. `<manifest>/../../..`: the repository root, for in-tree development.
+///
+/// No symlinks are involved and no path is canonicalized, so the result only
+/// depends on the manifest directory and the environment.
+fn locate_sources(manifest_dir: &Path) -> (PathBuf, PathBuf) {
+    println!("cargo:rerun-if-env-changed=ZXC_SOURCE_DIR");
+    let has_layout = |root: &Path| root.join("include/zxc_constants.h").is_file();
 
-    // Fallback for local development if symlinks are broken or missing
-    let (src_lib, include_dir) = if src_lib.exists() && include_dir.exists() {
-        (src_lib, include_dir)
+    let root = if let Some(dir) = env::var_os("ZXC_SOURCE_DIR") {
+        let dir = PathBuf::from(dir);
+        assert!(
+            has_layout(&dir),
+            "ZXC_SOURCE_DIR={:?} does not contain include/zxc_constants.h",
+            dir
+        );
+        dir
     } else {
-        // Try direct path from workspace root
-        let zxc_root = manifest_dir
-            .join("../../..")
-            .canonicalize()
-            .expect("Failed to find ZXC root directory");
-        (zxc_root.join("src/lib"), zxc_root.join("include"))
+        let vendored = manifest_dir.join("zxc");
+        let in_tree = manifest_dir.join("..").join("..").join("..");
+        if has_layout(&vendored) {
+            vendored
+        } else if has_layout(&in_tree) {
+            in_tree
+        } else {
+            panic!(
+                "ZXC C sources not found: run zxc-sys/vendor.sh, build from a \
+                 repository checkout, or set ZXC_SOURCE_DIR"
+            );
+        }
     };
 
+    (root.join("src").join("lib"), root.join("include"))
+}
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let (src_lib, include_dir) = locate_sources(&manifest_dir);
+
     // Verify the headers exist: even the `system` feature needs them to
     // extract the version/level constants consumed by lib.rs's env!() calls.
     assert!(
@@ -173,13 +405,46 @@ fn main() {
     println!("cargo:rustc-env=ZXC_LEVEL_DENSITY={}", density);
     println!("cargo:rustc-env=ZXC_LEVEL_ULTRA={}", ultra);
 
+    // Extract format limits from header and make them available to lib.rs
+    let (min_log2, max_log2, header_size, footer_size, headroom_log2) =
+        extract_format_limits(&include_dir);
+    println!("cargo:rustc-env=ZXC_BLOCK_SIZE_MIN_LOG2={}", min_log2);
+    println!("cargo:rustc-env=ZXC_BLOCK_SIZE_MAX_LOG2={}", max_log2);
+    println!("cargo:rustc-env=ZXC_FILE_HEADER_SIZE={}", header_size);
+    println!("cargo:rustc-env=ZXC_FILE_FOOTER_SIZE={}", footer_size);
+    println!(
+        "cargo:rustc-env=ZXC_INPUT_SIZE_HEADROOM_LOG2={}",
+        headroom_log2
+    );
+
+    // Extract the frame format version the C decoder accepts
+    let format_version = extract_format_version(&src_lib);
+    println!("cargo:rustc-env=ZXC_FILE_FORMAT_VERSION={}", format_version);
+
+    // `zxc_stream`: the FILE*-based streaming API is linked in.
+    println!("cargo::rustc-check-cfg=cfg(zxc_stream)");
+
+    // Declarations only: a Rust stand-in provides the implementations.
+    if env::var_os("CARGO_FEATURE_BINDINGS_ONLY").is_some() {
+        return;
+    }
+
     // Use the system library instead of compiling from source
     if env::var("CARGO_FEATURE_SYSTEM").is_ok() {
+        // A system libzxc is a full build, driver included.
+        if env::var_os("CARGO_FEATURE_STD").is_some() {
+            println!("cargo:rustc-cfg=zxc_stream");
+        }
         println!("cargo:rerun-if-env-changed=ZXC_LIB_DIR");
         if let Ok(dir) = env::var("ZXC_LIB_DIR") {
             println!("cargo:rustc-link-search=native={}", dir);
         }
         println!("cargo:rustc-link-lib=zxc");
+        if env::var_os("CARGO_FEATURE_ZEROIZE").is_some() {
+            println!(
+                "cargo:warning=zxc-sys: zeroize does not reach a system libzxc, only the Rust side"
+            );
+        }
         return;
     }
 
@@ -193,25 +458,226 @@ fn main() {
     let is_x86_64 = target.contains("x86_64");
     let is_arm32 = (target.starts_with("arm") && !target.starts_with("arm64"))
         || target.starts_with("thumbv7");
+    let is_android = target.contains("android");
+    let is_apple = target.contains("apple");
+    if is_apple {
+        drop_foreign_arch_flags(&target);
+    }
+    let is_wasm = env::var("CARGO_CFG_TARGET_FAMILY")
+        .unwrap_or_default()
+        .split(',')
+        .any(|f| f == "wasm");
+    let is_bare_metal = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("none");
+    let is_unix = env::var("CARGO_CFG_TARGET_FAMILY")
+        .unwrap_or_default()
+        .split(',')
+        .any(|f| f == "unix");
+    // wasm32-wasip1-threads is the only wasm target with pthreads.
+    let no_threads = is_bare_metal || (is_wasm && !target.contains("threads"));
+    // Bionic (Android) and libSystem (Apple) ship pthreads inside libc: there
+    // is no separate libpthread to link, and `-pthread` is at best ignored.
+    // FreeBSD, NetBSD and illumos still ship one (a filter on libc for
+    // illumos), so they link it like glibc.
+    let needs_pthread =
+        !target.contains("windows") && !is_android && !is_apple && !is_wasm && !is_bare_metal;
+    // musl also folds pthreads into libc.a, and Rust's self-contained musl
+    // runtime (used for static binaries) ships no libpthread.a to link.
+    let is_musl = env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("musl");
+
+    // =========================================================================
+    // Variant selection features
+    // =========================================================================
+    // The same defines go to every unit: ZXC_DISABLE_SIMD changes code paths
+    // in the shared headers, and the dispatcher must not reference variants
+    // that are not compiled.
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{name}")).is_some();
+    // wasm has no runtime dispatch: mirror the Emscripten overrides in
+    // CMakeLists.txt (scalar, single variant).
+    let scalar_only = is_wasm || feature("SCALAR_ONLY");
+    // Bare-metal targets have no way to probe the CPU at runtime.
+    let default_only = scalar_only || is_bare_metal || feature("DEFAULT_ONLY");
+    let no_avx512 = feature("NO_AVX512");
+
+    // The FILE* driver needs stdio and threads; it backs the `std` feature.
+    let no_stream = is_wasm || is_bare_metal || !feature("STD") || feature("NO_STREAM");
+    if !no_stream {
+        println!("cargo:rustc-cfg=zxc_stream");
+    }
+
+    let mut cfg = CConfig::default();
+    if scalar_only {
+        cfg.defines.push("ZXC_DISABLE_SIMD");
+    }
+    if default_only {
+        cfg.defines.push("ZXC_ONLY_DEFAULT");
+    }
+    if no_avx512 {
+        cfg.defines.push("ZXC_DISABLE_AVX512");
+    }
+    if no_threads {
+        cfg.defines.push("ZXC_NO_THREADS");
+    }
+    if is_wasm {
+        // wasm32-unknown-unknown has no libc at all: wasm-shim/ provides the
+        // few headers the buffer API needs, backed by src/wasm_shim.rs.
+        if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("unknown") {
+            cfg.includes.push(manifest_dir.join("wasm-shim"));
+        }
+    }
+
+    // =========================================================================
+    // Native CPU tuning (opt-in)
+    // =========================================================================
+    // Layered on top of the FMV build: the variants keep their own ISA flags,
+    // and the dispatcher still picks one at runtime.
+    let is_msvc = target.contains("msvc");
+    if feature("NATIVE") || env_enabled("ZXC_NATIVE_ARCH") {
+        let host = env::var("HOST").unwrap_or_default();
+        if is_msvc {
+            println!("cargo:warning=zxc-sys: native build ignored (MSVC has no -march=native)");
+        } else if host != target {
+            println!(
+                "cargo:warning=zxc-sys: native build ignored when cross-compiling ({host} -> {target})"
+            );
+        } else {
+            cfg.flags.push("-march=native".to_string());
+        }
+    }
+
+    // =========================================================================
+    // LTO / PGO (opt-in)
+    // =========================================================================
+    let compiler = cc::Build::new().get_compiler();
+    if feature("LTO") || env_enabled("ZXC_ENABLE_LTO") {
+        // Only LLVM bitcode can be consumed by rustc's linker plugin; GCC or
+        // MSVC LTO objects would silently fall back to a regular link at best.
+        if compiler.is_like_clang() {
+            cfg.flags.push("-flto=thin".to_string());
+        } else {
+            println!("cargo:warning=zxc-sys: LTO ignored (cross-language LTO requires Clang)");
+        }
+    }
+
+    println!("cargo:rerun-if-env-changed=ZXC_PGO_MODE");
+    println!("cargo:rerun-if-env-changed=ZXC_PGO_DIR");
+    let pgo_mode = env::var("ZXC_PGO_MODE")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !pgo_mode.is_empty() && pgo_mode != "off" {
+        let pgo_dir =
+            PathBuf::from(env::var_os("ZXC_PGO_DIR").expect("ZXC_PGO_MODE requires ZXC_PGO_DIR"));
+        let pgo_dir = pgo_dir.display();
+        let is_clang = compiler.is_like_clang();
+        match pgo_mode.as_str() {
+            _ if compiler.is_like_msvc() => {
+                println!("cargo:warning=zxc-sys: PGO ignored (not supported with MSVC)");
+            }
+            "generate" if is_clang => {
+                cfg.flags.push(format!(
+                    "-fprofile-instr-generate={pgo_dir}/default_%m.profraw"
+                ));
+            }
+            "generate" => {
+                cfg.flags.push(format!("-fprofile-generate={pgo_dir}"));
+                println!("cargo:rustc-link-lib=gcov");
+            }
+            "use" if is_clang => {
+                let profdata = format!("{pgo_dir}/default.profdata");
+                assert!(
+                    Path::new(&profdata).is_file(),
+                    "PGO profile not found: {profdata} (run with ZXC_PGO_MODE=generate first)"
+                );
+                println!("cargo:rerun-if-changed={profdata}");
+                cfg.flags.push(format!("-fprofile-instr-use={profdata}"));
+            }
+            "use" => {
+                println!("cargo:rerun-if-changed={pgo_dir}");
+                cfg.flags.push(format!("-fprofile-use={pgo_dir}"));
+                cfg.flags.push("-fprofile-correction".to_string());
+            }
+            other => panic!("ZXC_PGO_MODE must be off, generate or use (got {other:?})"),
+        }
+    }
+
+    // =========================================================================
+    // Sanitizers (opt-in)
+    // =========================================================================
+    // The C objects get the same sanitizers as the Rust code. The runtime is
+    // linked by rustc (`-Zsanitizer=...`), so UBSan runs in trap mode: it has
+    // no runtime of its own to link and aborts on the first report.
+    if feature("SANITIZE") || env_enabled("ZXC_SANITIZE") {
+        if compiler.is_like_msvc() {
+            println!("cargo:warning=zxc-sys: sanitize ignored (not supported with MSVC)");
+        } else {
+            let rust_sanitizers = env::var("CARGO_CFG_SANITIZE").unwrap_or_default();
+            let mut sanitizers: Vec<&str> = rust_sanitizers
+                .split(',')
+                .filter(|s| matches!(*s, "address" | "hwaddress" | "leak" | "memory" | "thread"))
+                .collect();
+            if sanitizers.is_empty() {
+                println!(
+                    "cargo:warning=zxc-sys: no Rust sanitizer enabled, instrumenting the C code \
+                     for ASan; build with -Zsanitizer=address or link the runtime yourself"
+                );
+                sanitizers.push("address");
+            }
+            sanitizers.push("undefined");
+            cfg.flags
+                .push(format!("-fsanitize={}", sanitizers.join(",")));
+            cfg.flags
+                .push("-fsanitize-undefined-trap-on-error".to_string());
+            cfg.flags.push("-fno-omit-frame-pointer".to_string());
+        }
+    }
+
+    // =========================================================================
+    // Zeroization (opt-in)
+    // =========================================================================
+    // Every heap allocation of the C library goes through the ZXC_* allocator
+    // hooks of zxc_deps.h. Route them to src/wipe.rs, which zeroes each block
+    // before handing it back, so no plaintext outlives a context or stream.
+    if feature("ZEROIZE") {
+        cfg.defines.extend([
+            "ZXC_MALLOC(size)=zxc_wipe_malloc(size)",
+            "ZXC_CALLOC(nmemb,size)=zxc_wipe_calloc(nmemb,size)",
+            "ZXC_REALLOC(ptr,size)=zxc_wipe_realloc(ptr,size)",
+            "ZXC_FREE(ptr)=zxc_wipe_free(ptr)",
+            "ZXC_ALIGNED_MALLOC(size,alignment)=zxc_wipe_aligned_malloc(size,alignment)",
+            "ZXC_ALIGNED_FREE(ptr)=zxc_wipe_free(ptr)",
+        ]);
+        let header = manifest_dir.join("zeroize").join("zxc_wipe.h");
+        println!("cargo:rerun-if-changed={}", header.display());
+        cfg.force_includes.push(header);
+    }
 
     // =========================================================================
     // Core library files (common to all architectures)
     // =========================================================================
-    let mut core_build = cc::Build::new();
+    let mut core_build = base_build(&include_dir, &src_lib, &cfg);
     core_build
-        .include(&include_dir)
-        .include(&src_lib)
-        .include(src_lib.join("vendors"))
-        .define("ZXC_STATIC_DEFINE", None)
         .file(src_lib.join("zxc_common.c"))
         .file(src_lib.join("zxc_dict.c"))
         .file(src_lib.join("zxc_dispatch.c"))
-        .file(src_lib.join("zxc_driver.c"))
         .file(src_lib.join("zxc_seekable.c"))
-        .file(src_lib.join("zxc_pstream.c"))
-        .opt_level(3)
-        .warnings(false)
-        .flag_if_supported("-pthread");
+        .file(src_lib.join("zxc_pstream.c"));
+    // The FILE* driver needs pthreads and stdio (excluded for Emscripten in
+    // CMakeLists.txt as well).
+    if !no_stream {
+        core_build.file(src_lib.join("zxc_driver.c"));
+        // The driver seeks with fseeko/ftello and reads with pread. Mirror the
+        // CMake defines so off_t is 64-bit on 32-bit glibc; on the BSDs and
+        // illumos they are harmless (off_t is already 64-bit or the large-file
+        // interfaces are selected the same way), and the default feature set
+        // exposes the POSIX calls the driver needs without extra macros.
+        if is_unix && !is_apple {
+            core_build
+                .define("_FILE_OFFSET_BITS", "64")
+                .define("_LARGEFILE_SOURCE", None);
+        }
+    }
+    if needs_pthread {
+        core_build.flag_if_supported("-pthread");
+    }
 
     core_build.compile("zxc_core");
 
@@ -220,72 +686,76 @@ fn main() {
     // =========================================================================
 
     // --- Default variant (baseline, always compiled) ---
-    compile_variant(&include_dir, &src_lib, "_default", &[]);
+    compile_variant(&include_dir, &src_lib, &cfg, "_default", &[]);
 
     // --- Architecture-specific variants ---
     // The per-variant flags mirror zxc_add_variant() in CMakeLists.txt;
     // keep both in sync. MSVC ignores GCC-style -m flags silently, so it
     // needs its own /arch spellings plus the __BMI*__/__LZCNT__ macros the
     // sources test (cl.exe never defines them itself).
-    let is_msvc = target.contains("msvc");
-    if is_x86_64 && is_msvc {
+    if default_only {
+        // Only `_default`: nothing else to build.
+    } else if is_x86_64 && is_msvc {
         compile_variant(
             &include_dir,
             &src_lib,
+            &cfg,
             "_avx2",
             &["/arch:AVX2", "/D__BMI__", "/D__BMI2__", "/D__LZCNT__"],
         );
-        compile_variant(
-            &include_dir,
-            &src_lib,
-            "_avx512",
-            &["/arch:AVX512", "/D__BMI__", "/D__BMI2__", "/D__LZCNT__"],
-        );
+        if !no_avx512 {
+            compile_variant(
+                &include_dir,
+                &src_lib,
+                &cfg,
+                "_avx512",
+                &["/arch:AVX512", "/D__BMI__", "/D__BMI2__", "/D__LZCNT__"],
+            );
+        }
     } else if is_x86_64 {
         compile_variant(
             &include_dir,
             &src_lib,
+            &cfg,
             "_avx2",
             &["-mavx2", "-mbmi", "-mbmi2", "-mlzcnt"],
         );
-        compile_variant(
-            &include_dir,
-            &src_lib,
-            "_avx512",
-            &[
-                "-mavx512f",
-                "-mavx512bw",
-                "-mavx512vbmi",
-                "-mavx512vbmi2",
-                "-mbmi",
-                "-mbmi2",
-                "-mlzcnt",
-            ],
-        );
+        if !no_avx512 {
+            compile_variant(
+                &include_dir,
+                &src_lib,
+                &cfg,
+                "_avx512",
+                &[
+                    "-mavx512f",
+                    "-mavx512bw",
+                    "-mavx512vbmi",
+                    "-mavx512vbmi2",
+                    "-mbmi",
+                    "-mbmi2",
+                    "-mlzcnt",
+                ],
+            );
+        }
     } else if is_arm32 {
-        compile_variant(
-            &include_dir,
-            &src_lib,
-            "_neon32",
-            &["-march=armv7-a", "-mfpu=neon"],
-        );
+        // Apple's 32-bit ARM targets (armv7s, armv7k) always have NEON and
+        // must keep their own -arch, so only the FPU is selected there.
+        let neon_flags: &[&str] = if is_apple {
+            &["-mfpu=neon"]
+        } else {
+            &["-march=armv7-a", "-mfpu=neon"]
+        };
+        compile_variant(&include_dir, &src_lib, &cfg, "_neon32", neon_flags);
     }
 
     // PivCo tables: const data used only by the variant Huffman decoders, so it
     // must link LAST (zxc_core doesn't reference it, or the linker drops it).
-    let mut pivco_tables = cc::Build::new();
-    pivco_tables
-        .include(&include_dir)
-        .include(&src_lib)
-        .include(src_lib.join("vendors"))
-        .define("ZXC_STATIC_DEFINE", None)
-        .file(src_lib.join("zxc_pivco_tables.c"))
-        .opt_level(3)
-        .warnings(false);
+    let mut pivco_tables = base_build(&include_dir, &src_lib, &cfg);
+    pivco_tables.file(src_lib.join("zxc_pivco_tables.c"));
     pivco_tables.compile("zxc_pivco_tables");
 
     // Threading support (not needed on Windows, which uses kernel32)
-    if !target.contains("windows") {
+    if needs_pthread && !is_musl {
         println!("cargo:rustc-link-lib=pthread");
     }
 
@@ -20,12 +20,29 @@
 //!     // ... allocate buffer and compress
 //! }
 //! ```
+//!
+//! The crate is `no_std`. The `FILE*`-based streaming functions are only
+//! declared when the C driver is linked in: with the default `std` feature,
+//! without `no-stream`, and on targets with stdio and threads (not wasm or
+//! bare metal).
 
+#![cfg_attr(not(test), no_std)]
 #![allow(non_camel_case_types)]
 #![allow(non_upper_case_globals)]
 
-use std::ffi::c_int;
-use std::os::raw::{c_char, c_void};
+use core::ffi::{c_char, c_int, c_void};
+
+#[cfg(any(
+    all(target_family = "wasm", target_os = "unknown"),
+    all(feature = "zeroize", not(feature = "bindings-only"))
+))]
+extern crate alloc;
+
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+mod wasm_shim;
+
+#[cfg(all(feature = "zeroize", not(feature = "bindings-only")))]
+mod wipe;
 
 // =============================================================================
 // ZXC Version Constants
@@ -100,6 +117,41 @@ pub const ZXC_LEVEL_DENSITY: i32 = parse_i32(env!("ZXC_LEVEL_DENSITY"));
 /// Slowest compression, best ratio (level 7 / ULTRA).
 pub const ZXC_LEVEL_ULTRA: i32 = parse_i32(env!("ZXC_LEVEL_ULTRA"));
 
+// =============================================================================
+// Format Limits
+// =============================================================================
+
+// Format limits - automatically extracted from zxc_constants.h by build.rs
+/// log2 of [`ZXC_BLOCK_SIZE_MIN`]
+pub const ZXC_BLOCK_SIZE_MIN_LOG2: u32 = parse_version(env!("ZXC_BLOCK_SIZE_MIN_LOG2"));
+
+/// log2 of [`ZXC_BLOCK_SIZE_MAX`]
+pub const ZXC_BLOCK_SIZE_MAX_LOG2: u32 = parse_version(env!("ZXC_BLOCK_SIZE_MAX_LOG2"));
+
+/// Minimum block size in bytes (4 KiB)
+pub const ZXC_BLOCK_SIZE_MIN: u32 = 1 << ZXC_BLOCK_SIZE_MIN_LOG2;
+
+/// Maximum block size in bytes (2 MiB)
+pub const ZXC_BLOCK_SIZE_MAX: u32 = 1 << ZXC_BLOCK_SIZE_MAX_LOG2;
+
+/// File header size in bytes: magic, version, block size code, flags,
+/// reserved bytes and header check value
+pub const ZXC_FILE_HEADER_SIZE: u32 = parse_version(env!("ZXC_FILE_HEADER_SIZE"));
+
+/// File footer size in bytes: original size and global checksum
+pub const ZXC_FILE_FOOTER_SIZE: u32 = parse_version(env!("ZXC_FILE_FOOTER_SIZE"));
+
+/// log2 of the share of `SIZE_MAX` kept free of input by
+/// `zxc_compress_bound`
+pub const ZXC_INPUT_SIZE_HEADROOM_LOG2: u32 = parse_version(env!("ZXC_INPUT_SIZE_HEADROOM_LOG2"));
+
+/// Largest input size `zxc_compress_bound` accepts; it returns 0 above it
+pub const ZXC_INPUT_SIZE_MAX: usize = usize::MAX - (usize::MAX >> ZXC_INPUT_SIZE_HEADROOM_LOG2);
+
+/// Frame format version the library writes, and the only one its decoder
+/// accepts (extracted from zxc_internal.h by build.rs)
+pub const ZXC_FILE_FORMAT_VERSION: u32 = parse_version(env!("ZXC_FILE_FORMAT_VERSION"));
+
 // =============================================================================
 // Error Codes
 // =============================================================================
@@ -161,6 +213,25 @@ pub const ZXC_ERROR_DICT_TOO_LARGE: i32 = -17;
 /// Compression level out of range, or not supported by this context's workspace
 pub const ZXC_ERROR_BAD_LEVEL: i32 = -18;
 
+// =============================================================================
+// SIMD Variants (values of zxc_simd_variant())
+// =============================================================================
+
+/// Restores runtime detection (argument of `zxc_simd_force()` only)
+pub const ZXC_SIMD_AUTO: i32 = -1;
+
+/// Baseline variant (scalar; SSE2 on x86-64, NEON on AArch64)
+pub const ZXC_SIMD_DEFAULT: i32 = 0;
+
+/// 32-bit ARM NEON variant
+pub const ZXC_SIMD_NEON: i32 = 1;
+
+/// x86-64 AVX2 variant
+pub const ZXC_SIMD_AVX2: i32 = 2;
+
+/// x86-64 AVX-512 (F+BW+VBMI2) variant
+pub const ZXC_SIMD_AVX512: i32 = 3;
+
 // =============================================================================
 // Dictionary Constants
 // =============================================================================
@@ -175,10 +246,85 @@ pub const ZXC_DICT_HEADER_SIZE: usize = 16;
 /// (packed 4-bit code lengths for 256 symbols).
 pub const ZXC_HUF_TABLE_SIZE: usize = 128;
 
+// =============================================================================
+// Threading
+// =============================================================================
+
+/// Worker thread cap of the file API: `zxc_stream_compress` and
+/// `zxc_stream_decompress` clamp larger `n_threads` to it.
+pub const ZXC_MAX_THREADS: usize = 512;
+
 // =============================================================================
 // Options Structs (mirroring C API)
 // =============================================================================
 
+/// Progress callback (mirrors `zxc_progress_callback_t` from `zxc_opts.h`).
+///
+/// Invoked from the writer thread after each block with the input bytes
+/// processed so far, the total (0 if unknown) and the options' `user_data`.
+pub type zxc_progress_callback_t =
+    Option<unsafe extern "C" fn(bytes_processed: u64, bytes_total: u64, user_data: *const c_void)>;
+
+/// Statistics of one block (mirrors `zxc_block_stats_t` from `zxc_opts.h`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct zxc_block_stats_t {
+    /// Block number in file order, from 0.
+    pub index: u64,
+    /// Bytes consumed: plain data when compressing, the encoded block when
+    /// decompressing.
+    pub in_size: u64,
+    /// Bytes produced.
+    pub out_size: u64,
+    /// Time the worker spent on the block, in nanoseconds.
+    pub time_ns: u64,
+    /// Worker thread that processed the block, from 0.
+    pub worker_id: c_int,
+}
+
+/// Per-block statistics callback (mirrors `zxc_block_callback_t`).
+///
+/// Invoked from the writer thread once per block, in file order, with the
+/// options' `user_data`.
+pub type zxc_block_callback_t =
+    Option<unsafe extern "C" fn(stats: *const zxc_block_stats_t, user_data: *const c_void)>;
+
+/// Stage timings of one `FILE*` streaming call (mirrors `zxc_stream_stats_t`
+/// from `zxc_opts.h`), filled in through the `stats` pointer of
+/// [`zxc_stream_ext_t`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct zxc_stream_stats_t {
+    /// Duration of the whole call, in nanoseconds.
+    pub wall_ns: u64,
+    /// Time the calling thread spent reading blocks from the input.
+    pub read_ns: u64,
+    /// Time the workers spent on blocks, summed over the workers.
+    pub compute_ns: u64,
+    /// Time the writer thread spent writing blocks to the output.
+    pub write_ns: u64,
+    /// Worker threads that ran.
+    pub n_workers: c_int,
+    /// Caller array receiving each worker's busy time (NULL = skip).
+    pub worker_ns: *mut u64,
+    /// Length of `worker_ns`; further workers are not reported.
+    pub worker_ns_len: c_int,
+}
+
+impl Default for zxc_stream_stats_t {
+    fn default() -> Self {
+        Self {
+            wall_ns: 0,
+            read_ns: 0,
+            compute_ns: 0,
+            write_ns: 0,
+            n_workers: 0,
+            worker_ns: core::ptr::null_mut(),
+            worker_ns_len: 0,
+        }
+    }
+}
+
 /// Compression options (mirrors `zxc_compress_opts_t` from C API).
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -200,9 +346,9 @@ pub struct zxc_compress_opts_t {
     /// Shared literal Huffman table: 128-byte packed code-lengths header
     /// (NULL = none; ignored without dict).
     pub dict_huf: *const c_void,
-    /// Progress callback (NULL to disable).
-    pub progress_cb: *const c_void,
-    /// User context pointer passed to progress_cb.
+    /// Progress callback (`None` to disable).
+    pub progress_cb: zxc_progress_callback_t,
+    /// User context pointer passed to the callbacks.
     pub user_data: *mut c_void,
 }
 
@@ -214,11 +360,11 @@ impl Default for zxc_compress_opts_t {
             block_size: 0,
             checksum_enabled: 0,
             seekable: 0,
-            dict: std::ptr::null(),
+            dict: core::ptr::null(),
             dict_size: 0,
-            dict_huf: std::ptr::null(),
-            progress_cb: std::ptr::null(),
-            user_data: std::ptr::null_mut(),
+            dict_huf: core::ptr::null(),
+            progress_cb: None,
+            user_data: core::ptr::null_mut(),
         }
     }
 }
@@ -238,9 +384,9 @@ pub struct zxc_decompress_opts_t {
     /// Shared literal Huffman table: 128-byte packed code-lengths header
     /// (NULL = none; ignored without dict).
     pub dict_huf: *const c_void,
-    /// Progress callback (NULL to disable).
-    pub progress_cb: *const c_void,
-    /// User context pointer passed to progress_cb.
+    /// Progress callback (`None` to disable).
+    pub progress_cb: zxc_progress_callback_t,
+    /// User context pointer passed to the callbacks.
     pub user_data: *mut c_void,
 }
 
@@ -249,11 +395,33 @@ impl Default for zxc_decompress_opts_t {
         Self {
             n_threads: 0,
             checksum_enabled: 0,
-            dict: std::ptr::null(),
+            dict: core::ptr::null(),
             dict_size: 0,
-            dict_huf: std::ptr::null(),
-            progress_cb: std::ptr::null(),
-            user_data: std::ptr::null_mut(),
+            dict_huf: core::ptr::null(),
+            progress_cb: None,
+            user_data: core::ptr::null_mut(),
+        }
+    }
+}
+
+/// Extensions of the `FILE*` streaming API (mirrors `zxc_stream_ext_t` from
+/// `zxc_opts.h`), passed to [`zxc_stream_compress_ex`] and
+/// [`zxc_stream_decompress_ex`]. The callbacks receive the options'
+/// `user_data`.
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct zxc_stream_ext_t {
+    /// Per-block statistics callback (`None` to disable).
+    pub block_cb: zxc_block_callback_t,
+    /// Stage timings, filled in on success (NULL to disable).
+    pub stats: *mut zxc_stream_stats_t,
+}
+
+impl Default for zxc_stream_ext_t {
+    fn default() -> Self {
+        Self {
+            block_cb: None,
+            stats: core::ptr::null_mut(),
         }
     }
 }
@@ -292,6 +460,19 @@ unsafe extern "C" {
     ///
     /// The returned pointer is a compile-time constant and must not be freed.
     pub fn zxc_version_string() -> *const c_char;
+
+    /// Returns the variant the runtime dispatcher runs on this CPU (one of
+    /// the `ZXC_SIMD_*` constants).
+    pub fn zxc_simd_variant() -> c_int;
+
+    /// Pins the dispatcher to a `ZXC_SIMD_*` variant, or restores detection
+    /// with `ZXC_SIMD_AUTO`.
+    ///
+    /// A SIMD variant is accepted only when this CPU and build would run it
+    /// or a superset of it; the baseline always is. Returns the variant in
+    /// effect afterwards. Process-wide: calls already running finish on the
+    /// previous variant.
+    pub fn zxc_simd_force(variant: c_int) -> c_int;
 }
 
 // =============================================================================
@@ -388,7 +569,7 @@ unsafe extern "C" {
     ///
     /// A constant string such as "ZXC_OK" or "ZXC_ERROR_MEMORY".
     /// Returns "ZXC_UNKNOWN_ERROR" for unrecognized codes.
-    pub fn zxc_error_name(code: c_int) -> *const std::os::raw::c_char;
+    pub fn zxc_error_name(code: c_int) -> *const c_char;
 
     /// Returns `sizeof(zxc_compress_opts_t)` as compiled into the C library.
     ///
@@ -716,6 +897,9 @@ unsafe extern "C" {
 // Streaming API (FILE-based)
 // =============================================================================
 
+// Only declared when build.rs links the multi-threaded FILE* driver (see
+// the crate docs).
+#[cfg(zxc_stream)]
 unsafe extern "C" {
     /// Compresses data from an input stream to an output stream.
     ///
@@ -770,6 +954,32 @@ unsafe extern "C" {
         opts: *const zxc_decompress_opts_t,
     ) -> i64;
 
+    /// Like [`zxc_stream_compress`], with the extensions in `ext` (NULL
+    /// disables them).
+    ///
+    /// # Safety
+    ///
+    /// As [`zxc_stream_compress`]; `ext` must be NULL or valid for the call.
+    pub fn zxc_stream_compress_ex(
+        f_in: *mut libc::FILE,
+        f_out: *mut libc::FILE,
+        opts: *const zxc_compress_opts_t,
+        ext: *const zxc_stream_ext_t,
+    ) -> i64;
+
+    /// Like [`zxc_stream_decompress`], with the extensions in `ext` (NULL
+    /// disables them).
+    ///
+    /// # Safety
+    ///
+    /// As [`zxc_stream_decompress`]; `ext` must be NULL or valid for the call.
+    pub fn zxc_stream_decompress_ex(
+        f_in: *mut libc::FILE,
+        f_out: *mut libc::FILE,
+        opts: *const zxc_decompress_opts_t,
+        ext: *const zxc_stream_ext_t,
+    ) -> i64;
+
     /// Returns the decompressed size stored in a ZXC compressed file.
     ///
     /// Reads the file footer to extract the original size without decompressing.
@@ -837,6 +1047,7 @@ unsafe extern "C" {
     ///
     /// # Safety
     /// - `f` must be a valid FILE* opened in "rb" mode.
+    #[cfg(zxc_stream)]
     pub fn zxc_seekable_open_file(f: *mut libc::FILE) -> *mut zxc_seekable;
 
     /// Opens a seekable archive through a user-supplied [`zxc_reader_t`].
@@ -1072,7 +1283,7 @@ unsafe extern "C" {
 // Tests
 // =============================================================================
 
-#[cfg(test)]
+#[cfg(all(test, not(feature = "bindings-only")))]
 mod tests {
     /// The options structs above are hand-mirrored from zxc_opts.h. A C-side
     /// field change that is not replicated here silently shifts every later
@@ -1095,6 +1306,148 @@ mod tests {
     }
 
     use super::*;
+    use std::mem::{align_of, offset_of, size_of};
+
+    /// Field offsets of a C struct laid out with natural alignment from the
+    /// given `(size, align)` fields, plus its padded total size.
+    fn c_layout(fields: &[(usize, usize)]) -> (Vec<usize>, usize) {
+        let mut offsets = Vec::with_capacity(fields.len());
+        let mut pos = 0usize;
+        let mut max_align = 1;
+        for &(size, align) in fields {
+            pos = pos.next_multiple_of(align);
+            offsets.push(pos);
+            pos += size;
+            max_align = max_align.max(align);
+        }
+        (offsets, pos.next_multiple_of(max_align))
+    }
+
+    /// Field-by-field check of the mirrored structs against the declaration
+    /// order and C types in the headers: a reordered or retyped field can keep
+    /// the total size (and thus pass the check above) while still shifting data.
+    #[test]
+    fn struct_field_offsets_match_headers() {
+        let int = (size_of::<c_int>(), align_of::<c_int>());
+        let size_t = (size_of::<usize>(), align_of::<usize>());
+        let ptr = (size_of::<*const c_void>(), align_of::<*const c_void>());
+        let u64_ = (size_of::<u64>(), align_of::<u64>());
+
+        let (off, size) = c_layout(&[int, int, size_t, int, int, ptr, size_t, ptr, ptr, ptr]);
+        assert_eq!(
+            [
+                offset_of!(zxc_compress_opts_t, n_threads),
+                offset_of!(zxc_compress_opts_t, level),
+                offset_of!(zxc_compress_opts_t, block_size),
+                offset_of!(zxc_compress_opts_t, checksum_enabled),
+                offset_of!(zxc_compress_opts_t, seekable),
+                offset_of!(zxc_compress_opts_t, dict),
+                offset_of!(zxc_compress_opts_t, dict_size),
+                offset_of!(zxc_compress_opts_t, dict_huf),
+                offset_of!(zxc_compress_opts_t, progress_cb),
+                offset_of!(zxc_compress_opts_t, user_data),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_compress_opts_t>(), size);
+
+        let (off, size) = c_layout(&[int, int, ptr, size_t, ptr, ptr, ptr]);
+        assert_eq!(
+            [
+                offset_of!(zxc_decompress_opts_t, n_threads),
+                offset_of!(zxc_decompress_opts_t, checksum_enabled),
+                offset_of!(zxc_decompress_opts_t, dict),
+                offset_of!(zxc_decompress_opts_t, dict_size),
+                offset_of!(zxc_decompress_opts_t, dict_huf),
+                offset_of!(zxc_decompress_opts_t, progress_cb),
+                offset_of!(zxc_decompress_opts_t, user_data),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_decompress_opts_t>(), size);
+
+        let (off, size) = c_layout(&[ptr, size_t, size_t]);
+        assert_eq!(
+            [
+                offset_of!(zxc_inbuf_t, src),
+                offset_of!(zxc_inbuf_t, size),
+                offset_of!(zxc_inbuf_t, pos),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_inbuf_t>(), size);
+        assert_eq!(
+            [
+                offset_of!(zxc_outbuf_t, dst),
+                offset_of!(zxc_outbuf_t, size),
+                offset_of!(zxc_outbuf_t, pos),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_outbuf_t>(), size);
+
+        let (off, size) = c_layout(&[ptr, ptr, u64_]);
+        assert_eq!(
+            [
+                offset_of!(zxc_reader_t, read_at),
+                offset_of!(zxc_reader_t, ctx),
+                offset_of!(zxc_reader_t, size),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_reader_t>(), size);
+
+        let (off, size) = c_layout(&[u64_, u64_, u64_, u64_, int]);
+        assert_eq!(
+            [
+                offset_of!(zxc_block_stats_t, index),
+                offset_of!(zxc_block_stats_t, in_size),
+                offset_of!(zxc_block_stats_t, out_size),
+                offset_of!(zxc_block_stats_t, time_ns),
+                offset_of!(zxc_block_stats_t, worker_id),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_block_stats_t>(), size);
+
+        let (off, size) = c_layout(&[u64_, u64_, u64_, u64_, int, ptr, int]);
+        assert_eq!(
+            [
+                offset_of!(zxc_stream_stats_t, wall_ns),
+                offset_of!(zxc_stream_stats_t, read_ns),
+                offset_of!(zxc_stream_stats_t, compute_ns),
+                offset_of!(zxc_stream_stats_t, write_ns),
+                offset_of!(zxc_stream_stats_t, n_workers),
+                offset_of!(zxc_stream_stats_t, worker_ns),
+                offset_of!(zxc_stream_stats_t, worker_ns_len),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_stream_stats_t>(), size);
+
+        let (off, size) = c_layout(&[ptr, ptr]);
+        assert_eq!(
+            [
+                offset_of!(zxc_stream_ext_t, block_cb),
+                offset_of!(zxc_stream_ext_t, stats),
+            ][..],
+            off[..]
+        );
+        assert_eq!(size_of::<zxc_stream_ext_t>(), size);
+
+        // Nullable function pointers must stay pointer-sized.
+        assert_eq!(size_of::<zxc_progress_callback_t>(), ptr.0);
+        assert_eq!(size_of::<zxc_block_callback_t>(), ptr.0);
+    }
+
+    #[test]
+    fn test_simd_variant() {
+        let variant = unsafe { zxc_simd_variant() };
+        assert!((ZXC_SIMD_DEFAULT..=ZXC_SIMD_AVX512).contains(&variant));
+        if cfg!(feature = "scalar-only") || cfg!(feature = "default-only") {
+            assert_eq!(variant, ZXC_SIMD_DEFAULT);
+        }
+    }
 
     #[test]
     fn test_compress_bound() {
@@ -0,0 +1,116 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! C allocator for `wasm32-unknown-unknown`.
+//!
+//! That target has no C library, so the `malloc` family used by the C code
+//! (see `wasm-shim/stdlib.h`) is routed to the Rust global allocator. Every
+//! block carries a small header in front of the returned pointer recording
+//! the layout needed to free it.
+
+use alloc::alloc::{Layout, alloc, alloc_zeroed, dealloc};
+use core::ffi::{c_int, c_void};
+use core::ptr;
+
+/// Alignment of plain `malloc` blocks (C's `max_align_t` on wasm32).
+const MIN_ALIGN: usize = 16;
+const ENOMEM: c_int = 48;
+const EINVAL: c_int = 28;
+
+/// Allocates `size` bytes aligned to `align` (a power of two >= MIN_ALIGN).
+///
+/// The header (total size, offset) sits in the two words just below the
+/// returned pointer; `offset == align` keeps the user pointer aligned.
+unsafe fn allocate(size: usize, align: usize, zeroed: bool) -> *mut c_void {
+    let Some(total) = size.checked_add(align) else {
+        return ptr::null_mut();
+    };
+    let Ok(layout) = Layout::from_size_align(total, align) else {
+        return ptr::null_mut();
+    };
+    let base = unsafe {
+        if zeroed {
+            alloc_zeroed(layout)
+        } else {
+            alloc(layout)
+        }
+    };
+    if base.is_null() {
+        return ptr::null_mut();
+    }
+    unsafe {
+        let user = base.add(align);
+        let header = user.cast::<usize>().sub(2);
+        header.write(total);
+        header.add(1).write(align);
+        user.cast()
+    }
+}
+
+/// Returns the header `(total, align)` stored in front of `p`.
+unsafe fn header(p: *mut c_void) -> (usize, usize) {
+    let words = p.cast::<usize>();
+    unsafe { (words.sub(2).read(), words.sub(1).read()) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wasm_shim_malloc(size: usize) -> *mut c_void {
+    unsafe { allocate(size, MIN_ALIGN, false) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wasm_shim_calloc(nmemb: usize, size: usize) -> *mut c_void {
+    match nmemb.checked_mul(size) {
+        Some(n) => unsafe { allocate(n, MIN_ALIGN, true) },
+        None => ptr::null_mut(),
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wasm_shim_free(p: *mut c_void) {
+    if p.is_null() {
+        return;
+    }
+    unsafe {
+        let (total, align) = header(p);
+        let base = p.cast::<u8>().sub(align);
+        dealloc(base, Layout::from_size_align_unchecked(total, align));
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wasm_shim_realloc(p: *mut c_void, size: usize) -> *mut c_void {
+    if p.is_null() {
+        return unsafe { zxc_wasm_shim_malloc(size) };
+    }
+    unsafe {
+        let (total, align) = header(p);
+        let new = allocate(size, align, false);
+        if !new.is_null() {
+            ptr::copy_nonoverlapping(p.cast::<u8>(), new.cast::<u8>(), (total - align).min(size));
+            zxc_wasm_shim_free(p);
+        }
+        new
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wasm_shim_posix_memalign(
+    memptr: *mut *mut c_void,
+    alignment: usize,
+    size: usize,
+) -> c_int {
+    if !alignment.is_power_of_two() || alignment % size_of::<*mut c_void>() != 0 {
+        return EINVAL;
+    }
+    let p = unsafe { allocate(size, alignment.max(MIN_ALIGN), false) };
+    if p.is_null() {
+        return ENOMEM;
+    }
+    unsafe { memptr.write(p) };
+    0
+}
@@ -0,0 +1,140 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! C allocator for the `zeroize` feature.
+//!
+//! build.rs points the ZXC_* allocator hooks of `zxc_deps.h` here (see
+//! `zeroize/zxc_wipe.h`). Blocks come from the Rust global allocator with
+//! the same header as `wasm_shim.rs`, and are zeroed in full before being
+//! freed, so plaintext held in contexts, match tables and stream buffers
+//! does not linger in released memory.
+
+use alloc::alloc::{Layout, alloc, alloc_zeroed, dealloc};
+use core::ffi::c_void;
+use core::ptr;
+use core::slice;
+
+use zeroize::Zeroize;
+
+/// Alignment of plain `malloc` blocks, at least C's `max_align_t`.
+const MIN_ALIGN: usize = 16;
+
+/// Allocates `size` bytes aligned to `align` (a power of two >= MIN_ALIGN).
+///
+/// The header (total size, offset) sits in the two words just below the
+/// returned pointer; `offset == align` keeps the user pointer aligned.
+unsafe fn allocate(size: usize, align: usize, zeroed: bool) -> *mut c_void {
+    let Some(total) = size.checked_add(align) else {
+        return ptr::null_mut();
+    };
+    let Ok(layout) = Layout::from_size_align(total, align) else {
+        return ptr::null_mut();
+    };
+    let base = unsafe {
+        if zeroed {
+            alloc_zeroed(layout)
+        } else {
+            alloc(layout)
+        }
+    };
+    if base.is_null() {
+        return ptr::null_mut();
+    }
+    unsafe {
+        let user = base.add(align);
+        let header = user.cast::<usize>().sub(2);
+        header.write(total);
+        header.add(1).write(align);
+        user.cast()
+    }
+}
+
+/// Returns the header `(total, align)` stored in front of `p`.
+unsafe fn header(p: *mut c_void) -> (usize, usize) {
+    let words = p.cast::<usize>();
+    unsafe { (words.sub(2).read(), words.sub(1).read()) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wipe_malloc(size: usize) -> *mut c_void {
+    unsafe { allocate(size, MIN_ALIGN, false) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wipe_calloc(nmemb: usize, size: usize) -> *mut c_void {
+    match nmemb.checked_mul(size) {
+        Some(n) => unsafe { allocate(n, MIN_ALIGN, true) },
+        None => ptr::null_mut(),
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wipe_aligned_malloc(size: usize, alignment: usize) -> *mut c_void {
+    if !alignment.is_power_of_two() {
+        return ptr::null_mut();
+    }
+    unsafe { allocate(size, alignment.max(MIN_ALIGN), false) }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wipe_free(p: *mut c_void) {
+    if p.is_null() {
+        return;
+    }
+    unsafe {
+        let (total, align) = header(p);
+        let base = p.cast::<u8>().sub(align);
+        // Volatile writes: the stores cannot be elided ahead of `dealloc`.
+        slice::from_raw_parts_mut(base, total).zeroize();
+        dealloc(base, Layout::from_size_align_unchecked(total, align));
+    }
+}
+
+/// Always moves the block, so the old copy is wiped rather than left behind
+/// by an in-place shrink or a system `realloc`.
+#[unsafe(no_mangle)]
+unsafe extern "C" fn zxc_wipe_realloc(p: *mut c_void, size: usize) -> *mut c_void {
+    if p.is_null() {
+        return unsafe { zxc_wipe_malloc(size) };
+    }
+    unsafe {
+        let (total, align) = header(p);
+        let new = allocate(size, align, false);
+        if !new.is_null() {
+            ptr::copy_nonoverlapping(p.cast::<u8>(), new.cast::<u8>(), (total - align).min(size));
+            zxc_wipe_free(p);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wipe_allocator() {
+        unsafe {
+            let p = zxc_wipe_aligned_malloc(100, 64).cast::<u8>();
+            assert!(!p.is_null());
+            assert_eq!(p as usize % 64, 0);
+            ptr::write_bytes(p, 0xAB, 100);
+
+            let q = zxc_wipe_realloc(p.cast(), 200).cast::<u8>();
+            assert!(!q.is_null());
+            assert_eq!(q as usize % 64, 0, "realloc keeps the alignment");
+            assert!(slice::from_raw_parts(q, 100).iter().all(|&b| b == 0xAB));
+            zxc_wipe_free(q.cast());
+
+            let z = zxc_wipe_calloc(16, 4).cast::<u8>();
+            assert!(slice::from_raw_parts(z, 64).iter().all(|&b| b == 0));
+            zxc_wipe_free(z.cast());
+            assert!(zxc_wipe_calloc(usize::MAX, 2).is_null());
+            zxc_wipe_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#!/bin/sh
+#
+# ZXC - High-performance lossless compression
+#
+# Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+# SPDX-License-Identifier: BSD-3-Clause
+
+# Copies the C sources into zxc-sys/zxc/ so the crate can be packaged and
+# built outside the repository (`cargo publish`, `cargo package`, vendoring).
+#
+# The upstream src/lib + include layout is preserved because the C sources
+# reach their headers through relative paths (../../include/...).
+#
+# Usage: ./vendor.sh        (from any directory)
+
+set -eu
+
+here=$(cd "$(dirname "$0")" && pwd)
+root=$(cd "$here/../../.." && pwd)
+dest="$here/zxc"
+
+rm -rf "$dest"
+mkdir -p "$dest/src"
+cp -R "$root/include" "$dest/include"
+cp -R "$root/src/lib" "$dest/src/lib"
+cp "$root/LICENSE" "$dest/LICENSE"
+
+echo "Vendored ZXC sources into $dest"
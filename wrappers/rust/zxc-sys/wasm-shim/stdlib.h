@@ -0,0 +1,38 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+/*
+ * Minimal <stdlib.h> for wasm32-unknown-unknown, which has no C library.
+ * The allocator is provided by the Rust side (zxc-sys/src/wasm_shim.rs).
+ */
+
+#ifndef ZXC_WASM_SHIM_STDLIB_H
+#define ZXC_WASM_SHIM_STDLIB_H
+
+#include <stddef.h>
+
+void* zxc_wasm_shim_malloc(size_t size);
+void* zxc_wasm_shim_calloc(size_t nmemb, size_t size);
+void* zxc_wasm_shim_realloc(void* ptr, size_t size);
+void zxc_wasm_shim_free(void* ptr);
+int zxc_wasm_shim_posix_memalign(void** memptr, size_t alignment, size_t size);
+
+static inline void* malloc(size_t size) { return zxc_wasm_shim_malloc(size); }
+
+static inline void* calloc(size_t nmemb, size_t size) {
+    return zxc_wasm_shim_calloc(nmemb, size);
+}
+
+static inline void* realloc(void* ptr, size_t size) { return zxc_wasm_shim_realloc(ptr, size); }
+
+static inline void free(void* ptr) { zxc_wasm_shim_free(ptr); }
+
+static inline int posix_memalign(void** memptr, size_t alignment, size_t size) {
+    return zxc_wasm_shim_posix_memalign(memptr, alignment, size);
+}
+
+#endif /* ZXC_WASM_SHIM_STDLIB_H */
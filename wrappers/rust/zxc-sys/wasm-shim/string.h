@@ -0,0 +1,23 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+/*
+ * Minimal <string.h> for wasm32-unknown-unknown. The mem* symbols are
+ * exported by Rust's compiler-builtins on this target.
+ */
+
+#ifndef ZXC_WASM_SHIM_STRING_H
+#define ZXC_WASM_SHIM_STRING_H
+
+#include <stddef.h>
+
+void* memcpy(void* dst, const void* src, size_t n);
+void* memmove(void* dst, const void* src, size_t n);
+void* memset(void* dst, int c, size_t n);
+int memcmp(const void* a, const void* b, size_t n);
+
+#endif /* ZXC_WASM_SHIM_STRING_H */
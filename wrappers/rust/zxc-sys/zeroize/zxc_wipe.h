@@ -0,0 +1,26 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+/*
+ * Allocator behind the ZXC_* hooks when the `zeroize` feature is enabled.
+ * Force-included in every translation unit by build.rs; the implementation
+ * lives on the Rust side (zxc-sys/src/wipe.rs) and zeroes each block before
+ * it is released.
+ */
+
+#ifndef ZXC_WIPE_H
+#define ZXC_WIPE_H
+
+#include <stddef.h>
+
+void* zxc_wipe_malloc(size_t size);
+void* zxc_wipe_calloc(size_t nmemb, size_t size);
+void* zxc_wipe_realloc(void* ptr, size_t size);
+void* zxc_wipe_aligned_malloc(size_t size, size_t alignment);
+void zxc_wipe_free(void* ptr);
+
+#endif /* ZXC_WIPE_H */
@@ -0,0 +1,189 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Throughput of the wrapper's buffer and file paths.
+//!
+//! The groups compare the allocating one-shot calls with `compress_to` and
+//! `decompress_to` into reused buffers, so a copy or an allocation added on
+//! the Rust side shows up as a gap between the two rather than being lost in
+//! the codec's own time.
+//!
+//! Run with: `cargo bench -p zxc-compress`, or `ZXC_BENCH_LARGE=1 cargo bench`
+//! to add the 256 MiB inputs.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use zxc::{CompressOptions, DecompressOptions, Level};
+
+const KIB: usize = 1 << 10;
+const MIB: usize = 1 << 20;
+
+/// Text-like input: words and raw bytes picked by an LCG, compressing about
+/// as well as logs or JSON.
+fn corpus(len: usize) -> Vec<u8> {
+    const WORDS: [&[u8]; 8] = [
+        b"zxc", b"block", b"offset", b"literal", b"huffman", b"endian", b"s390x", b"frame",
+    ];
+    let mut s: u32 = 0x2104;
+    let mut out = Vec::with_capacity(len + 16);
+    while out.len() < len {
+        s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        out.extend_from_slice(WORDS[(s >> 16) as usize % WORDS.len()]);
+        out.push(if s & 0x80 != 0 { b'\n' } else { b' ' });
+        out.extend_from_slice(&(s >> 8).to_le_bytes()[..(s & 3) as usize]);
+    }
+    out.truncate(len);
+    out
+}
+
+fn sizes() -> Vec<usize> {
+    let mut sizes = vec![4 * KIB, 64 * KIB, MIB, 16 * MIB];
+    if std::env::var_os("ZXC_BENCH_LARGE").is_some() {
+        sizes.push(256 * MIB);
+    }
+    sizes
+}
+
+fn label(size: usize) -> String {
+    match size {
+        s if s >= MIB => format!("{}MiB", s / MIB),
+        s => format!("{}KiB", s / KIB),
+    }
+}
+
+/// Every level on 1 MiB, without checksums.
+fn levels(c: &mut Criterion) {
+    let data = corpus(MIB);
+    let mut group = c.benchmark_group("levels");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for &level in Level::all() {
+        let opts = CompressOptions::with_level(level).without_checksum();
+        let packed = zxc::compress_with_options(&data, &opts).unwrap();
+        let mut out = vec![0u8; zxc::compress_bound(data.len())];
+        group.bench_function(BenchmarkId::new("compress", level as i32), |b| {
+            b.iter(|| zxc::compress_to(black_box(&data), &mut out, &opts).unwrap())
+        });
+        let dopts = DecompressOptions::default();
+        let mut plain = vec![0u8; data.len()];
+        group.bench_function(BenchmarkId::new("decompress", level as i32), |b| {
+            b.iter(|| zxc::decompress_to(black_box(&packed), &mut plain, &dopts).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// 4 KiB to 16 MiB (256 MiB with `ZXC_BENCH_LARGE`) at the default level,
+/// through the allocating calls and into reused buffers.
+fn sizes_group(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sizes");
+    let opts = CompressOptions::default().without_checksum();
+    let dopts = DecompressOptions::default();
+    for size in sizes() {
+        let data = corpus(size);
+        let packed = zxc::compress_with_options(&data, &opts).unwrap();
+        let mut out = vec![0u8; zxc::compress_bound(size)];
+        let mut plain = vec![0u8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        if size >= 16 * MIB {
+            group.sample_size(10);
+        }
+        let id = label(size);
+        group.bench_function(BenchmarkId::new("compress", &id), |b| {
+            b.iter(|| zxc::compress_with_options(black_box(&data), &opts).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("compress_to", &id), |b| {
+            b.iter(|| zxc::compress_to(black_box(&data), &mut out, &opts).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("decompress", &id), |b| {
+            b.iter(|| zxc::decompress(black_box(&packed)).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("decompress_to", &id), |b| {
+            b.iter(|| zxc::decompress_to(black_box(&packed), &mut plain, &dopts).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// The cost of checksums on 1 MiB, written and verified.
+fn checksum(c: &mut Criterion) {
+    let data = corpus(MIB);
+    let mut group = c.benchmark_group("checksum");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    let mut out = vec![0u8; zxc::compress_bound(data.len())];
+    let mut plain = vec![0u8; data.len()];
+    for on in [false, true] {
+        let opts = CompressOptions {
+            checksum: on,
+            ..Default::default()
+        };
+        let mut dopts = DecompressOptions::default();
+        dopts.verify_checksum = on;
+        let packed = zxc::compress_with_options(&data, &opts).unwrap();
+        let id = if on { "on" } else { "off" };
+        group.bench_function(BenchmarkId::new("compress", id), |b| {
+            b.iter(|| zxc::compress_to(black_box(&data), &mut out, &opts).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("decompress", id), |b| {
+            b.iter(|| zxc::decompress_to(black_box(&packed), &mut plain, &dopts).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// The multithreaded file path on a 64 MiB file, with one thread and with
+/// every core.
+#[cfg(not(feature = "no-stream"))]
+fn files(c: &mut Criterion) {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use zxc::{StreamCompressOptions, StreamDecompressOptions};
+
+    let dir = std::env::temp_dir().join(format!("zxc_bench_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| -> PathBuf { dir.join(name) };
+    let data = corpus(64 * MIB);
+    fs::write(path("plain"), &data).unwrap();
+    zxc::compress_file(path("plain"), path("packed"), Level::Default, None, None).unwrap();
+
+    let mut group = c.benchmark_group("file");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(10);
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = vec![1, cores];
+    threads.dedup();
+    for threads in threads {
+        let copts = StreamCompressOptions {
+            checksum: false,
+            ..Default::default()
+        }
+        .threads(threads);
+        group.bench_function(BenchmarkId::new("compress", threads), |b| {
+            b.iter(|| {
+                zxc::compress_file_with_options(path("plain"), path("out.zxc"), &copts).unwrap()
+            })
+        });
+        let dopts = StreamDecompressOptions {
+            threads: Some(threads),
+            ..Default::default()
+        };
+        group.bench_function(BenchmarkId::new("decompress", threads), |b| {
+            b.iter(|| {
+                zxc::decompress_file_with_options(path("packed"), path("out"), &dopts).unwrap()
+            })
+        });
+    }
+    group.finish();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "no-stream")]
+fn files(_: &mut Criterion) {}
+
+criterion_group!(benches, levels, sizes_group, checksum, files);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Bundling game assets into one pack and loading them by name.
+//!
+//! Run with: `cargo run --example asset_pack`
+
+use std::time::Instant;
+
+use zxc::pack::{Pack, PackWriter};
+
+/// Stand-ins for a game's files: text shaders, tile maps and meshes.
+fn assets() -> Vec<(String, Vec<u8>)> {
+    let mut assets = Vec::new();
+    for i in 0..40 {
+        let shader = format!(
+            "// shader {i}\n@fragment fn main() -> @location(0) vec4f {{ return vec4f({i}.0); }}\n"
+        );
+        assets.push((
+            format!("shaders/{i:02}.wgsl"),
+            shader.repeat(20).into_bytes(),
+        ));
+    }
+    for i in 0..20 {
+        let map: Vec<u8> = (0..64 * 64)
+            .map(|t| ((t / 64 + t % 7 + i) % 5) as u8)
+            .collect();
+        assets.push((format!("levels/{i:02}.map"), map));
+    }
+    for i in 0..10 {
+        let mesh: Vec<u8> = (0..20_000u32)
+            .flat_map(|v| ((v * (i + 1)) as f32 * 0.01).sin().to_le_bytes())
+            .collect();
+        assets.push((format!("meshes/{i}.bin"), mesh));
+    }
+    assets
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join("zxc-assets.zxp");
+
+    // Build time: bundle everything.
+    let assets = assets();
+    let raw: usize = assets.iter().map(|(_, data)| data.len()).sum();
+    let mut writer = PackWriter::new();
+    for (name, data) in &assets {
+        writer.add(name, data)?;
+    }
+    writer.write_to(std::fs::File::create(&path)?)?;
+    let packed = std::fs::metadata(&path)?.len();
+    println!(
+        "Packed {} assets: {raw} -> {packed} bytes ({:.1}%)",
+        assets.len(),
+        packed as f64 * 100.0 / raw as f64
+    );
+
+    // Run time: open once, then load assets on demand into one buffer.
+    let mut pack = Pack::open(&path)?;
+    let mut buf = Vec::new();
+    for name in ["levels/03.map", "shaders/17.wgsl", "meshes/9.bin"] {
+        let size = pack.size_of(name).ok_or("asset missing from the pack")?;
+        buf.resize(size, 0);
+        let start = Instant::now();
+        pack.read_into(name, &mut buf)?;
+        println!("{name}: {size} bytes in {:?}", start.elapsed());
+        assert!(assets.iter().any(|(n, data)| n == name && *data == buf));
+    }
+    println!("{} assets, first: {:?}", pack.len(), pack.names().next());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,215 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! zxc against `lz4_flex`, `zstd` and `snap` on your own files.
+//!
+//! Each file is cut into chunks (1 MiB by default) that every codec
+//! compresses and decompresses on its own, spread over 1, 2, 4… worker
+//! threads, so all codecs see the same work and the thread counts measure
+//! aggregate throughput. Checksums are off on both sides for zxc, as none of
+//! the other formats carry one here. Every round trip is checked once before
+//! it is timed.
+//!
+//! Run with:
+//! `cargo run --release --example compare --features compare-example -- [-T 1,4,8] [-t SECS] [-B KIB] FILE...`
+
+use std::error::Error;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use zxc::Level;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+type Compress = fn(&[u8]) -> Result<Vec<u8>, BoxError>;
+type Decompress = fn(&[u8], usize) -> Result<Vec<u8>, BoxError>;
+
+struct Codec {
+    name: &'static str,
+    compress: Compress,
+    decompress: Decompress,
+}
+
+fn zxc_compress(chunk: &[u8], level: Level) -> Result<Vec<u8>, BoxError> {
+    Ok(zxc::compress(chunk, level, Some(false))?)
+}
+
+fn zxc_decompress(chunk: &[u8], _: usize) -> Result<Vec<u8>, BoxError> {
+    Ok(zxc::decompress(chunk)?)
+}
+
+fn zstd_decompress(chunk: &[u8], len: usize) -> Result<Vec<u8>, BoxError> {
+    Ok(zstd::bulk::decompress(chunk, len)?)
+}
+
+const CODECS: &[Codec] = &[
+    Codec {
+        name: "zxc -1",
+        compress: |c| zxc_compress(c, Level::Fastest),
+        decompress: zxc_decompress,
+    },
+    Codec {
+        name: "zxc -3",
+        compress: |c| zxc_compress(c, Level::Default),
+        decompress: zxc_decompress,
+    },
+    Codec {
+        name: "zxc -5",
+        compress: |c| zxc_compress(c, Level::Compact),
+        decompress: zxc_decompress,
+    },
+    Codec {
+        name: "lz4_flex",
+        compress: |c| Ok(lz4_flex::block::compress(c)),
+        decompress: |c, len| Ok(lz4_flex::block::decompress(c, len)?),
+    },
+    Codec {
+        name: "snap",
+        compress: |c| Ok(snap::raw::Encoder::new().compress_vec(c)?),
+        decompress: |c, _| Ok(snap::raw::Decoder::new().decompress_vec(c)?),
+    },
+    Codec {
+        name: "zstd -1",
+        compress: |c| Ok(zstd::bulk::compress(c, 1)?),
+        decompress: zstd_decompress,
+    },
+    Codec {
+        name: "zstd -3",
+        compress: |c| Ok(zstd::bulk::compress(c, 3)?),
+        decompress: zstd_decompress,
+    },
+];
+
+struct Args {
+    threads: Vec<usize>,
+    time: Duration,
+    chunk: usize,
+    files: Vec<String>,
+}
+
+fn usage() -> String {
+    "usage: compare [-T 1,2,4] [-t SECS] [-B KIB] FILE...".to_string()
+}
+
+fn parse_args() -> Result<Args, BoxError> {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut args = Args {
+        threads: [1, 2, 4, 8, 16]
+            .into_iter()
+            .filter(|&t| t <= cores)
+            .collect(),
+        time: Duration::from_secs(1),
+        chunk: 1 << 20,
+        files: Vec::new(),
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(usage);
+        match arg.as_str() {
+            "-T" => {
+                args.threads = value()?
+                    .split(',')
+                    .map(|t| t.parse::<usize>())
+                    .collect::<Result<_, _>>()?;
+                if args.threads.contains(&0) {
+                    return Err("thread counts start at 1".into());
+                }
+            }
+            "-t" => args.time = Duration::from_secs_f64(value()?.parse()?),
+            "-B" => args.chunk = value()?.parse::<usize>()? << 10,
+            "-h" | "--help" => return Err(usage().into()),
+            _ => args.files.push(arg),
+        }
+    }
+    if args.files.is_empty() || args.threads.is_empty() || args.chunk == 0 {
+        return Err(usage().into());
+    }
+    Ok(args)
+}
+
+/// Runs `f` over every item of `work` on `threads` workers, over and over
+/// until `time` has passed, and returns the rounds per second.
+fn rate<T: Sync>(
+    work: &[T],
+    threads: usize,
+    time: Duration,
+    f: impl Fn(&T) -> Result<(), BoxError> + Sync,
+) -> Result<f64, BoxError> {
+    let start = Instant::now();
+    let mut rounds = 0u32;
+    while rounds == 0 || start.elapsed() < time {
+        let per_thread = work.len().div_ceil(threads);
+        thread::scope(|s| {
+            let workers: Vec<_> = work
+                .chunks(per_thread.max(1))
+                .map(|part| s.spawn(|| part.iter().try_for_each(&f)))
+                .collect();
+            workers
+                .into_iter()
+                .try_for_each(|w| w.join().map_err(|_| BoxError::from("worker panicked"))?)
+        })?;
+        rounds += 1;
+    }
+    Ok(f64::from(rounds) / start.elapsed().as_secs_f64())
+}
+
+fn main() -> Result<(), BoxError> {
+    let args = parse_args()?;
+    println!(
+        "zxc {} ({:?})",
+        zxc::version_string(),
+        zxc::cpu::active_variant()
+    );
+
+    for path in &args.files {
+        let data = std::fs::read(path)?;
+        let chunks: Vec<&[u8]> = data.chunks(args.chunk).collect();
+        let mb = data.len() as f64 / 1e6;
+        println!(
+            "\n{path}: {} bytes in {} chunk(s) of {} KiB",
+            data.len(),
+            chunks.len(),
+            args.chunk >> 10
+        );
+        println!(
+            "{:<10} {:>7} {:>11} {:>8} {:>14} {:>14}",
+            "codec", "ratio", "compressed", "threads", "compress MB/s", "decomp. MB/s"
+        );
+
+        for codec in CODECS {
+            let packed = chunks
+                .iter()
+                .map(|c| (codec.compress)(c).map(|p| (p, c.len())))
+                .collect::<Result<Vec<_>, _>>()?;
+            for ((p, len), c) in packed.iter().zip(&chunks) {
+                if (codec.decompress)(p, *len)? != *c {
+                    return Err(format!("{}: round trip mismatch", codec.name).into());
+                }
+            }
+            let size: usize = packed.iter().map(|(p, _)| p.len()).sum();
+            let ratio = data.len() as f64 / size.max(1) as f64;
+
+            for (i, &threads) in args.threads.iter().enumerate() {
+                let c = rate(&chunks, threads, args.time, |c| {
+                    (codec.compress)(c).map(drop)
+                })?;
+                let d = rate(&packed, threads, args.time, |(p, len)| {
+                    (codec.decompress)(p, *len).map(drop)
+                })?;
+                let (name, ratio, size) = match i {
+                    0 => (codec.name, format!("{ratio:.3}"), size.to_string()),
+                    _ => ("", String::new(), String::new()),
+                };
+                println!(
+                    "{name:<10} {ratio:>7} {size:>11} {threads:>8} {:>14.1} {:>14.1}",
+                    c * mb,
+                    d * mb
+                );
+            }
+        }
+    }
+    Ok(())
+}
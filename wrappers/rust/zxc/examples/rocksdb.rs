@@ -0,0 +1,81 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Storing zxc-compressed values in RocksDB through `rust-rocksdb`.
+//!
+//! `rust-rocksdb` cannot plug a compressor into RocksDB's block-based
+//! tables, so this example turns RocksDB's own compression off and runs
+//! each value through the same C-ABI callbacks (`zxc::rocksdb::CODEC`) a
+//! C++ `Compressor` shim would call, with the uncompressed size in front as
+//! RocksDB stores it for its blocks.
+//!
+//! Run with: `cargo run --example rocksdb --features rocksdb-example`
+
+use rocksdb::{DB, DBCompressionType, Options};
+use zxc::rocksdb::CODEC;
+
+fn encode(value: &[u8]) -> Result<Vec<u8>, String> {
+    let bound = (CODEC.compress_bound)(value.len());
+    if bound == 0 {
+        return Err(format!("{} bytes is above the block limit", value.len()));
+    }
+    let mut out = vec![0u8; 4 + bound];
+    out[..4].copy_from_slice(&(value.len() as u32).to_le_bytes());
+    let n =
+        unsafe { (CODEC.compress)(3, value.as_ptr(), value.len(), out[4..].as_mut_ptr(), bound) };
+    if n < 0 {
+        return Err(format!("compression failed: {n}"));
+    }
+    out.truncate(4 + n as usize);
+    Ok(out)
+}
+
+fn decode(stored: &[u8]) -> Result<Vec<u8>, String> {
+    let (size, block) = stored.split_at_checked(4).ok_or("truncated value")?;
+    let size = u32::from_le_bytes(size.try_into().unwrap()) as usize;
+    let mut out = vec![0u8; size];
+    let n = unsafe { (CODEC.decompress)(block.as_ptr(), block.len(), out.as_mut_ptr(), size) };
+    if n < 0 || n as usize != size {
+        return Err(format!("decompression failed: {n}"));
+    }
+    Ok(out)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("zxc-rocksdb-{}", std::process::id()));
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_compression_type(DBCompressionType::None);
+    let db = DB::open(&opts, &path)?;
+
+    let (mut raw, mut stored) = (0, 0);
+    for i in 0..1000u32 {
+        let value = format!(
+            r#"{{"user":{},"region":"eu-west-{}","tags":["alpha","beta","gamma"],"score":{}}}"#,
+            i,
+            i % 3,
+            i * 7 % 100
+        )
+        .repeat(8);
+        let encoded = encode(value.as_bytes())?;
+        raw += value.len();
+        stored += encoded.len();
+        db.put(i.to_be_bytes(), encoded)?;
+    }
+
+    let value = db.get(42u32.to_be_bytes())?.ok_or("key 42 missing")?;
+    let value = String::from_utf8(decode(&value)?)?;
+    println!("key 42: {}...", &value[..60]);
+    println!(
+        "{raw} bytes of values stored in {stored} ({:.1}%)",
+        stored as f64 * 100.0 / raw as f64
+    );
+
+    drop(db);
+    DB::destroy(&opts, &path)?;
+    Ok(())
+}
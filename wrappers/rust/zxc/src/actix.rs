@@ -0,0 +1,543 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! `Content-Encoding: zxc` for actix-web servers (the `actix` feature).
+//!
+//! [`Zxc`] is a middleware covering both directions: request bodies whose
+//! `Content-Encoding` ends in `zxc` reach the handlers decompressed, and
+//! responses are compressed for clients whose `Accept-Encoding` accepts
+//! `zxc`. Both stream through the push API; neither body is buffered.
+//!
+//! ```rust
+//! use actix_web::{App, HttpResponse, web};
+//!
+//! let app = App::new()
+//!     .wrap(zxc::actix::Zxc::new().with_min_size(256))
+//!     .route("/echo", web::post().to(|body: web::Bytes| async move {
+//!         HttpResponse::Ok().body(body)
+//!     }));
+//! ```
+//!
+//! A response is left as it is when it already has a `Content-Encoding` or
+//! a `Content-Range`, when it has no body, or when its size is known and
+//! under the minimum. Every other response gets `Vary: Accept-Encoding`,
+//! compressed or not. Clients that do not list `zxc` get the response
+//! unencoded, so the middleware can sit next to actix-web's own `Compress`.
+//!
+//! Size limits on extractors such as `web::Json` apply to the decompressed
+//! request body. A corrupt or truncated request body fails the extractor
+//! with `400 Bad Request`.
+
+use std::boxed::Box;
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::{Future, Ready, ready};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{
+    Payload, ResponseHead, Service, ServiceRequest, ServiceResponse, Transform, forward_ready,
+};
+use actix_web::error::PayloadError;
+use actix_web::http::header::{
+    ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, HeaderValue, VARY,
+};
+use bytes::Bytes;
+use futures::Stream;
+
+use crate::http::{Decode, ENCODING, strip_coding};
+use crate::scratch::Scratch;
+use crate::{CStream, CompressOptions, Error, Level};
+
+/// The middleware. See the [module docs](self).
+#[derive(Debug, Clone, Copy)]
+pub struct Zxc {
+    level: Level,
+    checksum: bool,
+    min_size: u64,
+}
+
+impl Default for Zxc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Zxc {
+    /// Compresses responses of 1 KiB or more, or of unknown size, at the
+    /// default level with checksums.
+    pub fn new() -> Self {
+        Self {
+            level: Level::Default,
+            checksum: true,
+            min_size: 1024,
+        }
+    }
+
+    /// Compresses responses at `level`.
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Leaves the checksums out of compressed responses.
+    pub fn without_checksum(mut self) -> Self {
+        self.checksum = false;
+        self
+    }
+
+    /// Leaves responses of known size under `min_size` bytes unencoded.
+    pub fn with_min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Wraps a response body, compressing it when `accepted` and eligible.
+    fn encode<B: MessageBody>(
+        self,
+        head: &mut ResponseHead,
+        body: B,
+        accepted: bool,
+    ) -> CompressBody<B> {
+        let size = match body.size() {
+            BodySize::None => return CompressBody::passthrough(body),
+            BodySize::Sized(n) => Some(n),
+            BodySize::Stream => None,
+        };
+        if head.headers.contains_key(CONTENT_ENCODING) || head.headers.contains_key(CONTENT_RANGE) {
+            return CompressBody::passthrough(body);
+        }
+        head.headers
+            .append(VARY, HeaderValue::from_static("accept-encoding"));
+        if !accepted || size.is_some_and(|n| n < self.min_size) {
+            return CompressBody::passthrough(body);
+        }
+        head.headers
+            .insert(CONTENT_ENCODING, HeaderValue::from_static(ENCODING));
+        head.headers.remove(CONTENT_LENGTH);
+        CompressBody::new(body, self)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Zxc
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<CompressBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = ZxcMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ZxcMiddleware {
+            service,
+            config: *self,
+        }))
+    }
+}
+
+/// The service built by [`Zxc`].
+pub struct ZxcMiddleware<S> {
+    service: S,
+    config: Zxc,
+}
+
+impl<S> fmt::Debug for ZxcMiddleware<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZxcMiddleware")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for ZxcMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<CompressBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let accepted = req
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(accepts);
+
+        let rest = req
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .and_then(strip_coding);
+        if let Some(rest) = rest {
+            let headers = req.headers_mut();
+            match HeaderValue::from_str(&rest) {
+                Ok(rest) if !rest.is_empty() => headers.insert(CONTENT_ENCODING, rest),
+                _ => headers.remove(CONTENT_ENCODING),
+            };
+            headers.remove(CONTENT_LENGTH);
+            let payload = req.parts_mut().1.take();
+            req.set_payload(Payload::Stream {
+                payload: Box::pin(DecompressPayload::new(payload)),
+            });
+        }
+
+        let fut = self.service.call(req);
+        let config = self.config;
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_body(|head, body| config.encode(head, body, accepted)))
+        })
+    }
+}
+
+/// Whether an `Accept-Encoding` value accepts zxc: listed with a non-zero
+/// quality, or not listed and covered by a non-zero `*`.
+fn accepts(value: &str) -> bool {
+    let mut wildcard = false;
+    for item in value.split(',') {
+        let mut params = item.split(';').map(str::trim);
+        let coding = params.next().unwrap_or_default();
+        let q = params
+            .filter_map(|p| p.strip_prefix("q=").or_else(|| p.strip_prefix("Q=")))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if coding.eq_ignore_ascii_case(ENCODING) {
+            return q > 0.0;
+        }
+        if coding == "*" {
+            wildcard = q > 0.0;
+        }
+    }
+    wildcard
+}
+
+/// A request payload decompressed on the fly.
+struct DecompressPayload {
+    inner: Payload,
+    decode: Decode,
+    done: bool,
+}
+
+impl DecompressPayload {
+    fn new(inner: Payload) -> Self {
+        Self {
+            inner,
+            decode: Decode::new(),
+            done: false,
+        }
+    }
+}
+
+impl Stream for DecompressPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        loop {
+            match this.decode.advance() {
+                Ok(Some(data)) => return Poll::Ready(Some(Ok(data))),
+                Ok(None) if this.decode.inner_done => {
+                    this.done = true;
+                    return Poll::Ready(None);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(PayloadError::Io(io::Error::other(e)))));
+                }
+            }
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => this.decode.push(data),
+                Poll::Ready(Some(Err(e))) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => this.decode.inner_done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A response body compressed on the fly, or passed through.
+    pub struct CompressBody<B> {
+        #[pin]
+        inner: B,
+        encode: Option<Box<Encode>>,
+        done: bool,
+    }
+}
+
+impl<B> fmt::Debug for CompressBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressBody")
+            .field("compress", &self.encode.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B> CompressBody<B> {
+    fn new(inner: B, config: Zxc) -> Self {
+        Self {
+            inner,
+            encode: Some(Box::new(Encode {
+                config,
+                cs: None,
+                chunk: Bytes::new(),
+                pos: 0,
+                pending: false,
+                out: Scratch::zeroed(0),
+                inner_done: false,
+                finished: false,
+            })),
+            done: false,
+        }
+    }
+
+    fn passthrough(inner: B) -> Self {
+        Self {
+            inner,
+            encode: None,
+            done: false,
+        }
+    }
+
+    /// Whether the body compresses its inner body.
+    pub fn is_compressing(&self) -> bool {
+        self.encode.is_some()
+    }
+}
+
+impl<B: MessageBody> MessageBody for CompressBody<B> {
+    type Error = Box<dyn StdError>;
+
+    fn size(&self) -> BodySize {
+        match self.encode {
+            None => self.inner.size(),
+            Some(_) => BodySize::Stream,
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+        let Some(encode) = this.encode else {
+            return this.inner.poll_next(cx).map_err(Into::into);
+        };
+        loop {
+            match encode.advance() {
+                Ok(Some(data)) => return Poll::Ready(Some(Ok(data))),
+                Ok(None) if encode.finished => {
+                    *this.done = true;
+                    return Poll::Ready(None);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+            }
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => {
+                    encode.chunk = data;
+                    encode.pos = 0;
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                Poll::Ready(None) => encode.inner_done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Encoder state of a [`CompressBody`].
+struct Encode {
+    config: Zxc,
+    /// Created on first use, so that a response is only failed once sent.
+    cs: Option<CStream>,
+    chunk: Bytes,
+    pos: usize,
+    /// Output still staged in the stream.
+    pending: bool,
+    out: Scratch,
+    inner_done: bool,
+    finished: bool,
+}
+
+impl Encode {
+    /// The next compressed chunk, or `None` when more input is needed or
+    /// the frame is complete.
+    fn advance(&mut self) -> crate::Result<Option<Bytes>> {
+        let cs = match &mut self.cs {
+            Some(cs) => cs,
+            None => {
+                let opts = CompressOptions {
+                    level: self.config.level,
+                    checksum: self.config.checksum,
+                    ..Default::default()
+                };
+                self.cs.insert(CStream::new(Some(&opts))?)
+            }
+        };
+        if self.out.is_empty() {
+            *self.out = Scratch::zeroed(cs.out_size()).into_vec();
+        }
+        while !self.finished {
+            let input = self.chunk.get(self.pos..).unwrap_or_default();
+            let ending = input.is_empty() && self.inner_done;
+            if input.is_empty() && !self.pending && !ending {
+                return Ok(None);
+            }
+            let p = match ending {
+                true => cs.end(&mut self.out)?,
+                false => cs.compress(input, &mut self.out)?,
+            };
+            self.pos += p.consumed;
+            self.pending = p.pending > 0;
+            self.finished = ending && !self.pending;
+            if p.produced > 0 {
+                let out = self.out.get(..p.produced).unwrap_or_default();
+                return Ok(Some(Bytes::copy_from_slice(out)));
+            }
+            if p.consumed == 0 && !self.finished {
+                return Err(Error::DstTooSmall { needed: 0 });
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service, read_body};
+    use actix_web::{App, HttpResponse, rt, web};
+
+    use super::*;
+    use crate::{compress, decompress};
+
+    fn json() -> Vec<u8> {
+        br#"{"id":1,"name":"sensor","values":[1.5,2.5,3.5]}"#.repeat(200)
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(accepts("zxc"));
+        assert!(accepts("gzip, ZXC;q=0.5"));
+        assert!(accepts("gzip;q=1.0, *;q=0.1"));
+        assert!(!accepts("gzip, br"));
+        assert!(!accepts("zxc;q=0"));
+        assert!(!accepts("*, zxc;q=0"));
+        assert!(!accepts("*;q=0"));
+        assert!(!accepts(""));
+    }
+
+    #[test]
+    fn test_middleware() {
+        rt::System::new().block_on(async {
+            let app = init_service(
+                App::new()
+                    .wrap(Zxc::new())
+                    .route(
+                        "/echo",
+                        web::post()
+                            .to(|body: web::Bytes| async move { HttpResponse::Ok().body(body) }),
+                    )
+                    .route(
+                        "/gzip",
+                        web::get().to(|| async {
+                            HttpResponse::Ok()
+                                .insert_header((CONTENT_ENCODING, "gzip"))
+                                .body(json())
+                        }),
+                    )
+                    .route("/small", web::get().to(|| async { "ok" })),
+            )
+            .await;
+            let data = json();
+            let frame = compress(&data, Level::Default, None).unwrap();
+
+            // Compressed request, compressed response.
+            let req = TestRequest::post()
+                .uri("/echo")
+                .insert_header((CONTENT_ENCODING, "zxc"))
+                .insert_header((ACCEPT_ENCODING, "gzip, zxc"))
+                .set_payload(frame.clone())
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "zxc");
+            assert_eq!(res.headers().get(VARY).unwrap(), "accept-encoding");
+            assert_eq!(decompress(&read_body(res).await).unwrap(), data);
+
+            // Compressed request, plain response.
+            let req = TestRequest::post()
+                .uri("/echo")
+                .insert_header((CONTENT_ENCODING, "zxc"))
+                .set_payload(frame.clone())
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert!(!res.headers().contains_key(CONTENT_ENCODING));
+            assert_eq!(read_body(res).await, data);
+
+            // Plain request bodies pass through.
+            let req = TestRequest::post()
+                .uri("/echo")
+                .set_payload(data.clone())
+                .to_request();
+            assert_eq!(read_body(call_service(&app, req).await).await, data);
+
+            // Truncated request body.
+            let req = TestRequest::post()
+                .uri("/echo")
+                .insert_header((CONTENT_ENCODING, "zxc"))
+                .set_payload(frame.get(..frame.len() / 2).unwrap().to_vec())
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+            // Already encoded and small responses are left alone.
+            for uri in ["/gzip", "/small"] {
+                let req = TestRequest::get()
+                    .uri(uri)
+                    .insert_header((ACCEPT_ENCODING, "zxc"))
+                    .to_request();
+                let res = call_service(&app, req).await;
+                assert!(
+                    res.headers()
+                        .get(CONTENT_ENCODING)
+                        .is_none_or(|v| v != "zxc")
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,69 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Arenas for the memory of a request.
+//!
+//! A server decoding many frames at once spends much of its time in the
+//! global allocator, all threads contending for it. With an [`Arena`] per
+//! worker, reset between requests, the decoder state
+//! ([`Decompressor::in_arena`]) and the output come from memory the worker
+//! already holds, and the allocator is out of the loop.
+//!
+//! [`Decompressor::in_arena`]: crate::Decompressor::in_arena
+
+/// A source of byte buffers that live as long as the arena is borrowed,
+/// typically a bump allocator freed all at once.
+///
+/// Implemented for `bumpalo::Bump` with the `bumpalo` feature.
+pub trait Arena {
+    /// Returns `len` bytes from the arena, or `None` when it cannot hold
+    /// them. The contents are unspecified.
+    // Handing out disjoint buffers from a shared arena is what a bump
+    // allocator does.
+    #[allow(clippy::mut_from_ref)]
+    fn alloc_bytes(&self, len: usize) -> Option<&mut [u8]>;
+}
+
+#[cfg(feature = "bumpalo")]
+impl Arena for bumpalo::Bump {
+    fn alloc_bytes(&self, len: usize) -> Option<&mut [u8]> {
+        self.try_alloc_slice_fill_copy(len, 0u8).ok()
+    }
+}
+
+#[cfg(all(test, feature = "bumpalo"))]
+mod tests {
+    use super::*;
+    use crate::{DecompressOptions, Decompressor, Level};
+
+    #[test]
+    fn test_bump_requests() {
+        let block_size = 512 * 1024;
+        let frames: Vec<Vec<u8>> = (0..4u8)
+            .map(|i| crate::compress(&[i; 50_000], Level::Default, None).unwrap())
+            .collect();
+        let mut bump = bumpalo::Bump::new();
+        let mut high_water = 0;
+        for (i, frame) in frames.iter().enumerate() {
+            {
+                let mut dec = Decompressor::in_arena(&bump, block_size).unwrap();
+                let size = crate::decompressed_size(frame).unwrap() as usize;
+                let output = bump.alloc_bytes(size).unwrap();
+                let n = dec
+                    .decompress(frame, output, &DecompressOptions::default())
+                    .unwrap();
+                assert_eq!(output[..n], [i as u8; 50_000]);
+            }
+            bump.reset();
+            // After the first request, the arena serves from what it holds.
+            if i > 0 {
+                assert_eq!(bump.allocated_bytes(), high_water);
+            }
+            high_water = bump.allocated_bytes();
+        }
+    }
+}
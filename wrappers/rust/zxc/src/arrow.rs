@@ -0,0 +1,190 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Arrow IPC buffer compression (the `arrow` feature).
+//!
+//! [`ArrowCodec`] compresses Arrow buffers in the layout the IPC format
+//! uses for its body buffers: the uncompressed length as a little-endian
+//! `i64`, then the compressed data, or `-1` then the raw bytes when
+//! compression would not make the buffer smaller. It has the same two
+//! methods as the LZ4 and zstd codecs inside `arrow-ipc`.
+//!
+//! The IPC format identifies codecs by a closed `CompressionType` enum
+//! (`LZ4_FRAME` and `ZSTD`), and `arrow-ipc` offers no way to plug in
+//! another. Until the format gains a zxc entry, this serves writers that
+//! lay out their own IPC-style bodies, such as caches and spill files of
+//! query engines, rather than Feather files that other readers open.
+//!
+//! ```rust
+//! use arrow_buffer::Buffer;
+//! use zxc::arrow::ArrowCodec;
+//!
+//! let column = Buffer::from_vec((0..10_000i64).map(|i| i / 16).collect::<Vec<_>>());
+//! let codec = ArrowCodec::default();
+//! let mut body = Vec::new();
+//! codec.compress_to_vec(&column, &mut body)?;
+//! assert!(body.len() < column.len() / 4);
+//! assert_eq!(codec.decompress_to_buffer(&Buffer::from_vec(body))?, column);
+//! # Ok::<(), arrow_schema::ArrowError>(())
+//! ```
+
+use std::format;
+use std::vec::Vec;
+
+use arrow_buffer::Buffer;
+use arrow_schema::ArrowError;
+
+use crate::{
+    CompressOptions, DecompressOptions, Level, compress_bound, compress_to, decompress_to,
+    decompressed_size,
+};
+
+/// Length prefix of a buffer stored uncompressed.
+const LENGTH_NO_COMPRESSED_DATA: i64 = -1;
+/// Size of the length prefix.
+const LENGTH_OF_PREFIX_DATA: usize = 8;
+
+/// Compresses Arrow buffers with zxc in the IPC body layout.
+///
+/// Checksums are off, as for the other IPC codecs; the frames are plain
+/// zxc frames otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArrowCodec {
+    level: Level,
+}
+
+impl ArrowCodec {
+    /// A codec compressing at `level`.
+    pub fn new(level: Level) -> Self {
+        Self { level }
+    }
+
+    /// Appends `input`, compressed, to `output`, and returns the number of
+    /// bytes appended.
+    ///
+    /// An empty input appends nothing. An input that does not compress is
+    /// stored raw behind a `-1` length.
+    pub fn compress_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, ArrowError> {
+        let start = output.len();
+        if input.is_empty() {
+            return Ok(0);
+        }
+        let bound = match compress_bound(input.len()) {
+            0 => {
+                return Err(ArrowError::ComputeError(
+                    "buffer too large to compress".into(),
+                ));
+            }
+            b => b,
+        };
+        output.extend_from_slice(&(input.len() as i64).to_le_bytes());
+        let body = output.len();
+        output.resize(body + bound, 0);
+        let opts = CompressOptions::with_level(self.level).without_checksum();
+        let dst = output.get_mut(body..).unwrap_or_default();
+        let written = compress_to(input, dst, &opts).map_err(external)?;
+        output.truncate(body + written);
+        if LENGTH_OF_PREFIX_DATA + written > input.len() {
+            output.truncate(start);
+            output.extend_from_slice(&LENGTH_NO_COMPRESSED_DATA.to_le_bytes());
+            output.extend_from_slice(input);
+        }
+        Ok(output.len() - start)
+    }
+
+    /// Decompresses a buffer written by [`compress_to_vec`](Self::compress_to_vec).
+    ///
+    /// A raw buffer is returned as a slice of `input`, without copying.
+    pub fn decompress_to_buffer(&self, input: &Buffer) -> Result<Buffer, ArrowError> {
+        if input.is_empty() {
+            return Ok(Buffer::from_vec(Vec::<u8>::new()));
+        }
+        let prefix = input
+            .first_chunk::<LENGTH_OF_PREFIX_DATA>()
+            .ok_or_else(|| ArrowError::IpcError("buffer shorter than its length prefix".into()))?;
+        let length = i64::from_le_bytes(*prefix);
+        if length == LENGTH_NO_COMPRESSED_DATA {
+            return Ok(input.slice(LENGTH_OF_PREFIX_DATA));
+        }
+        let compressed = input.get(LENGTH_OF_PREFIX_DATA..).unwrap_or_default();
+        // Check the prefix against the frame before allocating for it.
+        let length = usize::try_from(length)
+            .ok()
+            .filter(|&l| decompressed_size(compressed).ok() == Some(l as u64))
+            .ok_or_else(|| {
+                ArrowError::IpcError(format!("Invalid uncompressed length: {length}"))
+            })?;
+        let mut out = Vec::new();
+        out.try_reserve_exact(length)
+            .map_err(|_| ArrowError::MemoryError(format!("cannot allocate {length} bytes")))?;
+        out.resize(length, 0);
+        let opts = DecompressOptions {
+            verify_checksum: false,
+            ..Default::default()
+        };
+        let written = decompress_to(compressed, &mut out, &opts).map_err(external)?;
+        if written != length {
+            return Err(ArrowError::IpcError(format!(
+                "Expected compressed length of {length} got {written}"
+            )));
+        }
+        Ok(Buffer::from_vec(out))
+    }
+}
+
+fn external(e: crate::Error) -> ArrowError {
+    ArrowError::ExternalError(std::boxed::Box::new(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_codec() {
+        let codec = ArrowCodec::new(Level::Fast);
+        let column: Vec<i32> = (0..50_000).map(|i| i % 1000).collect();
+        let column = Buffer::from_vec(column);
+
+        let mut body = std::vec![0xAA];
+        let n = codec.compress_to_vec(&column, &mut body).unwrap();
+        assert_eq!(n, body.len() - 1);
+        assert_eq!(&body[1..9], &(column.len() as i64).to_le_bytes());
+        let body = Buffer::from_vec(body).slice(1);
+        assert_eq!(codec.decompress_to_buffer(&body).unwrap(), column);
+
+        // Too short to compress: stored raw behind -1.
+        let mut body = Vec::new();
+        codec.compress_to_vec(b"abc", &mut body).unwrap();
+        assert_eq!(&body[..8], &(-1i64).to_le_bytes());
+        let body = Buffer::from_vec(body);
+        assert_eq!(
+            codec.decompress_to_buffer(&body).unwrap().as_slice(),
+            b"abc"
+        );
+
+        let mut body = Vec::new();
+        assert_eq!(codec.compress_to_vec(&[], &mut body).unwrap(), 0);
+        assert!(
+            codec
+                .decompress_to_buffer(&Buffer::from_vec(body))
+                .unwrap()
+                .is_empty()
+        );
+
+        // A wrong length prefix or a truncated prefix is an error.
+        let mut body = Vec::new();
+        codec.compress_to_vec(&column, &mut body).unwrap();
+        body[0] ^= 1;
+        assert!(codec.decompress_to_buffer(&Buffer::from_vec(body)).is_err());
+        assert!(
+            codec
+                .decompress_to_buffer(&Buffer::from_vec(std::vec![1u8, 2]))
+                .is_err()
+        );
+    }
+}
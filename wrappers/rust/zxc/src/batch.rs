@@ -0,0 +1,228 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Record batches: many small records compressed as one block.
+//!
+//! A batch is a [`message::Frame`] record whose message is a table of
+//! record end offsets followed by the records back to back, so the records
+//! share one block and its header, and repeated fields across records
+//! compress against each other. All integers are little-endian:
+//!
+//! ```text
+//! 0x00    4  Record count n
+//! 0x04  4*n  End offset of each record in the data, non-decreasing
+//!  ...       The records, concatenated
+//! ```
+//!
+//! A batch is limited to the 2 MiB of a message, table included.
+
+use alloc::vec::Vec;
+
+use crate::message::Frame;
+use crate::{Error, Result, compress_block_bound};
+
+/// Compresses `records` into one batch, at the default level with a
+/// checksum. Use [`Frame::encode_batch`] to pick the level or to reuse
+/// the contexts across batches.
+///
+/// ```rust
+/// let records: Vec<String> = (0..500)
+///     .map(|i| format!(r#"{{"offset":{i},"topic":"orders","status":"paid"}}"#))
+///     .collect();
+/// let batch = zxc::compress_batch(&records)?;
+///
+/// let records_back = zxc::decompress_batch(&batch)?;
+/// assert_eq!(records_back.len(), 500);
+/// assert_eq!(records_back.get(42), Some(records[42].as_bytes()));
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn compress_batch<R: AsRef<[u8]>>(records: &[R]) -> Result<Vec<u8>> {
+    Frame::new().encode_batch(records)
+}
+
+/// Decompresses a batch made by [`compress_batch`] or
+/// [`Frame::encode_batch`].
+pub fn decompress_batch(batch: &[u8]) -> Result<Batch> {
+    Frame::new().decode_batch(batch)
+}
+
+impl Frame {
+    /// Compresses `records` into one batch record. See
+    /// [`compress_batch`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::TooLarge`] if the records and their table are over 2 MiB,
+    /// and the errors of [`Frame::encode`].
+    pub fn encode_batch<R: AsRef<[u8]>>(&mut self, records: &[R]) -> Result<Vec<u8>> {
+        let too_large = || Error::TooLarge(u64::MAX);
+        // The same record may be passed many times, so its lengths can add
+        // up past the address space.
+        let data = records
+            .iter()
+            .try_fold(0usize, |n, r| n.checked_add(r.as_ref().len()))
+            .ok_or_else(too_large)?;
+        let table = records
+            .len()
+            .checked_mul(4)
+            .and_then(|t| t.checked_add(4))
+            .ok_or_else(too_large)?;
+        let total = table.checked_add(data).ok_or_else(too_large)?;
+        if compress_block_bound(total) == 0 {
+            return Err(Error::TooLarge(total as u64));
+        }
+
+        let mut msg = Vec::new();
+        msg.try_reserve_exact(total).map_err(|_| Error::Memory)?;
+        // The count and the offsets fit in 32 bits: the batch is one block.
+        msg.extend_from_slice(&(records.len() as u32).to_le_bytes());
+        let mut end = 0u32;
+        for r in records {
+            end += r.as_ref().len() as u32;
+            msg.extend_from_slice(&end.to_le_bytes());
+        }
+        for r in records {
+            msg.extend_from_slice(r.as_ref());
+        }
+        self.encode(&msg)
+    }
+
+    /// Decompresses a batch record, which must span all of `batch`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::CorruptData`] if the offset table does not match the
+    /// records, and the errors of [`Frame::decode`].
+    pub fn decode_batch(&mut self, batch: &[u8]) -> Result<Batch> {
+        Batch::new(self.decode(batch)?)
+    }
+}
+
+/// The records of a decompressed batch, in one buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    buf: Vec<u8>,
+    count: usize,
+}
+
+impl Batch {
+    /// Checks the table of a decompressed batch message.
+    fn new(buf: Vec<u8>) -> Result<Self> {
+        let count = read_u32(&buf, 0).ok_or(Error::CorruptData)? as usize;
+        let data = count
+            .checked_mul(4)
+            .and_then(|t| t.checked_add(4))
+            .filter(|&d| d <= buf.len())
+            .ok_or(Error::CorruptData)?;
+        let mut prev = 0;
+        for i in 0..count {
+            let end = read_u32(&buf, 4 + 4 * i).ok_or(Error::CorruptData)?;
+            if end < prev {
+                return Err(Error::CorruptData);
+            }
+            prev = end;
+        }
+        if prev as usize != buf.len() - data {
+            return Err(Error::CorruptData);
+        }
+        Ok(Self { buf, count })
+    }
+
+    /// The number of records.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether the batch holds no record.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Record `i`, if there is one.
+    pub fn get(&self, i: usize) -> Option<&[u8]> {
+        if i >= self.count {
+            return None;
+        }
+        let start = match i {
+            0 => 0,
+            _ => read_u32(&self.buf, 4 * i)?,
+        };
+        let end = read_u32(&self.buf, 4 + 4 * i)?;
+        let data = 4 + 4 * self.count;
+        self.buf.get(data + start as usize..data + end as usize)
+    }
+
+    /// The records, in order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &[u8]> + '_ {
+        (0..self.count).map(|i| self.get(i).unwrap_or_default())
+    }
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    let bytes = buf.get(at..at.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::*;
+    use crate::Level;
+    use crate::message::frame_len;
+
+    #[test]
+    fn test_batches() {
+        let records: Vec<String> = (0..1000)
+            .map(|i| {
+                format!(
+                    r#"{{"offset":{i},"key":"user-{}","event":"page_view","path":"/home"}}"#,
+                    i % 37
+                )
+            })
+            .collect();
+        let batch = compress_batch(&records).unwrap();
+        assert_eq!(frame_len(&batch), Some(batch.len()));
+        #[cfg(not(feature = "mock-backend"))]
+        {
+            let raw: usize = records.iter().map(String::len).sum();
+            assert!(batch.len() * 4 < raw, "{} of {raw} bytes", batch.len());
+        }
+
+        let back = decompress_batch(&batch).unwrap();
+        assert_eq!(back.len(), records.len());
+        assert!(back.iter().eq(records.iter().map(String::as_bytes)));
+        assert_eq!(back.get(999), Some(records[999].as_bytes()));
+        assert_eq!(back.get(1000), None);
+
+        // Empty records, and empty batches.
+        let mut frame = Frame::with_level(Level::Fastest).without_checksum();
+        let odd: [&[u8]; 4] = [b"", b"a", b"", b"bc"];
+        let record = frame.encode_batch(&odd).unwrap();
+        let back = frame.decode_batch(&record).unwrap();
+        assert!(back.iter().eq(odd));
+        let back = decompress_batch(&compress_batch::<&[u8]>(&[]).unwrap()).unwrap();
+        assert!(back.is_empty() && back.iter().next().is_none());
+
+        // Over the message limit.
+        let big = [vec![0u8; 1 << 20], vec![0u8; 1 << 20]];
+        assert!(matches!(compress_batch(&big), Err(Error::TooLarge(_))));
+
+        // Tables that do not match the data.
+        for msg in [
+            &[][..],
+            &[2, 0, 0, 0, 1, 0, 0, 0][..],
+            &[2, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, b'a', b'b'][..],
+            &[1, 0, 0, 0, 3, 0, 0, 0, b'a', b'b'][..],
+        ] {
+            let record = Frame::new().encode(msg).unwrap();
+            assert!(matches!(decompress_batch(&record), Err(Error::CorruptData)));
+        }
+    }
+}
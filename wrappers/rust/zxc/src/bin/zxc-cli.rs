@@ -0,0 +1,677 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! `zxc-cli`, the `zxc` command line built from the Rust crate (the `cli`
+//! feature).
+//!
+//! It accepts the C CLI's options for compressing, decompressing, testing
+//! and listing files, so scripts can call either one. Invoked as `unzxc` it
+//! decompresses by default, and as `zxccat` it decompresses to stdout.
+//! Benchmarking, dictionaries, block sizes and recursion are left to the C
+//! CLI.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use zxc::{
+    CompressOptions, DecompressOptions, FOOTER_SIZE, FRAME_HEADER_SIZE, Level,
+    StreamCompressOptions, StreamDecompressOptions,
+};
+
+/// Buffer in front of stdout when decompressing.
+const STDOUT_BUFFER_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Compress,
+    Decompress,
+    Test,
+    List,
+    Cat,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Cli {
+    mode: Mode,
+    level: Level,
+    threads: Option<usize>,
+    checksum: bool,
+    seekable: bool,
+    keep: bool,
+    force: bool,
+    stdout: bool,
+    multiple: bool,
+    quiet: bool,
+    verbose: bool,
+    output: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Run(Cli),
+    Help,
+    Version,
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args_os();
+    let argv0 = args.next().unwrap_or_else(|| "zxc-cli".into());
+    match parse(&argv0, args) {
+        Ok(Command::Run(cli)) => match run(&cli) {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        },
+        Ok(Command::Help) => {
+            print_help(&argv0);
+            ExitCode::SUCCESS
+        }
+        Ok(Command::Version) => {
+            println!(
+                "ZXC CLI (Rust, {}-bit) v{}, by Bertrand Lebonnois\nBSD 3-Clause License",
+                usize::BITS,
+                zxc::version_string()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(msg) => {
+            eprintln!("Error: {msg}\nFor help, type: {} -h", program(&argv0));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn program(argv0: &OsStr) -> String {
+    Path::new(argv0)
+        .file_name()
+        .unwrap_or(argv0)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn print_help(argv0: &OsStr) {
+    println!(
+        "Usage: {} [<options>] [<argument>]...\n\n\
+         Standard Modes:\n  \
+           -z, --compress    Compress FILE {{default}}\n  \
+           -d, --decompress  Decompress FILE (or stdin -> stdout)\n  \
+           -l, --list        List archive info\n  \
+           -t, --test        Test compressed FILE integrity\n  \
+           cat               Decompress FILEs (or stdin) to stdout\n\n\
+         Batch Processing:\n  \
+           -m, --multiple    Multiple input files\n\n\
+         Special Options:\n  \
+           -V, --version     Show version information\n  \
+           -h, --help        Show this help message\n\n\
+         Options:\n  \
+           -1..-7            Compression level {{3}}\n  \
+           -T, --threads N   Number of threads (0=auto)\n  \
+           -C, --checksum    Enable checksum {{default}}\n  \
+           -N, --no-checksum Disable checksum\n  \
+           -S, --seekable    Append seek table for random-access decompression\n  \
+           -o, --output FILE Write output to FILE (else derived from input)\n  \
+           -k, --keep        Keep input file\n  \
+           -f, --force       Force overwrite\n  \
+           -c, --stdout      Write to stdout\n  \
+           -v, --verbose     Verbose mode\n  \
+           -q, --quiet       Quiet mode",
+        program(argv0)
+    );
+}
+
+// =============================================================================
+// Arguments
+// =============================================================================
+
+/// Parses the arguments after `argv0` the way the C CLI's `getopt_long`
+/// does: grouped short options (`-dkf`), attached or separate values (`-T4`,
+/// `-T 4`, `--threads=4`), `--` to end the options, and `-` for stdin.
+fn parse(argv0: &OsStr, args: impl IntoIterator<Item = OsString>) -> Result<Command, String> {
+    let name = program(argv0);
+    let mut cli = Cli {
+        mode: match () {
+            _ if name.contains("zxccat") => Mode::Cat,
+            _ if name.contains("unzxc") => Mode::Decompress,
+            _ => Mode::Compress,
+        },
+        level: Level::Default,
+        threads: None,
+        checksum: true,
+        seekable: false,
+        keep: false,
+        force: false,
+        stdout: false,
+        multiple: false,
+        quiet: false,
+        verbose: false,
+        output: None,
+        files: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        let Some(s) = arg.to_str().filter(|s| s.len() > 1 && s.starts_with('-')) else {
+            positional.push(arg);
+            continue;
+        };
+        if s == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+        if let Some(long) = s.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(OsString::from(value))),
+                None => (long, None),
+            };
+            let flag = long_flag(name).ok_or_else(|| format!("unknown option '--{name}'"))?;
+            let value = match (takes_value(flag), value) {
+                (true, Some(v)) => Some(v),
+                (true, None) => Some(args.next().ok_or_else(|| missing(flag))?),
+                (false, None) => None,
+                (false, Some(_)) => return Err(format!("option '--{name}' takes no value")),
+            };
+            if let Some(done) = apply(&mut cli, flag, value)? {
+                return Ok(done);
+            }
+            continue;
+        }
+        let shorts = s.get(1..).unwrap_or_default();
+        for (i, flag) in shorts.char_indices() {
+            if takes_value(flag) {
+                let rest = shorts.get(i + flag.len_utf8()..).unwrap_or_default();
+                let value = match rest {
+                    "" => args.next().ok_or_else(|| missing(flag))?,
+                    _ => rest.into(),
+                };
+                if let Some(done) = apply(&mut cli, flag, Some(value))? {
+                    return Ok(done);
+                }
+                break;
+            }
+            if let Some(done) = apply(&mut cli, flag, None)? {
+                return Ok(done);
+            }
+        }
+    }
+
+    // A leading mode word, as in `zxc d file.zxc`.
+    let mode = positional.first().and_then(|w| match w.to_str()? {
+        "z" => Some(Mode::Compress),
+        "d" => Some(Mode::Decompress),
+        "l" | "list" => Some(Mode::List),
+        "t" | "test" => Some(Mode::Test),
+        "cat" => Some(Mode::Cat),
+        _ => None,
+    });
+    if let Some(mode) = mode {
+        cli.mode = mode;
+        positional.remove(0);
+    }
+    cli.files = positional.into_iter().map(PathBuf::from).collect();
+    Ok(Command::Run(cli))
+}
+
+fn long_flag(name: &str) -> Option<char> {
+    Some(match name {
+        "compress" => 'z',
+        "decompress" => 'd',
+        "list" => 'l',
+        "test" => 't',
+        "threads" => 'T',
+        "checksum" => 'C',
+        "no-checksum" => 'N',
+        "seekable" => 'S',
+        "output" => 'o',
+        "keep" => 'k',
+        "force" => 'f',
+        "stdout" => 'c',
+        "multiple" => 'm',
+        "verbose" => 'v',
+        "quiet" => 'q',
+        "version" => 'V',
+        "help" => 'h',
+        _ => return None,
+    })
+}
+
+fn takes_value(flag: char) -> bool {
+    matches!(flag, 'T' | 'o')
+}
+
+fn missing(flag: char) -> String {
+    format!("option '-{flag}' requires a value")
+}
+
+/// Applies one option; returns the command to run at once for `-h`/`-V`.
+fn apply(cli: &mut Cli, flag: char, value: Option<OsString>) -> Result<Option<Command>, String> {
+    let value = value.unwrap_or_default();
+    match flag {
+        'z' => cli.mode = Mode::Compress,
+        'd' => cli.mode = Mode::Decompress,
+        'l' => cli.mode = Mode::List,
+        't' => cli.mode = Mode::Test,
+        '1'..='7' => {
+            let n = flag as usize - '1' as usize;
+            cli.level = Level::all().get(n).copied().unwrap_or_default();
+        }
+        'T' => {
+            let n = value
+                .to_str()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|&n| n <= zxc_sys::ZXC_MAX_THREADS)
+                .ok_or_else(|| {
+                    format!(
+                        "num_threads must be between 0 and {}",
+                        zxc_sys::ZXC_MAX_THREADS
+                    )
+                })?;
+            cli.threads = (n > 0).then_some(n);
+        }
+        'C' => cli.checksum = true,
+        'N' => cli.checksum = false,
+        'S' => cli.seekable = true,
+        'o' => cli.output = Some(value.into()),
+        'k' => cli.keep = true,
+        'f' => cli.force = true,
+        'c' => cli.stdout = true,
+        'm' => cli.multiple = true,
+        'v' => cli.verbose = true,
+        'q' => cli.quiet = true,
+        'V' => return Ok(Some(Command::Version)),
+        'h' => return Ok(Some(Command::Help)),
+        _ => return Err(format!("unknown option '-{flag}'")),
+    }
+    Ok(None)
+}
+
+// =============================================================================
+// Modes
+// =============================================================================
+
+/// Runs `cli`, reporting errors on stderr; returns whether all went well.
+fn run(cli: &Cli) -> bool {
+    let report = |path: Option<&Path>, err: &dyn std::fmt::Display| {
+        if !cli.quiet {
+            eprintln!("Error: {}: {err}", display(path));
+        }
+        false
+    };
+    match cli.mode {
+        Mode::List | Mode::Test if cli.files.is_empty() => {
+            report(None, &"this mode requires an input file")
+        }
+        Mode::List => cli.files.iter().fold(true, |ok, f| match list(cli, f) {
+            Ok(()) => ok,
+            Err(e) => report(Some(f), &e),
+        }),
+        Mode::Test => cli.files.iter().filter(|f| !test(cli, f)).count() == 0,
+        Mode::Cat => {
+            let mut out = BufWriter::with_capacity(STDOUT_BUFFER_SIZE, io::stdout().lock());
+            let inputs = inputs(&cli.files);
+            let opts = decompress_options(cli);
+            let mut ok = true;
+            for f in inputs {
+                let decoded = match f {
+                    Some(path) => zxc::decompress_to_writer(path, &mut out, Some(&opts)),
+                    None => zxc::decompress_stream(io::stdin().lock(), &mut out, Some(&opts)),
+                };
+                match decoded {
+                    Ok(_) => {}
+                    // The reader went away (`zxccat big.zxc | head`): stop quietly.
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return ok,
+                    Err(e) => ok = report(f, &e),
+                }
+            }
+            match out.flush() {
+                Ok(()) => ok,
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ok,
+                Err(e) => report(None, &e),
+            }
+        }
+        Mode::Compress | Mode::Decompress => {
+            if cli.multiple && cli.stdout {
+                return report(None, &"cannot write to stdout with multiple files (-m)");
+            }
+            if cli.multiple && cli.output.is_some() {
+                return report(None, &"cannot use -o with multiple files (-m)");
+            }
+            // Without -m, a second name is the output file.
+            let (inputs, output) = match cli.multiple || cli.stdout {
+                true => (inputs(&cli.files), None),
+                false => (
+                    inputs(cli.files.get(..1).unwrap_or_default()),
+                    cli.output
+                        .as_deref()
+                        .or(cli.files.get(1).map(PathBuf::as_path)),
+                ),
+            };
+            inputs
+                .iter()
+                .fold(true, |ok, f| match process(cli, *f, output) {
+                    Ok(()) => ok,
+                    Err(e) => report(*f, &e),
+                })
+        }
+    }
+}
+
+/// The input files, with `None` for stdin (no file, or `-`).
+fn inputs(files: &[PathBuf]) -> Vec<Option<&Path>> {
+    match files {
+        [] => vec![None],
+        _ => files
+            .iter()
+            .map(|f| (f.as_os_str() != "-").then_some(f.as_path()))
+            .collect(),
+    }
+}
+
+fn display(path: Option<&Path>) -> String {
+    path.map_or_else(|| "<stdin>".into(), |p| p.display().to_string())
+}
+
+fn open(path: Option<&Path>) -> io::Result<Box<dyn Read>> {
+    Ok(match path {
+        Some(p) => Box::new(File::open(p)?),
+        None => Box::new(io::stdin().lock()),
+    })
+}
+
+fn compress_options(cli: &Cli) -> CompressOptions {
+    CompressOptions {
+        level: cli.level,
+        checksum: cli.checksum,
+        ..Default::default()
+    }
+}
+
+fn decompress_options(cli: &Cli) -> DecompressOptions {
+    let mut opts = DecompressOptions::default();
+    opts.verify_checksum = cli.checksum;
+    opts
+}
+
+/// Compresses or decompresses one input to a file or to stdout.
+fn process(cli: &Cli, input: Option<&Path>, output: Option<&Path>) -> Result<(), String> {
+    let compress = cli.mode == Mode::Compress;
+    let to_stdout = cli.stdout || (input.is_none() && output.is_none());
+    let started = Instant::now();
+    let written = match to_stdout {
+        true => {
+            let stdout = io::stdout();
+            if compress && stdout.is_terminal() && !cli.force {
+                return Err("refusing to write compressed data to terminal".into());
+            }
+            if compress && cli.seekable {
+                return Err("a seek table needs an output file".into());
+            }
+            let reader = open(input).map_err(|e| e.to_string())?;
+            let mut out = BufWriter::with_capacity(STDOUT_BUFFER_SIZE, stdout.lock());
+            let written = match compress {
+                true => zxc::compress_stream(reader, &mut out, Some(&compress_options(cli))),
+                false => zxc::decompress_stream(reader, &mut out, Some(&decompress_options(cli))),
+            };
+            written.and_then(|n| out.flush().map(|()| n))
+        }
+        false => {
+            // Without -o, stdin goes to stdout, so `input` is a file here.
+            let target = match (output, input) {
+                (Some(out), _) => out.to_owned(),
+                (None, input) => output_name(input.unwrap_or(Path::new("-")), compress)?,
+            };
+            check_output(input, &target, cli.force)?;
+            let written = match input {
+                Some(input) => to_file(cli, input, &target),
+                None => {
+                    let mut out = File::create(&target).map_err(|e| e.to_string())?;
+                    let stdin = io::stdin().lock();
+                    match compress {
+                        true => zxc::compress_stream(stdin, &mut out, Some(&compress_options(cli))),
+                        false => {
+                            zxc::decompress_stream(stdin, &mut out, Some(&decompress_options(cli)))
+                        }
+                    }
+                }
+            };
+            if written.is_err() {
+                let _ = fs::remove_file(&target);
+            }
+            written
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    if cli.verbose && !cli.quiet {
+        let elapsed = started.elapsed();
+        eprintln!(
+            "Processed {written} bytes in {:.3}s ({})",
+            elapsed.as_secs_f64(),
+            zxc::report::Throughput::new(written, elapsed)
+        );
+    }
+    if let Some(input) = input {
+        if !to_stdout && !cli.keep && output.is_none() {
+            fs::remove_file(input).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the multi-threaded file API from `input` to `output`.
+fn to_file(cli: &Cli, input: &Path, output: &Path) -> io::Result<u64> {
+    let written = match cli.mode {
+        Mode::Compress => zxc::compress_file_with_options(
+            input,
+            output,
+            &StreamCompressOptions {
+                level: cli.level,
+                threads: cli.threads,
+                checksum: cli.checksum,
+                seekable: cli.seekable,
+                ..Default::default()
+            },
+        ),
+        _ => zxc::decompress_file_with_options(
+            input,
+            output,
+            &StreamDecompressOptions {
+                threads: cli.threads,
+                verify_checksum: cli.checksum,
+                ..Default::default()
+            },
+        ),
+    };
+    written.map_err(io::Error::other)
+}
+
+/// `FILE.zxc` when compressing, `FILE` for `FILE.zxc` when decompressing.
+fn output_name(input: &Path, compress: bool) -> Result<PathBuf, String> {
+    let mut name = input.as_os_str().to_owned();
+    if compress {
+        name.push(".zxc");
+        return Ok(name.into());
+    }
+    match input.extension() {
+        Some(ext) if ext == "zxc" && input.file_stem().is_some() => Ok(input.with_extension("")),
+        _ => Err(format!(
+            "cannot determine output filename: '{}' does not end with .zxc",
+            input.display()
+        )),
+    }
+}
+
+/// Refuses to overwrite an existing file without `-f`, or the input itself.
+fn check_output(input: Option<&Path>, output: &Path, force: bool) -> Result<(), String> {
+    if !output.exists() {
+        return Ok(());
+    }
+    if let Some(input) = input {
+        if fs::canonicalize(input).ok() == fs::canonicalize(output).ok() {
+            return Err("input and output files are identical".into());
+        }
+    }
+    match force {
+        true => Ok(()),
+        false => Err(format!(
+            "output exists. Use -f to overwrite '{}'",
+            output.display()
+        )),
+    }
+}
+
+/// Decodes `path` in full, printing `OK` or the reason it failed.
+fn test(cli: &Cli, path: &Path) -> bool {
+    let started = Instant::now();
+    match zxc::verify_file(path) {
+        Ok(report) => {
+            println!("{}: OK", path.display());
+            if cli.verbose {
+                let checksum = match report.checksummed {
+                    true => "verified (RapidHash)",
+                    false => "not verified",
+                };
+                println!(
+                    "  Checksum:     {checksum}\n  Time:         {:.3}s",
+                    started.elapsed().as_secs_f64()
+                );
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("{}: FAILED ({e})", path.display());
+            false
+        }
+    }
+}
+
+/// Prints the sizes, ratio, checksum and dictionary of an archive, from its
+/// header and footer.
+fn list(cli: &Cli, path: &Path) -> Result<(), String> {
+    let fail = |e: io::Error| e.to_string();
+    let size = zxc::file_decompressed_size(path).map_err(|e| match e {
+        zxc::StreamError::Io(e) => e.to_string(),
+        _ => "not a valid ZXC archive".to_string(),
+    })?;
+    let mut f = File::open(path).map_err(fail)?;
+    let compressed = f.metadata().map_err(fail)?.len();
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    f.read_exact(&mut header).map_err(fail)?;
+    let mut footer = [0u8; FOOTER_SIZE];
+    f.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))
+        .and_then(|_| f.read_exact(&mut footer))
+        .map_err(fail)?;
+
+    let checksum = match footer.get(8..12).and_then(|c| c.try_into().ok()) {
+        Some(c) if u32::from_le_bytes(c) != 0 => "RapidHash",
+        _ => "-",
+    };
+    let dict = match zxc::get_dict_id(&header) {
+        0 => "-".into(),
+        id => format!("0x{id:08X}"),
+    };
+    let ratio = match compressed {
+        0 => 0.0,
+        n => size as f64 / n as f64,
+    };
+    println!(
+        "\n  {:>12}   {:>12}   {:>5}   {:<10}   {:<10}   Filename",
+        "Compressed", "Uncompressed", "Ratio", "Checksum", "Dict ID"
+    );
+    println!(
+        "  {:>12}   {:>12}   {ratio:5.2}   {checksum:<10}   {dict:<10}   {}",
+        decimal_size(compressed),
+        decimal_size(size),
+        path.display()
+    );
+    // -lv: the header, every block and the footer.
+    if cli.verbose {
+        println!();
+        let file = BufReader::new(File::open(path).map_err(fail)?);
+        zxc::debug::dump(file, io::stdout().lock()).map_err(fail)?;
+    }
+    Ok(())
+}
+
+fn decimal_size(bytes: u64) -> String {
+    let units = [(1e12, "TB"), (1e9, "GB"), (1e6, "MB"), (1e3, "KB")];
+    match units.iter().find(|(unit, _)| bytes as f64 >= *unit) {
+        Some((unit, name)) => format!("{:.1} {name}", bytes as f64 / unit),
+        None => format!("{bytes} B"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(argv0: &str, args: &[&str]) -> Result<Command, String> {
+        parse(argv0.as_ref(), args.iter().map(OsString::from))
+    }
+
+    fn cli(argv0: &str, args: &[&str]) -> Cli {
+        match parse_args(argv0, args) {
+            Ok(Command::Run(cli)) => cli,
+            other => panic!("{args:?}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let c = cli("zxc-cli", &["-dkfT4", "-5", "--no-checksum", "in.zxc"]);
+        assert_eq!(c.mode, Mode::Decompress);
+        assert!(c.keep && c.force && !c.checksum);
+        assert_eq!((c.threads, c.level), (Some(4), Level::Compact));
+        assert_eq!(c.files, [PathBuf::from("in.zxc")]);
+
+        let c = cli(
+            "zxc-cli",
+            &["-T", "0", "-o", "out.zxc", "--threads=2", "-", "--", "-k"],
+        );
+        assert_eq!(c.threads, Some(2));
+        assert_eq!(c.output, Some(PathBuf::from("out.zxc")));
+        assert_eq!(c.files, [PathBuf::from("-"), PathBuf::from("-k")]);
+        assert!(!c.keep);
+
+        // Mode words and program names.
+        assert_eq!(cli("zxc-cli", &["t", "a.zxc"]).mode, Mode::Test);
+        assert_eq!(cli("zxc-cli", &["cat", "a.zxc"]).files.len(), 1);
+        assert_eq!(cli("/usr/bin/unzxc", &["a.zxc"]).mode, Mode::Decompress);
+        assert_eq!(cli("zxccat", &[]).mode, Mode::Cat);
+        assert_eq!(cli("unzxc", &["-z"]).mode, Mode::Compress);
+
+        assert_eq!(parse_args("zxc", &["-kh"]), Ok(Command::Help));
+        assert_eq!(parse_args("zxc", &["--version"]), Ok(Command::Version));
+        for bad in [
+            &["-x"][..],
+            &["-T"],
+            &["-T", "9999"],
+            &["--keep=1"],
+            &["--nope"],
+        ] {
+            assert!(parse_args("zxc", bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_output_names() {
+        let p = Path::new;
+        assert_eq!(output_name(p("a.txt"), true).unwrap(), p("a.txt.zxc"));
+        assert_eq!(
+            output_name(p("dir/a.txt.zxc"), false).unwrap(),
+            p("dir/a.txt")
+        );
+        assert!(output_name(p("a.txt"), false).is_err());
+        assert!(output_name(p(".zxc"), false).is_err());
+        assert_eq!(decimal_size(999), "999 B");
+        assert_eq!(decimal_size(1_500_000), "1.5 MB");
+    }
+}
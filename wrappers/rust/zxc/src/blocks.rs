@@ -0,0 +1,286 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Block-by-block decompression of a frame in memory.
+//!
+//! Blocks of a frame decode independently, so [`Blocks`] hands the data
+//! out one block at a time, decoding each when asked: a consumer that
+//! processes the data as it comes holds one block (at most 2 MiB) rather
+//! than the whole payload.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_HEADER_SIZE, FILE_FOOTER_SIZE, FILE_HEADER_SIZE,
+    FLAG_HAS_CHECKSUM, FLAG_HAS_DICTIONARY,
+};
+use crate::inspect::{check_version, header};
+use crate::locate::checksum_mismatch;
+use crate::scratch::Scratch;
+use crate::{Dctx, DecompressOptions, Error, Result, decompress_block_bound};
+
+/// Decompresses `compressed`, one frame, lazily: each item is the data of
+/// the next block. See [`Blocks`].
+///
+/// ```rust
+/// let data = vec![b'z'; 2 << 20];
+/// let frame = zxc::compress(&data, zxc::Level::Default, None)?;
+///
+/// let mut total = 0;
+/// for block in zxc::decompress_blocks(&frame) {
+///     total += block?.len();
+/// }
+/// assert_eq!(total, data.len());
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn decompress_blocks(compressed: &[u8]) -> Blocks<'_> {
+    Blocks::new(compressed, &DecompressOptions::default())
+}
+
+/// An iterator over the decompressed blocks of a frame.
+///
+/// As an [`Iterator`], each block comes as a `Vec` of its own. To reuse
+/// one buffer for all blocks instead, call [`Blocks::next_block`], which
+/// lends the block out until the next call.
+///
+/// The first error ends the iteration. Errors in a block come as
+/// [`Error::Corrupted`] with its index and offset; a frame that is
+/// truncated or whose footer disagrees with the blocks fails after the
+/// blocks before the damage were returned. Each block's checksum is
+/// verified as it is decoded, but not the frame's global hash in the
+/// footer, which needs all blocks.
+pub struct Blocks<'a> {
+    frame: &'a [u8],
+    opts: DecompressOptions,
+    /// Set up from the file header by the first call.
+    state: Option<State>,
+    /// Offset of the next block header.
+    pos: usize,
+    index: u32,
+    total: u64,
+    done: bool,
+}
+
+/// What decoding needs once the file header is read.
+struct State {
+    dctx: Dctx,
+    out: Scratch,
+    block_opts: DecompressOptions,
+    has_checksum: bool,
+}
+
+impl<'a> Blocks<'a> {
+    /// Iterates over the blocks of `compressed` decoded with `opts`: its
+    /// checksum setting and dictionary.
+    pub fn new(compressed: &'a [u8], opts: &DecompressOptions) -> Self {
+        Self {
+            frame: compressed,
+            opts: opts.clone(),
+            state: None,
+            pos: FILE_HEADER_SIZE,
+            index: 0,
+            total: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes the next block into a buffer of the iterator's and returns
+    /// it, or `None` after the last block or an error.
+    pub fn next_block(&mut self) -> Option<Result<&[u8]>> {
+        if self.done {
+            return None;
+        }
+        match self.step() {
+            Ok(Some(n)) => {
+                let out = self.state.as_ref().map(|s| &s.out[..]).unwrap_or_default();
+                Some(Ok(out.get(..n).unwrap_or_default()))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Decodes the next block; returns its size, or `None` at the end of the
+    /// frame.
+    fn step(&mut self) -> Result<Option<usize>> {
+        if self.state.is_none() {
+            self.state = Some(self.start()?);
+        }
+        let Some(state) = self.state.as_mut() else {
+            return Ok(None);
+        };
+        let body = self
+            .frame
+            .len()
+            .checked_sub(FILE_FOOTER_SIZE)
+            .and_then(|end| self.frame.get(..end))
+            .unwrap_or_default();
+        let pos = self.pos;
+        let corrupted = |index, source| Error::Corrupted {
+            block: index,
+            offset: pos as u64,
+            source: Box::new(source),
+        };
+        let Some(header) = body
+            .get(pos..)
+            .and_then(<[u8]>::first_chunk::<BLOCK_HEADER_SIZE>)
+        else {
+            return Err(corrupted(Some(self.index), Error::SrcTooSmall));
+        };
+        if header[0] == BLOCK_EOF {
+            let footer = self.frame.get(body.len()..).unwrap_or_default();
+            let size = footer
+                .first_chunk::<8>()
+                .map_or(u64::MAX, |s| u64::from_le_bytes(*s));
+            if size != self.total {
+                return Err(corrupted(None, Error::CorruptData));
+            }
+            return Ok(None);
+        }
+        let comp_size = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as usize;
+        let payload_end = pos
+            .saturating_add(BLOCK_HEADER_SIZE)
+            .saturating_add(comp_size);
+        let end = payload_end.saturating_add(match state.has_checksum {
+            true => BLOCK_CHECKSUM_SIZE,
+            false => 0,
+        });
+        let (Some(framed), Some(payload)) = (body.get(pos..end), body.get(pos..payload_end)) else {
+            return Err(corrupted(Some(self.index), Error::SrcTooSmall));
+        };
+        let block = match state.block_opts.verify_checksum {
+            true => framed,
+            false => payload,
+        };
+        let n = state
+            .dctx
+            .decompress_block(block, &mut state.out, &state.block_opts)
+            .map_err(|e| match e {
+                Error::BadChecksum => checksum_mismatch(self.index, framed).unwrap_or(e),
+                e => e,
+            })
+            .map_err(|e| corrupted(Some(self.index), e))?;
+        self.pos = end;
+        self.index += 1;
+        self.total += n as u64;
+        Ok(Some(n))
+    }
+
+    /// Reads the file header and sets up the decoder.
+    fn start(&self) -> Result<State> {
+        check_version(self.frame, self.opts.format_versions())?;
+        let mut src = self.frame;
+        let flags = header(&mut src)?;
+        if self.frame.len() < FILE_HEADER_SIZE + FILE_FOOTER_SIZE {
+            return Err(Error::SrcTooSmall);
+        }
+        if flags & FLAG_HAS_DICTIONARY != 0 && self.opts.dict.is_none() {
+            return Err(Error::DictRequired);
+        }
+        let has_checksum = flags & FLAG_HAS_CHECKSUM != 0;
+        let block_size = 1usize << self.frame.get(5).copied().unwrap_or_default();
+        Ok(State {
+            dctx: Dctx::new()?,
+            out: Scratch::zeroed(decompress_block_bound(block_size) as usize),
+            block_opts: DecompressOptions {
+                verify_checksum: self.opts.verify_checksum && has_checksum,
+                ..self.opts.clone()
+            },
+            has_checksum,
+        })
+    }
+}
+
+impl Iterator for Blocks<'_> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block().map(|r| r.map(<[u8]>::to_vec))
+    }
+}
+
+impl FusedIterator for Blocks<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_decompress_blocks() {
+        let data: Vec<u8> = (0..1_300_000u32).map(|i| (i % 253) as u8).collect();
+        let frame = crate::compress(&data, Level::Default, None).unwrap();
+        let blocks: Vec<Vec<u8>> = decompress_blocks(&frame).map(|b| b.unwrap()).collect();
+        assert_eq!(
+            blocks.iter().map(Vec::len).collect::<Vec<_>>(),
+            [512 << 10, 512 << 10, 1_300_000 - (1024 << 10)]
+        );
+        assert_eq!(blocks.concat(), data);
+
+        // Lending, with the checksums skipped.
+        let opts = DecompressOptions {
+            verify_checksum: false,
+            ..Default::default()
+        };
+        let mut blocks = Blocks::new(&frame, &opts);
+        let mut back = Vec::new();
+        while let Some(block) = blocks.next_block() {
+            back.extend_from_slice(block.unwrap());
+        }
+        assert_eq!(back, data);
+        assert!(blocks.next_block().is_none());
+
+        // The empty frame has no blocks.
+        let empty = crate::compress(b"", Level::Default, None).unwrap();
+        assert_eq!(decompress_blocks(&empty).count(), 0);
+    }
+
+    #[test]
+    fn test_decompress_blocks_errors() {
+        let data = vec![b'q'; 1 << 20];
+        let frame = crate::compress(&data, Level::Default, None).unwrap();
+
+        // A truncated frame yields the blocks before the cut, then fails.
+        let cut = &frame[..frame.len() - 30];
+        let results: Vec<_> = decompress_blocks(cut).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_deref().unwrap(), &data[..512 << 10]);
+        assert!(matches!(
+            results[1],
+            Err(Error::Corrupted { block: Some(1), .. })
+        ));
+
+        // So does a footer that disagrees with the blocks.
+        let mut lying = frame.clone();
+        let footer = lying.len() - FILE_FOOTER_SIZE;
+        lying[footer] ^= 1;
+        let results: Vec<_> = decompress_blocks(&lying).collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            results[2],
+            Err(Error::Corrupted { block: None, .. })
+        ));
+
+        assert!(matches!(
+            decompress_blocks(b"not a frame").next(),
+            Some(Err(Error::SrcTooSmall | Error::BadMagic))
+        ));
+        let mut bad = frame;
+        bad[0] ^= 1;
+        let mut blocks = decompress_blocks(&bad);
+        assert!(matches!(blocks.next(), Some(Err(Error::BadMagic))));
+        assert!(blocks.next().is_none());
+    }
+}
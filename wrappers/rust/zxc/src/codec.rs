@@ -0,0 +1,321 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! A content-codec interface for protocol stacks.
+//!
+//! [`ContentCodec`] is what an HTTP stack, a message bus or an RPC layer
+//! needs from a compression format: its name, one-shot `encode`/`decode`,
+//! and push-style streaming coders that turn input slices into output bytes
+//! appended to a `Vec`. It names no type of this crate, so a framework can
+//! keep gzip, zstd and zxc in one `Vec<Box<dyn ContentCodec>>` and pick by
+//! the negotiated name. [`ZxcCodec`] is the zxc implementation.
+//!
+//! ```rust
+//! use zxc::codec::{ContentCodec, ZxcCodec};
+//!
+//! let codecs: Vec<Box<dyn ContentCodec>> = vec![Box::new(ZxcCodec::new())];
+//! let codec = codecs.iter().find(|c| c.name() == "zxc").unwrap();
+//!
+//! let body = br#"{"event":"click","target":"buy"}"#.repeat(100);
+//! let mut encoder = codec.encoder()?;
+//! let mut wire = Vec::new();
+//! for chunk in body.chunks(1000) {
+//!     encoder.write(chunk, &mut wire)?;
+//! }
+//! encoder.finish(&mut wire)?;
+//!
+//! assert_eq!(codec.decode(&wire)?, body);
+//! # Ok::<(), zxc::codec::BoxError>(())
+//! ```
+//!
+//! Implementations for other formats only need the streaming coders; the
+//! one-shot methods default to running them over the whole input.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::scratch::Scratch;
+use crate::{CStream, CompressOptions, DStream, Error, Level};
+
+/// The error type of the codec traits.
+pub type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// A compression format as seen by a protocol stack. See the
+/// [module docs](self).
+pub trait ContentCodec: Send + Sync {
+    /// The format's token, as used in `Content-Encoding`.
+    fn name(&self) -> &'static str;
+
+    /// A coder compressing one stream.
+    fn encoder(&self) -> Result<Box<dyn StreamEncoder>, BoxError>;
+
+    /// A coder decompressing one stream.
+    fn decoder(&self) -> Result<Box<dyn StreamDecoder>, BoxError>;
+
+    /// Compresses `src` as one stream.
+    fn encode(&self, src: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let mut out = Vec::new();
+        let mut encoder = self.encoder()?;
+        encoder.write(src, &mut out)?;
+        encoder.finish(&mut out)?;
+        Ok(out)
+    }
+
+    /// Decompresses the stream in `src`.
+    fn decode(&self, src: &[u8]) -> Result<Vec<u8>, BoxError> {
+        let mut out = Vec::new();
+        let mut decoder = self.decoder()?;
+        decoder.write(src, &mut out)?;
+        decoder.finish(&mut out)?;
+        Ok(out)
+    }
+}
+
+/// Compresses a stream pushed in slices.
+pub trait StreamEncoder: Send {
+    /// Compresses `input`, appending whatever output is ready to `out`.
+    fn write(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError>;
+
+    /// Ends the stream, appending the rest of the output to `out`.
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), BoxError>;
+}
+
+/// Decompresses a stream pushed in slices.
+pub trait StreamDecoder: Send {
+    /// Decompresses `input`, appending whatever output is ready to `out`.
+    fn write(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError>;
+
+    /// Checks that the stream ended where the input did, appending any
+    /// output left to `out`.
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), BoxError>;
+}
+
+/// zxc as a [`ContentCodec`]: one frame per stream.
+#[derive(Debug, Clone, Copy)]
+pub struct ZxcCodec {
+    level: Level,
+    checksum: bool,
+}
+
+impl Default for ZxcCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZxcCodec {
+    /// The codec at the default level, with checksums.
+    pub fn new() -> Self {
+        Self::with_level(Level::Default)
+    }
+
+    /// The codec at `level`, with checksums.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            level,
+            checksum: true,
+        }
+    }
+
+    /// Leaves the checksums out of the frames this codec writes.
+    pub fn without_checksum(mut self) -> Self {
+        self.checksum = false;
+        self
+    }
+
+    fn options(&self) -> CompressOptions {
+        CompressOptions {
+            level: self.level,
+            checksum: self.checksum,
+            ..Default::default()
+        }
+    }
+}
+
+impl ContentCodec for ZxcCodec {
+    fn name(&self) -> &'static str {
+        "zxc"
+    }
+
+    fn encoder(&self) -> Result<Box<dyn StreamEncoder>, BoxError> {
+        let cs = CStream::new(Some(&self.options()))?;
+        let buf = Scratch::zeroed(cs.out_size());
+        Ok(Box::new(ZxcEncoder { cs, buf }))
+    }
+
+    fn decoder(&self) -> Result<Box<dyn StreamDecoder>, BoxError> {
+        let ds = DStream::new(None)?;
+        let buf = Scratch::zeroed(ds.out_size());
+        Ok(Box::new(ZxcDecoder { ds, buf }))
+    }
+
+    fn encode(&self, src: &[u8]) -> Result<Vec<u8>, BoxError> {
+        Ok(crate::compress_with_options(src, &self.options())?)
+    }
+
+    fn decode(&self, src: &[u8]) -> Result<Vec<u8>, BoxError> {
+        Ok(crate::decompress(src)?)
+    }
+}
+
+struct ZxcEncoder {
+    cs: CStream,
+    buf: Scratch,
+}
+
+impl StreamEncoder for ZxcEncoder {
+    fn write(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError> {
+        loop {
+            let p = self.cs.compress(input, &mut self.buf)?;
+            out.extend_from_slice(self.buf.get(..p.produced).unwrap_or_default());
+            input = input.get(p.consumed..).unwrap_or_default();
+            if input.is_empty() && p.pending == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), BoxError> {
+        loop {
+            let p = self.cs.end(&mut self.buf)?;
+            out.extend_from_slice(self.buf.get(..p.produced).unwrap_or_default());
+            if p.pending == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+struct ZxcDecoder {
+    ds: DStream,
+    buf: Scratch,
+}
+
+impl StreamDecoder for ZxcDecoder {
+    fn write(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError> {
+        while !input.is_empty() {
+            if self.ds.finished() {
+                return Err(Error::CorruptData.into());
+            }
+            let p = self.ds.decompress(input, &mut self.buf)?;
+            out.extend_from_slice(self.buf.get(..p.produced).unwrap_or_default());
+            input = input.get(p.consumed..).unwrap_or_default();
+            if p.consumed == 0 && p.produced == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), BoxError> {
+        while !self.ds.finished() {
+            let p = self.ds.decompress(&[], &mut self.buf)?;
+            out.extend_from_slice(self.buf.get(..p.produced).unwrap_or_default());
+            if p.produced == 0 && !self.ds.finished() {
+                return Err(Error::SrcTooSmall.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// A codec written against the traits alone, leaning on the defaults.
+    struct Identity;
+
+    impl ContentCodec for Identity {
+        fn name(&self) -> &'static str {
+            "identity"
+        }
+
+        fn encoder(&self) -> Result<Box<dyn StreamEncoder>, BoxError> {
+            Ok(Box::new(Identity))
+        }
+
+        fn decoder(&self) -> Result<Box<dyn StreamDecoder>, BoxError> {
+            Ok(Box::new(Identity))
+        }
+    }
+
+    impl StreamEncoder for Identity {
+        fn write(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError> {
+            out.extend_from_slice(input);
+            Ok(())
+        }
+
+        fn finish(&mut self, _: &mut Vec<u8>) -> Result<(), BoxError> {
+            Ok(())
+        }
+    }
+
+    impl StreamDecoder for Identity {
+        fn write(&mut self, input: &[u8], out: &mut Vec<u8>) -> Result<(), BoxError> {
+            out.extend_from_slice(input);
+            Ok(())
+        }
+
+        fn finish(&mut self, _: &mut Vec<u8>) -> Result<(), BoxError> {
+            Ok(())
+        }
+    }
+
+    fn stream(codec: &dyn ContentCodec, data: &[u8], chunk: usize) -> (Vec<u8>, Vec<u8>) {
+        let mut encoder = codec.encoder().unwrap();
+        let mut wire = Vec::new();
+        for c in data.chunks(chunk) {
+            encoder.write(c, &mut wire).unwrap();
+        }
+        encoder.finish(&mut wire).unwrap();
+
+        let mut decoder = codec.decoder().unwrap();
+        let mut back = Vec::new();
+        for c in wire.chunks(chunk) {
+            decoder.write(c, &mut back).unwrap();
+        }
+        decoder.finish(&mut back).unwrap();
+        (wire, back)
+    }
+
+    #[test]
+    fn test_content_codecs() {
+        let data = crate::fixtures::input().repeat(30);
+        let codecs: Vec<Box<dyn ContentCodec>> = vec![
+            Box::new(ZxcCodec::new()),
+            Box::new(ZxcCodec::with_level(Level::Fastest).without_checksum()),
+            Box::new(Identity),
+        ];
+        for codec in &codecs {
+            for chunk in [1 << 20, 4096, 333] {
+                let (wire, back) = stream(codec.as_ref(), &data, chunk);
+                assert_eq!(back, data, "{} by {chunk}", codec.name());
+                assert_eq!(codec.decode(&wire).unwrap(), data);
+            }
+            let one = codec.encode(&data).unwrap();
+            assert_eq!(codec.decode(&one).unwrap(), data);
+            assert_eq!(codec.decode(&codec.encode(&[]).unwrap()).unwrap(), b"");
+        }
+
+        // Truncated and trailing input fail the zxc decoder.
+        let zxc = ZxcCodec::new();
+        let wire = zxc.encode(&data).unwrap();
+        let mut decoder = zxc.decoder().unwrap();
+        let mut out = Vec::new();
+        decoder.write(&wire[..wire.len() - 4], &mut out).unwrap();
+        assert!(decoder.finish(&mut out).is_err());
+        let mut decoder = zxc.decoder().unwrap();
+        assert!(
+            decoder
+                .write(&[&wire[..], &b"!"[..]].concat(), &mut out)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Drop-in replacements for the APIs of other compression crates.
+
+/// The stream helpers of the [`zstd`](https://docs.rs/zstd) crate, with the
+/// same signatures, writing ZXC frames.
+///
+/// Code built on `zstd::encode_all` and friends can be evaluated on ZXC by
+/// swapping an import:
+///
+/// ```rust
+/// use zxc::compat::zstd;
+///
+/// let compressed = zstd::encode_all(&b"evaluate me"[..], 3)?;
+/// assert_eq!(zstd::decode_all(&compressed[..])?, b"evaluate me");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// zstd levels are mapped onto ZXC's seven: 0 (zstd's "default") is
+/// [`Level::Default`](crate::Level::Default), negative levels are
+/// [`Level::Fastest`](crate::Level::Fastest), 1 to 7 are the ZXC level of
+/// that number, and anything above is [`Level::Ultra`](crate::Level::Ultra).
+/// The frames are ZXC frames with checksums: they are not readable by zstd,
+/// and a zstd frame given to `decode_all` is an error.
+pub mod zstd {
+    use std::io::{self, Read, Write};
+    use std::vec::Vec;
+
+    use crate::{CompressOptions, Level};
+
+    /// Compresses all of `source` into a new frame.
+    pub fn encode_all<R: Read>(source: R, level: i32) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        stream::copy_encode(source, &mut out, level)?;
+        Ok(out)
+    }
+
+    /// Decompresses all of `source`, a ZXC frame, into a new buffer.
+    pub fn decode_all<R: Read>(source: R) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        stream::copy_decode(source, &mut out)?;
+        Ok(out)
+    }
+
+    /// Streaming helpers, as in `zstd::stream`.
+    pub mod stream {
+        use super::*;
+        pub use super::{decode_all, encode_all};
+
+        /// Compresses all of `source` into `destination`.
+        pub fn copy_encode<R: Read, W: Write>(
+            source: R,
+            destination: W,
+            level: i32,
+        ) -> io::Result<()> {
+            let opts = CompressOptions::with_level(level_from_zstd(level));
+            crate::compress_stream(source, destination, Some(&opts)).map(drop)
+        }
+
+        /// Decompresses all of `source` into `destination`.
+        pub fn copy_decode<R: Read, W: Write>(source: R, destination: W) -> io::Result<()> {
+            crate::decompress_stream(source, destination, None).map(drop)
+        }
+    }
+
+    fn level_from_zstd(level: i32) -> Level {
+        match level {
+            0 => Level::Default,
+            ..0 => Level::Fastest,
+            _ => Level::try_from(level).unwrap_or(Level::Ultra),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_zstd_shim() {
+            let data = crate::fixtures::input();
+            for level in [-5, 0, 1, 3, 7, 19] {
+                let frame = encode_all(&data[..], level).unwrap();
+                assert!(crate::detect_zxc(&frame));
+                assert_eq!(decode_all(&frame[..]).unwrap(), data);
+            }
+            assert_eq!(level_from_zstd(0), Level::Default);
+            assert_eq!(level_from_zstd(-1), Level::Fastest);
+            assert_eq!(level_from_zstd(5), Level::Compact);
+            assert_eq!(level_from_zstd(22), Level::Ultra);
+
+            let mut out = Vec::new();
+            stream::copy_encode(&data[..], &mut out, 1).unwrap();
+            let mut back = Vec::new();
+            stream::copy_decode(&out[..], &mut back).unwrap();
+            assert_eq!(back, data);
+
+            // A zstd frame (magic 0xFD2FB528) is not mistaken for data.
+            assert!(decode_all(&[0x28, 0xb5, 0x2f, 0xfd, 0, 0, 0, 0][..]).is_err());
+        }
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! The SIMD code path the C library runs on this CPU.
+//!
+//! The library is built in several variants and picks one at runtime from
+//! the CPU's features. A machine that lacks AVX2 (a common VM setting)
+//! silently falls back to the baseline, at about half the throughput, so
+//! services may want to report the choice at startup:
+//!
+//! ```rust
+//! let variant = zxc::cpu::active_variant();
+//! println!("zxc: using the {variant} code path");
+//! ```
+//!
+//! [`force_variant`] pins a variant instead, to benchmark the code paths
+//! against each other or to check whether a suspected bug is SIMD-specific:
+//!
+//! ```rust
+//! use zxc::cpu::{Variant, active_variant, force_variant};
+//!
+//! force_variant(Some(Variant::Default))?;
+//! assert_eq!(active_variant(), Variant::Default);
+//! force_variant(None)?; // back to runtime detection
+//! # Ok::<(), zxc::Error>(())
+//! ```
+//!
+//! With `std`, [`has_avx2`], [`has_avx512`], [`has_neon`] and [`has_crc32`]
+//! query the CPU directly, whatever the build, so a deployment check can
+//! tell a CPU without AVX2 from a build without the AVX2 variant.
+
+use core::fmt;
+
+use crate::{Error, Result, ffi};
+
+/// A SIMD variant of the C library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Variant {
+    /// The baseline: scalar code, SSE2 on x86-64 and NEON on AArch64.
+    Default,
+    /// 32-bit ARM NEON.
+    Neon,
+    /// x86-64 AVX2 (with BMI1, BMI2 and LZCNT).
+    Avx2,
+    /// x86-64 AVX-512 (F, BW and VBMI2).
+    Avx512,
+}
+
+impl Variant {
+    /// Maps a `ZXC_SIMD_*` value; unknown values are the baseline.
+    pub(crate) fn from_raw(raw: i32) -> Self {
+        match raw {
+            zxc_sys::ZXC_SIMD_NEON => Self::Neon,
+            zxc_sys::ZXC_SIMD_AVX2 => Self::Avx2,
+            zxc_sys::ZXC_SIMD_AVX512 => Self::Avx512,
+            _ => Self::Default,
+        }
+    }
+
+    fn to_raw(self) -> i32 {
+        match self {
+            Self::Default => zxc_sys::ZXC_SIMD_DEFAULT,
+            Self::Neon => zxc_sys::ZXC_SIMD_NEON,
+            Self::Avx2 => zxc_sys::ZXC_SIMD_AVX2,
+            Self::Avx512 => zxc_sys::ZXC_SIMD_AVX512,
+        }
+    }
+
+    /// A short name, as printed by [`Display`](fmt::Display).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "baseline",
+            Self::Neon => "NEON",
+            Self::Avx2 => "AVX2",
+            Self::Avx512 => "AVX-512",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the variant the C dispatcher runs on this CPU.
+///
+/// This reflects both the CPU and the build: the `no-avx512`,
+/// `default-only` and `scalar-only` features cap it, and the mock backend
+/// always reports [`Variant::Default`]. `ZXC_NO_SIMD` pins the baseline.
+pub fn active_variant() -> Variant {
+    crate::env::init();
+    Variant::from_raw(unsafe { ffi::zxc_simd_variant() })
+}
+
+/// Pins the C library to `variant`, or with `None` returns to runtime
+/// detection.
+///
+/// [`Variant::Default`] is always accepted. A SIMD variant is accepted when
+/// this CPU and build would run it or a superset of it (an AVX-512 machine
+/// also runs [`Variant::Avx2`]); otherwise this fails with
+/// [`Error::Unsupported`] and the current variant stays in place.
+///
+/// The setting is process-wide, and overrides `ZXC_NO_SIMD`. Calls already running finish on the
+/// previous variant, so pin it before starting work rather than while
+/// other threads compress.
+pub fn force_variant(variant: Option<Variant>) -> Result<()> {
+    // Read `ZXC_NO_SIMD` first, so that it cannot undo this call later.
+    crate::env::init();
+    let raw = variant.map_or(zxc_sys::ZXC_SIMD_AUTO, Variant::to_raw);
+    let now = Variant::from_raw(unsafe { ffi::zxc_simd_force(raw) });
+    match variant {
+        Some(v) if v != now => Err(Error::Unsupported(
+            "SIMD variant not available on this CPU or build",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns `true` if the CPU can run the AVX2 variant: AVX2 together with
+/// BMI1, BMI2 and LZCNT, which that variant is compiled with.
+#[cfg(feature = "std")]
+pub fn has_avx2() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        bmi_lzcnt() && std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Returns `true` if the CPU can run the AVX-512 variant: AVX-512 F, BW and
+/// VBMI2 together with BMI1, BMI2 and LZCNT.
+///
+/// This is about the CPU only; a build with the `no-avx512` feature still
+/// runs the AVX2 variant on it.
+#[cfg(feature = "std")]
+pub fn has_avx512() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        bmi_lzcnt()
+            && std::is_x86_feature_detected!("avx512f")
+            && std::is_x86_feature_detected!("avx512bw")
+            && std::is_x86_feature_detected!("avx512vbmi2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Returns `true` if the CPU has NEON.
+///
+/// Always the case on AArch64, where the baseline variant uses it. On
+/// 32-bit ARM this reflects the target features the crate was compiled
+/// with, as runtime detection is not available in stable Rust there.
+#[cfg(feature = "std")]
+pub fn has_neon() -> bool {
+    cfg!(any(
+        target_arch = "aarch64",
+        all(target_arch = "arm", target_feature = "neon")
+    ))
+}
+
+/// Returns `true` if the CPU has CRC32 instructions (SSE4.2 on x86-64, the
+/// CRC extension on AArch64).
+///
+/// The format's checksums are not CRC32, so no code path depends on it;
+/// it is reported for deployment checks that cover other libraries too.
+#[cfg(feature = "std")]
+pub fn has_crc32() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::is_x86_feature_detected!("sse4.2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("crc")
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+fn bmi_lzcnt() -> bool {
+    std::is_x86_feature_detected!("bmi1")
+        && std::is_x86_feature_detected!("bmi2")
+        && std::is_x86_feature_detected!("lzcnt")
+}
+
+/// Serializes the tests that pin a variant, as the setting is process-wide.
+#[cfg(test)]
+pub(crate) static PIN_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_variant() {
+        // `test_force_variant` may pin the baseline meanwhile.
+        let variant = active_variant();
+        #[cfg(target_arch = "x86_64")]
+        match variant {
+            Variant::Avx512 => assert!(std::is_x86_feature_detected!("avx512vbmi2")),
+            Variant::Avx2 => assert!(std::is_x86_feature_detected!("avx2")),
+            Variant::Neon => panic!("NEON reported on x86-64"),
+            _ => {}
+        }
+        #[cfg(feature = "std")]
+        match variant {
+            Variant::Avx512 => assert!(has_avx512()),
+            Variant::Avx2 => assert!(has_avx2()),
+            Variant::Neon => assert!(has_neon()),
+            _ => {}
+        }
+        assert_eq!(Variant::from_raw(-1), Variant::Default);
+        assert_eq!(Variant::Avx512.to_string(), "AVX-512");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_cpu_features() {
+        // The AVX-512 variant needs everything the AVX2 one does.
+        assert!(!has_avx512() || has_avx2());
+        assert!(!has_neon() || !has_avx2());
+        if cfg!(target_arch = "aarch64") {
+            assert!(has_neon());
+        }
+    }
+
+    #[test]
+    fn test_force_variant() {
+        let _pinned = PIN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let detected = active_variant();
+        // The variants emit the same frames, so other tests running
+        // alongside are unaffected by the switch.
+        let data = crate::fixtures::input();
+        let reference = crate::compress(&data, crate::Level::Default, None).unwrap();
+
+        force_variant(Some(Variant::Default)).unwrap();
+        assert_eq!(active_variant(), Variant::Default);
+        let pinned = crate::compress(&data, crate::Level::Default, None).unwrap();
+        assert_eq!(pinned, reference);
+        assert_eq!(crate::decompress(&pinned).unwrap(), data);
+
+        if detected != Variant::Avx512 {
+            assert!(matches!(
+                force_variant(Some(Variant::Avx512)),
+                Err(Error::Unsupported(_))
+            ));
+            assert_eq!(active_variant(), Variant::Default);
+        }
+        force_variant(None).unwrap();
+        assert_eq!(active_variant(), detected);
+    }
+}
@@ -7,10 +7,16 @@
 
 //! Block API: reusable single-block compression / decompression contexts.
 
-use std::ffi::c_void;
+use core::ffi::c_void;
+use core::marker::PhantomData;
 
-use crate::error::error_from_code;
-use crate::{CompressOptions, DecompressOptions, Error, Result};
+use crate::error::size_from_code;
+use crate::inspect::check_version;
+use crate::metrics::{self, Operation};
+use crate::oneshot::check_room;
+use zxc_sys::{ZXC_DICT_SIZE_MAX, ZXC_HUF_TABLE_SIZE};
+
+use crate::{Arena, CompressOptions, DecompressOptions, Error, Result, diag, env, ffi};
 
 /// Reusable compression context for the Block API.
 ///
@@ -41,18 +47,20 @@ impl Cctx {
     /// When `opts` is `Some`, internal buffers are pre-allocated with those
     /// parameters. When `None`, allocation is deferred to first use.
     pub fn new(opts: Option<&CompressOptions>) -> Result<Self> {
+        env::init();
+        diag::variant();
         let c_opts = opts.map(|o| zxc_sys::zxc_compress_opts_t {
-            level: o.level as i32,
+            level: env::level(o.level) as i32,
             checksum_enabled: o.checksum as i32,
             seekable: o.seekable as i32,
             ..Default::default()
         });
         let ptr = unsafe {
-            zxc_sys::zxc_create_cctx(
+            ffi::zxc_create_cctx(
                 c_opts
                     .as_ref()
                     .map(|o| o as *const _)
-                    .unwrap_or(std::ptr::null()),
+                    .unwrap_or(core::ptr::null()),
             )
         };
         if ptr.is_null() {
@@ -65,21 +73,44 @@ impl Cctx {
     /// Compresses a single block (no file framing).
     ///
     /// Output format: 8-byte block header + payload (+ optional 4-byte checksum).
-    /// Use [`compress_block_bound`] to size `dst`.
+    /// Use [`compress_block_bound`] to size `dst`. With `opts.dict` set, the
+    /// block needs the same dictionary to decompress.
     pub fn compress_block(
         &mut self,
         src: &[u8],
         dst: &mut [u8],
         opts: &CompressOptions,
     ) -> Result<usize> {
+        let dict = opts.dict.as_deref().unwrap_or_default();
+        self.compress_block_with_dict(src, dst, opts, dict)
+    }
+
+    /// [`Cctx::compress_block`] with `dict` in place of `opts.dict`, for
+    /// callers whose dictionary changes between blocks.
+    pub(crate) fn compress_block_with_dict(
+        &mut self,
+        src: &[u8],
+        dst: &mut [u8],
+        opts: &CompressOptions,
+        dict: &[u8],
+    ) -> Result<usize> {
+        if dst.is_empty() {
+            return Err(Error::DstTooSmall {
+                needed: usize::try_from(compress_block_bound(src.len())).unwrap_or(usize::MAX),
+            });
+        }
+        let (dict, dict_size, dict_huf) = dict_parts(dict, opts.dict_huf.as_deref())?;
         let copts = zxc_sys::zxc_compress_opts_t {
-            level: opts.level as i32,
+            level: env::level(opts.level) as i32,
             checksum_enabled: opts.checksum as i32,
             seekable: opts.seekable as i32,
+            dict,
+            dict_size,
+            dict_huf,
             ..Default::default()
         };
         let res = unsafe {
-            zxc_sys::zxc_compress_block(
+            ffi::zxc_compress_block(
                 self.inner,
                 src.as_ptr() as *const c_void,
                 src.len(),
@@ -88,17 +119,15 @@ impl Cctx {
                 &copts,
             )
         };
-        if res < 0 {
-            Err(error_from_code(res))
-        } else {
-            Ok(res as usize)
-        }
+        let written = size_from_code(res)?;
+        diag::stored_block(dst.get(..written).unwrap_or_default());
+        Ok(written)
     }
 }
 
 impl Drop for Cctx {
     fn drop(&mut self) {
-        unsafe { zxc_sys::zxc_free_cctx(self.inner) };
+        unsafe { ffi::zxc_free_cctx(self.inner) };
     }
 }
 
@@ -114,7 +143,9 @@ unsafe impl Send for Dctx {}
 impl Dctx {
     /// Creates a new decompression context.
     pub fn new() -> Result<Self> {
-        let ptr = unsafe { zxc_sys::zxc_create_dctx() };
+        env::init();
+        diag::variant();
+        let ptr = unsafe { ffi::zxc_create_dctx() };
         if ptr.is_null() {
             Err(Error::Memory)
         } else {
@@ -133,12 +164,19 @@ impl Dctx {
         dst: &mut [u8],
         opts: &DecompressOptions,
     ) -> Result<usize> {
+        let (dict, dict_size, dict_huf) = dict_parts(
+            opts.dict.as_deref().unwrap_or_default(),
+            opts.dict_huf.as_deref(),
+        )?;
         let dopts = zxc_sys::zxc_decompress_opts_t {
             checksum_enabled: opts.verify_checksum as i32,
+            dict,
+            dict_size,
+            dict_huf,
             ..Default::default()
         };
         let res = unsafe {
-            zxc_sys::zxc_decompress_block(
+            ffi::zxc_decompress_block(
                 self.inner,
                 src.as_ptr() as *const c_void,
                 src.len(),
@@ -147,11 +185,7 @@ impl Dctx {
                 &dopts,
             )
         };
-        if res < 0 {
-            Err(error_from_code(res))
-        } else {
-            Ok(res as usize)
-        }
+        size_from_code(res)
     }
 
     /// Strict-sized variant of [`Dctx::decompress_block`]: accepts
@@ -163,12 +197,28 @@ impl Dctx {
         dst: &mut [u8],
         opts: &DecompressOptions,
     ) -> Result<usize> {
+        let dict = opts.dict.as_deref().unwrap_or_default();
+        self.decompress_block_safe_with_dict(src, dst, opts, dict)
+    }
+
+    /// [`Dctx::decompress_block_safe`] with `dict` in place of `opts.dict`.
+    pub(crate) fn decompress_block_safe_with_dict(
+        &mut self,
+        src: &[u8],
+        dst: &mut [u8],
+        opts: &DecompressOptions,
+        dict: &[u8],
+    ) -> Result<usize> {
+        let (dict, dict_size, dict_huf) = dict_parts(dict, opts.dict_huf.as_deref())?;
         let dopts = zxc_sys::zxc_decompress_opts_t {
             checksum_enabled: opts.verify_checksum as i32,
+            dict,
+            dict_size,
+            dict_huf,
             ..Default::default()
         };
         let res = unsafe {
-            zxc_sys::zxc_decompress_block_safe(
+            ffi::zxc_decompress_block_safe(
                 self.inner,
                 src.as_ptr() as *const c_void,
                 src.len(),
@@ -177,24 +227,203 @@ impl Dctx {
                 &dopts,
             )
         };
-        if res < 0 {
-            Err(error_from_code(res))
-        } else {
-            Ok(res as usize)
-        }
+        size_from_code(res)
     }
 }
 
 impl Drop for Dctx {
     fn drop(&mut self) {
-        unsafe { zxc_sys::zxc_free_dctx(self.inner) };
+        unsafe { ffi::zxc_free_dctx(self.inner) };
+    }
+}
+
+/// Alignment the C library requires of a static workspace.
+const WORKSPACE_ALIGN: usize = 64;
+
+/// Frame decompression context living in a workspace the caller supplies,
+/// for loops that must not allocate once running.
+///
+/// The whole decoder state is carved out of the workspace when the context
+/// is created, so [`Decompressor::decompress`] allocates nothing: decoding
+/// a frame touches only the workspace, the input and the output. The
+/// workspace is sized for one block size, fixed at creation; frames written
+/// with another block size are rejected with [`Error::BadBlockSize`].
+///
+/// ```rust
+/// use zxc::{DecompressOptions, Decompressor};
+///
+/// // The block size of the frames from `zxc::compress`.
+/// let block_size = 512 * 1024;
+/// let frame = zxc::compress(&[7u8; 10_000], zxc::Level::Default, None)?;
+///
+/// // Once, at start-up.
+/// let mut workspace = vec![0u8; Decompressor::workspace_size(block_size)];
+/// let mut output = vec![0u8; 10_000];
+/// let mut dec = Decompressor::with_workspace(&mut workspace, block_size)?;
+///
+/// // Every frame after that.
+/// let n = dec.decompress(&frame, &mut output, &DecompressOptions::default())?;
+/// assert_eq!(&output[..n], &[7u8; 10_000]);
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub struct Decompressor<'w> {
+    inner: *mut zxc_sys::zxc_dctx,
+    block_size: usize,
+    /// The handle points into the workspace; `zxc_free_dctx` is a no-op on
+    /// it, so there is nothing to drop.
+    _workspace: PhantomData<&'w mut [u8]>,
+}
+
+unsafe impl Send for Decompressor<'_> {}
+
+impl<'w> Decompressor<'w> {
+    /// Bytes of workspace [`Decompressor::with_workspace`] needs for frames
+    /// of `block_size`, alignment slack included, so that any slice of this
+    /// length will do. Returns 0 for a block size out of range.
+    pub fn workspace_size(block_size: usize) -> usize {
+        match unsafe { ffi::zxc_static_dctx_workspace_size(block_size) } {
+            0 => 0,
+            n => n + WORKSPACE_ALIGN - 1,
+        }
+    }
+
+    /// Creates a context inside `workspace` for frames of `block_size`, a
+    /// power of two from 4 KiB to 2 MiB. The start of the workspace is
+    /// skipped as needed to align it.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadBlockSize`] if `block_size` is out of range, and
+    /// [`Error::Memory`] if the workspace is shorter than
+    /// [`Decompressor::workspace_size`].
+    pub fn with_workspace(workspace: &'w mut [u8], block_size: usize) -> Result<Self> {
+        env::init();
+        diag::variant();
+        if Self::workspace_size(block_size) == 0 {
+            return Err(Error::BadBlockSize);
+        }
+        let offset = workspace.as_ptr().align_offset(WORKSPACE_ALIGN);
+        let aligned = workspace.get_mut(offset..).ok_or(Error::Memory)?;
+        let ptr = unsafe {
+            ffi::zxc_init_static_dctx(
+                aligned.as_mut_ptr() as *mut c_void,
+                aligned.len(),
+                block_size,
+            )
+        };
+        if ptr.is_null() {
+            return Err(Error::Memory);
+        }
+        Ok(Self {
+            inner: ptr,
+            block_size,
+            _workspace: PhantomData,
+        })
+    }
+
+    /// [`Decompressor::with_workspace`] over a workspace drawn from `arena`,
+    /// so that the context takes no memory of its own and goes when the
+    /// arena is reset.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadBlockSize`] if `block_size` is out of range, and
+    /// [`Error::Memory`] if the arena cannot hold the workspace.
+    pub fn in_arena<A: Arena + ?Sized>(arena: &'w A, block_size: usize) -> Result<Self> {
+        let size = Self::workspace_size(block_size);
+        if size == 0 {
+            return Err(Error::BadBlockSize);
+        }
+        let workspace = arena.alloc_bytes(size).ok_or(Error::Memory)?;
+        Self::with_workspace(workspace, block_size)
+    }
+
+    /// The block size of the frames this context reads.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Decompresses the frame `compressed` into `output`, returning the
+    /// number of bytes written. Same contract as [`crate::decompress_to`],
+    /// without allocating.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadBlockSize`] for a frame of another block size, and the
+    /// errors of [`crate::decompress_to`].
+    pub fn decompress(
+        &mut self,
+        compressed: &[u8],
+        output: &mut [u8],
+        opts: &DecompressOptions,
+    ) -> Result<usize> {
+        check_version(compressed, opts.format_versions())?;
+        check_room(compressed, output.len())?;
+        diag::checksum(opts.verify_checksum, compressed.get(6).copied());
+        let timer = metrics::start();
+        let (dict, dict_size, dict_huf) = dict_parts(
+            opts.dict.as_deref().unwrap_or_default(),
+            opts.dict_huf.as_deref(),
+        )?;
+        let dopts = zxc_sys::zxc_decompress_opts_t {
+            checksum_enabled: opts.verify_checksum as i32,
+            dict,
+            dict_size,
+            dict_huf,
+            ..Default::default()
+        };
+        let res = unsafe {
+            ffi::zxc_decompress_dctx(
+                self.inner,
+                compressed.as_ptr() as *const c_void,
+                compressed.len(),
+                output.as_mut_ptr() as *mut c_void,
+                output.len(),
+                &dopts,
+            )
+        };
+        let written = size_from_code(res)?;
+        timer.finish(
+            Operation::Decompress,
+            compressed.len() as u64,
+            written as u64,
+        );
+        Ok(written)
+    }
+}
+
+/// Dictionary pointers for the C options; an empty dictionary is none, and
+/// the Huffman table only counts with a dictionary.
+pub(crate) fn dict_parts(
+    dict: &[u8],
+    huf: Option<&[u8]>,
+) -> Result<(*const c_void, usize, *const c_void)> {
+    if dict.is_empty() {
+        return Ok((core::ptr::null(), 0, core::ptr::null()));
+    }
+    if dict.len() > ZXC_DICT_SIZE_MAX {
+        return Err(Error::DictTooLarge);
+    }
+    Ok((dict.as_ptr() as *const c_void, dict.len(), huf_ptr(huf)?))
+}
+
+/// The Huffman table for the C options, null if none. The library reads
+/// [`ZXC_HUF_TABLE_SIZE`] bytes from it, so a table of another size is
+/// rejected rather than read past its end.
+pub(crate) fn huf_ptr(huf: Option<&[u8]>) -> Result<*const c_void> {
+    match huf {
+        Some(h) if h.len() == ZXC_HUF_TABLE_SIZE => Ok(h.as_ptr() as *const c_void),
+        Some(h) if !h.is_empty() => Err(Error::InvalidOption(
+            "dict_huf must be ZXC_HUF_TABLE_SIZE bytes",
+        )),
+        _ => Ok(core::ptr::null()),
     }
 }
 
 /// Returns the maximum compressed size for a single block of `input_size`
 /// bytes (no file framing).
 pub fn compress_block_bound(input_size: usize) -> u64 {
-    unsafe { zxc_sys::zxc_compress_block_bound(input_size) }
+    unsafe { ffi::zxc_compress_block_bound(input_size) }
 }
 
 /// Returns the minimum destination buffer size required by
@@ -204,5 +433,102 @@ pub fn compress_block_bound(input_size: usize) -> u64 {
 /// strictly-sized destination, use [`Dctx::decompress_block_safe`] instead
 /// and size the buffer to exactly the uncompressed length.
 pub fn decompress_block_bound(uncompressed_size: usize) -> u64 {
-    unsafe { zxc_sys::zxc_decompress_block_bound(uncompressed_size) }
+    unsafe { ffi::zxc_decompress_block_bound(uncompressed_size) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn test_static_decompressor() {
+        let block_size = 512 * 1024;
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 97) as u8).collect();
+        let frame = crate::compress(&data, Level::Default, None).unwrap();
+        assert_eq!(frame[5], 19, "block size code");
+
+        // Any offset into the workspace will do.
+        let size = Decompressor::workspace_size(block_size);
+        let mut workspace = vec![0u8; size + 3];
+        let mut output = vec![0u8; data.len()];
+        let opts = DecompressOptions::default();
+        let mut dec = Decompressor::with_workspace(&mut workspace[3..], block_size).unwrap();
+        assert_eq!(dec.block_size(), block_size);
+        for _ in 0..3 {
+            output.fill(0);
+            let n = dec.decompress(&frame, &mut output, &opts).unwrap();
+            assert_eq!(output[..n], data);
+        }
+        assert!(dec.decompress(&frame, &mut output[..10], &opts).is_err());
+
+        #[cfg(not(feature = "mock-backend"))]
+        {
+            let mut small = crate::Recompressor::new(&crate::RecompressOptions {
+                block_size: 16 * 1024,
+                ..Default::default()
+            })
+            .unwrap();
+            let mut other = Vec::new();
+            small.write(&frame, &mut other).unwrap();
+            small.finish(&mut other).unwrap();
+            assert!(matches!(
+                dec.decompress(&other, &mut output, &opts),
+                Err(Error::BadBlockSize)
+            ));
+        }
+
+        assert_eq!(Decompressor::workspace_size(1000), 0);
+        assert!(matches!(
+            Decompressor::with_workspace(&mut workspace, 1000),
+            Err(Error::BadBlockSize)
+        ));
+        let short = &mut workspace[..size / 2];
+        assert!(matches!(
+            Decompressor::with_workspace(short, block_size),
+            Err(Error::Memory)
+        ));
+    }
+
+    #[test]
+    fn test_options_checked() {
+        let data = b"options checked before the C library sees them";
+        let dict = b"options checked".repeat(4);
+
+        // The library would read 128 bytes from a shorter table.
+        let bad_huf = CompressOptions::default()
+            .with_dict(dict.clone())
+            .with_dict_huf(vec![1u8; 5]);
+        assert!(matches!(
+            crate::compress_with_options(data, &bad_huf),
+            Err(Error::InvalidOption(_))
+        ));
+        let frame = crate::compress(data, Level::Default, None).unwrap();
+        let dopts = DecompressOptions::default()
+            .with_dict(dict.clone())
+            .with_dict_huf(vec![0u8; 200]);
+        assert!(matches!(
+            crate::decompress_with_options(&frame, &dopts),
+            Err(Error::InvalidOption(_))
+        ));
+        // Without a dictionary the table is ignored.
+        let no_dict = CompressOptions {
+            dict_huf: Some(vec![1u8; 5]),
+            ..Default::default()
+        };
+        assert!(crate::compress_with_options(data, &no_dict).is_ok());
+
+        let huge = CompressOptions::default().with_dict(vec![7u8; ZXC_DICT_SIZE_MAX + 1]);
+        assert!(matches!(
+            crate::compress_with_options(data, &huge),
+            Err(Error::DictTooLarge)
+        ));
+
+        let mut cctx = Cctx::new(None).unwrap();
+        let opts = CompressOptions::default();
+        assert!(matches!(
+            cctx.compress_block(data, &mut [], &opts),
+            Err(Error::DstTooSmall { needed }) if needed as u64 == compress_block_bound(data.len())
+        ));
+    }
 }
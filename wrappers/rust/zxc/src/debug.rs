@@ -0,0 +1,389 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! A human-readable listing of a frame, for bug reports.
+//!
+//! [`dump`] walks a frame and prints every structure of `docs/FORMAT.md`
+//! it meets: the file header and its flags, one line per block with its
+//! type, sizes, checksum and the encodings of its sections, the seek table
+//! and the footer. Each block is also decoded on its own, so the listing
+//! says which block is damaged and how, the way `zstd -lv` does.
+//!
+//! ```rust
+//! let frame = zxc::compress(&b"dump me ".repeat(1000), zxc::Level::Density, Some(true))?;
+//! let mut listing = Vec::new();
+//! zxc::debug::dump(&frame[..], &mut listing)?;
+//! let listing = String::from_utf8(listing).unwrap();
+//! assert!(listing.contains("global hash   0x") && !listing.contains("BAD"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The listing of a 640 KiB file:
+//!
+//! ```text
+//! file header (16 bytes)
+//!   magic         0x9cb02ef5
+//!   version       7
+//!   block size    512 KiB (code 19)
+//!   flags         0x80: checksum (rapidhash)
+//!   header hash   0x5d3e ok
+//!
+//!   block   offset  type compressed       size  checksum    sections
+//!       0       16  GLO        8208     524288  0x152fa6f0  seq 1558, lit 3457 raw, tokens raw, offsets 16-bit
+//!       1     8236  GLO        8154     131072  0xba1b8c0f  seq 1570, lit 3385 raw, tokens raw, offsets 16-bit
+//!     eof    16402
+//!
+//! footer (12 bytes at 16410)
+//!   size          655360 ok
+//!   global hash   0x9044c1ef ok
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_GHI, BLOCK_GLO, BLOCK_HEADER_SIZE, BLOCK_SEK,
+    BLOCK_SIZE_LOG2, FILE_FOOTER_SIZE, FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM, FLAG_HAS_DICTIONARY,
+    FORMAT_VERSION, GNR_HEADER_SIZE, MAGIC_WORD,
+};
+use crate::hash::{hash8, hash16};
+use crate::scratch::Scratch;
+use crate::{Dctx, DecompressOptions, compress_block_bound, decompress_block_bound};
+
+/// Prints a listing of the frame read from `reader` to `w`.
+///
+/// Damage is part of the listing, not an error: a bad hash is marked next
+/// to its field, a block that fails to decode carries the error, and the
+/// walk goes on as long as the block headers can be trusted. It stops at
+/// an unreadable header or the end of the input, saying so. Blocks of
+/// dictionary frames are listed but not decoded.
+///
+/// # Errors
+///
+/// I/O errors of `reader` and `w`.
+pub fn dump<R: Read, W: Write>(mut reader: R, mut w: W) -> io::Result<()> {
+    let mut header = [0u8; FILE_HEADER_SIZE];
+    let got = read_full(&mut reader, &mut header)?;
+    if got < FILE_HEADER_SIZE {
+        return writeln!(
+            w,
+            "truncated: {got} bytes, a frame starts with a 16-byte header"
+        );
+    }
+    writeln!(w, "file header ({FILE_HEADER_SIZE} bytes)")?;
+    let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    writeln!(
+        w,
+        "  magic         {magic:#010x}{}",
+        bad_if(magic != MAGIC_WORD)
+    )?;
+    writeln!(
+        w,
+        "  version       {}{}",
+        header[4],
+        bad_if(header[4] != FORMAT_VERSION)
+    )?;
+    let code = header[5];
+    let block_size = BLOCK_SIZE_LOG2.contains(&code).then(|| 1usize << code);
+    match block_size {
+        Some(size) => writeln!(w, "  block size    {} KiB (code {code})", size >> 10)?,
+        None => writeln!(w, "  block size    code {code} BAD")?,
+    }
+    let flags = header[6];
+    let has_checksum = flags & FLAG_HAS_CHECKSUM != 0;
+    let has_dict = flags & FLAG_HAS_DICTIONARY != 0;
+    let mut names = Vec::new();
+    if has_checksum {
+        names.push("checksum");
+    }
+    if has_dict {
+        names.push("dictionary");
+    }
+    let algorithm = match flags & 0x0F {
+        0 => "rapidhash",
+        _ => "unknown",
+    };
+    writeln!(
+        w,
+        "  flags         {flags:#04x}: {} ({algorithm})",
+        if names.is_empty() {
+            "none".into()
+        } else {
+            names.join(", ")
+        }
+    )?;
+    if has_dict {
+        let id = u32::from_le_bytes([header[7], header[8], header[9], header[10]]);
+        writeln!(w, "  dictionary id {id:#010x}")?;
+    }
+    let mut zeroed = header;
+    zeroed[14..].fill(0);
+    let stored = u16::from_le_bytes([header[14], header[15]]);
+    writeln!(
+        w,
+        "  header hash   {stored:#06x}{}",
+        ok_or_bad(stored == hash16(&zeroed))
+    )?;
+    let Some(block_size) = block_size.filter(|_| magic == MAGIC_WORD) else {
+        return writeln!(w, "\nnot a zxc frame: stopping");
+    };
+
+    let max_comp_size = compress_block_bound(block_size) as usize;
+    let mut dctx = (!has_dict)
+        .then(Dctx::new)
+        .transpose()
+        .map_err(io::Error::other)?;
+    let opts = DecompressOptions {
+        verify_checksum: has_checksum,
+        ..Default::default()
+    };
+    let mut block = Vec::new();
+    let mut out = Scratch::zeroed(decompress_block_bound(block_size) as usize);
+    let mut offset = FILE_HEADER_SIZE as u64;
+    let (mut decoded, mut global_hash, mut blocks) = (0u64, 0u32, 0u32);
+
+    writeln!(
+        w,
+        "\n  {:>5} {:>8}  {:<4} {:>10} {:>10}  {:<10}  sections",
+        "block", "offset", "type", "compressed", "size", "checksum"
+    )?;
+    loop {
+        let mut bh = [0u8; BLOCK_HEADER_SIZE];
+        let got = read_full(&mut reader, &mut bh)?;
+        if got < BLOCK_HEADER_SIZE {
+            return writeln!(
+                w,
+                "\ntruncated at byte {}: no end-of-frame block",
+                offset + got as u64
+            );
+        }
+        let mut zeroed = bh;
+        zeroed[7] = 0;
+        let comp_size = u32::from_le_bytes([bh[3], bh[4], bh[5], bh[6]]) as usize;
+        if bh[7] != hash8(&zeroed) {
+            return writeln!(
+                w,
+                "  {blocks:>5} {offset:>8}  block header hash BAD: stopping"
+            );
+        }
+        if bh[0] == BLOCK_EOF {
+            writeln!(w, "  {:>5} {offset:>8}{}", "eof", bad_if(comp_size != 0))?;
+            offset += BLOCK_HEADER_SIZE as u64;
+            break;
+        }
+        if comp_size > max_comp_size {
+            return writeln!(
+                w,
+                "  {blocks:>5} {offset:>8}  {:<4} {comp_size:>10} BAD (above the {max_comp_size}-byte bound): stopping",
+                kind(bh[0])
+            );
+        }
+        let rest = comp_size + if has_checksum { BLOCK_CHECKSUM_SIZE } else { 0 };
+        block.clear();
+        block.extend_from_slice(&bh);
+        if (&mut reader).take(rest as u64).read_to_end(&mut block)? < rest {
+            return writeln!(
+                w,
+                "  {blocks:>5} {offset:>8}  {:<4} {comp_size:>10}  truncated",
+                kind(bh[0])
+            );
+        }
+        let stored = match has_checksum {
+            true => block
+                .last_chunk::<BLOCK_CHECKSUM_SIZE>()
+                .map(|c| u32::from_le_bytes(*c)),
+            false => None,
+        };
+        if let Some(stored) = stored {
+            global_hash = global_hash.rotate_left(1) ^ stored;
+        }
+        let size = match dctx.as_mut() {
+            Some(dctx) => match dctx.decompress_block(&block, &mut out, &opts) {
+                Ok(n) => {
+                    decoded += n as u64;
+                    n.to_string()
+                }
+                Err(e) => format!("BAD: {e}"),
+            },
+            None => "-".into(),
+        };
+        let payload = block
+            .get(BLOCK_HEADER_SIZE..BLOCK_HEADER_SIZE + comp_size)
+            .unwrap_or_default();
+        writeln!(
+            w,
+            "  {blocks:>5} {offset:>8}  {:<4} {comp_size:>10} {size:>10}  {:<10}  {}",
+            kind(bh[0]),
+            stored.map_or_else(|| "-".into(), |c| format!("{c:#010x}")),
+            sections(bh[0], payload)
+        )?;
+        offset += (BLOCK_HEADER_SIZE + rest) as u64;
+        blocks += 1;
+    }
+
+    // An optional seek table, then the footer.
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail)?;
+    let Some(split) = tail.len().checked_sub(FILE_FOOTER_SIZE) else {
+        return writeln!(
+            w,
+            "\ntruncated at byte {}: no footer",
+            offset + tail.len() as u64
+        );
+    };
+    let (table, footer) = tail.split_at(split);
+    if !table.is_empty() {
+        let entries = table.len().saturating_sub(BLOCK_HEADER_SIZE) / 4;
+        let valid = table.first() == Some(&BLOCK_SEK)
+            && table.len() == BLOCK_HEADER_SIZE + 4 * blocks as usize;
+        writeln!(
+            w,
+            "\nseek table ({} bytes at {offset}): {entries} entries{}",
+            table.len(),
+            match valid {
+                true => "",
+                false => " BAD (expected one per block)",
+            }
+        )?;
+        offset += table.len() as u64;
+    }
+    writeln!(w, "\nfooter ({FILE_FOOTER_SIZE} bytes at {offset})")?;
+    let size = footer
+        .first_chunk::<8>()
+        .map_or(0, |b| u64::from_le_bytes(*b));
+    let hash = footer
+        .last_chunk::<4>()
+        .map_or(0, |b| u32::from_le_bytes(*b));
+    match dctx {
+        Some(_) => writeln!(w, "  size          {size}{}", ok_or_bad(size == decoded))?,
+        None => writeln!(w, "  size          {size}")?,
+    }
+    match has_checksum {
+        true => writeln!(
+            w,
+            "  global hash   {hash:#010x}{}",
+            ok_or_bad(hash == global_hash)
+        ),
+        false => writeln!(w, "  global hash   {hash:#010x} (unused)"),
+    }
+}
+
+/// Reads as much of `buf` as the input holds; returns the bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while let Some(rest) = buf.get_mut(filled..).filter(|r| !r.is_empty()) {
+        match reader.read(rest) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn kind(kind: u8) -> String {
+    match kind {
+        0 => "RAW".into(),
+        BLOCK_GLO => "GLO".into(),
+        BLOCK_GHI => "GHI".into(),
+        BLOCK_SEK => "SEK".into(),
+        other => format!("?{other}"),
+    }
+}
+
+/// The counts and section encodings of a GLO or GHI payload.
+fn sections(kind: u8, payload: &[u8]) -> String {
+    if kind != BLOCK_GLO && kind != BLOCK_GHI {
+        return String::new();
+    }
+    let Some(h) = payload.first_chunk::<GNR_HEADER_SIZE>() else {
+        return "header truncated".into();
+    };
+    let n_sequences = u32::from_le_bytes([h[0], h[1], h[2], h[3]]);
+    let n_literals = u32::from_le_bytes([h[4], h[5], h[6], h[7]]);
+    let literals = match h[8] {
+        0 => "raw",
+        1 => "rle",
+        2 => "huffman",
+        3 => "huffman (shared table)",
+        _ => "unknown",
+    };
+    if kind == BLOCK_GHI {
+        return format!("seq {n_sequences}, lit {n_literals} {literals}");
+    }
+    let tokens = match h[9] {
+        0 => "raw",
+        2 => "huffman",
+        _ => "unknown",
+    };
+    let offsets = match h[11] {
+        0 => "16-bit",
+        1 => "8-bit",
+        _ => "unknown",
+    };
+    format!("seq {n_sequences}, lit {n_literals} {literals}, tokens {tokens}, offsets {offsets}")
+}
+
+fn bad_if(bad: bool) -> &'static str {
+    if bad { " BAD" } else { "" }
+}
+
+fn ok_or_bad(ok: bool) -> &'static str {
+    if ok { " ok" } else { " BAD" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, compress};
+
+    fn listing(frame: &[u8]) -> String {
+        let mut out = Vec::new();
+        dump(frame, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dump() {
+        let data = crate::fixtures::input().repeat(40);
+        let frame = compress(&data, Level::Density, Some(true)).unwrap();
+        let text = listing(&frame);
+        assert!(
+            text.contains("flags         0x80: checksum (rapidhash)"),
+            "{text}"
+        );
+        assert!(text.contains("header hash"));
+        assert!(!text.contains("BAD"), "{text}");
+        assert!(text.contains(&format!("size          {} ok", data.len())));
+        assert!(text.contains("global hash"));
+        // Two 512 KiB blocks and the end-of-frame marker.
+        assert!(text.contains("\n      1 "));
+        assert!(text.contains("eof"));
+
+        #[cfg(not(feature = "mock-backend"))]
+        assert!(text.contains("  GLO ") && text.contains("seq "), "{text}");
+
+        let seekable = crate::CompressOptions::default().with_seekable();
+        let frame_sek = crate::compress_with_options(&data, &seekable).unwrap();
+        let text = listing(&frame_sek);
+        assert!(text.contains("): 2 entries\n"), "{text}");
+        assert!(!text.contains("BAD"), "{text}");
+
+        // A flipped payload byte shows on its block, and the walk goes on.
+        let mut damaged = frame.clone();
+        damaged[FILE_HEADER_SIZE + BLOCK_HEADER_SIZE + 40] ^= 0xFF;
+        let text = listing(&damaged);
+        assert!(text.contains("BAD: "), "{text}");
+        assert!(text.contains("footer"));
+
+        // Truncated input and garbage are described, not errors.
+        assert!(listing(&frame[..frame.len() - 5]).contains("no footer"));
+        assert!(listing(&frame[..100]).contains("truncated"));
+        assert!(listing(b"not a frame at all").contains("not a zxc frame"));
+        assert!(listing(b"tiny").starts_with("truncated: 4 bytes"));
+    }
+}
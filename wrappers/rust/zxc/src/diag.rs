@@ -0,0 +1,177 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Diagnostics reported through the `log` crate (the `log` feature).
+//!
+//! Each hook logs one notable condition. Without the feature they are empty
+//! and inlined away, so call sites need no `cfg`.
+
+pub(crate) use imp::{checksum, stored_block, stored_blocks, variant};
+#[cfg(feature = "std")]
+pub(crate) use imp::{ignored_env, threads};
+
+#[cfg(feature = "log")]
+mod imp {
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::format::{
+        BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_HEADER_SIZE, BLOCK_RAW, FILE_HEADER_SIZE,
+        FLAG_HAS_CHECKSUM,
+    };
+
+    /// Logs the SIMD code path picked by the C dispatcher, once per process
+    /// (and not before a logger wants it, so that it is not lost to calls
+    /// made before the logger was installed).
+    pub(crate) fn variant() {
+        static LOGGED: AtomicBool = AtomicBool::new(false);
+        if !log::log_enabled!(log::Level::Debug) || LOGGED.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        log::debug!("using the {} code path", crate::cpu::active_variant());
+    }
+
+    /// Logs the blocks of a freshly written `frame` that were stored raw
+    /// because they did not compress.
+    pub(crate) fn stored_blocks(frame: &[u8]) {
+        let Some(flags) = frame.get(6) else {
+            return;
+        };
+        let checksum = if flags & FLAG_HAS_CHECKSUM != 0 {
+            BLOCK_CHECKSUM_SIZE
+        } else {
+            0
+        };
+        let (mut raw, mut total) = (0u32, 0u32);
+        let mut pos = FILE_HEADER_SIZE;
+        while let Some(header) = frame
+            .get(pos..)
+            .and_then(<[u8]>::first_chunk::<BLOCK_HEADER_SIZE>)
+        {
+            if header[0] == BLOCK_EOF {
+                break;
+            }
+            raw += u32::from(header[0] == BLOCK_RAW);
+            total += 1;
+            let comp_size = u32::from_le_bytes([header[3], header[4], header[5], header[6]]);
+            pos = pos
+                .saturating_add(BLOCK_HEADER_SIZE + checksum)
+                .saturating_add(comp_size as usize);
+        }
+        if raw > 0 {
+            log::debug!("{raw} of {total} blocks stored raw (incompressible input)");
+        }
+    }
+
+    /// Logs a block from the Block API that was stored raw.
+    pub(crate) fn stored_block(block: &[u8]) {
+        if block.first() == Some(&BLOCK_RAW) {
+            log::debug!(
+                "block of {} bytes stored raw (incompressible input)",
+                block.len()
+            );
+        }
+    }
+
+    /// Logs a decode that will not check checksums: `verify` is the
+    /// caller's option, `flags` the file header flags when known.
+    pub(crate) fn checksum(verify: bool, flags: Option<u8>) {
+        if !verify {
+            log::debug!("checksum verification skipped (disabled in the options)");
+        } else if flags.is_some_and(|f| f & FLAG_HAS_CHECKSUM == 0) {
+            log::debug!("checksum verification skipped (the frame carries no checksums)");
+        }
+    }
+
+    /// Logs a thread count that could not be honoured.
+    #[cfg(feature = "std")]
+    pub(crate) fn threads(requested: usize, running: usize, reason: &str) {
+        log::warn!("{requested} threads requested, running {running} ({reason})");
+    }
+
+    /// Logs an environment override that did not parse.
+    #[cfg(feature = "std")]
+    pub(crate) fn ignored_env(name: &str, value: &str) {
+        log::warn!("ignoring {name}={value:?} (not a valid value)");
+    }
+}
+
+#[cfg(not(feature = "log"))]
+mod imp {
+    #[inline(always)]
+    pub(crate) fn variant() {}
+
+    #[inline(always)]
+    pub(crate) fn stored_blocks(_frame: &[u8]) {}
+
+    #[inline(always)]
+    pub(crate) fn stored_block(_block: &[u8]) {}
+
+    #[inline(always)]
+    pub(crate) fn checksum(_verify: bool, _flags: Option<u8>) {}
+
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn threads(_requested: usize, _running: usize, _reason: &str) {}
+
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn ignored_env(_name: &str, _value: &str) {}
+}
+
+#[cfg(all(test, feature = "log", feature = "std"))]
+mod tests {
+    use std::string::{String, ToString};
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    use crate::{
+        CompressOptions, DecompressOptions, compress_with_options, decompress_with_options,
+    };
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            RECORDS.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_diagnostics_are_logged() {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        // An LCG stream does not compress: every block is stored raw.
+        let mut x = 0x2545_F491u32;
+        let noise: Vec<u8> = (0..4096)
+            .map(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (x >> 24) as u8
+            })
+            .collect();
+        let frame = compress_with_options(&noise, &CompressOptions::default()).unwrap();
+        let opts = DecompressOptions {
+            verify_checksum: false,
+            ..Default::default()
+        };
+        decompress_with_options(&frame, &opts).unwrap();
+
+        let records = RECORDS.lock().unwrap();
+        let logged = |needle: &str| records.iter().any(|r| r.contains(needle));
+        assert!(logged("code path"), "{records:?}");
+        assert!(logged("1 of 1 blocks stored raw"), "{records:?}");
+        assert!(logged("disabled in the options"), "{records:?}");
+    }
+}
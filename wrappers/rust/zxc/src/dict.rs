@@ -43,12 +43,14 @@
 //! # Ok::<(), zxc::Error>(())
 //! ```
 
-use std::ffi::c_void;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_void;
 
 pub use zxc_sys::{ZXC_DICT_SIZE_MAX, ZXC_HUF_TABLE_SIZE};
 
 use crate::error::error_from_code;
-use crate::{Error, Result};
+use crate::{Error, Result, ffi};
 
 /// Trains a dictionary from a corpus of samples.
 ///
@@ -74,7 +76,7 @@ pub fn train_dict(samples: &[&[u8]], max_size: usize) -> Result<Vec<u8>> {
 
     let mut buf = vec![0u8; cap];
     let written = unsafe {
-        zxc_sys::zxc_train_dict(
+        ffi::zxc_train_dict(
             ptrs.as_ptr(),
             sizes.as_ptr(),
             samples.len(),
@@ -97,10 +99,10 @@ pub fn train_dict(samples: &[&[u8]], max_size: usize) -> Result<Vec<u8>> {
 /// Returns 0 for empty content.
 pub fn dict_id(content: &[u8]) -> u32 {
     unsafe {
-        zxc_sys::zxc_dict_id(
+        ffi::zxc_dict_id(
             content.as_ptr() as *const c_void,
             content.len(),
-            std::ptr::null(),
+            core::ptr::null(),
         )
     }
 }
@@ -110,14 +112,14 @@ pub fn dict_id(content: &[u8]) -> u32 {
 /// Returns 0 if the archive was produced without a dictionary, or if the
 /// buffer is not a valid archive.
 pub fn get_dict_id(archive: &[u8]) -> u32 {
-    unsafe { zxc_sys::zxc_get_dict_id(archive.as_ptr() as *const c_void, archive.len()) }
+    unsafe { ffi::zxc_get_dict_id(archive.as_ptr() as *const c_void, archive.len()) }
 }
 
 /// Returns the dictionary ID stored in a `.zxd` file buffer.
 ///
 /// Returns 0 if the buffer is not a valid `.zxd` file.
 pub fn dict_get_id(zxd: &[u8]) -> u32 {
-    unsafe { zxc_sys::zxc_dict_get_id(zxd.as_ptr() as *const c_void, zxd.len()) }
+    unsafe { ffi::zxc_dict_get_id(zxd.as_ptr() as *const c_void, zxd.len()) }
 }
 
 /// Trains the shared literal Huffman table for an already-trained dictionary.
@@ -140,7 +142,7 @@ pub fn train_dict_huf(samples: &[&[u8]], dict: &[u8]) -> Result<[u8; ZXC_HUF_TAB
 
     let mut huf = [0u8; ZXC_HUF_TABLE_SIZE];
     let rc = unsafe {
-        zxc_sys::zxc_train_dict_huf(
+        ffi::zxc_train_dict_huf(
             ptrs.as_ptr(),
             sizes.as_ptr(),
             samples.len(),
@@ -170,10 +172,10 @@ pub fn dict_save(content: &[u8], huf_lengths: &[u8]) -> Result<Vec<u8>> {
     if huf_lengths.len() != ZXC_HUF_TABLE_SIZE {
         return Err(Error::InvalidData);
     }
-    let bound = unsafe { zxc_sys::zxc_dict_save_bound(content.len()) };
+    let bound = unsafe { ffi::zxc_dict_save_bound(content.len()) };
     let mut buf = vec![0u8; bound];
     let written = unsafe {
-        zxc_sys::zxc_dict_save(
+        ffi::zxc_dict_save(
             content.as_ptr() as *const c_void,
             content.len(),
             huf_lengths.as_ptr() as *const c_void,
@@ -191,13 +193,13 @@ pub fn dict_save(content: &[u8], huf_lengths: &[u8]) -> Result<Vec<u8>> {
 /// Returns an owned copy of the shared Huffman table stored in a `.zxd`
 /// buffer, or `None` if the buffer is not a valid `.zxd` file.
 pub fn dict_huf(zxd: &[u8]) -> Option<[u8; ZXC_HUF_TABLE_SIZE]> {
-    let p = unsafe { zxc_sys::zxc_dict_huf(zxd.as_ptr() as *const c_void, zxd.len()) };
+    let p = unsafe { ffi::zxc_dict_huf(zxd.as_ptr() as *const c_void, zxd.len()) };
     if p.is_null() {
         return None;
     }
     let mut huf = [0u8; ZXC_HUF_TABLE_SIZE];
     // The pointer aims into `zxd` (zero-copy); copy out for an owned result.
-    huf.copy_from_slice(unsafe { std::slice::from_raw_parts(p as *const u8, ZXC_HUF_TABLE_SIZE) });
+    huf.copy_from_slice(unsafe { core::slice::from_raw_parts(p as *const u8, ZXC_HUF_TABLE_SIZE) });
     Some(huf)
 }
 
@@ -239,10 +241,10 @@ impl Dictionary {
             .collect();
         let sizes: Vec<usize> = samples.iter().map(|s| s.len()).collect();
 
-        let cap = unsafe { zxc_sys::zxc_dict_save_bound(ZXC_DICT_SIZE_MAX) };
+        let cap = unsafe { ffi::zxc_dict_save_bound(ZXC_DICT_SIZE_MAX) };
         let mut zxd = vec![0u8; cap];
         let written = unsafe {
-            zxc_sys::zxc_dict_train(
+            ffi::zxc_dict_train(
                 ptrs.as_ptr(),
                 sizes.as_ptr(),
                 samples.len(),
@@ -263,13 +265,13 @@ impl Dictionary {
 
     /// Parses `.zxd` bytes into an owned dictionary.
     pub fn load(zxd: &[u8]) -> Result<Self> {
-        let mut content_ptr: *const c_void = std::ptr::null();
+        let mut content_ptr: *const c_void = core::ptr::null();
         let mut content_size: usize = 0;
-        let mut huf_ptr: *const c_void = std::ptr::null();
+        let mut huf_ptr: *const c_void = core::ptr::null();
         let mut id: u32 = 0;
 
         let rc = unsafe {
-            zxc_sys::zxc_dict_load(
+            ffi::zxc_dict_load(
                 zxd.as_ptr() as *const c_void,
                 zxd.len(),
                 &mut content_ptr,
@@ -287,12 +289,12 @@ impl Dictionary {
         let content = if content_ptr.is_null() || content_size == 0 {
             Vec::new()
         } else {
-            unsafe { std::slice::from_raw_parts(content_ptr as *const u8, content_size).to_vec() }
+            unsafe { core::slice::from_raw_parts(content_ptr as *const u8, content_size).to_vec() }
         };
         let mut huf = [0u8; ZXC_HUF_TABLE_SIZE];
         if !huf_ptr.is_null() {
             huf.copy_from_slice(unsafe {
-                std::slice::from_raw_parts(huf_ptr as *const u8, ZXC_HUF_TABLE_SIZE)
+                core::slice::from_raw_parts(huf_ptr as *const u8, ZXC_HUF_TABLE_SIZE)
             });
         }
         Ok(Self { content, huf, id })
@@ -363,6 +365,7 @@ mod tests {
             verify_checksum: true,
             dict: Some(dict),
             dict_huf: None,
+            ..Default::default()
         };
         let restored = decompress_with_options(&archive, &dopts).expect("decompress with dict");
         assert_eq!(restored, sample);
@@ -0,0 +1,136 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Overrides read from the environment (`ZXC_LEVEL`, `ZXC_NTHREADS` and
+//! `ZXC_NO_SIMD`); see the crate documentation.
+
+#[cfg(all(
+    feature = "std",
+    not(any(feature = "no-stream", target_family = "wasm"))
+))]
+pub(crate) use imp::threads;
+pub(crate) use imp::{init, level};
+
+#[cfg(feature = "std")]
+mod imp {
+    use std::string::String;
+    use std::sync::OnceLock;
+
+    use crate::{Level, diag, ffi};
+
+    #[derive(Debug, Default, PartialEq)]
+    pub(super) struct Env {
+        pub(super) level: Option<Level>,
+        pub(super) threads: Option<usize>,
+        pub(super) no_simd: bool,
+    }
+
+    impl Env {
+        pub(super) fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+            let level = var(&lookup, "ZXC_LEVEL", |v| {
+                Level::try_from(v.parse::<i32>().ok()?).ok()
+            });
+            Self {
+                level,
+                threads: var(&lookup, "ZXC_NTHREADS", |v| v.parse().ok()),
+                no_simd: lookup("ZXC_NO_SIMD").is_some_and(|v| !matches!(v.trim(), "" | "0")),
+            }
+        }
+    }
+
+    /// Parses the variable `name`, logging a value that does not parse.
+    fn var<T>(
+        lookup: impl Fn(&str) -> Option<String>,
+        name: &str,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let value = lookup(name)?;
+        let parsed = parse(value.trim());
+        if parsed.is_none() {
+            diag::ignored_env(name, &value);
+        }
+        parsed
+    }
+
+    fn get() -> &'static Env {
+        static ENV: OnceLock<Env> = OnceLock::new();
+        ENV.get_or_init(|| {
+            let env = Env::from_lookup(|name| std::env::var(name).ok());
+            if env.no_simd {
+                unsafe { ffi::zxc_simd_force(zxc_sys::ZXC_SIMD_DEFAULT) };
+            }
+            env
+        })
+    }
+
+    /// Reads the environment and applies `ZXC_NO_SIMD`, once per process.
+    pub(crate) fn init() {
+        get();
+    }
+
+    /// The level to compress at: `ZXC_LEVEL` when `level` is the default.
+    pub(crate) fn level(level: Level) -> Level {
+        match get().level {
+            Some(l) if level == Level::Default => l,
+            _ => level,
+        }
+    }
+
+    /// The file API's thread count: `ZXC_NTHREADS` when `threads` is unset.
+    #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+    pub(crate) fn threads(threads: Option<usize>) -> Option<usize> {
+        threads.or(get().threads)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use crate::Level;
+
+    #[inline(always)]
+    pub(crate) fn init() {}
+
+    #[inline(always)]
+    pub(crate) fn level(level: Level) -> Level {
+        level
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::imp::Env;
+    use crate::Level;
+
+    fn env(vars: &[(&str, &str)]) -> Env {
+        Env::from_lookup(|name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        assert_eq!(env(&[]), Env::default());
+        let e = env(&[
+            ("ZXC_LEVEL", "5"),
+            ("ZXC_NTHREADS", " 4 "),
+            ("ZXC_NO_SIMD", "1"),
+        ]);
+        assert_eq!(e.level, Some(Level::Compact));
+        assert_eq!(e.threads, Some(4));
+        assert!(e.no_simd);
+
+        let e = env(&[
+            ("ZXC_LEVEL", "9"),
+            ("ZXC_NTHREADS", "many"),
+            ("ZXC_NO_SIMD", "0"),
+        ]);
+        assert_eq!(e, Env::default());
+        assert_eq!(env(&[("ZXC_LEVEL", "fast")]).level, None);
+    }
+}
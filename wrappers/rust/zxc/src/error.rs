@@ -7,6 +7,10 @@
 
 //! Error types and code mapping shared across the crate.
 
+use alloc::boxed::Box;
+
+use crate::cpu::Variant;
+
 use zxc_sys::{
     ZXC_ERROR_BAD_BLOCK_SIZE, ZXC_ERROR_BAD_BLOCK_TYPE, ZXC_ERROR_BAD_CHECKSUM,
     ZXC_ERROR_BAD_HEADER, ZXC_ERROR_BAD_LEVEL, ZXC_ERROR_BAD_MAGIC, ZXC_ERROR_BAD_OFFSET,
@@ -16,15 +20,22 @@ use zxc_sys::{
 };
 
 /// Errors that can occur during ZXC operations.
+///
+/// New variants may come in a minor release.
 #[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     /// Memory allocation failure
     #[error("memory allocation failed")]
     Memory,
 
     /// Destination buffer too small
-    #[error("destination buffer too small")]
-    DstTooSmall,
+    #[error("destination buffer too small{}", Needed(*.needed))]
+    DstTooSmall {
+        /// A size the buffer must have for the call to succeed, or 0 when
+        /// it is not known
+        needed: usize,
+    },
 
     /// Source buffer too small or truncated input
     #[error("source buffer too small or truncated")]
@@ -94,20 +105,118 @@ pub enum Error {
     #[error("unsupported option: {0}")]
     Unsupported(&'static str),
 
+    /// An option has a value the C library cannot be handed, such as a
+    /// Huffman table of the wrong size
+    #[error("invalid option: {0}")]
+    InvalidOption(&'static str),
+
     /// The compressed data appears to be invalid or truncated
     #[error("invalid compressed data")]
     InvalidData,
 
+    /// A size does not fit the platform's address space (32-bit targets) or
+    /// the format's limits
+    #[error("size {0} exceeds the supported range")]
+    TooLarge(u64),
+
+    /// A stream was fed a different number of bytes than
+    /// [`CompressOptions::pledged_size`](crate::CompressOptions::pledged_size)
+    #[error("pledged {pledged} bytes of input, got {actual}")]
+    PledgeMismatch {
+        /// The pledged size
+        pledged: u64,
+        /// The bytes fed so far (at the end of the stream, all of them)
+        actual: u64,
+    },
+
+    /// A block's checksum, or the frame's global hash in the footer,
+    /// disagrees with the data: the values stored in the frame and computed
+    /// from what was read
+    #[error("checksum mismatch: expected {expected:#010x}, computed {actual:#010x}")]
+    ChecksumMismatch {
+        /// Index of the block, `None` for the global hash
+        block: Option<u32>,
+        /// The value stored in the frame
+        expected: u64,
+        /// The value computed from the data
+        actual: u64,
+    },
+
     /// Unknown error code from C library
     #[error("unknown error (code: {0})")]
     Unknown(i32),
+
+    /// Decoding failed at a known place in the frame.
+    ///
+    /// `block` is the index of the failing data block, or `None` when every
+    /// block decoded and the trailer (declared size or global checksum)
+    /// disagrees. `offset` is the byte offset of that block header, or of
+    /// the footer, in the compressed input. [`Error::cause`] gives the
+    /// underlying error.
+    #[error("{source} ({})", Location(*.block, *.offset))]
+    Corrupted {
+        /// Index of the failing block, `None` for the trailer
+        block: Option<u32>,
+        /// Byte offset in the compressed input
+        offset: u64,
+        /// What went wrong there
+        source: Box<Error>,
+    },
+
+    /// [`crate::self_test`] caught a code path misbehaving.
+    #[error("self-test failed on the {variant} code path: {source}")]
+    SelfTest {
+        /// The SIMD variant that failed
+        variant: Variant,
+        /// What went wrong on it
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// The underlying error, with any [`Error::Corrupted`] location or
+    /// [`Error::SelfTest`] variant peeled off.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Corrupted { source, .. } | Error::SelfTest { source, .. } => source.cause(),
+            other => other,
+        }
+    }
+}
+
+/// Formats the position of an [`Error::Corrupted`].
+struct Location(Option<u32>, u64);
+
+impl core::fmt::Display for Location {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Some(block) => write!(f, "block {block} at byte {}", self.1),
+            None => write!(f, "frame trailer at byte {}", self.1),
+        }
+    }
+}
+
+/// Formats the size an [`Error::DstTooSmall`] asks for, if known.
+struct Needed(usize);
+
+impl core::fmt::Display for Needed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            n => write!(f, " ({n} bytes needed)"),
+        }
+    }
 }
 
 /// Convert a negative error code from the C library to a Rust [`Error`].
 pub(crate) fn error_from_code(code: i64) -> Error {
-    match code as i32 {
+    // Codes are small negative `int`s; anything else is no code at all.
+    let Ok(code) = i32::try_from(code) else {
+        return Error::Unknown(i32::MIN);
+    };
+    match code {
         ZXC_ERROR_MEMORY => Error::Memory,
-        ZXC_ERROR_DST_TOO_SMALL => Error::DstTooSmall,
+        ZXC_ERROR_DST_TOO_SMALL => Error::DstTooSmall { needed: 0 },
         ZXC_ERROR_SRC_TOO_SMALL => Error::SrcTooSmall,
         ZXC_ERROR_BAD_MAGIC => Error::BadMagic,
         ZXC_ERROR_BAD_VERSION => Error::BadVersion,
@@ -124,9 +233,58 @@ pub(crate) fn error_from_code(code: i64) -> Error {
         ZXC_ERROR_DICT_MISMATCH => Error::DictMismatch,
         ZXC_ERROR_DICT_TOO_LARGE => Error::DictTooLarge,
         ZXC_ERROR_BAD_LEVEL => Error::BadLevel,
-        _ => Error::Unknown(code as i32),
+        _ => Error::Unknown(code),
     }
 }
 
+/// Convert an [`Error`] back to the C library's code, for callers that
+/// speak the C ABI.
+#[cfg(feature = "std")]
+pub(crate) fn code_from_error(e: &Error) -> i32 {
+    match e.cause() {
+        Error::Memory => ZXC_ERROR_MEMORY,
+        Error::DstTooSmall { .. } => ZXC_ERROR_DST_TOO_SMALL,
+        Error::SrcTooSmall => ZXC_ERROR_SRC_TOO_SMALL,
+        Error::BadMagic => ZXC_ERROR_BAD_MAGIC,
+        Error::BadVersion => ZXC_ERROR_BAD_VERSION,
+        Error::BadHeader => ZXC_ERROR_BAD_HEADER,
+        Error::BadChecksum | Error::ChecksumMismatch { .. } => ZXC_ERROR_BAD_CHECKSUM,
+        Error::BadOffset => ZXC_ERROR_BAD_OFFSET,
+        Error::Overflow | Error::TooLarge(_) => ZXC_ERROR_OVERFLOW,
+        Error::PledgeMismatch { pledged, actual } if actual < pledged => ZXC_ERROR_SRC_TOO_SMALL,
+        Error::PledgeMismatch { .. } => ZXC_ERROR_OVERFLOW,
+        Error::Io => ZXC_ERROR_IO,
+        Error::NullInput | Error::InvalidOption(_) => ZXC_ERROR_NULL_INPUT,
+        Error::BadBlockType => ZXC_ERROR_BAD_BLOCK_TYPE,
+        Error::BadBlockSize => ZXC_ERROR_BAD_BLOCK_SIZE,
+        Error::DictRequired => ZXC_ERROR_DICT_REQUIRED,
+        Error::DictMismatch => ZXC_ERROR_DICT_MISMATCH,
+        Error::DictTooLarge => ZXC_ERROR_DICT_TOO_LARGE,
+        Error::BadLevel => ZXC_ERROR_BAD_LEVEL,
+        Error::Unknown(code) => *code,
+        Error::CorruptData
+        | Error::InvalidData
+        | Error::Unsupported(_)
+        | Error::Corrupted { .. }
+        | Error::SelfTest { .. } => ZXC_ERROR_CORRUPT_DATA,
+    }
+}
+
+/// Converts a byte count returned by the C library, or the error code it
+/// returned instead, to a [`Result`].
+pub(crate) fn size_from_code(code: i64) -> Result<usize> {
+    if code < 0 {
+        Err(error_from_code(code))
+    } else {
+        size_to_usize(code.unsigned_abs())
+    }
+}
+
+/// Converts a size reported by the C library (or stored in a frame) to
+/// `usize`, failing instead of truncating on 32-bit targets.
+pub(crate) fn size_to_usize(size: u64) -> Result<usize> {
+    usize::try_from(size).map_err(|_| Error::TooLarge(size))
+}
+
 /// Result type for ZXC operations.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
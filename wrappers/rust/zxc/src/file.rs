@@ -6,16 +6,34 @@
  */
 
 //! File-based multi-threaded streaming API.
-
+//!
+//! These functions hand the files to the C driver as `FILE*` streams;
+//! [`ZxcFileReader`] and [`ZxcFileWriter`] own such streams, to keep them
+//! open across calls. For other readers and writers, use
+//! [`crate::compress_stream`] / [`crate::decompress_stream`], which run on
+//! the push API instead.
+//!
+//! Builds without the C driver (the `no-stream` feature, and all wasm
+//! targets) keep these signatures, but every call fails with
+//! [`io::ErrorKind::Unsupported`] unless a deadline is set.
+//! [`compress_to_writer`], [`decompress_from_reader`] and
+//! [`decompress_to_writer`], which move a file to or from a socket or a
+//! pipe, run on the push API and work in every build.
+
+use std::fmt;
 use std::fs::File;
-use std::io;
-use std::path::Path;
-
-#[cfg(unix)]
-use std::os::unix::io::AsRawFd;
-
-use crate::error::error_from_code;
-use crate::{Error, Level};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{self, Operation};
+use crate::stdio::{compress_until, decompress_until};
+use crate::{CompressOptions, DecompressOptions, Error, Level, MemoryOptions, diag, memory};
 
 /// Options for streaming compression operations.
 #[derive(Debug, Clone)]
@@ -28,6 +46,11 @@ pub struct StreamCompressOptions {
     pub checksum: bool,
     /// Enable seek table for random-access decompression (default: `false`)
     pub seekable: bool,
+    /// Give up with [`StreamError::TimedOut`] past this instant (default:
+    /// `None`). See [`compress_file_with_options`].
+    pub deadline: Option<Instant>,
+    /// Called with the [`BlockStats`] of every block (default: `None`).
+    pub on_block: Option<BlockCallback>,
 }
 
 impl Default for StreamCompressOptions {
@@ -37,6 +60,8 @@ impl Default for StreamCompressOptions {
             threads: None,
             checksum: true,
             seekable: false,
+            deadline: None,
+            on_block: None,
         }
     }
 }
@@ -50,7 +75,9 @@ impl StreamCompressOptions {
         }
     }
 
-    /// Set the number of worker threads.
+    /// Set the number of worker threads. Counts above the C library's
+    /// `ZXC_MAX_THREADS` run that many; one beyond `i32::MAX` fails with
+    /// [`Error::InvalidOption`].
     pub fn threads(mut self, n: usize) -> Self {
         self.threads = Some(n);
         self
@@ -67,6 +94,24 @@ impl StreamCompressOptions {
         self.seekable = true;
         self
     }
+
+    /// Abort with [`StreamError::TimedOut`] once `deadline` has passed.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Abort with [`StreamError::TimedOut`] once `timeout` has elapsed,
+    /// counted from now.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Report the statistics of every block to `f`.
+    pub fn on_block(mut self, f: impl Fn(&BlockStats) + Send + Sync + 'static) -> Self {
+        self.on_block = Some(BlockCallback::new(f));
+        self
+    }
 }
 
 /// Options for streaming decompression operations.
@@ -76,6 +121,11 @@ pub struct StreamDecompressOptions {
     pub threads: Option<usize>,
     /// Verify checksum during decompression (default: `true`)
     pub verify_checksum: bool,
+    /// Give up with [`StreamError::TimedOut`] past this instant (default:
+    /// `None`). See [`decompress_file_with_options`].
+    pub deadline: Option<Instant>,
+    /// Called with the [`BlockStats`] of every block (default: `None`).
+    pub on_block: Option<BlockCallback>,
 }
 
 impl Default for StreamDecompressOptions {
@@ -83,12 +133,15 @@ impl Default for StreamDecompressOptions {
         Self {
             threads: None,
             verify_checksum: true,
+            deadline: None,
+            on_block: None,
         }
     }
 }
 
 impl StreamDecompressOptions {
-    /// Set the number of worker threads.
+    /// Set the number of worker threads, as
+    /// [`StreamCompressOptions::threads`].
     pub fn threads(mut self, n: usize) -> Self {
         self.threads = Some(n);
         self
@@ -99,10 +152,136 @@ impl StreamDecompressOptions {
         self.verify_checksum = false;
         self
     }
+
+    /// Abort with [`StreamError::TimedOut`] once `deadline` has passed.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Abort with [`StreamError::TimedOut`] once `timeout` has elapsed,
+    /// counted from now.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Report the statistics of every block to `f`.
+    pub fn on_block(mut self, f: impl Fn(&BlockStats) + Send + Sync + 'static) -> Self {
+        self.on_block = Some(BlockCallback::new(f));
+        self
+    }
+}
+
+/// Statistics of one block of a file operation.
+///
+/// The C driver reports every block once it is written, in file order,
+/// from its writer thread. With the per-block sizes and times, a caller can
+/// map which parts of a file compress poorly or keep a worker busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockStats {
+    /// Block number in file order, from 0.
+    pub index: u64,
+    /// Bytes consumed: plain data when compressing, the encoded block
+    /// (header and checksum included) when decompressing.
+    pub in_size: u64,
+    /// Bytes produced.
+    pub out_size: u64,
+    /// Time the worker spent on the block, I/O excluded.
+    pub elapsed: Duration,
+    /// Worker thread that processed the block, from 0.
+    pub worker: usize,
+}
+
+/// Per-block callback of [`StreamCompressOptions`] and
+/// [`StreamDecompressOptions`], shared by their clones.
+///
+/// It runs on the driver's writer thread while the file function waits,
+/// and is not called for a job with a deadline, which runs without the
+/// driver. A panic in it is propagated once the file function returns;
+/// the remaining blocks are then not reported.
+#[derive(Clone)]
+pub struct BlockCallback(Arc<dyn Fn(&BlockStats) + Send + Sync>);
+
+impl BlockCallback {
+    /// Wraps `f`.
+    pub fn new(f: impl Fn(&BlockStats) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Invokes the callback.
+    pub fn call(&self, stats: &BlockStats) {
+        (self.0)(stats)
+    }
+}
+
+// Keeps the options unwind-safe, as they were before the callback: it only
+// runs inside the file functions, which catch its panics themselves.
+impl UnwindSafe for BlockCallback {}
+impl RefUnwindSafe for BlockCallback {}
+
+impl fmt::Debug for BlockCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlockCallback(..)")
+    }
+}
+
+/// Where the time of a file operation went, from
+/// [`compress_file_with_stats`] and [`decompress_file_with_stats`].
+///
+/// The C driver reads on the calling thread, hands blocks to its workers
+/// and writes from a writer thread, so the stages overlap. A run is I/O
+/// bound when `read` or `write` is close to `wall`; it is CPU bound when
+/// [`utilization`](Self::utilization) is close to 1, and more threads may
+/// help.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StreamStats {
+    /// Bytes written to the output.
+    pub bytes_out: u64,
+    /// Duration of the whole call.
+    pub wall: Duration,
+    /// Time spent reading blocks from the input.
+    pub read: Duration,
+    /// Time the workers spent on blocks, summed over the workers.
+    pub compute: Duration,
+    /// Time spent writing blocks to the output.
+    pub write: Duration,
+    /// Busy time of each worker, indexed like [`BlockStats::worker`].
+    pub workers: Vec<Duration>,
+}
+
+impl StreamStats {
+    /// Share of the workers' time spent on blocks, from 0.0 to 1.0.
+    pub fn utilization(&self) -> f64 {
+        let available = self.wall.as_secs_f64() * self.workers.len() as f64;
+        if available > 0.0 {
+            (self.compute.as_secs_f64() / available).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Share of each worker's time spent on blocks, from 0.0 to 1.0.
+    pub fn worker_utilization(&self) -> Vec<f64> {
+        let wall = self.wall.as_secs_f64();
+        self.workers
+            .iter()
+            .map(|busy| {
+                if wall > 0.0 {
+                    (busy.as_secs_f64() / wall).min(1.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
 }
 
 /// Errors specific to the streaming file API.
+///
+/// New variants may come in a minor release.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum StreamError {
     /// I/O error during file operations
     #[error("I/O error: {0}")]
@@ -123,149 +302,48 @@ pub enum StreamError {
     /// Invalid compressed file
     #[error("invalid compressed file")]
     InvalidFile,
-}
-
-/// Result type for streaming operations.
-pub type StreamResult<T> = std::result::Result<T, StreamError>;
-
-/// Convert a Rust File to a C FILE* for read operations.
-///
-/// This function duplicates the file descriptor before passing it to fdopen,
-/// so the returned FILE* owns its own fd and must be closed with fclose().
-#[cfg(unix)]
-unsafe fn file_to_c_file_read(file: &File) -> *mut libc::FILE {
-    let fd = file.as_raw_fd();
-    // Duplicate the fd so C FILE* has its own ownership
-    let dup_fd = unsafe { libc::dup(fd) };
-    if dup_fd < 0 {
-        return std::ptr::null_mut();
-    }
-
-    let file_ptr = unsafe { libc::fdopen(dup_fd, c"rb".as_ptr()) };
-    if file_ptr.is_null() {
-        // fdopen failed, close the duplicated fd to avoid leak
-        unsafe {
-            libc::close(dup_fd);
-        }
-    }
-    file_ptr
-}
-
-/// Convert a Rust File to a C FILE* for write operations.
-///
-/// This function duplicates the file descriptor before passing it to fdopen,
-/// so the returned FILE* owns its own fd and must be closed with fclose().
-#[cfg(unix)]
-unsafe fn file_to_c_file_write(file: &File) -> *mut libc::FILE {
-    let fd = file.as_raw_fd();
-    // Duplicate the fd so C FILE* has its own ownership
-    let dup_fd = unsafe { libc::dup(fd) };
-    if dup_fd < 0 {
-        return std::ptr::null_mut();
-    }
 
-    let file_ptr = unsafe { libc::fdopen(dup_fd, c"wb".as_ptr()) };
-    if file_ptr.is_null() {
-        // fdopen failed, close the duplicated fd to avoid leak
-        unsafe {
-            libc::close(dup_fd);
-        }
-    }
-    file_ptr
+    /// The deadline passed before the operation completed
+    #[error("deadline exceeded")]
+    TimedOut,
+
+    /// The output's disk or quota filled up. `bytes_written` is the size
+    /// the output file had reached; the partial file is left in place.
+    #[error("disk full after {bytes_written} bytes written")]
+    DiskFull {
+        /// Size of the output file when the write failed
+        bytes_written: u64,
+    },
 }
 
-/// Convert a Rust File to a C FILE* for read operations (Windows).
-///
-/// This function duplicates the file handle before passing it to the C runtime,
-/// so the returned FILE* owns its own handle and must be closed with fclose().
-#[cfg(windows)]
-unsafe fn file_to_c_file_read(file: &File) -> *mut libc::FILE {
-    use std::os::windows::io::AsRawHandle;
-
-    let handle = file.as_raw_handle();
-
-    // Duplicate the handle so C FILE* has its own ownership
-    let mut dup_handle: *mut std::ffi::c_void = std::ptr::null_mut();
-    let result = unsafe {
-        windows_sys::Win32::Foundation::DuplicateHandle(
-            windows_sys::Win32::System::Threading::GetCurrentProcess(),
-            handle as *mut std::ffi::c_void,
-            windows_sys::Win32::System::Threading::GetCurrentProcess(),
-            &mut dup_handle,
-            0,
-            0,
-            windows_sys::Win32::Foundation::DUPLICATE_SAME_ACCESS,
-        )
-    };
-
-    if result == 0 {
-        return std::ptr::null_mut();
-    }
-
-    let fd = unsafe { libc::open_osfhandle(dup_handle as libc::intptr_t, libc::O_RDONLY) };
-    if fd < 0 {
-        // open_osfhandle failed, close the duplicated handle to avoid leak
-        unsafe {
-            windows_sys::Win32::Foundation::CloseHandle(dup_handle);
-        }
-        return std::ptr::null_mut();
-    }
-
-    let file_ptr = unsafe { libc::fdopen(fd, c"rb".as_ptr()) };
-    if file_ptr.is_null() {
-        // fdopen failed, close the fd (which will close the handle)
-        unsafe {
-            libc::close(fd);
-        }
+impl StreamError {
+    /// Whether the operation failed because a signal interrupted a read or
+    /// a write ([`io::ErrorKind::Interrupted`]): a transient failure, worth
+    /// retrying, unlike the other I/O errors.
+    ///
+    /// The file functions already start over on their own, a few times,
+    /// when both files are regular files; the error reaches the caller
+    /// when they cannot (a pipe was read from or written to) or when the
+    /// signals keep coming.
+    pub fn is_interrupted(&self) -> bool {
+        matches!(self, Self::Io(e) if e.kind() == io::ErrorKind::Interrupted)
     }
-    file_ptr
 }
 
-/// Convert a Rust File to a C FILE* for write operations (Windows).
-///
-/// This function duplicates the file handle before passing it to the C runtime,
-/// so the returned FILE* owns its own handle and must be closed with fclose().
-#[cfg(windows)]
-unsafe fn file_to_c_file_write(file: &File) -> *mut libc::FILE {
-    use std::os::windows::io::AsRawHandle;
-
-    let handle = file.as_raw_handle();
-
-    // Duplicate the handle so C FILE* has its own ownership
-    let mut dup_handle: *mut std::ffi::c_void = std::ptr::null_mut();
-    let result = unsafe {
-        windows_sys::Win32::Foundation::DuplicateHandle(
-            windows_sys::Win32::System::Threading::GetCurrentProcess(),
-            handle as *mut std::ffi::c_void,
-            windows_sys::Win32::System::Threading::GetCurrentProcess(),
-            &mut dup_handle,
-            0,
-            0,
-            windows_sys::Win32::Foundation::DUPLICATE_SAME_ACCESS,
-        )
-    };
-
-    if result == 0 {
-        return std::ptr::null_mut();
-    }
+/// Result type for streaming operations.
+pub type StreamResult<T> = std::result::Result<T, StreamError>;
 
-    let fd = unsafe { libc::open_osfhandle(dup_handle as libc::intptr_t, libc::O_WRONLY) };
-    if fd < 0 {
-        // open_osfhandle failed, close the duplicated handle to avoid leak
-        unsafe {
-            windows_sys::Win32::Foundation::CloseHandle(dup_handle);
-        }
-        return std::ptr::null_mut();
+/// Unwraps the [`Error`] carried by an I/O error from the push-API paths.
+pub(crate) fn stream_error(e: io::Error) -> StreamError {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => return Error::SrcTooSmall.into(),
+        io::ErrorKind::TimedOut => return StreamError::TimedOut,
+        _ => {}
     }
-
-    let file_ptr = unsafe { libc::fdopen(fd, c"wb".as_ptr()) };
-    if file_ptr.is_null() {
-        // fdopen failed, close the fd (which will close the handle)
-        unsafe {
-            libc::close(fd);
-        }
+    match e.downcast::<Error>() {
+        Ok(e) => e.into(),
+        Err(e) => e.into(),
     }
-    file_ptr
 }
 
 /// Compresses a file using multi-threaded streaming.
@@ -312,7 +390,7 @@ pub fn compress_file<P: AsRef<Path>>(
             level,
             threads,
             checksum: checksum.unwrap_or(false),
-            seekable: false,
+            ..Default::default()
         },
     )
 }
@@ -334,52 +412,168 @@ pub fn compress_file<P: AsRef<Path>>(
 /// let bytes = compress_file_with_options("input.bin", "output.zxc", &opts)?;
 /// # Ok::<(), zxc::StreamError>(())
 /// ```
+///
+/// # Deadlines
+///
+/// With `opts.deadline` set, the file is compressed on the single-threaded
+/// push API in a thread of its own, which checks the deadline between
+/// blocks; `threads` is ignored and a seek table cannot be requested. Once
+/// the deadline passes the call returns [`StreamError::TimedOut`] and
+/// leaves a partial output file. The thread stops at its next check and
+/// releases its buffers and files; one blocked in a read or write that
+/// never returns (a hung network mount) exits as soon as it does.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use zxc::{compress_file_with_options, StreamCompressOptions, StreamError};
+///
+/// let opts = StreamCompressOptions::default().timeout(Duration::from_secs(600));
+/// match compress_file_with_options("/mnt/nfs/input.bin", "output.zxc", &opts) {
+///     Err(StreamError::TimedOut) => eprintln!("gave up after 10 minutes"),
+///     other => println!("{other:?}"),
+/// }
+/// ```
 pub fn compress_file_with_options<P: AsRef<Path>>(
     input: P,
     output: P,
     opts: &StreamCompressOptions,
 ) -> StreamResult<u64> {
-    let f_in = File::open(input)?;
-    let f_out = File::create(output)?;
+    compress_file_counted(input.as_ref(), output.as_ref(), opts).map(|(_, written)| written)
+}
+
+/// [`compress_file_with_options`], returning the bytes read and written.
+fn compress_file_counted(
+    input: &Path,
+    output: &Path,
+    opts: &StreamCompressOptions,
+) -> StreamResult<(u64, u64)> {
+    let held = reserve(Some(opts.level), opts.threads, opts.deadline.is_some());
+    let timer = metrics::start();
+    let (read, written) = match opts.deadline {
+        None => imp::compress_file(input, output, opts, None)?,
+        Some(deadline) => compress_file_until(input, output, opts, deadline, held)?,
+    };
+    timer.finish(Operation::CompressFile, read, written);
+    Ok((read, written))
+}
 
-    unsafe {
-        let c_in = file_to_c_file_read(&f_in);
-        let c_out = file_to_c_file_write(&f_out);
+/// [`compress_file_with_options`], also reporting where the time went.
+///
+/// Use the [`StreamStats`] to tell an I/O-bound run from a CPU-bound one
+/// before picking a thread count:
+///
+/// ```rust,no_run
+/// use zxc::{compress_file_with_stats, StreamCompressOptions};
+///
+/// let opts = StreamCompressOptions::default();
+/// let stats = compress_file_with_stats("input.bin", "output.zxc", &opts)?;
+/// println!(
+///     "read {:?}, compute {:?} ({:.0}% busy), write {:?} of {:?}",
+///     stats.read, stats.compute, stats.utilization() * 100.0, stats.write, stats.wall,
+/// );
+/// # Ok::<(), zxc::StreamError>(())
+/// ```
+///
+/// The stages are measured by the multi-threaded driver, so `opts.deadline`
+/// must not be set: the call then fails with
+/// [`io::ErrorKind::InvalidInput`].
+pub fn compress_file_with_stats<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    opts: &StreamCompressOptions,
+) -> StreamResult<StreamStats> {
+    if opts.deadline.is_some() {
+        return Err(no_stats_under_deadline());
+    }
+    let _held = reserve(Some(opts.level), opts.threads, false);
+    let timer = metrics::start();
+    let mut stats = StreamStats::default();
+    let (read, written) =
+        imp::compress_file(input.as_ref(), output.as_ref(), opts, Some(&mut stats))?;
+    timer.finish(Operation::CompressFile, read, written);
+    Ok(stats)
+}
 
-        // Check for errors and cleanup on failure
-        if c_in.is_null() {
-            if !c_out.is_null() {
-                libc::fclose(c_out);
-            }
-            return Err(StreamError::Io(io::Error::last_os_error()));
-        }
-        if c_out.is_null() {
-            libc::fclose(c_in);
-            return Err(StreamError::Io(io::Error::last_os_error()));
+/// Takes the memory budget share of a file operation: compressing at
+/// `level` or decompressing with `None`, on the driver's threads or, under
+/// a deadline, on one.
+fn reserve(level: Option<Level>, threads: Option<usize>, deadline: bool) -> memory::Reservation {
+    let opts = match deadline {
+        true => MemoryOptions::with_level(level.unwrap_or_default()),
+        false => {
+            #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+            let threads = crate::env::threads(threads);
+            let threads =
+                threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+            MemoryOptions::file(level.unwrap_or_default(), threads)
         }
+    };
+    memory::reserve_file(level, &opts)
+}
 
-        let result = zxc_sys::zxc_stream_compress(
-            c_in,
-            c_out,
-            &zxc_sys::zxc_compress_opts_t {
-                n_threads: opts.threads.unwrap_or(0) as i32,
-                level: opts.level as i32,
-                checksum_enabled: opts.checksum as i32,
-                seekable: opts.seekable as i32,
-                ..Default::default()
-            },
-        );
+fn no_stats_under_deadline() -> StreamError {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "stage timings are not measured under a deadline",
+    )
+    .into()
+}
+
+/// Whether `e` is a write to a disk or quota with no room left.
+fn is_full(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    if e.raw_os_error() == Some(libc::EDQUOT) {
+        return true;
+    }
+    e.kind() == io::ErrorKind::StorageFull
+}
 
-        // Always close C FILE handles (they own duplicated fds)
-        libc::fclose(c_in);
-        libc::fclose(c_out);
+/// `e` as [`StreamError::DiskFull`] when it is a full disk met writing
+/// `output`.
+fn full_disk(e: StreamError, output: &Path) -> StreamError {
+    match &e {
+        StreamError::Io(io) if is_full(io) => StreamError::DiskFull {
+            bytes_written: std::fs::metadata(output).map_or(0, |m| m.len()),
+        },
+        _ => e,
+    }
+}
 
-        if result < 0 {
-            Err(StreamError::BufferError(error_from_code(result)))
-        } else {
-            Ok(result as u64)
-        }
+/// [`compress_file_with_options`] on the push API, abandoned at `deadline`.
+///
+/// The job keeps `held`, so an abandoned one gives its budget share back
+/// when it actually ends rather than when the caller stops waiting.
+fn compress_file_until(
+    input: &Path,
+    output: &Path,
+    opts: &StreamCompressOptions,
+    deadline: Instant,
+    held: memory::Reservation,
+) -> StreamResult<(u64, u64)> {
+    if opts.seekable {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a seek table cannot be written under a deadline",
+        )
+        .into());
+    }
+    if let Some(n) = opts.threads.filter(|&n| n > 1) {
+        diag::threads(n, 1, "a deadline is set");
     }
+    let (input, output) = (input.to_owned(), output.to_owned());
+    let job_output = output.clone();
+    let options = CompressOptions {
+        level: opts.level,
+        checksum: opts.checksum,
+        ..Default::default()
+    };
+    with_deadline(deadline, move || {
+        let _held = held;
+        let reader = BufReader::new(open_path(&input, |p| File::open(p))?);
+        let writer = create_path(&job_output)?;
+        compress_until(reader, writer, Some(&options), Some(deadline))
+    })
+    .map_err(|e| full_disk(e, &output))
 }
 
 /// Decompresses a file using multi-threaded streaming.
@@ -404,7 +598,7 @@ pub fn decompress_file<P: AsRef<Path>>(
         output,
         &StreamDecompressOptions {
             threads,
-            verify_checksum: true,
+            ..Default::default()
         },
     )
 }
@@ -424,51 +618,129 @@ pub fn decompress_file<P: AsRef<Path>>(
 /// let bytes = decompress_file_with_options("compressed.zxc", "output.bin", &opts)?;
 /// # Ok::<(), zxc::StreamError>(())
 /// ```
+///
+/// With `opts.deadline` set, the file is decompressed on the push API in a
+/// thread of its own and `threads` is ignored; see the deadline section of
+/// [`compress_file_with_options`].
 pub fn decompress_file_with_options<P: AsRef<Path>>(
     input: P,
     output: P,
     opts: &StreamDecompressOptions,
 ) -> StreamResult<u64> {
-    let f_in = File::open(input)?;
-    let f_out = File::create(output)?;
+    let held = reserve(None, opts.threads, opts.deadline.is_some());
+    let timer = metrics::start();
+    let (read, written) = match opts.deadline {
+        None => imp::decompress_file(input.as_ref(), output.as_ref(), opts, None)?,
+        Some(deadline) => {
+            decompress_file_until(input.as_ref(), output.as_ref(), opts, deadline, held)?
+        }
+    };
+    timer.finish(Operation::DecompressFile, read, written);
+    Ok(written)
+}
 
-    let n_threads = opts.threads.unwrap_or(0) as i32;
-    let checksum_enabled = opts.verify_checksum as i32;
+/// [`decompress_file_with_options`], also reporting where the time went.
+///
+/// See [`compress_file_with_stats`]; `opts.deadline` must not be set.
+pub fn decompress_file_with_stats<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    opts: &StreamDecompressOptions,
+) -> StreamResult<StreamStats> {
+    if opts.deadline.is_some() {
+        return Err(no_stats_under_deadline());
+    }
+    let _held = reserve(None, opts.threads, false);
+    let timer = metrics::start();
+    let mut stats = StreamStats::default();
+    let (read, written) =
+        imp::decompress_file(input.as_ref(), output.as_ref(), opts, Some(&mut stats))?;
+    timer.finish(Operation::DecompressFile, read, written);
+    Ok(stats)
+}
 
-    unsafe {
-        let c_in = file_to_c_file_read(&f_in);
-        let c_out = file_to_c_file_write(&f_out);
+/// Whether `f` is a regular file too short to hold a frame, an empty one
+/// typically. Decoders fail it as [`file_decompressed_size`] does, with
+/// [`Error::SrcTooSmall`], before creating the output. Pipes and devices,
+/// whose length is unknown, are left to the decoder.
+fn too_short(f: &File) -> bool {
+    const MIN_FRAME: u64 = (crate::FRAME_HEADER_SIZE + crate::FOOTER_SIZE) as u64;
+    is_regular(f) && f.metadata().is_ok_and(|m| m.len() < MIN_FRAME)
+}
 
-        // Check for errors and cleanup on failure
-        if c_in.is_null() {
-            if !c_out.is_null() {
-                libc::fclose(c_out);
-            }
-            return Err(StreamError::Io(io::Error::last_os_error()));
-        }
-        if c_out.is_null() {
-            libc::fclose(c_in);
-            return Err(StreamError::Io(io::Error::last_os_error()));
-        }
+/// Whether `f` is a regular file, whose length is that of its content.
+#[cfg(not(windows))]
+fn is_regular(f: &File) -> bool {
+    f.metadata().is_ok_and(|m| m.is_file())
+}
 
-        let result = zxc_sys::zxc_stream_decompress(
-            c_in,
-            c_out,
-            &zxc_sys::zxc_decompress_opts_t {
-                n_threads,
-                checksum_enabled,
-                ..Default::default()
-            },
-        );
+/// Windows reports pipes as files of no length: only a disk file is
+/// regular.
+#[cfg(windows)]
+fn is_regular(f: &File) -> bool {
+    use std::os::windows::io::AsRawHandle;
 
-        // Always close C FILE handles (they own duplicated fds)
-        libc::fclose(c_in);
-        libc::fclose(c_out);
+    const FILE_TYPE_DISK: u32 = 0x0001;
+    unsafe extern "system" {
+        fn GetFileType(file: *mut std::ffi::c_void) -> u32;
+    }
+    // SAFETY: the handle is open for as long as `f` is borrowed.
+    let disk = unsafe { GetFileType(f.as_raw_handle()) } == FILE_TYPE_DISK;
+    disk && f.metadata().is_ok_and(|m| m.is_file())
+}
 
-        if result < 0 {
-            Err(StreamError::BufferError(error_from_code(result)))
-        } else {
-            Ok(result as u64)
+/// [`decompress_file_with_options`] on the push API, abandoned at
+/// `deadline`. The job keeps `held`, as in [`compress_file_until`].
+fn decompress_file_until(
+    input: &Path,
+    output: &Path,
+    opts: &StreamDecompressOptions,
+    deadline: Instant,
+    held: memory::Reservation,
+) -> StreamResult<(u64, u64)> {
+    if let Some(n) = opts.threads.filter(|&n| n > 1) {
+        diag::threads(n, 1, "a deadline is set");
+    }
+    let (input, job_output) = (input.to_owned(), output.to_owned());
+    let options = DecompressOptions {
+        verify_checksum: opts.verify_checksum,
+        ..Default::default()
+    };
+    with_deadline(deadline, move || {
+        let _held = held;
+        let reader = open_path(&input, |p| File::open(p))?;
+        if too_short(&reader) {
+            return Err(io::Error::other(Error::SrcTooSmall));
+        }
+        let mut writer = BufWriter::new(create_path(&job_output)?);
+        let counts = decompress_until(reader, &mut writer, Some(&options), Some(deadline))?;
+        writer.flush()?;
+        Ok(counts)
+    })
+    .map_err(|e| full_disk(e, output))
+}
+
+/// Runs `job` on a thread of its own, waiting for it until `deadline`.
+///
+/// The job checks the deadline itself and winds down on its own; the
+/// caller only stops waiting for it.
+fn with_deadline<T, F>(deadline: Instant, job: F) -> StreamResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("zxc-deadline".into())
+        .spawn(move || {
+            // The caller may have stopped listening.
+            let _ = tx.send(job());
+        })?;
+    match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(result) => result.map_err(stream_error),
+        Err(RecvTimeoutError::Timeout) => Err(StreamError::TimedOut),
+        Err(RecvTimeoutError::Disconnected) => {
+            Err(io::Error::other("zxc: deadline worker panicked").into())
         }
     }
 }
@@ -478,6 +750,12 @@ pub fn decompress_file_with_options<P: AsRef<Path>>(
 /// This reads the file footer without performing decompression,
 /// useful for pre-allocating buffers or showing progress.
 ///
+/// # Errors
+///
+/// [`StreamError::Io`] if the file cannot be opened or read, and
+/// [`StreamError::BufferError`] with [`Error::SrcTooSmall`] or
+/// [`Error::BadMagic`] if it is too short or not a ZXC file.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -488,61 +766,1230 @@ pub fn decompress_file_with_options<P: AsRef<Path>>(
 /// # Ok::<(), zxc::StreamError>(())
 /// ```
 pub fn file_decompressed_size<P: AsRef<Path>>(path: P) -> StreamResult<u64> {
-    let f = File::open(path)?;
+    imp::decompressed_size(path.as_ref())
+}
+
+/// Returns the decompressed size stored in the footer of the frame read
+/// from `reader`.
+///
+/// [`file_decompressed_size`] for anything seekable: an object-store range
+/// reader, an archive member, a [`Cursor`](std::io::Cursor). Only the
+/// header magic and the footer are read, and `reader` is left where it
+/// was. The frame is taken to span the whole stream, from its start to its
+/// end.
+///
+/// # Errors
+///
+/// [`StreamError::Io`] if `reader` fails, and [`StreamError::BufferError`]
+/// with [`Error::SrcTooSmall`] or [`Error::BadMagic`] if the stream is too
+/// short or not a ZXC frame.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// let frame = zxc::compress(&[7u8; 1000], zxc::Level::Default, None)?;
+/// assert_eq!(zxc::decompressed_size_from(Cursor::new(frame))?, 1000);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn decompressed_size_from<R: Read + Seek>(mut reader: R) -> StreamResult<u64> {
+    const FILE_HEADER_SIZE: u64 = crate::FRAME_HEADER_SIZE as u64;
+    const FILE_FOOTER_SIZE: u64 = crate::FOOTER_SIZE as u64;
+
+    let saved = reader.stream_position()?;
+    let result = (|| {
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < FILE_HEADER_SIZE + FILE_FOOTER_SIZE {
+            return Err(Error::SrcTooSmall.into());
+        }
+        let mut magic = [0u8; 4];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut magic)?;
+        if u32::from_le_bytes(magic) != crate::format::MAGIC_WORD {
+            return Err(Error::BadMagic.into());
+        }
+        let mut size = [0u8; 8];
+        reader.seek(SeekFrom::Start(len - FILE_FOOTER_SIZE))?;
+        reader.read_exact(&mut size)?;
+        Ok(u64::from_le_bytes(size))
+    })();
+    reader.seek(SeekFrom::Start(saved))?;
+    result
+}
 
-    unsafe {
-        let c_file = file_to_c_file_read(&f);
+// =============================================================================
+// Many files
+// =============================================================================
+
+/// Outcome of one file of [`compress_many`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileStats {
+    /// The compressed file: the input path with `.zxc` appended.
+    pub output: PathBuf,
+    /// Bytes read from the input.
+    pub bytes_in: u64,
+    /// Bytes written to the output.
+    pub bytes_out: u64,
+    /// Time spent on the file, from open to close.
+    pub elapsed: Duration,
+}
 
-        if c_file.is_null() {
-            return Err(StreamError::Io(io::Error::last_os_error()));
+/// Compresses each of `inputs` to the same path with `.zxc` appended,
+/// replacing any file of that name, and returns one result per input, in
+/// order.
+///
+/// [`compress_file_with_options`] spreads the blocks of one file over its
+/// workers, which leaves them idle on files of a block or two. Here the
+/// files themselves are the jobs: `opts.threads` workers (default: one per
+/// core) each take the next file and compress it on one thread, so a
+/// directory of small logs keeps every core busy. A file that fails does
+/// not stop the others.
+///
+/// `opts.deadline` covers the whole batch: files not done by then fail with
+/// [`StreamError::TimedOut`]. `opts.on_block` is called from all workers;
+/// its [`BlockStats::worker`] is always 0.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use zxc::{StreamCompressOptions, compress_many};
+///
+/// let logs: Vec<_> = std::fs::read_dir("/var/log/app")?
+///     .map(|e| e.map(|e| e.path()))
+///     .collect::<Result<_, _>>()?;
+/// let opts = StreamCompressOptions::default();
+/// for (path, result) in logs.iter().zip(compress_many(&logs, &opts)) {
+///     match result {
+///         Ok(stats) => println!("{}: {} -> {}", path.display(), stats.bytes_in, stats.bytes_out),
+///         Err(e) => eprintln!("{}: {e}", path.display()),
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn compress_many<P: AsRef<Path> + Sync>(
+    inputs: &[P],
+    opts: &StreamCompressOptions,
+) -> Vec<StreamResult<FileStats>> {
+    let workers = opts
+        .threads
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .min(inputs.len());
+    let per_file = StreamCompressOptions {
+        threads: Some(1),
+        ..opts.clone()
+    };
+    let next = AtomicUsize::new(0);
+    let job = || {
+        let mut done = Vec::new();
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let Some(input) = inputs.get(i) else {
+                return done;
+            };
+            let input = input.as_ref();
+            let mut output = input.as_os_str().to_owned();
+            output.push(".zxc");
+            let output = PathBuf::from(output);
+            let started = Instant::now();
+            let result =
+                compress_file_counted(input, &output, &per_file).map(|(read, written)| FileStats {
+                    output,
+                    bytes_in: read,
+                    bytes_out: written,
+                    elapsed: started.elapsed(),
+                });
+            done.push((i, result));
         }
+    };
+    let mut results: Vec<Option<StreamResult<FileStats>>> = Vec::new();
+    results.resize_with(inputs.len(), || None);
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..workers).map(|_| s.spawn(job)).collect();
+        for handle in handles {
+            // A panic in `on_block` goes on to the caller.
+            let done = handle
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload));
+            for (i, result) in done {
+                if let Some(slot) = results.get_mut(i) {
+                    *slot = Some(result);
+                }
+            }
+        }
+    });
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(io::Error::other("zxc: file was not compressed").into())))
+        .collect()
+}
 
-        let result = zxc_sys::zxc_stream_get_decompressed_size(c_file);
+// =============================================================================
+// Files to and from sockets
+// =============================================================================
 
-        // The FILE* owns a dup'd fd; it must be closed here or every call
-        // leaks one file descriptor.
-        libc::fclose(c_file);
+/// Buffer size on the file side of [`compress_to_writer`] and
+/// [`decompress_from_reader`], and on the peer side of the latter: large
+/// enough that a socket is read, and a file read or written, in a few
+/// hundred syscalls per gigabyte.
+const PEER_BUFFER_SIZE: usize = 1 << 20;
 
-        if result < 0 {
-            Err(StreamError::InvalidFile)
-        } else {
-            Ok(result as u64)
-        }
-    }
+/// Compresses the file at `path` into a ZXC frame written to `writer`,
+/// typically a socket or a pipe.
+///
+/// The file is read in 1 MiB chunks that go straight into the push API, and
+/// each compressed block is handed to `writer` in one `write_all`, as soon
+/// as it is ready: nothing is staged on disk and the frame never sits in
+/// memory whole. `writer` is flushed at the end. Like [`crate::Encoder`],
+/// this runs on one thread and `opts.seekable` is ignored; use
+/// [`compress_file_with_options`] to write a multi-threaded or seekable
+/// frame to disk.
+///
+/// Returns the number of compressed bytes written.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::net::TcpStream;
+///
+/// let socket = TcpStream::connect("backup.example.com:9000")?;
+/// let sent = zxc::compress_to_writer("/var/lib/db/snapshot.bin", socket, None)?;
+/// println!("sent {sent} bytes");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn compress_to_writer<P: AsRef<Path>, W: Write>(
+    path: P,
+    writer: W,
+    opts: Option<&CompressOptions>,
+) -> io::Result<u64> {
+    let timer = metrics::start();
+    let file = open_path(path.as_ref(), |p| File::open(p))?;
+    let reader = BufReader::with_capacity(PEER_BUFFER_SIZE, file);
+    let mut writer = writer;
+    let (read, written) = compress_until(reader, &mut writer, opts, None)?;
+    writer.flush()?;
+    timer.finish(Operation::CompressStream, read, written);
+    Ok(written)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
-    use std::fs;
-    use std::io::Write;
+/// Decompresses a ZXC frame read from `reader`, typically a socket or a
+/// pipe, into the file at `path`, which is created or truncated.
+///
+/// `reader` is read in 1 MiB chunks and the output reaches the file through
+/// a 1 MiB buffer, flushed before returning. Fails with
+/// [`io::ErrorKind::UnexpectedEof`] if the peer closes the stream before the
+/// end of the frame; the partial file is left in place.
+///
+/// Returns the number of decompressed bytes written.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::net::TcpListener;
+///
+/// let (socket, _) = TcpListener::bind("0.0.0.0:9000")?.accept()?;
+/// let size = zxc::decompress_from_reader(socket, "/srv/restore/snapshot.bin", None)?;
+/// println!("restored {size} bytes");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn decompress_from_reader<R: Read, P: AsRef<Path>>(
+    reader: R,
+    path: P,
+    opts: Option<&DecompressOptions>,
+) -> io::Result<u64> {
+    let timer = metrics::start();
+    let file = create_path(path.as_ref())?;
+    let reader = BufReader::with_capacity(PEER_BUFFER_SIZE, reader);
+    let mut writer = BufWriter::with_capacity(PEER_BUFFER_SIZE, file);
+    let (read, written) = decompress_until(reader, &mut writer, opts, None)?;
+    writer.flush()?;
+    timer.finish(Operation::DecompressStream, read, written);
+    Ok(written)
+}
 
-    fn temp_path(name: &str) -> String {
-        let dir_name = format!("zxc_test_{}", std::process::id());
+/// Decompresses the ZXC file at `path` into `writer`, typically stdout or a
+/// pipe, without an intermediate file: the building block of a `zcat`.
+///
+/// The file is read through a 1 MiB buffer and the output is handed to
+/// `writer` block by block; `writer` is flushed at the end but not
+/// buffered, so wrap a raw `Stdout` in a `BufWriter` first. A consumer that
+/// goes away early, like `head`, surfaces as
+/// [`io::ErrorKind::BrokenPipe`]. For input that is not a file, use
+/// [`crate::decompress_stream`].
+///
+/// Returns the number of decompressed bytes written.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io::{self, BufWriter};
+///
+/// let mut out = BufWriter::new(io::stdout().lock());
+/// for path in std::env::args_os().skip(1) {
+///     zxc::decompress_to_writer(path, &mut out, None)?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn decompress_to_writer<P: AsRef<Path>, W: Write>(
+    path: P,
+    writer: W,
+    opts: Option<&DecompressOptions>,
+) -> io::Result<u64> {
+    let timer = metrics::start();
+    let file = open_path(path.as_ref(), |p| File::open(p))?;
+    let reader = BufReader::with_capacity(PEER_BUFFER_SIZE, file);
+    let mut writer = writer;
+    let (read, written) = decompress_until(reader, &mut writer, opts, None)?;
+    writer.flush()?;
+    timer.finish(Operation::DecompressStream, read, written);
+    Ok(written)
+}
 
-        // Try the system temp directory first
-        let mut path = std::env::temp_dir();
-        path.push(&dir_name);
+// =============================================================================
+// Owned file streams
+// =============================================================================
 
-        // If we can't create the directory in the system temp, fall back
-        // to a subdirectory relative to the current working directory.
-        // This happens on some Windows CI runners where TEMP is missing or
-        // points to a path the process cannot access.
-        if fs::create_dir_all(&path).is_err() {
-            path = std::env::current_dir().expect("cannot determine current directory");
-            path.push(&dir_name);
-            fs::create_dir_all(&path).expect("failed to create temp directory in current dir");
-        }
+/// A file the C streaming driver reads from: data to compress, or frames
+/// to decompress.
+///
+/// The reader owns a C `FILE*` over a duplicate of the file's descriptor
+/// (its handle on Windows), closed on drop; the [`File`] it was made from
+/// stays usable and is closed independently. It reads ahead, so the two
+/// do not share a position once it has been read from.
+///
+/// Compressing reads it to the end. Decompressing reads one frame and
+/// leaves it at the next, so a file of frames written one after another
+/// by [`ZxcFileWriter::compress_from`] reads back a frame per call.
+///
+/// Any descriptor will do, not only a file on disk: a pipe, or a socket
+/// such as a `UnixStream` turned into a [`File`] through its `OwnedFd`.
+/// A frame read from a socket needs only its own bytes, so the peer can
+/// keep the connection open for the next.
+///
+/// A reader is [`Send`], to be handed to another thread, but not
+/// [`Sync`]: calls on it cannot overlap.
+pub struct ZxcFileReader(imp::CFile);
+
+// SAFETY: the stream is owned by the reader and only reached through
+// `&mut self`, so moving it to another thread is fine; sharing it is not.
+unsafe impl Send for ZxcFileReader {}
+
+impl ZxcFileReader {
+    /// Opens the file at `path` for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(&open_path(path.as_ref(), |p| File::open(p))?)
+    }
 
-        path.push(name);
-        path.to_string_lossy().into_owned()
+    /// Reads `file` from its current position, through a duplicate of its
+    /// descriptor.
+    pub fn new(file: &File) -> io::Result<Self> {
+        imp::CFile::read(file).map(Self)
     }
+}
 
-    #[test]
-    fn test_file_roundtrip() {
-        let input_path = temp_path("roundtrip_input.bin");
-        let compressed_path = temp_path("roundtrip_compressed.zxc");
-        let output_path = temp_path("roundtrip_output.bin");
+impl fmt::Debug for ZxcFileReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZxcFileReader").finish_non_exhaustive()
+    }
+}
+
+/// A file the C streaming driver writes to, kept open across calls.
+///
+/// Each [`compress_from`](Self::compress_from) appends one complete frame
+/// after the previous ones, so a long-running process can keep one output
+/// open and add a frame per batch instead of reopening it; each
+/// [`decompress_from`](Self::decompress_from) appends the data of one
+/// frame.
+///
+/// Like [`ZxcFileReader`], the writer owns a C `FILE*` over a duplicate of
+/// the file's descriptor and is [`Send`] but not [`Sync`]. A write that
+/// finds the disk full fails with [`StreamError::DiskFull`]. Output is
+/// buffered and flushed when the writer is dropped, where an error goes
+/// unreported: call [`flush`](Self::flush) first to see it.
+///
+/// The options' `deadline` is not supported here: calls with one set fail
+/// with [`io::ErrorKind::InvalidInput`].
+///
+/// Unlike the file functions, the methods do not start over when a signal
+/// interrupts a read or a write, since the streams cannot be rewound: they
+/// fail with an error for which [`StreamError::is_interrupted`] holds.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use zxc::{StreamCompressOptions, ZxcFileReader, ZxcFileWriter};
+///
+/// let opts = StreamCompressOptions::default();
+/// let mut out = ZxcFileWriter::append("events.zxc")?;
+/// for batch in ["batch-1.log", "batch-2.log"] {
+///     out.compress_from(&mut ZxcFileReader::open(batch)?, &opts)?;
+///     out.flush()?;
+/// }
+/// # Ok::<(), zxc::StreamError>(())
+/// ```
+pub struct ZxcFileWriter {
+    stream: imp::CFile,
+    /// The file written to, for its size and free space when a write fails.
+    file: File,
+}
+
+// SAFETY: as for `ZxcFileReader`.
+unsafe impl Send for ZxcFileWriter {}
+
+impl ZxcFileWriter {
+    /// Creates the file at `path`, or truncates it, for writing.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(&create_path(path.as_ref())?)
+    }
+
+    /// Opens the file at `path` for writing at its end, creating it if
+    /// needed.
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let open = |p: &Path| File::options().append(true).create(true).open(p);
+        Self::new(&open_path(path.as_ref(), open)?)
+    }
+
+    /// Writes to `file` from its current position, through a duplicate of
+    /// its descriptor.
+    pub fn new(file: &File) -> io::Result<Self> {
+        Ok(Self {
+            stream: imp::CFile::write(file)?,
+            file: file.try_clone()?,
+        })
+    }
+
+    /// Compresses what is left of `input` into one frame appended to the
+    /// file. Returns the number of compressed bytes written.
+    pub fn compress_from(
+        &mut self,
+        input: &mut ZxcFileReader,
+        opts: &StreamCompressOptions,
+    ) -> StreamResult<u64> {
+        if opts.deadline.is_some() {
+            return Err(no_deadline_on_handles());
+        }
+        let _held = reserve(Some(opts.level), opts.threads, false);
+        let timer = metrics::start();
+        let written = imp::compress(&input.0, &self.stream, &self.file, opts, None)?;
+        timer.finish(Operation::CompressFile, 0, written);
+        Ok(written)
+    }
+
+    /// Decompresses the next frame of `input`, appending its data to the
+    /// file. Returns the number of decompressed bytes written.
+    pub fn decompress_from(
+        &mut self,
+        input: &mut ZxcFileReader,
+        opts: &StreamDecompressOptions,
+    ) -> StreamResult<u64> {
+        if opts.deadline.is_some() {
+            return Err(no_deadline_on_handles());
+        }
+        let _held = reserve(None, opts.threads, false);
+        let timer = metrics::start();
+        let written = imp::decompress(&input.0, &self.stream, &self.file, opts, None)?;
+        timer.finish(Operation::DecompressFile, 0, written);
+        Ok(written)
+    }
+
+    /// Hands the buffered output to the OS. A full disk fails with
+    /// [`io::ErrorKind::StorageFull`].
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl fmt::Debug for ZxcFileWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZxcFileWriter").finish_non_exhaustive()
+    }
+}
+
+fn no_deadline_on_handles() -> StreamError {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "deadlines are not supported on open file handles",
+    )
+    .into()
+}
+
+// =============================================================================
+// Paths
+// =============================================================================
+
+/// Opens `path` with `open`. On Windows a failed attempt is retried once
+/// with the extended-length form of the path (see [`extended_length`]),
+/// which lifts the `MAX_PATH` limit for deep trees and UNC shares.
+#[cfg(windows)]
+pub(crate) fn open_path(path: &Path, open: impl Fn(&Path) -> io::Result<File>) -> io::Result<File> {
+    open(path).or_else(|err| match extended_length(path) {
+        Some(long) => open(&long).map_err(|_| err),
+        None => Err(err),
+    })
+}
+
+#[cfg(not(windows))]
+pub(crate) fn open_path(path: &Path, open: impl Fn(&Path) -> io::Result<File>) -> io::Result<File> {
+    open(path)
+}
+
+/// Opens `path` for output, created or truncated. A Windows named pipe
+/// (`\\.\pipe\name`) is opened as it is instead: its server created it,
+/// and pipe clients may only open existing instances.
+pub(crate) fn create_path(path: &Path) -> io::Result<File> {
+    match is_pipe(path) {
+        true => File::options().write(true).open(path),
+        false => open_path(path, |p| File::create(p)),
+    }
+}
+
+/// Whether `path` names a Windows named pipe, in the `\\.\pipe\` or
+/// `\\?\pipe\` namespace.
+#[cfg(windows)]
+fn is_pipe(path: &Path) -> bool {
+    let head = path.as_os_str().as_encoded_bytes().get(..9);
+    head.is_some_and(|h| {
+        matches!(h.get(..4), Some(br"\\.\" | br"\\?\"))
+            && h.get(4..)
+                .is_some_and(|n| n.eq_ignore_ascii_case(br"pipe\"))
+    })
+}
+
+/// FIFOs and character devices open through [`File::create`] as they are.
+#[cfg(not(windows))]
+fn is_pipe(_path: &Path) -> bool {
+    false
+}
+
+/// Returns the `\\?\` (extended-length) spelling of `path`, made absolute
+/// first, or `None` if it is already verbatim or a device path.
+///
+/// `C:\dir\f` becomes `\\?\C:\dir\f` and `\\server\share\f` becomes
+/// `\\?\UNC\server\share\f`. Verbatim paths skip Win32 normalisation, so
+/// `.`/`..` components must be resolved first: `std::path::absolute` does.
+#[cfg(windows)]
+fn extended_length(path: &Path) -> Option<std::path::PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Component, Prefix};
+
+    let abs = std::path::absolute(path).ok()?;
+    // A UNC path keeps one of its two leading backslashes: `\\?\UNC` + `\server\...`.
+    let (lead, skip) = match abs.components().next()? {
+        Component::Prefix(p) => match p.kind() {
+            Prefix::Disk(_) => (r"\\?\", 0),
+            Prefix::UNC(..) => (r"\\?\UNC", 1),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut wide: Vec<u16> = lead.encode_utf16().collect();
+    wide.extend(abs.as_os_str().encode_wide().skip(skip));
+    Some(OsString::from_wide(&wide).into())
+}
+
+// =============================================================================
+// C FILE* bridge
+// =============================================================================
+
+#[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+pub(crate) use imp::CFile;
+
+#[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+mod imp {
+    use std::any::Any;
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::path::Path;
+    use std::sync::{Mutex, PoisonError};
+    use std::time::Duration;
+
+    use errno::Errno;
+    use zxc_sys::ZXC_MAX_THREADS;
+
+    use super::{
+        BlockCallback, BlockStats, StreamCompressOptions, StreamDecompressOptions, StreamError,
+        StreamResult, StreamStats, create_path, is_full, is_regular, open_path, too_short,
+    };
+    use crate::error::error_from_code;
+    use crate::{Error, diag, env};
+
+    /// Owned C `FILE*` handed to the C streaming driver, closed on drop.
+    ///
+    /// The stream wraps a duplicate of the Rust file's descriptor (Unix) or
+    /// handle (Windows), so the caller's [`File`] stays usable and is closed
+    /// independently.
+    pub(crate) struct CFile(*mut libc::FILE);
+
+    impl CFile {
+        pub(crate) fn read(file: &File) -> io::Result<Self> {
+            Self::open(file, false)
+        }
+
+        pub(crate) fn write(file: &File) -> io::Result<Self> {
+            Self::open(file, true)
+        }
+
+        fn open(file: &File, write: bool) -> io::Result<Self> {
+            let dup = file.try_clone()?;
+            // SAFETY: `dup` is a valid, exclusively owned file; ownership moves
+            // into the returned stream (or is released on failure).
+            unsafe { crt::fdopen_owned(dup, write) }.map(Self)
+        }
+
+        pub(crate) fn as_ptr(&self) -> *mut libc::FILE {
+            self.0
+        }
+
+        /// Whether a read or write on the stream failed since the last
+        /// [`CFile::clear_error`].
+        pub(crate) fn failed(&self) -> bool {
+            // SAFETY: the pointer came from a successful fdopen and is open.
+            unsafe { crt::ferror(self.0) != 0 }
+        }
+
+        pub(crate) fn clear_error(&self) {
+            // SAFETY: as above.
+            unsafe { crt::clearerr(self.0) }
+        }
+
+        /// Hands what the stream buffers to the OS.
+        pub(crate) fn flush(&self) -> io::Result<()> {
+            // SAFETY: the pointer came from a successful fdopen and is open.
+            match unsafe { crt::fflush(self.0) } {
+                0 => Ok(()),
+                _ => Err(io::Error::last_os_error()),
+            }
+        }
+    }
+
+    impl Drop for CFile {
+        fn drop(&mut self) {
+            // SAFETY: the pointer came from a successful fdopen and is closed once.
+            unsafe { crt::fclose(self.0) };
+        }
+    }
+
+    #[cfg(unix)]
+    mod crt {
+        use std::fs::File;
+        use std::io;
+        use std::os::unix::io::IntoRawFd;
+
+        pub(super) use libc::{clearerr, fclose, ferror, fflush};
+
+        /// Turns `file` into a C stream that owns its descriptor.
+        ///
+        /// `fdopen` is plain POSIX, so this covers Linux, macOS, the BSDs
+        /// and illumos alike. The C driver only needs a real descriptor
+        /// behind the stream (it calls `fileno` + `pread`), which is also
+        /// why no `fopencookie`/`funopen` stream is used here.
+        pub(super) unsafe fn fdopen_owned(file: File, write: bool) -> io::Result<*mut libc::FILE> {
+            let fd = file.into_raw_fd();
+            let mode = if write { c"wb" } else { c"rb" };
+            let f = unsafe { libc::fdopen(fd, mode.as_ptr()) };
+            if f.is_null() {
+                let err = io::Error::last_os_error();
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+            Ok(f)
+        }
+    }
+
+    /// The C library links against the same Universal CRT as the Rust binary, so
+    /// the low-level I/O entry points are declared here against their documented
+    /// MSVC names instead of relying on the `libc` crate's Windows aliases.
+    #[cfg(windows)]
+    mod crt {
+        use std::ffi::{c_char, c_int};
+        use std::fs::File;
+        use std::io;
+        use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+
+        const _O_RDONLY: c_int = 0x0000;
+        const _O_WRONLY: c_int = 0x0001;
+        const _O_BINARY: c_int = 0x8000;
+
+        unsafe extern "C" {
+            fn _open_osfhandle(osfhandle: isize, flags: c_int) -> c_int;
+            fn _fdopen(fd: c_int, mode: *const c_char) -> *mut libc::FILE;
+            fn _close(fd: c_int) -> c_int;
+            pub(super) fn fclose(stream: *mut libc::FILE) -> c_int;
+            pub(super) fn fflush(stream: *mut libc::FILE) -> c_int;
+            pub(super) fn ferror(stream: *mut libc::FILE) -> c_int;
+            pub(super) fn clearerr(stream: *mut libc::FILE);
+        }
+
+        /// Turns `file` into a binary-mode C stream that owns its handle.
+        pub(super) unsafe fn fdopen_owned(file: File, write: bool) -> io::Result<*mut libc::FILE> {
+            let handle = file.into_raw_handle();
+            let flags = _O_BINARY | if write { _O_WRONLY } else { _O_RDONLY };
+            let fd = unsafe { _open_osfhandle(handle as isize, flags) };
+            if fd < 0 {
+                // The CRT did not take ownership: close the handle ourselves.
+                drop(unsafe { File::from_raw_handle(handle) });
+                return Err(io::Error::other("_open_osfhandle failed"));
+            }
+            let mode = if write { c"wb" } else { c"rb" };
+            let f = unsafe { _fdopen(fd, mode.as_ptr()) };
+            if f.is_null() {
+                // Closing the CRT descriptor also closes the handle.
+                unsafe { _close(fd) };
+                return Err(io::Error::other("_fdopen failed"));
+            }
+            Ok(f)
+        }
+    }
+
+    /// Thread count for the C options: `None` means auto-detect (0). The
+    /// library runs at most `ZXC_MAX_THREADS`; a count that does not even
+    /// fit its `int` is rejected rather than wrapped.
+    fn c_threads(threads: Option<usize>) -> Result<i32, Error> {
+        let n = threads.unwrap_or(0);
+        let c = i32::try_from(n).map_err(|_| Error::InvalidOption("threads exceeds i32::MAX"))?;
+        if n > ZXC_MAX_THREADS {
+            diag::threads(n, ZXC_MAX_THREADS, "the C library's limit");
+        }
+        Ok(c)
+    }
+
+    /// Forwards the driver's per-block statistics to a [`BlockCallback`].
+    ///
+    /// A panic cannot unwind through the C driver: it is caught, the later
+    /// blocks are skipped, and [`BlockSink::finish`] resumes it.
+    struct BlockSink<'a> {
+        callback: &'a BlockCallback,
+        panic: Mutex<Option<Box<dyn Any + Send>>>,
+    }
+
+    impl<'a> BlockSink<'a> {
+        fn new(callback: &'a BlockCallback) -> Self {
+            Self {
+                callback,
+                panic: Mutex::new(None),
+            }
+        }
+
+        /// `block_cb` for the C extensions and `user_data` for the C
+        /// options; the sink must outlive the driver call.
+        fn c_fields(sink: Option<&Self>) -> (zxc_sys::zxc_block_callback_t, *mut c_void) {
+            match sink {
+                Some(sink) => (Some(Self::trampoline), sink as *const Self as *mut c_void),
+                None => (None, std::ptr::null_mut()),
+            }
+        }
+
+        unsafe extern "C" fn trampoline(
+            stats: *const zxc_sys::zxc_block_stats_t,
+            user_data: *const c_void,
+        ) {
+            // SAFETY: `user_data` is the sink set up by `c_fields`, alive
+            // for the whole driver call; `stats` is valid during this call.
+            let (sink, stats) = unsafe { (&*user_data.cast::<Self>(), stats.as_ref()) };
+            let Some(stats) = stats else {
+                return;
+            };
+            let mut panicked = sink.panic.lock().unwrap_or_else(PoisonError::into_inner);
+            if panicked.is_some() {
+                return;
+            }
+            let stats = BlockStats {
+                index: stats.index,
+                in_size: stats.in_size,
+                out_size: stats.out_size,
+                elapsed: Duration::from_nanos(stats.time_ns),
+                worker: usize::try_from(stats.worker_id).unwrap_or(0),
+            };
+            let callback = sink.callback;
+            // The driver may still have a failed read to report: the
+            // callback must not leave its own `errno` behind.
+            let saved = errno::errno();
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| callback.call(&stats))) {
+                *panicked = Some(payload);
+            }
+            errno::set_errno(saved);
+        }
+
+        /// Resumes a panic raised by the callback.
+        fn finish(self) {
+            if let Some(payload) = self
+                .panic
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+            {
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    /// Receives the driver's stage timings for a [`StreamStats`].
+    struct StageTimes {
+        c: zxc_sys::zxc_stream_stats_t,
+        // Sized for the most workers the driver runs.
+        workers: Vec<u64>,
+    }
+
+    impl StageTimes {
+        fn new() -> Self {
+            let mut workers = vec![0u64; ZXC_MAX_THREADS];
+            let c = zxc_sys::zxc_stream_stats_t {
+                worker_ns: workers.as_mut_ptr(),
+                worker_ns_len: ZXC_MAX_THREADS as i32,
+                ..Default::default()
+            };
+            Self { c, workers }
+        }
+
+        /// `stats` for the C extensions; the times must outlive the call.
+        fn c_ptr(times: Option<&mut Self>) -> *mut zxc_sys::zxc_stream_stats_t {
+            times.map_or(std::ptr::null_mut(), |t| &mut t.c)
+        }
+
+        fn report(self, stats: &mut StreamStats, bytes_out: u64) {
+            let n = usize::try_from(self.c.n_workers).unwrap_or(0);
+            *stats = StreamStats {
+                bytes_out,
+                wall: Duration::from_nanos(self.c.wall_ns),
+                read: Duration::from_nanos(self.c.read_ns),
+                compute: Duration::from_nanos(self.c.compute_ns),
+                write: Duration::from_nanos(self.c.write_ns),
+                workers: self
+                    .workers
+                    .iter()
+                    .take(n)
+                    .map(|&ns| Duration::from_nanos(ns))
+                    .collect(),
+            };
+        }
+    }
+
+    pub(super) fn compress_file(
+        input: &Path,
+        output: &Path,
+        opts: &StreamCompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<(u64, u64)> {
+        env::init();
+        let n_threads = c_threads(env::threads(opts.threads))?;
+        let mut stats = stats;
+        retry_interrupted(|| {
+            let f_in = open_path(input, |p| File::open(p))?;
+            let f_out = create_path(output)?;
+            // Reported to the metrics sink; 0 for a pipe or a device.
+            let read = f_in.metadata().map_or(0, |m| m.len());
+            let c_in = CFile::read(&f_in)?;
+            let c_out = CFile::write(&f_out)?;
+            let written =
+                run_compress(&c_in, &c_out, &f_out, n_threads, opts, stats.as_deref_mut())
+                    .and_then(|w| flushed(&c_out, &f_out).map(|()| (read, w)));
+            Ok((written, restartable(&f_in, &f_out)))
+        })
+    }
+
+    pub(super) fn decompress_file(
+        input: &Path,
+        output: &Path,
+        opts: &StreamDecompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<(u64, u64)> {
+        env::init();
+        let n_threads = c_threads(env::threads(opts.threads))?;
+        let mut stats = stats;
+        retry_interrupted(|| {
+            let f_in = open_path(input, |p| File::open(p))?;
+            if too_short(&f_in) {
+                return Err(StreamError::BufferError(Error::SrcTooSmall));
+            }
+            let f_out = create_path(output)?;
+            // Reported to the metrics sink; 0 for a pipe or a device.
+            let read = f_in.metadata().map_or(0, |m| m.len());
+            let c_in = CFile::read(&f_in)?;
+            let c_out = CFile::write(&f_out)?;
+            let written =
+                run_decompress(&c_in, &c_out, &f_out, n_threads, opts, stats.as_deref_mut())
+                    .and_then(|w| flushed(&c_out, &f_out).map(|()| (read, w)));
+            Ok((written, restartable(&f_in, &f_out)))
+        })
+    }
+
+    /// Interrupted runs of a whole file start over, at most this many
+    /// times.
+    const INTERRUPTED_RETRIES: usize = 3;
+
+    /// Runs `attempt` until it does not fail with
+    /// [`io::ErrorKind::Interrupted`], a signal landing in a read or a
+    /// write, or [`INTERRUPTED_RETRIES`] times over. An attempt that fails
+    /// before getting anywhere returns its error in the outer `Err`; one
+    /// that ran returns its result with whether it can start over, which
+    /// it cannot once a pipe was read from or written to.
+    pub(super) fn retry_interrupted<T>(
+        mut attempt: impl FnMut() -> StreamResult<(StreamResult<T>, bool)>,
+    ) -> StreamResult<T> {
+        let mut retries = 0;
+        loop {
+            match attempt()? {
+                (Err(e), true) if e.is_interrupted() && retries < INTERRUPTED_RETRIES => {
+                    retries += 1;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// Whether a run between `f_in` and `f_out` can start over from
+    /// scratch: both are regular files, reopened from the start (the
+    /// output truncated).
+    fn restartable(f_in: &File, f_out: &File) -> bool {
+        is_regular(f_in) && is_regular(f_out)
+    }
+
+    /// Flushes `c_out`, the stream over `f_out`, where closing it would
+    /// lose the error.
+    fn flushed(c_out: &CFile, f_out: &File) -> StreamResult<()> {
+        c_out.flush().map_err(|e| output_error(e, f_out))
+    }
+
+    /// The error of a failed write to `c_out`, the stream over `f_out`.
+    ///
+    /// The driver writes from a thread of its own, whose `errno` is gone:
+    /// flushing here meets the error again when data is left, and a file
+    /// system without room for another block tells a full disk otherwise.
+    fn write_error(c_out: &CFile, f_out: &File) -> StreamError {
+        let e = c_out
+            .flush()
+            .err()
+            .unwrap_or_else(|| io::Error::other("writing the output failed"));
+        output_error(e, f_out)
+    }
+
+    fn output_error(e: io::Error, f_out: &File) -> StreamError {
+        match is_full(&e) || out_of_space(f_out) {
+            true => StreamError::DiskFull {
+                bytes_written: f_out.metadata().map_or(0, |m| m.len()),
+            },
+            false => e.into(),
+        }
+    }
+
+    /// Whether the file system of `f` has less room left than the largest
+    /// write of the driver, a compressed block.
+    #[cfg(unix)]
+    // The field types vary across platforms.
+    #[allow(clippy::unnecessary_cast)]
+    fn out_of_space(f: &File) -> bool {
+        use std::os::unix::io::AsRawFd;
+
+        let mut st = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: the descriptor is open and `st` is written on success.
+        if unsafe { libc::fstatvfs(f.as_raw_fd(), st.as_mut_ptr()) } != 0 {
+            return false;
+        }
+        // SAFETY: fstatvfs succeeded.
+        let st = unsafe { st.assume_init() };
+        let room = (st.f_bavail as u64).saturating_mul(st.f_frsize as u64);
+        room < crate::compress_block_bound(crate::MAX_BLOCK_SIZE)
+    }
+
+    /// Without `fstatvfs`, only the errors seen here tell a full disk.
+    #[cfg(not(unix))]
+    fn out_of_space(_f: &File) -> bool {
+        false
+    }
+
+    /// The error of a failed read of `c_in`, from the `errno` the driver
+    /// left on the calling thread, where it reads.
+    ///
+    /// `errno` is cleared before the driver call and taken as soon as it
+    /// returns, so a value left by an earlier call (a stale `EINTR` would
+    /// send [`retry_interrupted`] into restarts)
+    /// is never reported; a read that set none fails as [`io::Error::other`].
+    fn read_error(errno: Errno) -> StreamError {
+        match errno.0 {
+            0 => io::Error::other("reading the input failed").into(),
+            code => io::Error::from_raw_os_error(code).into(),
+        }
+    }
+
+    /// Compresses what is left of `c_in` into one frame appended to
+    /// `c_out`; returns the compressed bytes written. The output is
+    /// buffered until `c_out` is flushed or dropped.
+    pub(super) fn compress(
+        c_in: &CFile,
+        c_out: &CFile,
+        f_out: &File,
+        opts: &StreamCompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        env::init();
+        let n_threads = c_threads(env::threads(opts.threads))?;
+        run_compress(c_in, c_out, f_out, n_threads, opts, stats)
+    }
+
+    fn run_compress(
+        c_in: &CFile,
+        c_out: &CFile,
+        f_out: &File,
+        n_threads: i32,
+        opts: &StreamCompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        diag::variant();
+        c_in.clear_error();
+        let sink = opts.on_block.as_ref().map(BlockSink::new);
+        let (block_cb, user_data) = BlockSink::c_fields(sink.as_ref());
+        let mut times = stats.is_some().then(StageTimes::new);
+
+        errno::set_errno(Errno(0));
+        // SAFETY: both streams are open and, like the sink and the times,
+        // outlive the call.
+        let result = unsafe {
+            zxc_sys::zxc_stream_compress_ex(
+                c_in.as_ptr(),
+                c_out.as_ptr(),
+                &zxc_sys::zxc_compress_opts_t {
+                    n_threads,
+                    level: env::level(opts.level) as i32,
+                    checksum_enabled: opts.checksum as i32,
+                    seekable: opts.seekable as i32,
+                    user_data,
+                    ..Default::default()
+                },
+                &zxc_sys::zxc_stream_ext_t {
+                    block_cb,
+                    stats: StageTimes::c_ptr(times.as_mut()),
+                },
+            )
+        };
+        let read_errno = errno::errno();
+
+        if let Some(sink) = sink {
+            sink.finish();
+        }
+        // The driver takes a failed read for the end of the input: the
+        // frame of what it read so far would pass for the whole.
+        if c_in.failed() {
+            return Err(read_error(read_errno));
+        }
+        if result < 0 && c_out.failed() {
+            return Err(write_error(c_out, f_out));
+        }
+        if result < 0 {
+            return Err(StreamError::BufferError(error_from_code(result)));
+        }
+        if let (Some(times), Some(stats)) = (times, stats) {
+            times.report(stats, result as u64);
+        }
+        Ok(result as u64)
+    }
+
+    /// Decompresses the frame at the position of `c_in`, leaving it past
+    /// the frame, and appends its data to `c_out`; returns the bytes
+    /// written. The output is buffered until `c_out` is flushed or dropped.
+    pub(super) fn decompress(
+        c_in: &CFile,
+        c_out: &CFile,
+        f_out: &File,
+        opts: &StreamDecompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        env::init();
+        let n_threads = c_threads(env::threads(opts.threads))?;
+        run_decompress(c_in, c_out, f_out, n_threads, opts, stats)
+    }
+
+    fn run_decompress(
+        c_in: &CFile,
+        c_out: &CFile,
+        f_out: &File,
+        n_threads: i32,
+        opts: &StreamDecompressOptions,
+        stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        diag::variant();
+        diag::checksum(opts.verify_checksum, None);
+        c_in.clear_error();
+        let sink = opts.on_block.as_ref().map(BlockSink::new);
+        let (block_cb, user_data) = BlockSink::c_fields(sink.as_ref());
+        let mut times = stats.is_some().then(StageTimes::new);
+
+        errno::set_errno(Errno(0));
+        // SAFETY: both streams are open and, like the sink and the times,
+        // outlive the call.
+        let result = unsafe {
+            zxc_sys::zxc_stream_decompress_ex(
+                c_in.as_ptr(),
+                c_out.as_ptr(),
+                &zxc_sys::zxc_decompress_opts_t {
+                    n_threads,
+                    checksum_enabled: opts.verify_checksum as i32,
+                    user_data,
+                    ..Default::default()
+                },
+                &zxc_sys::zxc_stream_ext_t {
+                    block_cb,
+                    stats: StageTimes::c_ptr(times.as_mut()),
+                },
+            )
+        };
+        let read_errno = errno::errno();
+
+        if let Some(sink) = sink {
+            sink.finish();
+        }
+        // A failed read shows as a truncated frame or a bad header.
+        if result < 0 && c_in.failed() {
+            return Err(read_error(read_errno));
+        }
+        if result < 0 && c_out.failed() {
+            return Err(write_error(c_out, f_out));
+        }
+        if result < 0 {
+            return Err(StreamError::BufferError(error_from_code(result)));
+        }
+        if let (Some(times), Some(stats)) = (times, stats) {
+            times.report(stats, result as u64);
+        }
+        Ok(result as u64)
+    }
+
+    pub(super) fn decompressed_size(path: &Path) -> StreamResult<u64> {
+        let f = open_path(path, |p| File::open(p))?;
+        let c_file = CFile::read(&f)?;
+
+        errno::set_errno(Errno(0));
+        // SAFETY: the stream is open for the duration of the call.
+        let result = unsafe { zxc_sys::zxc_stream_get_decompressed_size(c_file.as_ptr()) };
+        let read_errno = errno::errno();
+
+        match result {
+            0.. => Ok(result as u64),
+            r if r == i64::from(zxc_sys::ZXC_ERROR_IO) => Err(read_error(read_errno)),
+            r => Err(StreamError::BufferError(error_from_code(r))),
+        }
+    }
+}
+
+/// Stand-in for builds without the C streaming driver (`no-stream`, wasm):
+/// every entry point fails with [`io::ErrorKind::Unsupported`].
+#[cfg(any(feature = "no-stream", target_family = "wasm"))]
+mod imp {
+    use std::convert::Infallible;
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    use super::{
+        StreamCompressOptions, StreamDecompressOptions, StreamError, StreamResult, StreamStats,
+    };
+
+    fn unsupported_io() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "zxc was built without the file streaming API",
+        )
+    }
+
+    fn unsupported() -> StreamError {
+        StreamError::Io(unsupported_io())
+    }
+
+    /// Never opens: there is no C stream to hand anything to.
+    pub(crate) struct CFile(Infallible);
+
+    impl CFile {
+        pub(crate) fn read(_file: &File) -> io::Result<Self> {
+            Err(unsupported_io())
+        }
+
+        pub(crate) fn write(_file: &File) -> io::Result<Self> {
+            Err(unsupported_io())
+        }
+
+        pub(crate) fn flush(&self) -> io::Result<()> {
+            match self.0 {}
+        }
+    }
+
+    pub(super) fn compress_file(
+        _input: &Path,
+        _output: &Path,
+        _opts: &StreamCompressOptions,
+        _stats: Option<&mut StreamStats>,
+    ) -> StreamResult<(u64, u64)> {
+        Err(unsupported())
+    }
+
+    pub(super) fn decompress_file(
+        _input: &Path,
+        _output: &Path,
+        _opts: &StreamDecompressOptions,
+        _stats: Option<&mut StreamStats>,
+    ) -> StreamResult<(u64, u64)> {
+        Err(unsupported())
+    }
+
+    pub(super) fn compress(
+        c_in: &CFile,
+        _c_out: &CFile,
+        _f_out: &File,
+        _opts: &StreamCompressOptions,
+        _stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        match c_in.0 {}
+    }
+
+    pub(super) fn decompress(
+        c_in: &CFile,
+        _c_out: &CFile,
+        _f_out: &File,
+        _opts: &StreamDecompressOptions,
+        _stats: Option<&mut StreamStats>,
+    ) -> StreamResult<u64> {
+        match c_in.0 {}
+    }
+
+    pub(super) fn decompressed_size(_path: &Path) -> StreamResult<u64> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(all(test, not(any(feature = "no-stream", target_family = "wasm"))))]
+mod tests {
+    use crate::*;
+    use std::fs;
+    use std::io::{self, Write};
+
+    fn temp_path(name: &str) -> String {
+        let dir_name = format!("zxc_test_{}", std::process::id());
+
+        // Try the system temp directory first
+        let mut path = std::env::temp_dir();
+        path.push(&dir_name);
+
+        // If we can't create the directory in the system temp, fall back
+        // to a subdirectory relative to the current working directory.
+        // This happens on some Windows CI runners where TEMP is missing or
+        // points to a path the process cannot access.
+        if fs::create_dir_all(&path).is_err() {
+            path = std::env::current_dir().expect("cannot determine current directory");
+            path.push(&dir_name);
+            fs::create_dir_all(&path).expect("failed to create temp directory in current dir");
+        }
+
+        path.push(name);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let input_path = temp_path("roundtrip_input.bin");
+        let compressed_path = temp_path("roundtrip_compressed.zxc");
+        let output_path = temp_path("roundtrip_output.bin");
 
         // Create test data
         let data: Vec<u8> = (0..64 * 1024) // 64 KB
@@ -574,6 +2021,556 @@ mod tests {
         let _ = fs::remove_file(&output_path);
     }
 
+    /// Bytes that a text-mode C stream would translate (CR/LF) or stop at
+    /// (Ctrl-Z on Windows) must survive the FILE* bridge untouched.
+    #[test]
+    fn test_file_roundtrip_binary_bytes() {
+        let input_path = temp_path("binary_input.bin");
+        let compressed_path = temp_path("binary_compressed.zxc");
+        let output_path = temp_path("binary_output.bin");
+
+        let data: Vec<u8> = b"\r\n\n\r\x1a\x00\xff"
+            .iter()
+            .copied()
+            .cycle()
+            .take(100_003)
+            .collect();
+        fs::write(&input_path, &data).unwrap();
+
+        compress_file(&input_path, &compressed_path, Level::Fast, None, Some(true)).unwrap();
+        let n = decompress_file(&compressed_path, &output_path, None).unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&compressed_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_file_to_and_from_peer() {
+        let input_path = temp_path("peer_input.bin");
+        let output_path = temp_path("peer_output.bin");
+        let data = crate::fixtures::input().repeat(200);
+        fs::write(&input_path, &data).unwrap();
+
+        let mut wire = Vec::new();
+        let sent = compress_to_writer(&input_path, &mut wire, None).unwrap();
+        assert_eq!(sent, wire.len() as u64);
+        assert_eq!(decompress(&wire).unwrap(), data);
+
+        let n = decompress_from_reader(&wire[..], &output_path, None).unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let err = decompress_from_reader(&wire[..wire.len() - 1], &output_path, None).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(compress_to_writer(temp_path("peer_missing.bin"), std::io::sink(), None).is_err());
+
+        // The zcat direction: a compressed file into a writer, which may
+        // stop taking output early.
+        fs::write(&output_path, &wire).unwrap();
+        let mut plain = Vec::new();
+        let n = decompress_to_writer(&output_path, &mut plain, None).unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(plain, data);
+        struct Head(usize);
+        impl Write for Head {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(io::ErrorKind::BrokenPipe.into());
+                }
+                let n = buf.len().min(self.0);
+                self.0 -= n;
+                Ok(n)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = decompress_to_writer(&output_path, Head(1000), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    /// Runs interrupted by a signal start over when they can, a few times.
+    #[test]
+    fn test_interrupted_runs_retry() {
+        use super::imp::retry_interrupted;
+
+        let interrupted = || StreamError::Io(io::ErrorKind::Interrupted.into());
+        assert!(interrupted().is_interrupted());
+        assert!(!StreamError::Io(io::ErrorKind::Other.into()).is_interrupted());
+
+        let mut attempts = 0;
+        let result = retry_interrupted(|| {
+            attempts += 1;
+            match attempts {
+                1 | 2 => Ok((Err(interrupted()), true)),
+                _ => Ok((Ok(7), true)),
+            }
+        });
+        assert_eq!((result.unwrap(), attempts), (7, 3));
+
+        // Not when a pipe was involved, nor forever.
+        attempts = 0;
+        let result = retry_interrupted::<()>(|| {
+            attempts += 1;
+            Ok((Err(interrupted()), false))
+        });
+        assert!(result.unwrap_err().is_interrupted());
+        assert_eq!(attempts, 1);
+        attempts = 0;
+        let result = retry_interrupted::<()>(|| {
+            attempts += 1;
+            Ok((Err(interrupted()), true))
+        });
+        assert!(result.unwrap_err().is_interrupted());
+        assert_eq!(attempts, 4);
+
+        // Failures before the run are not retried.
+        attempts = 0;
+        let result = retry_interrupted::<()>(|| {
+            attempts += 1;
+            Err(interrupted())
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    /// A failed read of the input is an error, not the end of the input:
+    /// the driver would otherwise write a valid frame of nothing. Its
+    /// cause is the `errno` of the read, not one left from before.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_failed_read_is_reported() {
+        let dir = std::env::temp_dir();
+        let output_path = dir.join(temp_path("failed_read.zxc"));
+        errno::set_errno(errno::Errno(libc::EINTR));
+        let err = compress_file(&dir, &output_path, Level::Default, None, None).unwrap_err();
+        assert!(
+            matches!(&err, StreamError::Io(e) if e.kind() == io::ErrorKind::IsADirectory),
+            "{err:?}"
+        );
+        let _ = fs::remove_file(&output_path);
+    }
+
+    /// Writes to `/dev/full` fail with ENOSPC. The driver's own thread
+    /// loses the error, and the device's file system has room, so only
+    /// the last flush and the deadline path, which writes here, tell.
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_disk_full() {
+        let small_path = temp_path("full_small.bin");
+        let large_path = temp_path("full_large.bin");
+        let packed_path = temp_path("full_packed.zxc");
+        fs::write(&small_path, b"tiny").unwrap();
+        fs::write(&large_path, crate::fixtures::input().repeat(60)).unwrap();
+        compress_file(&small_path, &packed_path, Level::Default, None, None).unwrap();
+
+        let full = "/dev/full".to_string();
+        let err = compress_file(&small_path, &full, Level::Default, Some(2), None).unwrap_err();
+        assert!(
+            matches!(err, StreamError::DiskFull { bytes_written: 0 }),
+            "{err:?}"
+        );
+        let err = decompress_file(&packed_path, &full, None).unwrap_err();
+        assert!(matches!(err, StreamError::DiskFull { .. }), "{err:?}");
+        assert!(compress_file(&large_path, &full, Level::Default, Some(2), None).is_err());
+
+        let opts = StreamCompressOptions::default().timeout(std::time::Duration::from_secs(60));
+        let err = compress_file_with_options(&large_path, &full, &opts).unwrap_err();
+        assert!(matches!(err, StreamError::DiskFull { .. }), "{err:?}");
+        assert_eq!(err.to_string(), "disk full after 0 bytes written");
+
+        for p in [small_path, large_path, packed_path] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    /// One writer kept open across compressions appends a frame per call;
+    /// one reader reads them back a frame per call.
+    #[test]
+    fn test_owned_handles() {
+        let first_path = temp_path("handles_first.bin");
+        let second_path = temp_path("handles_second.bin");
+        let packed_path = temp_path("handles_packed.zxc");
+        let output_path = temp_path("handles_output.bin");
+        let first = crate::fixtures::input();
+        let second = crate::fixtures::input().repeat(40);
+        fs::write(&first_path, &first).unwrap();
+        fs::write(&second_path, &second).unwrap();
+
+        let opts = StreamCompressOptions::default().threads(2);
+        let mut out = ZxcFileWriter::create(&packed_path).unwrap();
+        let mut sizes = Vec::new();
+        for path in [&first_path, &second_path] {
+            let mut input = ZxcFileReader::open(path).unwrap();
+            sizes.push(out.compress_from(&mut input, &opts).unwrap());
+        }
+        // Both handles move between threads.
+        let moved = opts.clone();
+        let worker = std::thread::spawn(move || {
+            let mut input = ZxcFileReader::open(&first_path).unwrap();
+            sizes.push(out.compress_from(&mut input, &moved).unwrap());
+            out.flush().unwrap();
+            let _ = fs::remove_file(&first_path);
+            (out, sizes)
+        });
+        let (out, sizes) = worker.join().unwrap();
+        drop(out);
+        let packed = fs::read(&packed_path).unwrap();
+        assert_eq!(packed.len() as u64, sizes.iter().sum::<u64>());
+        let (a, rest) = packed.split_at(sizes[0] as usize);
+        let (b, c) = rest.split_at(sizes[1] as usize);
+        assert_eq!(decompress(a).unwrap(), first);
+        assert_eq!(decompress(b).unwrap(), second);
+        assert_eq!(decompress(c).unwrap(), first);
+
+        // Reopened for appending, the file takes one more frame.
+        let mut out = ZxcFileWriter::append(&packed_path).unwrap();
+        let mut input = ZxcFileReader::open(&second_path).unwrap();
+        out.compress_from(&mut input, &opts).unwrap();
+        drop(out);
+
+        let dopts = StreamDecompressOptions::default();
+        let mut input = ZxcFileReader::open(&packed_path).unwrap();
+        let mut out = ZxcFileWriter::create(&output_path).unwrap();
+        let mut total = 0;
+        for _ in 0..4 {
+            total += out.decompress_from(&mut input, &dopts).unwrap();
+        }
+        assert!(out.decompress_from(&mut input, &dopts).is_err());
+        out.flush().unwrap();
+        assert_eq!(total, 2 * (first.len() + second.len()) as u64);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            [&first[..], &second, &first, &second].concat()
+        );
+
+        let timed = StreamCompressOptions::default().timeout(std::time::Duration::from_secs(1));
+        let err = out.compress_from(&mut input, &timed).unwrap_err();
+        assert!(
+            matches!(&err, StreamError::Io(e) if e.kind() == io::ErrorKind::InvalidInput),
+            "{err:?}"
+        );
+        assert!(ZxcFileReader::open(temp_path("handles_missing.zxc")).is_err());
+
+        for path in [&second_path, &packed_path, &output_path] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// A producer and an archiver over a Unix socket: through `Read` and
+    /// `Write`, then through the owned handles over its descriptors, a frame
+    /// after another on the open connection.
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_sockets() {
+        use std::os::fd::OwnedFd;
+        use std::os::unix::net::UnixStream;
+
+        let input_path = temp_path("socket_input.bin");
+        let output_path = temp_path("socket_output.bin");
+        let data = crate::fixtures::input().repeat(30);
+        fs::write(&input_path, &data).unwrap();
+
+        let (producer, archiver) = UnixStream::pair().unwrap();
+        let input = input_path.clone();
+        let sender = std::thread::spawn(move || compress_to_writer(&input, producer, None));
+        let received = decompress_from_reader(archiver, &output_path, None).unwrap();
+        assert!(sender.join().unwrap().unwrap() < received);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let (producer, archiver) = UnixStream::pair().unwrap();
+        let (producer, archiver) = (
+            fs::File::from(OwnedFd::from(producer)),
+            fs::File::from(OwnedFd::from(archiver)),
+        );
+        let input = input_path.clone();
+        let sender = std::thread::spawn(move || {
+            let opts = StreamCompressOptions::default().threads(2);
+            let mut out = ZxcFileWriter::new(&producer).unwrap();
+            for _ in 0..2 {
+                out.compress_from(&mut ZxcFileReader::open(&input).unwrap(), &opts)
+                    .unwrap();
+                // The frame goes out whole; the connection stays open.
+                out.flush().unwrap();
+            }
+            producer
+        });
+        let dopts = StreamDecompressOptions::default().threads(2);
+        let mut input = ZxcFileReader::new(&archiver).unwrap();
+        let mut out = ZxcFileWriter::create(&output_path).unwrap();
+        for _ in 0..2 {
+            let written = out.decompress_from(&mut input, &dopts).unwrap();
+            assert_eq!(written, data.len() as u64);
+        }
+        out.flush().unwrap();
+        drop(sender.join().unwrap());
+        assert_eq!(fs::read(&output_path).unwrap(), data.repeat(2));
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_compress_many() {
+        let data = crate::fixtures::input();
+        let mut inputs: Vec<_> = (0..9)
+            .map(|i| {
+                let path = std::path::PathBuf::from(temp_path(&format!("many_{i}.log")));
+                fs::write(&path, data.repeat(i + 1)).unwrap();
+                path
+            })
+            .collect();
+        inputs.insert(4, temp_path("many_missing.log").into());
+
+        let opts = StreamCompressOptions::default().threads(3);
+        let results = compress_many(&inputs, &opts);
+        assert_eq!(results.len(), inputs.len());
+        for (i, (input, result)) in inputs.iter().zip(&results).enumerate() {
+            if i == 4 {
+                assert!(result.is_err());
+                continue;
+            }
+            let stats = result.as_ref().unwrap();
+            assert_eq!(
+                stats.output.to_str(),
+                Some(&*format!("{}.zxc", input.display()))
+            );
+            assert_eq!(stats.bytes_in, fs::metadata(input).unwrap().len());
+            assert_eq!(stats.bytes_out, fs::metadata(&stats.output).unwrap().len());
+            let packed = fs::read(&stats.output).unwrap();
+            assert_eq!(decompress(&packed).unwrap(), fs::read(input).unwrap());
+            let _ = fs::remove_file(input);
+            let _ = fs::remove_file(&stats.output);
+        }
+        assert!(compress_many::<&str>(&[], &opts).is_empty());
+    }
+
+    /// On Windows an open handle prevents deleting or renaming the file, so a
+    /// handle leaked by the CRT bridge shows up as a failing removal.
+    #[cfg(windows)]
+    #[test]
+    fn test_file_handles_released_windows() {
+        let input_path = temp_path("handles_input.bin");
+        let compressed_path = temp_path("handles_compressed.zxc");
+        let output_path = temp_path("handles_output.bin");
+
+        fs::write(&input_path, vec![7u8; 64 * 1024]).unwrap();
+        compress_file(&input_path, &compressed_path, Level::Default, None, None).unwrap();
+        file_decompressed_size(&compressed_path).unwrap();
+        decompress_file(&compressed_path, &output_path, None).unwrap();
+
+        fs::remove_file(&input_path).expect("input handle leaked");
+        fs::remove_file(&compressed_path).expect("compressed handle leaked");
+        fs::remove_file(&output_path).expect("output handle leaked");
+    }
+
+    /// Runs the whole file API (plus `Seekable::open`) on files under `dir`
+    /// whose names contain `stem`.
+    fn roundtrip_in(dir: &std::path::Path, stem: &str) {
+        let input = dir.join(format!("{stem}.bin"));
+        let compressed = dir.join(format!("{stem}.zxc"));
+        let output = dir.join(format!("{stem}.out"));
+
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&input, &data).unwrap();
+
+        let opts = StreamCompressOptions::default().with_seekable();
+        compress_file_with_options(&input, &compressed, &opts).unwrap();
+        assert_eq!(
+            file_decompressed_size(&compressed).unwrap(),
+            data.len() as u64
+        );
+        decompress_file(&compressed, &output, None).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), data);
+
+        let mut s = seekable::Seekable::open(&compressed).unwrap();
+        let mut buf = vec![0u8; 100];
+        s.decompress_range(&mut buf, 1000, 100).unwrap();
+        assert_eq!(&buf[..], &data[1000..1100]);
+        drop(s);
+
+        for p in [&input, &compressed, &output] {
+            let _ = fs::remove_file(p);
+        }
+    }
+
+    /// Names outside ASCII (and outside any ANSI code page on Windows).
+    #[test]
+    fn test_file_non_ascii_names() {
+        let dir = std::path::PathBuf::from(temp_path("unicode_dïr_日本"));
+        fs::create_dir_all(&dir).unwrap();
+        roundtrip_in(&dir, "données_αβγ_😀");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// A tree deeper than Windows' legacy 260-character `MAX_PATH`.
+    #[test]
+    fn test_file_deep_paths() {
+        let root = std::path::PathBuf::from(temp_path("deep"));
+        let mut dir = root.clone();
+        while dir.as_os_str().len() < 320 {
+            dir.push("a_fairly_long_directory_name_0123456789");
+        }
+        fs::create_dir_all(&dir).unwrap();
+        roundtrip_in(&dir, "deep_file");
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_extended_length_forms() {
+        use super::extended_length;
+        use std::path::{Path, PathBuf};
+
+        assert_eq!(
+            extended_length(Path::new(r"C:\a\..\b\f.zxc")),
+            Some(PathBuf::from(r"\\?\C:\b\f.zxc"))
+        );
+        assert_eq!(
+            extended_length(Path::new(r"\\server\share\dir\f.zxc")),
+            Some(PathBuf::from(r"\\?\UNC\server\share\dir\f.zxc"))
+        );
+        assert_eq!(extended_length(Path::new(r"\\?\C:\f.zxc")), None);
+    }
+
+    /// Goes through the `\\localhost\C$` administrative share; skipped where
+    /// the share is not reachable (disabled, or not running on drive C:).
+    #[cfg(windows)]
+    #[test]
+    fn test_file_unc_share() {
+        let local = std::path::PathBuf::from(temp_path("unc"));
+        fs::create_dir_all(&local).unwrap();
+        let Some(rest) = local.to_str().and_then(|s| s.strip_prefix(r"C:\")) else {
+            return;
+        };
+        let unc = std::path::PathBuf::from(format!(r"\\localhost\C$\{rest}"));
+        if fs::metadata(&unc).is_err() {
+            return;
+        }
+        roundtrip_in(&unc, "unc_file");
+        let _ = fs::remove_dir_all(&local);
+    }
+
+    /// The far end of a pipe: made before the file API opens its own,
+    /// connected from a thread of the test.
+    type PipeEnd = Box<dyn FnOnce() -> fs::File + Send>;
+
+    /// Compresses into and decompresses out of pipes served by a thread.
+    /// `pipe` makes the pipe named after a stem and returns its path and
+    /// the end to read (or, given `true`, write) in the thread.
+    fn roundtrip_pipes(pipe: impl Fn(&str, bool) -> (String, PipeEnd)) {
+        let input_path = temp_path("pipe_input.bin");
+        let output_path = temp_path("pipe_output.bin");
+        let data = crate::fixtures::input().repeat(30);
+        fs::write(&input_path, &data).unwrap();
+
+        let (path, end) = pipe("zxc_pipe_out", false);
+        let reader = std::thread::spawn(move || {
+            let mut frame = Vec::new();
+            io::Read::read_to_end(&mut end(), &mut frame).unwrap();
+            frame
+        });
+        compress_file(&input_path, &path, Level::Default, Some(2), None).unwrap();
+        let frame = reader.join().unwrap();
+        assert_eq!(crate::decompress(&frame).unwrap(), data);
+
+        // The non-seekable decode path, footer and all.
+        let (path, end) = pipe("zxc_pipe_in", true);
+        let writer = std::thread::spawn(move || end().write_all(&frame).unwrap());
+        let written = decompress_file(&path, &output_path, Some(2)).unwrap();
+        writer.join().unwrap();
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_fifos() {
+        let made = std::sync::Mutex::new(Vec::new());
+        roundtrip_pipes(|stem, write| {
+            let path = temp_path(&format!("{stem}.fifo"));
+            let _ = fs::remove_file(&path);
+            let c_path = std::ffi::CString::new(path.as_str()).unwrap();
+            assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+            made.lock().unwrap().push(path.clone());
+            // Opening one end of a FIFO waits for the other.
+            let end = path.clone();
+            let end: PipeEnd = Box::new(move || match write {
+                true => fs::File::options().write(true).open(end).unwrap(),
+                false => fs::File::open(end).unwrap(),
+            });
+            (path, end)
+        });
+        for path in made.into_inner().unwrap() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_file_named_pipes() {
+        use std::ffi::c_void;
+        use std::os::windows::ffi::OsStrExt;
+        use std::os::windows::io::{AsRawHandle, FromRawHandle};
+
+        unsafe extern "system" {
+            fn CreateNamedPipeW(
+                name: *const u16,
+                open_mode: u32,
+                pipe_mode: u32,
+                max_instances: u32,
+                out_buffer_size: u32,
+                in_buffer_size: u32,
+                default_timeout: u32,
+                security_attributes: *mut c_void,
+            ) -> *mut c_void;
+            fn ConnectNamedPipe(pipe: *mut c_void, overlapped: *mut c_void) -> i32;
+        }
+        const PIPE_ACCESS_INBOUND: u32 = 0x1;
+        const PIPE_ACCESS_OUTBOUND: u32 = 0x2;
+
+        roundtrip_pipes(|stem, write| {
+            let path = format!(r"\\.\pipe\{stem}_{}", std::process::id());
+            let wide: Vec<u16> = std::ffi::OsStr::new(&path)
+                .encode_wide()
+                .chain(Some(0))
+                .collect();
+            let mode = match write {
+                true => PIPE_ACCESS_OUTBOUND,
+                false => PIPE_ACCESS_INBOUND,
+            };
+            let h = unsafe {
+                CreateNamedPipeW(
+                    wide.as_ptr(),
+                    mode,
+                    0,
+                    1,
+                    1 << 16,
+                    1 << 16,
+                    0,
+                    std::ptr::null_mut(),
+                )
+            };
+            assert_ne!(h as isize, -1, "{}", io::Error::last_os_error());
+            let server = unsafe { fs::File::from_raw_handle(h) };
+            // Fails with ERROR_PIPE_CONNECTED when the client came first.
+            let end: PipeEnd = Box::new(move || {
+                unsafe { ConnectNamedPipe(server.as_raw_handle(), std::ptr::null_mut()) };
+                server
+            });
+            (path, end)
+        });
+    }
+
     #[test]
     fn test_file_decompressed_size_query() {
         let input_path = temp_path("size_input.bin");
@@ -595,11 +2592,127 @@ mod tests {
         let reported_size = file_decompressed_size(&compressed_path).unwrap();
         assert_eq!(reported_size, data.len() as u64);
 
+        // Each failure says what went wrong.
+        assert!(matches!(
+            file_decompressed_size(temp_path("size_missing.zxc")),
+            Err(StreamError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+        assert!(matches!(
+            file_decompressed_size(&input_path),
+            Err(StreamError::BufferError(Error::BadMagic))
+        ));
+        fs::write(&input_path, b"short").unwrap();
+        assert!(matches!(
+            file_decompressed_size(&input_path),
+            Err(StreamError::BufferError(Error::SrcTooSmall))
+        ));
+
         // Cleanup
         let _ = fs::remove_file(&input_path);
         let _ = fs::remove_file(&compressed_path);
     }
 
+    #[test]
+    fn test_empty_files() {
+        let empty_path = temp_path("empty_input.bin");
+        let compressed_path = temp_path("empty_compressed.zxc");
+        let output_path = temp_path("empty_output.bin");
+        fs::write(&empty_path, b"").unwrap();
+
+        // A zero-byte file compresses to the frame of empty input.
+        compress_file(
+            &empty_path,
+            &compressed_path,
+            Level::Default,
+            None,
+            Some(true),
+        )
+        .unwrap();
+        let frame = fs::read(&compressed_path).unwrap();
+        assert_eq!(frame, compress(b"", Level::Default, Some(true)).unwrap());
+        assert_eq!(file_decompressed_size(&compressed_path).unwrap(), 0);
+        assert_eq!(
+            decompress_file(&compressed_path, &output_path, None).unwrap(),
+            0
+        );
+        assert!(fs::read(&output_path).unwrap().is_empty());
+
+        // A zero-byte file is no frame, with or without a deadline, and the
+        // output is left alone.
+        fs::write(&output_path, b"keep").unwrap();
+        let deadline =
+            StreamDecompressOptions::default().timeout(std::time::Duration::from_secs(60));
+        for opts in [StreamDecompressOptions::default(), deadline] {
+            assert!(matches!(
+                decompress_file_with_options(&empty_path, &output_path, &opts),
+                Err(StreamError::BufferError(Error::SrcTooSmall))
+            ));
+        }
+        assert!(matches!(
+            file_decompressed_size(&empty_path),
+            Err(StreamError::BufferError(Error::SrcTooSmall))
+        ));
+        assert_eq!(fs::read(&output_path).unwrap(), b"keep");
+
+        let _ = fs::remove_file(&empty_path);
+        let _ = fs::remove_file(&compressed_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_thread_count_checked() {
+        let input_path = temp_path("threads_input.bin");
+        let output_path = temp_path("threads_output.zxc");
+        fs::write(&input_path, b"thread count").unwrap();
+        let _ = fs::remove_file(&output_path);
+
+        // A count the C library's `int` cannot hold fails before any file
+        // is created.
+        let opts = StreamCompressOptions::default().threads(usize::MAX);
+        assert!(matches!(
+            compress_file_with_options(&input_path, &output_path, &opts),
+            Err(StreamError::BufferError(Error::InvalidOption(_)))
+        ));
+        assert!(fs::metadata(&output_path).is_err());
+
+        let _ = fs::remove_file(&input_path);
+    }
+
+    #[test]
+    fn test_decompressed_size_from() {
+        use std::io::{Cursor, Seek, SeekFrom};
+
+        let data = vec![42u8; 100_000];
+        let frame = compress(&data, Level::Default, None).unwrap();
+        let mut cursor = Cursor::new(&frame);
+        cursor.seek(SeekFrom::Start(7)).unwrap();
+        assert_eq!(decompressed_size_from(&mut cursor).unwrap(), 100_000);
+        assert_eq!(cursor.position(), 7);
+        assert_eq!(
+            decompressed_size_from(Cursor::new(compress(b"", Level::Default, None).unwrap()))
+                .unwrap(),
+            0
+        );
+
+        assert!(matches!(
+            decompressed_size_from(Cursor::new(&frame[..20])),
+            Err(StreamError::BufferError(Error::SrcTooSmall))
+        ));
+        assert!(matches!(
+            decompressed_size_from(Cursor::new(&data)),
+            Err(StreamError::BufferError(Error::BadMagic))
+        ));
+
+        // Agrees with the C footer read on a file.
+        let path = temp_path("size_from.zxc");
+        fs::write(&path, &frame).unwrap();
+        assert_eq!(
+            decompressed_size_from(fs::File::open(&path).unwrap()).unwrap(),
+            file_decompressed_size(&path).unwrap()
+        );
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_file_all_levels() {
         let input_path = temp_path("levels_input.bin");
@@ -678,4 +2791,248 @@ mod tests {
         let _ = fs::remove_file(&compressed_path);
         let _ = fs::remove_file(&output_path);
     }
+
+    #[test]
+    fn test_block_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let input_path = temp_path("blocks_input.bin");
+        let compressed_path = temp_path("blocks_compressed.zxc");
+        let output_path = temp_path("blocks_output.bin");
+        // Two full 512 KiB blocks and a partial one.
+        let data: Vec<u8> = (0..1024 * 1024 + 123).map(|i| (i % 251) as u8).collect();
+        fs::write(&input_path, &data).unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let copts = StreamCompressOptions::default()
+            .threads(4)
+            .on_block(move |s| sink.lock().unwrap().push(*s));
+        let written = compress_file_with_options(&input_path, &compressed_path, &copts).unwrap();
+        let blocks = std::mem::take(&mut *seen.lock().unwrap());
+        assert_eq!(blocks.len(), 3);
+        assert!(blocks.iter().enumerate().all(|(i, b)| b.index == i as u64));
+        assert!(blocks.iter().all(|b| b.worker < 3));
+        assert_eq!(
+            blocks.iter().map(|b| b.in_size).sum::<u64>(),
+            data.len() as u64
+        );
+        let encoded: u64 = blocks.iter().map(|b| b.out_size).sum();
+        assert!(encoded < written);
+
+        let sink = Arc::clone(&seen);
+        let dopts = StreamDecompressOptions::default()
+            .threads(2)
+            .on_block(move |s| sink.lock().unwrap().push(*s));
+        decompress_file_with_options(&compressed_path, &output_path, &dopts).unwrap();
+        let blocks = std::mem::take(&mut *seen.lock().unwrap());
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks.iter().map(|b| b.in_size).sum::<u64>(), encoded);
+        assert_eq!(
+            blocks.iter().map(|b| b.out_size).sum::<u64>(),
+            data.len() as u64
+        );
+
+        // A panic in the callback surfaces once the driver is done.
+        let dopts = StreamDecompressOptions::default().on_block(|_| panic!("block callback"));
+        let caught = std::panic::catch_unwind(|| {
+            decompress_file_with_options(&compressed_path, &output_path, &dopts)
+        });
+        assert!(caught.is_err());
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&compressed_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_stream_stats() {
+        use std::time::{Duration, Instant};
+
+        let input_path = temp_path("stats_input.bin");
+        let compressed_path = temp_path("stats_compressed.zxc");
+        let output_path = temp_path("stats_output.bin");
+        let data: Vec<u8> = (0..2 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        fs::write(&input_path, &data).unwrap();
+
+        let copts = StreamCompressOptions::default().threads(3);
+        let stats = compress_file_with_stats(&input_path, &compressed_path, &copts).unwrap();
+        assert_eq!(
+            stats.bytes_out,
+            fs::metadata(&compressed_path).unwrap().len()
+        );
+        assert_eq!(stats.workers.len(), 2);
+        assert!(stats.compute > Duration::ZERO && stats.read > Duration::ZERO);
+        assert!(stats.read <= stats.wall && stats.write <= stats.wall);
+        assert_eq!(stats.workers.iter().sum::<Duration>(), stats.compute);
+        assert!((0.0..=1.0).contains(&stats.utilization()));
+        assert_eq!(stats.worker_utilization().len(), 2);
+
+        let dopts = StreamDecompressOptions::default().threads(1);
+        let stats = decompress_file_with_stats(&compressed_path, &output_path, &dopts).unwrap();
+        assert_eq!(stats.bytes_out, data.len() as u64);
+        assert_eq!(stats.workers.len(), 1);
+        assert!(stats.compute > Duration::ZERO && stats.compute <= stats.wall);
+
+        let late = copts.deadline(Instant::now() + Duration::from_secs(60));
+        match compress_file_with_stats(&input_path, &compressed_path, &late) {
+            Err(StreamError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+            other => panic!("expected InvalidInput, got {other:?}"),
+        }
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&compressed_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_file_deadline() {
+        use std::time::{Duration, Instant};
+
+        let input_path = temp_path("deadline_input.bin");
+        let compressed_path = temp_path("deadline_compressed.zxc");
+        let output_path = temp_path("deadline_output.bin");
+        let data: Vec<u8> = (0..700 * 1024).map(|i| (i % 251) as u8).collect();
+        fs::write(&input_path, &data).unwrap();
+
+        let copts = StreamCompressOptions::default().timeout(Duration::from_secs(60));
+        let dopts = StreamDecompressOptions::default().timeout(Duration::from_secs(60));
+        let written = compress_file_with_options(&input_path, &compressed_path, &copts).unwrap();
+        assert_eq!(written, fs::metadata(&compressed_path).unwrap().len());
+        let size = decompress_file_with_options(&compressed_path, &output_path, &dopts).unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let expired = StreamCompressOptions::default().deadline(Instant::now());
+        assert!(matches!(
+            compress_file_with_options(&input_path, &compressed_path, &expired),
+            Err(StreamError::TimedOut)
+        ));
+        let expired = StreamDecompressOptions::default().deadline(Instant::now());
+        assert!(matches!(
+            decompress_file_with_options(&compressed_path, &output_path, &expired),
+            Err(StreamError::TimedOut)
+        ));
+        let err = compress_file_with_options(
+            &input_path,
+            &compressed_path,
+            &copts.clone().with_seekable(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, StreamError::Io(e) if e.kind() == std::io::ErrorKind::InvalidInput),
+            "{err:?}"
+        );
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&compressed_path);
+        let _ = fs::remove_file(&output_path);
+    }
+
+    /// A job stuck in a call that does not return still lets the caller go.
+    #[test]
+    fn test_deadline_abandons_hung_job() {
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let result = super::with_deadline(start + Duration::from_millis(50), || {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(0)
+        });
+        assert!(matches!(result, Err(StreamError::TimedOut)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    /// Archives written by the C CLI go through the file driver as well.
+    #[test]
+    fn test_cli_fixtures_decompress_file() {
+        let expected = crate::fixtures::input();
+        let output_path = temp_path("cli_fixture_output.bin");
+        for (i, (level, checksum, frame)) in crate::fixtures::CLI.into_iter().enumerate() {
+            let input_path = temp_path(&format!("cli_fixture_{i}.zxc"));
+            fs::write(&input_path, frame).unwrap();
+
+            let what = format!("level {level}, checksum {checksum}");
+            assert_eq!(
+                file_decompressed_size(&input_path).unwrap(),
+                expected.len() as u64,
+                "{what}"
+            );
+            decompress_file(&input_path, &output_path, None).unwrap();
+            assert_eq!(fs::read(&output_path).unwrap(), expected, "{what}");
+            let _ = fs::remove_file(&input_path);
+        }
+        let _ = fs::remove_file(&output_path);
+    }
+
+    /// Archives written by the wrapper pass `zxc -t` and `zxc -d` of the C
+    /// CLI named by `ZXC_CLI`; skipped when it is unset.
+    #[test]
+    fn test_cli_accepts_rust_archives() {
+        let Some(cli) = std::env::var_os("ZXC_CLI") else {
+            eprintln!("ZXC_CLI not set, skipping");
+            return;
+        };
+        let data = crate::fixtures::input();
+        let input_path = temp_path("to_cli_input.bin");
+        fs::write(&input_path, &data).unwrap();
+
+        for level in Level::all() {
+            for checksum in [false, true] {
+                let what = format!("{level:?}, checksum {checksum}");
+                let archives = [
+                    (temp_path("to_cli_buffer.zxc"), {
+                        let mut opts = CompressOptions::with_level(*level);
+                        opts.checksum = checksum;
+                        compress_with_options(&data, &opts).unwrap()
+                    }),
+                    (temp_path("to_cli_file.zxc"), {
+                        let path = temp_path("to_cli_file.zxc");
+                        compress_file(&input_path, &path, *level, Some(2), Some(checksum)).unwrap();
+                        fs::read(&path).unwrap()
+                    }),
+                ];
+                for (path, archive) in archives {
+                    fs::write(&path, archive).unwrap();
+                    let test = std::process::Command::new(&cli)
+                        .arg("-t")
+                        .arg(&path)
+                        .output()
+                        .unwrap();
+                    assert!(test.status.success(), "zxc -t rejected it ({what})");
+                    let out = std::process::Command::new(&cli)
+                        .args(["-d", "-c"])
+                        .arg(&path)
+                        .output()
+                        .unwrap();
+                    assert!(out.status.success(), "zxc -d failed ({what})");
+                    assert_eq!(out.stdout, data, "{what}");
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+        let _ = fs::remove_file(&input_path);
+    }
+}
+
+#[cfg(all(test, any(feature = "no-stream", target_family = "wasm")))]
+mod stub_tests {
+    use crate::*;
+
+    #[test]
+    fn test_file_api_unsupported() {
+        let err = file_decompressed_size("missing.zxc").unwrap_err();
+        assert!(
+            matches!(&err, StreamError::Io(e) if e.kind() == std::io::ErrorKind::Unsupported),
+            "{err:?}"
+        );
+        assert!(matches!(
+            seekable::Seekable::open("missing.zxc"),
+            Err(Error::Unsupported(_))
+        ));
+        let file = std::fs::File::open("Cargo.toml").unwrap();
+        let err = ZxcFileReader::new(&file).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert!(ZxcFileWriter::new(&file).is_err());
+    }
 }
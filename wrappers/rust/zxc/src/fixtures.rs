@@ -0,0 +1,86 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Frames written on a little-endian host (x86_64), decoded by the tests on
+//! every target. Running the suite on a big-endian machine (s390x,
+//! powerpc64) checks that the format is read with fixed endianness.
+//! See `testdata/README.md` for how they were produced.
+
+/// `Level::Default`, per-block checksums.
+pub(crate) const LE_DEFAULT_CHECKSUM: &[u8] = include_bytes!("../testdata/le_default_checksum.zxc");
+/// `Level::Density` (Huffman literals), no checksum.
+pub(crate) const LE_DENSITY: &[u8] = include_bytes!("../testdata/le_density.zxc");
+/// `Level::Fast`, checksums and a seek table.
+#[cfg(feature = "std")]
+pub(crate) const LE_SEEKABLE: &[u8] = include_bytes!("../testdata/le_seekable.zxc");
+
+/// Frames written by the C `zxc` CLI (v0.13.1) from `input()`, as
+/// `(level, checksum, frame)`: every level once without checksums in a
+/// single block (`-N`), and once with checksums in 4 KiB blocks
+/// (`-B 4K -C`).
+pub(crate) const CLI: [(u8, bool, &[u8]); 14] = [
+    (1, false, include_bytes!("../testdata/cli_l1.zxc")),
+    (2, false, include_bytes!("../testdata/cli_l2.zxc")),
+    (3, false, include_bytes!("../testdata/cli_l3.zxc")),
+    (4, false, include_bytes!("../testdata/cli_l4.zxc")),
+    (5, false, include_bytes!("../testdata/cli_l5.zxc")),
+    (6, false, include_bytes!("../testdata/cli_l6.zxc")),
+    (7, false, include_bytes!("../testdata/cli_l7.zxc")),
+    (
+        1,
+        true,
+        include_bytes!("../testdata/cli_l1_b4k_checksum.zxc"),
+    ),
+    (
+        2,
+        true,
+        include_bytes!("../testdata/cli_l2_b4k_checksum.zxc"),
+    ),
+    (
+        3,
+        true,
+        include_bytes!("../testdata/cli_l3_b4k_checksum.zxc"),
+    ),
+    (
+        4,
+        true,
+        include_bytes!("../testdata/cli_l4_b4k_checksum.zxc"),
+    ),
+    (
+        5,
+        true,
+        include_bytes!("../testdata/cli_l5_b4k_checksum.zxc"),
+    ),
+    (
+        6,
+        true,
+        include_bytes!("../testdata/cli_l6_b4k_checksum.zxc"),
+    ),
+    (
+        7,
+        true,
+        include_bytes!("../testdata/cli_l7_b4k_checksum.zxc"),
+    ),
+];
+
+/// Regenerates the 16 KiB plaintext of every fixture: words and raw
+/// little-endian bytes picked by a glibc-constant LCG.
+pub(crate) fn input() -> Vec<u8> {
+    const WORDS: [&[u8]; 8] = [
+        b"zxc", b"block", b"offset", b"literal", b"huffman", b"endian", b"s390x", b"frame",
+    ];
+    let mut s: u32 = 0x2104;
+    let mut out = Vec::with_capacity(16 * 1024);
+    while out.len() < 16 * 1024 {
+        s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        out.extend_from_slice(WORDS[(s >> 16) as usize % WORDS.len()]);
+        out.push(if s & 0x80 != 0 { b'\n' } else { b' ' });
+        out.extend_from_slice(&(s >> 8).to_le_bytes()[..(s & 3) as usize]);
+    }
+    out.truncate(16 * 1024);
+    out
+}
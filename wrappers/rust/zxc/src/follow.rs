@@ -0,0 +1,319 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Compression of a growing file or a pipe as data arrives.
+//!
+//! A frame is only readable once its footer is written, so [`follow`]
+//! finishes the current frame whenever the input goes quiet and starts a
+//! new one with the next data. The output is a run of complete frames plus
+//! at most one being written; [`Decoder::multiple_frames`] reads them back
+//! to back.
+//!
+//! [`Decoder::multiple_frames`]: crate::Decoder::multiple_frames
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::thread;
+use std::time::Duration;
+
+use crate::stdio::Counted;
+use crate::{CompressOptions, Encoder};
+
+/// Size of the reads from the followed input.
+const CHUNK: usize = 64 * 1024;
+
+/// Options for [`follow`].
+#[derive(Debug, Clone)]
+pub struct FollowOptions {
+    /// Options of every frame written (default: `CompressOptions::default()`)
+    pub compress: CompressOptions,
+    /// Finish the current frame once no data has arrived for this long
+    /// (default: 1 s)
+    pub idle: Duration,
+    /// At the end of the input, wait this long and read again rather than
+    /// stop, like `tail -f` (default: `None`, stop at the end of the input,
+    /// as for a pipe)
+    pub poll: Option<Duration>,
+    /// Stop once this is set (default: `None`). It is checked between reads,
+    /// so a read blocked on a pipe is not interrupted.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            compress: CompressOptions::default(),
+            idle: Duration::from_secs(1),
+            poll: None,
+            stop: None,
+        }
+    }
+}
+
+/// What [`follow`] wrote, once it returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FollowStats {
+    /// Frames written, all of them complete.
+    pub frames: u64,
+    /// Bytes read from the input.
+    pub bytes_in: u64,
+    /// Compressed bytes written.
+    pub bytes_out: u64,
+}
+
+/// Compresses `reader` into `writer` as data arrives, until the end of the
+/// input (or, with `opts.poll`, until `opts.stop` is set).
+///
+/// Reads run on a helper thread so that a quiet input can be told apart
+/// from a slow one: once nothing has arrived for `opts.idle`, the frame
+/// being written is finished and `writer` flushed, so everything read so
+/// far can be decompressed at the other end. The next data starts a new
+/// frame. Quiet periods without data in between write nothing.
+///
+/// To follow a log file, open it and set `opts.poll`; truncation and
+/// rotation of the file are not detected.
+///
+/// # Errors
+///
+/// The errors of `reader`, `writer` and the encoder. On a read error the
+/// frame being written is finished first, so the output stays readable.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Read;
+///
+/// let mut out = Vec::new();
+/// let stats = zxc::follow(&b"one line\n"[..], &mut out, &Default::default())?;
+/// assert_eq!(stats.frames, 1);
+///
+/// let mut back = Vec::new();
+/// zxc::Decoder::new(&out[..])?.multiple_frames().read_to_end(&mut back)?;
+/// assert_eq!(back, b"one line\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn follow<R: Read + Send, W: Write>(
+    reader: R,
+    writer: W,
+    opts: &FollowOptions,
+) -> io::Result<FollowStats> {
+    let quit = AtomicBool::new(false);
+    let (tx, rx) = mpsc::sync_channel(4);
+    thread::scope(|s| {
+        let pump = s.spawn(|| pump(reader, tx, opts, &quit));
+        let mut out = Output {
+            writer: Some(writer),
+            frame: None,
+            stats: FollowStats::default(),
+        };
+        let result = loop {
+            match rx.recv_timeout(opts.idle) {
+                Ok(Ok(chunk)) => {
+                    if let Err(e) = out.write(&chunk, &opts.compress) {
+                        break Err(e);
+                    }
+                }
+                Ok(Err(e)) => break out.finish_frame().and(Err(e)),
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Err(e) = out.finish_frame() {
+                        break Err(e);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break out.finish_frame(),
+            }
+        };
+        // Unblocks a pump polling a quiet file after a write error.
+        quit.store(true, Ordering::Relaxed);
+        drop(rx);
+        if let Err(payload) = pump.join() {
+            std::panic::resume_unwind(payload);
+        }
+        result.map(|()| out.stats)
+    })
+}
+
+/// The writer, and the frame being written to it if any.
+struct Output<W: Write> {
+    writer: Option<W>,
+    frame: Option<Encoder<Counted<W>>>,
+    stats: FollowStats,
+}
+
+impl<W: Write> Output<W> {
+    fn write(&mut self, chunk: &[u8], opts: &CompressOptions) -> io::Result<()> {
+        let frame = match &mut self.frame {
+            Some(frame) => frame,
+            None => {
+                let writer = self.writer.take().ok_or_else(lost)?;
+                let frame = Encoder::with_options(Counted::new(writer), Some(opts))
+                    .map_err(io::Error::other)?;
+                self.frame.insert(frame)
+            }
+        };
+        frame.write_all(chunk)?;
+        self.stats.bytes_in += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Finishes the current frame, if any, and flushes the writer.
+    fn finish_frame(&mut self) -> io::Result<()> {
+        let Some(frame) = self.frame.take() else {
+            return Ok(());
+        };
+        let counted = frame.finish()?;
+        self.stats.frames += 1;
+        self.stats.bytes_out += counted.count;
+        let writer = self.writer.insert(counted.inner);
+        writer.flush()
+    }
+}
+
+/// The writer went with an encoder that failed to finish.
+fn lost() -> io::Error {
+    io::Error::other("zxc: follow output lost after an error")
+}
+
+/// Reads `reader` into `tx` until the end of the input, `opts.stop`, or
+/// `quit`.
+fn pump<R: Read>(
+    mut reader: R,
+    tx: SyncSender<io::Result<Vec<u8>>>,
+    opts: &FollowOptions,
+    quit: &AtomicBool,
+) {
+    let stopped = || {
+        quit.load(Ordering::Relaxed)
+            || opts
+                .stop
+                .as_ref()
+                .is_some_and(|s| s.load(Ordering::Relaxed))
+    };
+    let mut buf = vec![0u8; CHUNK];
+    while !stopped() {
+        match reader.read(&mut buf) {
+            Ok(0) => match opts.poll {
+                Some(poll) => thread::sleep(poll),
+                None => return,
+            },
+            Ok(n) => {
+                let chunk = buf.get(..n).unwrap_or_default().to_vec();
+                if tx.send(Ok(chunk)).is_err() {
+                    return;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => {
+                let _ = tx.send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::Decoder;
+
+    fn unpack(frames: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        Decoder::new(frames)
+            .unwrap()
+            .multiple_frames()
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    /// Hands out its parts one read at a time, sleeping `pause` before
+    /// each.
+    struct Slow {
+        parts: Vec<&'static [u8]>,
+        pause: Duration,
+    }
+
+    impl Read for Slow {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.parts.is_empty() {
+                return Ok(0);
+            }
+            thread::sleep(self.pause);
+            let part = self.parts.remove(0);
+            buf.get_mut(..part.len()).unwrap().copy_from_slice(part);
+            Ok(part.len())
+        }
+    }
+
+    #[test]
+    fn test_follow_pipe() {
+        let opts = FollowOptions {
+            idle: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let slow = Slow {
+            parts: vec![b"first ", b"second ", b"third"],
+            pause: Duration::from_millis(200),
+        };
+        let mut out = Vec::new();
+        let stats = follow(slow, &mut out, &opts).unwrap();
+        // One frame per burst.
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.bytes_in, 18);
+        assert_eq!(stats.bytes_out, out.len() as u64);
+        assert_eq!(unpack(&out), b"first second third");
+
+        // A quick input is one frame.
+        let opts = FollowOptions::default();
+        let mut out = Vec::new();
+        let data = vec![b'x'; 3 * CHUNK + 5];
+        let stats = follow(&data[..], &mut out, &opts).unwrap();
+        assert_eq!(stats.frames, 1);
+        assert_eq!(unpack(&out), data);
+
+        // Nothing in, nothing out.
+        let stats = follow(&b""[..], &mut out, &opts).unwrap();
+        assert_eq!(stats, FollowStats::default());
+    }
+
+    #[test]
+    fn test_follow_file() {
+        let dir = std::env::temp_dir().join(format!("zxc_follow_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("app.log");
+        fs::write(&log, b"started\n").unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let opts = FollowOptions {
+            idle: Duration::from_millis(50),
+            poll: Some(Duration::from_millis(5)),
+            stop: Some(stop.clone()),
+            ..Default::default()
+        };
+        let file = fs::File::open(&log).unwrap();
+        let follower = thread::spawn(move || {
+            let mut out = Vec::new();
+            follow(file, &mut out, &opts).map(|stats| (stats, out))
+        });
+
+        // Let the first frame be written before appending.
+        thread::sleep(Duration::from_millis(300));
+        let mut appender = fs::OpenOptions::new().append(true).open(&log).unwrap();
+        appender.write_all(b"request 1\nrequest 2\n").unwrap();
+        thread::sleep(Duration::from_millis(300));
+        stop.store(true, Ordering::Relaxed);
+
+        let (stats, out) = follower.join().unwrap().unwrap();
+        assert_eq!(stats.frames, 2);
+        assert_eq!(unpack(&out), b"started\nrequest 1\nrequest 2\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,44 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! The frame layout of docs/FORMAT.md, for the code that reads frames
+//! itself: the block walkers, the inspection tools and the pure-Rust
+//! decoder. The values the C headers define come from build.rs through
+//! `zxc_sys`; the others are fixed by the format.
+
+use core::ops::RangeInclusive;
+
+/// The frame format version this library writes, and the only one it
+/// reads (docs/FORMAT.md). Read from the C sources at build time.
+pub const FORMAT_VERSION: u8 = zxc_sys::ZXC_FILE_FORMAT_VERSION as u8;
+
+pub(crate) const MAGIC_WORD: u32 = 0x9CB0_2EF5;
+pub(crate) const FILE_HEADER_SIZE: usize = crate::FRAME_HEADER_SIZE;
+pub(crate) const FILE_FOOTER_SIZE: usize = crate::FOOTER_SIZE;
+pub(crate) const BLOCK_HEADER_SIZE: usize = 8;
+pub(crate) const BLOCK_CHECKSUM_SIZE: usize = 4;
+/// log2 of the block sizes a file header may name.
+pub(crate) const BLOCK_SIZE_LOG2: RangeInclusive<u8> =
+    zxc_sys::ZXC_BLOCK_SIZE_MIN_LOG2 as u8..=zxc_sys::ZXC_BLOCK_SIZE_MAX_LOG2 as u8;
+
+// File header flags.
+pub(crate) const FLAG_HAS_CHECKSUM: u8 = 0x80;
+pub(crate) const FLAG_HAS_DICTIONARY: u8 = 0x40;
+
+// Block types.
+pub(crate) const BLOCK_RAW: u8 = 0;
+pub(crate) const BLOCK_GLO: u8 = 1;
+pub(crate) const BLOCK_GHI: u8 = 2;
+pub(crate) const BLOCK_SEK: u8 = 254;
+pub(crate) const BLOCK_EOF: u8 = 255;
+
+/// GLO/GHI header: sequence and literal counts, then one encoding byte
+/// each for literals, tokens, match lengths and offsets.
+#[cfg(any(feature = "std", feature = "pure-rust-decode"))]
+pub(crate) const GNR_HEADER_SIZE: usize = 16;
+/// The Huffman section encoding.
+pub(crate) const ENC_HUFFMAN: u8 = 2;
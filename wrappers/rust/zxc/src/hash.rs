@@ -0,0 +1,151 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Header check values of docs/FORMAT.md (§3, §4) and the RapidHash
+//! checksums of blocks and frames, shared by the Rust decoders and the frame
+//! walkers that do not go through the C library.
+
+const HASH_PRIME1: u64 = 0x9E37_79B9_7F4A_7C15;
+const HASH_PRIME2: u64 = 0xD2D8_4A61_D2D8_4A61;
+
+/// File header check value: xorshift over the two halves, folded to 16 bits.
+pub(crate) fn hash16(p: &[u8; 16]) -> u16 {
+    let v1 = u64::from_le_bytes([p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7]]);
+    let v2 = u64::from_le_bytes([p[8], p[9], p[10], p[11], p[12], p[13], p[14], p[15]]);
+    let res = xorshift_fold(v1 ^ v2 ^ HASH_PRIME2);
+    ((res >> 16) ^ res) as u16
+}
+
+/// Block header check value, folded to 8 bits.
+pub(crate) fn hash8(p: &[u8; 8]) -> u8 {
+    xorshift_fold(u64::from_le_bytes(*p) ^ HASH_PRIME1) as u8
+}
+
+fn xorshift_fold(mut h: u64) -> u32 {
+    h ^= h << 13;
+    h ^= h >> 7;
+    h ^= h << 17;
+    ((h >> 32) ^ h) as u32
+}
+
+/// Per-block checksum: RapidHash folded to 32 bits.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    fold32(rapidhash(data, 0))
+}
+
+pub(crate) fn fold32(hash: u64) -> u32 {
+    (hash ^ (hash >> 32)) as u32
+}
+
+const RAPID_SECRET: [u64; 8] = [
+    0x2d35_8dcc_aa6c_78a5,
+    0x8bb8_4b93_962e_acc9,
+    0x4b33_a62e_d433_d4a3,
+    0x4d5a_2da5_1de1_aa47,
+    0xa076_1d64_78bd_642f,
+    0xe703_7ed1_a0b4_28db,
+    0x90ed_1765_281c_388c,
+    0xaaaa_aaaa_aaaa_aaaa,
+];
+
+fn rapid_mum(a: u64, b: u64) -> (u64, u64) {
+    let r = u128::from(a) * u128::from(b);
+    (r as u64, (r >> 64) as u64)
+}
+
+fn rapid_mix(a: u64, b: u64) -> u64 {
+    let (lo, hi) = rapid_mum(a, b);
+    lo ^ hi
+}
+
+// Reads within the length the callers branch on.
+#[allow(clippy::indexing_slicing)]
+fn rapid_read64(p: &[u8], at: usize) -> u64 {
+    let mut word = [0; 8];
+    word.copy_from_slice(&p[at..at + 8]);
+    u64::from_le_bytes(word)
+}
+
+#[allow(clippy::indexing_slicing)]
+fn rapid_read32(p: &[u8], at: usize) -> u64 {
+    let mut word = [0; 4];
+    word.copy_from_slice(&p[at..at + 4]);
+    u64::from(u32::from_le_bytes(word))
+}
+
+/// RapidHash (the vendored `rapidhash.h`, default "compact" + "fast" build).
+#[allow(clippy::indexing_slicing)]
+pub(crate) fn rapidhash(key: &[u8], mut seed: u64) -> u64 {
+    let s = &RAPID_SECRET;
+    let len = key.len();
+    seed ^= rapid_mix(seed ^ s[2], s[1]);
+    let (mut a, mut b);
+    let mut i = len;
+    if len <= 16 {
+        if len >= 4 {
+            seed ^= len as u64;
+            if len >= 8 {
+                a = rapid_read64(key, 0);
+                b = rapid_read64(key, len - 8);
+            } else {
+                a = rapid_read32(key, 0);
+                b = rapid_read32(key, len - 4);
+            }
+        } else if len > 0 {
+            a = (u64::from(key[0]) << 45) | u64::from(key[len - 1]);
+            b = u64::from(key[len >> 1]);
+        } else {
+            a = 0;
+            b = 0;
+        }
+    } else {
+        let mut p = 0;
+        if len > 112 {
+            let mut see = [seed; 6];
+            loop {
+                seed = rapid_mix(rapid_read64(key, p) ^ s[0], rapid_read64(key, p + 8) ^ seed);
+                for (k, lane) in see.iter_mut().enumerate() {
+                    let at = p + 16 * (k + 1);
+                    *lane = rapid_mix(
+                        rapid_read64(key, at) ^ s[k + 1],
+                        rapid_read64(key, at + 8) ^ *lane,
+                    );
+                }
+                p += 112;
+                i -= 112;
+                if i <= 112 {
+                    break;
+                }
+            }
+            seed ^= see[0];
+            see[1] ^= see[2];
+            see[3] ^= see[4];
+            seed ^= see[5];
+            see[1] ^= see[3];
+            seed ^= see[1];
+        }
+        // Up to six 16-byte rounds over what is left, with the secret index
+        // pattern of the reference implementation.
+        const TAIL_SECRETS: [usize; 6] = [2, 2, 1, 1, 2, 1];
+        for (k, &idx) in TAIL_SECRETS.iter().enumerate() {
+            if i <= 16 * (k + 1) {
+                break;
+            }
+            let at = p + 16 * k;
+            seed = rapid_mix(
+                rapid_read64(key, at) ^ s[idx],
+                rapid_read64(key, at + 8) ^ seed,
+            );
+        }
+        a = rapid_read64(key, p + i - 16) ^ i as u64;
+        b = rapid_read64(key, p + i - 8);
+    }
+    a ^= s[1];
+    b ^= seed;
+    (a, b) = rapid_mum(a, b);
+    rapid_mix(a ^ s[7], b ^ s[1] ^ i as u64)
+}
@@ -0,0 +1,429 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! `Content-Encoding: zxc` for HTTP clients (the `http` feature).
+//!
+//! [`DecompressBody`] wraps any [`http_body::Body`] and yields the
+//! decompressed bytes as the compressed ones arrive, through a push stream;
+//! [`decompress_response`] applies it to a response whose
+//! `Content-Encoding` ends in `zxc`, and leaves other responses as they are.
+//! hyper's `Incoming` and reqwest's `Body` are both `http-body` 1.0 bodies.
+//!
+//! ```rust
+//! use bytes::Bytes;
+//! use http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+//! use http_body_util::{BodyExt, Full};
+//!
+//! # futures::executor::block_on(async {
+//! let json = br#"{"items":[1,2,3]}"#.repeat(1000);
+//! let res = http::Response::builder()
+//!     .header(CONTENT_ENCODING, "zxc")
+//!     .body(Full::new(Bytes::from(zxc::compress(&json, zxc::Level::Default, None)?)))?;
+//!
+//! let res = zxc::http::decompress_response(res);
+//! assert!(!res.headers().contains_key(CONTENT_ENCODING));
+//! assert!(!res.headers().contains_key(CONTENT_LENGTH));
+//! assert_eq!(res.into_body().collect().await?.to_bytes(), json);
+//! # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+//! # }).unwrap();
+//! ```
+//!
+//! With reqwest, ask for the encoding with `Accept-Encoding: zxc`
+//! ([`ENCODING`]), then go through `http::Response` and back:
+//!
+//! ```rust,ignore
+//! let res = client.get(url).header(ACCEPT_ENCODING, zxc::http::ENCODING).send().await?;
+//! let res = zxc::http::decompress_response(http::Response::from(res));
+//! let res = reqwest::Response::from(res.map(reqwest::Body::wrap));
+//! let text = res.text().await?;
+//! ```
+//!
+//! The body is a single zxc frame. Decompression runs inline on the task
+//! polling the body.
+
+use std::boxed::Box;
+use std::error::Error as StdError;
+use std::fmt;
+use std::pin::Pin;
+use std::string::String;
+use std::task::{Context, Poll, ready};
+use std::vec::Vec;
+
+use ::http::header::{CONTENT_ENCODING, CONTENT_LENGTH, HeaderMap, HeaderValue};
+use bytes::{Buf, Bytes};
+use http_body::{Body, Frame, SizeHint};
+
+use crate::scratch::Scratch;
+use crate::{DStream, Error};
+
+/// The `Content-Encoding` and `Accept-Encoding` token of zxc.
+pub const ENCODING: &str = "zxc";
+
+/// Errors of a [`DecompressBody`]: the inner body's, or a [`crate::Error`].
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Wraps the body of `res` in a [`DecompressBody`] when the last coding of
+/// its `Content-Encoding` is `zxc`, and removes that coding and the now
+/// wrong `Content-Length`. Other responses pass through unchanged.
+pub fn decompress_response<B>(res: ::http::Response<B>) -> ::http::Response<DecompressBody<B>> {
+    let (mut parts, body) = res.into_parts();
+    let body = if strip_encoding(&mut parts.headers) {
+        parts.headers.remove(CONTENT_LENGTH);
+        DecompressBody::new(body)
+    } else {
+        DecompressBody::passthrough(body)
+    };
+    ::http::Response::from_parts(parts, body)
+}
+
+/// Removes a trailing `zxc` from the `Content-Encoding` header, and the
+/// header itself when nothing is left. Returns whether it did.
+fn strip_encoding(headers: &mut HeaderMap) -> bool {
+    let Some(rest) = headers
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .and_then(strip_coding)
+    else {
+        return false;
+    };
+    match HeaderValue::from_str(&rest) {
+        Ok(rest) if !rest.is_empty() => {
+            headers.insert(CONTENT_ENCODING, rest);
+        }
+        _ => {
+            headers.remove(CONTENT_ENCODING);
+        }
+    }
+    true
+}
+
+/// The codings of a `Content-Encoding` value left once its last one, `zxc`,
+/// is undone, or `None` when the last coding is not `zxc`.
+pub(crate) fn strip_coding(value: &str) -> Option<String> {
+    let mut codings: Vec<&str> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    codings
+        .pop()
+        .is_some_and(|c| c.eq_ignore_ascii_case(ENCODING))
+        .then(|| codings.join(", "))
+}
+
+/// A body decompressing the zxc frame of an inner body, or passing it
+/// through. See the [module docs](self).
+///
+/// `B` must be [`Unpin`] to be polled; pin other bodies with `Box::pin`.
+pub struct DecompressBody<B> {
+    inner: B,
+    decode: Option<Decode>,
+    done: bool,
+}
+
+impl<B> fmt::Debug for DecompressBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecompressBody")
+            .field("decompress", &self.decode.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B> DecompressBody<B> {
+    /// Decompresses `inner`, whatever the headers it came with said.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            decode: Some(Decode::new()),
+            done: false,
+        }
+    }
+
+    /// Passes `inner` through unchanged.
+    pub fn passthrough(inner: B) -> Self {
+        Self {
+            inner,
+            decode: None,
+            done: false,
+        }
+    }
+
+    /// Whether the body decompresses its inner body.
+    pub fn is_decompressing(&self) -> bool {
+        self.decode.is_some()
+    }
+
+    /// Returns a reference to the inner body.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner body. Reading from it
+    /// directly skips the decompression of what is read.
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the inner body.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> Body for DecompressBody<B>
+where
+    B: Body + Unpin,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<core::result::Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let Some(decode) = &mut this.decode else {
+            return Poll::Ready(match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => Some(Ok(frame.map_data(|mut d| d.copy_to_bytes(d.remaining())))),
+                Some(Err(e)) => Some(Err(e.into())),
+                None => None,
+            });
+        };
+        loop {
+            match decode.advance() {
+                Ok(Some(data)) => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                Ok(None) if decode.inner_done => {
+                    this.done = true;
+                    return Poll::Ready(decode.trailers.take().map(|t| Ok(Frame::trailers(t))));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+            }
+            match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => decode.push(data),
+                    Err(frame) => decode.trailers = frame.into_trailers().ok(),
+                },
+                Some(Err(e)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                None => decode.inner_done = true,
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self.decode {
+            None => self.done || self.inner.is_end_stream(),
+            Some(_) => self.done,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.decode {
+            None => self.inner.size_hint(),
+            Some(_) => SizeHint::default(),
+        }
+    }
+}
+
+/// Decoder state of a [`DecompressBody`], and of request payloads in
+/// `zxc::actix`.
+pub(crate) struct Decode {
+    /// Created on first use, so that [`DecompressBody::new`] cannot fail.
+    ds: Option<DStream>,
+    input: Scratch,
+    pos: usize,
+    out: Scratch,
+    /// Set by the caller once the compressed input has ended.
+    pub(crate) inner_done: bool,
+    trailers: Option<HeaderMap>,
+}
+
+// SAFETY: `DStream` is not `Sync` because its `&self` methods call into the
+// C stream. `Decode` has no `&self` methods and `DecompressBody` only
+// reaches it mutably, so no two threads can use the stream at once.
+unsafe impl Sync for Decode {}
+
+impl Decode {
+    pub(crate) fn new() -> Self {
+        Self {
+            ds: None,
+            input: Scratch::zeroed(0),
+            pos: 0,
+            out: Scratch::zeroed(0),
+            inner_done: false,
+            trailers: None,
+        }
+    }
+
+    /// Appends a chunk of the compressed body, dropping consumed input.
+    pub(crate) fn push(&mut self, mut data: impl Buf) {
+        let consumed = self.pos.min(self.input.len());
+        self.input.drain(..consumed);
+        self.pos = 0;
+        while data.has_remaining() {
+            let chunk = data.chunk();
+            self.input.extend_from_slice(chunk);
+            let n = chunk.len();
+            data.advance(n);
+        }
+    }
+
+    /// The next decompressed chunk, or `None` when more input is needed or,
+    /// once the inner body has ended, at the end of the frame.
+    pub(crate) fn advance(&mut self) -> crate::Result<Option<Bytes>> {
+        let ds = match &mut self.ds {
+            Some(ds) => ds,
+            None => self.ds.insert(DStream::new(None)?),
+        };
+        if self.out.is_empty() {
+            *self.out = Scratch::zeroed(ds.out_size()).into_vec();
+        }
+        loop {
+            let input = self.input.get(self.pos..).unwrap_or_default();
+            if ds.finished() {
+                return match input.is_empty() {
+                    true => Ok(None),
+                    false => Err(Error::CorruptData),
+                };
+            }
+            if input.is_empty() && !self.inner_done {
+                return Ok(None);
+            }
+            let p = ds.decompress(input, &mut self.out)?;
+            self.pos += p.consumed;
+            if p.produced > 0 {
+                let out = self.out.get(..p.produced).unwrap_or_default();
+                return Ok(Some(Bytes::copy_from_slice(out)));
+            }
+            if p.consumed == 0 && !ds.finished() {
+                return match self.inner_done {
+                    true => Err(Error::SrcTooSmall),
+                    false => Ok(None),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures::executor::block_on;
+    use futures::stream;
+    use http_body_util::{BodyExt, Full, StreamBody};
+
+    use super::*;
+    use crate::{Level, compress};
+
+    fn chunked(
+        data: &[u8],
+        size: usize,
+    ) -> StreamBody<
+        impl futures::Stream<Item = core::result::Result<Frame<Bytes>, Infallible>> + Unpin,
+    > {
+        let frames: Vec<_> = data
+            .chunks(size)
+            .map(|c| Ok(Frame::data(Bytes::copy_from_slice(c))))
+            .collect();
+        StreamBody::new(stream::iter(frames))
+    }
+
+    #[test]
+    fn test_decompress_body() {
+        let data = crate::fixtures::input().repeat(40);
+        let frame = compress(&data, Level::Default, None).unwrap();
+
+        // Any chunking of the compressed body, trailers kept.
+        for size in [1, 7, 4096, frame.len()] {
+            let mut frames: Vec<_> = frame
+                .chunks(size)
+                .map(|c| Ok::<_, Infallible>(Frame::data(Bytes::copy_from_slice(c))))
+                .collect();
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-checksum", HeaderValue::from_static("ok"));
+            frames.push(Ok(Frame::trailers(trailers.clone())));
+            let body = DecompressBody::new(StreamBody::new(stream::iter(frames)));
+            let collected = block_on(body.collect()).unwrap();
+            assert_eq!(collected.trailers(), Some(&trailers));
+            assert_eq!(collected.to_bytes(), data);
+        }
+
+        // Truncated and trailing input.
+        let body = DecompressBody::new(chunked(&frame[..frame.len() - 1], 1000));
+        assert!(block_on(body.collect()).is_err());
+        let mut long = frame.clone();
+        long.push(0);
+        let body = DecompressBody::new(chunked(&long, 1000));
+        assert!(block_on(body.collect()).is_err());
+        let body = DecompressBody::new(Full::new(Bytes::from_static(b"not zxc")));
+        assert!(block_on(body.collect()).is_err());
+    }
+
+    #[test]
+    fn test_decompress_response() {
+        let data = b"hello hello hello hello".repeat(50);
+        let frame = Bytes::from(compress(&data, Level::Default, None).unwrap());
+        for (encoding, rest) in [
+            ("zxc", None),
+            ("ZXC", None),
+            ("gzip, zxc", Some("gzip")),
+            ("br ,gzip,zxc ", Some("br, gzip")),
+        ] {
+            let res = ::http::Response::builder()
+                .header(CONTENT_ENCODING, encoding)
+                .header(CONTENT_LENGTH, frame.len())
+                .body(Full::new(frame.clone()))
+                .unwrap();
+            let res = decompress_response(res);
+            assert!(res.body().is_decompressing());
+            assert_eq!(
+                res.headers()
+                    .get(CONTENT_ENCODING)
+                    .map(|v| v.to_str().unwrap()),
+                rest
+            );
+            assert!(!res.headers().contains_key(CONTENT_LENGTH));
+            assert_eq!(
+                block_on(res.into_body().collect()).unwrap().to_bytes(),
+                data
+            );
+        }
+
+        // Other encodings, or none, pass through with their headers.
+        for encoding in [Some("gzip"), Some("zxc, gzip"), None] {
+            let mut res = ::http::Response::new(Full::new(frame.clone()));
+            if let Some(encoding) = encoding {
+                res.headers_mut()
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+            }
+            let res = decompress_response(res);
+            assert!(!res.body().is_decompressing());
+            assert_eq!(res.body().size_hint().exact(), Some(frame.len() as u64));
+            assert_eq!(
+                res.headers()
+                    .get(CONTENT_ENCODING)
+                    .map(|v| v.to_str().unwrap()),
+                encoding
+            );
+            assert_eq!(
+                block_on(res.into_body().collect()).unwrap().to_bytes(),
+                frame
+            );
+        }
+    }
+}
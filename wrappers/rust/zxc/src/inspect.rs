@@ -0,0 +1,378 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! What a frame says about itself, read from its headers without decoding.
+//!
+//! [`frame_has_checksum`] reads the file header flags. [`frame_level`]
+//! walks the block headers: the level is not stored in the format, but the
+//! reference encoder only uses some block types and section encodings at
+//! some levels, so the blocks narrow it down to a [`LevelRange`].
+
+use core::ops::RangeInclusive;
+
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_GHI, BLOCK_GLO, BLOCK_HEADER_SIZE, BLOCK_RAW, BLOCK_SEK,
+    BLOCK_SIZE_LOG2, ENC_HUFFMAN, FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM, FORMAT_VERSION, MAGIC_WORD,
+};
+use crate::hash::{hash8, hash16};
+use crate::{Error, Level, Result};
+
+/// Offsets of the literal and token encodings in a GLO/GHI header. Literal
+/// encodings 2 and 3 are Huffman, token encoding 2 is Huffman.
+const ENC_LIT: usize = 8;
+const ENC_TOKENS: usize = 9;
+
+/// The compression levels a frame may have been written at, as far as its
+/// blocks tell.
+///
+/// Fast levels write GHI blocks, the others GLO blocks; Huffman-coded
+/// literals start at [`Level::Density`] and Huffman-coded tokens at
+/// [`Level::Ultra`]. Blocks stored raw say nothing, so a frame of
+/// incompressible data gives the full range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelRange {
+    /// The lowest level that could have written the frame.
+    pub min: Level,
+    /// The highest level that could have written the frame.
+    pub max: Level,
+}
+
+impl LevelRange {
+    /// The level, if only one fits.
+    pub fn exact(&self) -> Option<Level> {
+        (self.min == self.max).then_some(self.min)
+    }
+
+    /// Whether `level` could have written the frame.
+    pub fn contains(&self, level: Level) -> bool {
+        (self.min as i32..=self.max as i32).contains(&(level as i32))
+    }
+}
+
+/// Returns whether the frame carries block checksums, that is whether
+/// `verify_checksum` can check anything on it.
+///
+/// Only the 16-byte file header is read.
+///
+/// # Errors
+///
+/// [`Error::SrcTooSmall`] if `frame` is shorter than a file header,
+/// [`Error::BadMagic`], [`Error::BadVersion`], [`Error::BadBlockSize`] or
+/// [`Error::BadHeader`] if the header is not a valid ZXC header.
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::{Level, compress, frame_has_checksum};
+///
+/// assert!(frame_has_checksum(&compress(b"data", Level::Default, Some(true))?)?);
+/// assert!(!frame_has_checksum(&compress(b"data", Level::Default, Some(false))?)?);
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn frame_has_checksum(frame: &[u8]) -> Result<bool> {
+    has_checksum(&mut { frame })
+}
+
+/// Returns the range of levels the frame may have been compressed at.
+///
+/// Block headers, and the first bytes of compressed blocks, are read up to
+/// the end-of-frame block; payloads are skipped, not decoded or verified.
+/// The walk stops early once a single level fits.
+///
+/// # Errors
+///
+/// Those of [`frame_has_checksum`], [`Error::SrcTooSmall`] if the frame
+/// ends before its end-of-frame block, [`Error::BadHeader`] or
+/// [`Error::BadBlockType`] for a damaged block header, and
+/// [`Error::InvalidData`] if the blocks fit no level of the reference
+/// encoder (the frame was written by another one).
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::{Level, compress, frame_level};
+///
+/// let text = b"the quick brown fox jumps over the lazy dog. ".repeat(2000);
+/// let range = frame_level(&compress(&text, Level::Fastest, None)?)?;
+/// assert!(range.contains(Level::Fastest));
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn frame_level(frame: &[u8]) -> Result<LevelRange> {
+    level(&mut { frame })
+}
+
+/// Returns whether the file's frame carries block checksums; see
+/// [`frame_has_checksum`].
+///
+/// # Errors
+///
+/// [`StreamError::Io`](crate::StreamError::Io) if the file cannot be
+/// opened or read, and [`StreamError::BufferError`](crate::StreamError::BufferError)
+/// with the errors of [`frame_has_checksum`].
+#[cfg(feature = "std")]
+pub fn file_has_checksum<P: AsRef<std::path::Path>>(path: P) -> crate::StreamResult<bool> {
+    has_checksum(&mut FileSource(std::fs::File::open(path)?))
+}
+
+/// Returns the range of levels the file's frame may have been compressed
+/// at; see [`frame_level`]. Payloads are seeked over, not read.
+///
+/// # Errors
+///
+/// [`StreamError::Io`](crate::StreamError::Io) if the file cannot be
+/// opened or read, and [`StreamError::BufferError`](crate::StreamError::BufferError)
+/// with the errors of [`frame_level`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let range = zxc::file_level("archive.zxc")?;
+/// match range.exact() {
+///     Some(level) => println!("level {}", level as i32),
+///     None => println!("levels {} to {}", range.min as i32, range.max as i32),
+/// }
+/// # Ok::<(), zxc::StreamError>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn file_level<P: AsRef<std::path::Path>>(path: P) -> crate::StreamResult<LevelRange> {
+    level(&mut FileSource(std::io::BufReader::new(
+        std::fs::File::open(path)?,
+    )))
+}
+
+/// Where a frame is read from: a slice or a seekable reader.
+pub(crate) trait Source {
+    type Error: From<Error>;
+
+    /// Fills `buf` from the current position, or fails with
+    /// [`Error::SrcTooSmall`] at the end of the input.
+    fn read(&mut self, buf: &mut [u8]) -> core::result::Result<(), Self::Error>;
+
+    /// Moves `n` bytes forward.
+    fn skip(&mut self, n: u64) -> core::result::Result<(), Self::Error>;
+}
+
+impl Source for &[u8] {
+    type Error = Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        let (head, rest) = self.split_at_checked(buf.len()).ok_or(Error::SrcTooSmall)?;
+        buf.copy_from_slice(head);
+        *self = rest;
+        Ok(())
+    }
+
+    fn skip(&mut self, n: u64) -> Result<()> {
+        let n = usize::try_from(n).map_err(|_| Error::SrcTooSmall)?;
+        *self = self.get(n..).ok_or(Error::SrcTooSmall)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct FileSource<R>(R);
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + std::io::Seek> Source for FileSource<R> {
+    type Error = crate::StreamError;
+
+    fn read(&mut self, buf: &mut [u8]) -> crate::StreamResult<()> {
+        self.0.read_exact(buf).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => Error::SrcTooSmall.into(),
+            _ => e.into(),
+        })
+    }
+
+    fn skip(&mut self, n: u64) -> crate::StreamResult<()> {
+        let n = i64::try_from(n).map_err(|_| Error::SrcTooSmall)?;
+        self.0.seek_relative(n)?;
+        Ok(())
+    }
+}
+
+/// Reads and checks the file header; returns its flags.
+pub(crate) fn header<S: Source>(src: &mut S) -> core::result::Result<u8, S::Error> {
+    let mut h = [0u8; FILE_HEADER_SIZE];
+    src.read(&mut h)?;
+    if u32::from_le_bytes([h[0], h[1], h[2], h[3]]) != MAGIC_WORD {
+        return Err(Error::BadMagic.into());
+    }
+    if h[4] != FORMAT_VERSION {
+        return Err(Error::BadVersion.into());
+    }
+    if !BLOCK_SIZE_LOG2.contains(&h[5]) {
+        return Err(Error::BadBlockSize.into());
+    }
+    let mut zeroed = h;
+    zeroed[14..].fill(0);
+    if u16::from_le_bytes([h[14], h[15]]) != hash16(&zeroed) {
+        return Err(Error::BadHeader.into());
+    }
+    Ok(h[6])
+}
+
+/// Checks the version of the frame `frame` against `versions`, the range
+/// the options accept. Input too short or without the magic word is left
+/// to the decoder to reject.
+pub(crate) fn check_version(frame: &[u8], versions: RangeInclusive<u8>) -> Result<()> {
+    let Some(&[m0, m1, m2, m3, version]) = frame.first_chunk::<5>() else {
+        return Ok(());
+    };
+    if u32::from_le_bytes([m0, m1, m2, m3]) != MAGIC_WORD {
+        return Ok(());
+    }
+    match versions.contains(&version) {
+        true => Ok(()),
+        false => Err(Error::BadVersion),
+    }
+}
+
+fn has_checksum<S: Source>(src: &mut S) -> core::result::Result<bool, S::Error> {
+    Ok(header(src)? & FLAG_HAS_CHECKSUM != 0)
+}
+
+fn level<S: Source>(src: &mut S) -> core::result::Result<LevelRange, S::Error> {
+    let checksum = has_checksum(src)?;
+    let (mut min, mut max) = (Level::Fastest as i32, Level::Ultra as i32);
+    while min < max {
+        let mut bh = [0u8; BLOCK_HEADER_SIZE];
+        src.read(&mut bh)?;
+        let mut zeroed = bh;
+        zeroed[7] = 0;
+        if bh[7] != hash8(&zeroed) {
+            return Err(Error::BadHeader.into());
+        }
+        let mut rest = u64::from(u32::from_le_bytes([bh[3], bh[4], bh[5], bh[6]]));
+        match bh[0] {
+            BLOCK_RAW => {}
+            BLOCK_GLO | BLOCK_GHI => {
+                let mut gh = [0u8; ENC_TOKENS + 1];
+                src.read(&mut gh)?;
+                rest = rest.checked_sub(gh.len() as u64).ok_or(Error::BadHeader)?;
+                if bh[0] == BLOCK_GHI {
+                    max = max.min(Level::Fast as i32);
+                } else {
+                    min = min.max(Level::Default as i32);
+                }
+                if gh[ENC_LIT] >= ENC_HUFFMAN {
+                    min = min.max(Level::Density as i32);
+                }
+                if bh[0] == BLOCK_GLO && gh[ENC_TOKENS] == ENC_HUFFMAN {
+                    min = min.max(Level::Ultra as i32);
+                }
+            }
+            BLOCK_EOF | BLOCK_SEK => break,
+            _ => return Err(Error::BadBlockType.into()),
+        }
+        if checksum {
+            rest += BLOCK_CHECKSUM_SIZE as u64;
+        }
+        src.skip(rest)?;
+    }
+    if min > max {
+        return Err(Error::InvalidData.into());
+    }
+    Ok(LevelRange {
+        min: Level::try_from(min)?,
+        max: Level::try_from(max)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompressOptions, compress, compress_with_options};
+
+    #[test]
+    fn test_frame_has_checksum() {
+        let data = crate::fixtures::input();
+        let on = compress(&data, Level::Default, Some(true)).unwrap();
+        let off = compress(&data, Level::Default, Some(false)).unwrap();
+        assert!(frame_has_checksum(&on).unwrap());
+        assert!(!frame_has_checksum(&off).unwrap());
+
+        assert!(matches!(
+            frame_has_checksum(&on[..10]),
+            Err(Error::SrcTooSmall)
+        ));
+        assert!(matches!(
+            frame_has_checksum(b"not a zxc frame, no"),
+            Err(Error::BadMagic)
+        ));
+        let mut damaged = on.clone();
+        damaged[7] ^= 1;
+        assert!(matches!(
+            frame_has_checksum(&damaged),
+            Err(Error::BadHeader)
+        ));
+    }
+
+    #[test]
+    fn test_frame_level() {
+        // Incompressible or empty: raw blocks only, any level.
+        let any = LevelRange {
+            min: Level::Fastest,
+            max: Level::Ultra,
+        };
+        let empty = compress(b"", Level::Ultra, None).unwrap();
+        assert_eq!(frame_level(&empty).unwrap(), any);
+        assert_eq!(any.exact(), None);
+        assert!(any.contains(Level::Balanced));
+
+        let data = crate::fixtures::input().repeat(4);
+        for &level in Level::all() {
+            for checksum in [false, true] {
+                let frame = compress(&data, level, Some(checksum)).unwrap();
+                let range = frame_level(&frame).unwrap();
+                assert!(range.contains(level), "{level:?}: {range:?}");
+                // The walk never reads past the end-of-frame block.
+                let seekable = CompressOptions::with_level(level).with_seekable();
+                let frame = compress_with_options(&data, &seekable).unwrap();
+                assert_eq!(frame_level(&frame).unwrap(), range);
+            }
+        }
+
+        #[cfg(not(feature = "mock-backend"))]
+        {
+            let range = |level| frame_level(&compress(&data, level, None).unwrap()).unwrap();
+            assert_eq!(range(Level::Fastest).max, Level::Fast);
+            assert_eq!(range(Level::Default).min, Level::Default);
+            assert_eq!(range(Level::Ultra).exact(), Some(Level::Ultra));
+        }
+
+        let frame = compress(&data, Level::Default, None).unwrap();
+        assert!(matches!(
+            frame_level(&frame[..FILE_HEADER_SIZE + 4]),
+            Err(Error::SrcTooSmall)
+        ));
+        let mut damaged = frame.clone();
+        damaged[FILE_HEADER_SIZE + 3] ^= 1;
+        assert!(matches!(frame_level(&damaged), Err(Error::BadHeader)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_file_level() {
+        let path = std::env::temp_dir().join(format!("zxc_inspect_{}.zxc", std::process::id()));
+        let data = crate::fixtures::input();
+        let frame = compress(&data, Level::Compact, Some(true)).unwrap();
+        std::fs::write(&path, &frame).unwrap();
+        assert!(file_has_checksum(&path).unwrap());
+        assert_eq!(file_level(&path).unwrap(), frame_level(&frame).unwrap());
+
+        std::fs::write(&path, &frame[..FILE_HEADER_SIZE + 4]).unwrap();
+        assert!(matches!(
+            file_level(&path),
+            Err(crate::StreamError::BufferError(Error::SrcTooSmall))
+        ));
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(
+            file_has_checksum(&path),
+            Err(crate::StreamError::Io(_))
+        ));
+    }
+}
@@ -42,9 +42,48 @@
 //!
 //! - **Checksum verification**: Optional, disabled by default for maximum performance
 //! - **Zero-copy decompression bound**: Query the output size before decompressing
+//!
+//! # Environment
+//!
+//! Operators can tune a deployed binary without rebuilding it:
+//!
+//! | Variable | Effect |
+//! |----------|--------|
+//! | `ZXC_LEVEL` | Level (1-7) used where the options ask for [`Level::Default`] |
+//! | `ZXC_NTHREADS` | Worker threads for the file API when none were requested |
+//! | `ZXC_NO_SIMD` | Set to anything but `0`: run the baseline code path |
+//!
+//! They are read once, on the first call into the library; invalid values
+//! are ignored (and logged with the `log` feature). Explicit settings win:
+//! a level other than `Default`, a thread count, or
+//! [`cpu::force_variant`]. Without `std` the environment is not consulted.
+//!
+//! # `no_std`
+//!
+//! Without the default `std` feature the crate is `no_std` and only needs
+//! `alloc`: the buffer, block, push-stream and dictionary APIs remain, and
+//! [`Error`] implements `core::error::Error`. The `std::io` adapters, the
+//! file API and `Seekable` require `std`. The C library still expects a
+//! libc providing `malloc`/`free` and the `mem*` functions.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
+// Nothing reachable from the public API may panic: a storage engine cannot
+// afford an unwind out of a decode call. See `proptests::no_panic`.
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing
+    )
+)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
 
 pub use zxc_sys::{
     ZXC_ERROR_BAD_BLOCK_SIZE,
@@ -85,6 +124,11 @@ pub use zxc_sys::{
 /// Decompression speed is similar across most levels; levels 6-7 sit a notch
 /// below the others because Huffman-coded literals (and, at level 7, tokens)
 /// add a per-block decode cost relative to RAW/RLE literals.
+///
+/// The numeric value of each level, [`Level::as_i32`], is the C library's
+/// `ZXC_LEVEL_*` constant of the same name, as read from its headers at
+/// build time; the build fails if they ever disagree. Levels round-trip
+/// through `i32` with `From` and `TryFrom`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(i32)]
 pub enum Level {
@@ -114,26 +158,98 @@ pub enum Level {
 }
 
 impl Level {
+    /// All compression levels, from fastest to densest.
+    pub const ALL: [Level; 7] = [
+        Level::Fastest,
+        Level::Fast,
+        Level::Default,
+        Level::Balanced,
+        Level::Compact,
+        Level::Density,
+        Level::Ultra,
+    ];
+
     /// Returns all available compression levels.
     pub fn all() -> &'static [Level] {
-        &[
-            Level::Fastest,
-            Level::Fast,
-            Level::Default,
-            Level::Balanced,
-            Level::Compact,
-            Level::Density,
-            Level::Ultra,
-        ]
+        &Self::ALL
+    }
+
+    /// Iterates over all compression levels, from fastest to densest.
+    ///
+    /// ```rust
+    /// let names: Vec<i32> = zxc::Level::iter().map(zxc::Level::as_i32).collect();
+    /// assert_eq!(names, [1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    pub fn iter() -> impl DoubleEndedIterator<Item = Level> + ExactSizeIterator + Clone {
+        Self::ALL.into_iter()
+    }
+
+    /// The level's number, as the C library and the `zxc` CLI know it.
+    pub const fn as_i32(self) -> i32 {
+        self as i32
     }
 }
 
+// The discriminants above are the C constants; nothing else keeps them so.
+const _: () = {
+    assert!(Level::Fastest as i32 == ZXC_LEVEL_FASTEST);
+    assert!(Level::Fast as i32 == ZXC_LEVEL_FAST);
+    assert!(Level::Default as i32 == ZXC_LEVEL_DEFAULT);
+    assert!(Level::Balanced as i32 == ZXC_LEVEL_BALANCED);
+    assert!(Level::Compact as i32 == ZXC_LEVEL_COMPACT);
+    assert!(Level::Density as i32 == ZXC_LEVEL_DENSITY);
+    assert!(Level::Ultra as i32 == ZXC_LEVEL_ULTRA);
+};
+
 impl From<Level> for i32 {
     fn from(level: Level) -> i32 {
         level as i32
     }
 }
 
+impl TryFrom<i32> for Level {
+    type Error = Error;
+
+    /// The level numbered `n`, or [`Error::BadLevel`] if there is none.
+    ///
+    /// ```rust
+    /// use zxc::{Error, Level};
+    ///
+    /// assert_eq!(Level::try_from(6)?, Level::Density);
+    /// assert!(matches!(Level::try_from(0), Err(Error::BadLevel)));
+    /// # Ok::<(), zxc::Error>(())
+    /// ```
+    fn try_from(n: i32) -> Result<Level> {
+        Level::iter()
+            .find(|l| l.as_i32() == n)
+            .ok_or(Error::BadLevel)
+    }
+}
+
+// =============================================================================
+// Format Limits
+// =============================================================================
+
+/// Smallest block size a frame can be written with, in bytes (4 KiB).
+pub const MIN_BLOCK_SIZE: usize = zxc_sys::ZXC_BLOCK_SIZE_MIN as usize;
+
+/// Largest block size a frame can be written with, in bytes (2 MiB). No
+/// block of any frame decodes to more.
+pub const MAX_BLOCK_SIZE: usize = zxc_sys::ZXC_BLOCK_SIZE_MAX as usize;
+
+/// Size of the file header every frame starts with, in bytes.
+pub const FRAME_HEADER_SIZE: usize = zxc_sys::ZXC_FILE_HEADER_SIZE as usize;
+
+/// Size of the footer every frame ends with, in bytes. A frame is at least
+/// `FRAME_HEADER_SIZE + FOOTER_SIZE` bytes long.
+pub const FOOTER_SIZE: usize = zxc_sys::ZXC_FILE_FOOTER_SIZE as usize;
+
+/// Largest input size the C library accepts: [`compress_bound`] returns 0
+/// above it. This is a guard of its size arithmetic rather than a limit of
+/// the format, at about 99.6% of `usize::MAX`; inputs that large are out of
+/// reach in practice, and just below it the bound itself overflows.
+pub const MAX_INPUT_SIZE: usize = zxc_sys::ZXC_INPUT_SIZE_MAX;
+
 // =============================================================================
 // Compression Options
 // =============================================================================
@@ -163,6 +279,17 @@ pub struct CompressOptions {
     /// [`dict_huf`]. Becomes part of the archive's dictionary binding: the
     /// decoder must be given the same (dict, table) pair.
     pub dict_huf: Option<Vec<u8>>,
+
+    /// Total input size the caller commits to (default: `None`).
+    ///
+    /// Read by the push streaming encoders ([`CStream`], [`Encoder`],
+    /// [`compress_stream`]), which otherwise learn the size only at the end.
+    /// An input smaller than a default block is written with the smallest
+    /// block size that holds it, which cuts the memory the encoder and
+    /// every decoder of the frame allocate. Feeding more bytes than
+    /// pledged, or finishing with fewer, fails with
+    /// [`Error::PledgeMismatch`]. The one-shot functions ignore it.
+    pub pledged_size: Option<u64>,
 }
 
 impl Default for CompressOptions {
@@ -173,6 +300,7 @@ impl Default for CompressOptions {
             seekable: false,
             dict: None,
             dict_huf: None,
+            pledged_size: None,
         }
     }
 }
@@ -198,6 +326,13 @@ impl CompressOptions {
         self
     }
 
+    /// Pledge the total input size to the streaming encoders; see
+    /// [`CompressOptions::pledged_size`].
+    pub fn with_pledged_size(mut self, size: u64) -> Self {
+        self.pledged_size = Some(size);
+        self
+    }
+
     /// Attach a pre-trained dictionary (raw content bytes).
     pub fn with_dict(mut self, dict: impl Into<Vec<u8>>) -> Self {
         self.dict = Some(dict.into());
@@ -239,6 +374,10 @@ pub struct DecompressOptions {
     /// Must match the table used at compression time (the archive's dict_id
     /// binds the (dict, table) pair).
     pub dict_huf: Option<Vec<u8>>,
+
+    /// See [`DecompressOptions::with_format_versions`].
+    min_format_version: u8,
+    max_format_version: u8,
 }
 
 impl Default for DecompressOptions {
@@ -247,6 +386,8 @@ impl Default for DecompressOptions {
             verify_checksum: true,
             dict: None,
             dict_huf: None,
+            min_format_version: FORMAT_VERSION,
+            max_format_version: FORMAT_VERSION,
         }
     }
 }
@@ -278,20 +419,118 @@ impl DecompressOptions {
         self.dict_huf = Some(dictionary.huf().to_vec());
         self
     }
+
+    /// Accept only frames of the format versions in `versions` (default:
+    /// [`FORMAT_VERSION`] alone).
+    ///
+    /// A frame whose header names a version outside `versions` fails with
+    /// [`Error::BadVersion`] before anything is decoded, whatever the
+    /// library could read: an archive pipeline pins the versions it has
+    /// validated, and a frame from a newer writer fails loudly. Push
+    /// streams check it as soon as the version byte comes in.
+    pub fn with_format_versions(mut self, versions: core::ops::RangeInclusive<u8>) -> Self {
+        self.min_format_version = *versions.start();
+        self.max_format_version = *versions.end();
+        self
+    }
+
+    /// The format versions these options accept.
+    pub fn format_versions(&self) -> core::ops::RangeInclusive<u8> {
+        self.min_format_version..=self.max_format_version
+    }
 }
 
 // =============================================================================
 // Submodules
 // =============================================================================
 
+#[cfg(feature = "actix")]
+pub mod actix;
+mod arena;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod batch;
+mod blocks;
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod compat;
+pub mod cpu;
 mod ctx;
+#[cfg(feature = "std")]
+pub mod debug;
+mod diag;
 mod dict;
+mod env;
 mod error;
+#[cfg(feature = "std")]
 mod file;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "std")]
+mod follow;
+mod format;
+mod hash;
+#[cfg(feature = "http")]
+pub mod http;
+mod inspect;
+mod locate;
+mod memory;
+pub mod message;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(not(feature = "std"))]
+mod metrics;
+#[cfg(feature = "mock-backend")]
+#[allow(clippy::indexing_slicing)]
+mod mock;
+#[cfg(feature = "object_store")]
+pub mod object_store;
 mod oneshot;
+#[cfg(feature = "std")]
+pub mod pack;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "std")]
+mod profile;
+#[cfg(test)]
+mod proptests;
 mod pstream;
+#[cfg(feature = "pure-rust-decode")]
+pub mod pure;
+mod recompress;
+pub mod report;
+#[cfg(feature = "std")]
+pub mod rocksdb;
+#[cfg(feature = "std")]
+mod rotate;
+#[cfg(feature = "std")]
+mod salvage;
+pub mod sansio;
+mod scratch;
+#[cfg(feature = "std")]
 pub mod seekable;
+mod selftest;
+#[cfg(any(feature = "serde", feature = "bincode", feature = "postcard"))]
+pub mod serde;
+#[cfg(feature = "spill")]
+pub mod spill;
+#[cfg(feature = "std")]
 mod stdio;
+#[cfg(feature = "tensor")]
+pub mod tensor;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+pub mod transcode;
+#[cfg(feature = "std")]
+mod verify;
+pub mod websocket;
+
+// Entry points the wrappers call: the C library, or its Rust stand-in.
+#[cfg(feature = "mock-backend")]
+use mock as ffi;
+#[cfg(not(feature = "mock-backend"))]
+use zxc_sys as ffi;
 
 pub use dict::{
     Dictionary, dict_get_id, dict_huf, dict_id, dict_load, dict_save, get_dict_id, train_dict,
@@ -299,18 +538,51 @@ pub use dict::{
 };
 pub use zxc_sys::{ZXC_DICT_SIZE_MAX, ZXC_HUF_TABLE_SIZE};
 
-pub use ctx::{Cctx, Dctx, compress_block_bound, decompress_block_bound};
+pub use arena::Arena;
+pub use batch::{Batch, compress_batch, decompress_batch};
+pub use blocks::{Blocks, decompress_blocks};
+pub use ctx::{Cctx, Dctx, Decompressor, compress_block_bound, decompress_block_bound};
 pub use error::{Error, Result};
+#[cfg(feature = "std")]
 pub use file::{
-    StreamCompressOptions, StreamDecompressOptions, StreamError, StreamResult, compress_file,
-    compress_file_with_options, decompress_file, decompress_file_with_options,
-    file_decompressed_size,
+    BlockCallback, BlockStats, FileStats, StreamCompressOptions, StreamDecompressOptions,
+    StreamError, StreamResult, StreamStats, ZxcFileReader, ZxcFileWriter, compress_file,
+    compress_file_with_options, compress_file_with_stats, compress_many, compress_to_writer,
+    decompress_file, decompress_file_with_options, decompress_file_with_stats,
+    decompress_from_reader, decompress_to_writer, decompressed_size_from, file_decompressed_size,
 };
+#[cfg(feature = "std")]
+pub use follow::{FollowOptions, FollowStats, follow};
+pub use format::FORMAT_VERSION;
+pub use inspect::{LevelRange, frame_has_checksum, frame_level};
+#[cfg(feature = "std")]
+pub use inspect::{file_has_checksum, file_level};
+pub use memory::{MemoryEstimate, MemoryOptions, memory_usage};
+#[cfg(feature = "std")]
+pub use memory::{memory_budget, memory_in_use, set_memory_budget};
 pub use oneshot::{
-    compress, compress_bound, compress_to, compress_with_options, decompress, decompress_to,
-    decompress_with_options, decompressed_size, default_level, max_level, min_level,
-    runtime_version, version, version_string,
+    compress, compress_bound, compress_from_iter, compress_to, compress_with_options, decompress,
+    decompress_into_raw, decompress_to, decompress_with_options, decompressed_size, default_level,
+    max_level, min_level, runtime_version, version, version_string,
 };
+#[cfg(feature = "std")]
+pub use profile::{BlockProfile, Profile, profile};
 pub use pstream::{CStream, CStreamProgress, DStream, DStreamProgress};
+#[cfg(feature = "std")]
+pub use recompress::recompress_stream;
+pub use recompress::{RecompressOptions, Recompressor};
+#[cfg(feature = "std")]
+pub use rotate::{RotateOptions, RotatingZxcWriter};
+#[cfg(feature = "std")]
+pub use salvage::{
+    DamagedBlock, Fill, SalvageOptions, SalvageReport, decompress_stream_salvage, recover_file,
+};
+#[cfg(feature = "std")]
 pub use seekable::{Seekable, seek_table_size, write_seek_table};
-pub use stdio::{Decoder, Encoder, detect_zxc};
+pub use selftest::{SelfTestReport, self_test};
+#[cfg(feature = "std")]
+pub use stdio::{
+    Decoder, Encoder, compress_stream, decompress_scatter, decompress_stream, detect_zxc,
+};
+#[cfg(feature = "std")]
+pub use verify::{VerifyReport, verify, verify_file};
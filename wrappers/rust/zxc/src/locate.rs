@@ -0,0 +1,159 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Pinpoints where a frame failed to decode.
+//!
+//! The C frame decoder only returns an error code. When it fails on the
+//! data itself, the frame is walked again block by block: truncation is
+//! spotted from the block headers, and each block is decoded on its own
+//! through the Block API until one fails. The result is wrapped in
+//! [`Error::Corrupted`], a checksum failure with the stored and computed
+//! values as [`Error::ChecksumMismatch`].
+
+use alloc::boxed::Box;
+
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_HEADER_SIZE, BLOCK_SIZE_LOG2, FILE_FOOTER_SIZE,
+    FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM, FLAG_HAS_DICTIONARY,
+};
+use crate::hash::checksum;
+use crate::scratch::Scratch;
+use crate::{Dctx, DecompressOptions, Error, decompress_block_bound};
+
+/// Attaches the failing block (or the trailer) to a decode error.
+///
+/// Errors about the file header, the dictionary or the caller's buffers
+/// already say where they come from and are returned as they are.
+pub(crate) fn locate(compressed: &[u8], options: &DecompressOptions, err: Error) -> Error {
+    if !matches!(
+        err,
+        Error::SrcTooSmall
+            | Error::BadChecksum
+            | Error::CorruptData
+            | Error::BadOffset
+            | Error::Overflow
+            | Error::BadBlockType
+            | Error::BadBlockSize
+            | Error::Unknown(_)
+    ) {
+        return err;
+    }
+    let (Some(header), Some(body)) = (
+        compressed.first_chunk::<FILE_HEADER_SIZE>(),
+        compressed
+            .len()
+            .checked_sub(FILE_FOOTER_SIZE)
+            .and_then(|end| compressed.get(..end)),
+    ) else {
+        return err;
+    };
+    let flags = header[6];
+    let has_checksum = flags & FLAG_HAS_CHECKSUM != 0;
+    let verify = options.verify_checksum && has_checksum;
+    // The Block API takes no dictionary: blocks of a dictionary frame are
+    // only checked for truncation.
+    let decode_blocks = flags & FLAG_HAS_DICTIONARY == 0;
+    let block_size = 1usize << header[5].min(*BLOCK_SIZE_LOG2.end());
+
+    let mut dctx = Dctx::new().ok().filter(|_| decode_blocks);
+    let blocks_checked = dctx.is_some();
+    let mut scratch = Scratch::zeroed(decompress_block_bound(block_size) as usize);
+    let block_opts = DecompressOptions {
+        verify_checksum: verify,
+        ..Default::default()
+    };
+
+    let mut global_hash = 0u32;
+    let mut pos = FILE_HEADER_SIZE;
+    let mut index = 0u32;
+    loop {
+        let corrupted = |source| Error::Corrupted {
+            block: Some(index),
+            offset: pos as u64,
+            source: Box::new(source),
+        };
+        let Some(header) = body
+            .get(pos..)
+            .and_then(<[u8]>::first_chunk::<BLOCK_HEADER_SIZE>)
+        else {
+            return corrupted(Error::SrcTooSmall);
+        };
+        if header[0] == BLOCK_EOF {
+            break;
+        }
+        let comp_size = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as usize;
+        let payload_end = pos
+            .saturating_add(BLOCK_HEADER_SIZE)
+            .saturating_add(comp_size);
+        let end = payload_end.saturating_add(if has_checksum { BLOCK_CHECKSUM_SIZE } else { 0 });
+        let (Some(framed), Some(payload)) = (body.get(pos..end), body.get(pos..payload_end)) else {
+            return corrupted(Error::SrcTooSmall);
+        };
+        // Checksums need no decoding, so they are checked in dictionary
+        // frames too.
+        if verify {
+            if let Some(mismatch) = checksum_mismatch(index, framed) {
+                return corrupted(mismatch);
+            }
+            global_hash = global_hash.rotate_left(1) ^ stored_checksum(framed);
+        }
+        let block = if verify { framed } else { payload };
+        if let Err(e) = dctx.as_mut().map_or(Ok(0), |d| {
+            d.decompress_block(block, &mut scratch, &block_opts)
+        }) {
+            return corrupted(e);
+        }
+        pos = end;
+        index += 1;
+    }
+
+    let trailer = |source| Error::Corrupted {
+        block: None,
+        offset: body.len() as u64,
+        source: Box::new(source),
+    };
+    let stored_hash = compressed
+        .last_chunk::<4>()
+        .map_or(0, |h| u32::from_le_bytes(*h));
+    if verify && matches!(err, Error::BadChecksum) && stored_hash != global_hash {
+        return trailer(Error::ChecksumMismatch {
+            block: None,
+            expected: u64::from(stored_hash),
+            actual: u64::from(global_hash),
+        });
+    }
+    if !blocks_checked {
+        return err;
+    }
+    // Every block is sound: the declared size or the global hash is off.
+    trailer(err)
+}
+
+/// The checksum stored after a block, read as part of `framed` (block
+/// header, payload, checksum).
+fn stored_checksum(framed: &[u8]) -> u32 {
+    framed
+        .last_chunk::<BLOCK_CHECKSUM_SIZE>()
+        .map_or(0, |c| u32::from_le_bytes(*c))
+}
+
+/// [`Error::ChecksumMismatch`] for block `index` when the checksum stored
+/// at the end of `framed` (block header, payload, checksum) disagrees with
+/// its payload.
+pub(crate) fn checksum_mismatch(index: u32, framed: &[u8]) -> Option<Error> {
+    let payload = framed
+        .len()
+        .checked_sub(BLOCK_CHECKSUM_SIZE)
+        .and_then(|end| framed.get(BLOCK_HEADER_SIZE..end))?;
+    let expected = stored_checksum(framed);
+    let actual = checksum(payload);
+    (expected != actual).then_some(Error::ChecksumMismatch {
+        block: Some(index),
+        expected: u64::from(expected),
+        actual: u64::from(actual),
+    })
+}
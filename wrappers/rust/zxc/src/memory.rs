@@ -0,0 +1,370 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Estimates of the working memory of compression and decompression.
+//!
+//! The working memory of ZXC depends on the settings, not on the size of
+//! the data: each thread holds a context sized for one block, and each
+//! block in flight an input and an output buffer. [`memory_usage`] adds
+//! these up, for sizing containers ahead of time. The data passed in and
+//! the output of the one-shot functions are the caller's and not counted.
+//!
+//! With `std`, [`set_memory_budget`] caps the memory of the operations
+//! running at once across the process: each takes its share before it
+//! starts, and waits while the budget is spent.
+
+use crate::{
+    Level, MAX_BLOCK_SIZE, MIN_BLOCK_SIZE, compress_block_bound, decompress_block_bound, ffi,
+};
+
+/// The settings a [`memory_usage`] estimate is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryOptions {
+    /// Compression level (default: `Level::Default`)
+    pub level: Level,
+    /// Threads compressing or decompressing at once, each with a context of
+    /// its own (default: 1)
+    pub threads: usize,
+    /// Block size: a power of two from 4 KiB to 2 MiB, or 0 for the default
+    /// of 512 KiB (default: 0). Other sizes are rounded up to the next one
+    /// a frame can have.
+    pub block_size: usize,
+    /// Blocks in flight per thread, each with an input and an output buffer
+    /// (default: 1)
+    pub pipeline_depth: usize,
+}
+
+impl Default for MemoryOptions {
+    fn default() -> Self {
+        Self {
+            level: Level::Default,
+            threads: 1,
+            block_size: 0,
+            pipeline_depth: 1,
+        }
+    }
+}
+
+impl MemoryOptions {
+    /// Create options with the specified compression level.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            level,
+            ..Default::default()
+        }
+    }
+
+    /// The multi-threaded file API with `threads` threads, as in
+    /// [`compress_file`](crate::compress_file): one of them reads and
+    /// writes, the others work with four blocks in flight each.
+    pub fn file(level: Level, threads: usize) -> Self {
+        Self {
+            level,
+            threads: threads.saturating_sub(1).max(1),
+            pipeline_depth: 4,
+            ..Default::default()
+        }
+    }
+}
+
+/// Expected peak working memory, in bytes, from [`memory_usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryEstimate {
+    /// For compressing, contexts and buffers.
+    pub compress: u64,
+    /// For decompressing, contexts and buffers.
+    pub decompress: u64,
+    /// Of which the contexts, for compressing.
+    pub compress_contexts: u64,
+    /// Of which the contexts, for decompressing.
+    pub decompress_contexts: u64,
+}
+
+/// Estimates the working memory of compressing and decompressing with
+/// `opts`.
+///
+/// At [`Level::Density`] and above, the compression context includes the
+/// scratch of the optimal parser; the decompression figure covers frames
+/// of every level.
+///
+/// ```rust
+/// use zxc::{Level, MemoryOptions};
+///
+/// let one = zxc::memory_usage(&MemoryOptions::default());
+/// let eight = zxc::memory_usage(&MemoryOptions::file(Level::Default, 9));
+/// assert_eq!(eight.compress_contexts, 8 * one.compress_contexts);
+/// assert!(eight.decompress > 8 * one.decompress);
+/// ```
+pub fn memory_usage(opts: &MemoryOptions) -> MemoryEstimate {
+    let block_size = match opts.block_size {
+        0 => 512 << 10,
+        n => n.clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE).next_power_of_two(),
+    };
+    let threads = opts.threads.max(1) as u64;
+    let blocks = threads.saturating_mul(opts.pipeline_depth.max(1) as u64);
+    let cctx = unsafe { ffi::zxc_estimate_cctx_size(block_size, opts.level.as_i32()) };
+    let dctx = unsafe { ffi::zxc_static_dctx_workspace_size(block_size) } as u64;
+    let buffers = blocks.saturating_mul(
+        compress_block_bound(block_size).saturating_add(decompress_block_bound(block_size)),
+    );
+    let compress_contexts = threads.saturating_mul(cctx);
+    let decompress_contexts = threads.saturating_mul(dctx);
+    MemoryEstimate {
+        compress: compress_contexts.saturating_add(buffers),
+        decompress: decompress_contexts.saturating_add(buffers),
+        compress_contexts,
+        decompress_contexts,
+    }
+}
+
+#[cfg(feature = "std")]
+mod imp {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+    use super::{MemoryOptions, memory_usage};
+    use crate::Level;
+
+    /// No budget.
+    const UNLIMITED: u64 = u64::MAX;
+
+    pub(super) struct Budget {
+        /// Read without the lock, so that operations pay nothing while no
+        /// budget is set.
+        limit: AtomicU64,
+        in_use: Mutex<u64>,
+        freed: Condvar,
+    }
+
+    static BUDGET: Budget = Budget::new();
+
+    impl Budget {
+        pub(super) const fn new() -> Self {
+            Self {
+                limit: AtomicU64::new(UNLIMITED),
+                in_use: Mutex::new(0),
+                freed: Condvar::new(),
+            }
+        }
+
+        pub(super) fn set(&self, limit: Option<u64>) {
+            self.limit
+                .store(limit.unwrap_or(UNLIMITED), Ordering::Relaxed);
+            // Taking the lock orders the store with the waiters' checks.
+            drop(self.lock());
+            self.freed.notify_all();
+        }
+
+        fn limit(&self) -> Option<u64> {
+            Some(self.limit.load(Ordering::Relaxed)).filter(|&l| l != UNLIMITED)
+        }
+
+        fn lock(&self) -> MutexGuard<'_, u64> {
+            self.in_use.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        /// Waits until `bytes` fit in what is left of the budget, or until
+        /// nothing else holds any when they are more than the whole.
+        pub(super) fn reserve(&'static self, bytes: impl FnOnce() -> u64) -> Reservation {
+            if self.limit().is_none() {
+                return Reservation(None);
+            }
+            let bytes = bytes();
+            let mut in_use = self.lock();
+            // The limit is read again after each wait: lifting or raising
+            // it wakes the waiters.
+            loop {
+                match self.limit() {
+                    Some(limit) if *in_use > 0 && in_use.saturating_add(bytes) > limit => {
+                        in_use = self
+                            .freed
+                            .wait(in_use)
+                            .unwrap_or_else(PoisonError::into_inner);
+                    }
+                    _ => break,
+                }
+            }
+            *in_use += bytes;
+            Reservation(Some((self, bytes)))
+        }
+
+        pub(super) fn in_use(&self) -> u64 {
+            *self.lock()
+        }
+    }
+
+    /// A share of the budget, given back on drop.
+    pub(crate) struct Reservation(Option<(&'static Budget, u64)>);
+
+    impl Drop for Reservation {
+        fn drop(&mut self) {
+            if let Some((budget, bytes)) = self.0 {
+                *budget.lock() -= bytes;
+                budget.freed.notify_all();
+            }
+        }
+    }
+
+    /// Caps the working memory of the operations running at once in the
+    /// process to `bytes`, or lifts the cap with `None` (the default).
+    ///
+    /// Before it starts, each one-shot compression or decompression and
+    /// each file operation takes its share of the budget, given back when
+    /// it returns: its [`memory_usage`] estimate, plus the output it
+    /// allocates. While the budget is spent, new operations wait for
+    /// running ones to finish instead of overcommitting, so a server
+    /// decoding many large frames at once stays within its memory limit.
+    /// An operation needing more than the whole budget runs alone.
+    ///
+    /// Push streams and contexts, which live as long as the caller keeps
+    /// them, are not counted. Operations already running when the budget
+    /// is set are not either.
+    ///
+    /// ```rust
+    /// zxc::set_memory_budget(Some(256 << 20));
+    /// assert_eq!(zxc::memory_budget(), Some(256 << 20));
+    /// # zxc::set_memory_budget(None);
+    /// ```
+    pub fn set_memory_budget(bytes: Option<u64>) {
+        BUDGET.set(bytes);
+    }
+
+    /// The budget set with [`set_memory_budget`], if any.
+    pub fn memory_budget() -> Option<u64> {
+        BUDGET.limit()
+    }
+
+    /// Bytes of the budget held by the operations running now.
+    pub fn memory_in_use() -> u64 {
+        BUDGET.in_use()
+    }
+
+    /// Takes the share of a one-shot call on `size` bytes of plain data,
+    /// `output` of which it allocates: compressing at `level`, or
+    /// decompressing with `None`.
+    pub(crate) fn reserve(level: Option<Level>, size: usize, output: usize) -> Reservation {
+        BUDGET.reserve(|| {
+            let est = memory_usage(&MemoryOptions {
+                level: level.unwrap_or_default(),
+                block_size: size.clamp(1, 512 << 10),
+                ..Default::default()
+            });
+            let contexts = match level {
+                Some(_) => est.compress_contexts,
+                None => est.decompress_contexts,
+            };
+            contexts.saturating_add(output as u64)
+        })
+    }
+
+    /// Takes the share of a file operation: [`memory_usage`] of `opts`,
+    /// compressing at `opts.level` or decompressing with `None`.
+    pub(crate) fn reserve_file(level: Option<Level>, opts: &MemoryOptions) -> Reservation {
+        BUDGET.reserve(|| {
+            let est = memory_usage(opts);
+            match level {
+                Some(_) => est.compress,
+                None => est.decompress,
+            }
+        })
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use crate::Level;
+
+    /// Without `std` there is no budget and nothing is held.
+    pub(crate) struct Reservation;
+
+    #[inline(always)]
+    pub(crate) fn reserve(_level: Option<Level>, _size: usize, _output: usize) -> Reservation {
+        Reservation
+    }
+}
+
+pub(crate) use imp::reserve;
+#[cfg(feature = "std")]
+pub(crate) use imp::{Reservation, reserve_file};
+#[cfg(feature = "std")]
+pub use imp::{memory_budget, memory_in_use, set_memory_budget};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_usage() {
+        let base = memory_usage(&MemoryOptions::default());
+        assert!(base.compress > base.compress_contexts);
+        assert!(base.decompress > base.decompress_contexts);
+        assert!(base.decompress_contexts > 0);
+
+        // Rounded up to a valid block size.
+        let small = |block_size| {
+            memory_usage(&MemoryOptions {
+                block_size,
+                ..Default::default()
+            })
+        };
+        assert_eq!(small(5000), small(8192));
+        assert_eq!(small(1), small(MIN_BLOCK_SIZE));
+        assert_eq!(small(usize::MAX), small(MAX_BLOCK_SIZE));
+        assert!(small(MIN_BLOCK_SIZE).decompress < base.decompress);
+
+        let deep = memory_usage(&MemoryOptions {
+            threads: 3,
+            pipeline_depth: 2,
+            ..Default::default()
+        });
+        assert_eq!(deep.compress_contexts, 3 * base.compress_contexts);
+        assert_eq!(
+            deep.compress - deep.compress_contexts,
+            6 * (base.compress - base.compress_contexts)
+        );
+
+        #[cfg(not(feature = "mock-backend"))]
+        assert!(
+            memory_usage(&MemoryOptions::with_level(Level::Ultra)).compress_contexts
+                > base.compress_contexts
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_budget_queues() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::thread;
+        use std::time::Duration;
+
+        // A budget of its own: the global one would hold up other tests.
+        static BUDGET: imp::Budget = imp::Budget::new();
+        static PEAK: AtomicU64 = AtomicU64::new(0);
+
+        // Nothing is estimated nor held without a limit.
+        drop(BUDGET.reserve(|| unreachable!()));
+        assert_eq!(BUDGET.in_use(), 0);
+        BUDGET.set(Some(100));
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let _held = BUDGET.reserve(|| 40);
+                    PEAK.fetch_max(BUDGET.in_use(), Ordering::Relaxed);
+                    thread::sleep(Duration::from_millis(5));
+                });
+            }
+        });
+        assert_eq!(PEAK.load(Ordering::Relaxed), 80);
+        assert_eq!(BUDGET.in_use(), 0);
+
+        // More than the whole budget runs alone.
+        let big = BUDGET.reserve(|| 500);
+        assert_eq!(BUDGET.in_use(), 500);
+        drop(big);
+        assert_eq!(BUDGET.in_use(), 0);
+    }
+}
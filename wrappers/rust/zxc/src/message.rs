@@ -0,0 +1,726 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Compressed messages for datagram and record-oriented transports.
+//!
+//! A [`Frame`] turns one message into one self-delimiting record: a short
+//! header and a single zxc block, without the file header, EOF block and
+//! footer of a full frame. Records can be sent as datagrams, or written
+//! back to back on a stream and split with [`frame_len`].
+//!
+//! ```rust
+//! use zxc::message::{Frame, frame_len};
+//!
+//! let mut codec = Frame::new();
+//! let mut wire = Vec::new();
+//! for msg in [&b"{\"temp\":21.5,\"unit\":\"C\"}"[..], b"{\"temp\":21.6,\"unit\":\"C\"}"] {
+//!     codec.encode_into(msg, &mut wire)?;
+//! }
+//!
+//! let first = frame_len(&wire).unwrap();
+//! assert_eq!(codec.decode(&wire[..first])?, b"{\"temp\":21.5,\"unit\":\"C\"}");
+//! assert_eq!(codec.decode(&wire[first..])?, b"{\"temp\":21.6,\"unit\":\"C\"}");
+//! # Ok::<(), zxc::Error>(())
+//! ```
+//!
+//! # Linked messages
+//!
+//! Small messages compressed one by one leave little for the matcher to
+//! work with. An [`Encoder`] and [`Decoder`] pair instead keeps the last
+//! [`HISTORY_SIZE`] bytes of the messages sent so far, and compresses each
+//! message with them as its dictionary, as zlib does in stream mode. The
+//! gain starts at messages of about a hundred bytes; below that, the block
+//! overhead outweighs what the history saves.
+//!
+//! Linked records are numbered from the last reset point. A decoder that
+//! misses one fails with [`Error::DictMismatch`] until the next reset point,
+//! which decodes on its own: call [`Encoder::reset`] when the peer reports
+//! a loss, or reset on a schedule with [`Encoder::with_reset_interval`] on
+//! transports without a way back.
+//!
+//! ```rust
+//! use zxc::message::{Decoder, Encoder};
+//!
+//! let mut tx = Encoder::new();
+//! let mut rx = Decoder::new();
+//! for i in 0..100 {
+//!     let msg = format!("{{\"sensor\":\"boiler-3\",\"seq\":{i},\"temp\":{}}}", 60 + i % 7);
+//!     let record = tx.encode(msg.as_bytes())?;
+//!     assert_eq!(rx.decode(&record)?, msg.as_bytes());
+//! }
+//! # Ok::<(), zxc::Error>(())
+//! ```
+//!
+//! # Wire layout
+//!
+//! All integers are little-endian. The layout is stable: a decoder accepts
+//! every record with the version below.
+//!
+//! ```text
+//! 0x00  1  Version (high 4 bits, 1) and flags (low 4 bits):
+//!          bit 0 set when the block carries a checksum,
+//!          bit 1 set for a linked record
+//! 0x01  4  Message length in bytes
+//! 0x05  4  Block length N in bytes (0 for an empty message)
+//! 0x09  4  Linked records only: sequence number since the last reset
+//!          point, 0 for the reset point itself
+//!  ...  N  The message as one zxc block (Block API), its checksum included;
+//!          linked records use the history as the block's dictionary
+//! ```
+//!
+//! Messages are limited to the Block API's 2 MiB; larger payloads belong in
+//! a frame ([`compress`](crate::compress)) or a stream.
+
+use alloc::vec::Vec;
+
+use crate::{
+    Cctx, CompressOptions, Dctx, DecompressOptions, Error, Level, MAX_BLOCK_SIZE, Result,
+    ZXC_DICT_SIZE_MAX, compress_block_bound,
+};
+
+/// Size of the header of a [`Frame`] record.
+pub const HEADER_SIZE: usize = 9;
+
+/// Size of the header of a linked record.
+pub const LINKED_HEADER_SIZE: usize = HEADER_SIZE + 4;
+
+/// Bytes of past messages an [`Encoder`] and [`Decoder`] keep as history:
+/// the largest dictionary the format allows.
+pub const HISTORY_SIZE: usize = ZXC_DICT_SIZE_MAX;
+
+const VERSION: u8 = 1;
+const FLAG_CHECKSUM: u8 = 0x01;
+const FLAG_LINKED: u8 = 0x02;
+
+/// Encodes and decodes message records. See the [module docs](self).
+///
+/// The codec keeps a compression and a decompression context, created on
+/// first use and reused across messages; each message is still compressed
+/// on its own.
+pub struct Frame {
+    opts: CompressOptions,
+    cctx: Option<Cctx>,
+    dctx: Option<Dctx>,
+}
+
+impl core::fmt::Debug for Frame {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Frame")
+            .field("level", &self.opts.level)
+            .field("checksum", &self.opts.checksum)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Frame {
+    /// A codec at the default level, with checksums.
+    pub fn new() -> Self {
+        Self::with_level(Level::Default)
+    }
+
+    /// A codec at `level`, with checksums.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            opts: CompressOptions::with_level(level),
+            cctx: None,
+            dctx: None,
+        }
+    }
+
+    /// Leaves the checksum out of the records this codec encodes, for
+    /// transports that already check integrity.
+    pub fn without_checksum(mut self) -> Self {
+        self.opts.checksum = false;
+        self
+    }
+
+    /// Encodes `msg` into a new record.
+    pub fn encode(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.encode_into(msg, &mut out)?;
+        Ok(out)
+    }
+
+    /// Appends the record of `msg` to `out`, and returns its size.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::TooLarge`] if `msg` is over 2 MiB, and any compression
+    /// error; `out` is left as it was.
+    pub fn encode_into(&mut self, msg: &[u8], out: &mut Vec<u8>) -> Result<usize> {
+        encode_record(&mut self.cctx, &self.opts, msg, out, None, &[])
+    }
+
+    /// Decodes one record, which must span all of `record`.
+    pub fn decode(&mut self, record: &[u8]) -> Result<Vec<u8>> {
+        let header = Header::parse(record)?;
+        let mut out = alloc_message(header.len)?;
+        self.decode_into(record, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decodes one record, which must span all of `record`, into the start
+    /// of `dst`, and returns the message length. See [`message_len`].
+    ///
+    /// Linked records decode here only at reset points.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::TooLarge`] if the header claims a message over 2 MiB,
+    /// [`Error::DstTooSmall`] if `dst` is shorter than the message,
+    /// [`Error::SrcTooSmall`] if `record` is short of its block,
+    /// [`Error::DictRequired`] for a linked record past a reset point, and
+    /// any decompression error, checksum mismatches included.
+    pub fn decode_into(&mut self, record: &[u8], dst: &mut [u8]) -> Result<usize> {
+        let header = Header::parse(record)?;
+        if header.seq.is_some_and(|seq| seq != 0) {
+            return Err(Error::DictRequired);
+        }
+        decode_record(&mut self.dctx, &header, record, dst, &[])
+    }
+}
+
+/// Compresses messages as linked records. See the
+/// [module docs](self#linked-messages).
+pub struct Encoder {
+    opts: CompressOptions,
+    cctx: Option<Cctx>,
+    history: Vec<u8>,
+    next: u32,
+    reset_interval: u32,
+}
+
+impl core::fmt::Debug for Encoder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Encoder")
+            .field("level", &self.opts.level)
+            .field("checksum", &self.opts.checksum)
+            .field("next", &self.next)
+            .field("reset_interval", &self.reset_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder {
+    /// An encoder at the default level, with checksums, resetting only on
+    /// request.
+    pub fn new() -> Self {
+        Self::with_level(Level::Default)
+    }
+
+    /// An encoder at `level`, with checksums, resetting only on request.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            opts: CompressOptions::with_level(level),
+            cctx: None,
+            history: Vec::new(),
+            next: 0,
+            reset_interval: u32::MAX,
+        }
+    }
+
+    /// Leaves the checksum out of the records this encoder produces.
+    pub fn without_checksum(mut self) -> Self {
+        self.opts.checksum = false;
+        self
+    }
+
+    /// Makes every `interval`-th record a reset point, bounding how many
+    /// messages a decoder loses after a gap. 0 and 1 reset on every record.
+    pub fn with_reset_interval(mut self, interval: u32) -> Self {
+        self.reset_interval = interval.max(1);
+        self
+    }
+
+    /// Drops the history: the next record is a reset point.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.next = 0;
+    }
+
+    /// The sequence number of the next record; 0 when it is a reset point.
+    pub fn sequence(&self) -> u32 {
+        self.next
+    }
+
+    /// Encodes `msg` into a new record.
+    pub fn encode(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.encode_into(msg, &mut out)?;
+        Ok(out)
+    }
+
+    /// Appends the record of `msg` to `out`, and returns its size.
+    ///
+    /// # Errors
+    ///
+    /// As [`Frame::encode_into`]. On error the history is left as it was,
+    /// so the next message follows on from the last one encoded.
+    pub fn encode_into(&mut self, msg: &[u8], out: &mut Vec<u8>) -> Result<usize> {
+        if self.next >= self.reset_interval {
+            self.reset();
+        }
+        let written = encode_record(
+            &mut self.cctx,
+            &self.opts,
+            msg,
+            out,
+            Some(self.next),
+            &self.history,
+        )?;
+        push_history(&mut self.history, msg);
+        self.next += 1;
+        Ok(written)
+    }
+}
+
+/// Decompresses linked records. See the [module docs](self#linked-messages).
+///
+/// [`Frame`] records decode here too, on their own, and leave the history
+/// alone.
+#[derive(Default)]
+pub struct Decoder {
+    dctx: Option<Dctx>,
+    history: Vec<u8>,
+    next: Option<u32>,
+}
+
+impl core::fmt::Debug for Decoder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Decoder")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Decoder {
+    /// A decoder waiting for the first reset point.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the decoder holds the history of the next record; `false`
+    /// before the first record and after a gap or a failed record, until
+    /// the next reset point.
+    pub fn is_synced(&self) -> bool {
+        self.next.is_some()
+    }
+
+    /// Decodes one record, which must span all of `record`.
+    pub fn decode(&mut self, record: &[u8]) -> Result<Vec<u8>> {
+        let header = Header::parse(record)?;
+        let mut out = alloc_message(header.len)?;
+        self.decode_into(record, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decodes one record, which must span all of `record`, into the start
+    /// of `dst`, and returns the message length. See [`message_len`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::DictMismatch`] for a linked record that does not follow the
+    /// last one decoded, and the errors of [`Frame::decode_into`]. After
+    /// any error on a linked record the decoder waits for a reset point.
+    pub fn decode_into(&mut self, record: &[u8], dst: &mut [u8]) -> Result<usize> {
+        let header = Header::parse(record)?;
+        let Some(seq) = header.seq else {
+            return decode_record(&mut self.dctx, &header, record, dst, &[]);
+        };
+        if seq == 0 {
+            self.history.clear();
+        } else if self.next != Some(seq) {
+            self.next = None;
+            return Err(Error::DictMismatch);
+        }
+        self.next = None;
+        let n = decode_record(&mut self.dctx, &header, record, dst, &self.history)?;
+        push_history(&mut self.history, dst.get(..n).unwrap_or_default());
+        self.next = seq.checked_add(1);
+        Ok(n)
+    }
+}
+
+/// The size of the record at the start of `buf`, header included, or
+/// `None` if `buf` is shorter than a header or does not start with one.
+///
+/// Use it to split records written back to back; the record may extend
+/// past the end of `buf`.
+pub fn frame_len(buf: &[u8]) -> Option<usize> {
+    record_len(buf).ok()
+}
+
+/// As [`frame_len`], with the reason a header is refused: [`Error::SrcTooSmall`]
+/// while `buf` is shorter than the header.
+pub(crate) fn record_len(buf: &[u8]) -> Result<usize> {
+    let header = Header::parse(buf)?;
+    Ok(header.size + header.block_len)
+}
+
+/// The length of the message in the record at the start of `buf`, to size
+/// the destination of [`Frame::decode_into`].
+pub fn message_len(buf: &[u8]) -> Option<usize> {
+    Header::parse(buf).ok().map(|h| h.len)
+}
+
+/// Appends the record of `msg` to `out`, compressed against `dict`; a
+/// linked record when `seq` is set.
+fn encode_record(
+    cctx: &mut Option<Cctx>,
+    opts: &CompressOptions,
+    msg: &[u8],
+    out: &mut Vec<u8>,
+    seq: Option<u32>,
+    dict: &[u8],
+) -> Result<usize> {
+    let len = u32::try_from(msg.len()).map_err(|_| Error::TooLarge(msg.len() as u64))?;
+    let bound = match compress_block_bound(msg.len()) {
+        0 if !msg.is_empty() => return Err(Error::TooLarge(msg.len() as u64)),
+        b => usize::try_from(b).map_err(|_| Error::TooLarge(b))?,
+    };
+    let start = out.len();
+    let mut flags = if opts.checksum { FLAG_CHECKSUM } else { 0 };
+    if seq.is_some() {
+        flags |= FLAG_LINKED;
+    }
+    out.push(VERSION << 4 | flags);
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    if let Some(seq) = seq {
+        out.extend_from_slice(&seq.to_le_bytes());
+    }
+    let header_size = out.len() - start;
+    if msg.is_empty() {
+        return Ok(header_size);
+    }
+
+    out.resize(start + header_size + bound, 0);
+    let dst = out.get_mut(start + header_size..).unwrap_or_default();
+    let written = match cctx {
+        Some(cctx) => cctx.compress_block_with_dict(msg, dst, opts, dict),
+        None => Cctx::new(None).and_then(|c| {
+            cctx.insert(c)
+                .compress_block_with_dict(msg, dst, opts, dict)
+        }),
+    };
+    let written = match written {
+        Ok(n) => n,
+        Err(e) => {
+            out.truncate(start);
+            return Err(e);
+        }
+    };
+    out.truncate(start + header_size + written);
+    if let Some(n) = out.get_mut(start + 5..start + HEADER_SIZE) {
+        n.copy_from_slice(&(written as u32).to_le_bytes());
+    }
+    Ok(header_size + written)
+}
+
+/// Decodes the block of `record` into `dst` with `dict` as its dictionary.
+fn decode_record(
+    dctx: &mut Option<Dctx>,
+    header: &Header,
+    record: &[u8],
+    dst: &mut [u8],
+    dict: &[u8],
+) -> Result<usize> {
+    let block = match record.get(header.size..) {
+        Some(block) if block.len() == header.block_len => block,
+        Some(block) if block.len() > header.block_len => return Err(Error::CorruptData),
+        _ => return Err(Error::SrcTooSmall),
+    };
+    let dst = dst
+        .get_mut(..header.len)
+        .ok_or(Error::DstTooSmall { needed: header.len })?;
+    if header.len == 0 {
+        return match header.block_len {
+            0 => Ok(0),
+            _ => Err(Error::CorruptData),
+        };
+    }
+    let opts = DecompressOptions {
+        verify_checksum: header.checksum,
+        ..Default::default()
+    };
+    let n = match dctx {
+        Some(dctx) => dctx.decompress_block_safe_with_dict(block, dst, &opts, dict)?,
+        None => dctx
+            .insert(Dctx::new()?)
+            .decompress_block_safe_with_dict(block, dst, &opts, dict)?,
+    };
+    if n != header.len {
+        return Err(Error::CorruptData);
+    }
+    Ok(n)
+}
+
+fn alloc_message(len: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    out.try_reserve_exact(len).map_err(|_| Error::Memory)?;
+    out.resize(len, 0);
+    Ok(out)
+}
+
+/// Appends `msg` to `history`, keeping its last [`HISTORY_SIZE`] bytes.
+fn push_history(history: &mut Vec<u8>, msg: &[u8]) {
+    let msg = msg
+        .get(msg.len().saturating_sub(HISTORY_SIZE)..)
+        .unwrap_or_default();
+    let excess = (history.len() + msg.len()).saturating_sub(HISTORY_SIZE);
+    history.drain(..excess);
+    history.extend_from_slice(msg);
+}
+
+struct Header {
+    checksum: bool,
+    len: usize,
+    block_len: usize,
+    /// Sequence number of a linked record.
+    seq: Option<u32>,
+    size: usize,
+}
+
+impl Header {
+    fn parse(buf: &[u8]) -> Result<Self> {
+        let header = buf.first_chunk::<HEADER_SIZE>().ok_or(Error::SrcTooSmall)?;
+        let [flags, l0, l1, l2, l3, b0, b1, b2, b3] = *header;
+        if flags >> 4 != VERSION {
+            return Err(Error::BadVersion);
+        }
+        if flags & !(FLAG_CHECKSUM | FLAG_LINKED) & 0x0F != 0 {
+            return Err(Error::BadHeader);
+        }
+        let size = |b: [u8; 4]| usize::try_from(u32::from_le_bytes(b)).map_err(|_| Error::Overflow);
+        let (len, block_len) = (size([l0, l1, l2, l3])?, size([b0, b1, b2, b3])?);
+        // Refuse records no encoder could have written before the caller
+        // allocates for them.
+        if len > MAX_BLOCK_SIZE {
+            return Err(Error::TooLarge(len as u64));
+        }
+        if block_len as u64 > compress_block_bound(len) {
+            return Err(Error::CorruptData);
+        }
+        let seq = match flags & FLAG_LINKED {
+            0 => None,
+            _ => {
+                let seq = buf
+                    .get(HEADER_SIZE..LINKED_HEADER_SIZE)
+                    .and_then(|s| s.first_chunk::<4>())
+                    .ok_or(Error::SrcTooSmall)?;
+                Some(u32::from_le_bytes(*seq))
+            }
+        };
+        Ok(Self {
+            checksum: flags & FLAG_CHECKSUM != 0,
+            len,
+            block_len,
+            seq,
+            size: if seq.is_some() {
+                LINKED_HEADER_SIZE
+            } else {
+                HEADER_SIZE
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_message_frames() {
+        let msg = crate::fixtures::input();
+        for mut codec in [
+            Frame::new(),
+            Frame::with_level(Level::Fastest).without_checksum(),
+        ] {
+            let record = codec.encode(&msg).unwrap();
+            assert_eq!(frame_len(&record), Some(record.len()));
+            assert_eq!(message_len(&record), Some(msg.len()));
+            #[cfg(not(feature = "mock-backend"))]
+            assert!(record.len() < msg.len());
+            assert_eq!(codec.decode(&record).unwrap(), msg);
+
+            // Records back to back, an empty one in between.
+            let mut wire = vec![0xAA];
+            codec.encode_into(b"first", &mut wire).unwrap();
+            assert_eq!(codec.encode_into(b"", &mut wire).unwrap(), HEADER_SIZE);
+            codec.encode_into(&msg, &mut wire).unwrap();
+            let mut rest = &wire[1..];
+            let mut got = vec![];
+            while let Some(n) = frame_len(rest) {
+                got.push(codec.decode(&rest[..n]).unwrap());
+                rest = &rest[n..];
+            }
+            assert!(rest.is_empty());
+            assert_eq!(got, [&b"first"[..], b"", &msg]);
+
+            // Truncated, trailing bytes, and small destinations.
+            assert!(matches!(
+                codec.decode(&record[..record.len() - 1]),
+                Err(Error::SrcTooSmall)
+            ));
+            let mut long = record.clone();
+            long.push(0);
+            assert!(codec.decode(&long).is_err());
+            let mut small = vec![0u8; msg.len() - 1];
+            assert!(matches!(
+                codec.decode_into(&record, &mut small),
+                Err(Error::DstTooSmall { needed }) if needed == msg.len()
+            ));
+        }
+
+        // Checksummed records catch damage to the payload.
+        let mut codec = Frame::new();
+        let mut record = codec.encode(&msg).unwrap();
+        let last = record.len() - 10;
+        record[last] ^= 0x40;
+        assert!(codec.decode(&record).is_err());
+
+        // Unknown versions and flags are rejected.
+        let mut record = codec.encode(b"x").unwrap();
+        record[0] = 0x21;
+        assert!(matches!(codec.decode(&record), Err(Error::BadVersion)));
+        record[0] = 0x15;
+        assert!(matches!(codec.decode(&record), Err(Error::BadHeader)));
+        assert_eq!(frame_len(&record[..4]), None);
+    }
+
+    #[test]
+    fn test_oversized_headers() {
+        let mut frame = Frame::new();
+        let mut rx = Decoder::new();
+        let record = frame.encode(b"hello hello hello").unwrap();
+
+        // A length past the Block API's limit fails before the message is
+        // allocated.
+        let mut huge = record.clone();
+        huge[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(frame.decode(&huge), Err(Error::TooLarge(n)) if n == u32::MAX as u64));
+        assert!(matches!(
+            rx.decode(&huge[..HEADER_SIZE]),
+            Err(Error::TooLarge(_))
+        ));
+        assert_eq!(frame_len(&huge), None);
+        assert_eq!(message_len(&huge), None);
+        let mut over = record.clone();
+        over[1..5].copy_from_slice(&(MAX_BLOCK_SIZE as u32 + 1).to_le_bytes());
+        assert!(matches!(frame.decode(&over), Err(Error::TooLarge(_))));
+
+        // So does a block longer than any encoder writes for its message.
+        let mut long_block = record.clone();
+        long_block[5..9].copy_from_slice(&(64u32 << 20).to_le_bytes());
+        assert!(matches!(frame.decode(&long_block), Err(Error::CorruptData)));
+        assert!(matches!(record_len(&long_block), Err(Error::CorruptData)));
+        assert!(matches!(record_len(&record[..4]), Err(Error::SrcTooSmall)));
+    }
+
+    #[test]
+    fn test_linked_messages() {
+        let packets: Vec<Vec<u8>> = (0..200)
+            .map(|i| {
+                format!(
+                    "{{\"device\":\"pump-{}\",\"status\":\"ok\",\"rpm\":{},\"seq\":{i},\
+                     \"site\":\"north-plant\",\"firmware\":\"2.14.7\",\"alarms\":[],\
+                     \"temperature_c\":{}.5,\"pressure_kpa\":{}}}",
+                    i % 3,
+                    1200 + i % 17,
+                    40 + i % 5,
+                    300 + i % 11
+                )
+                .into_bytes()
+            })
+            .collect();
+
+        let mut tx = Encoder::new();
+        let mut rx = Decoder::new();
+        assert!(!rx.is_synced());
+        let mut records = vec![];
+        for packet in &packets {
+            let record = tx.encode(packet).unwrap();
+            assert_eq!(frame_len(&record), Some(record.len()));
+            assert_eq!(rx.decode(&record).unwrap(), *packet);
+            records.push(record);
+        }
+        assert!(rx.is_synced());
+        assert_eq!(tx.sequence(), 200);
+
+        // The history pays off over compressing each packet alone.
+        #[cfg(not(feature = "mock-backend"))]
+        {
+            let mut alone = Frame::new();
+            let linked: usize = records.iter().map(Vec::len).sum();
+            let single: usize = packets.iter().map(|p| alone.encode(p).unwrap().len()).sum();
+            assert!(linked * 3 < single * 2, "{linked} vs {single}");
+        }
+
+        // Only the reset point decodes without the history.
+        let mut frame = Frame::new();
+        assert_eq!(frame.decode(&records[0]).unwrap(), packets[0]);
+        assert!(matches!(
+            frame.decode(&records[1]),
+            Err(Error::DictRequired)
+        ));
+
+        // A lost record desyncs the decoder until the next reset point.
+        let mut rx = Decoder::new();
+        rx.decode(&records[0]).unwrap();
+        assert!(matches!(rx.decode(&records[2]), Err(Error::DictMismatch)));
+        assert!(matches!(rx.decode(&records[3]), Err(Error::DictMismatch)));
+        assert!(!rx.is_synced());
+        tx.reset();
+        assert_eq!(tx.sequence(), 0);
+        for packet in &packets[..3] {
+            assert_eq!(rx.decode(&tx.encode(packet).unwrap()).unwrap(), *packet);
+        }
+
+        // Scheduled reset points, and plain frames in between.
+        let mut tx = Encoder::with_level(Level::Fastest)
+            .without_checksum()
+            .with_reset_interval(4);
+        let mut rx = Decoder::new();
+        for (i, packet) in packets.iter().take(10).enumerate() {
+            let record = tx.encode(packet).unwrap();
+            assert_eq!(record[HEADER_SIZE] as usize, i % 4);
+            match i {
+                // Lost, then decoded past the gap up to the reset point.
+                2 | 3 | 5 => {}
+                6 | 7 => assert!(matches!(rx.decode(&record), Err(Error::DictMismatch))),
+                _ => assert_eq!(rx.decode(&record).unwrap(), *packet),
+            }
+            assert_eq!(rx.decode(&frame.encode(packet).unwrap()).unwrap(), *packet);
+        }
+        assert!(rx.is_synced());
+
+        // Messages larger than the history, and empty ones.
+        let big = crate::fixtures::input().repeat(5);
+        let mut tx = Encoder::new();
+        let mut rx = Decoder::new();
+        for msg in [&big[..], b"", &big[..1000], &big[..]] {
+            assert_eq!(rx.decode(&tx.encode(msg).unwrap()).unwrap(), msg);
+        }
+        assert_eq!(tx.history, big[big.len() - HISTORY_SIZE..]);
+        assert_eq!(rx.history, tx.history);
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Throughput accounting.
+//!
+//! Install a [`Metrics`] sink once with [`set_metrics`] and every buffer,
+//! stream and file operation reports its byte counts and duration to it,
+//! ready to feed counters such as `zxc_bytes_compressed_total`:
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use zxc::metrics::{Metrics, Sample};
+//!
+//! struct Counters {
+//!     compressed_in: AtomicU64,
+//! }
+//!
+//! impl Metrics for Counters {
+//!     fn record(&self, sample: &Sample) {
+//!         if sample.operation.is_compress() {
+//!             self.compressed_in.fetch_add(sample.bytes_in, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//!
+//! static COUNTERS: Counters = Counters { compressed_in: AtomicU64::new(0) };
+//!
+//! zxc::metrics::set_metrics(&COUNTERS).unwrap();
+//! zxc::compress(b"counted", zxc::Level::Default, None).unwrap();
+//! assert_eq!(COUNTERS.compressed_in.load(Ordering::Relaxed), 7);
+//! ```
+//!
+//! Only operations that succeed are recorded. The Block API and the
+//! [`Encoder`](crate::Encoder) / [`Decoder`](crate::Decoder) adapters are
+//! left to the caller, who knows what a unit of work is for them; the
+//! [`compress_stream`](crate::compress_stream) family built on them is
+//! recorded. Without a sink, an operation costs one atomic load.
+
+#[cfg(feature = "std")]
+pub use imp::{Metrics, Sample, SetMetricsError, set_metrics};
+
+pub(crate) use imp::start;
+
+/// The kind of operation a [`Sample`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub enum Operation {
+    /// [`compress`](crate::compress) and the other buffer functions.
+    Compress,
+    /// [`decompress`](crate::decompress) and the other buffer functions.
+    Decompress,
+    /// [`compress_stream`](crate::compress_stream).
+    CompressStream,
+    /// [`decompress_stream`](crate::decompress_stream).
+    DecompressStream,
+    /// [`compress_file`](crate::compress_file) and its variants.
+    CompressFile,
+    /// [`decompress_file`](crate::decompress_file) and its variants.
+    DecompressFile,
+}
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+impl Operation {
+    /// Returns `true` for the compressing operations.
+    pub fn is_compress(self) -> bool {
+        matches!(
+            self,
+            Self::Compress | Self::CompressStream | Self::CompressFile
+        )
+    }
+
+    /// A short lowercase name, suitable as a metric label.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compress => "compress",
+            Self::Decompress => "decompress",
+            Self::CompressStream => "compress_stream",
+            Self::DecompressStream => "decompress_stream",
+            Self::CompressFile => "compress_file",
+            Self::DecompressFile => "decompress_file",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod imp {
+    use std::fmt;
+    use std::sync::OnceLock;
+    use std::time::{Duration, Instant};
+
+    use super::Operation;
+
+    static METRICS: OnceLock<&'static dyn Metrics> = OnceLock::new();
+
+    /// Receives one [`Sample`] per completed operation.
+    ///
+    /// Called on the thread that ran the operation, after it returned, so
+    /// the implementation must be cheap and thread-safe (atomics or a
+    /// metrics library's handles).
+    pub trait Metrics: Send + Sync {
+        /// Records one completed operation.
+        fn record(&self, sample: &Sample);
+    }
+
+    /// One completed operation.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub struct Sample {
+        /// What ran.
+        pub operation: Operation,
+        /// Bytes consumed: plain data when compressing, the frame when
+        /// decompressing.
+        pub bytes_in: u64,
+        /// Bytes produced.
+        pub bytes_out: u64,
+        /// Wall-clock time of the call, I/O included for streams and files.
+        pub elapsed: Duration,
+    }
+
+    /// Returned by [`set_metrics`] when a sink is already installed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SetMetricsError;
+
+    impl fmt::Display for SetMetricsError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a metrics sink is already installed")
+        }
+    }
+
+    impl std::error::Error for SetMetricsError {}
+
+    /// Installs the process-wide [`Metrics`] sink.
+    ///
+    /// Like `log::set_logger`, this can be done once; later calls fail with
+    /// [`SetMetricsError`] and leave the first sink in place.
+    pub fn set_metrics(metrics: &'static dyn Metrics) -> Result<(), SetMetricsError> {
+        METRICS.set(metrics).map_err(|_| SetMetricsError)
+    }
+
+    /// A started measurement; inert when no sink is installed.
+    pub(crate) struct Timer(Option<(&'static dyn Metrics, Instant)>);
+
+    pub(crate) fn start() -> Timer {
+        Timer(METRICS.get().map(|m| (*m, Instant::now())))
+    }
+
+    impl Timer {
+        pub(crate) fn finish(self, operation: Operation, bytes_in: u64, bytes_out: u64) {
+            if let Some((metrics, started)) = self.0 {
+                metrics.record(&Sample {
+                    operation,
+                    bytes_in,
+                    bytes_out,
+                    elapsed: started.elapsed(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use super::Operation;
+
+    /// Without `std` there is no clock and nothing is recorded.
+    pub(crate) struct Timer;
+
+    #[inline(always)]
+    pub(crate) fn start() -> Timer {
+        Timer
+    }
+
+    impl Timer {
+        #[inline(always)]
+        pub(crate) fn finish(self, _operation: Operation, _bytes_in: u64, _bytes_out: u64) {}
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{Level, compress, compress_stream, decompress, decompress_stream};
+
+    struct Recorder(Mutex<Vec<Sample>>);
+
+    impl Metrics for Recorder {
+        fn record(&self, sample: &Sample) {
+            self.0.lock().unwrap().push(*sample);
+        }
+    }
+
+    static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+
+    #[test]
+    fn test_operations_are_recorded() {
+        set_metrics(&RECORDER).unwrap();
+        assert_eq!(set_metrics(&RECORDER), Err(SetMetricsError));
+
+        // Other tests run alongside: pick the samples out by their odd size.
+        let data = b"metrics ".repeat(1237);
+        let frame = compress(&data, Level::Default, None).unwrap();
+        assert_eq!(decompress(&frame).unwrap(), data);
+        let mut streamed = Vec::new();
+        compress_stream(&data[..], &mut streamed, None).unwrap();
+        decompress_stream(&streamed[..], Vec::new(), None).unwrap();
+
+        let samples = RECORDER.0.lock().unwrap();
+        let find = |op| {
+            samples
+                .iter()
+                .find(|s| s.operation == op && (s.bytes_in == 9896 || s.bytes_out == 9896))
+                .copied()
+                .unwrap_or_else(|| panic!("no {op:?} sample in {samples:?}"))
+        };
+        let c = find(Operation::Compress);
+        assert_eq!((c.bytes_in, c.bytes_out), (9896, frame.len() as u64));
+        let d = find(Operation::Decompress);
+        assert_eq!((d.bytes_in, d.bytes_out), (frame.len() as u64, 9896));
+        let cs = find(Operation::CompressStream);
+        assert_eq!(cs.bytes_out, streamed.len() as u64);
+        let ds = find(Operation::DecompressStream);
+        assert_eq!(ds.bytes_in, streamed.len() as u64);
+        assert!(c.operation.is_compress() && !d.operation.is_compress());
+        assert_eq!(ds.operation.as_str(), "decompress_stream");
+    }
+}
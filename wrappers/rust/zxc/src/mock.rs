@@ -0,0 +1,1325 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Store-only stand-in for the C library (`mock-backend` feature).
+//!
+//! Every function here has the signature of the `zxc_sys` binding of the
+//! same name, and the rest of the crate reaches them through `crate::ffi`.
+//! Compression writes well-formed frames made only of RAW blocks (no match
+//! finding, no entropy coding), so the real library reads them back;
+//! decompression goes through [`crate::pure`], which also accepts frames the
+//! real library wrote. Nothing here calls into C, so crates depending on
+//! `zxc` can run their tests under Miri or on targets where the C sources do
+//! not build.
+//!
+//! Levels are accepted and ignored, dictionary training keeps the tail of the
+//! corpus with an empty shared table, and the file API is left out
+//! (`mock-backend` implies `no-stream`).
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_int, c_void};
+use core::ptr::{self, NonNull};
+
+use zxc_sys::{
+    ZXC_DICT_HEADER_SIZE, ZXC_DICT_SIZE_MAX, ZXC_ERROR_BAD_BLOCK_SIZE, ZXC_ERROR_BAD_BLOCK_TYPE,
+    ZXC_ERROR_BAD_CHECKSUM, ZXC_ERROR_BAD_HEADER, ZXC_ERROR_BAD_LEVEL, ZXC_ERROR_BAD_MAGIC,
+    ZXC_ERROR_BAD_OFFSET, ZXC_ERROR_BAD_VERSION, ZXC_ERROR_CORRUPT_DATA, ZXC_ERROR_DICT_MISMATCH,
+    ZXC_ERROR_DICT_REQUIRED, ZXC_ERROR_DICT_TOO_LARGE, ZXC_ERROR_DST_TOO_SMALL, ZXC_ERROR_IO,
+    ZXC_ERROR_MEMORY, ZXC_ERROR_NULL_INPUT, ZXC_ERROR_OVERFLOW, ZXC_ERROR_SRC_TOO_SMALL,
+    ZXC_LEVEL_DEFAULT, ZXC_LEVEL_FASTEST, ZXC_LEVEL_ULTRA, ZXC_OK, ZXC_VERSION_MAJOR,
+    ZXC_VERSION_MINOR, ZXC_VERSION_PATCH, zxc_cctx, zxc_compress_opts_t, zxc_cstream, zxc_dctx,
+    zxc_decompress_opts_t, zxc_dstream, zxc_inbuf_t, zxc_outbuf_t,
+};
+
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_HEADER_SIZE, BLOCK_RAW, BLOCK_SEK, BLOCK_SIZE_LOG2,
+    FILE_FOOTER_SIZE, FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM, FLAG_HAS_DICTIONARY, FORMAT_VERSION,
+    MAGIC_WORD,
+};
+use crate::hash::{checksum, hash8, hash16};
+use crate::pure::{BlockHeader, FileHeader, HUF_TABLE_SIZE, dict_id, read_u32, read_u64, take};
+use crate::{DecompressOptions, Error, Result};
+
+const BLOCK_SIZE_DEFAULT: usize = 512 * 1024;
+const BLOCK_SIZE_MIN: usize = 1 << *BLOCK_SIZE_LOG2.start();
+const BLOCK_SIZE_MAX: usize = 1 << *BLOCK_SIZE_LOG2.end();
+const BLOCK_FORMAT_OVERHEAD: u64 = 64;
+const DECOMPRESS_TAIL_PAD: u64 = 32 * 66;
+const SEEK_ENTRY_SIZE: usize = 4;
+const DICT_MAGIC: u32 = 0x9CB0_D1C7;
+const DICT_VERSION: u8 = 1;
+
+// =============================================================================
+// Plumbing
+// =============================================================================
+
+/// Maps an error back to the code the C library would have returned.
+fn code(err: Error) -> i64 {
+    i64::from(match err {
+        Error::Corrupted { source, .. } | Error::SelfTest { source, .. } => return code(*source),
+        Error::Memory => ZXC_ERROR_MEMORY,
+        Error::DstTooSmall { .. } => ZXC_ERROR_DST_TOO_SMALL,
+        Error::SrcTooSmall => ZXC_ERROR_SRC_TOO_SMALL,
+        Error::BadMagic => ZXC_ERROR_BAD_MAGIC,
+        Error::BadVersion => ZXC_ERROR_BAD_VERSION,
+        Error::BadHeader => ZXC_ERROR_BAD_HEADER,
+        Error::BadChecksum | Error::ChecksumMismatch { .. } => ZXC_ERROR_BAD_CHECKSUM,
+        Error::CorruptData | Error::InvalidData => ZXC_ERROR_CORRUPT_DATA,
+        Error::BadOffset => ZXC_ERROR_BAD_OFFSET,
+        Error::Overflow | Error::TooLarge(_) => ZXC_ERROR_OVERFLOW,
+        Error::PledgeMismatch { pledged, actual } if actual < pledged => ZXC_ERROR_SRC_TOO_SMALL,
+        Error::PledgeMismatch { .. } => ZXC_ERROR_OVERFLOW,
+        Error::Io => ZXC_ERROR_IO,
+        Error::NullInput | Error::Unsupported(_) | Error::InvalidOption(_) => ZXC_ERROR_NULL_INPUT,
+        Error::BadBlockType => ZXC_ERROR_BAD_BLOCK_TYPE,
+        Error::BadBlockSize => ZXC_ERROR_BAD_BLOCK_SIZE,
+        Error::DictRequired => ZXC_ERROR_DICT_REQUIRED,
+        Error::DictMismatch => ZXC_ERROR_DICT_MISMATCH,
+        Error::DictTooLarge => ZXC_ERROR_DICT_TOO_LARGE,
+        Error::BadLevel => ZXC_ERROR_BAD_LEVEL,
+        Error::Unknown(c) => c,
+    })
+}
+
+/// Borrows a caller buffer; null or empty yields an empty slice.
+///
+/// # Safety
+///
+/// A non-null `p` must be valid for `len` bytes for the returned lifetime.
+unsafe fn bytes<'a>(p: *const c_void, len: usize) -> &'a [u8] {
+    if p.is_null() || len == 0 {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(p as *const u8, len) }
+    }
+}
+
+/// Borrows an optional 128-byte shared Huffman table.
+///
+/// # Safety
+///
+/// A non-null `p` must be valid for [`HUF_TABLE_SIZE`] bytes.
+unsafe fn huf_table<'a>(p: *const c_void) -> Option<&'a [u8; HUF_TABLE_SIZE]> {
+    if p.is_null() {
+        None
+    } else {
+        Some(unsafe { &*(p as *const [u8; HUF_TABLE_SIZE]) })
+    }
+}
+
+/// Copies `data` into a caller buffer and returns its length, or the
+/// "destination too small" code.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `cap` bytes.
+unsafe fn emit(data: &[u8], dst: *mut c_void, cap: usize) -> i64 {
+    if data.len() > cap {
+        return i64::from(ZXC_ERROR_DST_TOO_SMALL);
+    }
+    if !data.is_empty() {
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), dst as *mut u8, data.len()) };
+    }
+    data.len() as i64
+}
+
+/// Copies a result's bytes into a caller buffer, or returns the error code.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `cap` bytes.
+unsafe fn emit_result(data: Result<Vec<u8>>, dst: *mut c_void, cap: usize) -> i64 {
+    match data {
+        Ok(data) => unsafe { emit(&data, dst, cap) },
+        Err(err) => code(err),
+    }
+}
+
+/// Turns caller-owned dictionary pointers into owned decoder options.
+///
+/// # Safety
+///
+/// As for [`bytes`] and [`huf_table`].
+unsafe fn decompress_options(
+    checksum_enabled: c_int,
+    dict: *const c_void,
+    dict_size: usize,
+    dict_huf: *const c_void,
+) -> DecompressOptions {
+    let dict = unsafe { bytes(dict, dict_size) };
+    DecompressOptions {
+        verify_checksum: checksum_enabled != 0,
+        dict: (!dict.is_empty()).then(|| dict.to_vec()),
+        dict_huf: unsafe { huf_table(dict_huf) }.map(|h| h.to_vec()),
+        ..Default::default()
+    }
+}
+
+// =============================================================================
+// Library info
+// =============================================================================
+
+/// `"MAJOR.MINOR.PATCH"`, NUL-terminated.
+static VERSION: [u8; 36] = version_cstr();
+
+const fn version_cstr() -> [u8; 36] {
+    let mut buf = [0u8; 36];
+    let parts = [ZXC_VERSION_MAJOR, ZXC_VERSION_MINOR, ZXC_VERSION_PATCH];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        if i > 0 {
+            buf[pos] = b'.';
+            pos += 1;
+        }
+        let mut div = 1;
+        while parts[i] / div >= 10 {
+            div *= 10;
+        }
+        while div > 0 {
+            buf[pos] = b'0' + (parts[i] / div % 10) as u8;
+            pos += 1;
+            div /= 10;
+        }
+        i += 1;
+    }
+    buf
+}
+
+pub(crate) unsafe fn zxc_min_level() -> c_int {
+    ZXC_LEVEL_FASTEST
+}
+
+pub(crate) unsafe fn zxc_max_level() -> c_int {
+    ZXC_LEVEL_ULTRA
+}
+
+pub(crate) unsafe fn zxc_default_level() -> c_int {
+    ZXC_LEVEL_DEFAULT
+}
+
+pub(crate) unsafe fn zxc_version_string() -> *const c_char {
+    VERSION.as_ptr() as *const c_char
+}
+
+/// The stored-block codec has no SIMD code.
+pub(crate) unsafe fn zxc_simd_variant() -> c_int {
+    zxc_sys::ZXC_SIMD_DEFAULT
+}
+
+/// Only the baseline exists, so only it (or detection) can be pinned.
+pub(crate) unsafe fn zxc_simd_force(_variant: c_int) -> c_int {
+    zxc_sys::ZXC_SIMD_DEFAULT
+}
+
+// =============================================================================
+// Frame writer
+// =============================================================================
+
+/// Validates a requested block size (0 = default) and returns it with its
+/// log2 code.
+fn block_size_code(requested: usize) -> Result<(usize, u8)> {
+    let size = if requested == 0 {
+        BLOCK_SIZE_DEFAULT
+    } else {
+        requested
+    };
+    if !size.is_power_of_two() || !(BLOCK_SIZE_MIN..=BLOCK_SIZE_MAX).contains(&size) {
+        return Err(Error::BadBlockSize);
+    }
+    Ok((size, size.trailing_zeros() as u8))
+}
+
+fn file_header(code: u8, checksum: bool, dict_id: Option<u32>) -> [u8; FILE_HEADER_SIZE] {
+    let mut h = [0u8; FILE_HEADER_SIZE];
+    h[..4].copy_from_slice(&MAGIC_WORD.to_le_bytes());
+    h[4] = FORMAT_VERSION;
+    h[5] = code;
+    if checksum {
+        h[6] |= FLAG_HAS_CHECKSUM;
+    }
+    if let Some(id) = dict_id {
+        h[6] |= FLAG_HAS_DICTIONARY;
+        h[7..11].copy_from_slice(&id.to_le_bytes());
+    }
+    let crc = hash16(&h);
+    h[14..].copy_from_slice(&crc.to_le_bytes());
+    h
+}
+
+fn block_header(kind: u8, comp_size: u32) -> [u8; BLOCK_HEADER_SIZE] {
+    let mut h = [0u8; BLOCK_HEADER_SIZE];
+    h[0] = kind;
+    h[3..7].copy_from_slice(&comp_size.to_le_bytes());
+    h[7] = hash8(&h);
+    h
+}
+
+/// Block stream state shared by the one-shot and push compressors.
+struct FrameWriter {
+    block_size: usize,
+    code: u8,
+    checksum: bool,
+    dict_id: Option<u32>,
+    global_hash: u32,
+    total: u64,
+    comp_sizes: Vec<u32>,
+}
+
+impl FrameWriter {
+    fn new(block_size: usize, checksum: bool, dict_id: Option<u32>) -> Result<Self> {
+        let (block_size, code) = block_size_code(block_size)?;
+        Ok(Self {
+            block_size,
+            code,
+            checksum,
+            dict_id,
+            global_hash: 0,
+            total: 0,
+            comp_sizes: Vec::new(),
+        })
+    }
+
+    fn header(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&file_header(self.code, self.checksum, self.dict_id));
+    }
+
+    /// Appends `data` (at most one block) as a RAW block.
+    fn block(&mut self, out: &mut Vec<u8>, data: &[u8]) {
+        let start = out.len();
+        out.extend_from_slice(&block_header(BLOCK_RAW, data.len() as u32));
+        out.extend_from_slice(data);
+        if self.checksum {
+            let sum = checksum(data);
+            out.extend_from_slice(&sum.to_le_bytes());
+            self.global_hash = self.global_hash.rotate_left(1) ^ sum;
+        }
+        self.total += data.len() as u64;
+        self.comp_sizes.push((out.len() - start) as u32);
+    }
+
+    /// Appends the EOF block, the optional seek table and the footer.
+    fn finish(&self, out: &mut Vec<u8>, seekable: bool) {
+        out.extend_from_slice(&block_header(BLOCK_EOF, 0));
+        if seekable {
+            write_sek(out, &self.comp_sizes);
+        }
+        out.extend_from_slice(&self.total.to_le_bytes());
+        out.extend_from_slice(&self.global_hash.to_le_bytes());
+    }
+}
+
+fn write_sek(out: &mut Vec<u8>, comp_sizes: &[u32]) {
+    out.extend_from_slice(&block_header(
+        BLOCK_SEK,
+        (comp_sizes.len() * SEEK_ENTRY_SIZE) as u32,
+    ));
+    for size in comp_sizes {
+        out.extend_from_slice(&size.to_le_bytes());
+    }
+}
+
+/// Compresses `src` into a complete frame.
+///
+/// # Safety
+///
+/// The dictionary pointers in `opts` must be valid as documented on
+/// [`zxc_compress_opts_t`].
+unsafe fn compress_frame(src: &[u8], opts: &zxc_compress_opts_t) -> Result<Vec<u8>> {
+    let dict = unsafe { bytes(opts.dict, opts.dict_size) };
+    if dict.len() > ZXC_DICT_SIZE_MAX {
+        return Err(Error::DictTooLarge);
+    }
+    let id = (!dict.is_empty()).then(|| dict_id(dict, unsafe { huf_table(opts.dict_huf) }));
+    let mut w = FrameWriter::new(opts.block_size, opts.checksum_enabled != 0, id)?;
+    let mut out = Vec::with_capacity(src.len() + FILE_HEADER_SIZE + FILE_FOOTER_SIZE);
+    w.header(&mut out);
+    for chunk in src.chunks(w.block_size) {
+        w.block(&mut out, chunk);
+    }
+    w.finish(&mut out, opts.seekable != 0);
+    Ok(out)
+}
+
+// =============================================================================
+// Buffer API
+// =============================================================================
+
+// Only the tests call it: crate::compress_bound is the same formula in Rust.
+#[cfg(test)]
+pub(crate) unsafe fn zxc_compress_bound(input_size: usize) -> u64 {
+    if input_size > zxc_sys::ZXC_INPUT_SIZE_MAX {
+        return 0;
+    }
+    let n = (input_size as u64).div_ceil(BLOCK_SIZE_MIN as u64).max(1);
+    FILE_HEADER_SIZE as u64
+        + n * (BLOCK_HEADER_SIZE as u64 + BLOCK_CHECKSUM_SIZE as u64 + BLOCK_FORMAT_OVERHEAD)
+        + input_size as u64
+        + 2 * BLOCK_HEADER_SIZE as u64
+        + n * SEEK_ENTRY_SIZE as u64
+        + FILE_FOOTER_SIZE as u64
+}
+
+pub(crate) unsafe fn zxc_compress(
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_compress_opts_t,
+) -> i64 {
+    if dst.is_null() || (src.is_null() && src_size > 0) {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    let defaults = zxc_compress_opts_t::default();
+    let opts = unsafe { opts.as_ref() }.unwrap_or(&defaults);
+    unsafe {
+        let frame = compress_frame(bytes(src, src_size), opts);
+        emit_result(frame, dst, dst_capacity)
+    }
+}
+
+pub(crate) unsafe fn zxc_decompress(
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_decompress_opts_t,
+) -> i64 {
+    if src.is_null() || (dst.is_null() && dst_capacity > 0) {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    let options = match unsafe { opts.as_ref() } {
+        Some(o) => unsafe {
+            decompress_options(o.checksum_enabled, o.dict, o.dict_size, o.dict_huf)
+        },
+        None => DecompressOptions::skip_checksum(),
+    };
+    unsafe {
+        let data = crate::pure::decompress_with_options(bytes(src, src_size), &options);
+        emit_result(data, dst, dst_capacity)
+    }
+}
+
+pub(crate) unsafe fn zxc_get_decompressed_size(src: *const c_void, src_size: usize) -> u64 {
+    let src = unsafe { bytes(src, src_size) };
+    if src.len() < FILE_HEADER_SIZE + FILE_FOOTER_SIZE {
+        return 0;
+    }
+    let Ok(header) = FileHeader::parse(src) else {
+        return 0;
+    };
+    let size = read_u64(src, src.len() - FILE_FOOTER_SIZE).unwrap_or(0);
+    // Same plausibility bound as the C library: no more blocks than block
+    // headers would fit in the input.
+    if size.div_ceil(header.block_size as u64) > (src.len() / BLOCK_HEADER_SIZE) as u64 {
+        return 0;
+    }
+    size
+}
+
+pub(crate) unsafe fn zxc_get_dict_id(src: *const c_void, src_size: usize) -> u32 {
+    let src = unsafe { bytes(src, src_size) };
+    FileHeader::parse(src)
+        .ok()
+        .and_then(|h| h.dict_id)
+        .unwrap_or(0)
+}
+
+// =============================================================================
+// Dictionaries
+// =============================================================================
+
+/// Concatenates the samples, keeping the last `capacity` bytes (the ones a
+/// real trainer would place closest to the block start).
+///
+/// # Safety
+///
+/// As documented on `zxc_sys::zxc_train_dict`.
+unsafe fn train_content(
+    samples: *const *const c_void,
+    sample_sizes: *const usize,
+    n_samples: usize,
+    capacity: usize,
+) -> Vec<u8> {
+    let mut corpus = Vec::new();
+    for i in 0..n_samples {
+        unsafe { corpus.extend_from_slice(bytes(*samples.add(i), *sample_sizes.add(i))) };
+    }
+    let keep = corpus.len().min(capacity).min(ZXC_DICT_SIZE_MAX);
+    corpus.split_off(corpus.len() - keep)
+}
+
+fn save_zxd(content: &[u8], huf: &[u8; HUF_TABLE_SIZE]) -> Result<Vec<u8>> {
+    if content.is_empty() {
+        return Err(Error::NullInput);
+    }
+    if content.len() > ZXC_DICT_SIZE_MAX {
+        return Err(Error::DictTooLarge);
+    }
+    let mut header = [0u8; ZXC_DICT_HEADER_SIZE];
+    header[..4].copy_from_slice(&DICT_MAGIC.to_le_bytes());
+    header[4] = DICT_VERSION;
+    header[6..8].copy_from_slice(&(content.len() as u16).to_le_bytes());
+    header[8..12].copy_from_slice(&dict_id(content, Some(huf)).to_le_bytes());
+    let crc = hash16(&header);
+    header[14..].copy_from_slice(&crc.to_le_bytes());
+
+    let mut out = Vec::with_capacity(ZXC_DICT_HEADER_SIZE + content.len() + HUF_TABLE_SIZE);
+    out.extend_from_slice(&header);
+    out.extend_from_slice(content);
+    out.extend_from_slice(huf);
+    Ok(out)
+}
+
+/// Parsed `.zxd` buffer: `(content, table, id)`, borrowing from `buf`.
+fn parse_zxd(buf: &[u8]) -> Result<(&[u8], &[u8; HUF_TABLE_SIZE], u32)> {
+    let raw = take(buf, 0, ZXC_DICT_HEADER_SIZE)?;
+    if read_u32(raw, 0)? != DICT_MAGIC {
+        return Err(Error::BadMagic);
+    }
+    if raw[4] != DICT_VERSION {
+        return Err(Error::BadVersion);
+    }
+    let size = usize::from(u16::from_le_bytes([raw[6], raw[7]]));
+    if size == 0 {
+        return Err(Error::CorruptData);
+    }
+    let mut zeroed = [0u8; ZXC_DICT_HEADER_SIZE];
+    zeroed[..12].copy_from_slice(&raw[..12]);
+    if u16::from_le_bytes([raw[14], raw[15]]) != hash16(&zeroed) {
+        return Err(Error::BadHeader);
+    }
+    let content = take(buf, ZXC_DICT_HEADER_SIZE, size)?;
+    let huf: &[u8; HUF_TABLE_SIZE] = take(buf, ZXC_DICT_HEADER_SIZE + size, HUF_TABLE_SIZE)?
+        .try_into()
+        .map_err(|_| Error::SrcTooSmall)?;
+    let id = dict_id(content, Some(huf));
+    if read_u32(raw, 8)? != id {
+        return Err(Error::BadChecksum);
+    }
+    Ok((content, huf, id))
+}
+
+pub(crate) unsafe fn zxc_train_dict(
+    samples: *const *const c_void,
+    sample_sizes: *const usize,
+    n_samples: usize,
+    dict_buf: *mut c_void,
+    dict_capacity: usize,
+) -> i64 {
+    if samples.is_null() || sample_sizes.is_null() || dict_buf.is_null() {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    unsafe {
+        let content = train_content(samples, sample_sizes, n_samples, dict_capacity);
+        emit(&content, dict_buf, dict_capacity)
+    }
+}
+
+pub(crate) unsafe fn zxc_dict_id(
+    dict: *const c_void,
+    dict_size: usize,
+    huf_lengths: *const c_void,
+) -> u32 {
+    let content = unsafe { bytes(dict, dict_size) };
+    if content.is_empty() {
+        return 0;
+    }
+    dict_id(content, unsafe { huf_table(huf_lengths) })
+}
+
+pub(crate) unsafe fn zxc_dict_get_id(buf: *const c_void, buf_size: usize) -> u32 {
+    parse_zxd(unsafe { bytes(buf, buf_size) }).map_or(0, |(_, _, id)| id)
+}
+
+pub(crate) unsafe fn zxc_dict_save_bound(content_size: usize) -> usize {
+    ZXC_DICT_HEADER_SIZE + content_size + HUF_TABLE_SIZE
+}
+
+/// The mock never builds a shared table: an all-zero table means "none".
+pub(crate) unsafe fn zxc_train_dict_huf(
+    samples: *const *const c_void,
+    sample_sizes: *const usize,
+    n_samples: usize,
+    dict: *const c_void,
+    dict_size: usize,
+    huf_lengths_out: *mut u8,
+) -> c_int {
+    let _ = (samples, sample_sizes, n_samples);
+    if dict.is_null() || dict_size == 0 || huf_lengths_out.is_null() {
+        return ZXC_ERROR_NULL_INPUT;
+    }
+    unsafe { ptr::write_bytes(huf_lengths_out, 0, HUF_TABLE_SIZE) };
+    ZXC_OK
+}
+
+pub(crate) unsafe fn zxc_dict_save(
+    content: *const c_void,
+    content_size: usize,
+    huf_lengths: *const c_void,
+    buf: *mut c_void,
+    buf_capacity: usize,
+) -> i64 {
+    let Some(huf) = (unsafe { huf_table(huf_lengths) }) else {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    };
+    unsafe {
+        let zxd = save_zxd(bytes(content, content_size), huf);
+        emit_result(zxd, buf, buf_capacity)
+    }
+}
+
+pub(crate) unsafe fn zxc_dict_huf(buf: *const c_void, buf_size: usize) -> *const c_void {
+    parse_zxd(unsafe { bytes(buf, buf_size) })
+        .map_or(ptr::null(), |(_, huf, _)| huf.as_ptr() as *const c_void)
+}
+
+pub(crate) unsafe fn zxc_dict_load(
+    buf: *const c_void,
+    buf_size: usize,
+    content_out: *mut *const c_void,
+    content_size_out: *mut usize,
+    huf_out: *mut *const c_void,
+    dict_id_out: *mut u32,
+) -> c_int {
+    if buf.is_null() || content_out.is_null() || content_size_out.is_null() {
+        return ZXC_ERROR_NULL_INPUT;
+    }
+    match parse_zxd(unsafe { bytes(buf, buf_size) }) {
+        Ok((content, huf, id)) => unsafe {
+            *content_out = content.as_ptr() as *const c_void;
+            *content_size_out = content.len();
+            if !huf_out.is_null() {
+                *huf_out = huf.as_ptr() as *const c_void;
+            }
+            if !dict_id_out.is_null() {
+                *dict_id_out = id;
+            }
+            ZXC_OK
+        },
+        Err(err) => code(err) as c_int,
+    }
+}
+
+pub(crate) unsafe fn zxc_dict_train(
+    samples: *const *const c_void,
+    sample_sizes: *const usize,
+    n_samples: usize,
+    zxd_buf: *mut c_void,
+    zxd_capacity: usize,
+) -> i64 {
+    if samples.is_null() || sample_sizes.is_null() || zxd_buf.is_null() {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    unsafe {
+        let content = train_content(samples, sample_sizes, n_samples, ZXC_DICT_SIZE_MAX);
+        emit_result(
+            save_zxd(&content, &[0; HUF_TABLE_SIZE]),
+            zxd_buf,
+            zxd_capacity,
+        )
+    }
+}
+
+// =============================================================================
+// Block API
+// =============================================================================
+
+pub(crate) unsafe fn zxc_compress_block_bound(input_size: usize) -> u64 {
+    if input_size == 0 || input_size > BLOCK_SIZE_MAX {
+        return 0;
+    }
+    BLOCK_HEADER_SIZE as u64
+        + input_size as u64
+        + BLOCK_FORMAT_OVERHEAD
+        + BLOCK_CHECKSUM_SIZE as u64
+}
+
+pub(crate) unsafe fn zxc_decompress_block_bound(uncompressed_size: usize) -> u64 {
+    if uncompressed_size > BLOCK_SIZE_MAX {
+        return 0;
+    }
+    uncompressed_size as u64 + DECOMPRESS_TAIL_PAD
+}
+
+/// Contexts carry no state: any non-null handle will do.
+pub(crate) unsafe fn zxc_create_cctx(opts: *const zxc_compress_opts_t) -> *mut zxc_cctx {
+    let _ = opts;
+    NonNull::dangling().as_ptr()
+}
+
+pub(crate) unsafe fn zxc_free_cctx(cctx: *mut zxc_cctx) {
+    let _ = cctx;
+}
+
+pub(crate) unsafe fn zxc_create_dctx() -> *mut zxc_dctx {
+    NonNull::dangling().as_ptr()
+}
+
+pub(crate) unsafe fn zxc_free_dctx(dctx: *mut zxc_dctx) {
+    let _ = dctx;
+}
+
+/// The decoder state fits in the handle-less mock, so a static workspace
+/// only needs to exist: one block's worth stands in for the C layout.
+pub(crate) unsafe fn zxc_static_dctx_workspace_size(block_size: usize) -> usize {
+    match block_size.is_power_of_two() && (BLOCK_SIZE_MIN..=BLOCK_SIZE_MAX).contains(&block_size) {
+        true => block_size,
+        false => 0,
+    }
+}
+
+/// Stored blocks need no match finder: a block's worth of state, as for
+/// the static decoder.
+pub(crate) unsafe fn zxc_estimate_cctx_size(src_size: usize, level: c_int) -> u64 {
+    let _ = level;
+    src_size as u64
+}
+
+pub(crate) unsafe fn zxc_init_static_dctx(
+    workspace: *mut c_void,
+    workspace_size: usize,
+    block_size: usize,
+) -> *mut zxc_dctx {
+    let needed = unsafe { zxc_static_dctx_workspace_size(block_size) };
+    if workspace.is_null() || needed == 0 || workspace_size < needed || workspace as usize % 64 != 0
+    {
+        return ptr::null_mut();
+    }
+    workspace as *mut zxc_dctx
+}
+
+pub(crate) unsafe fn zxc_decompress_dctx(
+    dctx: *mut zxc_dctx,
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_decompress_opts_t,
+) -> i64 {
+    if dctx.is_null() {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    unsafe { zxc_decompress(src, src_size, dst, dst_capacity, opts) }
+}
+
+pub(crate) unsafe fn zxc_compress_block(
+    cctx: *mut zxc_cctx,
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_compress_opts_t,
+) -> i64 {
+    if cctx.is_null() || src.is_null() || dst.is_null() || src_size == 0 || dst_capacity == 0 {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    if src_size > BLOCK_SIZE_MAX {
+        return i64::from(ZXC_ERROR_BAD_BLOCK_SIZE);
+    }
+    let with_checksum = unsafe { opts.as_ref() }.is_some_and(|o| o.checksum_enabled != 0);
+    let data = unsafe { bytes(src, src_size) };
+    let mut out = Vec::with_capacity(BLOCK_HEADER_SIZE + src_size + BLOCK_CHECKSUM_SIZE);
+    out.extend_from_slice(&block_header(BLOCK_RAW, src_size as u32));
+    out.extend_from_slice(data);
+    if with_checksum {
+        out.extend_from_slice(&checksum(data).to_le_bytes());
+    }
+    unsafe { emit(&out, dst, dst_capacity) }
+}
+
+pub(crate) unsafe fn zxc_decompress_block(
+    dctx: *mut zxc_dctx,
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_decompress_opts_t,
+) -> i64 {
+    if dctx.is_null() || src.is_null() || dst.is_null() || src_size < BLOCK_HEADER_SIZE {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    if dst_capacity == 0 {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    let (verify, dict) = match unsafe { opts.as_ref() } {
+        Some(o) => (o.checksum_enabled != 0, unsafe {
+            bytes(o.dict, o.dict_size)
+        }),
+        None => (false, &[][..]),
+    };
+    unsafe {
+        let data = crate::pure::decompress_block(
+            bytes(src, src_size),
+            verify,
+            (!dict.is_empty()).then_some(dict),
+        );
+        emit_result(data, dst, dst_capacity)
+    }
+}
+
+/// The mock decoder never writes past the decoded size, so the strict
+/// variant is the same function.
+pub(crate) unsafe fn zxc_decompress_block_safe(
+    dctx: *mut zxc_dctx,
+    src: *const c_void,
+    src_size: usize,
+    dst: *mut c_void,
+    dst_capacity: usize,
+    opts: *const zxc_decompress_opts_t,
+) -> i64 {
+    unsafe { zxc_decompress_block(dctx, src, src_size, dst, dst_capacity, opts) }
+}
+
+// =============================================================================
+// Push streams
+// =============================================================================
+
+/// Bytes staged for the caller, drained across calls.
+#[derive(Default)]
+struct Pending {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Pending {
+    /// Moves as much as fits into `out`; returns the number of bytes written.
+    ///
+    /// # Safety
+    ///
+    /// `out.dst` must be valid for writes of `out.size` bytes.
+    unsafe fn drain(&mut self, out: &mut zxc_outbuf_t) -> usize {
+        let n = (self.buf.len() - self.pos).min(out.size.saturating_sub(out.pos));
+        if n > 0 {
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.buf[self.pos..].as_ptr(),
+                    (out.dst as *mut u8).add(out.pos),
+                    n,
+                );
+            }
+            self.pos += n;
+            out.pos += n;
+        }
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+        n
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+}
+
+/// Takes the unread part of an input descriptor and marks it consumed.
+///
+/// # Safety
+///
+/// `in_.src` must be valid for `in_.size` bytes.
+unsafe fn consume<'a>(in_: &mut zxc_inbuf_t, max: usize) -> &'a [u8] {
+    let avail = in_.size.saturating_sub(in_.pos);
+    let n = avail.min(max);
+    let data = unsafe { bytes((in_.src as *const u8).add(in_.pos) as *const c_void, n) };
+    in_.pos += n;
+    data
+}
+
+struct MockCStream {
+    writer: FrameWriter,
+    started: bool,
+    ended: bool,
+    staged: Vec<u8>,
+    pending: Pending,
+}
+
+impl MockCStream {
+    fn start(&mut self) {
+        if !self.started {
+            self.writer.header(&mut self.pending.buf);
+            self.started = true;
+        }
+    }
+}
+
+pub(crate) unsafe fn zxc_cstream_create(opts: *const zxc_compress_opts_t) -> *mut zxc_cstream {
+    let (block_size, checksum) = match unsafe { opts.as_ref() } {
+        Some(o) => (o.block_size, o.checksum_enabled != 0),
+        None => (0, false),
+    };
+    let Ok(writer) = FrameWriter::new(block_size, checksum, None) else {
+        return ptr::null_mut();
+    };
+    let cs = MockCStream {
+        writer,
+        started: false,
+        ended: false,
+        staged: Vec::new(),
+        pending: Pending::default(),
+    };
+    Box::into_raw(Box::new(cs)) as *mut zxc_cstream
+}
+
+pub(crate) unsafe fn zxc_cstream_free(cs: *mut zxc_cstream) {
+    if !cs.is_null() {
+        drop(unsafe { Box::from_raw(cs as *mut MockCStream) });
+    }
+}
+
+pub(crate) unsafe fn zxc_cstream_compress(
+    cs: *mut zxc_cstream,
+    out: *mut zxc_outbuf_t,
+    in_: *mut zxc_inbuf_t,
+) -> i64 {
+    let (Some(cs), Some(out), Some(in_)) = (unsafe {
+        (
+            (cs as *mut MockCStream).as_mut(),
+            out.as_mut(),
+            in_.as_mut(),
+        )
+    }) else {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    };
+    if cs.ended {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    }
+    cs.start();
+    loop {
+        unsafe { cs.pending.drain(out) };
+        if cs.pending.remaining() > 0 {
+            break;
+        }
+        let room = cs.writer.block_size - cs.staged.len();
+        let data = unsafe { consume(in_, room) };
+        if data.is_empty() {
+            break;
+        }
+        cs.staged.extend_from_slice(data);
+        if cs.staged.len() == cs.writer.block_size {
+            cs.writer.block(&mut cs.pending.buf, &cs.staged);
+            cs.staged.clear();
+        }
+    }
+    cs.pending.remaining() as i64
+}
+
+pub(crate) unsafe fn zxc_cstream_end(cs: *mut zxc_cstream, out: *mut zxc_outbuf_t) -> i64 {
+    let (Some(cs), Some(out)) = (unsafe { ((cs as *mut MockCStream).as_mut(), out.as_mut()) })
+    else {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    };
+    if !cs.ended {
+        cs.start();
+        if !cs.staged.is_empty() {
+            cs.writer.block(&mut cs.pending.buf, &cs.staged);
+            cs.staged.clear();
+        }
+        cs.writer.finish(&mut cs.pending.buf, false);
+        cs.ended = true;
+    }
+    unsafe { cs.pending.drain(out) };
+    cs.pending.remaining() as i64
+}
+
+pub(crate) unsafe fn zxc_cstream_in_size(cs: *const zxc_cstream) -> usize {
+    unsafe { (cs as *const MockCStream).as_ref() }.map_or(0, |cs| cs.writer.block_size)
+}
+
+pub(crate) unsafe fn zxc_cstream_out_size(cs: *const zxc_cstream) -> usize {
+    unsafe { (cs as *const MockCStream).as_ref() }.map_or(0, |cs| {
+        BLOCK_HEADER_SIZE + cs.writer.block_size + BLOCK_CHECKSUM_SIZE
+    })
+}
+
+struct MockDStream {
+    options: DecompressOptions,
+    frame: Vec<u8>,
+    finished: bool,
+    pending: Pending,
+}
+
+impl MockDStream {
+    /// Number of further bytes needed before the buffered frame can be
+    /// decoded, or 0 once it is complete. Never asks for more than the frame
+    /// holds, so trailing input stays with the caller.
+    fn needed(&self) -> Result<usize> {
+        let f = &self.frame;
+        if f.len() < FILE_HEADER_SIZE {
+            return Ok(FILE_HEADER_SIZE - f.len());
+        }
+        let header = FileHeader::parse(f)?;
+        let mut pos = FILE_HEADER_SIZE;
+        loop {
+            if f.len() < pos + BLOCK_HEADER_SIZE {
+                return Ok(pos + BLOCK_HEADER_SIZE - f.len());
+            }
+            let block = BlockHeader::parse(&f[pos..])?;
+            pos += BLOCK_HEADER_SIZE;
+            if block.kind == BLOCK_EOF {
+                break;
+            }
+            pos += block.comp_size;
+            if header.has_checksum {
+                pos += BLOCK_CHECKSUM_SIZE;
+            }
+        }
+        // An optional seek table sits between the EOF block and the footer.
+        if f.len() >= pos + BLOCK_HEADER_SIZE {
+            if let Ok(sek) = BlockHeader::parse(&f[pos..]) {
+                if sek.kind == BLOCK_SEK {
+                    pos += BLOCK_HEADER_SIZE + sek.comp_size;
+                }
+            }
+        }
+        Ok((pos + FILE_FOOTER_SIZE).saturating_sub(f.len()))
+    }
+}
+
+pub(crate) unsafe fn zxc_dstream_create(opts: *const zxc_decompress_opts_t) -> *mut zxc_dstream {
+    let verify = unsafe { opts.as_ref() }.is_some_and(|o| o.checksum_enabled != 0);
+    let ds = MockDStream {
+        options: DecompressOptions {
+            verify_checksum: verify,
+            ..Default::default()
+        },
+        frame: Vec::new(),
+        finished: false,
+        pending: Pending::default(),
+    };
+    Box::into_raw(Box::new(ds)) as *mut zxc_dstream
+}
+
+pub(crate) unsafe fn zxc_dstream_free(ds: *mut zxc_dstream) {
+    if !ds.is_null() {
+        drop(unsafe { Box::from_raw(ds as *mut MockDStream) });
+    }
+}
+
+pub(crate) unsafe fn zxc_dstream_decompress(
+    ds: *mut zxc_dstream,
+    out: *mut zxc_outbuf_t,
+    in_: *mut zxc_inbuf_t,
+) -> i64 {
+    let (Some(ds), Some(out), Some(in_)) = (unsafe {
+        (
+            (ds as *mut MockDStream).as_mut(),
+            out.as_mut(),
+            in_.as_mut(),
+        )
+    }) else {
+        return i64::from(ZXC_ERROR_NULL_INPUT);
+    };
+    if !ds.finished {
+        loop {
+            let need = match ds.needed() {
+                Ok(need) => need,
+                Err(err) => return code(err),
+            };
+            if need == 0 {
+                match crate::pure::decompress_with_options(&ds.frame, &ds.options) {
+                    Ok(data) => ds.pending.buf = data,
+                    Err(err) => return code(err),
+                }
+                ds.frame = Vec::new();
+                ds.finished = true;
+                break;
+            }
+            let data = unsafe { consume(in_, need) };
+            if data.is_empty() {
+                break;
+            }
+            ds.frame.extend_from_slice(data);
+        }
+    }
+    unsafe { ds.pending.drain(out) as i64 }
+}
+
+/// Finished once the footer is validated and every decoded byte was handed
+/// out.
+pub(crate) unsafe fn zxc_dstream_finished(ds: *const zxc_dstream) -> c_int {
+    unsafe { (ds as *const MockDStream).as_ref() }
+        .is_some_and(|ds| ds.finished && ds.pending.remaining() == 0) as c_int
+}
+
+pub(crate) unsafe fn zxc_dstream_in_size(ds: *const zxc_dstream) -> usize {
+    let _ = ds;
+    BLOCK_HEADER_SIZE + BLOCK_SIZE_DEFAULT + BLOCK_CHECKSUM_SIZE
+}
+
+pub(crate) unsafe fn zxc_dstream_out_size(ds: *const zxc_dstream) -> usize {
+    let _ = ds;
+    BLOCK_SIZE_DEFAULT
+}
+
+// =============================================================================
+// Seekable archives
+// =============================================================================
+
+#[cfg(feature = "std")]
+pub(crate) use seekable::*;
+
+#[cfg(feature = "std")]
+mod seekable {
+    use super::*;
+    use alloc::vec;
+
+    use zxc_sys::{zxc_reader_t, zxc_seekable};
+
+    struct MockSeekable {
+        archive: Vec<u8>,
+        block_size: usize,
+        total: u64,
+        comp_sizes: Vec<u32>,
+        dict_id: Option<u32>,
+        options: DecompressOptions,
+    }
+
+    impl MockSeekable {
+        /// Reads the header, footer and seek table like the C library does.
+        fn open(archive: Vec<u8>) -> Result<Self> {
+            let header = FileHeader::parse(&archive)?;
+            let footer = archive
+                .len()
+                .checked_sub(FILE_FOOTER_SIZE)
+                .ok_or(Error::SrcTooSmall)?;
+            let total = read_u64(&archive, footer)?;
+            let blocks = usize::try_from(total.div_ceil(header.block_size as u64))
+                .map_err(|_| Error::TooLarge(total))?;
+            let sek_size = blocks.checked_mul(SEEK_ENTRY_SIZE).ok_or(Error::Overflow)?;
+            let sek_start = footer
+                .checked_sub(BLOCK_HEADER_SIZE + sek_size)
+                .ok_or(Error::SrcTooSmall)?;
+            let sek = BlockHeader::parse(&archive[sek_start..])?;
+            if sek.kind != BLOCK_SEK || sek.comp_size != sek_size {
+                return Err(Error::BadBlockType);
+            }
+            let comp_sizes = (0..blocks)
+                .map(|i| {
+                    read_u32(
+                        &archive,
+                        sek_start + BLOCK_HEADER_SIZE + i * SEEK_ENTRY_SIZE,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Self {
+                archive,
+                block_size: header.block_size,
+                total,
+                comp_sizes,
+                dict_id: header.dict_id,
+                options: DecompressOptions::skip_checksum(),
+            })
+        }
+
+        fn block_decomp_size(&self, idx: usize) -> u64 {
+            let start = idx as u64 * self.block_size as u64;
+            (self.total - start).min(self.block_size as u64)
+        }
+
+        fn decompress_range(&self, offset: u64, len: usize) -> Result<Vec<u8>> {
+            if offset
+                .checked_add(len as u64)
+                .is_none_or(|end| end > self.total)
+            {
+                return Err(Error::SrcTooSmall);
+            }
+            if self.dict_id.is_some() && self.options.dict.is_none() {
+                return Err(Error::DictRequired);
+            }
+            let data = crate::pure::decompress_with_options(&self.archive, &self.options)?;
+            let start = offset as usize;
+            Ok(data[start..start + len].to_vec())
+        }
+    }
+
+    fn into_handle(s: Result<MockSeekable>) -> *mut zxc_seekable {
+        match s {
+            Ok(s) => Box::into_raw(Box::new(s)) as *mut zxc_seekable,
+            Err(_) => ptr::null_mut(),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `s` must be null or a live handle from this module.
+    unsafe fn handle<'a>(s: *const zxc_seekable) -> Option<&'a MockSeekable> {
+        unsafe { (s as *const MockSeekable).as_ref() }
+    }
+
+    pub(crate) unsafe fn zxc_seekable_open(
+        src: *const c_void,
+        src_size: usize,
+    ) -> *mut zxc_seekable {
+        into_handle(MockSeekable::open(unsafe { bytes(src, src_size) }.to_vec()))
+    }
+
+    /// Reads the whole archive up front; the mock has no use for
+    /// block-granular reads.
+    pub(crate) unsafe fn zxc_seekable_open_reader(r: *const zxc_reader_t) -> *mut zxc_seekable {
+        let Some(r) = (unsafe { r.as_ref() }) else {
+            return ptr::null_mut();
+        };
+        let (Some(read_at), Ok(size)) = (r.read_at, usize::try_from(r.size)) else {
+            return ptr::null_mut();
+        };
+        let mut archive = vec![0u8; size];
+        let got = unsafe { read_at(r.ctx, archive.as_mut_ptr() as *mut c_void, size, 0) };
+        if got != size as i64 {
+            return ptr::null_mut();
+        }
+        into_handle(MockSeekable::open(archive))
+    }
+
+    pub(crate) unsafe fn zxc_seekable_set_dict(
+        s: *mut zxc_seekable,
+        dict: *const c_void,
+        dict_size: usize,
+        dict_huf: *const c_void,
+    ) -> c_int {
+        let Some(s) = (unsafe { (s as *mut MockSeekable).as_mut() }) else {
+            return ZXC_ERROR_NULL_INPUT;
+        };
+        let content = unsafe { bytes(dict, dict_size) };
+        if content.len() > ZXC_DICT_SIZE_MAX {
+            return ZXC_ERROR_DICT_TOO_LARGE;
+        }
+        let huf = unsafe { huf_table(dict_huf) };
+        if let Some(id) = s.dict_id {
+            if dict_id(content, huf) != id {
+                return ZXC_ERROR_DICT_MISMATCH;
+            }
+        }
+        s.options = unsafe { decompress_options(0, dict, dict_size, dict_huf) };
+        ZXC_OK
+    }
+
+    pub(crate) unsafe fn zxc_seekable_get_num_blocks(s: *const zxc_seekable) -> u32 {
+        unsafe { handle(s) }.map_or(0, |s| s.comp_sizes.len() as u32)
+    }
+
+    pub(crate) unsafe fn zxc_seekable_get_decompressed_size(s: *const zxc_seekable) -> u64 {
+        unsafe { handle(s) }.map_or(0, |s| s.total)
+    }
+
+    pub(crate) unsafe fn zxc_seekable_get_block_comp_size(
+        s: *const zxc_seekable,
+        block_idx: u32,
+    ) -> u32 {
+        unsafe { handle(s) }
+            .and_then(|s| s.comp_sizes.get(block_idx as usize).copied())
+            .unwrap_or(0)
+    }
+
+    pub(crate) unsafe fn zxc_seekable_get_block_decomp_size(
+        s: *const zxc_seekable,
+        block_idx: u32,
+    ) -> u32 {
+        unsafe { handle(s) }
+            .filter(|s| (block_idx as usize) < s.comp_sizes.len())
+            .map_or(0, |s| s.block_decomp_size(block_idx as usize) as u32)
+    }
+
+    pub(crate) unsafe fn zxc_seekable_decompress_range(
+        s: *mut zxc_seekable,
+        dst: *mut c_void,
+        dst_capacity: usize,
+        offset: u64,
+        len: usize,
+    ) -> i64 {
+        if len == 0 {
+            return 0;
+        }
+        let Some(s) = (unsafe { handle(s) }) else {
+            return i64::from(ZXC_ERROR_NULL_INPUT);
+        };
+        if dst.is_null() {
+            return i64::from(ZXC_ERROR_NULL_INPUT);
+        }
+        if dst_capacity < len {
+            return i64::from(ZXC_ERROR_DST_TOO_SMALL);
+        }
+        unsafe { emit_result(s.decompress_range(offset, len), dst, dst_capacity) }
+    }
+
+    pub(crate) unsafe fn zxc_seekable_free(s: *mut zxc_seekable) {
+        if !s.is_null() {
+            drop(unsafe { Box::from_raw(s as *mut MockSeekable) });
+        }
+    }
+
+    pub(crate) unsafe fn zxc_write_seek_table(
+        dst: *mut u8,
+        dst_capacity: usize,
+        comp_sizes: *const u32,
+        num_blocks: u32,
+    ) -> i64 {
+        if num_blocks > u32::MAX / SEEK_ENTRY_SIZE as u32 {
+            return i64::from(ZXC_ERROR_OVERFLOW);
+        }
+        if dst_capacity < unsafe { zxc_seek_table_size(num_blocks) } {
+            return i64::from(ZXC_ERROR_DST_TOO_SMALL);
+        }
+        if dst.is_null() || (comp_sizes.is_null() && num_blocks > 0) {
+            return i64::from(ZXC_ERROR_NULL_INPUT);
+        }
+        let sizes = if num_blocks == 0 {
+            &[][..]
+        } else {
+            unsafe { core::slice::from_raw_parts(comp_sizes, num_blocks as usize) }
+        };
+        let mut out = Vec::new();
+        write_sek(&mut out, sizes);
+        unsafe { emit(&out, dst as *mut c_void, dst_capacity) }
+    }
+
+    pub(crate) unsafe fn zxc_seek_table_size(num_blocks: u32) -> usize {
+        BLOCK_HEADER_SIZE + num_blocks as usize * SEEK_ENTRY_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn frames_are_store_only() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 7) as u8).collect();
+        let frame =
+            compress_with_options(&data, &CompressOptions::with_level(Level::Ultra)).unwrap();
+        assert_eq!(frame[super::FILE_HEADER_SIZE], super::BLOCK_RAW);
+        assert_eq!(decompressed_size(&frame).unwrap(), data.len() as u64);
+        assert_eq!(crate::pure::decompress(&frame).unwrap(), data);
+    }
+
+    #[test]
+    fn corruption_is_detected() {
+        let data = b"mock backend payload".repeat(50);
+        let mut frame = compress_with_options(&data, &CompressOptions::default()).unwrap();
+        frame[super::FILE_HEADER_SIZE + super::BLOCK_HEADER_SIZE] ^= 1;
+        let err = decompress_with_options(&frame, &DecompressOptions::default()).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: Some(0), .. }),
+            "{err:?}"
+        );
+        assert!(
+            matches!(err.cause(), Error::ChecksumMismatch { block: Some(0), .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn version_string_matches_constants() {
+        let s = unsafe { core::ffi::CStr::from_ptr(super::zxc_version_string()) };
+        let (major, minor, patch) = version();
+        assert_eq!(
+            s.to_str().unwrap(),
+            alloc::format!("{major}.{minor}.{patch}")
+        );
+    }
+}
@@ -0,0 +1,520 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Transparent compression for object stores (the `object_store` feature).
+//!
+//! [`ZxcStore`] wraps any [`ObjectStore`] (S3, GCS, Azure, local files,
+//! memory): `put` stores each object as a seekable zxc frame, and `get`
+//! returns the original bytes. Ranged reads (`get_range`, `get_ranges` and
+//! `GetOptions::range`) fetch the frame's seek table and then only the
+//! blocks overlapping the range, so reading a Parquet footer out of a large
+//! compressed object moves a few blocks over the network, not the object.
+//!
+//! ```rust
+//! use object_store::memory::InMemory;
+//! use object_store::{ObjectStore, path::Path};
+//! use zxc::object_store::ZxcStore;
+//!
+//! # futures::executor::block_on(async {
+//! let store = ZxcStore::new(InMemory::new());
+//! let path = Path::from("logs/day-1.jsonl");
+//! let data = br#"{"level":"info","msg":"request served"}"#.repeat(10_000);
+//! store.put(&path, data.clone().into()).await?;
+//!
+//! assert_eq!(store.get(&path).await?.bytes().await?, data);
+//! assert_eq!(store.get_range(&path, 39..78).await?, data[39..78]);
+//! # Ok::<(), object_store::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! Compression and decompression run inline on the calling task.
+//!
+//! `head` and `list` report the stored, compressed size, since listing
+//! cannot read the frames; `get` results report the original size.
+//! Multipart uploads are buffered in memory and stored with a single `put`
+//! on `complete`, as a frame cannot be split across independently uploaded
+//! parts. Objects written around the wrapper must be seekable zxc frames
+//! for `get` to read them.
+
+use std::boxed::Box;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use ::object_store::path::Path;
+use ::object_store::{
+    GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult, Result, UploadPart,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{self, try_join_all};
+use futures::stream::{self, BoxStream, StreamExt};
+
+use crate::seekable::{ReadAt, Seekable};
+use crate::{CompressOptions, compress_with_options, decompress};
+
+/// Name reported in [`object_store::Error::Generic`].
+const STORE: &str = "zxc";
+/// Bytes fetched from each end of an object before opening it: the file
+/// header, the footer and the seek table of objects up to a few GiB.
+const PREFETCH: u64 = 64 * 1024;
+/// Fetch rounds allowed to open a frame and read a range from it.
+const MAX_ROUNDS: usize = 4;
+
+/// An [`ObjectStore`] that compresses objects with zxc. See the
+/// [module docs](self).
+#[derive(Debug)]
+pub struct ZxcStore<T> {
+    inner: Arc<T>,
+    opts: CompressOptions,
+}
+
+impl<T> Clone for ZxcStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            opts: self.opts.clone(),
+        }
+    }
+}
+
+impl<T: ObjectStore> ZxcStore<T> {
+    /// Wraps `inner`, compressing with the default options.
+    pub fn new(inner: T) -> Self {
+        Self::with_options(inner, CompressOptions::default())
+    }
+
+    /// Wraps `inner`, compressing with `opts`.
+    ///
+    /// `opts.seekable` is forced on, since ranged reads need the seek table.
+    pub fn with_options(inner: T, opts: CompressOptions) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            opts: CompressOptions {
+                seekable: true,
+                ..opts
+            },
+        }
+    }
+
+    /// The wrapped store, holding the compressed objects.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Decompresses `range` of the object behind `meta`, fetching the seek
+    /// table and then the blocks the range overlaps.
+    async fn get_range_of(&self, meta: &ObjectMeta, options: &GetOptions) -> Result<GetRange> {
+        let source = Fetched::new(meta.size);
+        let head = 0..PREFETCH.min(meta.size);
+        let tail = meta.size.saturating_sub(PREFETCH)..meta.size;
+        self.fetch(meta, &source, std::vec![head, tail]).await?;
+
+        let size = self
+            .read(meta, &source, |s| Ok(s.decompressed_size()))
+            .await?;
+        let range = match &options.range {
+            Some(range) => range.as_range(size).map_err(generic)?,
+            None => 0..size,
+        };
+        let len = usize::try_from(range.end - range.start).map_err(generic)?;
+        let data = self
+            .read(meta, &source, |s| {
+                source.hint(span(s, &range));
+                let mut buf = std::vec![0u8; len];
+                let n = s.decompress_range(&mut buf, range.start, len)?;
+                buf.truncate(n);
+                Ok(buf)
+            })
+            .await?;
+        Ok(GetRange { size, range, data })
+    }
+
+    /// Runs `f` on the frame, fetching what it was missing until it has
+    /// everything.
+    async fn read<R>(
+        &self,
+        meta: &ObjectMeta,
+        source: &Fetched,
+        mut f: impl FnMut(&mut Seekable) -> crate::Result<R>,
+    ) -> Result<R> {
+        for _ in 0..MAX_ROUNDS {
+            let attempt = Seekable::open_reader(source.clone()).and_then(|mut s| f(&mut s));
+            let missing = source.take_missing();
+            match attempt {
+                Ok(r) => return Ok(r),
+                Err(e) if missing.is_empty() => return Err(generic(e)),
+                Err(_) => self.fetch(meta, source, missing).await?,
+            }
+        }
+        Err(generic(crate::Error::InvalidData))
+    }
+
+    /// Fetches `ranges` of the object, as long as it is still the version
+    /// `meta` describes.
+    async fn fetch(
+        &self,
+        meta: &ObjectMeta,
+        source: &Fetched,
+        ranges: Vec<Range<u64>>,
+    ) -> Result<()> {
+        let gets = ranges.into_iter().filter(|r| !r.is_empty()).map(|range| {
+            let options = GetOptions {
+                range: Some(range.clone().into()),
+                if_match: meta.e_tag.clone(),
+                version: meta.version.clone(),
+                ..Default::default()
+            };
+            async move {
+                let bytes = self
+                    .inner
+                    .get_opts(&meta.location, options)
+                    .await?
+                    .bytes()
+                    .await?;
+                Ok::<_, ::object_store::Error>((range.start, bytes))
+            }
+        });
+        let chunks = try_join_all(gets).await?;
+        if let Ok(mut fetched) = source.chunks.lock() {
+            fetched.extend(chunks);
+        }
+        Ok(())
+    }
+}
+
+impl<T: ObjectStore> fmt::Display for ZxcStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZxcStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T: ObjectStore> ObjectStore for ZxcStore<T> {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        let mut data = Vec::with_capacity(payload.content_length());
+        for chunk in &payload {
+            data.extend_from_slice(chunk);
+        }
+        let frame = compress_with_options(&data, &self.opts).map_err(generic)?;
+        self.inner.put_opts(location, frame.into(), opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        Ok(Box::new(BufferedUpload {
+            store: self.clone(),
+            location: location.clone(),
+            opts: PutOptions {
+                mode: PutMode::Overwrite,
+                tags: opts.tags,
+                attributes: opts.attributes,
+                extensions: opts.extensions,
+            },
+            parts: Vec::new(),
+        }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        if options.head {
+            return self.inner.get_opts(location, options).await;
+        }
+        if options.range.is_none() {
+            let result = self.inner.get_opts(location, options).await?;
+            let (mut meta, attributes) = (result.meta.clone(), result.attributes.clone());
+            let data = decompress(&result.bytes().await?).map_err(generic)?;
+            meta.size = data.len() as u64;
+            let range = 0..meta.size;
+            return Ok(get_result(meta, attributes, range, data));
+        }
+        // Check the preconditions and pin the version once; the ranged
+        // reads that follow all require the same ETag.
+        let head = GetOptions {
+            head: true,
+            range: None,
+            ..options.clone()
+        };
+        let result = self.inner.get_opts(location, head).await?;
+        let GetRange { size, range, data } = self.get_range_of(&result.meta, &options).await?;
+        let meta = ObjectMeta {
+            size,
+            ..result.meta
+        };
+        Ok(get_result(meta, result.attributes, range, data))
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, Result<ObjectMeta>> {
+        self.inner.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.rename(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.rename_if_not_exists(from, to).await
+    }
+}
+
+/// A decompressed range and the object's original size.
+struct GetRange {
+    size: u64,
+    range: Range<u64>,
+    data: Vec<u8>,
+}
+
+fn get_result(
+    meta: ObjectMeta,
+    attributes: ::object_store::Attributes,
+    range: Range<u64>,
+    data: Vec<u8>,
+) -> GetResult {
+    let range = range.start..range.start + data.len() as u64;
+    GetResult {
+        payload: GetResultPayload::Stream(
+            stream::once(future::ready(Ok(Bytes::from(data)))).boxed(),
+        ),
+        meta,
+        range,
+        attributes,
+    }
+}
+
+/// The compressed size of the blocks overlapping `range`.
+fn span(s: &Seekable, range: &Range<u64>) -> u64 {
+    let (mut start, mut span) = (0u64, 0u64);
+    for block in 0..s.num_blocks() {
+        let end = start + u64::from(s.block_decompressed_size(block).unwrap_or(0));
+        if end > range.start && start < range.end {
+            span += u64::from(s.block_compressed_size(block).unwrap_or(0));
+        }
+        start = end;
+    }
+    span
+}
+
+/// The parts of an object fetched so far, read by the seekable decoder.
+///
+/// A read outside them fails and is recorded, to be fetched before the
+/// next attempt. The first missed read is widened to the hinted span, so
+/// that the blocks of a range come in one request rather than one round
+/// per block.
+#[derive(Clone)]
+struct Fetched {
+    size: u64,
+    chunks: Arc<Mutex<Vec<(u64, Bytes)>>>,
+    missing: Arc<Mutex<Vec<Range<u64>>>>,
+    hint: Arc<AtomicU64>,
+}
+
+impl Fetched {
+    fn new(size: u64) -> Self {
+        Self {
+            size,
+            chunks: Arc::default(),
+            missing: Arc::default(),
+            hint: Arc::default(),
+        }
+    }
+
+    fn hint(&self, span: u64) {
+        self.hint.store(span, Ordering::Relaxed);
+    }
+
+    fn take_missing(&self) -> Vec<Range<u64>> {
+        self.missing
+            .lock()
+            .map(|mut m| core::mem::take(&mut *m))
+            .unwrap_or_default()
+    }
+}
+
+impl ReadAt for Fetched {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, dst: &mut [u8], offset: u64) -> io::Result<()> {
+        let end = offset + dst.len() as u64;
+        let chunks = self.chunks.lock().map_err(|_| io::ErrorKind::Other)?;
+        let hit = chunks.iter().find_map(|(start, bytes)| {
+            let from = usize::try_from(offset.checked_sub(*start)?).ok()?;
+            bytes.get(from..from.checked_add(dst.len())?)
+        });
+        if let Some(src) = hit {
+            dst.copy_from_slice(src);
+            return Ok(());
+        }
+        let mut missing = self.missing.lock().map_err(|_| io::ErrorKind::Other)?;
+        let span = match missing.is_empty() {
+            true => self.hint.load(Ordering::Relaxed),
+            false => 0,
+        };
+        missing.push(offset..end.max(offset + span).min(self.size));
+        Err(io::ErrorKind::NotFound.into())
+    }
+}
+
+/// A multipart upload collected in memory and compressed on `complete`.
+#[derive(Debug)]
+struct BufferedUpload<T> {
+    store: ZxcStore<T>,
+    location: Path,
+    opts: PutOptions,
+    parts: Vec<Bytes>,
+}
+
+#[async_trait]
+impl<T: ObjectStore> MultipartUpload for BufferedUpload<T> {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        self.parts.extend(data);
+        Box::pin(future::ready(Ok(())))
+    }
+
+    async fn complete(&mut self) -> Result<PutResult> {
+        let payload = core::mem::take(&mut self.parts).into_iter().collect();
+        let opts = core::mem::take(&mut self.opts);
+        self.store.put_opts(&self.location, payload, opts).await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.parts.clear();
+        Ok(())
+    }
+}
+
+fn generic(e: impl std::error::Error + Send + Sync + 'static) -> ::object_store::Error {
+    ::object_store::Error::Generic {
+        store: STORE,
+        source: Box::new(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::object_store::memory::InMemory;
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn object() -> Vec<u8> {
+        (0..400_000u32)
+            .flat_map(|i| std::format!("{:08} ", i / 3).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_object_store_roundtrip() {
+        block_on(async {
+            let store = ZxcStore::new(InMemory::new());
+            let path = Path::from("lake/part-0000.bin");
+            let data = object();
+            store.put(&path, data.clone().into()).await.unwrap();
+
+            // Stored as a seekable frame, much smaller than the data.
+            let raw = store
+                .inner()
+                .get(&path)
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap();
+            assert!(crate::detect_zxc(&raw));
+            assert!(
+                raw.len() * 4 < data.len(),
+                "{} of {}",
+                raw.len(),
+                data.len()
+            );
+            assert_eq!(store.head(&path).await.unwrap().size, raw.len() as u64);
+
+            let got = store.get(&path).await.unwrap();
+            assert_eq!(got.meta.size, data.len() as u64);
+            assert_eq!(got.range, 0..data.len() as u64);
+            assert_eq!(got.bytes().await.unwrap(), data);
+
+            let n = data.len() as u64;
+            for range in [0..10, 1_000_000..1_300_000, n - 7..n, 5..n] {
+                let bytes = store.get_range(&path, range.clone()).await.unwrap();
+                assert_eq!(bytes, data[range.start as usize..range.end as usize]);
+            }
+            let ranges = [10..20, 2_000_000..2_000_100];
+            let parts = store.get_ranges(&path, &ranges).await.unwrap();
+            assert_eq!(parts[1], data[2_000_000..2_000_100]);
+
+            let suffix = GetOptions {
+                range: Some(::object_store::GetRange::Suffix(100)),
+                ..Default::default()
+            };
+            let got = store.get_opts(&path, suffix).await.unwrap();
+            assert_eq!(got.range, n - 100..n);
+            assert_eq!(got.meta.size, n);
+            assert_eq!(got.bytes().await.unwrap(), data[data.len() - 100..]);
+
+            let past_end = store.get_range(&path, n + 1..n + 2).await;
+            assert!(past_end.is_err());
+        });
+    }
+
+    #[test]
+    fn test_object_store_multipart() {
+        block_on(async {
+            let store = ZxcStore::new(InMemory::new());
+            let path = Path::from("upload.bin");
+            let data = object();
+            let mut upload = store.put_multipart(&path).await.unwrap();
+            for part in data.chunks(300_000) {
+                upload.put_part(part.to_vec().into()).await.unwrap();
+            }
+            upload.complete().await.unwrap();
+            assert_eq!(store.get(&path).await.unwrap().bytes().await.unwrap(), data);
+
+            // Objects written around the wrapper are an error, not garbage.
+            let plain = Path::from("plain.txt");
+            store.inner().put(&plain, "not zxc".into()).await.unwrap();
+            assert!(store.get(&plain).await.is_err());
+            assert!(store.get_range(&plain, 0..3).await.is_err());
+        });
+    }
+}
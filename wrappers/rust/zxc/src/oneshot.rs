@@ -7,26 +7,68 @@
 
 //! One-shot compress / decompress entry points and library version helpers.
 
-use std::ffi::c_void;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
 
 use zxc_sys::{ZXC_VERSION_MAJOR, ZXC_VERSION_MINOR, ZXC_VERSION_PATCH};
 
-use crate::error::error_from_code;
-use crate::{CompressOptions, DecompressOptions, Error, Level, Result};
+use crate::ctx::dict_parts;
+use crate::error::{size_from_code, size_to_usize};
+use crate::format::{self, MAGIC_WORD};
+use crate::inspect::{check_version, header};
+use crate::locate::locate;
+use crate::memory;
+use crate::metrics::{self, Operation};
+use crate::scratch::Scratch;
+use crate::{CStream, CompressOptions, DecompressOptions, Error, Level, Result, diag, env, ffi};
 
 /// Returns the maximum compressed size for an input of the given size.
 ///
-/// Use this to allocate a buffer before calling [`compress_to`].
+/// Use this to allocate a buffer before calling [`compress_to`]. Returns 0
+/// above [`MAX_INPUT_SIZE`](crate::MAX_INPUT_SIZE) or if the bound does not
+/// fit in a `usize` (on 32-bit targets, inputs close to 4 GiB).
 ///
-/// # Example
+/// This is the C library's `zxc_compress_bound` formula in Rust, so it
+/// costs no FFI call and can size arrays and statics:
 ///
 /// ```rust
+/// static SCRATCH: [u8; zxc::compress_bound(4096)] = [0; zxc::compress_bound(4096)];
+///
 /// let bound = zxc::compress_bound(1024);
 /// assert!(bound > 1024); // Accounts for headers and worst-case expansion
 /// ```
-#[inline]
-pub fn compress_bound(input_size: usize) -> u64 {
-    unsafe { zxc_sys::zxc_compress_bound(input_size) }
+pub const fn compress_bound(input_size: usize) -> usize {
+    // Sizes from zxc_constants.h and zxc_internal.h.
+    const FILE_HEADER_SIZE: u128 = crate::FRAME_HEADER_SIZE as u128;
+    const FILE_FOOTER_SIZE: u128 = crate::FOOTER_SIZE as u128;
+    const BLOCK_SIZE_MIN: u128 = crate::MIN_BLOCK_SIZE as u128;
+    const BLOCK_HEADER_SIZE: u128 = format::BLOCK_HEADER_SIZE as u128;
+    const BLOCK_CHECKSUM_SIZE: u128 = format::BLOCK_CHECKSUM_SIZE as u128;
+    const BLOCK_FORMAT_OVERHEAD: u128 = 64;
+    const SEEK_ENTRY_SIZE: u128 = 4;
+
+    if input_size > crate::MAX_INPUT_SIZE {
+        return 0;
+    }
+    // In u128, where nothing overflows; C computes in u64.
+    let n = match (input_size as u128).div_ceil(BLOCK_SIZE_MIN) {
+        0 => 1,
+        n => n,
+    };
+    let bound = FILE_HEADER_SIZE
+        + n * (BLOCK_HEADER_SIZE + BLOCK_CHECKSUM_SIZE + BLOCK_FORMAT_OVERHEAD)
+        + input_size as u128
+        + BLOCK_HEADER_SIZE // EOF block
+        + BLOCK_HEADER_SIZE // seek table block
+        + n * SEEK_ENTRY_SIZE
+        + FILE_FOOTER_SIZE;
+    if bound > usize::MAX as u128 {
+        0
+    } else {
+        bound as usize
+    }
 }
 
 /// Compresses data with the specified level.
@@ -61,6 +103,7 @@ pub fn compress(data: &[u8], level: Level, checksum: Option<bool>) -> Result<Vec
         seekable: false,
         dict: None,
         dict_huf: None,
+        pledged_size: None,
     };
     compress_with_options(data, &opts)
 }
@@ -78,15 +121,20 @@ pub fn compress(data: &[u8], level: Level, checksum: Option<bool>) -> Result<Vec
 /// # Ok::<(), zxc::Error>(())
 /// ```
 pub fn compress_with_options(data: &[u8], options: &CompressOptions) -> Result<Vec<u8>> {
-    let bound = compress_bound(data.len()) as usize;
-    let mut output = Vec::with_capacity(bound);
+    let bound = match compress_bound(data.len()) {
+        0 => return Err(Error::TooLarge(data.len() as u64)),
+        b => b,
+    };
+    let _held = memory::reserve(Some(options.level), data.len(), bound);
+    let mut output = Scratch::try_with_capacity(bound)?;
 
     let written = unsafe { impl_compress(data, output.as_mut_ptr(), output.capacity(), options)? };
 
     unsafe {
         output.set_len(written);
     }
-    Ok(output)
+    diag::stored_blocks(&output);
+    Ok(output.into_vec())
 }
 
 /// Helper to handle the raw compression call.
@@ -101,17 +149,24 @@ unsafe fn impl_compress(
     dst_cap: usize,
     options: &CompressOptions,
 ) -> Result<usize> {
+    // The exact size is only known once compressed; the bound always fits.
+    let too_small = || Error::DstTooSmall {
+        needed: compress_bound(data.len()),
+    };
+    // The C library takes a zero capacity for a missing buffer.
+    if dst_cap == 0 {
+        return Err(too_small());
+    }
+    env::init();
+    diag::variant();
+    let timer = metrics::start();
+    let (dict_ptr, dict_size, dict_huf_ptr) = dict_parts(
+        options.dict.as_deref().unwrap_or_default(),
+        options.dict_huf.as_deref(),
+    )?;
     let written = unsafe {
-        let (dict_ptr, dict_size) = match &options.dict {
-            Some(d) if !d.is_empty() => (d.as_ptr() as *const c_void, d.len()),
-            _ => (std::ptr::null(), 0),
-        };
-        let dict_huf_ptr = match &options.dict_huf {
-            Some(h) if !h.is_empty() => h.as_ptr() as *const c_void,
-            _ => std::ptr::null(),
-        };
         let copts = zxc_sys::zxc_compress_opts_t {
-            level: options.level as i32,
+            level: env::level(options.level) as i32,
             checksum_enabled: options.checksum as i32,
             seekable: options.seekable as i32,
             dict: dict_ptr,
@@ -119,7 +174,7 @@ unsafe fn impl_compress(
             dict_huf: dict_huf_ptr,
             ..Default::default()
         };
-        zxc_sys::zxc_compress(
+        ffi::zxc_compress(
             data.as_ptr() as *const c_void,
             data.len(),
             dst_ptr as *mut c_void,
@@ -128,25 +183,33 @@ unsafe fn impl_compress(
         )
     };
 
-    if written < 0 {
-        return Err(error_from_code(written));
-    }
-
-    if written == 0 && !data.is_empty() {
+    // Every frame has a header and a footer, that of empty input too, so
+    // nothing written is never a success.
+    let written = size_from_code(written).map_err(|e| match e {
+        Error::DstTooSmall { .. } => too_small(),
+        e => e,
+    })?;
+    if written == 0 {
         return Err(Error::InvalidData);
     }
 
-    Ok(written as usize)
+    timer.finish(Operation::Compress, data.len() as u64, written as u64);
+    Ok(written)
 }
 
 /// Compresses data into a pre-allocated buffer.
 ///
 /// Returns the number of bytes written to `output`.
 ///
+/// Empty `data` makes a frame too, of [`compress_bound(0)`](compress_bound)
+/// bytes at most.
+///
 /// # Errors
 ///
-/// Returns an [`Error`] if the output buffer is too small or an internal
-/// error occurs.
+/// Returns an [`Error`] if the output buffer is too small, including an
+/// empty one, or an internal error occurs. [`Error::DstTooSmall`] then
+/// gives [`compress_bound`] of `data` as the size needed: the frame may
+/// turn out smaller, but always fits in that.
 ///
 /// # Example
 ///
@@ -154,40 +217,152 @@ unsafe fn impl_compress(
 /// use zxc::{compress_to, compress_bound, CompressOptions};
 ///
 /// let data = b"Hello, world!";
-/// let mut output = vec![0u8; compress_bound(data.len()) as usize];
+/// let mut output = vec![0u8; compress_bound(data.len())];
 /// let size = compress_to(data, &mut output, &CompressOptions::default())?;
 /// output.truncate(size);
 /// # Ok::<(), zxc::Error>(())
 /// ```
 pub fn compress_to(data: &[u8], output: &mut [u8], options: &CompressOptions) -> Result<usize> {
-    unsafe { impl_compress(data, output.as_mut_ptr(), output.len(), options) }
+    let _held = memory::reserve(Some(options.level), data.len(), 0);
+    let written = unsafe { impl_compress(data, output.as_mut_ptr(), output.len(), options)? };
+    diag::stored_blocks(output.get(..written).unwrap_or_default());
+    Ok(written)
+}
+
+/// Compresses the concatenation of `chunks` into one frame, feeding them to
+/// the encoder one by one rather than joining them first: for data held in
+/// pieces, such as a rope or the scatter buffers of a protocol.
+///
+/// The chunks go through a [`CStream`], so `options.seekable` is ignored
+/// and dictionaries are rejected with [`Error::Unsupported`]. The frame
+/// decodes like any other.
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::CompressOptions;
+///
+/// let parts = ["HTTP/1.1 200 OK\r\n", "content-type: text/plain\r\n", "\r\n", "hello"];
+/// let frame = zxc::compress_from_iter(parts, &CompressOptions::default())?;
+/// assert_eq!(zxc::decompress(&frame)?, parts.concat().as_bytes());
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn compress_from_iter<I>(chunks: I, options: &CompressOptions) -> Result<Vec<u8>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let timer = metrics::start();
+    let mut cs = CStream::new(Some(options))?;
+    let mut out = Scratch::zeroed(cs.out_size());
+    let mut frame = Vec::new();
+    let mut total_in = 0u64;
+    for chunk in chunks {
+        let mut input = chunk.as_ref();
+        total_in += input.len() as u64;
+        loop {
+            let p = cs.compress(input, &mut out)?;
+            frame.extend_from_slice(out.get(..p.produced).unwrap_or_default());
+            input = input.get(p.consumed..).unwrap_or_default();
+            if input.is_empty() && p.pending == 0 {
+                break;
+            }
+        }
+    }
+    loop {
+        let p = cs.end(&mut out)?;
+        frame.extend_from_slice(out.get(..p.produced).unwrap_or_default());
+        if p.pending == 0 {
+            break;
+        }
+    }
+    diag::stored_blocks(&frame);
+    timer.finish(Operation::Compress, total_in, frame.len() as u64);
+    Ok(frame)
 }
 
 /// Returns the original uncompressed size from compressed data.
 ///
-/// This reads the footer without performing decompression.
-/// Returns `None` if the data is invalid or truncated.
+/// This reads the header and footer without decompressing, except for a
+/// footer that says 0 or more than the frame can hold: that frame is
+/// decoded into a 1-byte buffer to tell a valid empty frame from a damaged
+/// one.
+///
+/// # Errors
+///
+/// - [`Error::SrcTooSmall`] if `compressed` is shorter than a header and a
+///   footer.
+/// - [`Error::BadMagic`] if it does not start with the ZXC magic word.
+/// - [`Error::CorruptData`] if the footer gives a size the frame cannot
+///   hold.
+/// - The decoder's error ([`Error::BadHeader`], [`Error::BadVersion`], …)
+///   for a frame whose footer says 0 but which is not a valid empty frame.
 ///
 /// # Example
 ///
 /// ```rust
-/// use zxc::{compress, decompressed_size, Level};
+/// use zxc::{compress, decompressed_size, Error, Level};
 ///
 /// let data = b"Hello, world!";
 /// let compressed = compress(data, Level::Default, None)?;
-/// let size = decompressed_size(&compressed);
-/// assert_eq!(size, Some(data.len() as u64));
+/// assert_eq!(decompressed_size(&compressed)?, data.len() as u64);
+///
+/// let empty = compress(b"", Level::Default, None)?;
+/// assert_eq!(decompressed_size(&empty)?, 0);
+/// assert!(matches!(decompressed_size(b"garbage, not a frame"), Err(Error::SrcTooSmall)));
 /// # Ok::<(), zxc::Error>(())
 /// ```
-pub fn decompressed_size(compressed: &[u8]) -> Option<u64> {
-    let size = unsafe {
-        zxc_sys::zxc_get_decompressed_size(compressed.as_ptr() as *const c_void, compressed.len())
+pub fn decompressed_size(compressed: &[u8]) -> Result<u64> {
+    let size = footer_size(compressed);
+    if size > 0 {
+        return Ok(size);
+    }
+    if compressed.len() < crate::FRAME_HEADER_SIZE + crate::FOOTER_SIZE {
+        return Err(Error::SrcTooSmall);
+    }
+    if compressed
+        .first_chunk::<4>()
+        .is_none_or(|m| u32::from_le_bytes(*m) != MAGIC_WORD)
+    {
+        return Err(Error::BadMagic);
+    }
+    // The footer says 0 or is implausible. Decoding into a 1-byte buffer
+    // (an empty one only checks the footer) fails unless this is a valid
+    // empty frame; checksums are skipped, as for the footer read.
+    let mut probe = [0u8; 1];
+    let dopts = zxc_sys::zxc_decompress_opts_t::default();
+    let code = unsafe {
+        ffi::zxc_decompress(
+            compressed.as_ptr() as *const c_void,
+            compressed.len(),
+            probe.as_mut_ptr() as *mut c_void,
+            probe.len(),
+            &dopts,
+        )
     };
+    match size_from_code(code) {
+        Ok(0) => Ok(0),
+        Ok(_) => Err(Error::CorruptData),
+        Err(Error::DstTooSmall { .. }) => Err(Error::CorruptData),
+        Err(e) => Err(e),
+    }
+}
 
-    if size == 0 && !compressed.is_empty() {
-        None
-    } else {
-        Some(size)
+/// The size in the footer of `compressed`, or 0 if the C library finds the
+/// frame invalid or the size implausible.
+fn footer_size(compressed: &[u8]) -> u64 {
+    unsafe {
+        ffi::zxc_get_decompressed_size(compressed.as_ptr() as *const c_void, compressed.len())
+    }
+}
+
+/// Fails with [`Error::DstTooSmall`] before decoding when the footer of
+/// `compressed` declares more than `dst_cap` bytes.
+pub(crate) fn check_room(compressed: &[u8], dst_cap: usize) -> Result<()> {
+    let needed = usize::try_from(footer_size(compressed)).unwrap_or(usize::MAX);
+    match needed > dst_cap {
+        true => Err(Error::DstTooSmall { needed }),
+        false => Ok(()),
     }
 }
 
@@ -196,6 +371,9 @@ pub fn decompressed_size(compressed: &[u8]) -> Option<u64> {
 /// This is a convenience function that queries the output size and allocates
 /// the buffer automatically. For zero-allocation usage, see [`decompress_to`].
 ///
+/// The frame of empty input decodes to an empty `Vec`; empty `compressed`
+/// fails with [`Error::SrcTooSmall`], as with [`decompressed_size`].
+///
 /// # Example
 ///
 /// ```rust
@@ -213,14 +391,25 @@ pub fn decompress(compressed: &[u8]) -> Result<Vec<u8>> {
 
 /// Decompresses data with full options control.
 pub fn decompress_with_options(compressed: &[u8], options: &DecompressOptions) -> Result<Vec<u8>> {
-    // `decompressed_size` returns None for an ambiguous 0 (a valid empty-payload
-    // archive or invalid input); fall back to 0 and let the C decoder validate
-    // the frame (it returns a negative error code on genuinely corrupt input).
-    let size = decompressed_size(compressed).unwrap_or(0) as usize;
-    let mut output = Vec::with_capacity(size);
+    // The footer read gives 0 for a valid empty frame and for invalid input
+    // alike; the C decoder then tells them apart (it returns a negative error
+    // code on genuinely corrupt input).
+    let size = size_to_usize(footer_size(compressed))?;
+    let _held = memory::reserve(None, size, size);
+    let mut output = Scratch::try_with_capacity(size)?;
 
+    // The buffer is sized from the footer, so running out of room means the
+    // frame misstates its size (a truncated one, typically), or that its
+    // header is one the footer read gives 0 for.
     let written =
-        unsafe { impl_decompress(compressed, output.as_mut_ptr(), output.capacity(), options)? };
+        unsafe { impl_decompress(compressed, output.as_mut_ptr(), output.capacity(), options) }
+            .map_err(|e| match e {
+                Error::DstTooSmall { .. } => match header(&mut { compressed }) {
+                    Ok(_) => locate(compressed, options, Error::CorruptData),
+                    Err(e) => e,
+                },
+                e => e,
+            })?;
 
     if written != size {
         return Err(Error::InvalidData);
@@ -229,7 +418,7 @@ pub fn decompress_with_options(compressed: &[u8], options: &DecompressOptions) -
     unsafe {
         output.set_len(written);
     }
-    Ok(output)
+    Ok(output.into_vec())
 }
 
 /// Helper to handle the raw decompression call.
@@ -244,15 +433,21 @@ unsafe fn impl_decompress(
     dst_cap: usize,
     options: &DecompressOptions,
 ) -> Result<usize> {
+    // No input is a truncated frame, not a missing one.
+    if compressed.is_empty() {
+        return Err(Error::SrcTooSmall);
+    }
+    check_version(compressed, options.format_versions())?;
+    check_room(compressed, dst_cap)?;
+    env::init();
+    diag::variant();
+    diag::checksum(options.verify_checksum, compressed.get(6).copied());
+    let timer = metrics::start();
+    let (dict_ptr, dict_size, dict_huf_ptr) = dict_parts(
+        options.dict.as_deref().unwrap_or_default(),
+        options.dict_huf.as_deref(),
+    )?;
     let written = unsafe {
-        let (dict_ptr, dict_size) = match &options.dict {
-            Some(d) if !d.is_empty() => (d.as_ptr() as *const c_void, d.len()),
-            _ => (std::ptr::null(), 0),
-        };
-        let dict_huf_ptr = match &options.dict_huf {
-            Some(h) if !h.is_empty() => h.as_ptr() as *const c_void,
-            _ => std::ptr::null(),
-        };
         let dopts = zxc_sys::zxc_decompress_opts_t {
             checksum_enabled: if options.verify_checksum { 1 } else { 0 },
             dict: dict_ptr,
@@ -260,7 +455,7 @@ unsafe fn impl_decompress(
             dict_huf: dict_huf_ptr,
             ..Default::default()
         };
-        zxc_sys::zxc_decompress(
+        ffi::zxc_decompress(
             compressed.as_ptr() as *const c_void,
             compressed.len(),
             dst_ptr as *mut c_void,
@@ -269,30 +464,102 @@ unsafe fn impl_decompress(
         )
     };
 
-    if written < 0 {
-        return Err(error_from_code(written));
-    }
-
     // A non-negative return is a success: `written == 0` is valid (empty payload).
-    Ok(written as usize)
+    let written = size_from_code(written).map_err(|e| locate(compressed, options, e))?;
+    timer.finish(
+        Operation::Decompress,
+        compressed.len() as u64,
+        written as u64,
+    );
+    Ok(written)
 }
 
 /// Decompresses data into a pre-allocated buffer.
 ///
-/// Returns the number of bytes written to `output`.
+/// Returns the number of bytes written to `output`. The frame of empty
+/// input decodes to 0 bytes, into any `output`, an empty one included.
 ///
 /// # Errors
 ///
 /// Returns an error if decompression fails due to invalid data, corruption,
-/// or insufficient output buffer size.
+/// or insufficient output buffer size. Empty `compressed` is a truncated
+/// frame: [`Error::SrcTooSmall`].
+///
+/// An `output` shorter than the size in the footer fails with
+/// [`Error::DstTooSmall`] carrying that size, before anything is written:
+///
+/// ```rust
+/// use zxc::{DecompressOptions, Error};
+///
+/// let frame = zxc::compress(&[7u8; 5000], zxc::Level::Default, None)?;
+/// let mut output = vec![0u8; 16];
+/// let n = loop {
+///     match zxc::decompress_to(&frame, &mut output, &DecompressOptions::default()) {
+///         Err(Error::DstTooSmall { needed }) => output.resize(needed, 0),
+///         other => break other?,
+///     }
+/// };
+/// assert_eq!(n, 5000);
+/// # Ok::<(), zxc::Error>(())
+/// ```
 pub fn decompress_to(
     compressed: &[u8],
     output: &mut [u8],
     options: &DecompressOptions,
 ) -> Result<usize> {
+    let _held = memory::reserve(None, output.len(), 0);
     unsafe { impl_decompress(compressed, output.as_mut_ptr(), output.len(), options) }
 }
 
+/// Decompresses data into `dst_len` bytes of memory at `dst`, which need
+/// not be initialized nor owned by Rust: a GPU staging buffer, a shared
+/// memory segment, a device region mapped into the process.
+///
+/// Returns the number of bytes written, from the start of the region.
+/// Bytes past them may have been written too, as scratch, up to
+/// `dst_len`. Otherwise this is [`decompress_to`], and fails in the same
+/// ways.
+///
+/// # Safety
+///
+/// Unless `dst_len` is 0, `dst` must be non-null and valid for writes of
+/// `dst_len` bytes for the duration of the call, and nothing else may
+/// read or write them meanwhile, including another thread or a device.
+/// The region must not overlap `compressed`. It is only written to, never
+/// read, so its contents before the call do not matter.
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::DecompressOptions;
+///
+/// let frame = zxc::compress(b"into foreign memory", zxc::Level::Default, None)?;
+/// let mut region: Vec<u8> = Vec::with_capacity(64);
+/// let n = unsafe {
+///     zxc::decompress_into_raw(
+///         &frame,
+///         region.as_mut_ptr(),
+///         region.capacity(),
+///         &DecompressOptions::default(),
+///     )?
+/// };
+/// unsafe { region.set_len(n) };
+/// assert_eq!(region, b"into foreign memory");
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub unsafe fn decompress_into_raw(
+    compressed: &[u8],
+    dst: *mut u8,
+    dst_len: usize,
+    options: &DecompressOptions,
+) -> Result<usize> {
+    if dst.is_null() && dst_len > 0 {
+        return Err(Error::NullInput);
+    }
+    let _held = memory::reserve(None, dst_len, 0);
+    unsafe { impl_decompress(compressed, dst, dst_len, options) }
+}
+
 /// Returns the library version as a tuple (major, minor, patch).
 pub fn version() -> (u32, u32, u32) {
     (ZXC_VERSION_MAJOR, ZXC_VERSION_MINOR, ZXC_VERSION_PATCH)
@@ -315,8 +582,8 @@ pub fn version_string() -> String {
 /// libzxc matches the version `zxc-sys` was built against.
 pub fn runtime_version() -> &'static str {
     unsafe {
-        let ptr = zxc_sys::zxc_version_string();
-        std::ffi::CStr::from_ptr(ptr).to_str().unwrap_or("")
+        let ptr = ffi::zxc_version_string();
+        core::ffi::CStr::from_ptr(ptr).to_str().unwrap_or("")
     }
 }
 
@@ -324,27 +591,125 @@ pub fn runtime_version() -> &'static str {
 ///
 /// Equivalent to [`Level::Fastest`] as an integer.
 pub fn min_level() -> i32 {
-    unsafe { zxc_sys::zxc_min_level() }
+    unsafe { ffi::zxc_min_level() }
 }
 
 /// Returns the maximum supported compression level (currently `7`).
 ///
 /// Equivalent to [`Level::Ultra`] as an integer.
 pub fn max_level() -> i32 {
-    unsafe { zxc_sys::zxc_max_level() }
+    unsafe { ffi::zxc_max_level() }
 }
 
 /// Returns the default compression level (currently `3`).
 ///
 /// Equivalent to [`Level::Default`] as an integer.
 pub fn default_level() -> i32 {
-    unsafe { zxc_sys::zxc_default_level() }
+    unsafe { ffi::zxc_default_level() }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::*;
 
+    /// Frames written on x86_64 must decode identically on every target,
+    /// big-endian ones included.
+    #[test]
+    fn test_little_endian_fixtures_decode() {
+        use crate::fixtures::{LE_DEFAULT_CHECKSUM, LE_DENSITY, input};
+
+        let expected = input();
+        for frame in [LE_DEFAULT_CHECKSUM, LE_DENSITY] {
+            assert_eq!(decompressed_size(frame).unwrap(), expected.len() as u64);
+            assert_eq!(decompress(frame).unwrap(), expected);
+        }
+    }
+
+    /// Frames from the C CLI decode bit-exactly, checksums verified.
+    #[test]
+    fn test_cli_fixtures_decode() {
+        let expected = crate::fixtures::input();
+        for (level, checksum, frame) in crate::fixtures::CLI {
+            let what = format!("level {level}, checksum {checksum}");
+            assert_eq!(
+                decompressed_size(frame).unwrap(),
+                expected.len() as u64,
+                "{what}"
+            );
+            assert_eq!(decompress(frame).unwrap(), expected, "{what}");
+        }
+    }
+
+    /// Every fixture is of the current format version; frames naming any
+    /// other, older or newer, fail with `BadVersion` rather than decode,
+    /// and so do frames outside the range the caller accepts.
+    #[test]
+    fn test_format_version_policy() {
+        use crate::fixtures::{CLI, LE_DEFAULT_CHECKSUM, LE_DENSITY, input};
+
+        let pinned = DecompressOptions::default().with_format_versions(7..=7);
+        let frames = [LE_DEFAULT_CHECKSUM, LE_DENSITY];
+        for frame in frames.into_iter().chain(CLI.map(|(_, _, f)| f)) {
+            assert_eq!(frame[4], FORMAT_VERSION);
+            assert_eq!(decompress_with_options(frame, &pinned).unwrap(), input());
+        }
+
+        let with_version = |version: u8| {
+            let mut frame = LE_DEFAULT_CHECKSUM.to_vec();
+            frame[4] = version;
+            frame[14..16].fill(0);
+            let crc = crate::hash::hash16(frame.first_chunk().unwrap());
+            frame[14..16].copy_from_slice(&crc.to_le_bytes());
+            frame
+        };
+        let any = DecompressOptions::default().with_format_versions(0..=u8::MAX);
+        for version in (1..FORMAT_VERSION).chain([FORMAT_VERSION + 1, u8::MAX]) {
+            let frame = with_version(version);
+            assert!(matches!(decompress(&frame), Err(Error::BadVersion)));
+            assert!(matches!(
+                decompress_with_options(&frame, &any),
+                Err(Error::BadVersion)
+            ));
+            assert!(matches!(
+                decompress_blocks(&frame).next(),
+                Some(Err(Error::BadVersion))
+            ));
+        }
+        assert_eq!(
+            decompress_with_options(&with_version(FORMAT_VERSION), &any).unwrap(),
+            input()
+        );
+
+        let frame = compress(b"pinned", Level::Default, None).unwrap();
+        for versions in [1..=6, 8..=9] {
+            let opts = DecompressOptions::default().with_format_versions(versions);
+            assert!(matches!(
+                decompress_with_options(&frame, &opts),
+                Err(Error::BadVersion)
+            ));
+            assert!(matches!(
+                Blocks::new(&frame, &opts).next(),
+                Some(Err(Error::BadVersion))
+            ));
+            let mut out = [0u8; 16];
+            assert!(matches!(
+                decompress_to(&frame, &mut out, &opts),
+                Err(Error::BadVersion)
+            ));
+        }
+    }
+
+    /// A frame written on this host decodes back, whatever its endianness.
+    #[test]
+    fn test_fixture_input_roundtrip_native() {
+        let data = crate::fixtures::input();
+        for level in [Level::Default, Level::Density] {
+            let compressed =
+                compress_with_options(&data, &CompressOptions::with_level(level)).unwrap();
+            assert_eq!(decompress(&compressed).unwrap(), data, "{level:?}");
+        }
+    }
+
     #[test]
     fn test_roundtrip() {
         let data = b"Hello, ZXC! This is a test of the safe Rust wrapper.";
@@ -369,6 +734,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_level_numbers() {
+        assert_eq!(Level::iter().len(), Level::ALL.len());
+        assert_eq!(Level::iter().next_back(), Some(Level::Ultra));
+        for level in Level::iter() {
+            assert_eq!(Level::try_from(level.as_i32()).unwrap(), level);
+            assert_eq!(i32::from(level), level.as_i32());
+        }
+        assert_eq!(Level::Fastest.as_i32(), min_level());
+        assert_eq!(Level::Ultra.as_i32(), max_level());
+        assert_eq!(Level::default().as_i32(), default_level());
+        for n in [i32::MIN, -1, 0, 8, i32::MAX] {
+            assert!(matches!(Level::try_from(n), Err(Error::BadLevel)));
+        }
+    }
+
     #[test]
     fn test_empty() {
         // Empty input is valid: it produces a well-formed (header + EOF + footer)
@@ -382,6 +763,76 @@ mod tests {
         );
     }
 
+    /// Sizes and offsets past `u32::MAX` survive the round trip.
+    #[test]
+    // The mock backend's frames are stored, as large as the data.
+    #[cfg(all(
+        feature = "expensive-tests",
+        target_pointer_width = "64",
+        not(feature = "mock-backend")
+    ))]
+    fn test_over_4_gib() {
+        // The high half of the position is mixed into the data, so that
+        // anything cut to 32 bits on the way shows as a mismatch.
+        let len = (4usize << 30) + (3 << 20) + 17;
+        let byte = |i: usize| (i % 251) as u8 ^ (i >> 32) as u8;
+        let data: Vec<u8> = (0..len).map(byte).collect();
+        let frame = compress(&data, Level::Fastest, Some(true)).unwrap();
+        drop(data);
+        assert_eq!(decompressed_size(&frame).unwrap(), len as u64);
+
+        let mut back = decompress(&frame).unwrap();
+        assert_eq!(back.len(), len);
+        assert!(back.iter().enumerate().all(|(i, &b)| b == byte(i)));
+
+        back.fill(0);
+        let n = decompress_to(&frame, &mut back, &DecompressOptions::default()).unwrap();
+        assert_eq!(n, len);
+        assert!(back.iter().enumerate().all(|(i, &b)| b == byte(i)));
+        assert!(matches!(
+            decompress_to(&frame, &mut back[..len - 1], &DecompressOptions::default()),
+            Err(Error::DstTooSmall { needed }) if needed == len
+        ));
+    }
+
+    #[test]
+    fn test_empty_edges() {
+        let opts = CompressOptions::default();
+        let dopts = DecompressOptions::default();
+        let frame = compress_with_options(b"", &opts).unwrap();
+        assert!(frame.len() <= compress_bound(0));
+        assert_eq!(decompressed_size(&frame).unwrap(), 0);
+        assert_eq!(compress_from_iter([b""], &opts).unwrap(), frame);
+
+        // An empty output is too small for any frame.
+        assert!(matches!(
+            compress_to(b"", &mut [], &opts),
+            Err(Error::DstTooSmall { needed }) if needed == compress_bound(0)
+        ));
+        assert!(matches!(
+            compress_to(b"x", &mut [], &opts),
+            Err(Error::DstTooSmall { needed }) if needed == compress_bound(1)
+        ));
+        let mut exact = vec![0u8; frame.len()];
+        assert_eq!(compress_to(b"", &mut exact, &opts).unwrap(), frame.len());
+        assert!(matches!(
+            compress_to(b"", &mut exact[..frame.len() - 1], &opts),
+            Err(Error::DstTooSmall { needed }) if needed == compress_bound(0)
+        ));
+
+        // The empty frame decodes to nothing, even into nothing.
+        assert_eq!(decompress_to(&frame, &mut [], &dopts).unwrap(), 0);
+        assert_eq!(decompress_to(&frame, &mut [0u8; 8], &dopts).unwrap(), 0);
+
+        // No input is a truncated frame.
+        assert!(matches!(decompressed_size(b""), Err(Error::SrcTooSmall)));
+        assert!(matches!(decompress(b""), Err(Error::SrcTooSmall)));
+        assert!(matches!(
+            decompress_to(b"", &mut [0u8; 8], &dopts),
+            Err(Error::SrcTooSmall)
+        ));
+    }
+
     #[test]
     fn test_checksum_options() {
         let data = b"Test with and without checksum";
@@ -404,8 +855,35 @@ mod tests {
     fn test_decompressed_size() {
         let data = b"Hello, world! Testing decompressed_size function.";
         let compressed = compress(data, Level::Default, None).unwrap();
-        let size = decompressed_size(&compressed);
-        assert_eq!(size, Some(data.len() as u64));
+        assert_eq!(decompressed_size(&compressed).unwrap(), data.len() as u64);
+
+        // A valid empty frame is size 0, not an error.
+        let empty = compress(b"", Level::Default, Some(true)).unwrap();
+        assert_eq!(decompressed_size(&empty).unwrap(), 0);
+
+        assert!(matches!(decompressed_size(b""), Err(Error::SrcTooSmall)));
+        assert!(matches!(
+            decompressed_size(&compressed[..20]),
+            Err(Error::SrcTooSmall)
+        ));
+        assert!(decompressed_size(&compressed[..compressed.len() - 5]).is_err());
+        let mut garbage = compressed.clone();
+        garbage[0] ^= 0xff;
+        assert!(matches!(decompressed_size(&garbage), Err(Error::BadMagic)));
+
+        // A footer claiming more than the frame can hold.
+        let mut forged = compressed.clone();
+        let at = forged.len() - 12;
+        forged[at..at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            decompressed_size(&forged),
+            Err(Error::CorruptData)
+        ));
+
+        // A damaged header behind a footer that says 0.
+        let mut bad_header = empty.clone();
+        bad_header[4] ^= 0xff;
+        assert!(decompressed_size(&bad_header).is_err());
     }
 
     #[test]
@@ -444,6 +922,85 @@ mod tests {
         );
     }
 
+    /// Damage is reported with the block it sits in and where that block
+    /// starts, or against the trailer when every block is intact.
+    #[test]
+    fn test_corruption_is_located() {
+        let data: Vec<u8> = (0..3 * 512 * 1024u32)
+            .map(|i| (i % 251) as u8 ^ (i >> 13) as u8)
+            .collect();
+        let frame = compress(&data, Level::Fastest, Some(true)).unwrap();
+        let block_len = |at: usize| {
+            8 + u32::from_le_bytes(frame[at + 3..at + 7].try_into().unwrap()) as usize + 4
+        };
+        let block1 = 16 + block_len(16);
+        let block2 = block1 + block_len(block1);
+
+        let mut bad = frame.clone();
+        bad[block1 + 20] ^= 0x01;
+        let err = decompress(&bad).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: Some(1), offset, .. } if offset == block1 as u64),
+            "{err:?}"
+        );
+        assert!(
+            matches!(
+                err.cause(),
+                Error::ChecksumMismatch { block: Some(1), expected, actual } if expected != actual
+            ),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("block 1"), "{err}");
+
+        let err = decompress(&frame[..block2 + 100]).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: Some(2), offset, .. } if offset == block2 as u64),
+            "{err:?}"
+        );
+
+        let mut bad = frame.clone();
+        let last = bad.len() - 1;
+        bad[last] ^= 0x01;
+        let err = decompress(&bad).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: None, .. }),
+            "{err:?}"
+        );
+        let stored = u32::from_le_bytes(bad[last - 3..].try_into().unwrap());
+        assert!(
+            matches!(
+                err.cause(),
+                Error::ChecksumMismatch { block: None, expected, actual }
+                    if *expected == u64::from(stored) && *actual == u64::from(stored ^ 1 << 24)
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_decompress_into_raw() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 97) as u8).collect();
+        let frame = compress(&data, Level::Default, None).unwrap();
+        let opts = DecompressOptions::default();
+
+        let mut region = Vec::<core::mem::MaybeUninit<u8>>::with_capacity(data.len() + 64);
+        let ptr = region.as_mut_ptr() as *mut u8;
+        let n = unsafe { decompress_into_raw(&frame, ptr, region.capacity(), &opts) }.unwrap();
+        assert_eq!(n, data.len());
+        assert_eq!(unsafe { core::slice::from_raw_parts(ptr, n) }, &data[..]);
+
+        let short = unsafe { decompress_into_raw(&frame, ptr, data.len() - 1, &opts) };
+        assert!(matches!(
+            short,
+            Err(Error::DstTooSmall { needed }) if needed == data.len()
+        ));
+        let null = unsafe { decompress_into_raw(&frame, core::ptr::null_mut(), 10, &opts) };
+        assert!(matches!(null, Err(Error::NullInput)));
+        let empty = compress(b"", Level::Default, None).unwrap();
+        let none = unsafe { decompress_into_raw(&empty, core::ptr::null_mut(), 0, &opts) };
+        assert_eq!(none.unwrap(), 0);
+    }
+
     #[test]
     fn test_error_messages() {
         // Verify error messages are descriptive
@@ -451,7 +1008,14 @@ mod tests {
             (Error::Memory, "memory allocation failed"),
             (Error::BadChecksum, "checksum verification failed"),
             (Error::CorruptData, "corrupted compressed data"),
-            (Error::DstTooSmall, "destination buffer too small"),
+            (
+                Error::DstTooSmall { needed: 0 },
+                "destination buffer too small",
+            ),
+            (
+                Error::DstTooSmall { needed: 4096 },
+                "destination buffer too small (4096 bytes needed)",
+            ),
         ];
 
         for (error, expected_msg) in errors {
@@ -465,10 +1029,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_size_to_usize_never_truncates() {
+        use crate::error::size_to_usize;
+
+        assert_eq!(size_to_usize(1 << 20).unwrap(), 1 << 20);
+        let r = size_to_usize(u64::MAX);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(r.unwrap(), usize::MAX);
+        #[cfg(not(target_pointer_width = "64"))]
+        assert!(matches!(r, Err(Error::TooLarge(u64::MAX))));
+    }
+
+    #[test]
+    fn test_compress_bound_matches_c() {
+        for size in [0, 1, 100, 4095, 4096, 4097, 1 << 20, (1 << 20) + 1, 3 << 29] {
+            let c = unsafe { ffi::zxc_compress_bound(size) };
+            assert_eq!(compress_bound(size) as u64, c, "input size {size}");
+        }
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(compress_bound(1 << 40) as u64, unsafe {
+            ffi::zxc_compress_bound(1 << 40)
+        });
+        // Past the C guard, and where the bound would not fit a usize.
+        assert_eq!(compress_bound(usize::MAX), 0);
+        assert_eq!(compress_bound(usize::MAX - (usize::MAX >> 8)), 0);
+        const _: () = assert!(compress_bound(0) > 0);
+    }
+
+    #[test]
+    fn test_format_limits() {
+        assert_eq!(MIN_BLOCK_SIZE, 4 << 10);
+        assert_eq!(MAX_BLOCK_SIZE, 2 << 20);
+        assert_eq!(compress_bound(MAX_INPUT_SIZE + 1), 0);
+        // An empty frame: header, end-of-frame block, footer.
+        let empty = compress(b"", Level::Default, None).unwrap();
+        assert_eq!(empty.len(), FRAME_HEADER_SIZE + 8 + FOOTER_SIZE);
+    }
+
     #[test]
     fn test_compress_to_buffer() {
         let data = b"Testing compress_to with pre-allocated buffer";
-        let mut output = vec![0u8; compress_bound(data.len()) as usize];
+        let mut output = vec![0u8; compress_bound(data.len())];
 
         let size = compress_to(data, &mut output, &CompressOptions::default()).unwrap();
         output.truncate(size);
@@ -477,6 +1079,29 @@ mod tests {
         assert_eq!(&decompressed[..], &data[..]);
     }
 
+    #[test]
+    fn test_compress_from_iter() {
+        let data: Vec<u8> = (0..1_100_000u32).map(|i| (i % 241) as u8).collect();
+        let rope: Vec<Vec<u8>> = data.chunks(70_001).map(<[u8]>::to_vec).collect();
+        let frame = compress_from_iter(&rope, &CompressOptions::default()).unwrap();
+        assert_eq!(decompress(&frame).unwrap(), data);
+
+        let pieces = data.split(|&b| b == 0);
+        let opts = CompressOptions::with_level(Level::Fastest).without_checksum();
+        let frame = compress_from_iter(pieces, &opts).unwrap();
+        let unsplit: Vec<u8> = data.iter().copied().filter(|&b| b != 0).collect();
+        assert_eq!(decompress(&frame).unwrap(), unsplit);
+
+        let empty = compress_from_iter(core::iter::empty::<&[u8]>(), &CompressOptions::default());
+        assert_eq!(decompress(&empty.unwrap()).unwrap(), b"");
+
+        let dict = CompressOptions::default().with_dict(vec![1u8; 1024]);
+        assert!(matches!(
+            compress_from_iter([b"x"], &dict),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
     #[test]
     fn test_large_data() {
         // 1 MB of random-ish but compressible data
@@ -485,6 +1110,7 @@ mod tests {
             .collect();
 
         let compressed = compress(&data, Level::Default, None).unwrap();
+        #[cfg(not(feature = "mock-backend"))]
         assert!(compressed.len() < data.len()); // Should compress
 
         let decompressed = decompress(&compressed).unwrap();
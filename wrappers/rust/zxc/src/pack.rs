@@ -0,0 +1,443 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Asset packs: many named files in one compressed bundle.
+//!
+//! [`PackWriter`] concatenates assets into one seekable zxc frame and
+//! records where each starts; [`Pack`] opens the bundle, looks assets up by
+//! name in its index and decompresses only the blocks an asset spans,
+//! straight into the caller's buffer.
+//!
+//! ```rust
+//! use zxc::pack::{Pack, PackWriter};
+//!
+//! let mut writer = PackWriter::new();
+//! writer.add("shaders/sky.wgsl", b"@fragment fn main() {}")?;
+//! writer.add("levels/1.map", &[7u8; 4096])?;
+//! let bundle = writer.finish()?;
+//!
+//! let mut pack = Pack::from_bytes(bundle)?;
+//! let mut buf = vec![0u8; pack.size_of("levels/1.map").unwrap()];
+//! pack.read_into("levels/1.map", &mut buf)?;
+//! assert_eq!(buf, [7u8; 4096]);
+//! assert!(pack.read("missing.png")?.is_none());
+//! # Ok::<(), zxc::Error>(())
+//! ```
+//!
+//! # Layout
+//!
+//! ```text
+//! 0x00  4  Magic 0x9CB09AC0 (LE)
+//! 0x04  2  Version (1)
+//! 0x06  2  Reserved (0)
+//! 0x08  4  Size of the index frame (LE)
+//! 0x0C     Index: a zxc frame
+//!          Data: a seekable zxc frame, to the end of the file
+//! ```
+//!
+//! The index decompresses to an entry count (`u32`), then per asset, in
+//! name order: the name's length (`u16`), the UTF-8 name, and the asset's
+//! offset and length in the data (`u64` each), all little-endian.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::seekable::{ReadAt, Seekable};
+use crate::{CompressOptions, Error, Result, compress_with_options, decompress};
+
+/// Magic word at the start of a pack.
+pub const PACK_MAGIC: u32 = 0x9CB0_9AC0;
+
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 12;
+
+/// Builds a pack in memory. See the [module docs](self).
+#[derive(Debug, Default)]
+pub struct PackWriter {
+    opts: CompressOptions,
+    data: Vec<u8>,
+    entries: BTreeMap<String, Range<u64>>,
+}
+
+impl PackWriter {
+    /// A writer compressing with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A writer compressing with `opts`; `opts.seekable` is forced on.
+    pub fn with_options(opts: CompressOptions) -> Self {
+        Self {
+            opts,
+            ..Self::default()
+        }
+    }
+
+    /// Adds an asset.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Unsupported`] if the pack already has an asset of that name
+    /// or the name is longer than 65535 bytes.
+    pub fn add(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        if self.entries.contains_key(name) {
+            return Err(Error::Unsupported("asset name already in the pack"));
+        }
+        if u16::try_from(name.len()).is_err() {
+            return Err(Error::Unsupported("asset name longer than 65535 bytes"));
+        }
+        let start = self.data.len() as u64;
+        self.data.extend_from_slice(data);
+        self.entries
+            .insert(name.into(), start..self.data.len() as u64);
+        Ok(())
+    }
+
+    /// Compresses the assets and returns the pack.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let mut index = Vec::new();
+        index.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (name, range) in &self.entries {
+            index.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            index.extend_from_slice(name.as_bytes());
+            index.extend_from_slice(&range.start.to_le_bytes());
+            index.extend_from_slice(&(range.end - range.start).to_le_bytes());
+        }
+        let index = compress_with_options(&index, &self.opts)?;
+        let data = compress_with_options(
+            &self.data,
+            &CompressOptions {
+                seekable: true,
+                ..self.opts
+            },
+        )?;
+        let index_size =
+            u32::try_from(index.len()).map_err(|_| Error::TooLarge(index.len() as u64))?;
+
+        let mut pack = Vec::with_capacity(HEADER_SIZE + index.len() + data.len());
+        pack.extend_from_slice(&PACK_MAGIC.to_le_bytes());
+        pack.extend_from_slice(&VERSION.to_le_bytes());
+        pack.extend_from_slice(&[0, 0]);
+        pack.extend_from_slice(&index_size.to_le_bytes());
+        pack.extend_from_slice(&index);
+        pack.extend_from_slice(&data);
+        Ok(pack)
+    }
+
+    /// Compresses the assets and writes the pack to `writer`.
+    pub fn write_to<W: Write>(self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.finish().map_err(io::Error::other)?)
+    }
+}
+
+/// An asset in the index.
+#[derive(Debug)]
+struct Entry {
+    /// The name, in [`Pack::names`]
+    name: Range<usize>,
+    offset: u64,
+    len: u64,
+}
+
+/// An open pack. See the [module docs](self).
+///
+/// Reads go through `&mut self`, as the decoder keeps per-pack state; open
+/// the pack once per thread to read from several.
+pub struct Pack {
+    names: String,
+    entries: Vec<Entry>,
+    /// `None` when every asset is empty, as a frame without data has no
+    /// seek table to open.
+    data: Option<Seekable>,
+}
+
+impl core::fmt::Debug for Pack {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pack")
+            .field("assets", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Pack {
+    /// Opens a pack in memory.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Self::open_reader(bytes)
+    }
+
+    /// Opens a pack file. The file stays open, and assets are read from it
+    /// as they are requested.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path).map_err(|_| Error::Io)?;
+        Self::open_reader(FileAt(RefCell::new(file)))
+    }
+
+    /// Opens a pack from any positional reader.
+    pub fn open_reader<R: ReadAt + Send + 'static>(reader: R) -> Result<Self> {
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_at(&mut header, 0)
+            .map_err(|_| Error::SrcTooSmall)?;
+        let (magic, rest) = header.split_at(4);
+        if magic != PACK_MAGIC.to_le_bytes() {
+            return Err(Error::BadMagic);
+        }
+        if rest.get(..2) != Some(&VERSION.to_le_bytes()[..]) {
+            return Err(Error::BadVersion);
+        }
+        let mut size = [0u8; 4];
+        size.copy_from_slice(rest.get(4..8).unwrap_or_default());
+        let index_size = u64::from(u32::from_le_bytes(size));
+
+        let data_start = HEADER_SIZE as u64 + index_size;
+        if data_start > reader.size() {
+            return Err(Error::SrcTooSmall);
+        }
+        let mut index = std::vec![0u8; index_size as usize];
+        reader
+            .read_at(&mut index, HEADER_SIZE as u64)
+            .map_err(|_| Error::Io)?;
+        let (names, entries) = parse_index(&decompress(&index)?)?;
+
+        let data = match entries.iter().all(|e| e.len == 0) {
+            true => None,
+            false => Some(Seekable::open_reader(Window {
+                start: data_start,
+                size: reader.size() - data_start,
+                inner: reader,
+            })?),
+        };
+        Ok(Self {
+            names,
+            entries,
+            data,
+        })
+    }
+
+    /// Number of assets.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the pack has no assets.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The asset names, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| self.name(e))
+    }
+
+    /// The size of the asset `name`, or `None` if the pack has none.
+    pub fn size_of(&self, name: &str) -> Option<usize> {
+        self.find(name).and_then(|e| usize::try_from(e.len).ok())
+    }
+
+    /// Decompresses the asset `name` into the start of `dst`, and returns
+    /// its size, or `None` if the pack has no such asset.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::DstTooSmall`] if `dst` is shorter than the asset, and any
+    /// decompression error.
+    pub fn read_into(&mut self, name: &str, dst: &mut [u8]) -> Result<Option<usize>> {
+        let Some(entry) = self.find(name) else {
+            return Ok(None);
+        };
+        let (offset, len) = (entry.offset, entry.len);
+        let len = usize::try_from(len).map_err(|_| Error::TooLarge(len))?;
+        let dst = dst
+            .get_mut(..len)
+            .ok_or(Error::DstTooSmall { needed: len })?;
+        let Some(data) = self.data.as_mut().filter(|_| len > 0) else {
+            return Ok(Some(0));
+        };
+        let n = data.decompress_range(dst, offset, len)?;
+        if n != len {
+            return Err(Error::CorruptData);
+        }
+        Ok(Some(n))
+    }
+
+    /// Decompresses the asset `name` into a new buffer, or returns `None` if
+    /// the pack has no such asset.
+    pub fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>> {
+        let Some(size) = self.size_of(name) else {
+            return Ok(None);
+        };
+        let mut buf = std::vec![0u8; size];
+        self.read_into(name, &mut buf)?;
+        Ok(Some(buf))
+    }
+
+    fn name(&self, entry: &Entry) -> &str {
+        self.names.get(entry.name.clone()).unwrap_or_default()
+    }
+
+    fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .binary_search_by(|e| self.name(e).cmp(name))
+            .ok()
+            .and_then(|i| self.entries.get(i))
+    }
+}
+
+/// Parses the decompressed index into the names, back to back, and the
+/// entries pointing into them.
+fn parse_index(index: &[u8]) -> Result<(String, Vec<Entry>)> {
+    let mut rest = index;
+    let mut take = |n: usize| -> Result<&[u8]> {
+        let (head, tail) = rest.split_at_checked(n).ok_or(Error::InvalidData)?;
+        rest = tail;
+        Ok(head)
+    };
+    let word = |b: &[u8]| b.iter().rev().fold(0u64, |v, &x| (v << 8) | u64::from(x));
+
+    let count = word(take(4)?) as usize;
+    let mut names = String::new();
+    let mut entries = Vec::with_capacity(count.min(index.len() / 18));
+    for _ in 0..count {
+        let name_len = word(take(2)?) as usize;
+        let name = core::str::from_utf8(take(name_len)?).map_err(|_| Error::InvalidData)?;
+        let start = names.len();
+        names.push_str(name);
+        let (offset, len) = (word(take(8)?), word(take(8)?));
+        entries.push(Entry {
+            name: start..names.len(),
+            offset,
+            len,
+        });
+    }
+    let sorted = entries.windows(2).all(|w| match w {
+        [a, b] => names.get(a.name.clone()) < names.get(b.name.clone()),
+        _ => true,
+    });
+    if !sorted {
+        return Err(Error::InvalidData);
+    }
+    Ok((names, entries))
+}
+
+/// The part of a reader from `start` on.
+struct Window<R> {
+    inner: R,
+    start: u64,
+    size: u64,
+}
+
+impl<R: ReadAt> ReadAt for Window<R> {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn read_at(&self, dst: &mut [u8], offset: u64) -> io::Result<()> {
+        self.inner.read_at(dst, self.start + offset)
+    }
+}
+
+/// A file read by position.
+struct FileAt(RefCell<File>);
+
+impl ReadAt for FileAt {
+    fn size(&self) -> u64 {
+        self.0
+            .try_borrow()
+            .ok()
+            .and_then(|f| f.metadata().ok())
+            .map_or(0, |m| m.len())
+    }
+
+    fn read_at(&self, dst: &mut [u8], offset: u64) -> io::Result<()> {
+        let mut file = self
+            .0
+            .try_borrow_mut()
+            .map_err(|_| io::Error::other("reentrant read"))?;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(dst)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn read_at(&self, dst: &mut [u8], offset: u64) -> io::Result<()> {
+        let src = usize::try_from(offset)
+            .ok()
+            .and_then(|start| self.get(start..start.checked_add(dst.len())?))
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+        dst.copy_from_slice(src);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+
+    use super::*;
+
+    fn asset(i: usize) -> Vec<u8> {
+        format!("asset {i}: ")
+            .repeat(i * 37 % 5000 + 1)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let mut writer = PackWriter::new();
+        for i in (0..300).rev() {
+            writer
+                .add(&format!("textures/{i:03}.ktx"), &asset(i))
+                .unwrap();
+        }
+        writer.add("empty", &[]).unwrap();
+        assert!(writer.add("empty", b"again").is_err());
+        let bundle = writer.finish().unwrap();
+
+        let path = std::env::temp_dir().join(format!("zxc-pack-{}.zxp", std::process::id()));
+        std::fs::write(&path, &bundle).unwrap();
+        for mut pack in [
+            Pack::from_bytes(bundle.clone()).unwrap(),
+            Pack::open(&path).unwrap(),
+        ] {
+            assert_eq!(pack.len(), 301);
+            assert_eq!(pack.names().next(), Some("empty"));
+            let mut buf = std::vec![0u8; 64 * 1024];
+            for i in [0, 1, 150, 299] {
+                let name = format!("textures/{i:03}.ktx");
+                let n = pack.read_into(&name, &mut buf).unwrap().unwrap();
+                assert_eq!(buf[..n], asset(i));
+            }
+            assert_eq!(pack.read("empty").unwrap().unwrap(), b"");
+            assert!(pack.read("textures/300.ktx").unwrap().is_none());
+            let mut small = [0u8; 4];
+            assert!(matches!(
+                pack.read_into("textures/299.ktx", &mut small),
+                Err(Error::DstTooSmall { needed }) if needed == asset(299).len()
+            ));
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        // An empty pack, and things that are not packs.
+        let pack = Pack::from_bytes(PackWriter::new().finish().unwrap()).unwrap();
+        assert!(pack.is_empty());
+        assert!(matches!(
+            Pack::from_bytes(crate::compress(b"x", crate::Level::Default, None).unwrap()),
+            Err(Error::BadMagic)
+        ));
+        assert!(Pack::from_bytes(bundle[..40].to_vec()).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Parquet page compression (the `parquet` feature).
+//!
+//! [`ParquetCodec`] implements the `parquet` crate's page [`Codec`] trait
+//! (public under that crate's `experimental` feature, which this feature
+//! turns on), so page writers and readers that drive a codec themselves
+//! can compress pages with zxc:
+//!
+//! ```rust
+//! use parquet::compression::Codec;
+//! use zxc::parquet::ParquetCodec;
+//!
+//! let page: Vec<u8> = (0..4096u32).flat_map(|i| (i / 8).to_le_bytes()).collect();
+//! let mut codec = ParquetCodec::default();
+//! let mut compressed = Vec::new();
+//! codec.compress(&page, &mut compressed)?;
+//! let mut restored = Vec::new();
+//! codec.decompress(&compressed, &mut restored, Some(page.len()))?;
+//! assert_eq!(restored, page);
+//! # Ok::<(), parquet::errors::ParquetError>(())
+//! ```
+//!
+//! Parquet files name their codec with a closed enum (`Compression`) that
+//! has no zxc entry, and `SerializedFileWriter` only builds codecs from it.
+//! Files other readers open must therefore keep a standard codec; this one
+//! serves engines that store pages in their own containers, like caches of
+//! decoded row groups or spill files.
+
+use std::boxed::Box;
+use std::vec::Vec;
+
+use parquet::compression::Codec;
+use parquet::errors::{ParquetError, Result};
+
+use crate::{
+    CompressOptions, DecompressOptions, Level, compress_bound, compress_to, decompress_to,
+    decompressed_size,
+};
+
+/// Compresses Parquet pages with zxc.
+///
+/// Each page becomes one zxc frame without checksums, since Parquet pages
+/// carry their own optional CRC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParquetCodec {
+    level: Level,
+}
+
+impl ParquetCodec {
+    /// A codec compressing at `level`.
+    pub fn new(level: Level) -> Self {
+        Self { level }
+    }
+}
+
+impl Codec for ParquetCodec {
+    fn compress(&mut self, input_buf: &[u8], output_buf: &mut Vec<u8>) -> Result<()> {
+        let bound = match compress_bound(input_buf.len()) {
+            0 => return Err(ParquetError::General("page too large to compress".into())),
+            b => b,
+        };
+        let start = output_buf.len();
+        output_buf.resize(start + bound, 0);
+        let opts = CompressOptions::with_level(self.level).without_checksum();
+        let dst = output_buf.get_mut(start..).unwrap_or_default();
+        let written = compress_to(input_buf, dst, &opts).map_err(external)?;
+        output_buf.truncate(start + written);
+        Ok(())
+    }
+
+    fn decompress(
+        &mut self,
+        input_buf: &[u8],
+        output_buf: &mut Vec<u8>,
+        uncompress_size: Option<usize>,
+    ) -> Result<usize> {
+        // The frame footer holds the size; a page header that disagrees
+        // with it is corrupt.
+        let size = decompressed_size(input_buf)
+            .ok()
+            .and_then(|s| usize::try_from(s).ok())
+            .filter(|&s| uncompress_size.is_none_or(|expected| expected == s))
+            .ok_or_else(|| {
+                ParquetError::General("zxc frame size does not match the page header".into())
+            })?;
+        let start = output_buf.len();
+        output_buf
+            .try_reserve_exact(size)
+            .map_err(|e| ParquetError::External(Box::new(e)))?;
+        output_buf.resize(start + size, 0);
+        let opts = DecompressOptions {
+            verify_checksum: false,
+            ..Default::default()
+        };
+        let dst = output_buf.get_mut(start..).unwrap_or_default();
+        let written = match decompress_to(input_buf, dst, &opts) {
+            Ok(n) => n,
+            Err(e) => {
+                output_buf.truncate(start);
+                return Err(external(e));
+            }
+        };
+        output_buf.truncate(start + written);
+        Ok(written)
+    }
+}
+
+fn external(e: crate::Error) -> ParquetError {
+    ParquetError::External(Box::new(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    use super::*;
+
+    /// An uncompressed file of sensor readings: sorted timestamps and a
+    /// low-cardinality string column, in several pages.
+    fn column_chunks() -> Vec<u8> {
+        let schema = parse_message_type(
+            "message readings { REQUIRED INT64 ts; REQUIRED BYTE_ARRAY sensor (UTF8); }",
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_data_page_size_limit(16 * 1024)
+            .set_dictionary_enabled(false)
+            .build();
+        let mut file = Vec::new();
+        let mut writer =
+            SerializedFileWriter::new(&mut file, Arc::new(schema), Arc::new(props)).unwrap();
+        let ts: Vec<i64> = (0..20_000).map(|i| 1_700_000_000_000 + i * 250).collect();
+        let sensors: Vec<ByteArray> = (0..20_000)
+            .map(|i| ByteArray::from(format!("sensor-{:02}", i % 12).as_str()))
+            .collect();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut col = row_group.next_column().unwrap().unwrap();
+        col.typed::<Int64Type>()
+            .write_batch(&ts, None, None)
+            .unwrap();
+        col.close().unwrap();
+        let mut col = row_group.next_column().unwrap().unwrap();
+        col.typed::<ByteArrayType>()
+            .write_batch(&sensors, None, None)
+            .unwrap();
+        col.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parquet_pages_roundtrip() {
+        let reader = SerializedFileReader::new(bytes::Bytes::from(column_chunks())).unwrap();
+        let row_group = reader.get_row_group(0).unwrap();
+        let mut codec = ParquetCodec::new(Level::Fast);
+        let (mut pages, mut raw, mut packed) = (0, 0, 0);
+        for column in 0..2 {
+            let mut page_reader = row_group.get_column_page_reader(column).unwrap();
+            while let Some(page) = page_reader.get_next_page().unwrap() {
+                let data = page.buffer();
+                let mut compressed = std::vec![0xEE];
+                codec.compress(data, &mut compressed).unwrap();
+                let mut out = std::vec![0xDD];
+                let n = codec
+                    .decompress(&compressed[1..], &mut out, Some(data.len()))
+                    .unwrap();
+                assert_eq!(n, data.len());
+                assert_eq!(&out[1..], &data[..]);
+                pages += 1;
+                raw += data.len();
+                packed += compressed.len() - 1;
+            }
+        }
+        assert!(pages > 4, "{pages} pages");
+        assert!(packed * 2 < raw, "{packed} of {raw} bytes");
+
+        // The page header's size must match the frame.
+        let mut compressed = Vec::new();
+        codec.compress(b"page", &mut compressed).unwrap();
+        let mut out = Vec::new();
+        assert!(codec.decompress(&compressed, &mut out, Some(5)).is_err());
+        assert_eq!(codec.decompress(&compressed, &mut out, None).unwrap(), 4);
+        assert!(codec.decompress(b"junk", &mut out, None).is_err());
+        assert_eq!(out, b"page");
+    }
+}
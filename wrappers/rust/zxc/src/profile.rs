@@ -0,0 +1,262 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Compressibility estimates that look at the data without compressing it.
+//!
+//! An analytic engine deciding per column whether compression pays off
+//! needs an answer much faster than compressing the column would give it.
+//! [`profile`] runs one pass per block: a byte histogram for the order-0
+//! entropy, and a single-probe hash of 4-byte sequences for how much of the
+//! block repeats earlier content. The two combine into a predicted size,
+//! which tracks [`Level::Fast`](crate::Level::Fast) output closely enough to
+//! tell "stores well" from "leave it raw", not to size buffers with.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Block size the data is cut into, as the default of the file API.
+const BLOCK_SIZE: usize = 512 * 1024;
+/// Estimated cost of one match token.
+const MATCH_COST: usize = 3;
+/// Block header and checksum.
+const BLOCK_OVERHEAD: usize = 12;
+const MIN_MATCH: usize = 4;
+const HASH_LOG: u32 = 14;
+
+/// Compressibility of one block, from [`profile`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct BlockProfile {
+    /// Offset of the block in the input.
+    pub offset: usize,
+    /// Length of the block.
+    pub len: usize,
+    /// Order-0 entropy in bits per byte, from 0 (a single byte value) to 8
+    /// (uniformly random bytes).
+    pub entropy: f64,
+    /// Fraction of the block covered by repeats of earlier content in it.
+    pub matched: f64,
+    /// Predicted compressed size, at most `len` plus the block header as
+    /// incompressible blocks are stored raw.
+    pub predicted_size: usize,
+}
+
+impl BlockProfile {
+    /// Predicted compression ratio of the block (`len / predicted_size`).
+    pub fn predicted_ratio(&self) -> f64 {
+        ratio(self.len, self.predicted_size)
+    }
+}
+
+/// Compressibility of a buffer, block by block. See [`profile`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Profile {
+    /// The blocks, in input order.
+    pub blocks: Vec<BlockProfile>,
+}
+
+impl Profile {
+    /// Length of the profiled input.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|b| b.len).sum()
+    }
+
+    /// Returns `true` if the profiled input was empty.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Order-0 entropy in bits per byte, averaged over the blocks by size.
+    pub fn entropy(&self) -> f64 {
+        let weighted: f64 = self.blocks.iter().map(|b| b.entropy * b.len as f64).sum();
+        if self.is_empty() {
+            0.0
+        } else {
+            weighted / self.len() as f64
+        }
+    }
+
+    /// Predicted compressed size, block headers included.
+    pub fn predicted_size(&self) -> usize {
+        self.blocks.iter().map(|b| b.predicted_size).sum()
+    }
+
+    /// Predicted compression ratio (`len / predicted_size`); 1.0 for an
+    /// empty input.
+    pub fn predicted_ratio(&self) -> f64 {
+        ratio(self.len(), self.predicted_size())
+    }
+}
+
+/// Estimates how well `data` compresses, block by block, without
+/// compressing it.
+///
+/// No output is produced: the pass only allocates a 64 KiB hash table. The estimate ignores matches across blocks and the entropy
+/// coding of the higher levels, so it errs on the pessimistic side for
+/// them.
+///
+/// # Example
+///
+/// ```rust
+/// let column: Vec<u8> = (0..100_000u32).flat_map(|i| (i % 100).to_le_bytes()).collect();
+/// let profile = zxc::profile(&column);
+/// if profile.predicted_ratio() < 1.1 {
+///     // Not worth the decode cost: store the column as is.
+/// }
+/// assert!(profile.predicted_ratio() > 4.0);
+/// ```
+pub fn profile(data: &[u8]) -> Profile {
+    let mut table = vec![0u32; 1 << HASH_LOG];
+    let blocks = data
+        .chunks(BLOCK_SIZE)
+        .enumerate()
+        .map(|(i, block)| {
+            table.fill(0);
+            profile_block(i * BLOCK_SIZE, block, &mut table)
+        })
+        .collect();
+    Profile { blocks }
+}
+
+fn profile_block(offset: usize, block: &[u8], table: &mut [u32]) -> BlockProfile {
+    let mut all = [0u32; 256];
+    let mut literals = [0u32; 256];
+    for &b in block {
+        if let Some(n) = all.get_mut(usize::from(b)) {
+            *n += 1;
+        }
+    }
+
+    // Greedy parse against the latest position with the same 4-byte hash,
+    // as the fast levels do, counting the literals it leaves.
+    let mut matches = 0usize;
+    let mut matched = 0usize;
+    let mut pos = 0usize;
+    while let Some(seq) = read_u32(block, pos) {
+        let slot = (seq.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize;
+        // Positions are stored plus one so that 0 marks an empty slot;
+        // blocks are far below 4 GiB.
+        let candidate = table.get(slot).map_or(0, |&c| c as usize);
+        if let Some(entry) = table.get_mut(slot) {
+            *entry = pos as u32 + 1;
+        }
+        let len = match candidate.checked_sub(1) {
+            Some(cand) if read_u32(block, cand) == Some(seq) => common_prefix(block, cand, pos),
+            _ => 0,
+        };
+        if len >= MIN_MATCH {
+            matches += 1;
+            matched += len;
+            pos += len;
+        } else {
+            if let Some(n) = block
+                .get(pos)
+                .and_then(|&b| literals.get_mut(usize::from(b)))
+            {
+                *n += 1;
+            }
+            pos += 1;
+        }
+    }
+    for &b in block.get(pos..).unwrap_or_default() {
+        if let Some(n) = literals.get_mut(usize::from(b)) {
+            *n += 1;
+        }
+    }
+
+    let literal_count = block.len() - matched;
+    let literal_bits = entropy(&literals, literal_count) * literal_count as f64;
+    let estimate = (literal_bits / 8.0).ceil() as usize + matches * MATCH_COST + BLOCK_OVERHEAD;
+    BlockProfile {
+        offset,
+        len: block.len(),
+        entropy: entropy(&all, block.len()),
+        matched: matched as f64 / block.len() as f64,
+        predicted_size: estimate.min(block.len() + BLOCK_OVERHEAD),
+    }
+}
+
+fn read_u32(block: &[u8], pos: usize) -> Option<u32> {
+    let bytes = block.get(pos..pos.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Length of the common run at `earlier` and `pos`, which may overlap.
+fn common_prefix(block: &[u8], earlier: usize, pos: usize) -> usize {
+    match (block.get(earlier..), block.get(pos..)) {
+        (Some(a), Some(b)) => a.iter().zip(b).take_while(|(x, y)| x == y).count(),
+        _ => 0,
+    }
+}
+
+/// Shannon entropy of a histogram over `total` bytes, in bits per byte.
+fn entropy(histogram: &[u32; 256], total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    histogram
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = f64::from(n) / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn ratio(len: usize, size: usize) -> f64 {
+    if size == 0 {
+        1.0
+    } else {
+        len as f64 / size as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, compress};
+
+    #[test]
+    fn test_profile() {
+        assert!(profile(&[]).is_empty());
+        assert_eq!(profile(&[]).predicted_ratio(), 1.0);
+
+        // Random bytes: maximal entropy, predicted as stored raw.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let random: Vec<u8> = (0..BLOCK_SIZE + 1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        let p = profile(&random);
+        assert_eq!(p.blocks.len(), 2);
+        assert_eq!(p.blocks[1].offset, BLOCK_SIZE);
+        assert_eq!(p.len(), random.len());
+        assert!(p.entropy() > 7.9);
+        assert!(p.predicted_ratio() < 1.01);
+
+        // A single repeated byte.
+        let p = profile(&[7u8; 1000]);
+        assert_eq!(p.blocks[0].entropy, 0.0);
+        assert!(p.blocks[0].matched > 0.99);
+
+        // Close to what the library achieves on text-like data.
+        let data = crate::fixtures::input();
+        let actual = data.len() as f64 / compress(&data, Level::Fast, None).unwrap().len() as f64;
+        let predicted = profile(&data).predicted_ratio();
+        assert!(
+            predicted > actual / 2.0 && predicted < actual * 2.0,
+            "predicted {predicted}, actual {actual}"
+        );
+    }
+}
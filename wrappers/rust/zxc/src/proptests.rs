@@ -0,0 +1,344 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Property tests: arbitrary inputs through every level, with and without
+//! checksums, must decode to themselves and report their size; damaged or
+//! arbitrary frames must fail without panicking.
+//!
+//! Inputs are described by `(pattern, size, seed)` and generated on demand,
+//! so a failure shrinks towards a small size and the simplest pattern. Sizes
+//! reach 16 MiB but large ones are drawn rarely; set `PROPTEST_CASES` to run
+//! more cases than the defaults below.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use proptest::prelude::*;
+
+use crate::*;
+
+/// Shape of a generated input.
+#[derive(Debug, Clone, Copy)]
+enum Pattern {
+    /// A single repeated byte.
+    Constant,
+    /// Words and line breaks, roughly text-like.
+    Text,
+    /// A short random period repeated (long matches at small offsets).
+    Periodic,
+    /// Random runs of random lengths (RLE-friendly, poorly predictable).
+    Runs,
+    /// Incompressible bytes.
+    Random,
+    /// Random chunks, each repeated once near the end of the match window.
+    FarRepeats,
+}
+
+fn pattern() -> impl Strategy<Value = Pattern> {
+    prop_oneof![
+        Just(Pattern::Constant),
+        Just(Pattern::Text),
+        Just(Pattern::Periodic),
+        Just(Pattern::Runs),
+        Just(Pattern::Random),
+        Just(Pattern::FarRepeats),
+    ]
+}
+
+/// Mostly small inputs, with block-boundary sizes and the odd multi-MiB one.
+fn size() -> impl Strategy<Value = usize> {
+    prop_oneof![
+        8 => 0usize..=64,
+        8 => 0usize..=64 * 1024,
+        3 => prop::sample::select(&[4095usize, 4096, 4097, 65535, 65536, 65537, 262_144][..]),
+        2 => 64usize * 1024..=2 * 1024 * 1024,
+        1 => 2usize * 1024 * 1024..=16 * 1024 * 1024,
+    ]
+}
+
+fn level() -> impl Strategy<Value = Level> {
+    prop::sample::select(Level::all())
+}
+
+/// xorshift64*, good enough to fill megabytes quickly.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn generate(pattern: Pattern, size: usize, seed: u64) -> Vec<u8> {
+    let mut rng = Rng(seed | 1);
+    let mut out = Vec::with_capacity(size);
+    match pattern {
+        Pattern::Constant => out.resize(size, rng.next() as u8),
+        Pattern::Text => {
+            const WORDS: [&[u8]; 8] = [
+                b"the", b"block", b"offset", b"literal", b"match", b"zxc", b"frame", b"window",
+            ];
+            while out.len() < size {
+                out.extend_from_slice(WORDS[rng.below(WORDS.len())]);
+                out.push(if rng.below(8) == 0 { b'\n' } else { b' ' });
+            }
+        }
+        Pattern::Periodic => {
+            let period: Vec<u8> = (0..1 + rng.below(32)).map(|_| rng.next() as u8).collect();
+            out.extend(period.iter().copied().cycle().take(size));
+        }
+        Pattern::Runs => {
+            while out.len() < size {
+                let byte = rng.next() as u8;
+                let len = 1 + rng.below(300);
+                out.resize(out.len() + len, byte);
+            }
+        }
+        Pattern::Random => {
+            while out.len() < size {
+                out.extend_from_slice(&rng.next().to_le_bytes());
+            }
+        }
+        Pattern::FarRepeats => {
+            while out.len() < size {
+                let start = out.len();
+                let len = 16 + rng.below(512);
+                for _ in 0..len.div_ceil(8) {
+                    out.extend_from_slice(&rng.next().to_le_bytes());
+                }
+                let gap = rng.below(64 * 1024);
+                for _ in 0..gap.div_ceil(8) {
+                    out.extend_from_slice(&rng.next().to_le_bytes());
+                }
+                out.extend_from_within(start..start + len);
+            }
+        }
+    }
+    out.truncate(size);
+    out
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn roundtrip(
+        pattern in pattern(),
+        size in size(),
+        seed in any::<u64>(),
+        level in level(),
+        checksum in any::<bool>(),
+    ) {
+        let data = generate(pattern, size, seed);
+        let compressed = compress(&data, level, Some(checksum)).unwrap();
+        prop_assert!(compressed.len() <= compress_bound(data.len()));
+        prop_assert_eq!(decompressed_size(&compressed).ok(), Some(data.len() as u64));
+
+        let opts = if checksum {
+            DecompressOptions::default()
+        } else {
+            DecompressOptions::skip_checksum()
+        };
+        let decompressed = decompress_with_options(&compressed, &opts).unwrap();
+        prop_assert!(decompressed == data, "roundtrip mismatch ({} bytes)", data.len());
+    }
+
+    #[test]
+    fn pstream_roundtrip(
+        pattern in pattern(),
+        size in 0usize..=512 * 1024,
+        seed in any::<u64>(),
+        level in level(),
+        checksum in any::<bool>(),
+        chunk in 1usize..=64 * 1024,
+    ) {
+        let data = generate(pattern, size, seed);
+        let opts = CompressOptions { level, checksum, ..Default::default() };
+        let mut cs = CStream::new(Some(&opts)).unwrap();
+        let mut out = vec![0u8; cs.out_size()];
+        let mut compressed = Vec::new();
+        for piece in data.chunks(chunk) {
+            let mut rest = piece;
+            loop {
+                let p = cs.compress(rest, &mut out).unwrap();
+                compressed.extend_from_slice(&out[..p.produced]);
+                rest = &rest[p.consumed..];
+                if rest.is_empty() && p.pending == 0 {
+                    break;
+                }
+            }
+        }
+        loop {
+            let p = cs.end(&mut out).unwrap();
+            compressed.extend_from_slice(&out[..p.produced]);
+            if p.pending == 0 {
+                break;
+            }
+        }
+        prop_assert_eq!(decompressed_size(&compressed).ok(), Some(data.len() as u64));
+
+        let mut ds = DStream::new(None).unwrap();
+        let mut out = vec![0u8; ds.out_size()];
+        let mut decompressed = Vec::new();
+        let mut rest = &compressed[..];
+        while !ds.finished() {
+            let p = ds.decompress(&rest[..rest.len().min(chunk)], &mut out).unwrap();
+            decompressed.extend_from_slice(&out[..p.produced]);
+            rest = &rest[p.consumed..];
+            prop_assert!(p.consumed > 0 || p.produced > 0 || ds.finished(), "stalled");
+        }
+        prop_assert!(decompressed == data, "pstream mismatch ({} bytes)", data.len());
+    }
+}
+
+/// Feeds `frame` to every decoding entry point. Each may fail but none may
+/// panic.
+fn decode_everywhere(frame: &[u8]) {
+    let _ = decompressed_size(frame);
+    let _ = decompress(frame);
+    let _ = decompress_with_options(frame, &DecompressOptions::skip_checksum());
+    #[cfg(feature = "pure-rust-decode")]
+    let _ = pure::decompress(frame);
+
+    if let Ok(mut dctx) = Dctx::new() {
+        let mut out = vec![0u8; decompress_block_bound(64 * 1024) as usize];
+        let block = frame.get(16..).unwrap_or_default();
+        let _ = dctx.decompress_block(block, &mut out, &DecompressOptions::default());
+        let _ = dctx.decompress_block_safe(block, &mut out[..1000], &DecompressOptions::default());
+    }
+
+    if let Ok(mut ds) = DStream::new(None) {
+        let mut out = vec![0u8; ds.out_size()];
+        let mut rest = frame;
+        while let Ok(p) = ds.decompress(rest, &mut out) {
+            rest = &rest[p.consumed..];
+            if ds.finished() || (p.consumed == 0 && p.produced == 0) {
+                break;
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    {
+        use std::io::{Read, sink};
+
+        let _ = verify(frame);
+        let _ = decompress_stream_salvage(frame, sink(), &SalvageOptions::default());
+        let _ = decompress_stream(frame, sink(), None);
+        if let Ok(mut dec) = Decoder::new(frame) {
+            let _ = dec.read_to_end(&mut Vec::new());
+        }
+    }
+}
+
+mod no_panic {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        /// Valid frames with a few bytes flipped and possibly cut short.
+        #[test]
+        fn damaged_frames(
+            pattern in pattern(),
+            size in 0usize..=300 * 1024,
+            seed in any::<u64>(),
+            level in level(),
+            checksum in any::<bool>(),
+            flips in prop::collection::vec((any::<prop::sample::Index>(), 1u8..), 0..4),
+            cut in any::<Option<prop::sample::Index>>(),
+        ) {
+            let data = generate(pattern, size, seed);
+            let mut frame = compress(&data, level, Some(checksum)).unwrap();
+            for (at, mask) in flips {
+                let at = at.index(frame.len());
+                frame[at] ^= mask;
+            }
+            if let Some(cut) = cut {
+                frame.truncate(cut.index(frame.len() + 1));
+            }
+            decode_everywhere(&frame);
+        }
+
+        /// Arbitrary bytes, half of them behind a valid file header.
+        #[test]
+        fn arbitrary_bytes(
+            header in any::<bool>(),
+            bytes in prop::collection::vec(any::<u8>(), 0..512),
+        ) {
+            let mut frame = Vec::new();
+            if header {
+                frame.extend_from_slice(&compress(b"", Level::Default, Some(true)).unwrap()[..16]);
+            }
+            frame.extend_from_slice(&bytes);
+            decode_everywhere(&frame);
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    not(any(feature = "no-stream", target_family = "wasm"))
+))]
+mod file {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn temp_path(tag: &str) -> PathBuf {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "zxc_prop_{}_{}_{tag}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        path
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(12))]
+
+        #[test]
+        fn roundtrip_threads(
+            pattern in pattern(),
+            size in size(),
+            seed in any::<u64>(),
+            level in level(),
+            checksum in any::<bool>(),
+            threads in 1usize..=4,
+        ) {
+            let data = generate(pattern, size, seed);
+            let (input, packed, output) = (temp_path("in"), temp_path("zxc"), temp_path("out"));
+            fs::write(&input, &data).unwrap();
+
+            let mut copts = StreamCompressOptions::with_level(level).threads(threads);
+            copts.checksum = checksum;
+            compress_file_with_options(&input, &packed, &copts).unwrap();
+            let dopts = StreamDecompressOptions::default().threads(threads);
+            let written = decompress_file_with_options(&packed, &output, &dopts).unwrap();
+            let size_on_disk = file_decompressed_size(&packed).unwrap();
+            let decoded = fs::read(&output).unwrap();
+
+            for p in [&input, &packed, &output] {
+                let _ = fs::remove_file(p);
+            }
+            prop_assert_eq!(written, data.len() as u64);
+            prop_assert_eq!(size_on_disk, data.len() as u64);
+            prop_assert!(decoded == data, "file roundtrip mismatch ({} bytes)", data.len());
+        }
+    }
+}
@@ -9,10 +9,14 @@
 //! decompression streams. The safe Rust counterpart of `zxc_cstream` /
 //! `zxc_dstream`.
 
-use std::ffi::c_void;
+use core::ffi::c_void;
+use core::ops::RangeInclusive;
 
 use crate::error::error_from_code;
-use crate::{CompressOptions, DecompressOptions, Error, Result};
+use crate::inspect::check_version;
+use crate::{
+    CompressOptions, DecompressOptions, Error, FORMAT_VERSION, Level, Result, diag, env, ffi,
+};
 
 /// Reports how a single [`CStream::compress`] / [`CStream::end`] call
 /// progressed.
@@ -77,6 +81,9 @@ pub struct DStreamProgress {
 /// ```
 pub struct CStream {
     inner: *mut zxc_sys::zxc_cstream,
+    /// [`CompressOptions::pledged_size`], checked against `total_in`.
+    pledged: Option<u64>,
+    total_in: u64,
 }
 
 unsafe impl Send for CStream {}
@@ -85,7 +92,9 @@ impl CStream {
     /// Creates a new push compression stream.
     ///
     /// `opts.seekable` is ignored (the push API is single-threaded and does
-    /// not emit a seek table). Pass `None` for all defaults.
+    /// not emit a seek table). With `opts.pledged_size`, the stream fails
+    /// with [`Error::PledgeMismatch`] once fed more than pledged, and at
+    /// [`CStream::end`] if fed less. Pass `None` for all defaults.
     ///
     /// # Errors
     ///
@@ -93,6 +102,15 @@ impl CStream {
     /// is set: the push-stream format carries no dictionary ID, so
     /// dictionary compression would produce undecodable archives.
     pub fn new(opts: Option<&CompressOptions>) -> Result<Self> {
+        Self::with_block_size(opts, 0)
+    }
+
+    /// [`CStream::new`] writing blocks of `block_size` bytes, a power of two
+    /// from 4 KiB to 2 MiB, or of the default size for 0.
+    pub(crate) fn with_block_size(
+        opts: Option<&CompressOptions>,
+        block_size: usize,
+    ) -> Result<Self> {
         if let Some(o) = opts {
             if o.dict.is_some() || o.dict_huf.is_some() {
                 return Err(Error::Unsupported(
@@ -100,24 +118,48 @@ impl CStream {
                 ));
             }
         }
-        let c_opts = opts.map(|o| zxc_sys::zxc_compress_opts_t {
-            level: o.level as i32,
-            checksum_enabled: o.checksum as i32,
-            seekable: 0,
-            ..Default::default()
-        });
+        if block_size != 0
+            && (!block_size.is_power_of_two() || !(1 << 12..=1 << 21).contains(&block_size))
+        {
+            return Err(Error::BadBlockSize);
+        }
+        let pledged = opts.and_then(|o| o.pledged_size);
+        let block_size = match (block_size, pledged) {
+            (0, Some(size)) => pledged_block_size(size),
+            _ => block_size,
+        };
+        env::init();
+        diag::variant();
+        let c_opts = match (opts, block_size) {
+            (None, 0) => None,
+            _ => {
+                let (level, checksum) =
+                    opts.map_or((Level::Default, true), |o| (o.level, o.checksum));
+                Some(zxc_sys::zxc_compress_opts_t {
+                    level: env::level(level) as i32,
+                    checksum_enabled: checksum as i32,
+                    seekable: 0,
+                    block_size,
+                    ..Default::default()
+                })
+            }
+        };
         let ptr = unsafe {
-            zxc_sys::zxc_cstream_create(
+            ffi::zxc_cstream_create(
                 c_opts
                     .as_ref()
                     .map(|o| o as *const _)
-                    .unwrap_or(std::ptr::null()),
+                    .unwrap_or(core::ptr::null()),
             )
         };
         if ptr.is_null() {
             Err(Error::Memory)
         } else {
-            Ok(Self { inner: ptr })
+            Ok(Self {
+                inner: ptr,
+                pledged,
+                total_in: 0,
+            })
         }
     }
 
@@ -127,6 +169,12 @@ impl CStream {
     /// slice ranges that were processed; `progress.pending` is the number of
     /// bytes still staged inside the stream (drain `output` and call again).
     pub fn compress(&mut self, input: &[u8], output: &mut [u8]) -> Result<CStreamProgress> {
+        if let Some(pledged) = self.pledged {
+            let actual = self.total_in.saturating_add(input.len() as u64);
+            if actual > pledged {
+                return Err(Error::PledgeMismatch { pledged, actual });
+            }
+        }
         let mut in_buf = zxc_sys::zxc_inbuf_t {
             src: input.as_ptr() as *const c_void,
             size: input.len(),
@@ -137,10 +185,11 @@ impl CStream {
             size: output.len(),
             pos: 0,
         };
-        let r = unsafe { zxc_sys::zxc_cstream_compress(self.inner, &mut out_buf, &mut in_buf) };
+        let r = unsafe { ffi::zxc_cstream_compress(self.inner, &mut out_buf, &mut in_buf) };
         if r < 0 {
             return Err(error_from_code(r));
         }
+        self.total_in += in_buf.pos as u64;
         Ok(CStreamProgress {
             consumed: in_buf.pos,
             produced: out_buf.pos,
@@ -153,12 +202,18 @@ impl CStream {
     ///
     /// Call repeatedly while `pending > 0`, draining `output` between calls.
     pub fn end(&mut self, output: &mut [u8]) -> Result<CStreamProgress> {
+        if let Some(pledged) = self.pledged.filter(|&p| p != self.total_in) {
+            return Err(Error::PledgeMismatch {
+                pledged,
+                actual: self.total_in,
+            });
+        }
         let mut out_buf = zxc_sys::zxc_outbuf_t {
             dst: output.as_mut_ptr() as *mut c_void,
             size: output.len(),
             pos: 0,
         };
-        let r = unsafe { zxc_sys::zxc_cstream_end(self.inner, &mut out_buf) };
+        let r = unsafe { ffi::zxc_cstream_end(self.inner, &mut out_buf) };
         if r < 0 {
             return Err(error_from_code(r));
         }
@@ -171,18 +226,28 @@ impl CStream {
 
     /// Suggested input chunk size for best throughput.
     pub fn in_size(&self) -> usize {
-        unsafe { zxc_sys::zxc_cstream_in_size(self.inner) }
+        unsafe { ffi::zxc_cstream_in_size(self.inner) }
     }
 
     /// Suggested output chunk size to never trigger a partial drain.
     pub fn out_size(&self) -> usize {
-        unsafe { zxc_sys::zxc_cstream_out_size(self.inner) }
+        unsafe { ffi::zxc_cstream_out_size(self.inner) }
+    }
+}
+
+/// The block size for a stream of `size` bytes: the smallest that holds it
+/// in one block, or 0 (the default size) when that is no smaller.
+fn pledged_block_size(size: u64) -> usize {
+    const DEFAULT_BLOCK_SIZE: u64 = 512 << 10;
+    match size {
+        ..DEFAULT_BLOCK_SIZE => size.next_power_of_two().max(crate::MIN_BLOCK_SIZE as u64) as usize,
+        _ => 0,
     }
 }
 
 impl Drop for CStream {
     fn drop(&mut self) {
-        unsafe { zxc_sys::zxc_cstream_free(self.inner) };
+        unsafe { ffi::zxc_cstream_free(self.inner) };
     }
 }
 
@@ -212,6 +277,12 @@ impl Drop for CStream {
 /// ```
 pub struct DStream {
     inner: *mut zxc_sys::zxc_dstream,
+    /// [`DecompressOptions::format_versions`], checked against the frame's
+    /// version byte as soon as it comes in.
+    versions: RangeInclusive<u8>,
+    /// The start of the frame, up to its version byte, as consumed so far.
+    head: [u8; 5],
+    head_len: usize,
 }
 
 unsafe impl Send for DStream {}
@@ -232,29 +303,50 @@ impl DStream {
                 ));
             }
         }
+        env::init();
+        diag::variant();
+        if let Some(o) = opts {
+            diag::checksum(o.verify_checksum, None);
+        }
         let c_opts = opts.map(|o| zxc_sys::zxc_decompress_opts_t {
             checksum_enabled: o.verify_checksum as i32,
             ..Default::default()
         });
         let ptr = unsafe {
-            zxc_sys::zxc_dstream_create(
+            ffi::zxc_dstream_create(
                 c_opts
                     .as_ref()
                     .map(|o| o as *const _)
-                    .unwrap_or(std::ptr::null()),
+                    .unwrap_or(core::ptr::null()),
             )
         };
         if ptr.is_null() {
             Err(Error::Memory)
         } else {
-            Ok(Self { inner: ptr })
+            Ok(Self {
+                inner: ptr,
+                versions: opts
+                    .map(DecompressOptions::format_versions)
+                    .unwrap_or(FORMAT_VERSION..=FORMAT_VERSION),
+                head: [0; 5],
+                head_len: 0,
+            })
         }
     }
 
     /// Pushes compressed bytes from `input` and writes decompressed bytes
     /// to `output`. The state machine consumes file header, block headers,
     /// payloads, and footer transparently.
+    ///
+    /// A frame of a format version outside
+    /// [`DecompressOptions::with_format_versions`] fails with
+    /// [`Error::BadVersion`] once its version byte is in, before any of
+    /// that call's input is consumed.
     pub fn decompress(&mut self, input: &[u8], output: &mut [u8]) -> Result<DStreamProgress> {
+        if self.head_len < self.head.len() {
+            let (head, len) = self.peek_head(input);
+            check_version(head.get(..len).unwrap_or_default(), self.versions.clone())?;
+        }
         let mut in_buf = zxc_sys::zxc_inbuf_t {
             src: input.as_ptr() as *const c_void,
             size: input.len(),
@@ -265,42 +357,58 @@ impl DStream {
             size: output.len(),
             pos: 0,
         };
-        let r = unsafe { zxc_sys::zxc_dstream_decompress(self.inner, &mut out_buf, &mut in_buf) };
+        let r = unsafe { ffi::zxc_dstream_decompress(self.inner, &mut out_buf, &mut in_buf) };
         if r < 0 {
             return Err(error_from_code(r));
         }
+        if self.head_len < self.head.len() {
+            (self.head, self.head_len) =
+                self.peek_head(input.get(..in_buf.pos).unwrap_or_default());
+        }
         Ok(DStreamProgress {
             consumed: in_buf.pos,
             produced: out_buf.pos,
-            finished: unsafe { zxc_sys::zxc_dstream_finished(self.inner) } != 0,
+            finished: unsafe { ffi::zxc_dstream_finished(self.inner) } != 0,
         })
     }
 
+    /// The start of the frame as it stands once `input` is appended to
+    /// what was consumed, and its length.
+    fn peek_head(&self, input: &[u8]) -> ([u8; 5], usize) {
+        let (mut head, mut len) = (self.head, self.head_len);
+        for (slot, &b) in head.iter_mut().skip(len).zip(input) {
+            *slot = b;
+            len += 1;
+        }
+        (head, len)
+    }
+
     /// Returns `true` iff the decoder reached and validated the file footer.
     /// Useful to detect truncated streams after the input source is drained.
     pub fn finished(&self) -> bool {
-        unsafe { zxc_sys::zxc_dstream_finished(self.inner) != 0 }
+        unsafe { ffi::zxc_dstream_finished(self.inner) != 0 }
     }
 
     /// Suggested input chunk size for the decompressor.
     pub fn in_size(&self) -> usize {
-        unsafe { zxc_sys::zxc_dstream_in_size(self.inner) }
+        unsafe { ffi::zxc_dstream_in_size(self.inner) }
     }
 
     /// Suggested output chunk size for the decompressor.
     pub fn out_size(&self) -> usize {
-        unsafe { zxc_sys::zxc_dstream_out_size(self.inner) }
+        unsafe { ffi::zxc_dstream_out_size(self.inner) }
     }
 }
 
 impl Drop for DStream {
     fn drop(&mut self) {
-        unsafe { zxc_sys::zxc_dstream_free(self.inner) };
+        unsafe { ffi::zxc_dstream_free(self.inner) };
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::pledged_block_size;
     use crate::*;
 
     fn pstream_roundtrip(
@@ -387,6 +495,88 @@ mod tests {
         assert_eq!(out, data);
     }
 
+    #[test]
+    fn pstream_pledged_size() {
+        let data = b"a short record, pledged up front";
+        let copts = CompressOptions::default().with_pledged_size(data.len() as u64);
+        let out = pstream_roundtrip(data, Some(&copts), None);
+        assert_eq!(out, data);
+
+        // The smallest block that holds the input: the C decoder sizes its
+        // buffers from it.
+        let mut cs = CStream::new(Some(&copts)).unwrap();
+        let mut out = vec![0u8; cs.out_size()];
+        let mut frame = Vec::new();
+        let p = cs.compress(data, &mut out).unwrap();
+        assert_eq!(p.consumed, data.len());
+        frame.extend_from_slice(&out[..p.produced]);
+        let p = cs.end(&mut out).unwrap();
+        assert_eq!(p.pending, 0);
+        frame.extend_from_slice(&out[..p.produced]);
+        assert_eq!(frame[5], 12, "block size code");
+        assert_eq!(pledged_block_size(5000), 8192);
+        assert_eq!(pledged_block_size(512 << 10), 0);
+        assert_eq!(pledged_block_size(u64::MAX), 0);
+
+        // More than pledged fails before anything is consumed, fewer at the
+        // end.
+        let short = CompressOptions::default().with_pledged_size(10);
+        let mut cs = CStream::new(Some(&short)).unwrap();
+        let mut out = vec![0u8; cs.out_size()];
+        assert!(matches!(
+            cs.compress(data, &mut out),
+            Err(Error::PledgeMismatch {
+                pledged: 10,
+                actual: 32
+            })
+        ));
+        cs.compress(b"12345", &mut out).unwrap();
+        assert!(matches!(
+            cs.end(&mut out),
+            Err(Error::PledgeMismatch {
+                pledged: 10,
+                actual: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn pstream_format_versions() {
+        let data = b"a frame read with the format versions pinned";
+        let frame = compress(data, Level::Default, None).unwrap();
+        let current =
+            DecompressOptions::default().with_format_versions(FORMAT_VERSION..=FORMAT_VERSION);
+        assert_eq!(pstream_roundtrip(data, None, Some(&current)), data);
+
+        let newer = DecompressOptions::default().with_format_versions(8..=9);
+        let mut out = vec![0u8; 256];
+        let mut ds = DStream::new(Some(&newer)).unwrap();
+        assert!(matches!(
+            ds.decompress(&frame, &mut out),
+            Err(Error::BadVersion)
+        ));
+
+        // Fed a byte at a time, the magic word goes through and the version
+        // byte fails.
+        let mut ds = DStream::new(Some(&newer)).unwrap();
+        for b in &frame[..4] {
+            assert_eq!(ds.decompress(&[*b], &mut out).unwrap().consumed, 1);
+        }
+        assert!(matches!(
+            ds.decompress(&frame[4..], &mut out),
+            Err(Error::BadVersion)
+        ));
+        assert!(matches!(
+            ds.decompress(&frame[4..], &mut out),
+            Err(Error::BadVersion)
+        ));
+
+        // The sans-I/O decoder sits on the same stream.
+        let mut sd = crate::sansio::StreamingDecompressor::new(Some(&newer)).unwrap();
+        sd.feed(&frame);
+        assert!(matches!(sd.next_output(&mut out), Err(Error::BadVersion)));
+    }
+
     #[test]
     fn pstream_size_hints_nonzero() {
         let cs = CStream::new(None).unwrap();
@@ -0,0 +1,1080 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Frame decoder written in safe Rust (`pure-rust-decode` feature).
+//!
+//! This module reads the format described in `docs/FORMAT.md` without calling
+//! into the C library: RAW, GLO (including Huffman literal and token sections)
+//! and GHI blocks, dictionaries with or without a shared literal table,
+//! per-block and global checksums, and seekable archives. It trades speed for
+//! a decode path that is bounds-checked throughout, which suits untrusted
+//! input. The output is byte-identical to [`crate::decompress_with_options`].
+//!
+//! ```rust
+//! use zxc::{compress, Level};
+//!
+//! let data = b"Hello, ZXC! Hello, ZXC! Hello, ZXC!";
+//! let compressed = compress(data, Level::Default, Some(true))?;
+//! assert_eq!(zxc::pure::decompress(&compressed)?, data);
+//! # Ok::<(), zxc::Error>(())
+//! ```
+
+#![forbid(unsafe_code)]
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::size_to_usize;
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_GHI, BLOCK_GLO, BLOCK_HEADER_SIZE, BLOCK_RAW,
+    BLOCK_SIZE_LOG2, ENC_HUFFMAN, FILE_FOOTER_SIZE, FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM,
+    FLAG_HAS_DICTIONARY, FORMAT_VERSION, GNR_HEADER_SIZE, MAGIC_WORD,
+};
+use crate::hash::{checksum, fold32, hash8, hash16, rapidhash};
+use crate::inspect::check_version;
+use crate::{DecompressOptions, Error, Result};
+
+// =============================================================================
+// Format constants (docs/FORMAT.md), past those of crate::format
+// =============================================================================
+
+const CHECKSUM_RAPIDHASH: u8 = 0;
+
+const SECTION_DESC_SIZE: usize = 8;
+const GLO_SECTIONS: usize = 4;
+const GHI_SECTIONS: usize = 3;
+
+const ENC_RAW: u8 = 0;
+const ENC_RLE: u8 = 1;
+const ENC_HUFFMAN_DICT: u8 = 3;
+
+const MIN_MATCH_LEN: usize = 5;
+const TOKEN_LL_MASK: usize = 0x0F;
+const TOKEN_ML_MASK: usize = 0x0F;
+const SEQ_LL_MASK: usize = 0xFF;
+const SEQ_ML_MASK: usize = 0xFF;
+const LIT_RLE_FLAG: u8 = 0x80;
+
+pub(crate) const HUF_TABLE_SIZE: usize = 128;
+const HUF_NUM_SYMBOLS: usize = 256;
+const HUF_MAX_CODE_LEN: usize = 11;
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+/// Decompresses a ZXC frame without the C library, verifying checksums when
+/// the frame carries them.
+///
+/// Fails with [`Error::DictRequired`] for frames compressed with a
+/// dictionary; use [`decompress_with_options`] to supply it.
+pub fn decompress(compressed: &[u8]) -> Result<Vec<u8>> {
+    decompress_with_options(compressed, &DecompressOptions::default())
+}
+
+/// Decompresses a ZXC frame without the C library, honouring
+/// `verify_checksum`, `dict` and `dict_huf` like
+/// [`crate::decompress_with_options`].
+pub fn decompress_with_options(compressed: &[u8], options: &DecompressOptions) -> Result<Vec<u8>> {
+    if compressed.len() < FILE_HEADER_SIZE + FILE_FOOTER_SIZE {
+        return Err(Error::SrcTooSmall);
+    }
+    check_version(compressed, options.format_versions())?;
+    let header = FileHeader::parse(compressed)?;
+
+    let dict = options.dict.as_deref().filter(|d| !d.is_empty());
+    let dict_huf = match (dict, options.dict_huf.as_deref()) {
+        (Some(_), Some(h)) if !h.is_empty() => Some(h.try_into().map_err(|_| Error::CorruptData)?),
+        _ => None,
+    };
+    if let Some(id) = header.dict_id {
+        let dict = dict.ok_or(Error::DictRequired)?;
+        if dict_id(dict, dict_huf) != id {
+            return Err(Error::DictMismatch);
+        }
+    }
+    // An all-zero table (low-entropy training corpus) means "no shared table".
+    let shared_tree = match dict_huf {
+        Some(lengths) if lengths.iter().any(|&b| b != 0) => Some(HufTree::from_packed(lengths)?),
+        _ => None,
+    };
+
+    let (body, footer) = compressed.split_at(compressed.len() - FILE_FOOTER_SIZE);
+    let stored_size = read_u64(footer, 0)?;
+    let stored_hash = read_u32(footer, 8)?;
+    let verify = options.verify_checksum && header.has_checksum;
+
+    let ctx = BlockCtx {
+        block_size: header.block_size,
+        shared_tree: shared_tree.as_ref(),
+    };
+    let mut output = Vec::new();
+    // Dictionary frames decode each block behind a copy of the dictionary so
+    // that matches into it resolve like ordinary back-references.
+    let mut window = dict.map(<[u8]>::to_vec).unwrap_or_default();
+    let mut global_hash = 0u32;
+    let mut pos = FILE_HEADER_SIZE;
+    let mut index = 0u32;
+
+    loop {
+        let start = pos;
+        let at = |source| Error::Corrupted {
+            block: Some(index),
+            offset: start as u64,
+            source: Box::new(source),
+        };
+        let block = BlockHeader::parse(body.get(pos..).ok_or(Error::SrcTooSmall).map_err(at)?)
+            .map_err(at)?;
+        pos += BLOCK_HEADER_SIZE;
+
+        if block.kind == BLOCK_EOF {
+            if block.comp_size != 0 {
+                return Err(at(Error::BadHeader));
+            }
+            break;
+        }
+
+        let payload = take(body, pos, block.comp_size).map_err(|_| at(Error::SrcTooSmall))?;
+        pos += block.comp_size;
+        if header.has_checksum {
+            let stored = read_u32(body, pos).map_err(|_| at(Error::SrcTooSmall))?;
+            pos += BLOCK_CHECKSUM_SIZE;
+            if verify {
+                let actual = checksum(payload);
+                if actual != stored {
+                    return Err(at(Error::ChecksumMismatch {
+                        block: Some(index),
+                        expected: u64::from(stored),
+                        actual: u64::from(actual),
+                    }));
+                }
+                global_hash = global_hash.rotate_left(1) ^ stored;
+            }
+        }
+
+        let remaining = stored_size - output.len() as u64;
+        output.reserve(size_to_usize(remaining)?.min(header.block_size));
+        if dict.is_some() {
+            let floor = window.len();
+            decode_block(&ctx, block.kind, payload, &mut window, 0).map_err(at)?;
+            output.extend_from_slice(window.get(floor..).unwrap_or_default());
+            window.truncate(floor);
+        } else {
+            let floor = output.len();
+            decode_block(&ctx, block.kind, payload, &mut output, floor).map_err(at)?;
+        }
+        // Stop as soon as the frame overruns its declared size rather than
+        // decoding (and allocating) the rest of a corrupt stream.
+        if output.len() as u64 > stored_size {
+            return Err(at(Error::CorruptData));
+        }
+        index += 1;
+    }
+
+    let trailer = |source| Error::Corrupted {
+        block: None,
+        offset: body.len() as u64,
+        source: Box::new(source),
+    };
+    if output.len() as u64 != stored_size {
+        return Err(trailer(Error::CorruptData));
+    }
+    if verify && stored_hash != global_hash {
+        return Err(trailer(Error::ChecksumMismatch {
+            block: None,
+            expected: u64::from(stored_hash),
+            actual: u64::from(global_hash),
+        }));
+    }
+    Ok(output)
+}
+
+/// Decodes a single Block API block (block header, payload, then a checksum
+/// that is only read when `verify` is set), as the mock backend's
+/// `zxc_decompress_block`.
+#[cfg(feature = "mock-backend")]
+pub(crate) fn decompress_block(src: &[u8], verify: bool, dict: Option<&[u8]>) -> Result<Vec<u8>> {
+    let block = BlockHeader::parse(src)?;
+    let payload = take(src, BLOCK_HEADER_SIZE, block.comp_size)?;
+    if verify && read_u32(src, BLOCK_HEADER_SIZE + block.comp_size)? != checksum(payload) {
+        return Err(Error::BadChecksum);
+    }
+    let ctx = BlockCtx {
+        block_size: 1 << *BLOCK_SIZE_LOG2.end(),
+        shared_tree: None,
+    };
+    let mut window = dict.map(<[u8]>::to_vec).unwrap_or_default();
+    let floor = window.len();
+    decode_block(&ctx, block.kind, payload, &mut window, 0)?;
+    Ok(window.split_off(floor))
+}
+
+// =============================================================================
+// Frame structures
+// =============================================================================
+
+pub(crate) struct FileHeader {
+    pub(crate) block_size: usize,
+    pub(crate) has_checksum: bool,
+    pub(crate) dict_id: Option<u32>,
+}
+
+impl FileHeader {
+    pub(crate) fn parse(src: &[u8]) -> Result<Self> {
+        let raw: [u8; FILE_HEADER_SIZE] = take(src, 0, FILE_HEADER_SIZE)?
+            .try_into()
+            .map_err(|_| Error::SrcTooSmall)?;
+        if u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) != MAGIC_WORD {
+            return Err(Error::BadMagic);
+        }
+        if raw[4] != FORMAT_VERSION {
+            return Err(Error::BadVersion);
+        }
+        let mut zeroed = raw;
+        zeroed[14] = 0;
+        zeroed[15] = 0;
+        let flags = raw[6];
+        if u16::from_le_bytes([raw[14], raw[15]]) != hash16(&zeroed)
+            || flags & 0x0F != CHECKSUM_RAPIDHASH
+        {
+            return Err(Error::BadHeader);
+        }
+        let code = raw[5];
+        if !BLOCK_SIZE_LOG2.contains(&code) {
+            return Err(Error::BadBlockSize);
+        }
+        Ok(Self {
+            block_size: 1 << code,
+            has_checksum: flags & FLAG_HAS_CHECKSUM != 0,
+            dict_id: (flags & FLAG_HAS_DICTIONARY != 0)
+                .then(|| u32::from_le_bytes([raw[7], raw[8], raw[9], raw[10]])),
+        })
+    }
+}
+
+pub(crate) struct BlockHeader {
+    pub(crate) kind: u8,
+    pub(crate) comp_size: usize,
+}
+
+impl BlockHeader {
+    pub(crate) fn parse(src: &[u8]) -> Result<Self> {
+        let raw: [u8; BLOCK_HEADER_SIZE] = take(src, 0, BLOCK_HEADER_SIZE)
+            .map_err(|_| Error::SrcTooSmall)?
+            .try_into()
+            .map_err(|_| Error::SrcTooSmall)?;
+        let mut zeroed = raw;
+        zeroed[7] = 0;
+        if raw[7] != hash8(&zeroed) {
+            return Err(Error::BadHeader);
+        }
+        Ok(Self {
+            kind: raw[0],
+            comp_size: size_to_usize(u64::from(u32::from_le_bytes([
+                raw[3], raw[4], raw[5], raw[6],
+            ])))?,
+        })
+    }
+}
+
+/// Fields of a GLO/GHI block header that the decoder uses, with the
+/// `(compressed, raw)` size of each of its `N` sections.
+struct GnrHeader<const N: usize> {
+    n_sequences: usize,
+    enc_lit: u8,
+    enc_litlen: u8,
+    enc_off: u8,
+    sections: [(usize, usize); N],
+}
+
+impl<const N: usize> GnrHeader<N> {
+    /// Size of the header plus its section descriptors: where the first
+    /// section starts.
+    const SIZE: usize = GNR_HEADER_SIZE + N * SECTION_DESC_SIZE;
+
+    fn parse(src: &[u8]) -> Result<Self> {
+        if src.len() < Self::SIZE {
+            return Err(Error::BadHeader);
+        }
+        let head: &[u8; GNR_HEADER_SIZE] = src.first_chunk().ok_or(Error::BadHeader)?;
+        let mut sections = [(0, 0); N];
+        for (i, section) in sections.iter_mut().enumerate() {
+            let sizes = read_u64(src, GNR_HEADER_SIZE + i * SECTION_DESC_SIZE)?;
+            *section = (
+                size_to_usize(sizes & 0xFFFF_FFFF)?,
+                size_to_usize(sizes >> 32)?,
+            );
+        }
+        Ok(Self {
+            n_sequences: size_to_usize(u64::from(read_u32(src, 0)?))?,
+            enc_lit: head[8],
+            enc_litlen: head[9],
+            enc_off: head[11],
+            sections,
+        })
+    }
+}
+
+// =============================================================================
+// Block decoding
+// =============================================================================
+
+struct BlockCtx<'a> {
+    block_size: usize,
+    shared_tree: Option<&'a HufTree>,
+}
+
+/// Decodes one block, appending it to `out`. Matches may reach back to
+/// `floor` (the block start, or the dictionary start when one is active).
+fn decode_block(
+    ctx: &BlockCtx<'_>,
+    kind: u8,
+    payload: &[u8],
+    out: &mut Vec<u8>,
+    floor: usize,
+) -> Result<()> {
+    match kind {
+        BLOCK_RAW => {
+            if payload.len() > ctx.block_size {
+                return Err(Error::DstTooSmall {
+                    needed: payload.len(),
+                });
+            }
+            out.extend_from_slice(payload);
+            Ok(())
+        }
+        BLOCK_GLO => decode_glo(ctx, payload, out, floor),
+        BLOCK_GHI => decode_ghi(ctx, payload, out, floor),
+        _ => Err(Error::BadBlockType),
+    }
+}
+
+fn decode_glo(ctx: &BlockCtx<'_>, src: &[u8], out: &mut Vec<u8>, floor: usize) -> Result<()> {
+    let gh = GnrHeader::<GLO_SECTIONS>::parse(src)?;
+    let desc = gh.sections;
+    let mut p = GnrHeader::<GLO_SECTIONS>::SIZE;
+
+    let (lit_size, lit_raw) = desc[0];
+    let lit_section = take(src, p, lit_size).map_err(|_| Error::CorruptData)?;
+    let literals: Vec<u8>;
+    let lit: &[u8] = match gh.enc_lit {
+        ENC_RAW => lit_section,
+        ENC_RLE => {
+            literals = decode_rle(lit_section, lit_raw, ctx.block_size)?;
+            &literals
+        }
+        ENC_HUFFMAN | ENC_HUFFMAN_DICT if lit_raw == 0 => &[],
+        ENC_HUFFMAN => {
+            if lit_raw > ctx.block_size {
+                return Err(Error::DstTooSmall { needed: lit_raw });
+            }
+            let (lengths, runs) = split_huf_header(lit_section)?;
+            literals = HufTree::from_packed(lengths)?.decode(runs, lit_raw)?;
+            &literals
+        }
+        ENC_HUFFMAN_DICT => {
+            let tree = ctx.shared_tree.ok_or(Error::DictRequired)?;
+            if lit_raw > ctx.block_size {
+                return Err(Error::DstTooSmall { needed: lit_raw });
+            }
+            literals = tree.decode(lit_section, lit_raw)?;
+            &literals
+        }
+        _ => return Err(Error::CorruptData),
+    };
+    p += lit_size;
+
+    let (sz_tokens, _) = desc[1];
+    let (sz_offsets, _) = desc[2];
+    let (sz_extras, _) = desc[3];
+    let off_width = if gh.enc_off == 1 { 1 } else { 2 };
+    let token_section = take(src, p, sz_tokens).map_err(|_| Error::CorruptData)?;
+    let offsets = take(src, p + sz_tokens, sz_offsets).map_err(|_| Error::CorruptData)?;
+    let extras =
+        take(src, p + sz_tokens + sz_offsets, sz_extras).map_err(|_| Error::CorruptData)?;
+    if p + sz_tokens + sz_offsets + sz_extras != src.len()
+        || (sz_offsets as u64) < gh.n_sequences as u64 * off_width as u64
+    {
+        return Err(Error::CorruptData);
+    }
+
+    let decoded_tokens: Vec<u8>;
+    let tokens: &[u8] = if gh.enc_litlen == ENC_HUFFMAN {
+        if gh.n_sequences > ctx.block_size {
+            return Err(Error::CorruptData);
+        }
+        let (lengths, runs) = split_huf_header(token_section)?;
+        decoded_tokens = HufTree::from_packed(lengths)?.decode(runs, gh.n_sequences)?;
+        &decoded_tokens
+    } else {
+        token_section
+            .get(..gh.n_sequences)
+            .ok_or(Error::CorruptData)?
+    };
+
+    let mut seq = Sequencer::new(ctx, lit, extras, out, floor);
+    for (i, &token) in tokens.iter().enumerate() {
+        let mut ll = usize::from(token >> 4);
+        let mut ml = usize::from(token & 0x0F);
+        // The section holds `off_width` bytes per sequence, checked above.
+        let offset = if off_width == 1 {
+            usize::from(*at(offsets, i)?)
+        } else {
+            usize::from(read_u16(offsets, 2 * i).map_err(|_| Error::CorruptData)?)
+        } + 1;
+        if ll == TOKEN_LL_MASK {
+            ll += seq.varint()?;
+        }
+        if ml == TOKEN_ML_MASK {
+            ml += seq.varint()?;
+        }
+        seq.sequence(ll, ml + MIN_MATCH_LEN, offset)?;
+    }
+    seq.finish()
+}
+
+fn decode_ghi(ctx: &BlockCtx<'_>, src: &[u8], out: &mut Vec<u8>, floor: usize) -> Result<()> {
+    let gh = GnrHeader::<GHI_SECTIONS>::parse(src)?;
+    let desc = gh.sections;
+    let p = GnrHeader::<GHI_SECTIONS>::SIZE;
+
+    let (sz_lit, _) = desc[0];
+    let (sz_seqs, _) = desc[1];
+    let (sz_extras, _) = desc[2];
+    let lit = take(src, p, sz_lit).map_err(|_| Error::CorruptData)?;
+    let seqs = take(src, p + sz_lit, sz_seqs).map_err(|_| Error::CorruptData)?;
+    let extras = take(src, p + sz_lit + sz_seqs, sz_extras).map_err(|_| Error::CorruptData)?;
+    if p + sz_lit + sz_seqs + sz_extras != src.len() || (sz_seqs as u64) < gh.n_sequences as u64 * 4
+    {
+        return Err(Error::CorruptData);
+    }
+
+    let mut seq = Sequencer::new(ctx, lit, extras, out, floor);
+    for word in seqs.chunks_exact(4).take(gh.n_sequences) {
+        let [off_lo, off_hi, ml, ll] = word.try_into().map_err(|_| Error::CorruptData)?;
+        let mut ll = usize::from(ll);
+        let mut ml = usize::from(ml);
+        let offset = usize::from(u16::from_le_bytes([off_lo, off_hi])) + 1;
+        if ll == SEQ_LL_MASK {
+            ll += seq.varint()?;
+        }
+        if ml == SEQ_ML_MASK {
+            ml += seq.varint()?;
+        }
+        seq.sequence(ll, ml + MIN_MATCH_LEN, offset)?;
+    }
+    seq.finish()
+}
+
+/// Expands an RLE literal section (`enc_lit=1`) to exactly `raw_size` bytes.
+fn decode_rle(src: &[u8], raw_size: usize, block_size: usize) -> Result<Vec<u8>> {
+    if raw_size > block_size {
+        return Err(Error::DstTooSmall { needed: raw_size });
+    }
+    let mut out = Vec::with_capacity(raw_size);
+    let mut r = 0;
+    while out.len() < raw_size {
+        let Some(&token) = src.get(r) else {
+            break;
+        };
+        r += 1;
+        if token & LIT_RLE_FLAG == 0 {
+            let len = usize::from(token) + 1;
+            let run = take(src, r, len).map_err(|_| Error::CorruptData)?;
+            if out.len() + len > raw_size {
+                return Err(Error::CorruptData);
+            }
+            out.extend_from_slice(run);
+            r += len;
+        } else {
+            let len = usize::from(token & !LIT_RLE_FLAG) + 4;
+            let &byte = src.get(r).ok_or(Error::CorruptData)?;
+            if out.len() + len > raw_size {
+                return Err(Error::CorruptData);
+            }
+            out.resize(out.len() + len, byte);
+            r += 1;
+        }
+    }
+    if out.len() != raw_size {
+        return Err(Error::CorruptData);
+    }
+    Ok(out)
+}
+
+/// Replays LZ sequences (literal run + match) into the output, with every
+/// length and offset checked against the block bounds.
+struct Sequencer<'a, 'o> {
+    lit: &'a [u8],
+    extras: &'a [u8],
+    out: &'o mut Vec<u8>,
+    floor: usize,
+    limit: usize,
+}
+
+impl<'a, 'o> Sequencer<'a, 'o> {
+    fn new(
+        ctx: &BlockCtx<'_>,
+        lit: &'a [u8],
+        extras: &'a [u8],
+        out: &'o mut Vec<u8>,
+        floor: usize,
+    ) -> Self {
+        let limit = out.len() + ctx.block_size;
+        Self {
+            lit,
+            extras,
+            out,
+            floor,
+            limit,
+        }
+    }
+
+    /// Reads one prefix varint (§6) from the extras stream.
+    fn varint(&mut self) -> Result<usize> {
+        let e = self.extras;
+        let (value, len) = match *e.first().ok_or(Error::CorruptData)? {
+            b0 @ 0x00..=0x7F => (usize::from(b0), 1),
+            b0 @ 0x80..=0xBF => {
+                let b1 = *e.get(1).ok_or(Error::CorruptData)?;
+                (usize::from(b0 & 0x3F) | usize::from(b1) << 6, 2)
+            }
+            b0 @ 0xC0..=0xDF => {
+                let b1 = *e.get(1).ok_or(Error::CorruptData)?;
+                let b2 = *e.get(2).ok_or(Error::CorruptData)?;
+                (
+                    usize::from(b0 & 0x1F) | usize::from(b1) << 5 | usize::from(b2) << 13,
+                    3,
+                )
+            }
+            // Four bytes or more is out of spec for this format version.
+            _ => return Err(Error::CorruptData),
+        };
+        self.extras = e.get(len..).ok_or(Error::CorruptData)?;
+        Ok(value)
+    }
+
+    fn sequence(&mut self, ll: usize, ml: usize, offset: usize) -> Result<()> {
+        if ll + ml > self.limit - self.out.len() || ll > self.lit.len() {
+            return Err(Error::Overflow);
+        }
+        let (run, rest) = self.lit.split_at(ll);
+        self.out.extend_from_slice(run);
+        self.lit = rest;
+
+        if offset > self.out.len() - self.floor {
+            return Err(Error::BadOffset);
+        }
+        let start = self.out.len() - offset;
+        if offset >= ml {
+            self.out.extend_from_within(start..start + ml);
+        } else {
+            // Overlapping match: each copied byte may feed the next one.
+            for i in start..start + ml {
+                let byte = *at(self.out, i)?;
+                self.out.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the literals left over after the last sequence.
+    fn finish(self) -> Result<()> {
+        if self.lit.len() > self.limit - self.out.len() {
+            return Err(Error::Overflow);
+        }
+        self.out.extend_from_slice(self.lit);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Huffman sections (§5.2.1, PivCo layout)
+// =============================================================================
+
+fn split_huf_header(section: &[u8]) -> Result<(&[u8; HUF_TABLE_SIZE], &[u8])> {
+    let (lengths, runs) = section
+        .split_first_chunk::<HUF_TABLE_SIZE>()
+        .ok_or(Error::CorruptData)?;
+    Ok((lengths, runs))
+}
+
+#[derive(Clone, Copy)]
+struct HufNode {
+    child: [Option<u16>; 2],
+    sym: Option<u8>,
+    /// Depth of the complete subtree rooted here when this node is a flat
+    /// root, 0 otherwise.
+    flat_d: u8,
+    /// Inside a flat root's subtree: no run of its own.
+    covered: bool,
+}
+
+impl HufNode {
+    /// The child a 1 bit (`right`) or a 0 bit leads to.
+    fn child(&self, right: bool) -> Option<u16> {
+        let [left, r] = self.child;
+        if right { r } else { left }
+    }
+
+    fn child_mut(&mut self, right: bool) -> &mut Option<u16> {
+        let [left, r] = &mut self.child;
+        if right { r } else { left }
+    }
+}
+
+/// The canonical code tree rebuilt from the 256 packed code lengths.
+struct HufTree {
+    nodes: Vec<HufNode>,
+    /// Node indices in BFS order (parents first, left before right), which is
+    /// the wire order of the runs.
+    bfs: Vec<u16>,
+}
+
+impl HufTree {
+    fn from_packed(packed: &[u8; HUF_TABLE_SIZE]) -> Result<Self> {
+        let mut code_len = [0u8; HUF_NUM_SYMBOLS];
+        for (pair, &b) in code_len.chunks_exact_mut(2).zip(packed) {
+            if let [lo, hi] = pair {
+                *lo = b & 0x0F;
+                *hi = b >> 4;
+            }
+        }
+        Self::build(&code_len)
+    }
+
+    fn build(code_len: &[u8; HUF_NUM_SYMBOLS]) -> Result<Self> {
+        let mut bl_count = [0u32; HUF_MAX_CODE_LEN + 1];
+        for &l in code_len {
+            *at_mut(&mut bl_count, usize::from(l))? += 1;
+        }
+        bl_count[0] = 0;
+        let present: u32 = bl_count.iter().sum();
+        let kraft_ok = match present {
+            0 => false,
+            // Degenerate single-symbol table: the lone code must be 1 bit.
+            1 => bl_count[1] == 1,
+            _ => {
+                bl_count
+                    .iter()
+                    .enumerate()
+                    .map(|(l, &n)| n << (HUF_MAX_CODE_LEN - l))
+                    .sum::<u32>()
+                    == 1 << HUF_MAX_CODE_LEN
+            }
+        };
+        if !kraft_ok {
+            return Err(Error::CorruptData);
+        }
+
+        let mut next_code = [0u32; HUF_MAX_CODE_LEN + 1];
+        let mut code = 0;
+        for (next, &shorter) in next_code.iter_mut().skip(1).zip(&bl_count) {
+            code = (code + shorter) << 1;
+            *next = code;
+        }
+
+        let empty = HufNode {
+            child: [None, None],
+            sym: None,
+            flat_d: 0,
+            covered: false,
+        };
+        let mut nodes = vec![empty];
+        for (sym, &l) in code_len.iter().enumerate() {
+            if l == 0 {
+                continue;
+            }
+            let next_len = at_mut(&mut next_code, usize::from(l))?;
+            let c = *next_len;
+            *next_len += 1;
+            let mut cur = 0;
+            for d in (0..l).rev() {
+                let next = nodes.len();
+                let node = at_mut(&mut nodes, cur)?;
+                if node.sym.is_some() {
+                    return Err(Error::CorruptData);
+                }
+                let slot = node.child_mut((c >> d) & 1 != 0);
+                cur = match *slot {
+                    Some(id) => usize::from(id),
+                    None => {
+                        *slot = Some(next as u16);
+                        nodes.push(empty);
+                        next
+                    }
+                };
+            }
+            let leaf = at_mut(&mut nodes, cur)?;
+            if leaf.child != [None, None] || leaf.sym.is_some() {
+                return Err(Error::CorruptData);
+            }
+            leaf.sym = Some(sym as u8);
+        }
+
+        let mut bfs = vec![0u16];
+        let mut head = 0;
+        while let Some(&id) = bfs.get(head) {
+            let node = at(&nodes, usize::from(id))?;
+            bfs.extend(node.child.iter().flatten());
+            head += 1;
+        }
+
+        // Leaf depth range below every node, children first.
+        let mut depth = vec![(0u8, 0u8); nodes.len()];
+        for &id in bfs.iter().rev() {
+            let node = at(&nodes, usize::from(id))?;
+            *at_mut(&mut depth, usize::from(id))? = match node.child {
+                [Some(l), Some(r)] => {
+                    let (l, r) = (*at(&depth, usize::from(l))?, *at(&depth, usize::from(r))?);
+                    (1 + l.0.min(r.0), 1 + l.1.max(r.1))
+                }
+                [None, None] => (0, 0),
+                // A single-child node is never flat.
+                _ => (0, HUF_MAX_CODE_LEN as u8),
+            };
+        }
+        for &id in &bfs {
+            let (min, max) = *at(&depth, usize::from(id))?;
+            let node = at_mut(&mut nodes, usize::from(id))?;
+            if !node.covered && node.sym.is_none() && min == max && min >= 2 {
+                node.flat_d = min;
+            }
+            let covered = node.covered || node.flat_d != 0;
+            for child in node.child.into_iter().flatten() {
+                at_mut(&mut nodes, usize::from(child))?.covered = covered;
+            }
+        }
+        Ok(Self { nodes, bfs })
+    }
+
+    /// Decodes `n` symbols from the node runs that follow the lengths header.
+    fn decode(&self, runs: &[u8], n: usize) -> Result<Vec<u8>> {
+        if n == 0 {
+            return Err(Error::CorruptData);
+        }
+        // Pass 1: walk the runs in BFS order; popcounting a bitmap node's
+        // bits gives its right child's symbol count.
+        let mut count = vec![0usize; self.nodes.len()];
+        let mut run_at = vec![0usize; self.nodes.len()];
+        *at_mut(&mut count, 0)? = n;
+        let mut p = 0;
+        for &id in &self.bfs {
+            let id = usize::from(id);
+            let node = at(&self.nodes, id)?;
+            if node.covered || node.sym.is_some() {
+                continue;
+            }
+            let c = *at(&count, id)?;
+            *at_mut(&mut run_at, id)? = p;
+            if node.flat_d != 0 {
+                p += (c * usize::from(node.flat_d)).div_ceil(8);
+                if p > runs.len() {
+                    return Err(Error::CorruptData);
+                }
+                continue;
+            }
+            let bytes = take(runs, p, c.div_ceil(8)).map_err(|_| Error::CorruptData)?;
+            p += bytes.len();
+            let ones = (0..c).filter(|&i| bit(bytes, i)).count();
+            for (child, routed) in node.child.iter().zip([c - ones, ones]) {
+                match child {
+                    Some(child) => *at_mut(&mut count, usize::from(*child))? = routed,
+                    None if routed != 0 => return Err(Error::CorruptData),
+                    None => {}
+                }
+            }
+        }
+
+        // Pass 2: route every symbol root-to-leaf, consuming the next bit
+        // (or packed flat code) of each node it passes through.
+        let mut cursor = vec![0usize; self.nodes.len()];
+        let mut out = Vec::with_capacity(n);
+        for _ in 0..n {
+            let mut id = 0;
+            loop {
+                let node = at(&self.nodes, id)?;
+                if let Some(sym) = node.sym {
+                    out.push(sym);
+                    break;
+                }
+                let next = at_mut(&mut cursor, id)?;
+                let bit_at = *at(&run_at, id)? * 8 + *next;
+                if node.flat_d != 0 {
+                    *next += usize::from(node.flat_d);
+                    for j in 0..usize::from(node.flat_d) {
+                        id = self.child(id, bit_checked(runs, bit_at + j)?)?;
+                    }
+                    continue;
+                }
+                *next += 1;
+                id = self.child(id, bit_checked(runs, bit_at)?)?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn child(&self, id: usize, right: bool) -> Result<usize> {
+        at(&self.nodes, id)?
+            .child(right)
+            .map(usize::from)
+            .ok_or(Error::CorruptData)
+    }
+}
+
+/// Bit `i` of `bytes`, least significant first; clear past the end.
+fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes.get(i / 8).is_some_and(|b| (b >> (i % 8)) & 1 != 0)
+}
+
+fn bit_checked(bytes: &[u8], i: usize) -> Result<bool> {
+    let byte = bytes.get(i / 8).ok_or(Error::CorruptData)?;
+    Ok((byte >> (i % 8)) & 1 != 0)
+}
+
+// =============================================================================
+// Hashes (§3, §4, §7)
+// =============================================================================
+
+/// Dictionary id binding the content and, when present, the shared table.
+pub(crate) fn dict_id(content: &[u8], huf: Option<&[u8; HUF_TABLE_SIZE]>) -> u32 {
+    let base = checksum(content);
+    match huf {
+        Some(table) => fold32(rapidhash(table, u64::from(base))),
+        None => base,
+    }
+}
+
+// =============================================================================
+// Byte helpers
+// =============================================================================
+
+pub(crate) fn take(src: &[u8], at: usize, len: usize) -> Result<&[u8]> {
+    at.checked_add(len)
+        .and_then(|end| src.get(at..end))
+        .ok_or(Error::SrcTooSmall)
+}
+
+fn read_u16(src: &[u8], at: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes(*read_array(src, at)?))
+}
+
+pub(crate) fn read_u32(src: &[u8], at: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(*read_array(src, at)?))
+}
+
+pub(crate) fn read_u64(src: &[u8], at: usize) -> Result<u64> {
+    Ok(u64::from_le_bytes(*read_array(src, at)?))
+}
+
+fn read_array<const N: usize>(src: &[u8], at: usize) -> Result<&[u8; N]> {
+    src.get(at..)
+        .and_then(<[u8]>::first_chunk)
+        .ok_or(Error::SrcTooSmall)
+}
+
+/// `v[i]`, failing as corrupt data rather than panicking: the decoder's
+/// tables are only indexed through these.
+fn at<T>(v: &[T], i: usize) -> Result<&T> {
+    v.get(i).ok_or(Error::CorruptData)
+}
+
+fn at_mut<T>(v: &mut [T], i: usize) -> Result<&mut T> {
+    v.get_mut(i).ok_or(Error::CorruptData)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::{
+        CompressOptions, Dictionary, Level, compress_with_options,
+        decompress_with_options as c_decompress,
+    };
+
+    /// The `Hello ZXC\n` archive from docs/FORMAT.md §14.1.
+    const FORMAT_EXAMPLE: [u8; 58] = [
+        0xF5, 0x2E, 0xB0, 0x9C, 0x07, 0x13, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3E,
+        0x5D, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x69, 0x48, 0x65, 0x6C, 0x6C, 0x6F, 0x20,
+        0x5A, 0x58, 0x43, 0x0A, 0x90, 0xBB, 0xA1, 0x75, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x02, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x90, 0xBB, 0xA1, 0x75,
+    ];
+
+    /// Text-like input long enough for Huffman literal and token sections.
+    fn text(len: usize) -> Vec<u8> {
+        const WORDS: [&[u8]; 12] = [
+            b"the",
+            b"quick",
+            b"brown",
+            b"fox",
+            b"jumps",
+            b"over",
+            b"lazy",
+            b"dog",
+            b"zxc",
+            b"decoder",
+            b"literal",
+            b"sequence",
+        ];
+        let mut s: u32 = 0x2107;
+        let mut out = Vec::with_capacity(len);
+        while out.len() < len {
+            s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            out.extend_from_slice(WORDS[(s >> 16) as usize % WORDS.len()]);
+            out.push(b" \n,."[(s >> 8) as usize & 3]);
+        }
+        out.truncate(len);
+        out
+    }
+
+    #[test]
+    fn test_format_example() {
+        assert_eq!(checksum(b"Hello ZXC\n"), 0x75A1_BB90);
+        assert_eq!(decompress(&FORMAT_EXAMPLE).unwrap(), b"Hello ZXC\n");
+    }
+
+    #[test]
+    fn test_rapidhash_lengths() {
+        // Every length class of the hash: empty, 1-3, 4-7, 8-16, the 16-byte
+        // tail rounds and the 112-byte main loop.
+        let data = text(1000);
+        for len in [
+            0, 1, 3, 4, 7, 8, 16, 17, 33, 49, 65, 81, 97, 112, 113, 200, 225, 1000,
+        ] {
+            let opts = CompressOptions::with_level(Level::Fastest);
+            let frame = compress_with_options(&data[..len], &opts).unwrap();
+            assert_eq!(decompress(&frame).unwrap(), &data[..len], "len {len}");
+        }
+    }
+
+    #[test]
+    fn test_matches_c_decoder_all_levels() {
+        let inputs = [
+            text(300_000),
+            fixtures::input(),
+            vec![0x5A; 70_000],
+            Vec::new(),
+        ];
+        for data in &inputs {
+            for &level in Level::all() {
+                for checksum in [false, true] {
+                    let mut opts = CompressOptions::with_level(level);
+                    opts.checksum = checksum;
+                    let frame = compress_with_options(data, &opts).unwrap();
+                    let expected = c_decompress(&frame, &DecompressOptions::default()).unwrap();
+                    assert_eq!(
+                        decompress(&frame).unwrap(),
+                        expected,
+                        "{level:?} checksum={checksum} len={}",
+                        data.len()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_seekable_and_fixtures() {
+        let data = text(200_000);
+        let opts = CompressOptions::with_level(Level::Compact).with_seekable();
+        let frame = compress_with_options(&data, &opts).unwrap();
+        assert_eq!(decompress(&frame).unwrap(), data);
+
+        let input = fixtures::input();
+        assert_eq!(decompress(fixtures::LE_DEFAULT_CHECKSUM).unwrap(), input);
+        assert_eq!(decompress(fixtures::LE_DENSITY).unwrap(), input);
+        for (level, _, frame) in fixtures::CLI {
+            assert_eq!(decompress(frame).unwrap(), input, "CLI level {level}");
+        }
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let samples: Vec<Vec<u8>> = (0..64)
+            .map(|i| {
+                alloc::format!(
+                    "{{\"event\":\"login\",\"user_id\":{i},\"status\":\"ok\",\"region\":\"eu-west-{}\"}}",
+                    i % 3
+                )
+                .into_bytes()
+            })
+            .collect();
+        let refs: Vec<&[u8]> = samples.iter().map(Vec::as_slice).collect();
+        let dict = Dictionary::train(&refs).unwrap();
+        let sample = samples[..8].concat();
+
+        for level in [Level::Default, Level::Density, Level::Ultra] {
+            let opts = CompressOptions::with_level(level).with_dictionary(&dict);
+            let frame = compress_with_options(&sample, &opts).unwrap();
+            let dopts = DecompressOptions::default().with_dictionary(&dict);
+            assert_eq!(decompress_with_options(&frame, &dopts).unwrap(), sample);
+
+            assert!(matches!(decompress(&frame), Err(Error::DictRequired)));
+            let wrong = DecompressOptions::default().with_dict(b"not the dictionary".to_vec());
+            assert!(matches!(
+                decompress_with_options(&frame, &wrong),
+                Err(Error::DictMismatch)
+            ));
+        }
+
+        let opts = CompressOptions::default().with_dict(dict.content().to_vec());
+        let frame = compress_with_options(&sample, &opts).unwrap();
+        let dopts = DecompressOptions::default().with_dict(dict.content().to_vec());
+        assert_eq!(decompress_with_options(&frame, &dopts).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let data = text(20_000);
+        let opts = CompressOptions::with_level(Level::Ultra);
+        let frame = compress_with_options(&data, &opts).unwrap();
+
+        // Checksums cover every byte: any flipped bit must be reported.
+        for i in 0..frame.len() {
+            let mut bad = frame.clone();
+            bad[i] ^= 0x10;
+            assert!(decompress(&bad).is_err(), "flip at {i} went unnoticed");
+        }
+        for len in 0..frame.len() {
+            assert!(decompress(&frame[..len]).is_err(), "truncated to {len}");
+        }
+        let mut bad = frame.clone();
+        bad[FILE_HEADER_SIZE + BLOCK_HEADER_SIZE] ^= 0x10;
+        assert!(matches!(
+            decompress(&bad),
+            Err(Error::Corrupted {
+                block: Some(0),
+                offset: 16,
+                ..
+            })
+        ));
+        assert!(matches!(
+            decompress(b"definitely not a zxc frame, just text"),
+            Err(Error::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn test_unchecked_corruption_never_panics() {
+        // Without checksums the decoder sees the damage directly: it must
+        // fail cleanly or produce output, never panic or overrun.
+        let data = text(30_000);
+        for level in [Level::Fast, Level::Compact, Level::Ultra] {
+            let opts = CompressOptions::with_level(level).without_checksum();
+            let frame = compress_with_options(&data, &opts).unwrap();
+            let mut s: u32 = 0x2107;
+            for _ in 0..2000 {
+                s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let mut bad = frame.clone();
+                let at = (s >> 8) as usize % bad.len();
+                bad[at] ^= 1 << (s & 7);
+                let _ = decompress(&bad);
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Recompression of a frame with other settings, block by block.
+//!
+//! A [`Recompressor`] is fed a frame in slices and emits the same data as a
+//! new frame at another level, with or without checksums, or with another
+//! block size. The data goes through a [`DStream`] and a [`CStream`]
+//! back to back, so at most a block or two of each side is held at a time,
+//! whatever the size of the frame.
+
+use alloc::vec::Vec;
+
+use crate::scratch::Scratch;
+use crate::{CStream, CompressOptions, DStream, DecompressOptions, Error, Level, Result};
+
+/// Settings of the frames written by a [`Recompressor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecompressOptions {
+    /// Compression level of the new frame (default: `Level::Default`)
+    pub level: Level,
+    /// Enable checksum in the new frame (default: `true`)
+    pub checksum: bool,
+    /// Block size of the new frame: a power of two from 4 KiB to 2 MiB, or
+    /// 0 for the default of 512 KiB (default: 0)
+    pub block_size: usize,
+    /// Verify the checksum of the frame being read (default: `true`)
+    pub verify_checksum: bool,
+}
+
+impl Default for RecompressOptions {
+    fn default() -> Self {
+        Self {
+            level: Level::Default,
+            checksum: true,
+            block_size: 0,
+            verify_checksum: true,
+        }
+    }
+}
+
+impl RecompressOptions {
+    /// Create options with the specified compression level.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            level,
+            ..Default::default()
+        }
+    }
+}
+
+/// Rewrites one frame with new settings, as a push-style transform.
+///
+/// Compressed input goes into [`write`](Self::write) and recompressed
+/// output is appended to the `Vec` passed along; [`finish`](Self::finish)
+/// checks that the input frame is complete and ends the output frame.
+///
+/// ```rust
+/// use zxc::{Level, RecompressOptions, Recompressor};
+///
+/// let data = b"cold data, compressed fast at ingest time. ".repeat(2000);
+/// let hot = zxc::compress(&data, Level::Fastest, None)?;
+///
+/// let mut tier = Recompressor::new(&RecompressOptions::with_level(Level::Compact))?;
+/// let mut cold = Vec::new();
+/// for chunk in hot.chunks(4096) {
+///     tier.write(chunk, &mut cold)?;
+/// }
+/// tier.finish(&mut cold)?;
+///
+/// assert_eq!(zxc::decompress(&cold)?, data);
+/// # Ok::<(), zxc::Error>(())
+/// ```
+///
+/// Dictionary frames are not supported, as with the push API.
+pub struct Recompressor {
+    ds: DStream,
+    cs: CStream,
+    plain: Scratch,
+    out: Scratch,
+}
+
+impl Recompressor {
+    /// Creates a transform writing frames with `opts`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadBlockSize`] if `opts.block_size` is out of range, and
+    /// [`Error::Memory`] if the streams cannot be allocated.
+    pub fn new(opts: &RecompressOptions) -> Result<Self> {
+        let ds = DStream::new(Some(&DecompressOptions {
+            verify_checksum: opts.verify_checksum,
+            ..Default::default()
+        }))?;
+        let copts = CompressOptions {
+            level: opts.level,
+            checksum: opts.checksum,
+            ..Default::default()
+        };
+        let cs = CStream::with_block_size(Some(&copts), opts.block_size)?;
+        let plain = Scratch::zeroed(ds.out_size());
+        let out = Scratch::zeroed(cs.out_size());
+        Ok(Self { ds, cs, plain, out })
+    }
+
+    /// Reads the next slice of the input frame, appending whatever
+    /// recompressed output is ready to `out`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::CorruptData`] for data past the end of the frame, and the
+    /// errors of [`DStream::decompress`] and [`CStream::compress`].
+    pub fn write(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> Result<()> {
+        while !input.is_empty() {
+            if self.ds.finished() {
+                return Err(Error::CorruptData);
+            }
+            let p = self.ds.decompress(input, &mut self.plain)?;
+            self.compress(p.produced, out)?;
+            input = input.get(p.consumed..).unwrap_or_default();
+            if p.consumed == 0 && p.produced == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the input frame ended and appends the end of the output
+    /// frame to `out`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::SrcTooSmall`] if the input frame is truncated.
+    pub fn finish(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        while !self.ds.finished() {
+            let p = self.ds.decompress(&[], &mut self.plain)?;
+            self.compress(p.produced, out)?;
+            if p.produced == 0 && !self.ds.finished() {
+                return Err(Error::SrcTooSmall);
+            }
+        }
+        loop {
+            let p = self.cs.end(&mut self.out)?;
+            out.extend_from_slice(self.out.get(..p.produced).unwrap_or_default());
+            if p.pending == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Compresses the first `len` bytes of `plain` into `out`.
+    fn compress(&mut self, len: usize, out: &mut Vec<u8>) -> Result<()> {
+        let mut input = self.plain.get(..len).unwrap_or_default();
+        while !input.is_empty() {
+            let p = self.cs.compress(input, &mut self.out)?;
+            out.extend_from_slice(self.out.get(..p.produced).unwrap_or_default());
+            input = input.get(p.consumed..).unwrap_or_default();
+        }
+        Ok(())
+    }
+}
+
+/// Recompresses the frame read from `reader` with `opts`, writing the new
+/// frame to `writer` as it goes.
+///
+/// Returns the number of bytes written. Fails with
+/// [`std::io::ErrorKind::UnexpectedEof`] if the input frame is truncated.
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use zxc::{Level, RecompressOptions};
+///
+/// let opts = RecompressOptions {
+///     block_size: 1 << 21,
+///     ..RecompressOptions::with_level(Level::Ultra)
+/// };
+/// let hot = File::open("/data/hot/events.zxc")?;
+/// let cold = File::create("/data/cold/events.zxc")?;
+/// zxc::recompress_stream(hot, cold, &opts)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn recompress_stream<R: std::io::Read, W: std::io::Write>(
+    mut reader: R,
+    mut writer: W,
+    opts: &RecompressOptions,
+) -> std::io::Result<u64> {
+    use std::io;
+
+    let mut tier = Recompressor::new(opts).map_err(io::Error::other)?;
+    let mut input = Scratch::zeroed(1 << 16);
+    let mut out = Vec::new();
+    let mut written = 0;
+    loop {
+        let n = match reader.read(&mut input) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        match n {
+            0 => match tier.finish(&mut out) {
+                Err(Error::SrcTooSmall) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        Error::SrcTooSmall,
+                    ));
+                }
+                other => other.map_err(io::Error::other)?,
+            },
+            _ => tier
+                .write(input.get(..n).unwrap_or_default(), &mut out)
+                .map_err(io::Error::other)?,
+        }
+        writer.write_all(&out)?;
+        written += out.len() as u64;
+        out.clear();
+        if n == 0 {
+            writer.flush()?;
+            return Ok(written);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recompress() {
+        let data = crate::fixtures::input().repeat(40);
+        let hot = crate::compress(&data, Level::Fastest, Some(true)).unwrap();
+
+        for (opts, chunk) in [
+            (RecompressOptions::with_level(Level::Compact), 1 << 20),
+            (
+                RecompressOptions {
+                    checksum: false,
+                    block_size: 1 << 12,
+                    ..Default::default()
+                },
+                777,
+            ),
+        ] {
+            let mut tier = Recompressor::new(&opts).unwrap();
+            let mut cold = Vec::new();
+            for c in hot.chunks(chunk) {
+                tier.write(c, &mut cold).unwrap();
+            }
+            tier.finish(&mut cold).unwrap();
+            assert_eq!(crate::decompress(&cold).unwrap(), data);
+            // The block size code sits in the sixth byte of the file header.
+            let code = match opts.block_size {
+                0 => 19,
+                n => n.trailing_zeros() as u8,
+            };
+            assert_eq!(cold[5], code);
+        }
+
+        // Bad settings, and frames that are truncated or followed by data.
+        let odd = RecompressOptions {
+            block_size: 3000,
+            ..Default::default()
+        };
+        assert!(matches!(Recompressor::new(&odd), Err(Error::BadBlockSize)));
+        let opts = RecompressOptions::default();
+        let mut out = Vec::new();
+        let mut tier = Recompressor::new(&opts).unwrap();
+        tier.write(&hot[..hot.len() - 3], &mut out).unwrap();
+        assert!(matches!(tier.finish(&mut out), Err(Error::SrcTooSmall)));
+        let mut tier = Recompressor::new(&opts).unwrap();
+        let trailing = [&hot[..], b"!"].concat();
+        assert!(matches!(
+            tier.write(&trailing, &mut out),
+            Err(Error::CorruptData)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_recompress_stream() {
+        let data = crate::fixtures::input().repeat(40);
+        let hot = crate::compress(&data, Level::Fastest, None).unwrap();
+        let mut cold = Vec::new();
+        let opts = RecompressOptions::with_level(Level::Balanced);
+        let n = recompress_stream(&hot[..], &mut cold, &opts).unwrap();
+        assert_eq!(n, cold.len() as u64);
+        assert_eq!(crate::decompress(&cold).unwrap(), data);
+
+        let err = recompress_stream(&hot[..hot.len() - 1], std::io::sink(), &opts).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
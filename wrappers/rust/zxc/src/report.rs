@@ -0,0 +1,193 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Display of compression ratios and throughputs, for tools reporting on
+//! what they did.
+//!
+//! [`Ratio`] displays as `3.42x` and [`Throughput`] in binary units, as
+//! `1.8 GiB/s`. Both take a precision (`{:.1}`) and pad to a width
+//! (`{:>10}`), so they fit in tables. The stats structs of the crate give
+//! them directly:
+//!
+//! ```rust
+//! use zxc::report::{Ratio, Throughput};
+//! use std::time::Duration;
+//!
+//! assert_eq!(Ratio::new(3_420, 1_000).to_string(), "3.42x");
+//! let speed = Throughput::new(3 << 29, Duration::from_millis(800));
+//! assert_eq!(format!("{speed:>10}"), " 1.9 GiB/s");
+//! ```
+
+use alloc::format;
+use core::fmt;
+use core::time::Duration;
+
+/// Uncompressed size over compressed size. Displays with two decimals by
+/// default, as `3.42x`, or `-` when the compressed size is 0.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Ratio(pub f64);
+
+impl Ratio {
+    /// The ratio of `uncompressed` to `compressed` bytes.
+    pub fn new(uncompressed: u64, compressed: u64) -> Self {
+        Self(uncompressed as f64 / compressed as f64)
+    }
+
+    /// The share of the uncompressed size saved, `1 - 1 / ratio`: 0.75 for
+    /// a ratio of 4, negative for data that grew.
+    pub fn space_saving(self) -> f64 {
+        1.0 - 1.0 / self.0
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.0.is_finite() {
+            return pad(f, "-");
+        }
+        let precision = f.precision().unwrap_or(2);
+        pad(f, &format!("{:.precision$}x", self.0))
+    }
+}
+
+/// Bytes per second. Displays in binary units with one decimal by default,
+/// as `1.8 GiB/s`, or `-` when no time elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Throughput(pub f64);
+
+impl Throughput {
+    /// `bytes` processed in `elapsed`.
+    pub fn new(bytes: u64, elapsed: Duration) -> Self {
+        Self(bytes as f64 / elapsed.as_secs_f64())
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [(f64, &str); 4] = [
+            ((1u64 << 40) as f64, "TiB/s"),
+            ((1u64 << 30) as f64, "GiB/s"),
+            ((1u64 << 20) as f64, "MiB/s"),
+            ((1u64 << 10) as f64, "KiB/s"),
+        ];
+        if !self.0.is_finite() {
+            return pad(f, "-");
+        }
+        let precision = f.precision().unwrap_or(1);
+        match UNITS.iter().find(|(unit, _)| self.0 >= *unit) {
+            Some((unit, name)) => pad(f, &format!("{:.precision$} {name}", self.0 / unit)),
+            None => pad(f, &format!("{:.0} B/s", self.0)),
+        }
+    }
+}
+
+/// Writes `s` to the formatter's width, right-aligned like a number unless
+/// asked otherwise. [`fmt::Formatter::pad`] would cut it to the precision,
+/// which here is the number's.
+fn pad(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    let Some(width) = f.width() else {
+        return f.write_str(s);
+    };
+    match f.align() {
+        Some(fmt::Alignment::Left) => write!(f, "{s:<width$}"),
+        Some(fmt::Alignment::Center) => write!(f, "{s:^width$}"),
+        _ => write!(f, "{s:>width$}"),
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::VerifyReport {
+    /// The frame's compression ratio.
+    pub fn ratio(&self) -> Ratio {
+        Ratio::new(self.decompressed_size, self.compressed_size)
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::FileStats {
+    /// The file's compression ratio.
+    pub fn ratio(&self) -> Ratio {
+        Ratio::new(self.bytes_in, self.bytes_out)
+    }
+
+    /// Uncompressed bytes per second.
+    pub fn throughput(&self) -> Throughput {
+        Throughput::new(self.bytes_in, self.elapsed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::FollowStats {
+    /// The compression ratio over all frames written.
+    pub fn ratio(&self) -> Ratio {
+        Ratio::new(self.bytes_in, self.bytes_out)
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::metrics::Sample {
+    /// The compression ratio of the operation, whichever its direction.
+    pub fn ratio(&self) -> Ratio {
+        match self.operation.is_compress() {
+            true => Ratio::new(self.bytes_in, self.bytes_out),
+            false => Ratio::new(self.bytes_out, self.bytes_in),
+        }
+    }
+
+    /// Uncompressed bytes per second, whichever the direction.
+    pub fn throughput(&self) -> Throughput {
+        match self.operation.is_compress() {
+            true => Throughput::new(self.bytes_in, self.elapsed),
+            false => Throughput::new(self.bytes_out, self.elapsed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_ratio_display() {
+        assert_eq!(Ratio::new(3_420, 1_000).to_string(), "3.42x");
+        assert_eq!(Ratio::new(1, 3).to_string(), "0.33x");
+        assert_eq!(format!("{:.1}", Ratio::new(10, 4)), "2.5x");
+        assert_eq!(format!("{:>7}", Ratio::new(2, 1)), "  2.00x");
+        assert_eq!(Ratio::new(10, 0).to_string(), "-");
+        assert_eq!(Ratio::new(0, 0).to_string(), "-");
+        assert_eq!(Ratio::new(4, 1).space_saving(), 0.75);
+    }
+
+    #[test]
+    fn test_throughput_display() {
+        let second = Duration::from_secs(1);
+        assert_eq!(Throughput::new(512, second).to_string(), "512 B/s");
+        assert_eq!(Throughput::new(1536, second).to_string(), "1.5 KiB/s");
+        assert_eq!(Throughput::new(5 << 20, second).to_string(), "5.0 MiB/s");
+        assert_eq!(
+            Throughput::new(18 << 29, Duration::from_secs(5)).to_string(),
+            "1.8 GiB/s"
+        );
+        assert_eq!(
+            format!("{:.2}", Throughput::new(3 << 40, second)),
+            "3.00 TiB/s"
+        );
+        assert_eq!(format!("{:<9}|", Throughput::new(0, second)), "0 B/s    |");
+        assert_eq!(Throughput::new(100, Duration::ZERO).to_string(), "-");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_stats_ratio() {
+        let frame = crate::compress(&[b'z'; 100_000], crate::Level::Default, None).unwrap();
+        let report = crate::verify(&frame).unwrap();
+        #[cfg(not(feature = "mock-backend"))]
+        assert!(report.ratio().0 > 100.0);
+        assert_eq!(report.ratio(), Ratio::new(100_000, frame.len() as u64));
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! C-ABI block callbacks for RocksDB.
+//!
+//! RocksDB compresses each SST data block (4 KiB by default, rarely more
+//! than a few hundred KiB) as a unit, and stores the uncompressed size next
+//! to it. That is the Block API's contract, so [`CODEC`] exposes it as plain
+//! `extern "C"` function pointers that a C++ `rocksdb::Compressor` shim can
+//! call: no frame header or footer, no checksum (SST blocks carry their
+//! own), and one compression and one decompression context per thread,
+//! reused across calls.
+//!
+//! ```rust
+//! use zxc::rocksdb::CODEC;
+//!
+//! let block = b"key0001value key0002value key0003value ".repeat(100);
+//! let mut packed = vec![0u8; (CODEC.compress_bound)(block.len())];
+//! let n = unsafe {
+//!     (CODEC.compress)(3, block.as_ptr(), block.len(), packed.as_mut_ptr(), packed.len())
+//! };
+//! assert!(n > 0 && (n as usize) < block.len());
+//!
+//! // RocksDB knows the uncompressed size, and allocates exactly that.
+//! let mut out = vec![0u8; block.len()];
+//! let m = unsafe { (CODEC.decompress)(packed.as_ptr(), n as usize, out.as_mut_ptr(), out.len()) };
+//! assert_eq!(m as usize, block.len());
+//! assert_eq!(out, block);
+//! ```
+//!
+//! The `rust-rocksdb` crate does not let Rust code register a compressor,
+//! so the callbacks serve applications that embed RocksDB through C++ and
+//! link this crate; `examples/rocksdb.rs` applies the same codec to values
+//! from `rust-rocksdb`.
+
+use core::cell::RefCell;
+
+use crate::error::code_from_error;
+use crate::{
+    Cctx, CompressOptions, Dctx, DecompressOptions, Error, Level, Result, compress_block_bound,
+};
+
+/// The block callbacks, in one struct that can be handed to C as a pointer.
+///
+/// Each function returns the number of bytes written, or a negative
+/// `zxc_error_t` code. Empty input is not an error: it compresses to, and
+/// decompresses from, zero bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BlockCodec {
+    /// `compress(level, src, src_len, dst, dst_cap)`: compresses one block
+    /// of at most 2 MiB. Levels outside 1 to 7 use the default level.
+    pub compress: unsafe extern "C" fn(i32, *const u8, usize, *mut u8, usize) -> i64,
+    /// `decompress(src, src_len, dst, dst_cap)`: decompresses one block.
+    /// `dst_cap` may be exactly the uncompressed size.
+    pub decompress: unsafe extern "C" fn(*const u8, usize, *mut u8, usize) -> i64,
+    /// `compress_bound(src_len)`: the `dst_cap` that `compress` needs, or 0
+    /// when `src_len` is above the block limit.
+    pub compress_bound: extern "C" fn(usize) -> usize,
+}
+
+/// The zxc block callbacks.
+pub const CODEC: BlockCodec = BlockCodec {
+    compress,
+    decompress,
+    compress_bound: bound,
+};
+
+std::thread_local! {
+    static CCTX: RefCell<Option<Cctx>> = const { RefCell::new(None) };
+    static DCTX: RefCell<Option<Dctx>> = const { RefCell::new(None) };
+}
+
+unsafe extern "C" fn compress(
+    level: i32,
+    src: *const u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_cap: usize,
+) -> i64 {
+    let level = Level::try_from(level).unwrap_or_default();
+    let opts = CompressOptions::with_level(level).without_checksum();
+    // SAFETY: the caller passes valid buffers of the given lengths.
+    code(
+        unsafe { buffers(src, src_len, dst, dst_cap) }.and_then(|(src, dst)| {
+            if src.is_empty() {
+                return Ok(0);
+            }
+            CCTX.with_borrow_mut(|cctx| match cctx {
+                Some(cctx) => cctx.compress_block(src, dst, &opts),
+                None => cctx
+                    .insert(Cctx::new(None)?)
+                    .compress_block(src, dst, &opts),
+            })
+        }),
+    )
+}
+
+unsafe extern "C" fn decompress(
+    src: *const u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_cap: usize,
+) -> i64 {
+    let opts = DecompressOptions {
+        verify_checksum: false,
+        ..Default::default()
+    };
+    // SAFETY: the caller passes valid buffers of the given lengths.
+    code(
+        unsafe { buffers(src, src_len, dst, dst_cap) }.and_then(|(src, dst)| {
+            if src.is_empty() {
+                return Ok(0);
+            }
+            DCTX.with_borrow_mut(|dctx| match dctx {
+                Some(dctx) => dctx.decompress_block_safe(src, dst, &opts),
+                None => dctx
+                    .insert(Dctx::new()?)
+                    .decompress_block_safe(src, dst, &opts),
+            })
+        }),
+    )
+}
+
+extern "C" fn bound(src_len: usize) -> usize {
+    usize::try_from(compress_block_bound(src_len)).unwrap_or(0)
+}
+
+/// Borrows the caller's buffers, rejecting null pointers with a length.
+///
+/// # Safety
+///
+/// Non-null pointers must be valid for their lengths, and must not overlap.
+unsafe fn buffers<'a>(
+    src: *const u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_cap: usize,
+) -> Result<(&'a [u8], &'a mut [u8])> {
+    if (src.is_null() && src_len > 0) || (dst.is_null() && dst_cap > 0) {
+        return Err(Error::NullInput);
+    }
+    let src = match src_len {
+        0 => &[][..],
+        _ => unsafe { core::slice::from_raw_parts(src, src_len) },
+    };
+    let dst = match dst_cap {
+        0 => &mut [][..],
+        _ => unsafe { core::slice::from_raw_parts_mut(dst, dst_cap) },
+    };
+    Ok((src, dst))
+}
+
+fn code(res: Result<usize>) -> i64 {
+    match res {
+        Ok(n) => n as i64,
+        Err(e) => code_from_error(&e).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_block_callbacks() {
+        let block = crate::fixtures::input();
+        for level in [0, 1, 3, 7, 42] {
+            let mut packed = vec![0u8; (CODEC.compress_bound)(block.len())];
+            let n = unsafe {
+                (CODEC.compress)(
+                    level,
+                    block.as_ptr(),
+                    block.len(),
+                    packed.as_mut_ptr(),
+                    packed.len(),
+                )
+            };
+            assert!(n > 0, "level {level}: {n}");
+            #[cfg(not(feature = "mock-backend"))]
+            assert!((n as usize) < block.len(), "level {level}: {n}");
+
+            let mut out = vec![0u8; block.len()];
+            let m = unsafe {
+                (CODEC.decompress)(packed.as_ptr(), n as usize, out.as_mut_ptr(), out.len())
+            };
+            assert_eq!(m as usize, block.len());
+            assert_eq!(out, block);
+
+            // A destination one byte short is an error code, not an overrun.
+            let m = unsafe {
+                (CODEC.decompress)(packed.as_ptr(), n as usize, out.as_mut_ptr(), out.len() - 1)
+            };
+            assert!(m < 0);
+        }
+
+        let empty = unsafe { (CODEC.compress)(1, core::ptr::null(), 0, core::ptr::null_mut(), 0) };
+        assert_eq!(empty, 0);
+        let empty = unsafe { (CODEC.decompress)(core::ptr::null(), 0, core::ptr::null_mut(), 0) };
+        assert_eq!(empty, 0);
+        let null = unsafe { (CODEC.compress)(1, core::ptr::null(), 8, core::ptr::null_mut(), 0) };
+        assert_eq!(null, i64::from(zxc_sys::ZXC_ERROR_NULL_INPUT));
+        let junk = [0xA5u8; 64];
+        let mut out = [0u8; 64];
+        let bad = unsafe { (CODEC.decompress)(junk.as_ptr(), 64, out.as_mut_ptr(), 64) };
+        assert!(bad < 0);
+
+        // Blocks above the 2 MiB limit have no bound.
+        assert_eq!((CODEC.compress_bound)(4 << 20), 0);
+    }
+}
@@ -0,0 +1,257 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! A [`Write`] target that compresses into a series of files, for writing
+//! logs compressed rather than compressing them after rotation.
+
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{CompressOptions, Encoder};
+
+/// When [`RotatingZxcWriter`] moves on to a new file.
+#[derive(Debug, Clone, Default)]
+pub struct RotateOptions {
+    /// Options of every file's frame (default: `CompressOptions::default()`)
+    pub compress: CompressOptions,
+    /// Uncompressed bytes per file (default: `None`, no limit). A write that
+    /// would go past it starts a new file, unless the current one is empty.
+    pub max_bytes: Option<u64>,
+    /// Time from a file's first write to the write that starts the next
+    /// file (default: `None`, no limit).
+    pub max_age: Option<Duration>,
+}
+
+/// A [`Write`] target that rotates through compressed files.
+///
+/// The files are named after `base` with a sequence number: `app.log`
+/// gives `app.log.0.zxc`, `app.log.1.zxc` and so on. Numbers already taken
+/// are skipped, so a restarted process never overwrites an earlier file.
+/// Each file holds one frame, which is finished (footer written and
+/// flushed) when the writer moves to the next file, on
+/// [`RotatingZxcWriter::finish`], and on drop.
+///
+/// A file is created by the first write after a rotation, so quiet periods
+/// do not leave empty files. The thresholds are checked on each write and
+/// a single write is never split across files: a line passed to one
+/// `write_all` stays in one file (`writeln!` may write it in pieces, so
+/// format it into a buffer first where that matters).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::io::Write;
+/// use std::time::Duration;
+/// use zxc::{RotateOptions, RotatingZxcWriter};
+///
+/// let opts = RotateOptions {
+///     max_bytes: Some(256 << 20),
+///     max_age: Some(Duration::from_secs(3600)),
+///     ..Default::default()
+/// };
+/// let mut log = RotatingZxcWriter::new("/var/log/app/app.log", opts);
+/// writeln!(log, "service started")?;
+/// log.finish()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct RotatingZxcWriter {
+    base: PathBuf,
+    opts: RotateOptions,
+    /// The next sequence number to try.
+    next: u64,
+    current: Option<Current>,
+}
+
+/// The file being written.
+struct Current {
+    path: PathBuf,
+    encoder: Encoder<BufWriter<File>>,
+    opened: Instant,
+    written: u64,
+}
+
+impl RotatingZxcWriter {
+    /// Creates a writer for files named after `base`. No file is created
+    /// before the first write.
+    pub fn new<P: Into<PathBuf>>(base: P, opts: RotateOptions) -> Self {
+        Self {
+            base: base.into(),
+            opts,
+            next: 0,
+            current: None,
+        }
+    }
+
+    /// The file being written, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|c| c.path.as_path())
+    }
+
+    /// Finishes the current file now; the next write starts a new one.
+    /// Returns the path of the finished file, if there was one.
+    pub fn rotate(&mut self) -> io::Result<Option<PathBuf>> {
+        let Some(current) = self.current.take() else {
+            return Ok(None);
+        };
+        let mut file = current.encoder.finish()?;
+        file.flush()?;
+        Ok(Some(current.path))
+    }
+
+    /// Finishes the current file, reporting the errors [`Drop`] cannot.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.rotate().map(drop)
+    }
+
+    /// Rotates if writing `len` more bytes would cross a threshold, then
+    /// returns the file to write to, created if need be.
+    fn current(&mut self, len: usize) -> io::Result<&mut Current> {
+        if let Some(c) = &self.current {
+            let full = self
+                .opts
+                .max_bytes
+                .is_some_and(|max| c.written > 0 && c.written + len as u64 > max);
+            let old = self
+                .opts
+                .max_age
+                .is_some_and(|max| c.opened.elapsed() >= max);
+            if full || old {
+                self.rotate()?;
+            }
+        }
+        if self.current.is_none() {
+            let (path, file) = self.create()?;
+            let encoder = Encoder::with_options(BufWriter::new(file), Some(&self.opts.compress))
+                .map_err(io::Error::other)?;
+            self.current = Some(Current {
+                path,
+                encoder,
+                opened: Instant::now(),
+                written: 0,
+            });
+        }
+        self.current
+            .as_mut()
+            .ok_or_else(|| io::Error::other("zxc: no current file"))
+    }
+
+    /// Creates the file with the first free sequence number.
+    fn create(&mut self) -> io::Result<(PathBuf, File)> {
+        loop {
+            let mut name = OsString::from(self.base.as_os_str());
+            name.push(format!(".{}.zxc", self.next));
+            let path = PathBuf::from(name);
+            self.next += 1;
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((path, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Write for RotatingZxcWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let current = self.current(buf.len())?;
+        current.encoder.write_all(buf)?;
+        current.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    /// Flushes the compressed bytes written so far. The current file is
+    /// only readable once finished; see [`RotatingZxcWriter::rotate`].
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.current {
+            Some(c) => c.encoder.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for RotatingZxcWriter {
+    fn drop(&mut self) {
+        // Best-effort, as for Encoder: call finish() to see errors.
+        let _ = self.rotate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn unpack(path: &Path) -> Vec<u8> {
+        crate::decompress(&fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_rotating_writer() {
+        let dir = std::env::temp_dir().join(format!("zxc_rotate_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("app.log");
+        let name = |n: u64| dir.join(format!("app.log.{n}.zxc"));
+
+        // By size: 100 lines of 20 bytes, 500 bytes per file.
+        let opts = RotateOptions {
+            max_bytes: Some(500),
+            ..Default::default()
+        };
+        let mut log = RotatingZxcWriter::new(&base, opts.clone());
+        assert_eq!(log.path(), None);
+        for i in 0..100 {
+            writeln!(log, "request {i:>11}").unwrap();
+        }
+        assert_eq!(log.path(), Some(name(3).as_path()));
+        log.finish().unwrap();
+        let mut all = Vec::new();
+        for n in 0..4 {
+            let part = unpack(&name(n));
+            assert_eq!(part.len(), 500);
+            all.extend(part);
+        }
+        assert!(!name(4).exists());
+        assert!(all.starts_with(b"request           0\n"));
+        assert!(all.ends_with(b"request          99\n"));
+
+        // A write bigger than the limit gets a file of its own; a restart
+        // carries on after the existing files; drop finishes the frame.
+        {
+            let mut log = RotatingZxcWriter::new(&base, opts);
+            log.write_all(&[b'x'; 2000]).unwrap();
+            log.write_all(b"after").unwrap();
+            assert_eq!(log.rotate().unwrap(), Some(name(5)));
+            assert_eq!(log.rotate().unwrap(), None);
+            log.write_all(b"dropped").unwrap();
+        }
+        assert_eq!(unpack(&name(4)), [b'x'; 2000]);
+        assert_eq!(unpack(&name(5)), b"after");
+        assert_eq!(unpack(&name(6)), b"dropped");
+
+        // By age.
+        let opts = RotateOptions {
+            max_age: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let mut log = RotatingZxcWriter::new(&base, opts);
+        log.write_all(b"early").unwrap();
+        std::thread::sleep(Duration::from_millis(80));
+        log.write_all(b"late").unwrap();
+        log.finish().unwrap();
+        assert_eq!(unpack(&name(7)), b"early");
+        assert_eq!(unpack(&name(8)), b"late");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
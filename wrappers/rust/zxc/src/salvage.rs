@@ -0,0 +1,480 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Best-effort restore of damaged frames.
+//!
+//! [`decompress_stream_salvage`] reads a frame block by block and decodes
+//! each one on its own through the Block API. A block that fails to decode
+//! is recorded in a [`SalvageReport`] and either replaced by zeros or left
+//! out, and decoding carries on with the next one. Blocks carry no sync
+//! marker, so a damaged *block header* ends the salvage: nothing after it
+//! can be found again.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::file::stream_error;
+use crate::format::{
+    BLOCK_CHECKSUM_SIZE, BLOCK_EOF, BLOCK_HEADER_SIZE, BLOCK_SIZE_LOG2, FILE_FOOTER_SIZE,
+    FILE_HEADER_SIZE, FLAG_HAS_CHECKSUM, FLAG_HAS_DICTIONARY, MAGIC_WORD,
+};
+use crate::hash::{hash8, hash16};
+use crate::locate::checksum_mismatch;
+use crate::scratch::Scratch;
+use crate::{
+    Dctx, DecompressOptions, Error, StreamResult, compress_block_bound, decompress_block_bound,
+};
+
+/// What to write in place of a block that could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fill {
+    /// Zeros over the block's range, so later data keeps its offsets.
+    #[default]
+    Zeros,
+    /// Nothing: the output is shorter than the original.
+    Skip,
+}
+
+/// Options for [`decompress_stream_salvage`].
+#[derive(Debug, Clone)]
+pub struct SalvageOptions {
+    /// Treat a block whose checksum does not match as damaged (default
+    /// `true`). Without it, only blocks the decoder rejects are.
+    pub verify_checksum: bool,
+    /// Replacement for damaged blocks.
+    pub fill: Fill,
+}
+
+impl Default for SalvageOptions {
+    fn default() -> Self {
+        Self {
+            verify_checksum: true,
+            fill: Fill::Zeros,
+        }
+    }
+}
+
+/// A block that was not restored.
+#[derive(Debug, Clone)]
+pub struct DamagedBlock {
+    /// Index of the block in the frame.
+    pub block: u32,
+    /// Offset of its block header in the compressed stream.
+    pub offset: u64,
+    /// Offset in the output where its data belonged.
+    pub output_offset: u64,
+    /// Bytes written in its place (0 with [`Fill::Skip`]).
+    pub filled: u64,
+    /// Why it could not be decoded.
+    pub error: Error,
+}
+
+/// Outcome of [`decompress_stream_salvage`].
+#[derive(Debug, Clone, Default)]
+pub struct SalvageReport {
+    /// Data blocks seen, damaged ones included.
+    pub blocks: u32,
+    /// Bytes written to the output, fill included.
+    pub written: u64,
+    /// Compressed bytes parsed: the whole input when `complete`, otherwise
+    /// up to where the salvage stopped.
+    pub read: u64,
+    /// Blocks that were filled or skipped, in order.
+    pub damaged: Vec<DamagedBlock>,
+    /// Whether the stream was read up to its footer. `false` when the input
+    /// ended early or a block header was unreadable.
+    pub complete: bool,
+    /// Size the footer declares, once it was reached.
+    pub declared_size: Option<u64>,
+    /// Whether blocks were checked against their checksums.
+    pub checksummed: bool,
+    /// Whether the footer's size and global checksum match what was read.
+    pub trailer_ok: bool,
+}
+
+impl SalvageReport {
+    /// Reports whether the frame was restored without any damage.
+    pub fn is_clean(&self) -> bool {
+        self.damaged.is_empty() && self.complete && self.trailer_ok
+    }
+}
+
+/// Decompresses a possibly damaged ZXC frame from `reader` into `writer`,
+/// restoring every block that still decodes.
+///
+/// Unlike [`crate::decompress_stream`], a corrupted block does not abort
+/// the restore: it is reported in the returned [`SalvageReport`] and
+/// replaced according to `opts.fill`. Zeros stand in for a full block, or
+/// for whatever the footer says is left when the last block is the damaged
+/// one.
+///
+/// Fails only on I/O errors and on frames that cannot be salvaged at all: an
+/// unreadable file header, or a dictionary frame (the Block API decodes
+/// blocks without a dictionary).
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::{SalvageOptions, compress, decompress_stream_salvage, Level};
+///
+/// let frame = compress(b"hello salvage", Level::Default, None).unwrap();
+/// let mut out = Vec::new();
+/// let report = decompress_stream_salvage(&frame[..], &mut out, &SalvageOptions::default()).unwrap();
+/// assert!(report.is_clean());
+/// assert_eq!(out, b"hello salvage");
+/// ```
+pub fn decompress_stream_salvage<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    opts: &SalvageOptions,
+) -> io::Result<SalvageReport> {
+    salvage(reader, writer, opts, false)
+}
+
+/// Extracts the intact beginning of a truncated or tail-corrupted archive.
+///
+/// Blocks are decoded from the start of `input` up to the first one that is
+/// missing, cut short or damaged, and written to `output`, which is then a
+/// prefix of the original data. The returned report's `written` is the
+/// number of bytes recovered; `damaged` holds the block that stopped the
+/// recovery, if any. Use [`decompress_stream_salvage`] instead to carry on
+/// past damage in the middle of an archive.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let report = zxc::recover_file("truncated.zxc", "recovered.bin")?;
+/// println!("recovered {} bytes", report.written);
+/// # Ok::<(), zxc::StreamError>(())
+/// ```
+pub fn recover_file<P: AsRef<Path>>(input: P, output: P) -> StreamResult<SalvageReport> {
+    let reader = BufReader::new(File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    let report =
+        salvage(reader, &mut writer, &SalvageOptions::default(), true).map_err(stream_error)?;
+    writer.flush()?;
+    Ok(report)
+}
+
+/// Body of [`decompress_stream_salvage`]; with `stop_on_damage` set it
+/// returns as soon as a block is found damaged, without filling it.
+pub(crate) fn salvage<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    opts: &SalvageOptions,
+    stop_on_damage: bool,
+) -> io::Result<SalvageReport> {
+    let mut header = [0u8; FILE_HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) != MAGIC_WORD {
+        return Err(io::Error::other(Error::BadMagic));
+    }
+    let mut zeroed = header;
+    zeroed[14..].fill(0);
+    if u16::from_le_bytes([header[14], header[15]]) != hash16(&zeroed) {
+        return Err(io::Error::other(Error::BadHeader));
+    }
+    if !BLOCK_SIZE_LOG2.contains(&header[5]) {
+        return Err(io::Error::other(Error::BadBlockSize));
+    }
+    let flags = header[6];
+    if flags & FLAG_HAS_DICTIONARY != 0 {
+        return Err(io::Error::other(Error::Unsupported(
+            "salvaging a dictionary frame",
+        )));
+    }
+    let has_checksum = flags & FLAG_HAS_CHECKSUM != 0;
+    let verify = opts.verify_checksum && has_checksum;
+    let block_size = 1usize << header[5];
+    let max_comp_size = compress_block_bound(block_size) as usize;
+
+    let mut dctx = Dctx::new().map_err(io::Error::other)?;
+    let block_opts = DecompressOptions {
+        verify_checksum: verify,
+        ..Default::default()
+    };
+    let mut block =
+        Scratch::try_with_capacity(BLOCK_HEADER_SIZE + max_comp_size + BLOCK_CHECKSUM_SIZE)
+            .map_err(io::Error::other)?;
+    let mut out = Scratch::zeroed(decompress_block_bound(block_size) as usize);
+
+    let mut report = SalvageReport {
+        read: FILE_HEADER_SIZE as u64,
+        checksummed: verify,
+        ..Default::default()
+    };
+    let mut global_hash = 0u32;
+    // A damaged block is only filled once the next header tells whether it
+    // was the last one, whose size comes from the footer.
+    let mut pending = false;
+
+    loop {
+        let mut header = [0u8; BLOCK_HEADER_SIZE];
+        if !read_full(&mut reader, &mut header)? {
+            break;
+        }
+        if header[0] == BLOCK_EOF {
+            // Whatever follows (an optional seek table) ends with the footer.
+            let mut tail = Vec::new();
+            reader.read_to_end(&mut tail)?;
+            report.read += (BLOCK_HEADER_SIZE + tail.len()) as u64;
+            let Some((declared, stored_hash)) = tail
+                .last_chunk::<FILE_FOOTER_SIZE>()
+                .and_then(|f| Some((le_u64(f.first_chunk()?), le_u32(f.last_chunk()?))))
+            else {
+                break;
+            };
+            if pending {
+                let left = declared.saturating_sub(report.written);
+                fill(&mut writer, &mut report, opts, left.min(block_size as u64))?;
+            }
+            report.complete = true;
+            report.declared_size = Some(declared);
+            report.trailer_ok = report.damaged.is_empty()
+                && declared == report.written
+                && (!verify || stored_hash == global_hash);
+            return Ok(report);
+        }
+        if pending {
+            fill(&mut writer, &mut report, opts, block_size as u64)?;
+            pending = false;
+        }
+
+        let damaged = |error| DamagedBlock {
+            block: report.blocks,
+            offset: report.read,
+            output_offset: report.written,
+            filled: 0,
+            error,
+        };
+        // The Block API does not check the header, whose size must be
+        // trusted to find the next block.
+        let mut zeroed = header;
+        zeroed[7] = 0;
+        let comp_size = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as usize;
+        if header[7] != hash8(&zeroed) || comp_size > max_comp_size {
+            report.damaged.push(damaged(Error::BadHeader));
+            return Ok(report);
+        }
+        let rest = comp_size + if has_checksum { BLOCK_CHECKSUM_SIZE } else { 0 };
+        block.clear();
+        block.extend_from_slice(&header);
+        if (&mut reader).take(rest as u64).read_to_end(&mut block)? < rest {
+            report.damaged.push(damaged(Error::SrcTooSmall));
+            return Ok(report);
+        }
+        // The stored checksum is only handed to the decoder when verified.
+        let stored = block.last_chunk::<BLOCK_CHECKSUM_SIZE>().map(le_u32);
+        if !verify {
+            block.truncate(BLOCK_HEADER_SIZE + comp_size);
+        }
+
+        let decoded = dctx
+            .decompress_block(&block, &mut out, &block_opts)
+            .and_then(|n| out.get(..n).ok_or(Error::Overflow));
+        match decoded {
+            Ok(data) => {
+                writer.write_all(data)?;
+                report.written += data.len() as u64;
+                if let (true, Some(stored)) = (verify, stored) {
+                    global_hash = global_hash.rotate_left(1) ^ stored;
+                }
+            }
+            Err(e) => {
+                let e = match e {
+                    Error::BadChecksum => checksum_mismatch(report.blocks, &block).unwrap_or(e),
+                    e => e,
+                };
+                report.damaged.push(damaged(e));
+                if stop_on_damage {
+                    report.blocks += 1;
+                    return Ok(report);
+                }
+                pending = true;
+            }
+        }
+        report.blocks += 1;
+        report.read += (BLOCK_HEADER_SIZE + rest) as u64;
+    }
+
+    // The input ended before the EOF block.
+    if pending {
+        fill(&mut writer, &mut report, opts, block_size as u64)?;
+    }
+    Ok(report)
+}
+
+/// Writes the replacement for the last damaged block.
+fn fill<W: Write>(
+    writer: &mut W,
+    report: &mut SalvageReport,
+    opts: &SalvageOptions,
+    len: u64,
+) -> io::Result<()> {
+    if opts.fill == Fill::Skip || len == 0 {
+        return Ok(());
+    }
+    io::copy(&mut io::repeat(0).take(len), writer)?;
+    report.written += len;
+    if let Some(last) = report.damaged.last_mut() {
+        last.filled = len;
+    }
+    Ok(())
+}
+
+fn le_u32(b: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*b)
+}
+
+fn le_u64(b: &[u8; 8]) -> u64 {
+    u64::from_le_bytes(*b)
+}
+
+/// Fills `buf` from `reader`, returning `false` if the input ended first.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, compress};
+
+    const BLOCK: usize = 512 * 1024;
+
+    /// Four default-sized blocks, the last one partial.
+    fn frame(checksum: bool) -> (Vec<u8>, Vec<u8>) {
+        let data: Vec<u8> = (0..3 * BLOCK + 1000)
+            .map(|i| ((i / BLOCK) as u8).wrapping_mul(37) ^ (i % 251) as u8)
+            .collect();
+        (compress(&data, Level::Fast, Some(checksum)).unwrap(), data)
+    }
+
+    /// Offsets of the data block headers of `frame`.
+    fn block_offsets(frame: &[u8]) -> Vec<usize> {
+        let extra = if frame[6] & FLAG_HAS_CHECKSUM != 0 {
+            4
+        } else {
+            0
+        };
+        let mut offsets = Vec::new();
+        let mut pos = FILE_HEADER_SIZE;
+        while frame[pos] != BLOCK_EOF {
+            offsets.push(pos);
+            let size = u32::from_le_bytes(frame[pos + 3..pos + 7].try_into().unwrap());
+            pos += BLOCK_HEADER_SIZE + size as usize + extra;
+        }
+        offsets
+    }
+
+    #[test]
+    fn test_clean_frame() {
+        let (frame, data) = frame(true);
+        let mut out = Vec::new();
+        let report = decompress_stream_salvage(&frame[..], &mut out, &Default::default()).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.blocks, 4);
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_damaged_blocks_are_filled() {
+        let (mut frame, data) = frame(true);
+        let offsets = block_offsets(&frame);
+        // Flip a payload byte in the second and the last block.
+        frame[offsets[1] + 20] ^= 0xFF;
+        frame[offsets[3] + 20] ^= 0xFF;
+
+        let mut out = Vec::new();
+        let report = decompress_stream_salvage(&frame[..], &mut out, &Default::default()).unwrap();
+        assert!(report.complete);
+        assert!(!report.trailer_ok);
+        assert_eq!(report.blocks, 4);
+        assert_eq!(report.written, data.len() as u64);
+        assert_eq!(report.damaged.len(), 2);
+
+        let second = &report.damaged[0];
+        assert_eq!((second.block, second.offset), (1, offsets[1] as u64));
+        assert!(
+            matches!(second.error, Error::ChecksumMismatch { block: Some(1), .. }),
+            "{:?}",
+            second.error
+        );
+        assert_eq!(
+            (second.output_offset, second.filled),
+            (BLOCK as u64, BLOCK as u64)
+        );
+        // The short last block is filled up to the size the footer declares.
+        assert_eq!(report.damaged[1].filled, 1000);
+
+        assert_eq!(out.len(), data.len());
+        assert_eq!(out[..BLOCK], data[..BLOCK]);
+        assert!(out[BLOCK..2 * BLOCK].iter().all(|&b| b == 0));
+        assert_eq!(out[2 * BLOCK..3 * BLOCK], data[2 * BLOCK..3 * BLOCK]);
+        assert!(out[3 * BLOCK..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_skip_and_truncation() {
+        let (mut frame, data) = frame(true);
+        let offsets = block_offsets(&frame);
+        frame[offsets[0] + 20] ^= 0xFF;
+        frame.truncate(offsets[3] + 4);
+
+        let opts = SalvageOptions {
+            fill: Fill::Skip,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let report = decompress_stream_salvage(&frame[..], &mut out, &opts).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.damaged.len(), 1);
+        assert_eq!(report.damaged[0].filled, 0);
+        assert_eq!(out, data[BLOCK..3 * BLOCK]);
+    }
+
+    #[test]
+    fn test_damaged_header_stops() {
+        let (mut frame, data) = frame(false);
+        let offsets = block_offsets(&frame);
+        // Size field of the third block: covered by the header check value.
+        frame[offsets[2] + 4] ^= 0x01;
+
+        let mut out = Vec::new();
+        let report = decompress_stream_salvage(&frame[..], &mut out, &Default::default()).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.damaged.len(), 1);
+        assert!(matches!(report.damaged[0].error, Error::BadHeader));
+        assert_eq!(out, data[..2 * BLOCK]);
+    }
+
+    #[test]
+    fn test_recover_file() {
+        let (frame, data) = frame(true);
+        let offsets = block_offsets(&frame);
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("zxc_recover_{}.zxc", std::process::id()));
+        let output = dir.join(format!("zxc_recover_{}.out", std::process::id()));
+
+        // Cut in the middle of the third block.
+        std::fs::write(&input, &frame[..offsets[2] + 100]).unwrap();
+        let report = recover_file(&input, &output).unwrap();
+        let recovered = std::fs::read(&output).unwrap();
+        for p in [&input, &output] {
+            let _ = std::fs::remove_file(p);
+        }
+        assert!(!report.complete);
+        assert_eq!(report.written, 2 * BLOCK as u64);
+        assert!(matches!(report.damaged[0].error, Error::SrcTooSmall));
+        assert_eq!(recovered, data[..2 * BLOCK]);
+    }
+}
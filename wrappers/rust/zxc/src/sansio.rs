@@ -0,0 +1,380 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Sans-io streams: compression driven by the caller's own I/O.
+//!
+//! A [`StreamingCompressor`] or [`StreamingDecompressor`] does no I/O of its
+//! own. Bytes go in with `feed`, which takes what fits in a fixed input
+//! buffer, and come out with `next_output`, into a buffer of the caller's.
+//! The [`Status`] returned says whether to collect output, feed more, or
+//! stop. Nothing is tied to `std::io`, so the streams fit io_uring
+//! completions, DMA transfers and event loops alike, and work without `std`.
+//!
+//! The memory held is fixed when a stream is created: the input buffer
+//! (the `in_size` of the underlying [`CStream`] or [`DStream`]) plus that
+//! stream's own block buffers, whatever the size of the frame.
+//!
+//! ```rust
+//! use zxc::sansio::{Status, StreamingCompressor, StreamingDecompressor};
+//!
+//! let data = b"driven by the caller, ".repeat(1000);
+//! let mut out = [0u8; 4096];
+//!
+//! let mut cs = StreamingCompressor::new(None)?;
+//! let mut frame = Vec::new();
+//! let mut rest = &data[..];
+//! loop {
+//!     rest = &rest[cs.feed(rest)..];
+//!     if rest.is_empty() {
+//!         cs.finish();
+//!     }
+//!     match cs.next_output(&mut out)? {
+//!         Status::Output(n) => frame.extend_from_slice(&out[..n]),
+//!         Status::NeedInput => {}
+//!         Status::Done => break,
+//!     }
+//! }
+//!
+//! let mut ds = StreamingDecompressor::new(None)?;
+//! let mut back = Vec::new();
+//! let mut rest = &frame[..];
+//! loop {
+//!     rest = &rest[ds.feed(rest)..];
+//!     match ds.next_output(&mut out)? {
+//!         Status::Output(n) => back.extend_from_slice(&out[..n]),
+//!         Status::NeedInput if rest.is_empty() => return Err(zxc::Error::SrcTooSmall),
+//!         Status::NeedInput => {}
+//!         Status::Done => break,
+//!     }
+//! }
+//! assert_eq!(back, data);
+//! # Ok::<(), zxc::Error>(())
+//! ```
+
+use crate::scratch::Scratch;
+use crate::{CStream, CompressOptions, DStream, DecompressOptions, Error, Result};
+
+/// What a `next_output` call did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// This many bytes were written to the start of the output buffer; call
+    /// again, there may be more.
+    Output(usize),
+    /// Nothing more can come out before more input is fed (or, for the
+    /// compressor, before `finish` is called).
+    NeedInput,
+    /// The frame is complete and all of it has been output.
+    Done,
+}
+
+/// Bytes fed and not yet handed to the stream, in a buffer of fixed size.
+struct Pending {
+    buf: Scratch,
+    start: usize,
+    end: usize,
+}
+
+impl Pending {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buf: Scratch::zeroed(capacity),
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Copies what fits of `input`, returning the number of bytes taken.
+    fn feed(&mut self, input: &[u8]) -> usize {
+        if self.start > 0 && self.end + input.len() > self.buf.len() {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        let n = input.len().min(self.buf.len() - self.end);
+        if let (Some(dst), Some(src)) = (self.buf.get_mut(self.end..self.end + n), input.get(..n)) {
+            dst.copy_from_slice(src);
+            self.end += n;
+            return n;
+        }
+        0
+    }
+
+    fn data(&self) -> &[u8] {
+        self.buf.get(self.start..self.end).unwrap_or_default()
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.start = (self.start + n).min(self.end);
+        if self.start == self.end {
+            self.start = 0;
+            self.end = 0;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Compresses one frame from bytes fed by the caller.
+///
+/// Feed input with [`feed`](Self::feed) and collect the frame with
+/// [`next_output`](Self::next_output) until it returns
+/// [`Status::NeedInput`]; once all the input is fed, call
+/// [`finish`](Self::finish) and collect until [`Status::Done`]. The options
+/// and limits are those of [`CStream`], `pledged_size` included.
+pub struct StreamingCompressor {
+    cs: CStream,
+    input: Pending,
+    finishing: bool,
+    done: bool,
+}
+
+impl StreamingCompressor {
+    /// Creates a compressor writing a frame with `opts`, or the defaults for
+    /// `None`.
+    ///
+    /// # Errors
+    ///
+    /// Those of [`CStream::new`].
+    pub fn new(opts: Option<&CompressOptions>) -> Result<Self> {
+        let cs = CStream::new(opts)?;
+        let input = Pending::new(cs.in_size());
+        Ok(Self {
+            cs,
+            input,
+            finishing: false,
+            done: false,
+        })
+    }
+
+    /// Takes as much of `input` as the input buffer has room for and
+    /// returns how much that was: 0 when it is full (collect output to make
+    /// room) and after [`finish`](Self::finish).
+    pub fn feed(&mut self, input: &[u8]) -> usize {
+        match self.finishing {
+            true => 0,
+            false => self.input.feed(input),
+        }
+    }
+
+    /// Marks the end of the input: the next calls to
+    /// [`next_output`](Self::next_output) flush the last block and write
+    /// the end of the frame.
+    pub fn finish(&mut self) {
+        self.finishing = true;
+    }
+
+    /// Writes the next compressed bytes to `out`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::DstTooSmall`] if `out` is empty, and the errors of
+    /// [`CStream::compress`] and [`CStream::end`].
+    pub fn next_output(&mut self, out: &mut [u8]) -> Result<Status> {
+        if self.done {
+            return Ok(Status::Done);
+        }
+        if out.is_empty() {
+            return Err(Error::DstTooSmall { needed: 1 });
+        }
+        if !self.finishing || !self.input.is_empty() {
+            // Either all the input is taken in, or `out` is full.
+            let p = self.cs.compress(self.input.data(), out)?;
+            self.input.consume(p.consumed);
+            if p.produced > 0 {
+                return Ok(Status::Output(p.produced));
+            }
+            if !self.finishing {
+                return Ok(Status::NeedInput);
+            }
+        }
+        let p = self.cs.end(out)?;
+        self.done = p.pending == 0;
+        match p.produced {
+            0 => Ok(Status::Done),
+            n => Ok(Status::Output(n)),
+        }
+    }
+
+    /// Size of the input buffer, the most [`feed`](Self::feed) takes at
+    /// once.
+    pub fn in_size(&self) -> usize {
+        self.input.buf.len()
+    }
+
+    /// Output buffer size that takes a whole block at a time.
+    pub fn out_size(&self) -> usize {
+        self.cs.out_size()
+    }
+}
+
+/// Decompresses one frame from bytes fed by the caller.
+///
+/// Feed the frame with [`feed`](Self::feed) and collect the data with
+/// [`next_output`](Self::next_output) until it returns [`Status::Done`].
+/// [`Status::NeedInput`] once the input has run out means the frame is
+/// truncated. Bytes after the end of the frame are left unread.
+pub struct StreamingDecompressor {
+    ds: DStream,
+    input: Pending,
+}
+
+impl StreamingDecompressor {
+    /// Creates a decompressor reading a frame with `opts`, or the defaults
+    /// for `None`.
+    ///
+    /// # Errors
+    ///
+    /// Those of [`DStream::new`].
+    pub fn new(opts: Option<&DecompressOptions>) -> Result<Self> {
+        let ds = DStream::new(opts)?;
+        let input = Pending::new(ds.in_size());
+        Ok(Self { ds, input })
+    }
+
+    /// Takes as much of `input` as the input buffer has room for and
+    /// returns how much that was: 0 when it is full (collect output to make
+    /// room).
+    pub fn feed(&mut self, input: &[u8]) -> usize {
+        self.input.feed(input)
+    }
+
+    /// Writes the next decompressed bytes to `out`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::DstTooSmall`] if `out` is empty, and the errors of
+    /// [`DStream::decompress`].
+    pub fn next_output(&mut self, out: &mut [u8]) -> Result<Status> {
+        if out.is_empty() {
+            return Err(Error::DstTooSmall { needed: 1 });
+        }
+        loop {
+            let p = self.ds.decompress(self.input.data(), out)?;
+            self.input.consume(p.consumed);
+            if p.produced > 0 {
+                return Ok(Status::Output(p.produced));
+            }
+            if p.finished {
+                return Ok(Status::Done);
+            }
+            if p.consumed == 0 {
+                return Ok(Status::NeedInput);
+            }
+        }
+    }
+
+    /// Returns `true` once the footer of the frame has been read and
+    /// checked.
+    pub fn finished(&self) -> bool {
+        self.ds.finished()
+    }
+
+    /// Size of the input buffer, the most [`feed`](Self::feed) takes at
+    /// once.
+    pub fn in_size(&self) -> usize {
+        self.input.buf.len()
+    }
+
+    /// Suggested output buffer size.
+    pub fn out_size(&self) -> usize {
+        self.ds.out_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Compresses `data` fed `feed` bytes at a time into `out_len` byte
+    /// outputs.
+    fn compress(data: &[u8], feed: usize, out_len: usize) -> Vec<u8> {
+        let mut cs = StreamingCompressor::new(None).unwrap();
+        let mut out = vec![0u8; out_len];
+        let mut frame = Vec::new();
+        let mut rest = data;
+        loop {
+            let chunk = rest.get(..feed.min(rest.len())).unwrap();
+            rest = &rest[cs.feed(chunk)..];
+            if rest.is_empty() {
+                cs.finish();
+            }
+            match cs.next_output(&mut out).unwrap() {
+                Status::Output(n) => frame.extend_from_slice(&out[..n]),
+                Status::NeedInput => assert!(!rest.is_empty()),
+                Status::Done => return frame,
+            }
+        }
+    }
+
+    fn decompress(frame: &[u8], feed: usize, out_len: usize) -> crate::Result<Vec<u8>> {
+        let mut ds = StreamingDecompressor::new(None)?;
+        let mut out = vec![0u8; out_len];
+        let mut data = Vec::new();
+        let mut rest = frame;
+        loop {
+            let chunk = &rest[..feed.min(rest.len())];
+            rest = &rest[ds.feed(chunk)..];
+            match ds.next_output(&mut out)? {
+                Status::Output(n) => data.extend_from_slice(&out[..n]),
+                Status::NeedInput if rest.is_empty() => return Err(Error::SrcTooSmall),
+                Status::NeedInput => {}
+                Status::Done => return Ok(data),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sansio_roundtrip() {
+        let data: Vec<u8> = (0..1_200_000u32).map(|i| (i % 251) as u8).collect();
+        for (feed, out_len) in [(1 << 20, 1 << 20), (1000, 7), (3, 4096)] {
+            let frame = compress(&data[..300_000], feed, out_len);
+            assert_eq!(crate::decompress(&frame).unwrap(), &data[..300_000]);
+            assert_eq!(decompress(&frame, feed, out_len).unwrap(), &data[..300_000]);
+        }
+        let frame = compress(&data, 1 << 16, 1 << 16);
+        assert_eq!(decompress(&frame, 1 << 16, 1 << 16).unwrap(), data);
+
+        // The empty frame, and the end of the frame seen through.
+        let frame = compress(b"", 16, 16);
+        assert_eq!(
+            frame,
+            crate::compress(b"", crate::Level::Default, None).unwrap()
+        );
+        let mut ds = StreamingDecompressor::new(None).unwrap();
+        assert_eq!(ds.feed(&frame), frame.len());
+        assert_eq!(ds.next_output(&mut [0; 16]).unwrap(), Status::Done);
+        assert!(ds.finished());
+    }
+
+    #[test]
+    fn test_sansio_bounds() {
+        let mut cs = StreamingCompressor::new(None).unwrap();
+        let big = vec![b'a'; cs.in_size() * 2];
+        assert_eq!(cs.feed(&big), cs.in_size());
+        assert_eq!(cs.feed(&big), 0);
+        assert!(matches!(
+            cs.next_output(&mut []),
+            Err(Error::DstTooSmall { .. })
+        ));
+        cs.finish();
+        assert_eq!(cs.feed(b"late"), 0);
+        let mut out = vec![0u8; cs.out_size()];
+        let mut frame = Vec::new();
+        while let Status::Output(n) = cs.next_output(&mut out).unwrap() {
+            frame.extend_from_slice(&out[..n]);
+        }
+        assert_eq!(cs.next_output(&mut out).unwrap(), Status::Done);
+        assert_eq!(crate::decompress(&frame).unwrap().len(), big.len() / 2);
+
+        // A truncated frame never gets to Done.
+        let cut = &frame[..frame.len() - 1];
+        assert!(matches!(decompress(cut, 100, 100), Err(Error::SrcTooSmall)));
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Heap buffers for data in transit.
+//!
+//! With the `zeroize` feature, a [`Scratch`] zeroes its whole allocation
+//! (spare capacity included) when dropped. The buffers never grow after
+//! they are created, so no stale copy is left behind by a reallocation.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::{Error, Result};
+
+pub(crate) struct Scratch(Vec<u8>);
+
+impl Scratch {
+    /// A buffer of `len` zero bytes.
+    pub(crate) fn zeroed(len: usize) -> Self {
+        Self(vec![0u8; len])
+    }
+
+    /// An empty buffer with room for `capacity` bytes, reporting failure as
+    /// [`Error::Memory`] instead of aborting: the size may come from a frame.
+    pub(crate) fn try_with_capacity(capacity: usize) -> Result<Self> {
+        let mut buf = Vec::new();
+        buf.try_reserve_exact(capacity).map_err(|_| Error::Memory)?;
+        Ok(Self(buf))
+    }
+
+    /// Hands the buffer over to the caller, who becomes responsible for it.
+    pub(crate) fn into_vec(mut self) -> Vec<u8> {
+        mem::take(&mut self.0)
+    }
+}
+
+impl Deref for Scratch {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for Scratch {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch() {
+        let mut buf = Scratch::try_with_capacity(16).unwrap();
+        buf.extend_from_slice(b"in transit");
+        assert_eq!(buf.into_vec(), b"in transit");
+        assert_eq!(Scratch::zeroed(4).as_slice(), [0; 4]);
+        assert!(matches!(
+            Scratch::try_with_capacity(usize::MAX),
+            Err(Error::Memory)
+        ));
+    }
+}
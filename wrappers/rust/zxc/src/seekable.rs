@@ -31,12 +31,17 @@
 //! by this crate yet; the underlying `zxc_seekable_decompress_range_mt`
 //! symbol is reserved for a future addition.
 
-use std::ffi::{CString, c_void};
+use std::ffi::c_void;
+#[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+use std::fs::File;
 use std::path::Path;
 use std::ptr::NonNull;
 
-use crate::error::error_from_code;
-use crate::{Error, Result};
+use crate::ctx::huf_ptr;
+use crate::error::{error_from_code, size_from_code};
+#[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+use crate::file::{CFile, open_path};
+use crate::{Error, Result, ffi};
 
 /// Handle to a seekable ZXC archive.
 ///
@@ -45,9 +50,10 @@ use crate::{Error, Result};
 /// releases its underlying C resources on drop.
 pub struct Seekable {
     inner: NonNull<zxc_sys::zxc_seekable>,
-    /// When opened via `open`, we own the `FILE*` and must `fclose` it
-    /// after the handle is freed.
-    file: Option<*mut libc::FILE>,
+    /// When opened via `open`, we own the `FILE*`; `Drop` closes it after
+    /// the handle is freed.
+    #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+    file: Option<CFile>,
     /// When opened via `from_bytes`, we own the source buffer for the
     /// lifetime of the handle.
     _buf: Option<Vec<u8>>,
@@ -71,10 +77,11 @@ impl Seekable {
     /// The buffer is held alive for the lifetime of the returned handle.
     /// Use this when the archive is already in memory.
     pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
-        let ptr = unsafe { zxc_sys::zxc_seekable_open(data.as_ptr() as *const c_void, data.len()) };
+        let ptr = unsafe { ffi::zxc_seekable_open(data.as_ptr() as *const c_void, data.len()) };
         let inner = NonNull::new(ptr).ok_or(Error::InvalidData)?;
         Ok(Self {
             inner,
+            #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
             file: None,
             _buf: Some(data),
             reader_ctx: None,
@@ -84,24 +91,18 @@ impl Seekable {
     /// Opens a seekable archive from a file path.
     ///
     /// The file is opened in binary read mode and remains open for the
-    /// lifetime of the returned handle.
+    /// lifetime of the returned handle. Paths go through the native wide
+    /// APIs on Windows, so non-ANSI names, UNC shares and long paths work.
+    ///
+    /// Builds without the C streaming driver (`no-stream`, wasm) return
+    /// [`Error::Unsupported`]; use [`Seekable::open_reader`] there.
+    #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path_bytes = path_to_cstring(path.as_ref())?;
-        let mode = CString::new("rb").map_err(|_| Error::Io)?;
-        // SAFETY: both pointers point to valid NUL-terminated strings.
-        let f = unsafe { libc::fopen(path_bytes.as_ptr(), mode.as_ptr()) };
-        if f.is_null() {
-            return Err(Error::Io);
-        }
-        let ptr = unsafe { zxc_sys::zxc_seekable_open_file(f) };
-        let inner = match NonNull::new(ptr) {
-            Some(p) => p,
-            None => {
-                // SAFETY: f was just returned by fopen and not yet freed.
-                unsafe { libc::fclose(f) };
-                return Err(Error::InvalidData);
-            }
-        };
+        let file = open_path(path.as_ref(), |p| File::open(p)).map_err(|_| Error::Io)?;
+        let f = CFile::read(&file).map_err(|_| Error::Io)?;
+        // SAFETY: `f` is an open stream; on failure it is closed by its Drop.
+        let ptr = unsafe { ffi::zxc_seekable_open_file(f.as_ptr()) };
+        let inner = NonNull::new(ptr).ok_or(Error::InvalidData)?;
         Ok(Self {
             inner,
             file: Some(f),
@@ -110,6 +111,16 @@ impl Seekable {
         })
     }
 
+    /// Opens a seekable archive from a file path.
+    ///
+    /// This build has no C streaming driver (`no-stream`, wasm): always
+    /// returns [`Error::Unsupported`]; use [`Seekable::open_reader`].
+    #[cfg(any(feature = "no-stream", target_family = "wasm"))]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let _ = path;
+        Err(Error::Unsupported("built without the file streaming API"))
+    }
+
     /// Opens a seekable archive backed by a user-supplied [`ReadAt`]
     /// implementation.
     ///
@@ -143,7 +154,7 @@ impl Seekable {
 
         // SAFETY: `c_reader` is a valid stack-allocated struct; the C library
         // copies its contents at open time.
-        let ptr = unsafe { zxc_sys::zxc_seekable_open_reader(&c_reader) };
+        let ptr = unsafe { ffi::zxc_seekable_open_reader(&c_reader) };
         let inner = match NonNull::new(ptr) {
             Some(p) => p,
             None => {
@@ -157,6 +168,7 @@ impl Seekable {
         };
         Ok(Self {
             inner,
+            #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
             file: None,
             _buf: None,
             reader_ctx: Some(ctx_raw),
@@ -165,12 +177,12 @@ impl Seekable {
 
     /// Total number of data blocks (excludes the EOF marker block).
     pub fn num_blocks(&self) -> u32 {
-        unsafe { zxc_sys::zxc_seekable_get_num_blocks(self.inner.as_ptr()) }
+        unsafe { ffi::zxc_seekable_get_num_blocks(self.inner.as_ptr()) }
     }
 
     /// Total decompressed size of the archive in bytes.
     pub fn decompressed_size(&self) -> u64 {
-        unsafe { zxc_sys::zxc_seekable_get_decompressed_size(self.inner.as_ptr()) }
+        unsafe { ffi::zxc_seekable_get_decompressed_size(self.inner.as_ptr()) }
     }
 
     /// On-disk compressed size of a specific block (block header +
@@ -181,8 +193,7 @@ impl Seekable {
         if block_idx >= self.num_blocks() {
             return None;
         }
-        let sz =
-            unsafe { zxc_sys::zxc_seekable_get_block_comp_size(self.inner.as_ptr(), block_idx) };
+        let sz = unsafe { ffi::zxc_seekable_get_block_comp_size(self.inner.as_ptr(), block_idx) };
         Some(sz)
     }
 
@@ -193,8 +204,7 @@ impl Seekable {
         if block_idx >= self.num_blocks() {
             return None;
         }
-        let sz =
-            unsafe { zxc_sys::zxc_seekable_get_block_decomp_size(self.inner.as_ptr(), block_idx) };
+        let sz = unsafe { ffi::zxc_seekable_get_block_decomp_size(self.inner.as_ptr(), block_idx) };
         Some(sz)
     }
 
@@ -212,12 +222,9 @@ impl Seekable {
     /// Returns an [`Error`] if the dictionary is invalid or its ID does not
     /// match the one the archive requires.
     pub fn set_dict(&mut self, dict: &[u8], dict_huf: Option<&[u8]>) -> Result<()> {
-        let huf_ptr = match dict_huf {
-            Some(h) if !h.is_empty() => h.as_ptr() as *const c_void,
-            _ => std::ptr::null(),
-        };
+        let huf_ptr = huf_ptr(dict_huf)?;
         let rc = unsafe {
-            zxc_sys::zxc_seekable_set_dict(
+            ffi::zxc_seekable_set_dict(
                 self.inner.as_ptr(),
                 dict.as_ptr() as *const c_void,
                 dict.len(),
@@ -238,7 +245,7 @@ impl Seekable {
     /// decompressed. Returns the number of bytes actually written.
     pub fn decompress_range(&mut self, dst: &mut [u8], offset: u64, len: usize) -> Result<usize> {
         let res = unsafe {
-            zxc_sys::zxc_seekable_decompress_range(
+            ffi::zxc_seekable_decompress_range(
                 self.inner.as_ptr(),
                 dst.as_mut_ptr() as *mut c_void,
                 dst.len(),
@@ -246,11 +253,7 @@ impl Seekable {
                 len,
             )
         };
-        if res < 0 {
-            Err(error_from_code(res))
-        } else {
-            Ok(res as usize)
-        }
+        size_from_code(res)
     }
 }
 
@@ -259,11 +262,9 @@ impl Drop for Seekable {
         // SAFETY: inner was created by zxc_seekable_open / _open_file /
         // _open_reader and has not been freed yet. Free the C handle
         // first so no in-flight `read_at` calls reference our reader_ctx.
-        unsafe { zxc_sys::zxc_seekable_free(self.inner.as_ptr()) };
-        if let Some(f) = self.file.take() {
-            // SAFETY: f was returned by libc::fopen above.
-            unsafe { libc::fclose(f) };
-        }
+        unsafe { ffi::zxc_seekable_free(self.inner.as_ptr()) };
+        #[cfg(not(any(feature = "no-stream", target_family = "wasm")))]
+        drop(self.file.take());
         if let Some(ctx) = self.reader_ctx.take() {
             // SAFETY: ctx was produced by Box::into_raw in open_reader
             // and has not been freed. The C handle was just freed above,
@@ -301,7 +302,8 @@ pub trait ReadAt {
     /// Reads exactly `dst.len()` bytes at `offset` into `dst`.
     ///
     /// Returning `Err` (or panicking) causes the surrounding ZXC operation
-    /// to fail with an I/O error.
+    /// to fail with an I/O error, except for
+    /// [`std::io::ErrorKind::Interrupted`], on which the read is retried.
     fn read_at(&self, dst: &mut [u8], offset: u64) -> std::io::Result<()>;
 }
 
@@ -320,13 +322,17 @@ unsafe extern "C" fn reader_trampoline(
         // in `Seekable::reader_ctx`. It remains valid for the lifetime of
         // the seekable handle, and `read_at` is only called between
         // `open_reader` and `Drop`.
-        let reader: &Box<dyn ReadAt> = unsafe { &*(ctx as *const Box<dyn ReadAt>) };
+        let reader: &dyn ReadAt = unsafe { &**(ctx as *const Box<dyn ReadAt>) };
         // SAFETY: the C library guarantees `dst` points to `len` writable
         // bytes for the duration of the call.
         let buf = unsafe { std::slice::from_raw_parts_mut(dst as *mut u8, len) };
-        match reader.read_at(buf, offset) {
-            Ok(()) => len as i64,
-            Err(_) => zxc_sys::ZXC_ERROR_IO as i64,
+        // A signal landing in the read is not a failure of the archive.
+        loop {
+            match reader.read_at(buf, offset) {
+                Ok(()) => return len as i64,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(_) => return zxc_sys::ZXC_ERROR_IO as i64,
+            }
         }
     });
     result.unwrap_or(zxc_sys::ZXC_ERROR_IO as i64)
@@ -336,7 +342,7 @@ unsafe extern "C" fn reader_trampoline(
 ///
 /// Use this to size a destination buffer for [`write_seek_table`].
 pub fn seek_table_size(num_blocks: u32) -> usize {
-    unsafe { zxc_sys::zxc_seek_table_size(num_blocks) }
+    unsafe { ffi::zxc_seek_table_size(num_blocks) }
 }
 
 /// Low-level: writes a seek table (header + entries) into `dst`.
@@ -346,36 +352,12 @@ pub fn seek_table_size(num_blocks: u32) -> usize {
 /// file APIs already emit a seek table when
 /// [`CompressOptions::seekable`] is set.
 pub fn write_seek_table(dst: &mut [u8], comp_sizes: &[u32]) -> Result<usize> {
+    let num_blocks =
+        u32::try_from(comp_sizes.len()).map_err(|_| Error::TooLarge(comp_sizes.len() as u64))?;
     let res = unsafe {
-        zxc_sys::zxc_write_seek_table(
-            dst.as_mut_ptr(),
-            dst.len(),
-            comp_sizes.as_ptr(),
-            comp_sizes.len() as u32,
-        )
+        ffi::zxc_write_seek_table(dst.as_mut_ptr(), dst.len(), comp_sizes.as_ptr(), num_blocks)
     };
-    if res < 0 {
-        Err(error_from_code(res))
-    } else {
-        Ok(res as usize)
-    }
-}
-
-#[cfg(unix)]
-fn path_to_cstring(path: &Path) -> Result<CString> {
-    use std::os::unix::ffi::OsStrExt;
-    CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Io)
-}
-
-#[cfg(windows)]
-fn path_to_cstring(path: &Path) -> Result<CString> {
-    // libc::fopen on Windows expects an ANSI path. Round-trip through
-    // the lossy UTF-8 representation - paths with characters outside
-    // the active code page are not supported by this convenience entry
-    // point; use `from_bytes` after reading the file yourself if you
-    // need full Unicode path support.
-    let s = path.to_str().ok_or(Error::Io)?;
-    CString::new(s).map_err(|_| Error::Io)
+    size_from_code(res)
 }
 
 #[cfg(test)]
@@ -393,6 +375,16 @@ mod tests {
         compress_with_options(data, &opts).expect("compression failed")
     }
 
+    #[test]
+    fn little_endian_fixture_seek_table() {
+        let data = crate::fixtures::input();
+        let mut s = Seekable::from_bytes(crate::fixtures::LE_SEEKABLE.to_vec()).unwrap();
+        assert_eq!(s.decompressed_size(), data.len() as u64);
+        let mut out = vec![0u8; 1000];
+        assert_eq!(s.decompress_range(&mut out, 5000, 1000).unwrap(), 1000);
+        assert_eq!(&out[..], &data[5000..6000]);
+    }
+
     #[test]
     fn from_bytes_roundtrip() {
         let payload: Vec<u8> = (0..32_768).map(|i| (i as u8).wrapping_mul(31)).collect();
@@ -463,7 +455,7 @@ mod tests {
             let off = offset as usize;
             if off
                 .checked_add(dst.len())
-                .map_or(true, |end| end > self.data.len())
+                .is_none_or(|end| end > self.data.len())
             {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::UnexpectedEof,
@@ -504,6 +496,35 @@ mod tests {
         assert_eq!(out, payload[start..start + len]);
     }
 
+    /// A reader interrupted by signals is retried, not failed.
+    #[test]
+    fn open_reader_retries_interrupted() {
+        struct Flaky(VecReader, std::cell::Cell<bool>);
+        impl ReadAt for Flaky {
+            fn size(&self) -> u64 {
+                self.0.size()
+            }
+            fn read_at(&self, dst: &mut [u8], offset: u64) -> std::io::Result<()> {
+                if !self.1.replace(!self.1.get()) {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                self.0.read_at(dst, offset)
+            }
+        }
+
+        let payload: Vec<u8> = (0..64_000).map(|i| (i as u8) ^ 0x33).collect();
+        let reader = Flaky(
+            VecReader::new(build_archive(&payload)),
+            std::cell::Cell::new(false),
+        );
+        let mut s = Seekable::open_reader(reader).expect("open_reader failed");
+        let mut out = vec![0u8; payload.len()];
+        let n = s
+            .decompress_range(&mut out, 0, payload.len())
+            .expect("decompress_range failed");
+        assert_eq!(out[..n], payload);
+    }
+
     #[test]
     fn open_reader_invalid_archive_releases_reader() {
         // 64 bytes of zeros: passes the minimum-size check but parses as
@@ -542,6 +563,7 @@ mod tests {
             seekable: true,
             dict: Some(dict.clone()),
             dict_huf: None,
+            pledged_size: None,
         };
         let archive = compress_with_options(&payload, &opts).expect("compression failed");
 
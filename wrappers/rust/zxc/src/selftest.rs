@@ -0,0 +1,205 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! A startup check of the SIMD code paths.
+//!
+//! A hypervisor that advertises AVX2 or AVX-512 but emulates it wrongly
+//! makes the C library pick a code path that corrupts data quietly.
+//! [`self_test`] runs every code path this CPU and build accept on a small
+//! input and fails loudly instead, so a service can refuse to start.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::cpu::{Variant, active_variant, force_variant};
+use crate::{DecompressOptions, Error, Level, Result};
+
+/// Size of the test input: one block, small enough for every level to run
+/// in a few milliseconds.
+const INPUT_SIZE: usize = 96 * 1024;
+
+/// The code paths [`self_test`] tried, from [`self_test`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SelfTestReport {
+    /// The code path in use, as before the test.
+    pub active: Variant,
+    /// The code paths that passed, baseline first. Those this CPU or build
+    /// cannot run are left out.
+    pub variants: Vec<Variant>,
+    /// Frames compressed and decompressed on each code path.
+    pub round_trips: usize,
+}
+
+/// Checks every SIMD code path available here against the baseline.
+///
+/// On each variant [`cpu::force_variant`](crate::cpu::force_variant)
+/// accepts, the input is compressed at every level with checksums, then
+/// decompressed with checksum verification and compared. The variants
+/// must write the very frames the baseline writes, and a frame with a
+/// flipped byte must be rejected.
+///
+/// The variant in use before the call is restored before returning. Like
+/// [`force_variant`], this is process-wide: call it at startup, before
+/// other threads compress.
+///
+/// # Errors
+///
+/// [`Error::SelfTest`] naming the code path that misbehaved, wrapping
+/// [`Error::CorruptData`] for a frame that differs from the baseline's or
+/// a round trip that changed the data, [`Error::BadChecksum`] for a
+/// damaged frame that decoded without error, or the error a call returned.
+///
+/// # Example
+///
+/// ```rust
+/// let report = zxc::self_test()?;
+/// println!("zxc: {} code path(s) checked, using {}", report.variants.len(), report.active);
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn self_test() -> Result<SelfTestReport> {
+    let before = active_variant();
+    let result = run();
+    // Back to runtime detection if that is what ran before; otherwise
+    // (`ZXC_NO_SIMD`, or a variant pinned by the caller) pin it again.
+    let _ = force_variant(None);
+    if active_variant() != before {
+        let _ = force_variant(Some(before));
+    }
+    Ok(SelfTestReport {
+        active: active_variant(),
+        variants: result?,
+        round_trips: Level::all().len(),
+    })
+}
+
+/// Runs the checks, leaving the last variant pinned. Returns the variants
+/// that passed.
+fn run() -> Result<Vec<Variant>> {
+    let input = input();
+    let mut baseline = Vec::new();
+    let mut passed = Vec::new();
+    for variant in [
+        Variant::Default,
+        Variant::Neon,
+        Variant::Avx2,
+        Variant::Avx512,
+    ] {
+        if force_variant(Some(variant)).is_err() {
+            continue;
+        }
+        let frames = check(&input, &baseline).map_err(|source| Error::SelfTest {
+            variant,
+            source: Box::new(source),
+        })?;
+        if variant == Variant::Default {
+            baseline = frames;
+        }
+        passed.push(variant);
+    }
+    Ok(passed)
+}
+
+/// Round-trips `input` at every level on the current variant, comparing
+/// the frames with the `baseline` ones when given. Returns the frames.
+fn check(input: &[u8], baseline: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+    let mut frames = Vec::with_capacity(Level::all().len());
+    for (i, &level) in Level::all().iter().enumerate() {
+        let frame = crate::compress(input, level, Some(true))?;
+        if baseline.get(i).is_some_and(|b| *b != frame) {
+            return Err(Error::CorruptData);
+        }
+        expect(input, &frame)?;
+        frames.push(frame);
+    }
+
+    // A damaged frame must not decode.
+    let mut damaged = frames.first().cloned().unwrap_or_default();
+    let middle = damaged.len() / 2;
+    if let Some(b) = damaged.get_mut(middle) {
+        *b ^= 0x5A;
+    }
+    if expect(input, &damaged).is_ok() {
+        return Err(Error::BadChecksum);
+    }
+    Ok(frames)
+}
+
+/// Decompresses `frame` with checksum verification and compares it with
+/// `input`.
+fn expect(input: &[u8], frame: &[u8]) -> Result<()> {
+    let opts = DecompressOptions {
+        verify_checksum: true,
+        ..Default::default()
+    };
+    match crate::decompress_with_options(frame, &opts)? == input {
+        true => Ok(()),
+        false => Err(Error::CorruptData),
+    }
+}
+
+/// Text with repeats at short and long distances, runs and LCG noise, so
+/// that literals, short and far matches and Huffman tables all come up.
+fn input() -> Vec<u8> {
+    const WORDS: [&[u8]; 8] = [
+        b"vector",
+        b"lane",
+        b"shuffle",
+        b"gather",
+        b"mask",
+        b"stride",
+        b"popcnt",
+        b"broadcast",
+    ];
+    let mut s: u32 = 0x5e1f;
+    let mut out = Vec::with_capacity(INPUT_SIZE + 64);
+    while out.len() < INPUT_SIZE {
+        s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        match (s >> 12) % 16 {
+            0 => out.resize(out.len() + (s >> 20) as usize % 300, (s >> 8) as u8),
+            1 => out.extend_from_slice(&s.to_le_bytes()),
+            2 if out.len() > 40_000 => {
+                let from = out.len() - 40_000;
+                out.extend_from_within(from..from + 64);
+            }
+            _ => {
+                out.extend_from_slice(
+                    WORDS
+                        .get((s >> 16) as usize % WORDS.len())
+                        .copied()
+                        .unwrap_or_default(),
+                );
+                out.push(b' ');
+            }
+        }
+    }
+    // An incompressible tail.
+    for _ in 0..4096 {
+        s = s.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        out.push((s >> 16) as u8);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test() {
+        let _pinned = crate::cpu::PIN_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let before = active_variant();
+        let report = self_test().unwrap();
+        assert_eq!(report.active, before);
+        assert_eq!(active_variant(), before);
+        assert_eq!(report.variants.first(), Some(&Variant::Default));
+        assert!(report.variants.contains(&before));
+        assert_eq!(report.round_trips, Level::all().len());
+    }
+}
@@ -0,0 +1,372 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Serde integrations.
+//!
+//! - [`Compressed`] (the `serde` feature) compresses a field transparently,
+//!   in whatever format the enclosing struct is serialized to.
+//! - [`bincode`] and [`postcard`] (the features of the same names) serialize
+//!   a value in that format and compress the bytes in one call, the usual
+//!   pattern of RPC payloads and cache entries.
+//!
+//! # Compressed fields
+//!
+//! ```rust
+//! # #[cfg(feature = "serde")] {
+//! use serde::{Deserialize, Serialize};
+//! use zxc::serde::Compressed;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Document {
+//!     title: String,
+//!     body: Compressed<Vec<String>>,
+//! }
+//!
+//! let doc = Document {
+//!     title: "log".into(),
+//!     body: Compressed(vec!["the same line".to_string(); 1000]),
+//! };
+//! let json = serde_json::to_string(&doc).unwrap();
+//! let back: Document = serde_json::from_str(&json).unwrap();
+//! assert_eq!(back.body.len(), 1000);
+//! # }
+//! ```
+//!
+//! The value is encoded as CBOR, which is self-describing and so handles
+//! any `T`, then compressed at [`Level::Default`](crate::Level::Default)
+//! with checksums, and handed to the outer format as bytes. Binary formats
+//! store the frame as is; text formats store it however they store bytes
+//! (an array of numbers in JSON), so the wrapper pays off there only for
+//! large, redundant values.
+
+use core::fmt;
+
+#[cfg(feature = "serde")]
+pub use compressed::Compressed;
+
+/// Error of the [`bincode`] and [`postcard`] helpers: `E` is the format's
+/// own error type.
+#[derive(Debug)]
+pub enum PayloadError<E> {
+    /// Compressing or decompressing failed.
+    Zxc(crate::Error),
+    /// Encoding or decoding the value failed.
+    Format(E),
+}
+
+impl<E: fmt::Display> fmt::Display for PayloadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Zxc(e) => e.fmt(f),
+            Self::Format(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for PayloadError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Zxc(e) => Some(e),
+            Self::Format(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<crate::Error> for PayloadError<E> {
+    fn from(e: crate::Error) -> Self {
+        Self::Zxc(e)
+    }
+}
+
+/// Serialize-then-compress helpers for [bincode](https://docs.rs/bincode)
+/// (the `bincode` feature), with its standard configuration.
+///
+/// ```rust
+/// use zxc::CompressOptions;
+/// use zxc::serde::bincode::{from_compressed_slice, to_compressed_vec};
+///
+/// let entry = vec![(1u32, "cached".to_string()); 100];
+/// let bytes = to_compressed_vec(&entry, &CompressOptions::default())?;
+/// let back: Vec<(u32, String)> = from_compressed_slice(&bytes)?;
+/// assert_eq!(back, entry);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "bincode")]
+pub mod bincode {
+    use alloc::vec::Vec;
+
+    use ::bincode::error::{DecodeError, EncodeError};
+    use ::serde::Serialize;
+    use ::serde::de::DeserializeOwned;
+
+    use super::PayloadError;
+    use crate::{CompressOptions, compress_with_options, decompress};
+
+    /// Encodes `value` with bincode and compresses the result with `opts`.
+    pub fn to_compressed_vec<T: Serialize + ?Sized>(
+        value: &T,
+        opts: &CompressOptions,
+    ) -> Result<Vec<u8>, PayloadError<EncodeError>> {
+        let encoded = ::bincode::serde::encode_to_vec(value, ::bincode::config::standard())
+            .map_err(PayloadError::Format)?;
+        Ok(compress_with_options(&encoded, opts)?)
+    }
+
+    /// Decompresses `bytes` and decodes the result with bincode.
+    ///
+    /// Frames compressed with a dictionary are not supported.
+    pub fn from_compressed_slice<T: DeserializeOwned>(
+        bytes: &[u8],
+    ) -> Result<T, PayloadError<DecodeError>> {
+        let encoded = decompress(bytes)?;
+        ::bincode::serde::decode_from_slice(&encoded, ::bincode::config::standard())
+            .map(|(value, _)| value)
+            .map_err(PayloadError::Format)
+    }
+}
+
+/// Serialize-then-compress helpers for [postcard](https://docs.rs/postcard)
+/// (the `postcard` feature).
+///
+/// ```rust
+/// use zxc::CompressOptions;
+/// use zxc::serde::postcard::{from_compressed_slice, to_compressed_vec};
+///
+/// let reading = [21.5f32; 256];
+/// let bytes = to_compressed_vec(&reading[..], &CompressOptions::default())?;
+/// let back: Vec<f32> = from_compressed_slice(&bytes)?;
+/// assert_eq!(back, reading);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "postcard")]
+pub mod postcard {
+    use alloc::vec::Vec;
+
+    use ::serde::Serialize;
+    use ::serde::de::DeserializeOwned;
+
+    use super::PayloadError;
+    use crate::{CompressOptions, compress_with_options, decompress};
+
+    /// Encodes `value` with postcard and compresses the result with `opts`.
+    pub fn to_compressed_vec<T: Serialize + ?Sized>(
+        value: &T,
+        opts: &CompressOptions,
+    ) -> Result<Vec<u8>, PayloadError<::postcard::Error>> {
+        let encoded = ::postcard::to_allocvec(value).map_err(PayloadError::Format)?;
+        Ok(compress_with_options(&encoded, opts)?)
+    }
+
+    /// Decompresses `bytes` and decodes the result with postcard.
+    ///
+    /// Frames compressed with a dictionary are not supported.
+    pub fn from_compressed_slice<T: DeserializeOwned>(
+        bytes: &[u8],
+    ) -> Result<T, PayloadError<::postcard::Error>> {
+        let encoded = decompress(bytes)?;
+        ::postcard::from_bytes(&encoded).map_err(PayloadError::Format)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod compressed {
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::ops::{Deref, DerefMut};
+
+    use ::serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+    use ::serde::ser::{self, Serialize, Serializer};
+
+    use crate::{Level, compress, decompress};
+
+    /// A value serialized as a compressed blob. See the [module docs](super).
+    ///
+    /// Dereferences to the wrapped value, so it reads and updates like `T`.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Compressed<T>(pub T);
+
+    impl<T> Compressed<T> {
+        /// Unwraps the value.
+        pub fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T> From<T> for Compressed<T> {
+        fn from(value: T) -> Self {
+            Self(value)
+        }
+    }
+
+    impl<T> Deref for Compressed<T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    impl<T> DerefMut for Compressed<T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.0
+        }
+    }
+
+    impl<T: Serialize> Serialize for Compressed<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut encoded = Vec::new();
+            ciborium::into_writer(&self.0, &mut encoded).map_err(ser::Error::custom)?;
+            let frame =
+                compress(&encoded, Level::Default, Some(true)).map_err(ser::Error::custom)?;
+            serializer.serialize_bytes(&frame)
+        }
+    }
+
+    impl<'de, T: de::DeserializeOwned> Deserialize<'de> for Compressed<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let frame = deserializer.deserialize_bytes(FrameVisitor)?;
+            let encoded = decompress(&frame).map_err(de::Error::custom)?;
+            ciborium::from_reader(encoded.as_slice())
+                .map(Self)
+                .map_err(de::Error::custom)
+        }
+    }
+
+    /// Collects the frame from any of the ways a format may represent bytes.
+    struct FrameVisitor;
+
+    impl<'de> Visitor<'de> for FrameVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a zxc frame as bytes")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut frame = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(1 << 16));
+            while let Some(byte) = seq.next_element()? {
+                frame.push(byte);
+            }
+            Ok(frame)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::String;
+    use std::vec;
+
+    use ::serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    use std::{collections::BTreeMap, string::ToString};
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        id: u32,
+        tags: Compressed<BTreeMap<String, Vec<u64>>>,
+        #[serde(default)]
+        note: Option<Compressed<String>>,
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_compressed_roundtrip() {
+        let tags: BTreeMap<String, Vec<u64>> =
+            (0..200).map(|i| (i.to_string(), vec![i % 7; 64])).collect();
+        let row = Row {
+            id: 7,
+            tags: Compressed(tags.clone()),
+            note: Some("hello".to_string().into()),
+        };
+
+        let json = serde_json::to_string(&row).unwrap();
+        let back: Row = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, row);
+        assert_eq!(*back.tags, tags);
+
+        // The field is a zxc frame in the output.
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let frame: Vec<u8> = serde_json::from_value(value["tags"].clone()).unwrap();
+        assert!(crate::detect_zxc(&frame));
+        assert!(frame.len() < serde_json::to_vec(&tags).unwrap().len() / 4);
+
+        // Damage is an error from the outer format, not a panic.
+        let mut bad = frame;
+        let last = bad.len() - 1;
+        bad[last] ^= 0xff;
+        let json = serde_json::json!({ "id": 1, "tags": bad }).to_string();
+        assert!(serde_json::from_str::<Row>(&json).is_err());
+    }
+
+    #[cfg(any(feature = "bincode", feature = "postcard"))]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        key: String,
+        hits: Vec<u32>,
+    }
+
+    #[cfg(any(feature = "bincode", feature = "postcard"))]
+    fn entries() -> Vec<Entry> {
+        (0..500)
+            .map(|i| Entry {
+                key: std::format!("user:{}", i % 20),
+                hits: vec![i; 8],
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_payload() {
+        use super::bincode::{from_compressed_slice, to_compressed_vec};
+
+        let value = entries();
+        let bytes = to_compressed_vec(&value, &crate::CompressOptions::default()).unwrap();
+        assert!(crate::detect_zxc(&bytes));
+        assert_eq!(from_compressed_slice::<Vec<Entry>>(&bytes).unwrap(), value);
+
+        assert!(matches!(
+            from_compressed_slice::<Vec<Entry>>(b"not a frame"),
+            Err(PayloadError::Zxc(_))
+        ));
+        let wrong = crate::compress(&[0xff; 16], crate::Level::Default, None).unwrap();
+        assert!(matches!(
+            from_compressed_slice::<Vec<Entry>>(&wrong),
+            Err(PayloadError::Format(_))
+        ));
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard_payload() {
+        use super::postcard::{from_compressed_slice, to_compressed_vec};
+
+        let value = entries();
+        let bytes = to_compressed_vec(&value, &crate::CompressOptions::default()).unwrap();
+        assert!(crate::detect_zxc(&bytes));
+        assert_eq!(from_compressed_slice::<Vec<Entry>>(&bytes).unwrap(), value);
+
+        let wrong = crate::compress(&[0xff; 16], crate::Level::Default, None).unwrap();
+        assert!(matches!(
+            from_compressed_slice::<Vec<Entry>>(&wrong),
+            Err(PayloadError::Format(_))
+        ));
+    }
+}
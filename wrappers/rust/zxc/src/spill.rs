@@ -0,0 +1,229 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! One-shot compression whose output moves to disk past a size.
+//!
+//! [`compress`] and [`decompress`] work like their counterparts at the
+//! crate root, but an output larger than [`SpillOptions::threshold`] is
+//! written to an anonymous temporary file and handed back mapped into
+//! memory rather than allocated. Either way the result is a
+//! [`SpillBuffer`] that derefs to `[u8]`, so one code path serves small
+//! payloads and ones larger than RAM; the page cache holds only what is
+//! being read.
+//!
+//! The input is a slice too. An input larger than RAM can be a mapping of
+//! its own, such as a [`SpillBuffer`] from an earlier call.
+//!
+//! ```rust
+//! use zxc::spill::{self, SpillOptions};
+//!
+//! let data = vec![b'm'; 1 << 20];
+//! // Spill anything above 64 KiB.
+//! let opts = SpillOptions {
+//!     threshold: 64 << 10,
+//!     ..Default::default()
+//! };
+//! let frame = spill::compress(&data, &Default::default(), &opts)?;
+//! assert!(frame.is_spilled());
+//! assert!(frame.len() < 64 << 10);
+//!
+//! let back = spill::decompress(&frame, &Default::default(), &opts)?;
+//! assert!(back.is_spilled());
+//! assert_eq!(&back[..], &data[..]);
+//! # Ok::<(), zxc::StreamError>(())
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::vec::Vec;
+
+use memmap2::Mmap;
+
+use crate::file::stream_error;
+use crate::stdio::{compress_until, decompress_until};
+use crate::{
+    CompressOptions, DecompressOptions, StreamResult, compress_bound, compress_with_options,
+    decompress_with_options, decompressed_size,
+};
+
+/// Where [`compress`] and [`decompress`] put their output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillOptions {
+    /// Outputs that can be larger than this many bytes go to a temporary
+    /// file (default: 1 GiB). For compression, the bound of
+    /// [`compress_bound`] is compared; for decompression, the size in the
+    /// frame's footer.
+    pub threshold: u64,
+    /// Directory of the temporary files, or `None` for the system's
+    /// (default: `None`).
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for SpillOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 1 << 30,
+            dir: None,
+        }
+    }
+}
+
+/// The output of [`compress`] or [`decompress`]: in memory, or mapped
+/// from a temporary file that goes away with the buffer.
+pub struct SpillBuffer(Repr);
+
+enum Repr {
+    Memory(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl SpillBuffer {
+    /// Whether the data lives in a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.0, Repr::Mapped(_))
+    }
+}
+
+impl Deref for SpillBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Memory(v) => v,
+            Repr::Mapped(m) => m,
+        }
+    }
+}
+
+impl AsRef<[u8]> for SpillBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for SpillBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpillBuffer")
+            .field("len", &self.len())
+            .field("spilled", &self.is_spilled())
+            .finish()
+    }
+}
+
+/// Compresses `data` into one frame, like
+/// [`compress_with_options`], spilling the frame to a temporary file when
+/// its bound exceeds `spill.threshold`.
+///
+/// A spilled frame is written through [`Encoder`](crate::Encoder), one
+/// block at a time.
+pub fn compress(
+    data: &[u8],
+    opts: &CompressOptions,
+    spill: &SpillOptions,
+) -> StreamResult<SpillBuffer> {
+    if compress_bound(data.len()) as u64 <= spill.threshold {
+        return Ok(SpillBuffer(Repr::Memory(compress_with_options(
+            data, opts,
+        )?)));
+    }
+    to_file(spill, |out| compress_until(data, out, Some(opts), None))
+}
+
+/// Decompresses the frame `compressed`, like [`decompress_with_options`],
+/// spilling the data to a temporary file when the footer gives a size
+/// above `spill.threshold`.
+pub fn decompress(
+    compressed: &[u8],
+    opts: &DecompressOptions,
+    spill: &SpillOptions,
+) -> StreamResult<SpillBuffer> {
+    if decompressed_size(compressed)? <= spill.threshold {
+        let data = decompress_with_options(compressed, opts)?;
+        return Ok(SpillBuffer(Repr::Memory(data)));
+    }
+    to_file(spill, |out| {
+        decompress_until(compressed, out, Some(opts), None)
+    })
+}
+
+/// Runs `write` into a new temporary file and maps what it wrote.
+fn to_file(
+    spill: &SpillOptions,
+    write: impl FnOnce(&mut BufWriter<File>) -> std::io::Result<(u64, u64)>,
+) -> StreamResult<SpillBuffer> {
+    let file = match &spill.dir {
+        Some(dir) => tempfile::tempfile_in(dir)?,
+        None => tempfile::tempfile()?,
+    };
+    let mut out = BufWriter::new(file);
+    write(&mut out).map_err(stream_error)?;
+    out.flush()?;
+    let file = out.into_inner().map_err(|e| e.into_error())?;
+    // SAFETY: the file has no name, so no other handle to it can be
+    // opened, and this one is dropped without writing to it again.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(SpillBuffer(Repr::Mapped(map)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, StreamError};
+
+    #[test]
+    fn test_spill() {
+        let data = crate::fixtures::input().repeat(20);
+        let always = SpillOptions {
+            threshold: 0,
+            dir: Some(std::env::temp_dir()),
+        };
+        let never = SpillOptions {
+            threshold: u64::MAX,
+            ..Default::default()
+        };
+        let opts = CompressOptions::default();
+        let dopts = DecompressOptions::default();
+
+        let spilled = compress(&data, &opts, &always).unwrap();
+        let kept = compress(&data, &opts, &never).unwrap();
+        assert!(spilled.is_spilled());
+        assert!(!kept.is_spilled());
+        assert_eq!(crate::decompress(&spilled).unwrap(), data);
+
+        // Either frame, either way.
+        for frame in [&spilled, &kept] {
+            for spill in [&always, &never] {
+                let back = decompress(frame, &dopts, spill).unwrap();
+                assert_eq!(back.is_spilled(), spill.threshold == 0);
+                assert_eq!(&back[..], &data[..]);
+            }
+        }
+
+        // Nothing is above any threshold.
+        let empty = compress(b"", &opts, &always).unwrap();
+        let back = decompress(&empty, &dopts, &always).unwrap();
+        assert!(back.is_empty() && !back.is_spilled());
+
+        // Damage is the decoder's error, not an I/O one.
+        let mut bad = kept.to_vec();
+        let mid = bad.len() / 2;
+        bad[mid] ^= 0xff;
+        assert!(matches!(
+            decompress(&bad, &dopts, &always),
+            Err(StreamError::BufferError(_))
+        ));
+        assert!(matches!(
+            decompress(b"junk", &dopts, &always),
+            Err(StreamError::BufferError(
+                Error::SrcTooSmall | Error::BadMagic
+            ))
+        ));
+    }
+}
@@ -12,12 +12,13 @@
 //! into pipelines that expect them.
 
 use std::io::{self, Read, Write};
+use std::time::Instant;
 
+use crate::format::MAGIC_WORD;
+use crate::metrics::{self, Operation};
+use crate::scratch::Scratch;
 use crate::{CStream, CompressOptions, DStream, DecompressOptions, Error};
 
-/// Magic word identifying a ZXC file frame: little-endian `0x9CB02EF5`.
-const ZXC_MAGIC_LE: [u8; 4] = [0xF5, 0x2E, 0xB0, 0x9C];
-
 /// Reports whether `data` starts with the ZXC file magic word.
 ///
 /// Useful for content-type sniffing in containers / object stores that need
@@ -35,7 +36,9 @@ const ZXC_MAGIC_LE: [u8; 4] = [0xF5, 0x2E, 0xB0, 0x9C];
 /// assert!(!detect_zxc(b"not a zxc frame"));
 /// ```
 pub fn detect_zxc(data: &[u8]) -> bool {
-    data.len() >= 4 && data[..4] == ZXC_MAGIC_LE
+    // The format is little-endian on every host (docs/FORMAT.md).
+    data.first_chunk::<4>()
+        .is_some_and(|m| u32::from_le_bytes(*m) == MAGIC_WORD)
 }
 
 // ---------------------------------------------------------------------------
@@ -66,7 +69,8 @@ pub fn detect_zxc(data: &[u8]) -> bool {
 pub struct Encoder<W: Write> {
     inner: Option<W>,
     cs: Option<CStream>,
-    out_buf: Vec<u8>,
+    out_buf: Scratch,
+    deadline: Option<Instant>,
 }
 
 impl<W: Write> Encoder<W> {
@@ -83,7 +87,8 @@ impl<W: Write> Encoder<W> {
         Ok(Self {
             inner: Some(writer),
             cs: Some(cs),
-            out_buf: vec![0u8; cap],
+            out_buf: Scratch::zeroed(cap),
+            deadline: None,
         })
     }
 
@@ -93,16 +98,51 @@ impl<W: Write> Encoder<W> {
     /// possible.
     pub fn finish(mut self) -> io::Result<W> {
         self.do_finish()?;
-        // Safety: do_finish leaves both fields populated on success.
-        Ok(self.inner.take().expect("inner writer present"))
+        self.inner
+            .take()
+            .ok_or_else(|| io::Error::other("encoder finished"))
+    }
+
+    /// Finalises the frame and flushes the inner writer, keeping the
+    /// encoder, for when it sits behind a reference or a writer that has to
+    /// be reached through [`Encoder::get_mut`] afterwards. Later writes
+    /// fail; calling it again, [`Encoder::finish`] or dropping the encoder
+    /// does nothing more.
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        self.do_finish()?;
+        self.get_mut().flush()
+    }
+
+    /// Returns the inner writer without finishing the frame, which is left
+    /// incomplete unless [`Encoder::try_finish`] was called first. This is
+    /// the way to abandon a frame; use [`Encoder::finish`] otherwise.
+    // `inner` is only taken by `finish` and here, both consuming the encoder.
+    #[allow(clippy::expect_used)]
+    pub fn into_inner(mut self) -> W {
+        self.cs = None;
+        self.inner.take().expect("encoder not finished")
+    }
+
+    /// Makes every later write, and the finish, fail with
+    /// [`io::ErrorKind::TimedOut`] once `deadline` has passed. `None`
+    /// (the default) clears it.
+    ///
+    /// The deadline is checked before each block is compressed; a write to
+    /// the inner writer that blocks is not interrupted.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
     }
 
     /// Returns a reference to the underlying writer.
+    // `inner` is only taken by `finish` and `into_inner`, which consume the
+    // encoder.
+    #[allow(clippy::expect_used)]
     pub fn get_ref(&self) -> &W {
         self.inner.as_ref().expect("encoder not finished")
     }
 
     /// Returns a mutable reference to the underlying writer.
+    #[allow(clippy::expect_used)]
     pub fn get_mut(&mut self) -> &mut W {
         self.inner.as_mut().expect("encoder not finished")
     }
@@ -112,9 +152,10 @@ impl<W: Write> Encoder<W> {
             return Ok(());
         };
         loop {
+            check_deadline(self.deadline)?;
             let p = cs.end(&mut self.out_buf).map_err(map_err)?;
             if p.produced > 0 {
-                w.write_all(&self.out_buf[..p.produced])?;
+                w.write_all(produced(&self.out_buf, p.produced)?)?;
             }
             if p.pending == 0 {
                 break;
@@ -143,12 +184,13 @@ impl<W: Write> Write for Encoder<W> {
         let mut total = 0;
         let mut input = buf;
         while !input.is_empty() {
+            check_deadline(self.deadline)?;
             let p = cs.compress(input, &mut self.out_buf).map_err(map_err)?;
             if p.produced > 0 {
-                w.write_all(&self.out_buf[..p.produced])?;
+                w.write_all(produced(&self.out_buf, p.produced)?)?;
             }
             total += p.consumed;
-            input = &input[p.consumed..];
+            input = input.get(p.consumed..).ok_or_else(overrun)?;
             if p.consumed == 0 && p.produced == 0 && p.pending == 0 {
                 // No forward progress is possible — bail out to avoid spin.
                 break;
@@ -203,10 +245,14 @@ impl<W: Write> Drop for Encoder<W> {
 pub struct Decoder<R: Read> {
     inner: R,
     ds: DStream,
-    in_buf: Vec<u8>,
+    in_buf: Scratch,
     in_pos: usize,
     in_len: usize,
     eof: bool,
+    deadline: Option<Instant>,
+    /// Kept to start the next frame under [`Decoder::multiple_frames`].
+    opts: DecompressOptions,
+    multiple: bool,
 }
 
 impl<R: Read> Decoder<R> {
@@ -222,13 +268,35 @@ impl<R: Read> Decoder<R> {
         Ok(Self {
             inner: reader,
             ds,
-            in_buf: vec![0u8; cap],
+            in_buf: Scratch::zeroed(cap),
             in_pos: 0,
             in_len: 0,
             eof: false,
+            deadline: None,
+            opts: opts.cloned().unwrap_or_default(),
+            multiple: false,
         })
     }
 
+    /// Reads on past the end of a frame when another one follows, as
+    /// written by [`follow`](crate::follow), `cat a.zxc b.zxc` or an
+    /// appending logger, yielding the contents of all frames back to back.
+    ///
+    /// Each frame is decoded with the options the decoder was created with.
+    pub fn multiple_frames(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// Makes every later read fail with [`io::ErrorKind::TimedOut`] once
+    /// `deadline` has passed. `None` (the default) clears it.
+    ///
+    /// The deadline is checked before each read from the inner reader; a
+    /// read that blocks is not interrupted.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
     /// Returns a reference to the underlying reader.
     pub fn get_ref(&self) -> &R {
         &self.inner
@@ -244,10 +312,35 @@ impl<R: Read> Decoder<R> {
         self.inner
     }
 
-    /// Reports whether the decoder has reached and validated the file footer.
+    /// Reports whether the decoder has reached and validated the file footer
+    /// (with [`Decoder::multiple_frames`], that of the current frame).
     pub fn finished(&self) -> bool {
         self.ds.finished()
     }
+
+    /// Shifts consumed bytes out of the input buffer and reads more from
+    /// the inner reader.
+    fn refill(&mut self) -> io::Result<()> {
+        if self.in_pos > 0 {
+            self.in_buf.copy_within(self.in_pos..self.in_len, 0);
+            self.in_len -= self.in_pos;
+            self.in_pos = 0;
+        }
+        let free = self.in_buf.get_mut(self.in_len..).ok_or_else(overrun)?;
+        let n = loop {
+            check_deadline(self.deadline)?;
+            match self.inner.read(free) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                r => break r?,
+            }
+        };
+        if n == 0 {
+            self.eof = true;
+        } else {
+            self.in_len += n;
+        }
+        Ok(())
+    }
 }
 
 impl<R: Read> Read for Decoder<R> {
@@ -257,7 +350,18 @@ impl<R: Read> Read for Decoder<R> {
         }
         loop {
             if self.ds.finished() {
-                return Ok(0);
+                if !self.multiple {
+                    return Ok(0);
+                }
+                // Another frame, or the end of the input?
+                if self.in_pos == self.in_len {
+                    if self.eof {
+                        return Ok(0);
+                    }
+                    self.refill()?;
+                    continue;
+                }
+                self.ds = DStream::new(Some(&self.opts)).map_err(map_err)?;
             }
 
             // Try to decompress whatever is currently buffered (or drain mode
@@ -265,7 +369,12 @@ impl<R: Read> Read for Decoder<R> {
             if self.in_pos < self.in_len || self.eof {
                 let p = self
                     .ds
-                    .decompress(&self.in_buf[self.in_pos..self.in_len], buf)
+                    .decompress(
+                        self.in_buf
+                            .get(self.in_pos..self.in_len)
+                            .ok_or_else(overrun)?,
+                        buf,
+                    )
                     .map_err(map_err)?;
                 self.in_pos += p.consumed;
                 if p.produced > 0 {
@@ -288,19 +397,188 @@ impl<R: Read> Read for Decoder<R> {
                 }
             }
 
-            // Refill: shift consumed bytes out, then read more from src.
-            if self.in_pos > 0 {
-                self.in_buf.copy_within(self.in_pos..self.in_len, 0);
-                self.in_len -= self.in_pos;
-                self.in_pos = 0;
-            }
-            let n = self.inner.read(&mut self.in_buf[self.in_len..])?;
-            if n == 0 {
-                self.eof = true;
-            } else {
-                self.in_len += n;
+            self.refill()?;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reader-to-writer helpers
+// ---------------------------------------------------------------------------
+
+/// Compresses everything from `reader` into a ZXC frame written to `writer`.
+///
+/// Generic counterpart of [`crate::compress_file`] for any
+/// [`Read`] / [`Write`] pair (sockets, pipes, in-memory buffers, ...). It runs
+/// on the single-threaded push API, so no C `FILE*` is involved and it works
+/// identically on every platform. `opts.seekable` is ignored, as for
+/// [`Encoder`]. The length of the input need not be known: the footer
+/// records it once `reader` is exhausted.
+///
+/// Returns the number of compressed bytes written.
+///
+/// # Example
+///
+/// ```rust
+/// let mut frame = Vec::new();
+/// let written = zxc::compress_stream(&b"stream me"[..], &mut frame, None).unwrap();
+/// assert_eq!(written as usize, frame.len());
+/// ```
+pub fn compress_stream<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    opts: Option<&CompressOptions>,
+) -> io::Result<u64> {
+    let timer = metrics::start();
+    let (read, written) = compress_until(reader, writer, opts, None)?;
+    timer.finish(Operation::CompressStream, read, written);
+    Ok(written)
+}
+
+/// [`compress_stream`] with an optional deadline (see
+/// [`Encoder::set_deadline`]). Returns the bytes read and written.
+pub(crate) fn compress_until<R: Read, W: Write>(
+    mut reader: R,
+    writer: W,
+    opts: Option<&CompressOptions>,
+    deadline: Option<Instant>,
+) -> io::Result<(u64, u64)> {
+    let mut enc = Encoder::with_options(Counted::new(writer), opts).map_err(map_err)?;
+    enc.set_deadline(deadline);
+    let read = io::copy(&mut reader, &mut enc)?;
+    Ok((read, enc.finish()?.count))
+}
+
+/// Decompresses a ZXC frame read from `reader`, writing the output to
+/// `writer`.
+///
+/// Generic counterpart of [`crate::decompress_file`]; see
+/// [`compress_stream`]. Fails with [`io::ErrorKind::UnexpectedEof`] if the
+/// frame is truncated.
+///
+/// Returns the number of decompressed bytes written.
+pub fn decompress_stream<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    opts: Option<&DecompressOptions>,
+) -> io::Result<u64> {
+    let timer = metrics::start();
+    let (read, written) = decompress_until(reader, writer, opts, None)?;
+    timer.finish(Operation::DecompressStream, read, written);
+    Ok(written)
+}
+
+/// [`decompress_stream`] with an optional deadline (see
+/// [`Decoder::set_deadline`]). Returns the bytes read and written.
+pub(crate) fn decompress_until<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    opts: Option<&DecompressOptions>,
+    deadline: Option<Instant>,
+) -> io::Result<(u64, u64)> {
+    let mut dec = Decoder::with_options(Counted::new(reader), opts).map_err(map_err)?;
+    dec.set_deadline(deadline);
+    let written = io::copy(&mut dec, &mut writer)?;
+    Ok((dec.get_ref().count, written))
+}
+
+/// Decompresses the frame `compressed` into `outputs` in order, filling
+/// each before moving to the next, and returns the number of bytes
+/// written. The data goes straight into the slices, for ring buffers and
+/// arena segments that are not contiguous.
+///
+/// # Errors
+///
+/// [`Error::DstTooSmall`] if the slices cannot hold the size given by the
+/// footer, checked before decoding; [`Error::SrcTooSmall`] for a truncated
+/// frame; and the decoding errors of [`decompress`](crate::decompress).
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::IoSliceMut;
+///
+/// let frame = zxc::compress(b"0123456789", zxc::Level::Default, None)?;
+/// let (mut head, mut tail) = ([0u8; 4], [0u8; 8]);
+/// let mut outputs = [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)];
+/// assert_eq!(zxc::decompress_scatter(&frame, &mut outputs)?, 10);
+/// assert_eq!((&head, &tail[..6]), (b"0123", &b"456789"[..]));
+/// # Ok::<(), zxc::Error>(())
+/// ```
+pub fn decompress_scatter(
+    compressed: &[u8],
+    outputs: &mut [io::IoSliceMut<'_>],
+) -> Result<usize, Error> {
+    let size = crate::decompressed_size(compressed)?;
+    let room: u64 = outputs.iter().map(|o| o.len() as u64).sum();
+    if size > room {
+        let needed = usize::try_from(size).unwrap_or(usize::MAX);
+        return Err(Error::DstTooSmall { needed });
+    }
+    let timer = metrics::start();
+    let mut ds = DStream::new(None)?;
+    let mut input = compressed;
+    let mut written = 0;
+    for out in outputs.iter_mut() {
+        let mut filled = 0;
+        while let Some(free) = out.get_mut(filled..).filter(|f| !f.is_empty()) {
+            let p = ds.decompress(input, free)?;
+            input = input.get(p.consumed..).unwrap_or_default();
+            filled += p.produced;
+            if p.consumed == 0 && p.produced == 0 {
+                break;
             }
         }
+        written += filled;
+        if filled < out.len() {
+            break;
+        }
+    }
+    // With the data out, what is left is the end of the frame.
+    while !ds.finished() {
+        let p = ds.decompress(input, &mut [])?;
+        input = input.get(p.consumed..).unwrap_or_default();
+        if p.consumed == 0 {
+            return Err(Error::SrcTooSmall);
+        }
+    }
+    timer.finish(
+        Operation::Decompress,
+        compressed.len() as u64,
+        written as u64,
+    );
+    Ok(written)
+}
+
+/// Reader or writer adapter counting the bytes passed through.
+pub(crate) struct Counted<T> {
+    pub(crate) inner: T,
+    pub(crate) count: u64,
+}
+
+impl<T> Counted<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
     }
 }
 
@@ -312,6 +590,25 @@ fn map_err(e: Error) -> io::Error {
     io::Error::other(e)
 }
 
+/// A stream reported more bytes than the buffer it was handed.
+fn overrun() -> io::Error {
+    map_err(Error::Overflow)
+}
+
+fn produced(buf: &[u8], len: usize) -> io::Result<&[u8]> {
+    buf.get(..len).ok_or_else(overrun)
+}
+
+fn check_deadline(deadline: Option<Instant>) -> io::Result<()> {
+    match deadline {
+        Some(d) if Instant::now() >= d => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "zxc: deadline exceeded",
+        )),
+        _ => Ok(()),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -348,6 +645,89 @@ mod tests {
         assert_eq!(roundtrip(&data), data);
     }
 
+    #[test]
+    fn deadline_exceeded() {
+        let past = std::time::Instant::now();
+        let mut enc = Encoder::new(Vec::new()).unwrap();
+        enc.set_deadline(Some(past));
+        let err = enc.write_all(b"too late").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        enc.set_deadline(None);
+        enc.write_all(b"on time").unwrap();
+        let frame = enc.finish().unwrap();
+
+        let mut dec = Decoder::new(Cursor::new(frame)).unwrap();
+        dec.set_deadline(Some(past));
+        let err = dec.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        dec.set_deadline(None);
+        let mut out = Vec::new();
+        dec.read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"on time");
+    }
+
+    #[test]
+    fn decoder_multiple_frames() {
+        let mut frames = compress(b"first ", Level::Default, None).unwrap();
+        frames.extend(compress(&[7u8; 300_000], Level::Fast, None).unwrap());
+        frames.extend(compress(b"", Level::Default, None).unwrap());
+        frames.extend(compress(b"last", Level::Default, None).unwrap());
+
+        let mut one = Vec::new();
+        Decoder::new(&frames[..])
+            .unwrap()
+            .read_to_end(&mut one)
+            .unwrap();
+        assert_eq!(one, b"first ");
+
+        let mut all = Vec::new();
+        Decoder::new(&frames[..])
+            .unwrap()
+            .multiple_frames()
+            .read_to_end(&mut all)
+            .unwrap();
+        let mut want = b"first ".to_vec();
+        want.extend([7u8; 300_000]);
+        want.extend(b"last");
+        assert_eq!(all, want);
+
+        // A truncated last frame is still an error.
+        let cut = frames.get(..frames.len() - 3).unwrap();
+        let mut dec = Decoder::new(cut).unwrap().multiple_frames();
+        let err = dec.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    /// Reads interrupted by signals are retried inside the decoder, so a
+    /// caller calling `read` itself does not see them.
+    #[test]
+    fn decoder_retries_interrupted_reads() {
+        struct Flaky<R>(R, bool);
+        impl<R: Read> Read for Flaky<R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.1 = !self.1;
+                let n = buf.len().min(100);
+                match self.1 {
+                    true => Err(io::ErrorKind::Interrupted.into()),
+                    false => self.0.read(&mut buf[..n]),
+                }
+            }
+        }
+
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i % 251) as u8).collect();
+        let frame = crate::compress(&data, crate::Level::Default, None).unwrap();
+        let mut dec = Decoder::new(Flaky(&frame[..], false)).unwrap();
+        let mut got = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            match dec.read(&mut buf).unwrap() {
+                0 => break,
+                n => got.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(got, data);
+    }
+
     #[test]
     fn encoder_many_small_writes() {
         let mut enc = Encoder::new(Vec::new()).unwrap();
@@ -400,6 +780,27 @@ mod tests {
         assert_eq!(got, b"drop-flush");
     }
 
+    #[test]
+    fn encoder_accessors() {
+        let mut enc = Encoder::new(Vec::new()).unwrap();
+        enc.write_all(b"through a reference").unwrap();
+        assert!(detect_zxc(enc.get_ref()));
+        enc.try_finish().unwrap();
+        enc.try_finish().unwrap();
+        assert!(enc.write_all(b"late").is_err());
+        let frame = enc.get_ref().clone();
+        assert_eq!(crate::decompress(&frame).unwrap(), b"through a reference");
+        enc.get_mut().extend_from_slice(b"trailer");
+        let out = enc.finish().unwrap();
+        assert_eq!(out.strip_suffix(b"trailer"), Some(&frame[..]));
+
+        // Abandoning a frame writes nothing more, even on drop.
+        let mut enc = Encoder::new(Vec::new()).unwrap();
+        enc.write_all(b"abandoned").unwrap();
+        let partial = enc.into_inner();
+        assert!(crate::decompress(&partial).is_err());
+    }
+
     #[test]
     fn decoder_truncated_frame_errors() {
         let frame = compress(&vec![b'A'; 32 * 1024], Level::Default, None).unwrap();
@@ -410,6 +811,54 @@ mod tests {
         assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
     }
 
+    #[test]
+    fn stream_helpers_roundtrip() {
+        let data: Vec<u8> = (0..300 * 1024).map(|i| ((i * 7) % 253) as u8).collect();
+        let mut frame = Vec::new();
+        let written = compress_stream(Cursor::new(&data), &mut frame, None).unwrap();
+        assert_eq!(written as usize, frame.len());
+
+        let mut out = Vec::new();
+        let n = decompress_stream(Cursor::new(&frame), &mut out, None).unwrap();
+        assert_eq!(n as usize, data.len());
+        assert_eq!(out, data);
+
+        let truncated = &frame[..frame.len() - 1];
+        let err = decompress_stream(truncated, io::sink(), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn scatter_into_slices() {
+        let data: Vec<u8> = (0..700_000u32).map(|i| (i % 199) as u8).collect();
+        let frame = compress(&data, Level::Default, None).unwrap();
+        let mut ring = vec![0u8; 800_000];
+        let (a, rest) = ring.split_at_mut(3);
+        let (b, rest) = rest.split_at_mut(0);
+        let (c, d) = rest.split_at_mut(600_000);
+        let mut outputs = [
+            io::IoSliceMut::new(a),
+            io::IoSliceMut::new(b),
+            io::IoSliceMut::new(c),
+            io::IoSliceMut::new(d),
+        ];
+        assert_eq!(
+            decompress_scatter(&frame, &mut outputs).unwrap(),
+            data.len()
+        );
+        assert_eq!(ring[..data.len()], data);
+        assert!(ring[data.len()..].iter().all(|&b| b == 0));
+
+        let mut small = [0u8; 699_999];
+        let mut outputs = [io::IoSliceMut::new(&mut small)];
+        assert!(matches!(
+            decompress_scatter(&frame, &mut outputs),
+            Err(Error::DstTooSmall { needed: 700_000 })
+        ));
+        let empty = compress(b"", Level::Default, None).unwrap();
+        assert_eq!(decompress_scatter(&empty, &mut []).unwrap(), 0);
+    }
+
     #[test]
     fn detect_zxc_basic() {
         let frame = compress(b"sniff me", Level::Default, None).unwrap();
@@ -424,5 +873,6 @@ mod tests {
         assert!(!detect_zxc(&[0xF5, 0x2E, 0xB0])); // 3 bytes, too short
         assert!(!detect_zxc(&[0; 4]));
         assert!(!detect_zxc(b"not a zxc frame at all"));
+        assert!(detect_zxc(crate::fixtures::LE_DEFAULT_CHECKSUM));
     }
 }
@@ -0,0 +1,493 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Tensor compression with shape and dtype metadata (the `tensor` feature;
+//! `ndarray` adds the [`ndarray`](https://docs.rs/ndarray) helpers).
+//!
+//! A compressed tensor is a small metadata frame followed by an ordinary
+//! zxc frame holding the elements, little-endian, in row-major order:
+//!
+//! ```text
+//! 0x00  4      Magic 0x9CB07E50 (LE)
+//! 0x04  4      Payload size N (LE): bytes that follow, up to the zxc frame
+//! 0x08  1      DType
+//! 0x09  1      Number of dimensions D
+//! 0x0A  2      Reserved (0)
+//! 0x0C  8 * D  Dimensions, u64 LE each
+//! ```
+//!
+//! The size field lets readers skip the metadata without understanding it:
+//! [`data_frame`] returns the zxc frame behind it, for any zxc decoder.
+//! This is a convention of this crate, not part of the zxc format, so
+//! other zxc implementations do not skip it on their own.
+//!
+//! ```rust
+//! # #[cfg(feature = "ndarray")] {
+//! use ndarray::Array2;
+//! use zxc::CompressOptions;
+//! use zxc::tensor::{DType, compress_array, decompress_array, tensor_info};
+//!
+//! let weights = Array2::from_shape_fn((256, 64), |(i, j)| ((i * j) % 17) as f32 * 0.25);
+//! let bytes = compress_array(&weights, &CompressOptions::default())?;
+//!
+//! let info = tensor_info(&bytes)?;
+//! assert_eq!((info.dtype, info.shape.as_slice()), (DType::F32, &[256, 64][..]));
+//! let back = decompress_array::<f32>(&bytes)?;
+//! assert_eq!(back, weights.into_dyn());
+//! # }
+//! # Ok::<(), zxc::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    CompressOptions, DecompressOptions, Error, Result, compress_bound, compress_to, decompress_to,
+    decompressed_size,
+};
+
+/// Magic word of the metadata frame.
+pub const TENSOR_MAGIC: u32 = 0x9CB0_7E50;
+
+/// Size of the fixed part of the metadata frame, before the dimensions.
+const HEADER_SIZE: usize = 12;
+/// Dimensions a metadata frame may hold.
+const MAX_DIMS: usize = 32;
+
+/// Element type of a tensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum DType {
+    /// `u8`
+    U8 = 1,
+    /// `i8`
+    I8 = 2,
+    /// `u16`
+    U16 = 3,
+    /// `i16`
+    I16 = 4,
+    /// `u32`
+    U32 = 5,
+    /// `i32`
+    I32 = 6,
+    /// `u64`
+    U64 = 7,
+    /// `i64`
+    I64 = 8,
+    /// IEEE 754 half precision, stored as its bits
+    F16 = 9,
+    /// bfloat16, stored as its bits
+    BF16 = 10,
+    /// `f32`
+    F32 = 11,
+    /// `f64`
+    F64 = 12,
+}
+
+impl DType {
+    /// Size of one element in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            DType::U8 | DType::I8 => 1,
+            DType::U16 | DType::I16 | DType::F16 | DType::BF16 => 2,
+            DType::U32 | DType::I32 | DType::F32 => 4,
+            DType::U64 | DType::I64 | DType::F64 => 8,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        [
+            DType::U8,
+            DType::I8,
+            DType::U16,
+            DType::I16,
+            DType::U32,
+            DType::I32,
+            DType::U64,
+            DType::I64,
+            DType::F16,
+            DType::BF16,
+            DType::F32,
+            DType::F64,
+        ]
+        .into_iter()
+        .find(|&d| d as u8 == code)
+    }
+}
+
+/// Shape and element type of a compressed tensor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TensorInfo {
+    /// Element type
+    pub dtype: DType,
+    /// Dimensions, outermost first
+    pub shape: Vec<usize>,
+}
+
+impl TensorInfo {
+    /// Number of elements, or `None` if it overflows `usize`.
+    pub fn len(&self) -> Option<usize> {
+        self.shape.iter().try_fold(1usize, |n, &d| n.checked_mul(d))
+    }
+
+    /// Whether the tensor has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Size of the elements in bytes, or `None` if it overflows `usize`.
+    pub fn byte_len(&self) -> Option<usize> {
+        self.len()?.checked_mul(self.dtype.size())
+    }
+}
+
+/// Element types with a [`DType`], which any bit pattern is valid for.
+///
+/// Implemented for the Rust integer and float primitives; sealed.
+pub trait Element: Copy + sealed::Sealed {
+    /// The element type as stored.
+    const DTYPE: DType;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! element {
+    ($($t:ty => $d:ident),* $(,)?) => {$(
+        impl sealed::Sealed for $t {}
+        impl Element for $t {
+            const DTYPE: DType = DType::$d;
+        }
+    )*};
+}
+
+element!(
+    u8 => U8, i8 => I8, u16 => U16, i16 => I16, u32 => U32, i32 => I32,
+    u64 => U64, i64 => I64, f32 => F32, f64 => F64,
+);
+
+/// Compresses `data`, the little-endian, row-major elements of a tensor
+/// described by `info`.
+///
+/// # Errors
+///
+/// [`Error::InvalidData`] if `data` is not `info.byte_len()` bytes long or
+/// `info` has more than 32 dimensions, and any compression error.
+pub fn compress_tensor(data: &[u8], info: &TensorInfo, opts: &CompressOptions) -> Result<Vec<u8>> {
+    if info.byte_len() != Some(data.len()) || info.shape.len() > MAX_DIMS {
+        return Err(Error::InvalidData);
+    }
+    let mut out = Vec::new();
+    write_metadata(&mut out, info);
+    let start = out.len();
+    let bound = match compress_bound(data.len()) {
+        0 => return Err(Error::TooLarge(data.len() as u64)),
+        b => b,
+    };
+    out.resize(start + bound, 0);
+    let dst = out.get_mut(start..).unwrap_or_default();
+    let written = compress_to(data, dst, opts)?;
+    out.truncate(start + written);
+    Ok(out)
+}
+
+/// Reads the metadata of a compressed tensor.
+///
+/// # Errors
+///
+/// [`Error::BadMagic`] if `src` does not start with a metadata frame, and
+/// [`Error::InvalidData`] if the metadata is malformed.
+pub fn tensor_info(src: &[u8]) -> Result<TensorInfo> {
+    let (info, _) = parse(src)?;
+    Ok(info)
+}
+
+/// The zxc frame behind the metadata frame of a compressed tensor.
+///
+/// # Errors
+///
+/// As for [`tensor_info`].
+pub fn data_frame(src: &[u8]) -> Result<&[u8]> {
+    let (_, frame) = parse(src)?;
+    Ok(frame)
+}
+
+/// Decompresses a tensor into `dst`, which must be exactly its size in
+/// bytes, and returns its metadata. The elements are little-endian.
+///
+/// # Errors
+///
+/// [`Error::InvalidData`] if `dst` is not the tensor's size or the frame
+/// does not hold that many bytes, and any decompression error.
+pub fn decompress_tensor_into(src: &[u8], dst: &mut [u8]) -> Result<TensorInfo> {
+    let (info, frame) = parse(src)?;
+    let size = info.byte_len().ok_or(Error::InvalidData)?;
+    if size != dst.len() || decompressed_size(frame).ok() != Some(size as u64) {
+        return Err(Error::InvalidData);
+    }
+    let written = decompress_to(frame, dst, &DecompressOptions::default())?;
+    if written != size {
+        return Err(Error::InvalidData);
+    }
+    Ok(info)
+}
+
+/// Compresses a slice of elements as a tensor of `shape`.
+///
+/// # Errors
+///
+/// [`Error::InvalidData`] if `shape` does not match `data.len()`, and any
+/// compression error.
+pub fn compress_slice<A: Element>(
+    data: &[A],
+    shape: &[usize],
+    opts: &CompressOptions,
+) -> Result<Vec<u8>> {
+    let info = TensorInfo {
+        dtype: A::DTYPE,
+        shape: shape.to_vec(),
+    };
+    if cfg!(target_endian = "little") {
+        compress_tensor(as_bytes(data), &info, opts)
+    } else {
+        let mut le = as_bytes(data).to_vec();
+        to_le(&mut le, A::DTYPE);
+        compress_tensor(&le, &info, opts)
+    }
+}
+
+/// Decompresses a tensor of `A` into `dst`, which must hold exactly its
+/// elements, and returns its shape.
+///
+/// # Errors
+///
+/// [`Error::InvalidData`] if the stored dtype is not `A` or the sizes
+/// differ, and any decompression error.
+pub fn decompress_into_slice<A: Element>(src: &[u8], dst: &mut [A]) -> Result<Vec<usize>> {
+    if tensor_info(src)?.dtype != A::DTYPE {
+        return Err(Error::InvalidData);
+    }
+    let bytes = as_bytes_mut(dst);
+    let info = decompress_tensor_into(src, bytes)?;
+    to_le(bytes, info.dtype);
+    Ok(info.shape)
+}
+
+fn write_metadata(out: &mut Vec<u8>, info: &TensorInfo) {
+    let payload = 4 + 8 * info.shape.len();
+    out.reserve(8 + payload);
+    out.extend_from_slice(&TENSOR_MAGIC.to_le_bytes());
+    out.extend_from_slice(&(payload as u32).to_le_bytes());
+    out.extend_from_slice(&[info.dtype as u8, info.shape.len() as u8, 0, 0]);
+    for &d in &info.shape {
+        out.extend_from_slice(&(d as u64).to_le_bytes());
+    }
+}
+
+fn parse(src: &[u8]) -> Result<(TensorInfo, &[u8])> {
+    let magic = src.first_chunk::<4>().ok_or(Error::SrcTooSmall)?;
+    if u32::from_le_bytes(*magic) != TENSOR_MAGIC {
+        return Err(Error::BadMagic);
+    }
+    let header = src.get(..HEADER_SIZE).ok_or(Error::SrcTooSmall)?;
+    let mut word = [0u8; 4];
+    word.copy_from_slice(header.get(4..8).unwrap_or_default());
+    let payload = u32::from_le_bytes(word) as usize;
+    let (dtype, ndim) = match header.get(8..10) {
+        Some(&[dtype, ndim]) => (dtype, usize::from(ndim)),
+        _ => return Err(Error::InvalidData),
+    };
+    let dtype = DType::from_code(dtype).ok_or(Error::InvalidData)?;
+    if payload < 4 + 8 * ndim {
+        return Err(Error::InvalidData);
+    }
+    let dims = src
+        .get(HEADER_SIZE..HEADER_SIZE + 8 * ndim)
+        .ok_or(Error::SrcTooSmall)?;
+    let shape = dims
+        .chunks_exact(8)
+        .map(|d| {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(d);
+            usize::try_from(u64::from_le_bytes(le)).map_err(|_| Error::InvalidData)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let frame = src.get(8 + payload..).ok_or(Error::SrcTooSmall)?;
+    Ok((TensorInfo { dtype, shape }, frame))
+}
+
+/// Converts elements between little-endian and the host's byte order.
+fn to_le(bytes: &mut [u8], dtype: DType) {
+    if cfg!(target_endian = "big") {
+        for element in bytes.chunks_exact_mut(dtype.size()) {
+            element.reverse();
+        }
+    }
+}
+
+fn as_bytes<A: Element>(data: &[A]) -> &[u8] {
+    // SAFETY: `Element` types are integers and floats, without padding.
+    unsafe { core::slice::from_raw_parts(data.as_ptr().cast(), core::mem::size_of_val(data)) }
+}
+
+fn as_bytes_mut<A: Element>(data: &mut [A]) -> &mut [u8] {
+    // SAFETY: as above, and any bit pattern is a valid `Element`.
+    unsafe {
+        core::slice::from_raw_parts_mut(data.as_mut_ptr().cast(), core::mem::size_of_val(data))
+    }
+}
+
+#[cfg(feature = "ndarray")]
+pub use self::ndarray::{compress_array, decompress_array, decompress_into};
+
+#[cfg(feature = "ndarray")]
+mod ndarray {
+    use alloc::vec::Vec;
+
+    use ::ndarray::{ArrayBase, ArrayD, Data, DataMut, Dimension, IxDyn};
+
+    use super::{Element, compress_slice, decompress_into_slice, tensor_info};
+    use crate::{CompressOptions, Error, Result};
+
+    /// Compresses an array of any layout, with its shape.
+    ///
+    /// Arrays in standard (row-major, contiguous) layout are compressed in
+    /// place; others are copied into that order first.
+    pub fn compress_array<A: Element, S: Data<Elem = A>, D: Dimension>(
+        array: &ArrayBase<S, D>,
+        opts: &CompressOptions,
+    ) -> Result<Vec<u8>> {
+        match array.as_slice() {
+            Some(data) => compress_slice(data, array.shape(), opts),
+            None => compress_slice(
+                &array.iter().copied().collect::<Vec<_>>(),
+                array.shape(),
+                opts,
+            ),
+        }
+    }
+
+    /// Decompresses a tensor into a new array of its shape.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidData`] if the stored dtype is not `A`, and any
+    /// decompression error.
+    pub fn decompress_array<A: Element + Default>(src: &[u8]) -> Result<ArrayD<A>> {
+        let info = tensor_info(src)?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(info.len().ok_or(Error::InvalidData)?)
+            .map_err(|_| Error::Memory)?;
+        data.resize(info.len().unwrap_or(0), A::default());
+        decompress_into_slice(src, &mut data)?;
+        ArrayD::from_shape_vec(IxDyn(&info.shape), data).map_err(|_| Error::InvalidData)
+    }
+
+    /// Decompresses a tensor into `array`, which must have its shape and be
+    /// in standard layout, without allocating.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidData`] if the shape or dtype differs, and
+    /// [`Error::Unsupported`] if `array` is not in standard layout.
+    pub fn decompress_into<A: Element, S: DataMut<Elem = A>, D: Dimension>(
+        src: &[u8],
+        array: &mut ArrayBase<S, D>,
+    ) -> Result<()> {
+        if tensor_info(src)?.shape != array.shape() {
+            return Err(Error::InvalidData);
+        }
+        let data = array
+            .as_slice_mut()
+            .ok_or(Error::Unsupported("array is not in standard layout"))?;
+        decompress_into_slice(src, data).map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_tensor_roundtrip() {
+        let data: Vec<f32> = (0..3 * 128 * 64).map(|i| (i % 97) as f32 * 0.5).collect();
+        let bytes = compress_slice(&data, &[3, 128, 64], &CompressOptions::default()).unwrap();
+        let info = tensor_info(&bytes).unwrap();
+        assert_eq!(info.dtype, DType::F32);
+        assert_eq!(info.shape, [3, 128, 64]);
+        assert_eq!(info.byte_len(), Some(data.len() * 4));
+
+        // The metadata frame is skippable: behind it is a plain zxc frame.
+        let frame = data_frame(&bytes).unwrap();
+        assert!(crate::detect_zxc(frame));
+        assert_eq!(crate::decompress(frame).unwrap(), as_bytes(&data));
+
+        let mut out = vec![0f32; data.len()];
+        assert_eq!(
+            decompress_into_slice(&bytes, &mut out).unwrap(),
+            [3, 128, 64]
+        );
+        assert_eq!(out, data);
+
+        // Wrong dtype or size, and damaged metadata, are errors.
+        let mut ints = vec![0u32; data.len()];
+        assert!(decompress_into_slice(&bytes, &mut ints).is_err());
+        let mut short = vec![0f32; data.len() - 1];
+        assert!(decompress_into_slice(&bytes, &mut short).is_err());
+        assert!(matches!(tensor_info(frame), Err(Error::BadMagic)));
+        let mut bad = bytes.clone();
+        bad[8] = 0xEE;
+        assert!(tensor_info(&bad).is_err());
+        assert!(tensor_info(&bytes[..14]).is_err());
+        assert!(compress_slice(&data, &[3, 128], &CompressOptions::default()).is_err());
+
+        // Raw buffers for types without a Rust primitive, and scalars.
+        let half = TensorInfo {
+            dtype: DType::BF16,
+            shape: vec![4, 2],
+        };
+        let bytes = compress_tensor(&[7; 16], &half, &CompressOptions::default()).unwrap();
+        let mut out = [0u8; 16];
+        assert_eq!(decompress_tensor_into(&bytes, &mut out).unwrap(), half);
+        assert_eq!(out, [7; 16]);
+        let bytes = compress_slice(&[42i64], &[], &CompressOptions::default()).unwrap();
+        let mut out = [0i64];
+        assert!(decompress_into_slice(&bytes, &mut out).unwrap().is_empty());
+        assert_eq!(out, [42]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_ndarray_roundtrip() {
+        use ::ndarray::{Array2, Array3, ShapeBuilder};
+
+        let a = Array3::from_shape_fn((4, 32, 16), |(i, j, k)| (i * 1000 + j * 10 + k % 3) as i32);
+        let bytes = compress_array(&a, &CompressOptions::default()).unwrap();
+        assert_eq!(
+            decompress_array::<i32>(&bytes).unwrap(),
+            a.clone().into_dyn()
+        );
+
+        let mut out = Array3::<i32>::zeros((4, 32, 16));
+        decompress_into(&bytes, &mut out).unwrap();
+        assert_eq!(out, a);
+        let mut wrong = Array3::<i32>::zeros((4, 16, 32));
+        assert!(decompress_into(&bytes, &mut wrong).is_err());
+        let mut fortran = Array3::<i32>::zeros((4, 32, 16).f());
+        assert!(matches!(
+            decompress_into(&bytes, &mut fortran),
+            Err(Error::Unsupported(_))
+        ));
+
+        // A transposed view is stored in row-major order of its own shape.
+        let m = Array2::from_shape_fn((8, 5), |(i, j)| (i * 5 + j) as f64);
+        let bytes = compress_array(&m.t(), &CompressOptions::default()).unwrap();
+        assert_eq!(decompress_array::<f64>(&bytes).unwrap(), m.t().into_dyn());
+    }
+}
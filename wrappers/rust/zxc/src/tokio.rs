@@ -0,0 +1,253 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! A compressed message transport for tokio (the `tokio` feature).
+//!
+//! [`transport`] turns a `TcpStream`, a Unix socket or any other
+//! `AsyncRead + AsyncWrite` into a `Sink` of messages and a `Stream` of the
+//! messages sent by the peer, each carried as one self-delimiting
+//! [`message`](crate::message) record. [`MessageCodec`] is the
+//! `tokio_util::codec` codec underneath, for use with `FramedRead`,
+//! `FramedWrite` or an existing `Framed` setup.
+//!
+//! ```rust
+//! use futures::{SinkExt, StreamExt};
+//!
+//! # tokio::runtime::Builder::new_current_thread().build()?.block_on(async {
+//! let (client, server) = tokio::io::duplex(64 * 1024);
+//! let (mut client, mut server) = (zxc::tokio::transport(client), zxc::tokio::transport(server));
+//!
+//! client.send(br#"{"method":"get","key":"user:42"}"#).await?;
+//! let request = server.next().await.unwrap()?;
+//! assert_eq!(&request[..], br#"{"method":"get","key":"user:42"}"#);
+//! # Ok::<(), std::io::Error>(())
+//! # })?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Both ends must use the same mode. [`MessageCodec::linked`] compresses
+//! each message against the ones sent before it, which pays off for small,
+//! similar RPC messages; the stream is reliable and ordered, so the history
+//! never goes out of sync. Messages are limited to 2 MiB each.
+//!
+//! A transport is a `Sink` of anything `AsRef<[u8]>`, so `flush` and
+//! `close` need the item type spelled out:
+//! `SinkExt::<&[u8]>::close(&mut transport)`.
+//!
+//! Errors are `io::Error`s: `InvalidData` wrapping a [`crate::Error`] for a
+//! record that does not decode, or the transport's own.
+
+use std::io;
+use std::vec::Vec;
+
+use ::tokio::io::{AsyncRead, AsyncWrite};
+use ::tokio_util::codec::{Decoder, Encoder, Framed};
+use bytes::{Bytes, BytesMut};
+
+use crate::message::{self, Frame};
+use crate::{Error, Level};
+
+/// A compressed message transport over `io`. See the [module docs](self).
+pub type Transport<T> = Framed<T, MessageCodec>;
+
+/// Wraps `io` in a transport sending each message as its own record.
+pub fn transport<T: AsyncRead + AsyncWrite>(io: T) -> Transport<T> {
+    Framed::new(io, MessageCodec::new())
+}
+
+/// Wraps `io` in a transport sending linked records.
+pub fn linked_transport<T: AsyncRead + AsyncWrite>(io: T) -> Transport<T> {
+    Framed::new(io, MessageCodec::linked())
+}
+
+/// The `tokio_util` codec of a [`Transport`]: messages in, records out.
+#[derive(Debug)]
+pub struct MessageCodec {
+    tx: Tx,
+    rx: message::Decoder,
+    buf: Vec<u8>,
+}
+
+#[derive(Debug)]
+enum Tx {
+    Plain(Frame),
+    Linked(message::Encoder),
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageCodec {
+    /// A codec compressing each message on its own, at the default level,
+    /// with checksums.
+    pub fn new() -> Self {
+        Self::with_tx(Tx::Plain(Frame::new()))
+    }
+
+    /// A codec compressing each message against the ones before it.
+    pub fn linked() -> Self {
+        Self::with_tx(Tx::Linked(message::Encoder::new()))
+    }
+
+    fn with_tx(tx: Tx) -> Self {
+        Self {
+            tx,
+            rx: message::Decoder::new(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Compresses outgoing messages at `level`.
+    pub fn with_level(self, level: Level) -> Self {
+        let tx = match self.tx {
+            Tx::Plain(_) => Tx::Plain(Frame::with_level(level)),
+            Tx::Linked(_) => Tx::Linked(message::Encoder::with_level(level)),
+        };
+        Self { tx, ..self }
+    }
+
+    /// Leaves the checksums out of outgoing records.
+    pub fn without_checksum(self) -> Self {
+        let tx = match self.tx {
+            Tx::Plain(f) => Tx::Plain(f.without_checksum()),
+            Tx::Linked(e) => Tx::Linked(e.without_checksum()),
+        };
+        Self { tx, ..self }
+    }
+}
+
+impl<B: AsRef<[u8]>> Encoder<B> for MessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, msg: B, dst: &mut BytesMut) -> io::Result<()> {
+        self.buf.clear();
+        let written = match &mut self.tx {
+            Tx::Plain(frame) => frame.encode_into(msg.as_ref(), &mut self.buf),
+            Tx::Linked(encoder) => encoder.encode_into(msg.as_ref(), &mut self.buf),
+        };
+        written.map_err(io::Error::other)?;
+        dst.extend_from_slice(&self.buf);
+        Ok(())
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Bytes>> {
+        let invalid = |e: Error| io::Error::new(io::ErrorKind::InvalidData, e);
+        // The header check refuses records no encoder could have written
+        // before they are buffered.
+        let n = match message::record_len(src) {
+            Ok(n) => n,
+            Err(Error::SrcTooSmall) => return Ok(None),
+            Err(e) => return Err(invalid(e)),
+        };
+        if src.len() < n {
+            src.reserve(n - src.len());
+            return Ok(None);
+        }
+        let record = src.split_to(n);
+        let msg = self.rx.decode(&record).map_err(invalid)?;
+        Ok(Some(Bytes::from(msg)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::string::String;
+
+    use futures::{SinkExt, StreamExt};
+
+    use super::*;
+
+    fn block_on<F: Future>(f: F) -> F::Output {
+        ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    #[test]
+    fn test_transport() {
+        block_on(async {
+            let requests: Vec<String> = (0..200)
+                .map(|i| {
+                    format!(
+                        r#"{{"jsonrpc":"2.0","id":{i},"method":"get","params":["user:{}"]}}"#,
+                        i % 9
+                    )
+                })
+                .collect();
+            for linked in [false, true] {
+                let (a, b) = ::tokio::io::duplex(1024);
+                let (mut client, mut server) = match linked {
+                    false => (transport(a), transport(b)),
+                    true => (linked_transport(a), linked_transport(b)),
+                };
+                let sent = requests.clone();
+                let writer = async move {
+                    for r in &sent {
+                        client.send(r.as_bytes()).await.unwrap();
+                    }
+                    client.send(Vec::new()).await.unwrap();
+                    client.send(crate::fixtures::input()).await.unwrap();
+                    SinkExt::<&[u8]>::close(&mut client).await.unwrap();
+                };
+                let reader = async {
+                    let mut got = Vec::new();
+                    while let Some(msg) = server.next().await {
+                        got.push(msg.unwrap());
+                    }
+                    got
+                };
+                let ((), got) = futures::join!(writer, reader);
+                assert_eq!(got.len(), requests.len() + 2);
+                assert!(got.iter().zip(&requests).all(|(g, r)| g == r.as_bytes()));
+                assert!(got[requests.len()].is_empty());
+                assert_eq!(got[requests.len() + 1], crate::fixtures::input());
+            }
+        });
+    }
+
+    #[test]
+    fn test_codec_errors() {
+        let mut codec = MessageCodec::new()
+            .with_level(Level::Fastest)
+            .without_checksum();
+        let mut buf = BytesMut::new();
+        codec.encode(b"hello hello hello", &mut buf).unwrap();
+        let whole = buf.clone();
+
+        // Partial records wait for more input.
+        let mut partial = BytesMut::from(&whole[..whole.len() - 1]);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        let mut partial = BytesMut::from(&whole[..4]);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        assert_eq!(
+            &codec.decode(&mut buf).unwrap().unwrap()[..],
+            b"hello hello hello"
+        );
+        assert!(buf.is_empty());
+
+        // Garbage and oversized headers fail before buffering.
+        let mut garbage = BytesMut::from(&[0xFFu8; 32][..]);
+        let err = codec.decode(&mut garbage).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut huge = BytesMut::from(&whole[..]);
+        huge[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(codec.decode(&mut huge).is_err());
+        let mut long_block = BytesMut::from(&whole[..]);
+        long_block[5..9].copy_from_slice(&(64u32 << 20).to_le_bytes());
+        assert!(codec.decode(&mut long_block).is_err());
+    }
+}
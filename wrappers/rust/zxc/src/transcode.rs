@@ -0,0 +1,171 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Streaming conversion of gzip and zstd archives to ZXC.
+//!
+//! [`transcode`] decodes the source format and feeds the output straight
+//! into a ZXC encoder, so an archive can be migrated from one stream to
+//! another without a temporary file or holding it in memory.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::CompressOptions;
+
+/// A format [`transcode`] reads. Each needs its Cargo feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// gzip (RFC 1952), concatenated members included, as `gzip -d` reads
+    /// them. Needs the `flate2` feature.
+    #[cfg(feature = "flate2")]
+    Gzip,
+    /// zstd, consecutive frames included. Needs the `zstd` feature.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Format {
+    /// Recognizes a source format from the first bytes of a stream (four
+    /// are enough). Returns `None` for anything else, ZXC frames included.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "flate2")]
+    /// assert_eq!(
+    ///     zxc::transcode::Format::detect(&[0x1f, 0x8b, 0x08, 0x00]),
+    ///     Some(zxc::transcode::Format::Gzip)
+    /// );
+    /// assert_eq!(zxc::transcode::Format::detect(b"text"), None);
+    /// ```
+    pub fn detect(prefix: &[u8]) -> Option<Self> {
+        match prefix {
+            #[cfg(feature = "flate2")]
+            [0x1f, 0x8b, ..] => Some(Format::Gzip),
+            #[cfg(feature = "zstd")]
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Format::Zstd),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            #[cfg(feature = "flate2")]
+            Format::Gzip => "gzip",
+            #[cfg(feature = "zstd")]
+            Format::Zstd => "zstd",
+        })
+    }
+}
+
+/// Decodes a `from` stream read from `reader` and writes it to `writer` as
+/// one ZXC frame.
+///
+/// The data goes through a fixed-size buffer on its way from one codec to
+/// the other, so memory use does not depend on the archive size. `opts` are
+/// those of [`compress_stream`](crate::compress_stream), which this is a
+/// decoder in front of.
+///
+/// Returns the number of compressed bytes written.
+///
+/// # Errors
+///
+/// The source decoder's errors ([`io::ErrorKind::InvalidInput`] or
+/// [`io::ErrorKind::InvalidData`] for a damaged archive,
+/// [`io::ErrorKind::UnexpectedEof`] for a truncated one), and those of
+/// `reader`, `writer` and the ZXC encoder.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "zstd")] {
+/// use zxc::transcode::{Format, transcode};
+///
+/// let archive = zstd::encode_all(&b"move me to zxc"[..], 3)?;
+/// let mut frame = Vec::new();
+/// transcode(&archive[..], &mut frame, Format::Zstd, None)?;
+/// assert_eq!(zxc::decompress(&frame)?, b"move me to zxc");
+/// # }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn transcode<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    from: Format,
+    opts: Option<&CompressOptions>,
+) -> io::Result<u64> {
+    match from {
+        #[cfg(feature = "flate2")]
+        Format::Gzip => crate::compress_stream(
+            flate2::read::MultiGzDecoder::new(io::BufReader::new(reader)),
+            writer,
+            opts,
+        ),
+        #[cfg(feature = "zstd")]
+        Format::Zstd => {
+            crate::compress_stream(zstd::stream::read::Decoder::new(reader)?, writer, opts)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        (0..200_000u32)
+            .flat_map(|i| format!("{} line {}\n", i % 97, i / 13).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "flate2")]
+    fn test_transcode_gzip() {
+        use flate2::{Compression, write::GzEncoder};
+
+        let data = sample();
+        let (head, tail) = data.split_at(data.len() / 3);
+        // Two members, as `cat a.gz b.gz` makes.
+        let mut archive = Vec::new();
+        for part in [head, tail] {
+            let mut gz = GzEncoder::new(Vec::new(), Compression::fast());
+            gz.write_all(part).unwrap();
+            archive.extend(gz.finish().unwrap());
+        }
+        assert_eq!(Format::detect(&archive), Some(Format::Gzip));
+
+        let mut frame = Vec::new();
+        let written = transcode(&archive[..], &mut frame, Format::Gzip, None).unwrap();
+        assert_eq!(written as usize, frame.len());
+        assert_eq!(crate::decompress(&frame).unwrap(), data);
+
+        // A truncated archive is an error, not a short frame.
+        let cut = archive.get(..archive.len() - 10).unwrap();
+        let err = transcode(cut, &mut Vec::new(), Format::Gzip, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_transcode_zstd() {
+        let data = sample();
+        let mut archive = zstd::encode_all(&data[..], 1).unwrap();
+        archive.extend(zstd::encode_all(&b"second frame"[..], 1).unwrap());
+        assert_eq!(Format::detect(&archive), Some(Format::Zstd));
+
+        let opts = CompressOptions::with_level(crate::Level::Fast);
+        let mut frame = Vec::new();
+        transcode(&archive[..], &mut frame, Format::Zstd, Some(&opts)).unwrap();
+        let mut expected = data;
+        expected.extend_from_slice(b"second frame");
+        assert_eq!(crate::decompress(&frame).unwrap(), expected);
+
+        let cut = archive.get(..archive.len() / 2).unwrap();
+        assert!(transcode(cut, &mut Vec::new(), Format::Zstd, None).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Integrity checks that decode a frame without keeping the output, like
+//! `gzip -t`.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use crate::file::stream_error;
+use crate::format::FILE_FOOTER_SIZE;
+use crate::salvage::{SalvageOptions, SalvageReport, salvage};
+use crate::{Error, Result, StreamError, StreamResult};
+
+/// Outcome of a successful [`verify`] or [`verify_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Size of the compressed frame.
+    pub compressed_size: u64,
+    /// Size of the data it decodes to.
+    pub decompressed_size: u64,
+    /// Number of data blocks.
+    pub blocks: u32,
+    /// Whether the frame carries checksums, which were all verified. When
+    /// `false`, only the structure and the declared size were checked.
+    pub checksummed: bool,
+}
+
+/// Decodes `compressed` in full and verifies every checksum, discarding the
+/// output.
+///
+/// Blocks are decoded one at a time into a single block-sized buffer, so
+/// memory use does not depend on the size of the frame. A failure is
+/// reported as [`Error::Corrupted`], with the first bad block. Dictionary
+/// frames are not supported.
+///
+/// # Example
+///
+/// ```rust
+/// use zxc::{compress, verify, Level};
+///
+/// let frame = compress(b"scrub me", Level::Default, Some(true)).unwrap();
+/// let report = verify(&frame).unwrap();
+/// assert_eq!(report.decompressed_size, 8);
+/// assert!(report.checksummed);
+/// ```
+pub fn verify(compressed: &[u8]) -> Result<VerifyReport> {
+    verify_reader(compressed).map_err(|e| match e {
+        StreamError::BufferError(e) => e,
+        _ => Error::Io,
+    })
+}
+
+/// Decodes the file at `path` in full and verifies every checksum, without
+/// writing anything.
+///
+/// Streaming counterpart of [`verify`] for periodic integrity scrubs;
+/// corruption is returned as [`StreamError::BufferError`] holding an
+/// [`Error::Corrupted`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let report = zxc::verify_file("backup.zxc")?;
+/// println!("{} bytes in {} blocks are intact", report.decompressed_size, report.blocks);
+/// # Ok::<(), zxc::StreamError>(())
+/// ```
+pub fn verify_file<P: AsRef<Path>>(path: P) -> StreamResult<VerifyReport> {
+    verify_reader(BufReader::new(File::open(path)?))
+}
+
+fn verify_reader<R: Read>(reader: R) -> StreamResult<VerifyReport> {
+    let report =
+        salvage(reader, io::sink(), &SalvageOptions::default(), true).map_err(stream_error)?;
+    Ok(check(report)?)
+}
+
+/// Turns the first problem the salvage walk met into an error.
+fn check(report: SalvageReport) -> Result<VerifyReport> {
+    let corrupted = |block, offset, source| Error::Corrupted {
+        block,
+        offset,
+        source: Box::new(source),
+    };
+    if let Some(d) = report.damaged.into_iter().next() {
+        return Err(corrupted(Some(d.block), d.offset, d.error));
+    }
+    let Some(declared) = report.declared_size else {
+        return Err(corrupted(
+            Some(report.blocks),
+            report.read,
+            Error::SrcTooSmall,
+        ));
+    };
+    if !report.trailer_ok {
+        let source = if declared != report.written {
+            Error::CorruptData
+        } else {
+            Error::BadChecksum
+        };
+        return Err(corrupted(
+            None,
+            report.read - FILE_FOOTER_SIZE as u64,
+            source,
+        ));
+    }
+    Ok(VerifyReport {
+        compressed_size: report.read,
+        decompressed_size: report.written,
+        blocks: report.blocks,
+        checksummed: report.checksummed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Level, compress};
+
+    #[test]
+    fn test_verify() {
+        let data: Vec<u8> = (0..3 * 512 * 1024u32)
+            .map(|i| (i % 251) as u8 ^ (i >> 13) as u8)
+            .collect();
+        let frame = compress(&data, Level::Fast, Some(true)).unwrap();
+        let report = verify(&frame).unwrap();
+        assert_eq!(
+            report,
+            VerifyReport {
+                compressed_size: frame.len() as u64,
+                decompressed_size: data.len() as u64,
+                blocks: 3,
+                checksummed: true,
+            }
+        );
+
+        let block1 = 16 + 8 + u32::from_le_bytes(frame[19..23].try_into().unwrap()) as usize + 4;
+        let mut bad = frame.clone();
+        bad[block1 + 20] ^= 0x01;
+        let err = verify(&bad).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: Some(1), offset, .. } if offset == block1 as u64),
+            "{err:?}"
+        );
+
+        let mut bad = frame.clone();
+        let footer = bad.len() - 12;
+        bad[footer] ^= 0x01;
+        let err = verify(&bad).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: None, offset, .. } if offset == footer as u64),
+            "{err:?}"
+        );
+        assert!(matches!(err.cause(), Error::CorruptData));
+
+        let err = verify(&frame[..block1 + 4]).unwrap_err();
+        assert!(
+            matches!(err, Error::Corrupted { block: Some(1), .. }),
+            "{err:?}"
+        );
+        assert!(matches!(verify(&frame[..10]), Err(Error::SrcTooSmall)));
+    }
+
+    #[test]
+    fn test_verify_file() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("zxc_verify_{}.zxc", std::process::id()));
+        let frame = compress(b"periodic scrub", Level::Default, None).unwrap();
+        std::fs::write(&path, &frame).unwrap();
+        let report = verify_file(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let report = report.unwrap();
+        assert_eq!(report.decompressed_size, 14);
+        assert!(!report.checksummed);
+        assert!(matches!(
+            verify_file(&path),
+            Err(StreamError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
+}
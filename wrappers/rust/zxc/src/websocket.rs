@@ -0,0 +1,289 @@
+/*
+ * ZXC - High-performance lossless compression
+ *
+ * Copyright (c) 2025-2026 Bertrand Lebonnois and contributors.
+ * SPDX-License-Identifier: BSD-3-Clause
+ */
+
+//! Per-message compression for WebSocket connections.
+//!
+//! A [`Codec`] holds one connection's state in each direction: the outgoing
+//! side is a linked [`message::Encoder`](crate::message::Encoder) and the
+//! incoming side its [`Decoder`](crate::message::Decoder), so each message
+//! is compressed against the last [`HISTORY_SIZE`] bytes sent before it.
+//! Real-time feeds repeating the same JSON keys compress even when each
+//! message is too small to compress on its own. Both peers keep one codec per
+//! connection; the state is bounded at about 2 × `HISTORY_SIZE` plus the
+//! compression contexts, whatever the length of the connection.
+//!
+//! ```rust
+//! use zxc::websocket::{Codec, Kind};
+//!
+//! let (mut server, mut client) = (Codec::new(), Codec::new());
+//! for i in 0..50 {
+//!     let json = format!(r#"{{"type":"tick","symbol":"ZXC","price":{}.25,"volume":{}}}"#, 100 + i, i * 7);
+//!     let payload = server.compress(Kind::Text, json.as_bytes())?;
+//!     let (kind, msg) = client.decompress(&payload)?;
+//!     assert_eq!((kind, msg), (Kind::Text, json.into_bytes()));
+//! }
+//! # Ok::<(), zxc::Error>(())
+//! ```
+//!
+//! A compressed payload is sent as a binary message: one byte giving the
+//! original [`Kind`] (0 binary, 1 text), then a linked record in the
+//! [`message`](crate::message#wire-layout) layout. WebSocket delivers
+//! messages in order, so the history never desynchronises on a live
+//! connection. [`Codec::reset`] drops the outgoing history, for instance
+//! when the stream switches to unrelated messages; the peer follows
+//! without being told. A new connection starts with new codecs.
+//!
+//! With the `tungstenite` feature, [`Codec::compress_message`] and
+//! [`Codec::decompress_message`] work on `tungstenite::Message` directly,
+//! and pass control messages through.
+
+use alloc::vec::Vec;
+
+use crate::message::{Decoder, Encoder};
+use crate::{Error, Level, Result};
+
+pub use crate::message::HISTORY_SIZE;
+
+/// The kind of a WebSocket data message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// A binary message.
+    Binary,
+    /// A text message; its payload is UTF-8.
+    Text,
+}
+
+impl Kind {
+    fn tag(self) -> u8 {
+        match self {
+            Kind::Binary => 0,
+            Kind::Text => 1,
+        }
+    }
+}
+
+/// The compression state of one WebSocket connection. See the
+/// [module docs](self).
+#[derive(Debug, Default)]
+pub struct Codec {
+    tx: Encoder,
+    rx: Decoder,
+}
+
+impl Codec {
+    /// A codec compressing at the default level, with checksums.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A codec compressing at `level`, with checksums.
+    pub fn with_level(level: Level) -> Self {
+        Self {
+            tx: Encoder::with_level(level),
+            rx: Decoder::new(),
+        }
+    }
+
+    /// Leaves the checksums out of outgoing messages. WebSocket runs over a
+    /// reliable transport, and TLS already authenticates the bytes.
+    pub fn without_checksum(mut self) -> Self {
+        self.tx = self.tx.without_checksum();
+        self
+    }
+
+    /// Drops the outgoing history every `interval` messages.
+    pub fn with_reset_interval(mut self, interval: u32) -> Self {
+        self.tx = self.tx.with_reset_interval(interval);
+        self
+    }
+
+    /// Drops the outgoing history: the next message is compressed on its
+    /// own, and the peer's decoder starts over from it.
+    pub fn reset(&mut self) {
+        self.tx.reset();
+    }
+
+    /// Compresses the payload of an outgoing message of kind `kind`.
+    pub fn compress(&mut self, kind: Kind, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        out.push(kind.tag());
+        self.tx.encode_into(payload, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decompresses the payload of an incoming binary message, and returns
+    /// it with the kind of the original message.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadHeader`] for an unknown kind, [`Error::TooLarge`] for a
+    /// message claiming more than 2 MiB, refused before anything is
+    /// allocated, [`Error::CorruptData`] for a text message that is not
+    /// UTF-8, and the errors of
+    /// [`Decoder::decode`]. A failed message leaves the incoming history
+    /// unusable until the peer resets.
+    pub fn decompress(&mut self, payload: &[u8]) -> Result<(Kind, Vec<u8>)> {
+        let (kind, record) = match payload.split_first() {
+            Some((0, record)) => (Kind::Binary, record),
+            Some((1, record)) => (Kind::Text, record),
+            _ => return Err(Error::BadHeader),
+        };
+        let msg = self.rx.decode(record)?;
+        if kind == Kind::Text && core::str::from_utf8(&msg).is_err() {
+            return Err(Error::CorruptData);
+        }
+        Ok((kind, msg))
+    }
+}
+
+#[cfg(feature = "tungstenite")]
+mod tungstenite_impl {
+    use tungstenite::{Message, Utf8Bytes};
+
+    use super::{Codec, Kind};
+    use crate::{Error, Result};
+
+    impl Codec {
+        /// Compresses a text or binary message into a binary one; control
+        /// messages are returned as they are.
+        pub fn compress_message(&mut self, msg: Message) -> Result<Message> {
+            Ok(match msg {
+                Message::Text(text) => Message::binary(self.compress(Kind::Text, text.as_bytes())?),
+                Message::Binary(data) => Message::binary(self.compress(Kind::Binary, &data)?),
+                other => other,
+            })
+        }
+
+        /// Restores a binary message made by [`Codec::compress_message`];
+        /// other messages are returned as they are.
+        pub fn decompress_message(&mut self, msg: Message) -> Result<Message> {
+            let Message::Binary(data) = msg else {
+                return Ok(msg);
+            };
+            Ok(match self.decompress(&data)? {
+                (Kind::Binary, data) => Message::binary(data),
+                (Kind::Text, text) => {
+                    Message::Text(Utf8Bytes::try_from(text).map_err(|_| Error::CorruptData)?)
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::*;
+
+    fn tick(i: u32) -> String {
+        format!(
+            r#"{{"type":"tick","symbol":"ZXC-USD","price":{}.{:02},"volume":{},"exchange":"main","flags":["regular","lit"]}}"#,
+            1000 + i % 13,
+            i % 100,
+            i * 37
+        )
+    }
+
+    #[test]
+    fn test_codec() {
+        let mut server = Codec::new().with_reset_interval(64);
+        let mut client = Codec::new();
+        let mut sizes = Vec::new();
+        for i in 0..200 {
+            let msg = tick(i);
+            let (kind, payload) = match i % 5 {
+                0 => (Kind::Binary, msg.repeat(2).into_bytes()),
+                _ => (Kind::Text, msg.into_bytes()),
+            };
+            if i == 150 {
+                server.reset();
+            }
+            let wire = server.compress(kind, &payload).unwrap();
+            assert_eq!(client.decompress(&wire).unwrap(), (kind, payload.clone()));
+            sizes.push((payload.len(), wire.len()));
+        }
+        #[cfg(not(feature = "mock-backend"))]
+        {
+            let raw: usize = sizes.iter().map(|s| s.0).sum();
+            let sent: usize = sizes.iter().map(|s| s.1).sum();
+            assert!(sent * 4 < raw * 3, "{sent} of {raw} bytes");
+        }
+
+        // Both directions are independent.
+        let wire = client.compress(Kind::Binary, b"ack").unwrap();
+        assert_eq!(
+            server.decompress(&wire).unwrap(),
+            (Kind::Binary, b"ack".to_vec())
+        );
+
+        // Unknown kinds and invalid text.
+        let mut wire = server.compress(Kind::Binary, b"x").unwrap();
+        wire[0] = 7;
+        assert!(matches!(client.decompress(&wire), Err(Error::BadHeader)));
+        assert!(matches!(client.decompress(&[]), Err(Error::BadHeader)));
+        let mut tx = Codec::new();
+        let wire = tx.compress(Kind::Text, &[0xff, 0xfe]).unwrap();
+        assert!(matches!(
+            Codec::new().decompress(&wire),
+            Err(Error::CorruptData)
+        ));
+    }
+
+    #[test]
+    fn test_oversized_message() {
+        // A 10-byte message claiming 4 GiB fails on its header, plain or
+        // linked, before the decoder allocates for it.
+        let mut client = Codec::new();
+        for flags in [0x11, 0x13] {
+            let mut wire = vec![Kind::Binary.tag(), flags];
+            wire.extend_from_slice(&u32::MAX.to_le_bytes());
+            wire.extend_from_slice(&[0; 4]);
+            assert_eq!(wire.len(), 10);
+            assert!(matches!(
+                client.decompress(&wire),
+                Err(Error::TooLarge(n)) if n == u32::MAX as u64
+            ));
+        }
+
+        // The connection still decodes what follows.
+        let mut server = Codec::new();
+        let wire = server.compress(Kind::Text, b"still here").unwrap();
+        assert_eq!(
+            client.decompress(&wire).unwrap(),
+            (Kind::Text, b"still here".to_vec())
+        );
+    }
+
+    #[cfg(feature = "tungstenite")]
+    #[test]
+    fn test_tungstenite_messages() {
+        use tungstenite::Message;
+
+        let mut server = Codec::new();
+        let mut client = Codec::new();
+        for msg in [
+            Message::text(tick(1)),
+            Message::binary(tick(2).into_bytes()),
+            Message::Ping(b"hb".to_vec().into()),
+            Message::text(tick(3)),
+            Message::Close(None),
+        ] {
+            let wire = server.compress_message(msg.clone()).unwrap();
+            match &msg {
+                Message::Text(_) | Message::Binary(_) => assert!(wire.is_binary()),
+                _ => assert_eq!(wire, msg),
+            }
+            assert_eq!(client.decompress_message(wire).unwrap(), msg);
+        }
+        // Uncompressed text from the peer passes through.
+        let text = Message::text("plain");
+        assert_eq!(client.decompress_message(text.clone()).unwrap(), text);
+    }
+}